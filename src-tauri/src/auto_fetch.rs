@@ -1,10 +1,11 @@
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
 use std::time::Duration;
 
 use serde::Serialize;
 use tauri::{AppHandle, Emitter, Manager};
 
+use crate::git::dispatcher::GitDispatcher;
+use crate::git::transfer::{CancelToken, TransferControl};
+use crate::git::types::{FetchOptions, FetchRefUpdate};
 use crate::state::AppState;
 
 #[derive(Debug, Clone, Serialize)]
@@ -12,15 +13,17 @@ pub struct AutoFetchUpdate {
     pub tab_id: String,
     pub remote_name: String,
     pub new_commits_count: u32,
+    pub updates: Vec<FetchRefUpdate>,
 }
 
 pub struct AutoFetchHandle {
-    stop_flag: Arc<AtomicBool>,
+    stop_token: CancelToken,
 }
 
 impl AutoFetchHandle {
+    /// Stop the loop, aborting a fetch that is in flight.
     pub fn stop(&self) {
-        self.stop_flag.store(true, Ordering::Relaxed);
+        self.stop_token.cancel();
     }
 }
 
@@ -31,19 +34,19 @@ impl Drop for AutoFetchHandle {
 }
 
 pub fn start_auto_fetch(app_handle: AppHandle, interval_secs: u64) -> AutoFetchHandle {
-    let stop_flag = Arc::new(AtomicBool::new(false));
-    let stop_flag_clone = Arc::clone(&stop_flag);
+    let stop_token = CancelToken::new();
+    let control = TransferControl::new(stop_token.clone());
 
     std::thread::spawn(move || {
         loop {
             for _ in 0..interval_secs {
-                if stop_flag_clone.load(Ordering::Relaxed) {
+                if stop_token.is_cancelled() {
                     return;
                 }
                 std::thread::sleep(Duration::from_secs(1));
             }
 
-            if stop_flag_clone.load(Ordering::Relaxed) {
+            if stop_token.is_cancelled() {
                 return;
             }
 
@@ -53,7 +56,7 @@ pub fn start_auto_fetch(app_handle: AppHandle, interval_secs: u64) -> AutoFetchH
             };
 
             // Collect tab info while holding the lock briefly
-            let tab_entries: Vec<(String, Vec<String>)> = {
+            let tab_entries: Vec<(String, String, Vec<String>)> = {
                 let tabs = match state.tabs.try_lock() {
                     Ok(t) => t,
                     Err(_) => continue,
@@ -67,78 +70,38 @@ pub fn start_auto_fetch(app_handle: AppHandle, interval_secs: u64) -> AutoFetchH
                         if remote_names.is_empty() {
                             None
                         } else {
-                            Some((tab_id.clone(), remote_names))
+                            Some((tab_id.clone(), ctx.path.clone(), remote_names))
                         }
                     })
                     .collect()
             };
 
-            for (tab_id, remote_names) in &tab_entries {
+            for (tab_id, repo_path, remote_names) in &tab_entries {
+                // Fetch through a separate backend instance so that network I/O
+                // doesn't hold the tabs lock
+                let kind = crate::commands::repo::backend_kind_for(repo_path);
+                let backend = match GitDispatcher::open(repo_path, kind) {
+                    Ok(b) => b,
+                    Err(_) => continue,
+                };
+
                 for remote_name in remote_names {
-                    if stop_flag_clone.load(Ordering::Relaxed) {
+                    if stop_token.is_cancelled() {
                         return;
                     }
 
-                    // Snapshot branches before fetch and extract repo path (short lock)
-                    let (branches_before, repo_path) = {
-                        let tabs = match state.tabs.try_lock() {
-                            Ok(t) => t,
-                            Err(_) => continue,
-                        };
-                        match tabs.get(tab_id) {
-                            Some(ctx) => (
-                                ctx.backend.list_branches().unwrap_or_default(),
-                                ctx.backend.workdir().to_path_buf(),
-                            ),
-                            None => continue,
-                        }
-                    };
-
-                    // Execute fetch outside of lock via git CLI to avoid holding
-                    // MutexGuard during network I/O
-                    {
-                        let output = std::process::Command::new("git")
-                            .args(["fetch", remote_name])
-                            .current_dir(&repo_path)
-                            .stdout(std::process::Stdio::null())
-                            .stderr(std::process::Stdio::null())
-                            .status();
-                        match output {
-                            Ok(status) if status.success() => {}
-                            _ => continue,
-                        }
-                    }
-
-                    // Snapshot branches after fetch (short lock)
-                    let branches_after = {
-                        let tabs = match state.tabs.try_lock() {
-                            Ok(t) => t,
+                    let result =
+                        match backend.fetch(remote_name, &FetchOptions::default(), &control) {
+                            Ok(result) => result,
                             Err(_) => continue,
                         };
-                        match tabs.get(tab_id) {
-                            Some(ctx) => ctx.backend.list_branches().unwrap_or_default(),
-                            None => continue,
-                        }
-                    };
-
-                    // Compare outside of any lock
-                    let mut new_commits: u32 = 0;
-                    for after_branch in &branches_after {
-                        if let Some(before_branch) =
-                            branches_before.iter().find(|b| b.name == after_branch.name)
-                        {
-                            if after_branch.behind_count > before_branch.behind_count {
-                                new_commits +=
-                                    after_branch.behind_count - before_branch.behind_count;
-                            }
-                        }
-                    }
 
-                    if new_commits > 0 {
+                    if result.new_commits > 0 {
                         let update = AutoFetchUpdate {
                             tab_id: tab_id.clone(),
                             remote_name: remote_name.clone(),
-                            new_commits_count: new_commits,
+                            new_commits_count: result.new_commits,
+                            updates: result.updates,
                         };
                         let _ = app_handle.emit("auto-fetch:updated", &update);
                     }
@@ -147,5 +110,5 @@ pub fn start_auto_fetch(app_handle: AppHandle, interval_secs: u64) -> AutoFetchH
         }
     });
 
-    AutoFetchHandle { stop_flag }
+    AutoFetchHandle { stop_token }
 }
@@ -1,7 +1,14 @@
-use tauri::State;
+use tauri::{AppHandle, State};
 
-use crate::commands::with_repo;
-use crate::git::types::{BranchInfo, CommitInfo, MergeOption, MergeResult};
+use crate::commands::identity::enforce_identity;
+use crate::commands::remote::{begin_transfer, finish_transfer, RemoteOperation};
+use crate::commands::{with_repo, with_repo_unlocked};
+use crate::git::stack as git_stack;
+use crate::git::transfer::TransferControl;
+use crate::git::types::{
+    BranchInfo, BranchStack, CommitInfo, DeleteBranchOptions, DeleteBranchResult, MergeOption,
+    MergeResult, PruneCandidate, StackPushResult,
+};
 use crate::state::AppState;
 
 #[tauri::command]
@@ -18,10 +25,15 @@ pub fn list_branches(
 pub fn create_branch(
     tab_id: String,
     name: String,
+    start_point: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
     with_repo(&state, &tab_id, |backend| {
-        backend.create_branch(&name).map_err(|e| e.to_string())
+        match start_point.as_deref() {
+            Some(start_point) => backend.create_branch_from(&name, start_point),
+            None => backend.create_branch(&name),
+        }
+        .map_err(|e| e.to_string())
     })
 }
 
@@ -37,16 +49,105 @@ pub fn checkout_branch(
 }
 
 #[tauri::command]
-pub fn delete_branch(
+pub async fn delete_branch(
     tab_id: String,
     name: String,
+    options: Option<DeleteBranchOptions>,
+    app_handle: AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<DeleteBranchResult, String> {
+    let options = options.unwrap_or_default();
+    let control = if options.delete_remote {
+        begin_transfer(&state, &app_handle, &tab_id, RemoteOperation::Push)?
+    } else {
+        TransferControl::default()
+    };
+    let result = delete_one(&state, &tab_id, &name, &options, &control);
+    if options.delete_remote {
+        finish_transfer(&state, &tab_id);
+    }
+    result
+}
+
+/// Delete `name` with only the remote deletion run without the tabs lock;
+/// the local branch ref is deleted under it afterwards.
+fn delete_one(
+    state: &AppState,
+    tab_id: &str,
+    name: &str,
+    options: &DeleteBranchOptions,
+    control: &TransferControl,
+) -> Result<DeleteBranchResult, String> {
+    if !options.delete_remote {
+        return with_repo(state, tab_id, |backend| {
+            backend
+                .delete_branch(name, options, control)
+                .map_err(|e| e.to_string())
+        });
+    }
+    let upstream = with_repo_unlocked(state, tab_id, |backend| {
+        backend
+            .delete_upstream_branch(name, options, control)
+            .map_err(|e| e.to_string())
+    })?;
+    if !options.force && !upstream.unmerged_commits.is_empty() {
+        return Ok(upstream);
+    }
+    // 未マージの確認は済んでいる。upstream が消えた後に数え直すと残ってしまう
+    let local_options = DeleteBranchOptions {
+        force: true,
+        delete_remote: false,
+    };
+    let local = with_repo(state, tab_id, |backend| {
+        backend
+            .delete_branch(name, &local_options, &TransferControl::default())
+            .map_err(|e| e.to_string())
+    })?;
+    Ok(DeleteBranchResult {
+        deleted: local.deleted,
+        ..upstream
+    })
+}
+
+#[tauri::command]
+pub fn list_prune_candidates(
+    tab_id: String,
+    base: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<PruneCandidate>, String> {
     with_repo(&state, &tab_id, |backend| {
-        backend.delete_branch(&name).map_err(|e| e.to_string())
+        backend
+            .list_prune_candidates(base.as_deref())
+            .map_err(|e| e.to_string())
     })
 }
 
+/// Delete the branches picked from `list_prune_candidates`. Stops at the
+/// first branch that fails to delete.
+#[tauri::command]
+pub async fn prune_branches(
+    tab_id: String,
+    names: Vec<String>,
+    options: Option<DeleteBranchOptions>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<DeleteBranchResult>, String> {
+    let options = options.unwrap_or_default();
+    let control = if options.delete_remote {
+        begin_transfer(&state, &app_handle, &tab_id, RemoteOperation::Push)?
+    } else {
+        TransferControl::default()
+    };
+    let result = names
+        .iter()
+        .map(|name| delete_one(&state, &tab_id, name, &options, &control))
+        .collect();
+    if options.delete_remote {
+        finish_transfer(&state, &tab_id);
+    }
+    result
+}
+
 #[tauri::command]
 pub fn rename_branch(
     tab_id: String,
@@ -61,6 +162,34 @@ pub fn rename_branch(
     })
 }
 
+#[tauri::command]
+pub fn set_branch_upstream(
+    tab_id: String,
+    branch_name: String,
+    upstream: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    with_repo(&state, &tab_id, |backend| {
+        backend
+            .set_upstream(&branch_name, upstream.as_deref())
+            .map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn checkout_remote_branch(
+    tab_id: String,
+    remote_branch: String,
+    local_name: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    with_repo(&state, &tab_id, |backend| {
+        backend
+            .checkout_remote_branch(&remote_branch, local_name.as_deref())
+            .map_err(|e| e.to_string())
+    })
+}
+
 #[tauri::command]
 pub fn merge_branch(
     tab_id: String,
@@ -69,6 +198,7 @@ pub fn merge_branch(
     state: State<'_, AppState>,
 ) -> Result<MergeResult, String> {
     with_repo(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
         backend
             .merge_branch(&branch_name, option)
             .map_err(|e| e.to_string())
@@ -88,3 +218,34 @@ pub fn get_branch_commits(
             .map_err(|e| e.to_string())
     })
 }
+
+/// The stack of branches the checked out branch belongs to, bottom first.
+#[tauri::command]
+pub fn list_stack(
+    tab_id: String,
+    base: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<BranchStack, String> {
+    with_repo(&state, &tab_id, |backend| {
+        git_stack::list_stack(backend, base.as_deref()).map_err(|e| e.to_string())
+    })
+}
+
+/// Push every branch of the stack with `--force-with-lease`, e.g. after
+/// rebasing it. Stops at the first branch that fails.
+#[tauri::command]
+pub async fn push_stack(
+    tab_id: String,
+    remote_name: String,
+    base: Option<String>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<StackPushResult, String> {
+    let control = begin_transfer(&state, &app_handle, &tab_id, RemoteOperation::Push)?;
+    let result = with_repo_unlocked(&state, &tab_id, |backend| {
+        git_stack::push_stack(backend, base.as_deref(), &remote_name, &control)
+            .map_err(|e| e.to_string())
+    });
+    finish_transfer(&state, &tab_id);
+    result
+}
@@ -1,5 +1,6 @@
 use tauri::State;
 
+use crate::commands::identity::enforce_identity;
 use crate::commands::with_repo;
 use crate::git::types::{CherryPickMode, CherryPickResult};
 use crate::state::AppState;
@@ -12,6 +13,7 @@ pub fn cherry_pick(
     state: State<'_, AppState>,
 ) -> Result<CherryPickResult, String> {
     with_repo(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
         let oid_refs: Vec<&str> = oids.iter().map(|s| s.as_str()).collect();
         backend
             .cherry_pick(&oid_refs, mode)
@@ -39,6 +41,7 @@ pub fn continue_cherry_pick(
     state: State<'_, AppState>,
 ) -> Result<CherryPickResult, String> {
     with_repo(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
         backend.continue_cherry_pick().map_err(|e| e.to_string())
     })
 }
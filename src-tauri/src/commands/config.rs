@@ -1,4 +1,5 @@
 use crate::config::{self, AppConfig};
+use crate::git::types::CoAuthor;
 
 #[tauri::command]
 pub fn get_config() -> Result<AppConfig, String> {
@@ -9,3 +10,25 @@ pub fn get_config() -> Result<AppConfig, String> {
 pub fn save_config(config: AppConfig) -> Result<(), String> {
     config::save_config(&config).map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub fn get_co_authors() -> Result<Vec<CoAuthor>, String> {
+    let cfg = config::load_config().map_err(|e| e.to_string())?;
+    Ok(cfg.co_authors)
+}
+
+#[tauri::command]
+pub fn add_co_author(co_author: CoAuthor) -> Result<Vec<CoAuthor>, String> {
+    let mut cfg = config::load_config().map_err(|e| e.to_string())?;
+    config::add_co_author(&mut cfg, co_author);
+    config::save_config(&cfg).map_err(|e| e.to_string())?;
+    Ok(cfg.co_authors)
+}
+
+#[tauri::command]
+pub fn remove_co_author(email: String) -> Result<Vec<CoAuthor>, String> {
+    let mut cfg = config::load_config().map_err(|e| e.to_string())?;
+    config::remove_co_author(&mut cfg, &email);
+    config::save_config(&cfg).map_err(|e| e.to_string())?;
+    Ok(cfg.co_authors)
+}
@@ -1,5 +1,6 @@
 use tauri::State;
 
+use crate::commands::identity::enforce_identity;
 use crate::commands::with_repo;
 use crate::git::types::{CommitResult, ConflictFile, ConflictResolution};
 use crate::state::AppState;
@@ -68,6 +69,7 @@ pub fn continue_merge(
     state: State<'_, AppState>,
 ) -> Result<CommitResult, String> {
     with_repo(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
         backend.continue_merge(&message).map_err(|e| e.to_string())
     })
 }
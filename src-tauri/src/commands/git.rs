@@ -2,9 +2,14 @@ use std::path::Path;
 
 use tauri::State;
 
+use crate::commands::identity::enforce_identity;
 use crate::commands::with_repo;
+use crate::config;
+use crate::git::commit_lint;
+use crate::git::trailers;
 use crate::git::types::{
-    CommitResult, DiffOptions, FileDiff, HunkIdentifier, LineRange, RepoStatus,
+    CommitIdentity, CommitLintViolation, CommitResult, CommitTrailer, DiffOptions, FileDiff,
+    HunkIdentifier, LineRange, RepoStatus,
 };
 use crate::state::AppState;
 
@@ -66,18 +71,64 @@ pub fn unstage_all(tab_id: String, state: State<'_, AppState>) -> Result<(), Str
     })
 }
 
+/// `trailers` are appended to the message. Co-authors among them are
+/// remembered for the next commit. The commit is refused when the author does
+/// not match the repository's identity profile and the profile blocks
+/// mismatches.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn commit(
     tab_id: String,
     message: String,
     amend: bool,
     sign: bool,
+    skip_hooks: Option<bool>,
+    trailers: Option<Vec<CommitTrailer>>,
+    identity: Option<CommitIdentity>,
     state: State<'_, AppState>,
 ) -> Result<CommitResult, String> {
-    with_repo(&state, &tab_id, |backend| {
+    let trailers = trailers.unwrap_or_default();
+    let identity = identity.unwrap_or_default();
+    let message = trailers::append(&message, &trailers).map_err(|e| e.to_string())?;
+    let result = with_repo(&state, &tab_id, |backend| {
+        let author_email = identity.author.as_ref().and_then(|a| a.email.as_deref());
+        enforce_identity(backend, author_email)?;
         backend
-            .commit(&message, amend, sign)
+            .commit_as(
+                &message,
+                amend,
+                sign,
+                skip_hooks.unwrap_or_default(),
+                &identity,
+            )
             .map_err(|e| e.to_string())
+    })?;
+
+    let co_authors = trailers::co_authors(&trailers);
+    if !co_authors.is_empty() {
+        if let Ok(mut cfg) = config::load_config() {
+            for co_author in co_authors.into_iter().rev() {
+                config::add_co_author(&mut cfg, co_author);
+            }
+            let _ = config::save_config(&cfg);
+        }
+    }
+    Ok(result)
+}
+
+/// Check a message against the repository's `.commitlint.toml` without
+/// committing. `trailers` are appended first, as `commit` would.
+#[tauri::command]
+pub fn lint_commit_message(
+    tab_id: String,
+    message: String,
+    trailers: Option<Vec<CommitTrailer>>,
+    state: State<'_, AppState>,
+) -> Result<Vec<CommitLintViolation>, String> {
+    let message =
+        trailers::append(&message, &trailers.unwrap_or_default()).map_err(|e| e.to_string())?;
+    with_repo(&state, &tab_id, |backend| {
+        commit_lint::lint_message(backend.workdir(), &message).map_err(|e| e.to_string())
     })
 }
 
@@ -2,7 +2,7 @@ use tauri::State;
 
 use crate::commands::with_repo;
 use crate::git::types::{
-    BlameResult, CommitDetail, CommitInfo, CommitLogResult, FileDiff, LogFilter,
+    BlameOptions, BlameResult, CommitDetail, CommitLogResult, FileDiff, FileHistoryEntry, LogFilter,
 };
 use crate::state::AppState;
 
@@ -51,11 +51,27 @@ pub fn get_blame(
     tab_id: String,
     path: String,
     commit_oid: Option<String>,
+    options: Option<BlameOptions>,
     state: State<'_, AppState>,
 ) -> Result<BlameResult, String> {
     with_repo(&state, &tab_id, |backend| {
         backend
-            .get_blame(&path, commit_oid.as_deref())
+            .get_blame(&path, commit_oid.as_deref(), &options.unwrap_or_default())
+            .map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn get_blame_before(
+    tab_id: String,
+    commit_oid: String,
+    path: String,
+    options: Option<BlameOptions>,
+    state: State<'_, AppState>,
+) -> Result<BlameResult, String> {
+    with_repo(&state, &tab_id, |backend| {
+        backend
+            .get_blame_before(&commit_oid, &path, &options.unwrap_or_default())
             .map_err(|e| e.to_string())
     })
 }
@@ -66,11 +82,19 @@ pub fn get_file_history(
     path: String,
     limit: usize,
     skip: usize,
+    follow: Option<bool>,
     state: State<'_, AppState>,
-) -> Result<Vec<CommitInfo>, String> {
+) -> Result<Vec<FileHistoryEntry>, String> {
     with_repo(&state, &tab_id, |backend| {
         backend
-            .get_file_history(&path, limit, skip)
+            .get_file_history(&path, limit, skip, follow.unwrap_or(false))
             .map_err(|e| e.to_string())
     })
 }
+
+#[tauri::command]
+pub fn write_commit_graph(tab_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    with_repo(&state, &tab_id, |backend| {
+        backend.write_commit_graph().map_err(|e| e.to_string())
+    })
+}
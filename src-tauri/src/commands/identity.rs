@@ -0,0 +1,69 @@
+use tauri::State;
+
+use crate::commands::with_repo;
+use crate::config;
+use crate::git::backend::GitBackend;
+use crate::git::error::GitResult;
+use crate::git::identity;
+use crate::git::types::IdentityCheck;
+use crate::state::AppState;
+
+/// Check the repository against the profile its path or remotes select.
+pub(crate) fn check_repo(
+    backend: &dyn GitBackend,
+    author_email: Option<&str>,
+) -> GitResult<IdentityCheck> {
+    let cfg = config::load_config()?;
+    let remote_urls: Vec<String> = backend
+        .list_remotes()?
+        .into_iter()
+        .map(|remote| remote.url)
+        .collect();
+    let profile = cfg.identity.profile_for(backend.workdir(), &remote_urls);
+    Ok(identity::check(
+        backend.workdir(),
+        profile,
+        cfg.identity.on_mismatch,
+        author_email,
+    ))
+}
+
+/// Refuse to write commits when the identity breaks a blocking profile.
+/// Every command that creates commits calls this before touching the repository.
+pub(crate) fn enforce_identity(
+    backend: &dyn GitBackend,
+    author_email: Option<&str>,
+) -> Result<(), String> {
+    let check = check_repo(backend, author_email).map_err(|e| e.to_string())?;
+    identity::enforce(&check).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_identity_check(
+    tab_id: String,
+    state: State<'_, AppState>,
+) -> Result<IdentityCheck, String> {
+    with_repo(&state, &tab_id, |backend| {
+        check_repo(backend, None).map_err(|e| e.to_string())
+    })
+}
+
+/// Write a profile's identity to the repository's local config.
+#[tauri::command]
+pub fn apply_identity_profile(
+    tab_id: String,
+    label: String,
+    state: State<'_, AppState>,
+) -> Result<IdentityCheck, String> {
+    let cfg = config::load_config().map_err(|e| e.to_string())?;
+    let profile = cfg
+        .identity
+        .profiles
+        .iter()
+        .find(|p| p.label == label)
+        .ok_or_else(|| format!("identity profile not found: {label}"))?;
+    with_repo(&state, &tab_id, |backend| {
+        identity::apply_profile(backend, profile).map_err(|e| e.to_string())?;
+        check_repo(backend, None).map_err(|e| e.to_string())
+    })
+}
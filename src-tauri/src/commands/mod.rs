@@ -8,6 +8,7 @@ pub mod gitconfig;
 pub mod gitignore;
 pub mod history;
 pub mod hosting;
+pub mod identity;
 pub mod rebase;
 pub mod remote;
 pub mod repo;
@@ -21,6 +22,7 @@ pub mod tag;
 pub mod worktree;
 
 use crate::git::backend::GitBackend;
+use crate::git::dispatcher::GitDispatcher;
 use crate::state::AppState;
 
 pub fn with_repo<F, R>(state: &AppState, tab_id: &str, f: F) -> Result<R, String>
@@ -36,3 +38,26 @@ where
         .ok_or("No repository opened for this tab")?;
     f(ctx.backend.as_ref())
 }
+
+/// Like [`with_repo`], but on a separate backend opened for the tab's
+/// repository. Network operations and rebases use this so the tabs lock is
+/// not held while they wait on the remote or on `exec` steps, which would
+/// block every other command.
+pub fn with_repo_unlocked<F, R>(state: &AppState, tab_id: &str, f: F) -> Result<R, String>
+where
+    F: FnOnce(&dyn GitBackend) -> Result<R, String>,
+{
+    let path = {
+        let tabs = state
+            .tabs
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        tabs.get(tab_id)
+            .ok_or("No repository opened for this tab")?
+            .path
+            .clone()
+    };
+    let backend =
+        GitDispatcher::open(&path, repo::backend_kind_for(&path)).map_err(|e| e.to_string())?;
+    f(backend.as_ref())
+}
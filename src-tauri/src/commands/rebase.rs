@@ -1,32 +1,107 @@
-use tauri::State;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
 
-use crate::commands::with_repo;
-use crate::git::types::{MergeBaseContent, RebaseResult, RebaseState, RebaseTodoEntry};
+use crate::commands::identity::enforce_identity;
+use crate::commands::{with_repo, with_repo_unlocked};
+use crate::git::absorb as git_absorb;
+use crate::git::rebase::{self as git_rebase, RebaseControl};
+use crate::git::split as git_split;
+use crate::git::stack as git_stack;
+use crate::git::transfer::CancelToken;
+use crate::git::types::{
+    AbsorbOptions, AbsorbResult, CommitEdit, CommitResult, FixupKind, MergeBaseContent,
+    MoveDirection, RebaseOptions, RebaseProgress, RebaseResult, RebaseState, RebaseTodoEntry,
+    SplitCommitGroup, SplitCommitResult,
+};
 use crate::state::AppState;
 
+#[derive(Debug, Clone, Serialize)]
+pub struct RebaseProgressEvent {
+    pub tab_id: String,
+    pub progress: RebaseProgress,
+}
+
+/// Register a cancel token for the rebase running in `tab_id` and build a
+/// control that emits a `rebase:progress` event for each step. Pair with
+/// [`finish_rebase`].
+fn begin_rebase(
+    state: &AppState,
+    app_handle: &AppHandle,
+    tab_id: &str,
+) -> Result<RebaseControl, String> {
+    let token = CancelToken::new();
+    {
+        let mut rebases = state
+            .rebases
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        if rebases.contains_key(tab_id) {
+            return Err("A rebase is already running in this tab".to_string());
+        }
+        rebases.insert(tab_id.to_string(), token.clone());
+    }
+
+    let handle = app_handle.clone();
+    let tab_id = tab_id.to_string();
+    Ok(RebaseControl::new(token).with_progress(move |progress| {
+        let event = RebaseProgressEvent {
+            tab_id: tab_id.clone(),
+            progress: progress.clone(),
+        };
+        let _ = handle.emit("rebase:progress", &event);
+    }))
+}
+
+fn finish_rebase(state: &AppState, tab_id: &str) {
+    if let Ok(mut rebases) = state.rebases.lock() {
+        rebases.remove(tab_id);
+    }
+}
+
 #[tauri::command]
-pub fn rebase(
+pub async fn rebase(
     tab_id: String,
     onto: String,
+    options: Option<RebaseOptions>,
+    app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<RebaseResult, String> {
-    with_repo(&state, &tab_id, |backend| {
-        backend.rebase(&onto).map_err(|e| e.to_string())
-    })
+    let control = begin_rebase(&state, &app_handle, &tab_id)?;
+    let result = with_repo_unlocked(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
+        backend
+            .rebase(&onto, &options.unwrap_or_default(), &control)
+            .map_err(|e| e.to_string())
+    });
+    finish_rebase(&state, &tab_id);
+    result
 }
 
+/// `autosquash` moves `fixup!`, `squash!` and `amend!` picks after their
+/// targets before the rebase starts.
 #[tauri::command]
-pub fn interactive_rebase(
+pub async fn interactive_rebase(
     tab_id: String,
     onto: String,
     todo: Vec<RebaseTodoEntry>,
+    autosquash: Option<bool>,
+    app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<RebaseResult, String> {
-    with_repo(&state, &tab_id, |backend| {
+    let todo = if autosquash.unwrap_or_default() {
+        git_rebase::autosquash(todo)
+    } else {
+        todo
+    };
+    let control = begin_rebase(&state, &app_handle, &tab_id)?;
+    let result = with_repo_unlocked(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
         backend
-            .interactive_rebase(&onto, &todo)
+            .interactive_rebase(&onto, &todo, &control)
             .map_err(|e| e.to_string())
-    })
+    });
+    finish_rebase(&state, &tab_id);
+    result
 }
 
 #[tauri::command]
@@ -44,10 +119,35 @@ pub fn abort_rebase(tab_id: String, state: State<'_, AppState>) -> Result<(), St
 }
 
 #[tauri::command]
-pub fn continue_rebase(tab_id: String, state: State<'_, AppState>) -> Result<RebaseResult, String> {
-    with_repo(&state, &tab_id, |backend| {
-        backend.continue_rebase().map_err(|e| e.to_string())
-    })
+pub async fn continue_rebase(
+    tab_id: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<RebaseResult, String> {
+    let control = begin_rebase(&state, &app_handle, &tab_id)?;
+    let result = with_repo_unlocked(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
+        backend.continue_rebase(&control).map_err(|e| e.to_string())
+    });
+    finish_rebase(&state, &tab_id);
+    result
+}
+
+/// Cancel the rebase running for `tab_id`, killing the `exec` step it is
+/// running. Returns `false` when nothing was running.
+#[tauri::command]
+pub fn cancel_rebase(tab_id: String, state: State<'_, AppState>) -> Result<bool, String> {
+    let rebases = state
+        .rebases
+        .lock()
+        .map_err(|e| format!("Lock poisoned: {e}"))?;
+    match rebases.get(&tab_id) {
+        Some(token) => {
+            token.cancel();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
 }
 
 #[tauri::command]
@@ -60,20 +160,142 @@ pub fn get_rebase_state(
     })
 }
 
+/// With `rebase_merges` the todo keeps the branch's merges and `limit` is
+/// ignored. An `exec` command is added after each commit, once autosquash
+/// has placed the fixups.
 #[tauri::command]
 pub fn get_rebase_todo(
     tab_id: String,
     onto: String,
     limit: usize,
+    autosquash: Option<bool>,
+    options: Option<RebaseOptions>,
     state: State<'_, AppState>,
 ) -> Result<Vec<RebaseTodoEntry>, String> {
+    let options = options.unwrap_or_default();
+    let todo = with_repo(&state, &tab_id, |backend| {
+        let todo = if options.rebase_merges {
+            git_rebase::todo_with_merges(backend, &onto)
+        } else {
+            backend.get_rebase_todo(&onto, limit)
+        };
+        let todo = if autosquash.unwrap_or_default() {
+            todo.map(git_rebase::autosquash)
+        } else {
+            todo
+        };
+        // 積まれたブランチの update-ref も todo に出して、実行前に見えるようにする
+        todo.and_then(|todo| git_stack::with_update_refs(backend.workdir(), &onto, todo))
+            .map_err(|e| e.to_string())
+    })?;
+    Ok(match &options.exec {
+        Some(command) => git_rebase::insert_exec(todo, command),
+        None => todo,
+    })
+}
+
+/// Commit the staged changes as a `fixup!`, `squash!` or `amend!` commit of
+/// `target`.
+#[tauri::command]
+pub fn create_fixup_commit(
+    tab_id: String,
+    target: String,
+    kind: FixupKind,
+    message: Option<String>,
+    sign: Option<bool>,
+    skip_hooks: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<CommitResult, String> {
+    with_repo(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
+        git_rebase::create_fixup_commit(
+            backend,
+            &target,
+            kind,
+            &message.unwrap_or_default(),
+            sign.unwrap_or_default(),
+            skip_hooks.unwrap_or_default(),
+        )
+        .map_err(|e| e.to_string())
+    })
+}
+
+/// Split the staged hunks into fixup commits of the commits that last
+/// touched them.
+#[tauri::command]
+pub fn absorb(
+    tab_id: String,
+    options: Option<AbsorbOptions>,
+    state: State<'_, AppState>,
+) -> Result<AbsorbResult, String> {
     with_repo(&state, &tab_id, |backend| {
+        let options = options.unwrap_or_default();
+        if !options.dry_run {
+            enforce_identity(backend, None)?;
+        }
+        git_absorb::absorb(backend, &options).map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn reword_commit(
+    tab_id: String,
+    oid: String,
+    message: String,
+    state: State<'_, AppState>,
+) -> Result<RebaseResult, String> {
+    with_repo(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
+        backend
+            .edit_commit(&CommitEdit::Reword { oid, message })
+            .map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn drop_commit(
+    tab_id: String,
+    oid: String,
+    state: State<'_, AppState>,
+) -> Result<RebaseResult, String> {
+    with_repo(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
         backend
-            .get_rebase_todo(&onto, limit)
+            .edit_commit(&CommitEdit::Drop { oid })
             .map_err(|e| e.to_string())
     })
 }
 
+#[tauri::command]
+pub fn move_commit(
+    tab_id: String,
+    oid: String,
+    direction: MoveDirection,
+    state: State<'_, AppState>,
+) -> Result<RebaseResult, String> {
+    with_repo(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
+        backend
+            .edit_commit(&CommitEdit::Move { oid, direction })
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// Rewrite a commit on the current branch into one commit per group and
+/// replay the commits after it.
+#[tauri::command]
+pub fn split_commit(
+    tab_id: String,
+    oid: String,
+    groups: Vec<SplitCommitGroup>,
+    state: State<'_, AppState>,
+) -> Result<SplitCommitResult, String> {
+    with_repo(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
+        git_split::split_commit(backend, &oid, &groups).map_err(|e| e.to_string())
+    })
+}
+
 #[tauri::command]
 pub fn get_merge_base_content(
     tab_id: String,
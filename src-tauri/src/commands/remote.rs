@@ -1,43 +1,153 @@
-use tauri::State;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
 
-use crate::commands::with_repo;
-use crate::git::types::{FetchResult, MergeResult, PullOption, PushResult, RemoteInfo};
+use crate::commands::identity::enforce_identity;
+use crate::commands::{with_repo, with_repo_unlocked};
+use crate::git::error::GitError;
+use crate::git::transfer::{CancelToken, TransferControl, TransferProgress};
+use crate::git::types::{
+    FetchOptions, FetchResult, MergeResult, PullOption, PushOptions, PushResult, RemoteInfo,
+};
 use crate::state::AppState;
 
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteOperation {
+    Fetch,
+    Pull,
+    Push,
+    Clone,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteProgressEvent {
+    pub tab_id: String,
+    pub operation: RemoteOperation,
+    pub progress: TransferProgress,
+}
+
+/// Register a cancel token for `tab_id` and build a control that emits
+/// `remote:progress` events. Pair with [`finish_transfer`]. A tab runs one
+/// transfer at a time, so its token is the one `cancel_remote_operation`
+/// cancels.
+pub(crate) fn begin_transfer(
+    state: &AppState,
+    app_handle: &AppHandle,
+    tab_id: &str,
+    operation: RemoteOperation,
+) -> Result<TransferControl, String> {
+    let token = CancelToken::new();
+    {
+        let mut transfers = state
+            .transfers
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        if transfers.contains_key(tab_id) {
+            return Err("A network operation is already running in this tab".to_string());
+        }
+        transfers.insert(tab_id.to_string(), token.clone());
+    }
+
+    let handle = app_handle.clone();
+    let tab_id = tab_id.to_string();
+    Ok(TransferControl::new(token).with_progress(move |progress| {
+        let event = RemoteProgressEvent {
+            tab_id: tab_id.clone(),
+            operation,
+            progress: progress.clone(),
+        };
+        let _ = handle.emit("remote:progress", &event);
+    }))
+}
+
+pub(crate) fn finish_transfer(state: &AppState, tab_id: &str) {
+    if let Ok(mut transfers) = state.transfers.lock() {
+        transfers.remove(tab_id);
+    }
+}
+
+// ネットワーク操作は async にしてメインスレッドを塞がないようにする。
+// 同期コマンドのままだと cancel_remote_operation が処理されない。
+// 転送中は tabs のロックを握らないよう、別のバックエンドで実行する。
 #[tauri::command]
-pub fn fetch_remote(
+pub async fn fetch_remote(
     tab_id: String,
     remote_name: String,
+    options: Option<FetchOptions>,
+    app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<FetchResult, String> {
-    with_repo(&state, &tab_id, |backend| {
-        backend.fetch(&remote_name).map_err(|e| e.to_string())
-    })
+    let control = begin_transfer(&state, &app_handle, &tab_id, RemoteOperation::Fetch)?;
+    let result = with_repo_unlocked(&state, &tab_id, |backend| {
+        backend
+            .fetch(&remote_name, &options.unwrap_or_default(), &control)
+            .map_err(|e| e.to_string())
+    });
+    finish_transfer(&state, &tab_id);
+    result
 }
 
 #[tauri::command]
-pub fn pull_remote(
+pub async fn pull_remote(
     tab_id: String,
     remote_name: String,
     option: PullOption,
+    app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<MergeResult, String> {
+    with_repo(&state, &tab_id, |backend| enforce_identity(backend, None))?;
+    // 通信する fetch だけロックを外し、HEAD と作業ツリーを動かす統合はロックして行う
+    let control = begin_transfer(&state, &app_handle, &tab_id, RemoteOperation::Pull)?;
+    let fetched = with_repo_unlocked(&state, &tab_id, |backend| {
+        backend
+            .fetch(&remote_name, &FetchOptions::default(), &control)
+            .map_err(|e| e.to_string())
+    });
+    finish_transfer(&state, &tab_id);
+    fetched?;
+    if control.is_cancelled() {
+        return Err(GitError::Cancelled.to_string());
+    }
     with_repo(&state, &tab_id, |backend| {
         backend
-            .pull(&remote_name, option)
+            .pull_fetched(&remote_name, option)
             .map_err(|e| e.to_string())
     })
 }
 
 #[tauri::command]
-pub fn push_remote(
+pub async fn push_remote(
     tab_id: String,
     remote_name: String,
+    options: Option<PushOptions>,
+    app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<PushResult, String> {
-    with_repo(&state, &tab_id, |backend| {
-        backend.push(&remote_name).map_err(|e| e.to_string())
-    })
+    let control = begin_transfer(&state, &app_handle, &tab_id, RemoteOperation::Push)?;
+    let result = with_repo_unlocked(&state, &tab_id, |backend| {
+        backend
+            .push(&remote_name, &options.unwrap_or_default(), &control)
+            .map_err(|e| e.to_string())
+    });
+    finish_transfer(&state, &tab_id);
+    result
+}
+
+/// Cancel the network operation running for `tab_id`. Returns `false` when
+/// nothing was running.
+#[tauri::command]
+pub fn cancel_remote_operation(tab_id: String, state: State<'_, AppState>) -> Result<bool, String> {
+    let transfers = state
+        .transfers
+        .lock()
+        .map_err(|e| format!("Lock poisoned: {e}"))?;
+    match transfers.get(&tab_id) {
+        Some(token) => {
+            token.cancel();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
 }
 
 #[tauri::command]
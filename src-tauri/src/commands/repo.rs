@@ -1,10 +1,18 @@
 use tauri::{AppHandle, Emitter, State};
 
+use crate::commands::remote::{begin_transfer, finish_transfer, RemoteOperation};
 use crate::config::{self, RecentRepo};
-use crate::git::dispatcher::GitDispatcher;
+use crate::git::dispatcher::{BackendKind, GitDispatcher};
 use crate::state::{self, AppState, RepoContext};
 use crate::watcher;
 
+/// Resolve which `GitBackend` implementation the config selects for `path`.
+pub(crate) fn backend_kind_for(path: &str) -> BackendKind {
+    config::load_config()
+        .map(|cfg| cfg.backend.kind_for(path))
+        .unwrap_or_default()
+}
+
 pub(crate) fn setup_repo_after_open(
     backend: Box<dyn crate::git::backend::GitBackend>,
     app_handle: &AppHandle,
@@ -63,7 +71,7 @@ pub fn open_repository(
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let backend = GitDispatcher::open_default(&path).map_err(|e| e.to_string())?;
+    let backend = GitDispatcher::open(&path, backend_kind_for(&path)).map_err(|e| e.to_string())?;
     setup_repo_after_open(backend, &app_handle, &state, &path, &tab_id)
 }
 
@@ -75,7 +83,7 @@ pub fn init_repository(
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let backend = GitDispatcher::init(&path).map_err(|e| e.to_string())?;
+    let backend = GitDispatcher::init(&path, backend_kind_for(&path)).map_err(|e| e.to_string())?;
 
     if let Some(template_name) = gitignore_template {
         if !template_name.is_empty() {
@@ -104,13 +112,16 @@ pub fn remove_recent_repo(path: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn clone_repository(
+pub async fn clone_repository(
     url: String,
     path: String,
     tab_id: String,
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let backend = GitDispatcher::clone_repo(&url, &path).map_err(|e| e.to_string())?;
+    let control = begin_transfer(&state, &app_handle, &tab_id, RemoteOperation::Clone)?;
+    let result = GitDispatcher::clone_repo(&url, &path, backend_kind_for(&path), &control);
+    finish_transfer(&state, &tab_id);
+    let backend = result.map_err(|e| e.to_string())?;
     setup_repo_after_open(backend, &app_handle, &state, &path, &tab_id)
 }
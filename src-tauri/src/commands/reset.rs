@@ -1,7 +1,7 @@
 use tauri::State;
 
 use crate::commands::with_repo;
-use crate::git::types::{ReflogEntry, ResetMode, ResetResult};
+use crate::git::types::{OperationEntry, ReflogEntry, ResetMode, ResetResult};
 use crate::state::AppState;
 
 #[tauri::command]
@@ -28,6 +28,26 @@ pub fn reset_file(
     })
 }
 
+#[tauri::command]
+pub fn list_operations(
+    tab_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<OperationEntry>, String> {
+    with_repo(&state, &tab_id, |backend| {
+        backend.list_operations().map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn undo_last_operation(
+    tab_id: String,
+    state: State<'_, AppState>,
+) -> Result<OperationEntry, String> {
+    with_repo(&state, &tab_id, |backend| {
+        backend.undo_last_operation().map_err(|e| e.to_string())
+    })
+}
+
 #[tauri::command]
 pub fn get_reflog(
     tab_id: String,
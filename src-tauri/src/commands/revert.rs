@@ -1,5 +1,6 @@
 use tauri::State;
 
+use crate::commands::identity::enforce_identity;
 use crate::commands::with_repo;
 use crate::git::types::{RevertMode, RevertResult};
 use crate::state::AppState;
@@ -12,6 +13,7 @@ pub fn revert(
     state: State<'_, AppState>,
 ) -> Result<RevertResult, String> {
     with_repo(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
         backend.revert(&oid, mode).map_err(|e| e.to_string())
     })
 }
@@ -33,6 +35,7 @@ pub fn abort_revert(tab_id: String, state: State<'_, AppState>) -> Result<(), St
 #[tauri::command]
 pub fn continue_revert(tab_id: String, state: State<'_, AppState>) -> Result<RevertResult, String> {
     with_repo(&state, &tab_id, |backend| {
+        enforce_identity(backend, None)?;
         backend.continue_revert().map_err(|e| e.to_string())
     })
 }
@@ -1,18 +1,19 @@
 use tauri::State;
 
 use crate::commands::with_repo;
-use crate::git::types::{FileDiff, StashEntry};
+use crate::git::types::{FileDiff, StashApplyResult, StashDetail, StashEntry, StashOptions};
 use crate::state::AppState;
 
 #[tauri::command]
 pub fn stash_save(
     tab_id: String,
     message: Option<String>,
+    options: Option<StashOptions>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
     with_repo(&state, &tab_id, |backend| {
         backend
-            .stash_save(message.as_deref())
+            .stash_save(message.as_deref(), &options.unwrap_or_default())
             .map_err(|e| e.to_string())
     })
 }
@@ -25,16 +26,44 @@ pub fn list_stashes(tab_id: String, state: State<'_, AppState>) -> Result<Vec<St
 }
 
 #[tauri::command]
-pub fn apply_stash(tab_id: String, index: usize, state: State<'_, AppState>) -> Result<(), String> {
+pub fn apply_stash(
+    tab_id: String,
+    index: usize,
+    restore_index: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<StashApplyResult, String> {
+    with_repo(&state, &tab_id, |backend| {
+        backend
+            .stash_apply(index, restore_index.unwrap_or(false))
+            .map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn pop_stash(
+    tab_id: String,
+    index: usize,
+    restore_index: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<StashApplyResult, String> {
     with_repo(&state, &tab_id, |backend| {
-        backend.stash_apply(index).map_err(|e| e.to_string())
+        backend
+            .stash_pop(index, restore_index.unwrap_or(false))
+            .map_err(|e| e.to_string())
     })
 }
 
 #[tauri::command]
-pub fn pop_stash(tab_id: String, index: usize, state: State<'_, AppState>) -> Result<(), String> {
+pub fn stash_branch(
+    tab_id: String,
+    index: usize,
+    branch_name: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
     with_repo(&state, &tab_id, |backend| {
-        backend.stash_pop(index).map_err(|e| e.to_string())
+        backend
+            .stash_branch(index, &branch_name)
+            .map_err(|e| e.to_string())
     })
 }
 
@@ -55,3 +84,28 @@ pub fn get_stash_diff(
         backend.stash_diff(index).map_err(|e| e.to_string())
     })
 }
+
+#[tauri::command]
+pub fn get_stash_detail(
+    tab_id: String,
+    index: usize,
+    state: State<'_, AppState>,
+) -> Result<StashDetail, String> {
+    with_repo(&state, &tab_id, |backend| {
+        backend.stash_detail(index).map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn rename_stash(
+    tab_id: String,
+    index: usize,
+    message: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    with_repo(&state, &tab_id, |backend| {
+        backend
+            .stash_rename(index, &message)
+            .map_err(|e| e.to_string())
+    })
+}
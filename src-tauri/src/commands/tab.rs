@@ -18,7 +18,8 @@ pub fn open_tab(
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let backend = GitDispatcher::open_default(&path).map_err(|e| e.to_string())?;
+    let backend = GitDispatcher::open(&path, super::repo::backend_kind_for(&path))
+        .map_err(|e| e.to_string())?;
     super::repo::setup_repo_after_open(backend, &app_handle, &state, &path, &tab_id)
 }
 
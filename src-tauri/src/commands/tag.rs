@@ -1,7 +1,10 @@
 use tauri::State;
 
 use crate::commands::with_repo;
-use crate::git::types::TagInfo;
+use crate::git::release_notes;
+use crate::git::types::{
+    CreateTagOptions, ReleaseNotes, ReleaseNotesFormat, ReleaseNotesOptions, TagInfo,
+};
 use crate::state::AppState;
 
 #[tauri::command]
@@ -16,11 +19,12 @@ pub fn create_tag(
     tab_id: String,
     name: String,
     message: Option<String>,
+    options: Option<CreateTagOptions>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
     with_repo(&state, &tab_id, |backend| {
         backend
-            .create_tag(&name, message.as_deref())
+            .create_tag(&name, message.as_deref(), &options.unwrap_or_default())
             .map_err(|e| e.to_string())
     })
 }
@@ -42,3 +46,31 @@ pub fn checkout_tag(
         backend.checkout_tag(&name).map_err(|e| e.to_string())
     })
 }
+
+#[tauri::command]
+pub fn get_release_notes(
+    tab_id: String,
+    options: Option<ReleaseNotesOptions>,
+    state: State<'_, AppState>,
+) -> Result<ReleaseNotes, String> {
+    with_repo(&state, &tab_id, |backend| {
+        backend
+            .release_notes(&options.unwrap_or_default())
+            .map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn render_release_notes(
+    tab_id: String,
+    options: Option<ReleaseNotesOptions>,
+    format: Option<ReleaseNotesFormat>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    with_repo(&state, &tab_id, |backend| {
+        let notes = backend
+            .release_notes(&options.unwrap_or_default())
+            .map_err(|e| e.to_string())?;
+        release_notes::render(&notes, format.unwrap_or_default()).map_err(|e| e.to_string())
+    })
+}
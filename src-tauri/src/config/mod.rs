@@ -1,12 +1,16 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
 use crate::ai::types::AiConfig;
+use crate::git::dispatcher::BackendKind;
 use crate::git::error::{GitError, GitResult};
+use crate::git::identity;
+use crate::git::types::{CoAuthor, IdentityMismatchAction, IdentityProfile};
 
 const MAX_RECENT_REPOS: usize = 20;
+const MAX_CO_AUTHORS: usize = 50;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecentRepo {
@@ -30,6 +34,13 @@ pub struct AppConfig {
     pub keybindings: KeybindingsConfig,
     #[serde(default)]
     pub tools: ToolsConfig,
+    #[serde(default)]
+    pub backend: BackendConfig,
+    /// Co-authors offered when writing a commit, most recently used first.
+    #[serde(default)]
+    pub co_authors: Vec<CoAuthor>,
+    #[serde(default)]
+    pub identity: IdentityConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -122,6 +133,48 @@ impl Default for ToolsConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BackendConfig {
+    #[serde(default)]
+    pub default_kind: BackendKind,
+    #[serde(default)]
+    pub repositories: Vec<RepoBackendConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoBackendConfig {
+    pub path: String,
+    pub kind: BackendKind,
+}
+
+impl BackendConfig {
+    /// Backend to open `path` with: a per-repository override, else the default.
+    pub fn kind_for(&self, path: &str) -> BackendKind {
+        self.repositories
+            .iter()
+            .find(|r| r.path == path)
+            .map(|r| r.kind)
+            .unwrap_or(self.default_kind)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct IdentityConfig {
+    #[serde(default)]
+    pub profiles: Vec<IdentityProfile>,
+    /// What to do when a repository's `user.email` is not its profile's.
+    #[serde(default)]
+    pub on_mismatch: IdentityMismatchAction,
+}
+
+impl IdentityConfig {
+    /// Profile for the repository at `path`: the first one whose path or
+    /// remote URL patterns match.
+    pub fn profile_for(&self, path: &Path, remote_urls: &[String]) -> Option<&IdentityProfile> {
+        identity::select_profile(&self.profiles, path, remote_urls)
+    }
+}
+
 fn config_path() -> GitResult<PathBuf> {
     let config_dir = dirs::config_dir()
         .ok_or_else(|| GitError::ConfigReadFailed("config directory not found".into()))?;
@@ -167,6 +220,22 @@ pub fn remove_recent_repo(config: &mut AppConfig, path: &str) {
     config.recent_repos.retain(|r| r.path != path);
 }
 
+/// Add or move a co-author to the front of the list. Entries are matched by
+/// email, ignoring case.
+pub fn add_co_author(config: &mut AppConfig, co_author: CoAuthor) {
+    config
+        .co_authors
+        .retain(|c| !c.email.eq_ignore_ascii_case(&co_author.email));
+    config.co_authors.insert(0, co_author);
+    config.co_authors.truncate(MAX_CO_AUTHORS);
+}
+
+pub fn remove_co_author(config: &mut AppConfig, email: &str) {
+    config
+        .co_authors
+        .retain(|c| !c.email.eq_ignore_ascii_case(email));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,6 +256,9 @@ mod tests {
             editor: EditorConfig::default(),
             keybindings: KeybindingsConfig::default(),
             tools: ToolsConfig::default(),
+            backend: BackendConfig::default(),
+            co_authors: vec![],
+            identity: IdentityConfig::default(),
         };
         let serialized = toml::to_string(&config).unwrap();
         let deserialized: AppConfig = toml::from_str(&serialized).unwrap();
@@ -307,6 +379,15 @@ tab_style = "compact"
                 auto_fetch_interval: 600,
                 open_in_editor_on_double_click: false,
             },
+            backend: BackendConfig {
+                default_kind: BackendKind::Git2,
+                repositories: vec![RepoBackendConfig {
+                    path: "/tmp/sparse".to_string(),
+                    kind: BackendKind::Cli,
+                }],
+            },
+            co_authors: vec![],
+            identity: IdentityConfig::default(),
         };
         let serialized = toml::to_string(&config).unwrap();
         let deserialized: AppConfig = toml::from_str(&serialized).unwrap();
@@ -317,6 +398,29 @@ tab_style = "compact"
         assert_eq!(deserialized.keybindings.preset, "vim");
         assert_eq!(deserialized.tools.diff_tool, "vscode");
         assert!(!deserialized.tools.auto_fetch_on_open);
+        assert_eq!(
+            deserialized.backend.kind_for("/tmp/sparse"),
+            BackendKind::Cli
+        );
+    }
+
+    #[test]
+    fn backend_config_resolves_per_repository_kind() {
+        let toml_str = r#"
+[backend]
+default_kind = "cli"
+
+[[backend.repositories]]
+path = "/tmp/plain"
+kind = "git2"
+"#;
+        let config: AppConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.backend.kind_for("/tmp/plain"), BackendKind::Git2);
+        assert_eq!(config.backend.kind_for("/tmp/other"), BackendKind::Cli);
+        assert_eq!(
+            AppConfig::default().backend.kind_for("/tmp/plain"),
+            BackendKind::Git2
+        );
     }
 
     #[test]
@@ -390,4 +494,60 @@ tab_style = "compact"
         assert_eq!(deserialized.recent_repos[0].path, "/tmp/repo1");
         assert_eq!(deserialized.recent_repos[0].name, "repo1");
     }
+
+    #[test]
+    fn add_co_author_moves_existing_entry_to_front() {
+        let mut config = AppConfig::default();
+        let co_author = |name: &str, email: &str| CoAuthor {
+            name: name.to_string(),
+            email: email.to_string(),
+        };
+        add_co_author(&mut config, co_author("Ann", "ann@example.com"));
+        add_co_author(&mut config, co_author("Bo", "bo@example.com"));
+        add_co_author(&mut config, co_author("Ann Lee", "ANN@example.com"));
+
+        assert_eq!(config.co_authors.len(), 2);
+        assert_eq!(config.co_authors[0].name, "Ann Lee");
+
+        remove_co_author(&mut config, "ann@example.com");
+        let serialized = toml::to_string(&config).unwrap();
+        let deserialized: AppConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!(
+            deserialized.co_authors,
+            vec![co_author("Bo", "bo@example.com")]
+        );
+    }
+
+    #[test]
+    fn identity_config_deserializes_profiles() {
+        let toml_str = r#"
+[identity]
+on_mismatch = "block"
+
+[[identity.profiles]]
+label = "work"
+name = "Ann Lee"
+email = "ann@acme.example"
+signing_format = "ssh"
+remote_patterns = ["*github.com*acme/*"]
+"#;
+        let config: AppConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.identity.on_mismatch, IdentityMismatchAction::Block);
+        let profile = config
+            .identity
+            .profile_for(
+                Path::new("/tmp/api"),
+                &["git@github.com:acme/api.git".to_string()],
+            )
+            .unwrap();
+        assert_eq!(profile.email, "ann@acme.example");
+        assert!(config
+            .identity
+            .profile_for(Path::new("/tmp/api"), &[])
+            .is_none());
+        assert_eq!(
+            AppConfig::default().identity.on_mismatch,
+            IdentityMismatchAction::Warn
+        );
+    }
 }
@@ -0,0 +1,300 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::process::Command;
+
+use crate::git::backend::GitBackend;
+use crate::git::error::{GitError, GitResult};
+use crate::git::git2_backend::{format_hunk_patch, run_git_apply};
+use crate::git::rebase::{self, RebaseControl};
+use crate::git::types::{
+    AbsorbFixup, AbsorbHunk, AbsorbOptions, AbsorbResult, AbsorbSkipReason, AbsorbSkipped,
+    BlameOptions, DiffHunk, DiffOptions, FixupKind, HunkIdentifier,
+};
+
+fn git(workdir: &Path, args: &[&str]) -> GitResult<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| GitError::AbsorbFailed(Box::new(e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::AbsorbFailed(stderr.trim().to_string().into()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn identifier(hunk: &DiffHunk) -> HunkIdentifier {
+    HunkIdentifier {
+        old_start: hunk.old_start,
+        old_lines: hunk.old_lines,
+        new_start: hunk.new_start,
+        new_lines: hunk.new_lines,
+    }
+}
+
+/// HEAD lines a hunk depends on: the lines it changes, or for a pure
+/// insertion the lines either side of it.
+fn touched_lines(hunk: &DiffHunk, line_count: u32) -> Vec<u32> {
+    if hunk.old_lines > 0 {
+        return (hunk.old_start..hunk.old_start + hunk.old_lines).collect();
+    }
+    // 挿入のみの hunk では old_start は挿入位置の直前の行
+    [hunk.old_start, hunk.old_start + 1]
+        .into_iter()
+        .filter(|line| (1..=line_count).contains(line))
+        .collect()
+}
+
+/// `hunk` of the staged diff against HEAD moved by `offset` lines, for an
+/// index where earlier hunks of its file were already applied. The new side
+/// is numbered as if it were the only change, as `git apply` expects of
+/// hunks without context.
+fn shifted(hunk: &DiffHunk, offset: i64) -> DiffHunk {
+    let old_start = (i64::from(hunk.old_start) + offset) as u32;
+    let new_start = match (hunk.old_lines, hunk.new_lines) {
+        (0, _) => old_start + 1,
+        (_, 0) => old_start.saturating_sub(1),
+        _ => old_start,
+    };
+    DiffHunk {
+        old_start,
+        new_start,
+        ..hunk.clone()
+    }
+}
+
+/// Pick the commit a hunk belongs to from the commits that last touched its
+/// lines. `range` maps the oids of the commits that can be fixed up to their
+/// position on the branch.
+fn place_hunk(
+    owners: &BTreeSet<String>,
+    range: &HashMap<String, usize>,
+    is_insertion: bool,
+) -> Result<String, AbsorbSkipReason> {
+    let in_range: Vec<&String> = owners
+        .iter()
+        .filter(|oid| range.contains_key(*oid))
+        .collect();
+    match in_range.as_slice() {
+        [] => Err(AbsorbSkipReason::OutsideRange),
+        // 挿入は隣の行の片方が範囲外でも、範囲内の 1 コミットに寄せてよい
+        [oid] if is_insertion || owners.len() == 1 => Ok((*oid).clone()),
+        _ => Err(AbsorbSkipReason::Ambiguous),
+    }
+}
+
+/// Turn staged hunks into `fixup!` commits of the commits on the branch that
+/// last touched their lines, as found by blaming HEAD. Hunks that cannot be
+/// placed on exactly one commit in `base..HEAD` stay staged and are reported.
+pub fn absorb(backend: &dyn GitBackend, options: &AbsorbOptions) -> GitResult<AbsorbResult> {
+    let workdir = backend.workdir();
+    let base = match &options.base {
+        Some(base) => base.clone(),
+        None => git(
+            workdir,
+            &["rev-parse", "--verify", "--quiet", "@{upstream}"],
+        )
+        .map_err(|_| {
+            GitError::AbsorbFailed("the current branch has no upstream; pass a base".into())
+        })?,
+    };
+    let todo = backend.get_rebase_todo(&base, usize::MAX)?;
+    let range: HashMap<String, usize> = todo
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| (entry.oid.clone(), index))
+        .collect();
+
+    // 文脈行があると hunk が変えていない隣の行の持ち主まで数えてしまう
+    let staged = backend.diff(
+        None,
+        &DiffOptions {
+            context_lines: 0,
+            staged: true,
+        },
+    )?;
+    // ignore-revs で整形コミットを飛ばすと、行を最後に変えたコミットを外す
+    let plain_blame = BlameOptions {
+        no_default_ignore_revs: true,
+        ..BlameOptions::default()
+    };
+
+    let mut groups: HashMap<String, Vec<(String, DiffHunk)>> = HashMap::new();
+    let mut skipped = Vec::new();
+    for file in staged {
+        let (Some(old_path), Some(new_path)) = (&file.old_path, &file.new_path) else {
+            let path = file.new_path.or(file.old_path).unwrap_or_default();
+            skipped.push(AbsorbSkipped {
+                path,
+                hunk: None,
+                reason: AbsorbSkipReason::NotModified,
+            });
+            continue;
+        };
+        if old_path != new_path {
+            skipped.push(AbsorbSkipped {
+                path: new_path.clone(),
+                hunk: None,
+                reason: AbsorbSkipReason::NotModified,
+            });
+            continue;
+        }
+        if file.hunks.is_empty() {
+            skipped.push(AbsorbSkipped {
+                path: new_path.clone(),
+                hunk: None,
+                reason: AbsorbSkipReason::NoHunks,
+            });
+            continue;
+        }
+
+        let blame = backend.get_blame(new_path, None, &plain_blame)?;
+        let owner_of: HashMap<u32, &str> = blame
+            .lines
+            .iter()
+            .map(|line| (line.line_number, line.commit_oid.as_str()))
+            .collect();
+        let line_count = blame.lines.len() as u32;
+        for hunk in file.hunks {
+            let owners: BTreeSet<String> = touched_lines(&hunk, line_count)
+                .into_iter()
+                .filter_map(|line| owner_of.get(&line).map(|oid| oid.to_string()))
+                .collect();
+            match place_hunk(&owners, &range, hunk.old_lines == 0) {
+                Ok(target) => groups
+                    .entry(target)
+                    .or_default()
+                    .push((new_path.clone(), hunk)),
+                Err(reason) => skipped.push(AbsorbSkipped {
+                    path: new_path.clone(),
+                    hunk: Some(identifier(&hunk)),
+                    reason,
+                }),
+            }
+        }
+    }
+
+    let mut targets: Vec<(String, Vec<(String, DiffHunk)>)> = groups.into_iter().collect();
+    targets.sort_by_key(|(oid, _)| range[oid]);
+    let mut fixups: Vec<AbsorbFixup> = targets
+        .iter()
+        .map(|(oid, hunks)| AbsorbFixup {
+            target_oid: oid.clone(),
+            target_summary: todo[range[oid]].message.clone(),
+            oid: None,
+            hunks: hunks
+                .iter()
+                .map(|(path, hunk)| AbsorbHunk {
+                    path: path.clone(),
+                    hunk: identifier(hunk),
+                })
+                .collect(),
+        })
+        .collect();
+    if options.dry_run || fixups.is_empty() {
+        return Ok(AbsorbResult {
+            fixups,
+            skipped,
+            rebase: None,
+        });
+    }
+
+    // インデックスを HEAD に戻し、コミットごとに hunk を積み直す。最後に元の
+    // インデックスへ戻せば、新しい HEAD との差分は置き場のない hunk だけになる
+    let staged_tree = git(workdir, &["write-tree"])?;
+    git(workdir, &["read-tree", "HEAD"])?;
+    // ファイルごとに、適用済みの hunk の HEAD での位置と増えた行数
+    let mut applied: HashMap<&str, Vec<(u32, i64)>> = HashMap::new();
+    let created = targets
+        .iter()
+        .zip(fixups.iter_mut())
+        .try_for_each(|((target, hunks), fixup)| {
+            for (path, hunk) in hunks {
+                let moves = applied.entry(path.as_str()).or_default();
+                let offset: i64 = moves
+                    .iter()
+                    .filter(|(start, _)| *start < hunk.old_start)
+                    .map(|(_, delta)| delta)
+                    .sum();
+                let patch = format_hunk_patch(path, &shifted(hunk, offset));
+                run_git_apply(workdir, &patch, &["--cached", "--unidiff-zero"])
+                    .map_err(GitError::AbsorbFailed)?;
+                moves.push((
+                    hunk.old_start,
+                    i64::from(hunk.new_lines) - i64::from(hunk.old_lines),
+                ));
+            }
+            let commit =
+                rebase::create_fixup_commit(backend, target, FixupKind::Fixup, "", false, false)?;
+            fixup.oid = Some(commit.oid);
+            Ok(())
+        });
+    git(workdir, &["read-tree", &staged_tree])?;
+    created?;
+
+    let rebase = if options.autosquash && rebase::is_clean(backend)? {
+        let todo = rebase::autosquash(backend.get_rebase_todo(&base, usize::MAX)?);
+        Some(backend.interactive_rebase(&base, &todo, &RebaseControl::default())?)
+    } else {
+        None
+    };
+    Ok(AbsorbResult {
+        fixups,
+        skipped,
+        rebase,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(old_start: u32, old_lines: u32) -> DiffHunk {
+        DiffHunk {
+            header: String::new(),
+            old_start,
+            old_lines,
+            new_start: old_start,
+            new_lines: 1,
+            lines: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn touched_lines_uses_neighbours_for_insertions() {
+        assert_eq!(touched_lines(&hunk(3, 2), 10), vec![3, 4]);
+        assert_eq!(touched_lines(&hunk(0, 0), 10), vec![1]);
+        assert_eq!(touched_lines(&hunk(10, 0), 10), vec![10]);
+        assert_eq!(touched_lines(&hunk(4, 0), 10), vec![4, 5]);
+    }
+
+    #[test]
+    fn place_hunk_needs_a_single_commit_in_range() {
+        let range: HashMap<String, usize> = [("a".to_string(), 0), ("b".to_string(), 1)]
+            .into_iter()
+            .collect();
+        let owners = |oids: &[&str]| oids.iter().map(|s| s.to_string()).collect();
+
+        assert_eq!(
+            place_hunk(&owners(&["a"]), &range, false),
+            Ok("a".to_string())
+        );
+        assert_eq!(
+            place_hunk(&owners(&["a", "b"]), &range, false),
+            Err(AbsorbSkipReason::Ambiguous)
+        );
+        assert_eq!(
+            place_hunk(&owners(&["a", "old"]), &range, false),
+            Err(AbsorbSkipReason::Ambiguous)
+        );
+        assert_eq!(
+            place_hunk(&owners(&["a", "old"]), &range, true),
+            Ok("a".to_string())
+        );
+        assert_eq!(
+            place_hunk(&owners(&["old"]), &range, false),
+            Err(AbsorbSkipReason::OutsideRange)
+        );
+    }
+}
@@ -1,14 +1,19 @@
 use std::path::Path;
 
 use crate::git::error::GitResult;
+use crate::git::rebase::RebaseControl;
 use crate::git::search::{CodeSearchResult, CommitSearchResult, FilenameSearchResult};
+use crate::git::transfer::TransferControl;
 use crate::git::types::{
-    BlameResult, BranchInfo, CherryPickMode, CherryPickResult, CommitDetail, CommitInfo,
-    CommitLogResult, CommitResult, ConflictFile, ConflictResolution, DiffOptions, FetchResult,
-    FileDiff, GitConfigEntry, GitConfigScope, HunkIdentifier, LineRange, LogFilter,
-    MergeBaseContent, MergeOption, MergeResult, PullOption, PushResult, RebaseResult, RebaseState,
-    RebaseTodoEntry, ReflogEntry, RemoteInfo, RepoStatus, ResetMode, ResetResult, RevertMode,
-    RevertResult, SignatureStatus, StashEntry, SubmoduleInfo, TagInfo, WorktreeInfo,
+    BlameOptions, BlameResult, BranchInfo, CherryPickMode, CherryPickResult, CommitDetail,
+    CommitEdit, CommitIdentity, CommitInfo, CommitLogResult, CommitResult, ConflictFile,
+    ConflictResolution, CreateTagOptions, DeleteBranchOptions, DeleteBranchResult, DiffOptions,
+    FetchOptions, FetchResult, FileDiff, FileHistoryEntry, GitConfigEntry, GitConfigScope,
+    HunkIdentifier, LineRange, LogFilter, MergeBaseContent, MergeOption, MergeResult,
+    OperationEntry, PruneCandidate, PullOption, PushOptions, PushResult, RebaseOptions,
+    RebaseResult, RebaseState, RebaseTodoEntry, ReflogEntry, ReleaseNotes, ReleaseNotesOptions,
+    RemoteInfo, RepoStatus, ResetMode, ResetResult, RevertMode, RevertResult, SignatureStatus,
+    StashApplyResult, StashDetail, StashEntry, StashOptions, SubmoduleInfo, TagInfo, WorktreeInfo,
 };
 
 pub trait GitBackend: Send + Sync {
@@ -20,16 +25,82 @@ pub trait GitBackend: Send + Sync {
     fn stage_all(&self) -> GitResult<()>;
     fn unstage_all(&self) -> GitResult<()>;
     fn current_branch(&self) -> GitResult<String>;
-    fn commit(&self, message: &str, amend: bool, sign: bool) -> GitResult<CommitResult>;
+    /// `skip_hooks` skips `pre-commit` and `commit-msg`, like
+    /// `git commit --no-verify`.
+    fn commit(
+        &self,
+        message: &str,
+        amend: bool,
+        sign: bool,
+        skip_hooks: bool,
+    ) -> GitResult<CommitResult>;
+    /// `commit` with the author and committer overridden for this commit.
+    fn commit_as(
+        &self,
+        message: &str,
+        amend: bool,
+        sign: bool,
+        skip_hooks: bool,
+        identity: &CommitIdentity,
+    ) -> GitResult<CommitResult>;
     fn list_branches(&self) -> GitResult<Vec<BranchInfo>>;
     fn create_branch(&self, name: &str) -> GitResult<()>;
     fn checkout_branch(&self, name: &str) -> GitResult<()>;
-    fn delete_branch(&self, name: &str) -> GitResult<()>;
+    /// Delete a local branch. Unless forced, a branch with commits missing
+    /// from HEAD and its upstream is kept and those commits are reported.
+    fn delete_branch(
+        &self,
+        name: &str,
+        options: &DeleteBranchOptions,
+        control: &TransferControl,
+    ) -> GitResult<DeleteBranchResult>;
+    /// The network half of [`delete_branch`](Self::delete_branch): run the
+    /// same checks and delete the upstream when `options.delete_remote` is
+    /// set, but keep the local branch. `deleted` is always `false`.
+    fn delete_upstream_branch(
+        &self,
+        name: &str,
+        options: &DeleteBranchOptions,
+        control: &TransferControl,
+    ) -> GitResult<DeleteBranchResult>;
+    /// Local branches merged into `base` (default: main/master/HEAD) or whose
+    /// upstream is gone.
+    fn list_prune_candidates(&self, base: Option<&str>) -> GitResult<Vec<PruneCandidate>>;
     fn rename_branch(&self, old_name: &str, new_name: &str) -> GitResult<()>;
+    /// Create `name` at `start_point` (commit, tag or branch). Starting from a
+    /// remote-tracking branch also sets it as the upstream.
+    fn create_branch_from(&self, name: &str, start_point: &str) -> GitResult<()>;
+    /// Set (`Some`) or unset (`None`) the upstream of a local branch.
+    fn set_upstream(&self, branch: &str, upstream: Option<&str>) -> GitResult<()>;
+    /// Check out `remote_branch` through a local tracking branch, creating it
+    /// when needed. Returns the local branch name.
+    fn checkout_remote_branch(
+        &self,
+        remote_branch: &str,
+        local_name: Option<&str>,
+    ) -> GitResult<String>;
     fn merge_branch(&self, branch_name: &str, option: MergeOption) -> GitResult<MergeResult>;
-    fn fetch(&self, remote_name: &str) -> GitResult<FetchResult>;
-    fn pull(&self, remote_name: &str, option: PullOption) -> GitResult<MergeResult>;
-    fn push(&self, remote_name: &str) -> GitResult<PushResult>;
+    fn fetch(
+        &self,
+        remote_name: &str,
+        options: &FetchOptions,
+        control: &TransferControl,
+    ) -> GitResult<FetchResult>;
+    fn pull(
+        &self,
+        remote_name: &str,
+        option: PullOption,
+        control: &TransferControl,
+    ) -> GitResult<MergeResult>;
+    /// The local half of [`pull`](Self::pull): merge or rebase the current
+    /// branch onto its `remote_name` counterpart as last fetched.
+    fn pull_fetched(&self, remote_name: &str, option: PullOption) -> GitResult<MergeResult>;
+    fn push(
+        &self,
+        remote_name: &str,
+        options: &PushOptions,
+        control: &TransferControl,
+    ) -> GitResult<PushResult>;
     fn list_remotes(&self) -> GitResult<Vec<RemoteInfo>>;
     fn add_remote(&self, name: &str, url: &str) -> GitResult<()>;
     fn remove_remote(&self, name: &str) -> GitResult<()>;
@@ -42,9 +113,32 @@ pub trait GitBackend: Send + Sync {
     ) -> GitResult<CommitLogResult>;
     fn get_commit_detail(&self, oid: &str) -> GitResult<CommitDetail>;
     fn get_commit_file_diff(&self, oid: &str, path: &str) -> GitResult<Vec<FileDiff>>;
-    fn get_blame(&self, path: &str, commit_oid: Option<&str>) -> GitResult<BlameResult>;
-    fn get_file_history(&self, path: &str, limit: usize, skip: usize)
-        -> GitResult<Vec<CommitInfo>>;
+    fn get_blame(
+        &self,
+        path: &str,
+        commit_oid: Option<&str>,
+        options: &BlameOptions,
+    ) -> GitResult<BlameResult>;
+    /// Blame the file as it was just before `commit_oid`, at its path in the
+    /// first parent, to see what a line looked like before that commit.
+    fn get_blame_before(
+        &self,
+        commit_oid: &str,
+        path: &str,
+        options: &BlameOptions,
+    ) -> GitResult<BlameResult>;
+    /// Commits touching `path`, newest first. With `follow`, the history
+    /// continues across renames and each entry carries the path it had then.
+    fn get_file_history(
+        &self,
+        path: &str,
+        limit: usize,
+        skip: usize,
+        follow: bool,
+    ) -> GitResult<Vec<FileHistoryEntry>>;
+    /// Write git's commit-graph file with changed-path Bloom filters so
+    /// history walks, path-limited logs and ancestry checks get faster.
+    fn write_commit_graph(&self) -> GitResult<()>;
     fn get_branch_commits(&self, branch_name: &str, limit: usize) -> GitResult<Vec<CommitInfo>>;
     fn stage_hunk(&self, path: &Path, hunk: &HunkIdentifier) -> GitResult<()>;
     fn unstage_hunk(&self, path: &Path, hunk: &HunkIdentifier) -> GitResult<()>;
@@ -55,19 +149,36 @@ pub trait GitBackend: Send + Sync {
     fn get_head_commit_message(&self) -> GitResult<String>;
 
     // Stash operations
-    fn stash_save(&self, message: Option<&str>) -> GitResult<()>;
+    fn stash_save(&self, message: Option<&str>, options: &StashOptions) -> GitResult<()>;
     fn stash_list(&self) -> GitResult<Vec<StashEntry>>;
-    fn stash_apply(&self, index: usize) -> GitResult<()>;
-    fn stash_pop(&self, index: usize) -> GitResult<()>;
+    /// Apply a stash. With `restore_index`, changes that were staged when
+    /// stashing are staged again (`--index`).
+    fn stash_apply(&self, index: usize, restore_index: bool) -> GitResult<StashApplyResult>;
+    fn stash_pop(&self, index: usize, restore_index: bool) -> GitResult<StashApplyResult>;
+    /// Create `branch_name` at the commit the stash was made on, check it
+    /// out and pop the stash there with its index restored.
+    fn stash_branch(&self, index: usize, branch_name: &str) -> GitResult<()>;
     fn stash_drop(&self, index: usize) -> GitResult<()>;
     fn stash_diff(&self, index: usize) -> GitResult<Vec<FileDiff>>;
+    fn stash_detail(&self, index: usize) -> GitResult<StashDetail>;
+    /// Replace the message of a stash, keeping its position in the list.
+    fn stash_rename(&self, index: usize, message: &str) -> GitResult<()>;
 
     // Tag operations
     fn list_tags(&self) -> GitResult<Vec<TagInfo>>;
-    fn create_tag(&self, name: &str, message: Option<&str>) -> GitResult<()>;
+    fn create_tag(
+        &self,
+        name: &str,
+        message: Option<&str>,
+        options: &CreateTagOptions,
+    ) -> GitResult<()>;
     fn delete_tag(&self, name: &str) -> GitResult<()>;
     fn checkout_tag(&self, name: &str) -> GitResult<()>;
 
+    // Release notes
+    /// Notes for the commits in `from..to`, grouped by conventional commit type.
+    fn release_notes(&self, options: &ReleaseNotesOptions) -> GitResult<ReleaseNotes>;
+
     // Conflict operations
     fn get_conflict_files(&self) -> GitResult<Vec<ConflictFile>>;
     fn resolve_conflict(&self, path: &str, resolution: ConflictResolution) -> GitResult<()>;
@@ -83,13 +194,29 @@ pub trait GitBackend: Send + Sync {
     fn is_merging(&self) -> GitResult<bool>;
 
     // Rebase operations
-    fn rebase(&self, onto: &str) -> GitResult<RebaseResult>;
-    fn interactive_rebase(&self, onto: &str, todo: &[RebaseTodoEntry]) -> GitResult<RebaseResult>;
+    /// Rebase onto `onto`. Stops on conflicts, and when an `exec` command
+    /// fails. Each step is reported to `control` as it starts.
+    fn rebase(
+        &self,
+        onto: &str,
+        options: &RebaseOptions,
+        control: &RebaseControl,
+    ) -> GitResult<RebaseResult>;
+    /// Run `todo` on top of `onto`. Also stops at `edit` and `break` steps.
+    fn interactive_rebase(
+        &self,
+        onto: &str,
+        todo: &[RebaseTodoEntry],
+        control: &RebaseControl,
+    ) -> GitResult<RebaseResult>;
     fn is_rebasing(&self) -> GitResult<bool>;
     fn abort_rebase(&self) -> GitResult<()>;
-    fn continue_rebase(&self) -> GitResult<RebaseResult>;
+    fn continue_rebase(&self, control: &RebaseControl) -> GitResult<RebaseResult>;
     fn get_rebase_state(&self) -> GitResult<Option<RebaseState>>;
     fn get_rebase_todo(&self, onto: &str, limit: usize) -> GitResult<Vec<RebaseTodoEntry>>;
+    /// Reword, drop or move one commit. Stops like `interactive_rebase` when
+    /// replaying the later commits conflicts.
+    fn edit_commit(&self, edit: &CommitEdit) -> GitResult<RebaseResult>;
     fn get_merge_base_content(&self, path: &str) -> GitResult<MergeBaseContent>;
 
     // Cherry-pick operations
@@ -108,6 +235,12 @@ pub trait GitBackend: Send + Sync {
     fn reset(&self, oid: &str, mode: ResetMode) -> GitResult<ResetResult>;
     fn reset_file(&self, path: &str, oid: &str) -> GitResult<()>;
 
+    // Operation journal
+    /// Destructive operations recorded by the backend, newest first.
+    fn list_operations(&self) -> GitResult<Vec<OperationEntry>>;
+    /// Restore the state from before the most recent recorded operation.
+    fn undo_last_operation(&self) -> GitResult<OperationEntry>;
+
     // Reflog operations
     fn get_reflog(&self, ref_name: &str, limit: usize) -> GitResult<Vec<ReflogEntry>>;
 
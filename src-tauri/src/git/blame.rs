@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::git::error::{GitError, GitResult};
+use crate::git::types::{BlameLine, BlameOptions};
+
+/// Ignore list looked up when the options name none, as hosting services do.
+const DEFAULT_IGNORE_REVS_FILE: &str = ".git-blame-ignore-revs";
+
+/// The files `blame.ignoreRevsFile` names, `None` when it is not set. As in
+/// git, an empty value drops the files listed before it.
+fn configured_ignore_revs_files(workdir: &Path) -> Option<Vec<String>> {
+    let config = git2::Repository::open(workdir).ok()?.config().ok()?;
+    let mut files: Option<Vec<String>> = None;
+    config
+        .multivar("blame.ignoreRevsFile", None)
+        .ok()?
+        .for_each(|entry| {
+            let files = files.get_or_insert_with(Vec::new);
+            match entry.value() {
+                Some("") => files.clear(),
+                Some(file) => files.push(file.to_string()),
+                None => {}
+            }
+        })
+        .ok()?;
+    files
+}
+
+fn ignore_revs_file(workdir: &Path, options: &BlameOptions) -> Option<String> {
+    if let Some(file) = &options.ignore_revs_file {
+        return Some(file.clone());
+    }
+    // 設定を空にしてあれば、git の設定と同じく既定のファイルも使わない
+    if options.no_default_ignore_revs
+        || configured_ignore_revs_files(workdir).is_some_and(|files| files.is_empty())
+    {
+        return None;
+    }
+    workdir
+        .join(DEFAULT_IGNORE_REVS_FILE)
+        .is_file()
+        .then(|| DEFAULT_IGNORE_REVS_FILE.to_string())
+}
+
+/// Whether blaming with `options` needs the git binary: libgit2 implements
+/// neither move and copy detection nor ignored revisions.
+pub(crate) fn needs_git(workdir: &Path, options: &BlameOptions) -> bool {
+    options.detect_moves
+        || options.detect_copies
+        || !options.ignore_revs.is_empty()
+        || ignore_revs_file(workdir, options).is_some()
+        || (!options.no_default_ignore_revs
+            && configured_ignore_revs_files(workdir).is_some_and(|files| !files.is_empty()))
+}
+
+/// Arguments for `git blame --porcelain` of `path` at `rev`.
+pub(crate) fn porcelain_args(
+    workdir: &Path,
+    rev: &str,
+    path: &str,
+    options: &BlameOptions,
+) -> Vec<String> {
+    let mut args = vec!["blame".to_string(), "--porcelain".to_string()];
+    if options.detect_moves {
+        args.push("-M".to_string());
+    }
+    if options.detect_copies {
+        args.push("-C".to_string());
+    }
+    if options.no_default_ignore_revs {
+        // 空のファイル名は blame.ignoreRevsFile で読んだ分を取り消す
+        args.push("--ignore-revs-file=".to_string());
+    }
+    if let Some(file) = ignore_revs_file(workdir, options) {
+        args.push(format!("--ignore-revs-file={file}"));
+    }
+    for rev in &options.ignore_revs {
+        args.push(format!("--ignore-rev={rev}"));
+    }
+    args.extend([rev.to_string(), "--".to_string(), path.to_string()]);
+    args
+}
+
+/// Blame through the git binary, for options libgit2 does not implement.
+pub(crate) fn blame_with_git(
+    workdir: &Path,
+    rev: &str,
+    path: &str,
+    options: &BlameOptions,
+) -> GitResult<Vec<BlameLine>> {
+    let output = Command::new("git")
+        .args(porcelain_args(workdir, rev, path, options))
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| GitError::BlameFailed(Box::new(e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::BlameFailed(stderr.trim().to_string().into()));
+    }
+    Ok(parse_blame_porcelain(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+pub(crate) fn parse_blame_porcelain(output: &str) -> Vec<BlameLine> {
+    let mut authors: HashMap<String, (String, i64)> = HashMap::new();
+    let mut summaries: HashMap<String, String> = HashMap::new();
+    // The filename is only repeated when a commit touches several paths.
+    let mut filenames: HashMap<String, String> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut current_oid = String::new();
+    let mut current_line: u32 = 0;
+    let mut original_line: u32 = 0;
+    let mut last_oid: Option<String> = None;
+
+    for line in output.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            let (author_name, author_date) = authors.get(&current_oid).cloned().unwrap_or_default();
+            let is_block_start = last_oid.as_deref() != Some(current_oid.as_str());
+            last_oid = Some(current_oid.clone());
+
+            lines.push(BlameLine {
+                line_number: current_line,
+                content: content.to_string(),
+                commit_oid: current_oid.clone(),
+                commit_short_oid: current_oid[..7.min(current_oid.len())].to_string(),
+                author_name,
+                author_date,
+                summary: summaries.get(&current_oid).cloned().unwrap_or_default(),
+                is_block_start,
+                original_path: filenames.get(&current_oid).cloned().unwrap_or_default(),
+                original_line_number: original_line,
+            });
+            continue;
+        }
+
+        let mut parts = line.split(' ');
+        let first = parts.next().unwrap_or("");
+        if (first.len() == 40 || first.len() == 64) && first.bytes().all(|b| b.is_ascii_hexdigit())
+        {
+            current_oid = first.to_string();
+            original_line = parts.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+            current_line = parts.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+        } else if let Some(name) = line.strip_prefix("author ") {
+            authors.entry(current_oid.clone()).or_default().0 = name.to_string();
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            authors.entry(current_oid.clone()).or_default().1 = time.parse().unwrap_or(0);
+        } else if let Some(summary) = line.strip_prefix("summary ") {
+            summaries.insert(current_oid.clone(), summary.to_string());
+        } else if let Some(filename) = line.strip_prefix("filename ") {
+            filenames.insert(current_oid.clone(), filename.to_string());
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porcelain_args_pass_ignored_revisions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let options = BlameOptions {
+            ignore_revs: vec!["abc123".to_string()],
+            ..BlameOptions::default()
+        };
+        let args = porcelain_args(tmp.path(), "HEAD", "f", &options);
+        assert_eq!(
+            args,
+            [
+                "blame",
+                "--porcelain",
+                "--ignore-rev=abc123",
+                "HEAD",
+                "--",
+                "f"
+            ]
+        );
+        assert!(needs_git(tmp.path(), &options));
+
+        std::fs::write(tmp.path().join(DEFAULT_IGNORE_REVS_FILE), "").unwrap();
+        let args = porcelain_args(tmp.path(), "HEAD", "f", &BlameOptions::default());
+        assert!(args.contains(&"--ignore-revs-file=.git-blame-ignore-revs".to_string()));
+    }
+
+    #[test]
+    fn default_ignore_file_can_be_turned_off() {
+        let tmp = tempfile::tempdir().unwrap();
+        git2::Repository::init(tmp.path()).unwrap();
+        std::fs::write(tmp.path().join(DEFAULT_IGNORE_REVS_FILE), "").unwrap();
+        let plain = BlameOptions {
+            no_default_ignore_revs: true,
+            ..BlameOptions::default()
+        };
+        assert!(!needs_git(tmp.path(), &plain));
+        let args = porcelain_args(tmp.path(), "HEAD", "f", &plain);
+        assert!(args.contains(&"--ignore-revs-file=".to_string()));
+        assert!(!args
+            .iter()
+            .any(|arg| arg.ends_with(DEFAULT_IGNORE_REVS_FILE)));
+
+        // git と同じく、空の blame.ignoreRevsFile でも既定のファイルを使わない
+        let mut config = git2::Repository::open(tmp.path())
+            .unwrap()
+            .config()
+            .unwrap();
+        config.set_str("blame.ignoreRevsFile", "").unwrap();
+        assert!(!needs_git(tmp.path(), &BlameOptions::default()));
+        config.set_str("blame.ignoreRevsFile", "revs.txt").unwrap();
+        assert!(needs_git(tmp.path(), &BlameOptions::default()));
+        assert!(!needs_git(tmp.path(), &plain));
+    }
+
+    #[test]
+    fn parse_blame_porcelain_marks_block_starts() {
+        let a = "a".repeat(40);
+        let b = "b".repeat(40);
+        let output = format!(
+            "{a} 1 1 2\nauthor Alice\nauthor-time 100\nsummary Add f\nfilename f\n\tone\n\
+{a} 2 2\n\ttwo\n\
+{b} 3 3 1\nauthor Bob\nauthor-time 200\nsummary Extend f\nfilename f\n\tthree\n"
+        );
+        let lines = parse_blame_porcelain(&output);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].is_block_start);
+        assert!(!lines[1].is_block_start);
+        assert!(lines[2].is_block_start);
+        assert_eq!(lines[1].author_name, "Alice");
+        assert_eq!(lines[2].author_date, 200);
+        assert_eq!(lines[1].summary, "Add f");
+        assert_eq!(lines[2].summary, "Extend f");
+        assert_eq!(lines[2].line_number, 3);
+    }
+
+    #[test]
+    fn parse_blame_porcelain_reports_original_path_and_line() {
+        let a = "a".repeat(40);
+        let b = "b".repeat(40);
+        let output = format!(
+            "{a} 7 1 1\nauthor Alice\nauthor-time 100\nfilename old/lib.rs\n\tmoved\n\
+{b} 2 2 1\nauthor Bob\nauthor-time 200\nfilename src/lib.rs\n\tnew\n\
+{a} 9 3 1\n\tmoved again\n"
+        );
+        let lines = parse_blame_porcelain(&output);
+        assert_eq!(lines[0].original_path, "old/lib.rs");
+        assert_eq!(lines[0].original_line_number, 7);
+        assert_eq!(lines[1].original_path, "src/lib.rs");
+        assert_eq!(lines[2].original_path, "old/lib.rs");
+        assert_eq!(lines[2].original_line_number, 9);
+        assert_eq!(lines[2].line_number, 3);
+    }
+}
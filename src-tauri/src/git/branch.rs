@@ -0,0 +1,238 @@
+use crate::git::backend::GitBackend;
+use crate::git::error::{GitError, GitResult};
+use crate::git::transfer::TransferControl;
+use crate::git::types::{
+    BranchInfo, CommitInfo, DeleteBranchOptions, DeleteBranchResult, PruneCandidate, PushOptions,
+};
+
+/// Split `origin/feature/x` into (`origin`, `feature/x`) using the configured
+/// remote names. Remote names may contain `/`, so the longest match wins.
+pub(crate) fn split_remote_branch<'a>(
+    name: &'a str,
+    remotes: &[String],
+) -> Option<(&'a str, &'a str)> {
+    let name = name.strip_prefix("refs/remotes/").unwrap_or(name);
+    remotes
+        .iter()
+        .filter_map(|remote| {
+            let branch = name.strip_prefix(remote.as_str())?.strip_prefix('/')?;
+            (!branch.is_empty()).then(|| (&name[..remote.len()], branch))
+        })
+        .max_by_key(|(remote, _)| remote.len())
+}
+
+/// Create (or reuse) a local branch tracking `remote_branch` and check it out.
+/// An existing local branch is only reused when it already tracks that remote
+/// branch. Returns the local branch name.
+pub(crate) fn checkout_remote_branch(
+    backend: &dyn GitBackend,
+    remote_branch: &str,
+    local_name: Option<&str>,
+) -> GitResult<String> {
+    let remotes: Vec<String> = backend
+        .list_remotes()?
+        .into_iter()
+        .map(|r| r.name)
+        .collect();
+    let remote_branch = remote_branch
+        .strip_prefix("refs/remotes/")
+        .unwrap_or(remote_branch);
+    let (_, branch_name) = split_remote_branch(remote_branch, &remotes).ok_or_else(|| {
+        GitError::CheckoutFailed(format!("not a remote branch: {remote_branch}").into())
+    })?;
+    let local_name = local_name.unwrap_or(branch_name);
+
+    match find_local(backend.list_branches()?, local_name) {
+        Some(branch) if branch.upstream.as_deref() == Some(remote_branch) => {}
+        Some(_) => {
+            return Err(GitError::BranchCreateFailed(
+                format!("branch '{local_name}' already exists").into(),
+            ))
+        }
+        None => backend.create_branch_from(local_name, remote_branch)?,
+    }
+
+    backend.checkout_branch(local_name)?;
+    Ok(local_name.to_string())
+}
+
+fn find_local(branches: Vec<BranchInfo>, name: &str) -> Option<BranchInfo> {
+    branches
+        .into_iter()
+        .find(|b| !b.is_remote && b.name == name)
+}
+
+/// Delete a local branch, refusing when it has commits that are neither in HEAD
+/// nor in its upstream unless `options.force` is set.
+///
+/// `unmerged_commits(upstream)` lists the commits that would be lost and
+/// `delete_local` removes the branch ref; both are supplied by the backend.
+pub(crate) fn delete_branch(
+    backend: &dyn GitBackend,
+    name: &str,
+    options: &DeleteBranchOptions,
+    control: &TransferControl,
+    unmerged_commits: impl FnOnce(Option<&str>) -> GitResult<Vec<CommitInfo>>,
+    delete_local: impl FnOnce() -> GitResult<()>,
+) -> GitResult<DeleteBranchResult> {
+    let result = delete_upstream(backend, name, options, control, unmerged_commits)?;
+    if !options.force && !result.unmerged_commits.is_empty() {
+        return Ok(result);
+    }
+    delete_local()?;
+    Ok(DeleteBranchResult {
+        deleted: true,
+        ..result
+    })
+}
+
+/// The remote half of [`delete_branch`]: check the branch can be deleted and
+/// delete its upstream when `options.delete_remote` is set. The local branch
+/// is left alone, so `deleted` is always `false`.
+pub(crate) fn delete_upstream(
+    backend: &dyn GitBackend,
+    name: &str,
+    options: &DeleteBranchOptions,
+    control: &TransferControl,
+    unmerged_commits: impl FnOnce(Option<&str>) -> GitResult<Vec<CommitInfo>>,
+) -> GitResult<DeleteBranchResult> {
+    let branch = find_local(backend.list_branches()?, name)
+        .ok_or_else(|| GitError::BranchDeleteFailed(format!("branch not found: {name}").into()))?;
+    if branch.is_head {
+        return Err(GitError::BranchDeleteFailed(
+            format!("cannot delete the checked out branch '{name}'").into(),
+        ));
+    }
+
+    let unmerged = unmerged_commits(branch.upstream.as_deref())?;
+    if !options.force && !unmerged.is_empty() {
+        return Ok(DeleteBranchResult {
+            branch: branch.name,
+            deleted: false,
+            remote_deleted: false,
+            unmerged_commits: unmerged,
+        });
+    }
+
+    // リモートを先に消す。失敗したらローカルは残す
+    let mut remote_deleted = false;
+    if let (true, Some(upstream)) = (options.delete_remote, branch.upstream.as_deref()) {
+        let remotes: Vec<String> = backend
+            .list_remotes()?
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        if let Some((remote, remote_branch)) = split_remote_branch(upstream, &remotes) {
+            let push_options = PushOptions {
+                delete_branch: Some(remote_branch.to_string()),
+                ..PushOptions::default()
+            };
+            let result = backend.push(remote, &push_options, control)?;
+            if let Some(update) = result.updates.iter().find(|u| u.status.is_rejected()) {
+                let reason = update.message.as_deref().unwrap_or("rejected");
+                return Err(GitError::BranchDeleteFailed(
+                    format!("failed to delete {upstream}: {reason}").into(),
+                ));
+            }
+            remote_deleted = true;
+        }
+    }
+
+    Ok(DeleteBranchResult {
+        branch: branch.name,
+        deleted: false,
+        remote_deleted,
+        unmerged_commits: unmerged,
+    })
+}
+
+/// Branch that prune candidates are compared against when no base is given:
+/// `main`, then `master`, then the checked out branch.
+pub(crate) fn default_base(branches: &[BranchInfo]) -> Option<String> {
+    let local = |name: &str| branches.iter().any(|b| !b.is_remote && b.name == name);
+    ["main", "master"]
+        .into_iter()
+        .find(|name| local(name))
+        .map(str::to_string)
+        .or_else(|| {
+            branches
+                .iter()
+                .find(|b| b.is_head && !b.is_remote)
+                .map(|b| b.name.clone())
+        })
+}
+
+/// Local branches that can be cleaned up: fully merged into `base` or whose
+/// upstream was deleted on the remote. `base` and the checked out branch are
+/// never listed. `is_merged(branch, base)` is supplied by the backend.
+pub(crate) fn prune_candidates(
+    backend: &dyn GitBackend,
+    base: Option<&str>,
+    is_merged: impl Fn(&str, &str) -> bool,
+) -> GitResult<Vec<PruneCandidate>> {
+    let branches = backend.list_branches()?;
+    let base = match base {
+        Some(base) => base.to_string(),
+        None => default_base(&branches)
+            .ok_or_else(|| GitError::BranchListFailed("no base branch".into()))?,
+    };
+
+    Ok(branches
+        .into_iter()
+        .filter(|b| !b.is_remote && !b.is_head && b.name != base)
+        .filter_map(|b| {
+            let merged = is_merged(&b.name, &base);
+            let upstream_gone = b.upstream_gone;
+            (merged || upstream_gone).then_some(PruneCandidate {
+                name: b.name,
+                merged,
+                upstream_gone,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_remote_branch_prefers_longest_remote() {
+        let remotes = vec!["origin".to_string(), "origin/mirror".to_string()];
+        assert_eq!(
+            split_remote_branch("origin/feature/x", &remotes),
+            Some(("origin", "feature/x"))
+        );
+        assert_eq!(
+            split_remote_branch("refs/remotes/origin/mirror/main", &remotes),
+            Some(("origin/mirror", "main"))
+        );
+        assert_eq!(split_remote_branch("upstream/main", &remotes), None);
+        assert_eq!(split_remote_branch("origin", &remotes), None);
+    }
+
+    fn local(name: &str, is_head: bool) -> BranchInfo {
+        BranchInfo {
+            name: name.to_string(),
+            is_head,
+            is_remote: false,
+            remote_name: None,
+            upstream: None,
+            upstream_gone: false,
+            ahead_count: 0,
+            behind_count: 0,
+        }
+    }
+
+    #[test]
+    fn default_base_prefers_main_then_master_then_head() {
+        let branches = vec![local("master", false), local("topic", true)];
+        assert_eq!(default_base(&branches).as_deref(), Some("master"));
+
+        let branches = vec![local("main", false), local("master", false)];
+        assert_eq!(default_base(&branches).as_deref(), Some("main"));
+
+        let branches = vec![local("develop", true)];
+        assert_eq!(default_base(&branches).as_deref(), Some("develop"));
+    }
+}
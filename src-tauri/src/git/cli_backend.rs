@@ -0,0 +1,3299 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::Mutex;
+
+use crate::git::backend::GitBackend;
+use crate::git::blame;
+use crate::git::branch;
+use crate::git::error::{GitError, GitResult};
+use crate::git::fetch;
+use crate::git::git2_backend::{
+    compute_word_diffs, generate_hunk_patch, generate_line_patch, parse_conflict_markers,
+    parse_reflog_message, parse_stash_branch_name, resolve_single_block, run_git_apply,
+    verify_commit_signatures,
+};
+use crate::git::graph::{self, LogCache, WalkChunk};
+use crate::git::hooks::{self, CommitSource, PushUpdate};
+use crate::git::journal::{self, JournalRepo, SnapshotPlan};
+use crate::git::push;
+use crate::git::rebase::{self, collect_conflict_paths_from_workdir, RebaseControl};
+use crate::git::release_notes;
+use crate::git::search::{self, CodeSearchResult, CommitSearchResult, FilenameSearchResult};
+use crate::git::stack;
+use crate::git::stash;
+use crate::git::submodule;
+use crate::git::tag;
+use crate::git::trailers;
+use crate::git::transfer::{self, TransferControl};
+use crate::git::types::{
+    BlameOptions, BlameResult, BranchInfo, CherryPickMode, CherryPickResult, CommitDetail,
+    CommitEdit, CommitFileChange, CommitFileStatus, CommitIdentity, CommitInfo, CommitLogResult,
+    CommitRef, CommitRefKind, CommitResult, CommitStats, ConflictFile, ConflictResolution,
+    CreateTagOptions, DeleteBranchOptions, DeleteBranchResult, DiffHunk, DiffLine, DiffLineKind,
+    DiffOptions, FetchOptions, FetchResult, FetchTagMode, FileDiff, FileHistoryEntry, FileStatus,
+    FileStatusKind, GitConfigEntry, GitConfigScope, HunkIdentifier, LineRange, LogFilter,
+    MergeBaseContent, MergeKind, MergeOption, MergeResult, OperationEntry, OperationKind,
+    PruneCandidate, PullOption, PushOptions, PushRefStatus, PushRefUpdate, PushResult,
+    RebaseAction, RebaseOptions, RebaseResult, RebaseState, RebaseTodoEntry, ReflogEntry,
+    ReleaseNotes, ReleaseNotesOptions, RemoteInfo, RepoStatus, ResetMode, ResetResult, RevertMode,
+    RevertResult, SignatureStatus, StagingState, StashApplyResult, StashDetail, StashEntry,
+    StashOptions, StashState, SubmoduleInfo, TagInfo, WorktreeInfo,
+};
+use crate::git::worktree;
+
+type CmdResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+type RefMap = HashMap<String, Vec<CommitRef>>;
+
+/// Extra `git commit` arguments and environment variables.
+type IdentityOverrides = (Vec<String>, Vec<(&'static str, String)>);
+
+/// `git log -z` 用のフォーマット。フィールドは US (0x1f) 区切り、メッセージは最後に置く。
+const LOG_FORMAT: &str = "--format=%H%x1f%P%x1f%an%x1f%ae%x1f%at%x1f%ct%x1f%B";
+
+/// コミットログを読むとき 1 回の `git log` に渡すコミット数の上限。
+const LOG_BATCH: usize = 256;
+
+const NULL_OID: &str = "0000000000000000000000000000000000000000";
+
+/// Passed with `-c` to commands that create commits. `hooks` runs the hooks
+/// for them so that both backends run the same ones and report failures alike.
+const NO_HOOKS: &str = "core.hooksPath=/dev/null";
+
+/// Backend that drives the system `git` binary for every operation.
+///
+/// Useful for repositories that rely on features libgit2 does not support
+/// (partial clones, sparse checkout, custom merge drivers, fsmonitor, ...).
+pub struct CliBackend {
+    workdir: PathBuf,
+    git_dir: PathBuf,
+    log_cache: Mutex<Option<LogCache>>,
+}
+
+impl CliBackend {
+    pub fn open(path: impl AsRef<Path>) -> GitResult<Self> {
+        let path = path.as_ref();
+        let not_found = || GitError::RepositoryNotFound {
+            path: path.display().to_string(),
+        };
+
+        if !path.is_dir() {
+            return Err(not_found());
+        }
+
+        let output = Command::new("git")
+            .current_dir(path)
+            .env("LC_ALL", "C")
+            .args(["rev-parse", "--is-bare-repository", "--absolute-git-dir"])
+            .output()
+            .map_err(|e| GitError::OpenFailed(Box::new(e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("not a git repository") {
+                return Err(not_found());
+            }
+            return Err(GitError::OpenFailed(stderr.trim().to_string().into()));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines();
+        if lines.next() == Some("true") {
+            return Err(GitError::OpenFailed("bare repository".into()));
+        }
+        let git_dir = PathBuf::from(lines.next().unwrap_or_default());
+
+        let output = Command::new("git")
+            .current_dir(path)
+            .args(["rev-parse", "--show-toplevel"])
+            .output()
+            .map_err(|e| GitError::OpenFailed(Box::new(e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitError::OpenFailed(stderr.trim().to_string().into()));
+        }
+
+        let workdir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+
+        Ok(Self {
+            workdir,
+            git_dir,
+            log_cache: Mutex::new(None),
+        })
+    }
+
+    fn command(&self) -> Command {
+        let mut cmd = Command::new("git");
+        cmd.current_dir(&self.workdir)
+            .args(["-c", "core.quotePath=false", "-c", "color.ui=false"])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("LC_ALL", "C");
+        cmd
+    }
+
+    fn output(&self, args: &[&str], input: Option<&str>) -> CmdResult<Output> {
+        self.output_with_env(args, input, &[])
+    }
+
+    fn output_with_env(
+        &self,
+        args: &[&str],
+        input: Option<&str>,
+        env: &[(&str, String)],
+    ) -> CmdResult<Output> {
+        let mut cmd = self.command();
+        cmd.args(args);
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+
+        let Some(input) = input else {
+            return Ok(cmd.stdin(Stdio::null()).output()?);
+        };
+
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input.as_bytes())?;
+        }
+        Ok(child.wait_with_output()?)
+    }
+
+    fn run(&self, args: &[&str]) -> CmdResult<String> {
+        self.run_with_input(args, None)
+    }
+
+    fn run_with_input(&self, args: &[&str], input: Option<&str>) -> CmdResult<String> {
+        let output = self.output(args, input)?;
+        if !output.status.success() {
+            return Err(command_error(&output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Run a network command with progress reporting. `args` must include
+    /// `--progress` so that git writes progress to the (non-tty) stderr.
+    fn run_transfer(
+        &self,
+        args: &[&str],
+        control: &TransferControl,
+        wrap: fn(Box<dyn std::error::Error + Send + Sync>) -> GitError,
+    ) -> GitResult<Output> {
+        let output = self.transfer_output(args, control, wrap)?;
+        if !output.status.success() {
+            return Err(wrap(command_error(&output)));
+        }
+        Ok(output)
+    }
+
+    /// Like `run_transfer` but hands back the output of a failed command too.
+    fn transfer_output(
+        &self,
+        args: &[&str],
+        control: &TransferControl,
+        wrap: fn(Box<dyn std::error::Error + Send + Sync>) -> GitError,
+    ) -> GitResult<Output> {
+        let mut cmd = self.command();
+        cmd.args(args);
+        match transfer::run_with_progress(&mut cmd, control) {
+            Ok(Some(output)) => Ok(output),
+            Ok(None) => Err(GitError::Cancelled),
+            Err(e) => Err(wrap(Box::new(e))),
+        }
+    }
+
+    fn succeeds(&self, args: &[&str]) -> bool {
+        self.output(args, None)
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    fn resolve_commit(&self, rev: &str) -> Option<String> {
+        let spec = format!("{rev}^{{commit}}");
+        self.run(&["rev-parse", "--verify", "-q", &spec])
+            .ok()
+            .map(|s| s.trim().to_string())
+    }
+
+    fn head_oid(&self) -> Option<String> {
+        self.resolve_commit("HEAD")
+    }
+
+    fn head_branch(&self) -> Option<String> {
+        self.run(&["symbolic-ref", "--short", "-q", "HEAD"])
+            .ok()
+            .map(|s| s.trim().to_string())
+    }
+
+    fn ref_exists(&self, full_name: &str) -> bool {
+        self.succeeds(&["show-ref", "--verify", "-q", full_name])
+    }
+
+    /// Commits on `name` that are neither in HEAD nor in `upstream`.
+    fn unmerged_commits(&self, name: &str, upstream: Option<&str>) -> GitResult<Vec<CommitInfo>> {
+        let branch_ref = format!("refs/heads/{name}");
+        let mut args = vec!["--date-order".to_string(), branch_ref];
+        if self.head_oid().is_some() {
+            args.push("^HEAD".to_string());
+        }
+        if let Some(upstream) = upstream {
+            args.push(format!("^refs/remotes/{upstream}"));
+        }
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.collect_log(&args, usize::MAX)
+            .map_err(GitError::BranchDeleteFailed)
+    }
+
+    fn is_ancestor(&self, ancestor: &str, descendant: &str) -> bool {
+        self.succeeds(&["merge-base", "--is-ancestor", ancestor, descendant])
+    }
+
+    fn ref_snapshot(&self) -> CmdResult<fetch::RefSnapshot> {
+        let output = self.run(&[
+            "for-each-ref",
+            "--format=%(objectname) %(symref) %(refname)",
+        ])?;
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let (oid, rest) = line.split_once(' ')?;
+                // シンボリック参照 (refs/remotes/origin/HEAD など) は除外する
+                let name = rest.strip_prefix(' ')?;
+                Some((name.to_string(), oid.to_string()))
+            })
+            .collect())
+    }
+
+    fn count_new_commits(&self, tips: &[&str], hide: &[&str]) -> u32 {
+        let mut input = String::new();
+        for tip in tips {
+            input.push_str(tip);
+            input.push('\n');
+        }
+        for oid in hide {
+            input.push('^');
+            input.push_str(oid);
+            input.push('\n');
+        }
+        self.run_with_input(&["rev-list", "--count", "--stdin"], Some(&input))
+            .ok()
+            .and_then(|out| out.trim().parse().ok())
+            .unwrap_or(0)
+    }
+
+    fn conflict_paths(&self) -> Vec<String> {
+        collect_conflict_paths_from_workdir(&self.workdir)
+    }
+
+    /// `git stash apply|pop`, reporting conflicts instead of failing. A
+    /// conflicted pop keeps the stash.
+    fn apply_stash(
+        &self,
+        verb: &str,
+        index: usize,
+        restore_index: bool,
+    ) -> GitResult<StashApplyResult> {
+        let stash = format!("stash@{{{index}}}");
+        let mut args = vec!["stash", verb, "-q"];
+        if restore_index {
+            args.push("--index");
+        }
+        args.push(&stash);
+        let output = self.output(&args, None).map_err(GitError::StashFailed)?;
+        if output.status.success() {
+            return Ok(StashApplyResult {
+                completed: true,
+                conflicts: Vec::new(),
+            });
+        }
+        let conflicts = self.conflict_paths();
+        if conflicts.is_empty() {
+            return Err(GitError::StashFailed(command_error(&output)));
+        }
+        Ok(StashApplyResult {
+            completed: false,
+            conflicts,
+        })
+    }
+
+    fn stash_tree_diff(&self, from: &str, to: &str) -> GitResult<Vec<FileDiff>> {
+        let output = self
+            .run(&[
+                "diff",
+                "--no-color",
+                "--no-ext-diff",
+                "--no-renames",
+                "--src-prefix=a/",
+                "--dst-prefix=b/",
+                from,
+                to,
+            ])
+            .map_err(GitError::StashFailed)?;
+
+        let mut file_diffs = parse_unified_diff(&output);
+        compute_word_diffs(&mut file_diffs);
+        Ok(file_diffs)
+    }
+
+    /// libgit2 の `Repository::state()` と同じ優先順で進行中の操作を判定する。
+    fn in_progress_marker(&self) -> Option<&'static str> {
+        if self.git_dir.join("rebase-merge").is_dir() || self.git_dir.join("rebase-apply").is_dir()
+        {
+            return Some("rebase");
+        }
+        ["MERGE_HEAD", "REVERT_HEAD", "CHERRY_PICK_HEAD"]
+            .into_iter()
+            .find(|marker| self.git_dir.join(marker).exists())
+    }
+
+    fn stage_blob_content(&self, path: &str, stage: u8) -> GitResult<String> {
+        let spec = format!(":{stage}:{path}");
+        let output = self
+            .output(&["cat-file", "blob", &spec], None)
+            .map_err(GitError::ConflictFailed)?;
+        if !output.status.success() {
+            return Err(GitError::ConflictFailed(command_error(&output)));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn build_ref_map(&self) -> RefMap {
+        let mut map: RefMap = HashMap::new();
+
+        let output = self
+            .run(&[
+                "for-each-ref",
+                "--format=%(objectname)%1f%(*objectname)%1f%(refname)%1f%(symref)",
+                "refs/heads",
+                "refs/remotes",
+                "refs/tags",
+            ])
+            .unwrap_or_default();
+
+        for line in output.lines() {
+            let fields: Vec<&str> = line.split('\x1f').collect();
+            let [oid, peeled, refname, symref] = fields[..] else {
+                continue;
+            };
+            if !symref.is_empty() {
+                continue;
+            }
+
+            let (name, kind, target) = if let Some(name) = refname.strip_prefix("refs/heads/") {
+                (name, CommitRefKind::LocalBranch, oid)
+            } else if let Some(name) = refname.strip_prefix("refs/remotes/") {
+                (name, CommitRefKind::RemoteBranch, oid)
+            } else if let Some(name) = refname.strip_prefix("refs/tags/") {
+                let target = if peeled.is_empty() { oid } else { peeled };
+                (name, CommitRefKind::Tag, target)
+            } else {
+                continue;
+            };
+
+            map.entry(target.to_string()).or_default().push(CommitRef {
+                name: name.to_string(),
+                kind,
+            });
+        }
+
+        if let Some(head_oid) = self.head_oid() {
+            map.entry(head_oid).or_default().insert(
+                0,
+                CommitRef {
+                    name: "HEAD".to_string(),
+                    kind: CommitRefKind::Head,
+                },
+            );
+        }
+
+        map
+    }
+
+    /// `git log -z` の出力をコミット単位でストリームし、`visit` が `false` を返した時点で打ち切る。
+    fn walk_log<F>(&self, args: &[&str], mut visit: F) -> CmdResult<()>
+    where
+        F: FnMut(LogRecord) -> bool,
+    {
+        let mut child = self
+            .command()
+            .args(["log", "-z", LOG_FORMAT])
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or("failed to capture git log output")?;
+
+        for record in BufReader::new(stdout).split(b'\0') {
+            let record = record?;
+            let Some(record) = parse_log_record(&String::from_utf8_lossy(&record)) else {
+                continue;
+            };
+            if !visit(record) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(());
+            }
+        }
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(command_error(&output));
+        }
+        Ok(())
+    }
+
+    /// The commits of `tip`, excluding those of `hidden`, in log order. A
+    /// path filter is left to git, which simplifies the history by it.
+    fn log_order(
+        &self,
+        tip: &str,
+        hidden: Option<&str>,
+        filter: &LogFilter,
+    ) -> GitResult<Vec<String>> {
+        let hidden = hidden.map(|oid| format!("^{oid}"));
+        let mut args = vec!["rev-list", "--date-order", tip];
+        args.extend(hidden.as_deref());
+        if let Some(path) = &filter.path {
+            args.extend(["--full-history", "--", path.as_str()]);
+        }
+        Ok(self
+            .run(&args)
+            .map_err(GitError::LogFailed)?
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Read the commits `oids` in order until `wanted` of them match the
+    /// filter, a batch of commits per `git log`.
+    fn walk_commits(
+        &self,
+        oids: &[String],
+        filter: &LogFilter,
+        wanted: usize,
+    ) -> GitResult<WalkChunk> {
+        let no_refs = RefMap::new();
+        let mut chunk = WalkChunk::default();
+        for batch in oids.chunks(LOG_BATCH) {
+            if chunk.commits.len() >= wanted {
+                break;
+            }
+            let mut args = vec!["--no-walk=unsorted"];
+            args.extend(batch.iter().map(String::as_str));
+            self.walk_log(&args, |record| {
+                chunk.visit(record.commit_time);
+                if record.matches_filter(filter) {
+                    chunk.commits.push(record.into_info(&no_refs));
+                }
+                chunk.commits.len() < wanted
+            })
+            .map_err(GitError::LogFailed)?;
+        }
+        Ok(chunk)
+    }
+
+    fn collect_log(&self, args: &[&str], limit: usize) -> CmdResult<Vec<CommitInfo>> {
+        let ref_map = self.build_ref_map();
+        let mut commits = Vec::new();
+        if limit == 0 {
+            return Ok(commits);
+        }
+        self.walk_log(args, |record| {
+            commits.push(record.into_info(&ref_map));
+            commits.len() < limit
+        })?;
+        Ok(commits)
+    }
+
+    fn read_commit(&self, oid: &str) -> Option<LogRecord> {
+        let resolved = self.resolve_commit(oid)?;
+        let mut found = None;
+        self.walk_log(&["-1", &resolved], |record| {
+            found = Some(record);
+            false
+        })
+        .ok()?;
+        found
+    }
+
+    fn diff_commit_args<'a>(&self, record: &'a LogRecord) -> Vec<&'a str> {
+        match record.parent_oids.first() {
+            Some(parent) => vec![parent.as_str(), record.oid.as_str()],
+            None => vec!["--root", record.oid.as_str()],
+        }
+    }
+
+    /// 競合していなければ `Ok(None)`、競合していれば競合パスを返す。
+    fn conflicts_after(&self, output: &Output) -> CmdResult<Option<Vec<String>>> {
+        if output.status.success() {
+            return Ok(None);
+        }
+        let conflicts = self.conflict_paths();
+        if conflicts.is_empty() {
+            return Err(command_error(output));
+        }
+        Ok(Some(conflicts))
+    }
+
+    fn merge_ref(
+        &self,
+        target_ref: &str,
+        display_name: &str,
+        option: MergeOption,
+        wrap: fn(Box<dyn std::error::Error + Send + Sync>) -> GitError,
+    ) -> GitResult<MergeResult> {
+        let target_oid = self
+            .resolve_commit(target_ref)
+            .ok_or_else(|| wrap(format!("reference not found: {target_ref}").into()))?;
+
+        if self.head_oid().is_some() && self.is_ancestor(target_ref, "HEAD") {
+            return Ok(MergeResult {
+                kind: MergeKind::UpToDate,
+                oid: None,
+                conflicts: vec![],
+            });
+        }
+
+        let fast_forward = self.head_oid().is_none() || self.is_ancestor("HEAD", target_ref);
+        if fast_forward && option != MergeOption::NoFastForward {
+            self.run(&["-c", NO_HOOKS, "merge", "-q", "--ff-only", target_ref])
+                .map_err(wrap)?;
+            hooks::post_merge(&self.workdir);
+            return Ok(MergeResult {
+                kind: MergeKind::FastForward,
+                oid: Some(target_oid),
+                conflicts: vec![],
+            });
+        }
+
+        if !fast_forward && option == MergeOption::FastForwardOnly {
+            return Err(wrap("fast-forward not possible".into()));
+        }
+
+        let message = format!("Merge branch '{display_name}'");
+        let output = self
+            .output(
+                &[
+                    "-c",
+                    NO_HOOKS,
+                    "merge",
+                    "-q",
+                    "--no-ff",
+                    "--no-commit",
+                    "-m",
+                    &message,
+                    target_ref,
+                ],
+                None,
+            )
+            .map_err(wrap)?;
+
+        if let Some(conflicts) = self.conflicts_after(&output).map_err(wrap)? {
+            return Ok(MergeResult {
+                kind: MergeKind::Conflict,
+                oid: None,
+                conflicts,
+            });
+        }
+
+        // フックに拒否された場合は git と同様にマージ中の状態を残す
+        let message = hooks::prepare_commit(&self.workdir, &message, CommitSource::Merge, true)?;
+        self.run_with_input(
+            &[
+                "-c",
+                NO_HOOKS,
+                "commit",
+                "-q",
+                "--cleanup=verbatim",
+                "-F",
+                "-",
+            ],
+            Some(&message),
+        )
+        .map_err(wrap)?;
+        hooks::post_merge(&self.workdir);
+
+        Ok(MergeResult {
+            kind: MergeKind::Normal,
+            oid: self.head_oid(),
+            conflicts: vec![],
+        })
+    }
+
+    /// Commit the cherry-pick or revert in progress with the message git
+    /// prepared in `MERGE_MSG`.
+    fn commit_in_progress(
+        &self,
+        source: CommitSource,
+        wrap: fn(Box<dyn std::error::Error + Send + Sync>) -> GitError,
+    ) -> GitResult<String> {
+        if !self.conflict_paths().is_empty() {
+            return Err(wrap("unresolved conflicts remain".into()));
+        }
+        let prepared = std::fs::read_to_string(self.git_dir.join("MERGE_MSG")).unwrap_or_default();
+        let message = hooks::prepare_commit(&self.workdir, &prepared, source, true)?;
+        self.run_with_input(
+            &[
+                "-c",
+                NO_HOOKS,
+                "commit",
+                "-q",
+                "--allow-empty",
+                "--cleanup=strip",
+                "-F",
+                "-",
+            ],
+            Some(&message),
+        )
+        .map_err(wrap)?;
+        hooks::post_commit(&self.workdir);
+        self.head_oid()
+            .ok_or_else(|| wrap("HEAD not found after commit".into()))
+    }
+
+    fn abort_in_progress(&self, quit_command: &str) -> CmdResult<()> {
+        // sequencer の状態を破棄してから HEAD の内容に戻す
+        let _ = self.output(&[quit_command, "--quit"], None);
+        self.run(&["reset", "-q", "--hard", "HEAD"])?;
+        Ok(())
+    }
+
+    fn config_scope_flag(scope: GitConfigScope) -> &'static str {
+        match scope {
+            GitConfigScope::Local => "--local",
+            GitConfigScope::Global => "--global",
+        }
+    }
+
+    /// `git var GIT_AUTHOR_IDENT` and the like: name, email, time and UTC
+    /// offset in minutes.
+    fn ident(&self, var: &str) -> CmdResult<(String, String, i64, i32)> {
+        let output = self.run(&["var", var])?;
+        let mut parts = output.trim().rsplitn(3, ' ');
+        let (Some(tz), Some(time), Some(person)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!("unexpected {var}: {output}").into());
+        };
+        let identity = trailers::parse_identity(person)
+            .ok_or_else(|| format!("unexpected {var}: {output}"))?;
+        let time = time.parse()?;
+        let sign = if tz.starts_with('-') { -1 } else { 1 };
+        let digits = tz.trim_start_matches(['+', '-']);
+        let hours: i32 = digits.get(..2).unwrap_or("0").parse()?;
+        let minutes: i32 = digits.get(2..).unwrap_or("0").parse()?;
+        Ok((
+            identity.name,
+            identity.email,
+            time,
+            sign * (hours * 60 + minutes),
+        ))
+    }
+
+    /// `--author`/`--date` for an author override and `GIT_COMMITTER_*` for a
+    /// committer override. `--amend` ignores `GIT_AUTHOR_*`, so the author
+    /// goes on the command line with the unset fields filled in from git.
+    fn identity_overrides(&self, identity: &CommitIdentity) -> CmdResult<IdentityOverrides> {
+        let mut args = Vec::new();
+        if let Some(author) = &identity.author {
+            let (name, email, time, offset) = self.ident("GIT_AUTHOR_IDENT")?;
+            args.push(format!(
+                "--author={} <{}>",
+                author.name.as_deref().unwrap_or(&name),
+                author.email.as_deref().unwrap_or(&email)
+            ));
+            args.push(format!(
+                "--date={}",
+                git_date(
+                    author.time.unwrap_or(time),
+                    author.offset_minutes.unwrap_or(offset)
+                )
+            ));
+        }
+
+        let mut env = Vec::new();
+        if let Some(committer) = &identity.committer {
+            if let Some(name) = &committer.name {
+                env.push(("GIT_COMMITTER_NAME", name.clone()));
+            }
+            if let Some(email) = &committer.email {
+                env.push(("GIT_COMMITTER_EMAIL", email.clone()));
+            }
+            if let Some(time) = committer.time {
+                let offset = match committer.offset_minutes {
+                    Some(offset) => offset,
+                    None => self.ident("GIT_COMMITTER_IDENT")?.3,
+                };
+                env.push(("GIT_COMMITTER_DATE", git_date(time, offset)));
+            }
+        }
+        Ok((args, env))
+    }
+
+    fn write_commit(
+        &self,
+        message: &str,
+        amend: bool,
+        sign: bool,
+        identity: &CommitIdentity,
+    ) -> GitResult<CommitResult> {
+        let (identity_args, env) = self
+            .identity_overrides(identity)
+            .map_err(GitError::CommitFailed)?;
+        let mut args = vec![
+            "-c",
+            NO_HOOKS,
+            "commit",
+            "-q",
+            "--allow-empty",
+            "--allow-empty-message",
+            "--cleanup=verbatim",
+            "-F",
+            "-",
+        ];
+        if amend {
+            args.push("--amend");
+        }
+        args.push(if sign { "-S" } else { "--no-gpg-sign" });
+        args.extend(identity_args.iter().map(String::as_str));
+
+        let to_error = |e: Box<dyn std::error::Error + Send + Sync>| {
+            if sign {
+                GitError::SigningFailed(e)
+            } else if amend {
+                GitError::AmendFailed(e)
+            } else {
+                GitError::CommitFailed(e)
+            }
+        };
+
+        let output = self
+            .output_with_env(&args, Some(message), &env)
+            .map_err(to_error)?;
+        if !output.status.success() {
+            return Err(to_error(command_error(&output)));
+        }
+
+        let oid = self
+            .head_oid()
+            .ok_or_else(|| GitError::CommitFailed("HEAD not found after commit".into()))?;
+        Ok(CommitResult { oid })
+    }
+}
+
+impl GitBackend for CliBackend {
+    fn workdir(&self) -> &Path {
+        &self.workdir
+    }
+
+    fn status(&self) -> GitResult<RepoStatus> {
+        let output = self
+            .run(&[
+                "status",
+                "--porcelain=v2",
+                "-z",
+                "--untracked-files=all",
+                "--no-renames",
+            ])
+            .map_err(GitError::StatusFailed)?;
+
+        let mut files = parse_porcelain_status(&output);
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(RepoStatus { files })
+    }
+
+    fn diff(&self, path: Option<&Path>, options: &DiffOptions) -> GitResult<Vec<FileDiff>> {
+        let context = format!("-U{}", options.context_lines);
+        let mut args = vec![
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-renames",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            context.as_str(),
+        ];
+        if options.staged {
+            args.push("--cached");
+        }
+        args.push("--");
+        let path_str = path.map(|p| p.to_string_lossy().into_owned());
+        if let Some(p) = &path_str {
+            args.push(p);
+        }
+
+        let output = self.run(&args).map_err(GitError::DiffFailed)?;
+        let mut file_diffs = parse_unified_diff(&output);
+        compute_word_diffs(&mut file_diffs);
+
+        Ok(file_diffs)
+    }
+
+    fn stage(&self, path: &Path) -> GitResult<()> {
+        let path = path.to_string_lossy();
+        self.run(&["add", "-A", "--", &path])
+            .map_err(GitError::StageFailed)?;
+        Ok(())
+    }
+
+    fn unstage(&self, path: &Path) -> GitResult<()> {
+        let path = path.to_string_lossy();
+        if self.head_oid().is_some() {
+            self.run(&["reset", "-q", "HEAD", "--", &path])
+                .map_err(GitError::UnstageFailed)?;
+        } else {
+            // No HEAD (initial commit): remove the path from the index
+            self.run(&["rm", "-q", "--cached", "--", &path])
+                .map_err(GitError::UnstageFailed)?;
+        }
+        Ok(())
+    }
+
+    fn stage_all(&self) -> GitResult<()> {
+        self.run(&["add", "-A"]).map_err(GitError::StageFailed)?;
+        Ok(())
+    }
+
+    fn unstage_all(&self) -> GitResult<()> {
+        if self.head_oid().is_some() {
+            self.run(&["reset", "-q"])
+                .map_err(GitError::UnstageFailed)?;
+        } else {
+            // No HEAD (initial commit): clear the entire index
+            self.run(&["read-tree", "--empty"])
+                .map_err(GitError::UnstageFailed)?;
+        }
+        Ok(())
+    }
+
+    fn current_branch(&self) -> GitResult<String> {
+        if self.head_oid().is_none() {
+            return Err(GitError::BranchNotFound(
+                "HEAD does not point to a commit".into(),
+            ));
+        }
+        Ok(self.head_branch().unwrap_or_else(|| "HEAD".to_string()))
+    }
+
+    fn commit(
+        &self,
+        message: &str,
+        amend: bool,
+        sign: bool,
+        skip_hooks: bool,
+    ) -> GitResult<CommitResult> {
+        self.commit_as(message, amend, sign, skip_hooks, &CommitIdentity::default())
+    }
+
+    fn commit_as(
+        &self,
+        message: &str,
+        amend: bool,
+        sign: bool,
+        skip_hooks: bool,
+        identity: &CommitIdentity,
+    ) -> GitResult<CommitResult> {
+        let source = if amend {
+            CommitSource::Amend
+        } else {
+            CommitSource::Message
+        };
+        let message = hooks::prepare_linted_commit(&self.workdir, message, source, !skip_hooks)?;
+        let result = if amend {
+            let plan = SnapshotPlan {
+                refs: vec!["HEAD".to_string()],
+                index: true,
+                ..SnapshotPlan::default()
+            };
+            journal::journaled(self, OperationKind::Amend, "Amend commit", plan, || {
+                self.write_commit(&message, true, sign, identity)
+            })?
+        } else {
+            self.write_commit(&message, false, sign, identity)?
+        };
+        hooks::post_commit(&self.workdir);
+        Ok(result)
+    }
+
+    fn list_branches(&self) -> GitResult<Vec<BranchInfo>> {
+        let output = self
+            .run(&[
+                "for-each-ref",
+                "--format=%(refname)%1f%(HEAD)%1f%(upstream:short)%1f%(upstream:track,nobracket)",
+                "refs/heads",
+                "refs/remotes",
+            ])
+            .map_err(GitError::BranchListFailed)?;
+
+        let mut result = Vec::new();
+        for line in output.lines() {
+            let fields: Vec<&str> = line.split('\x1f').collect();
+            let [refname, head, upstream, track] = fields[..] else {
+                continue;
+            };
+
+            let (name, is_remote) = if let Some(name) = refname.strip_prefix("refs/heads/") {
+                (name.to_string(), false)
+            } else if let Some(name) = refname.strip_prefix("refs/remotes/") {
+                (name.to_string(), true)
+            } else {
+                continue;
+            };
+
+            let remote_name = if is_remote {
+                name.split('/').next().map(|s| s.to_string())
+            } else {
+                None
+            };
+
+            let upstream_gone = !upstream.is_empty() && track == "gone";
+            let upstream = (!upstream.is_empty() && !upstream_gone).then(|| upstream.to_string());
+            let (ahead_count, behind_count) = parse_upstream_track(track);
+
+            result.push(BranchInfo {
+                name,
+                is_head: head == "*",
+                is_remote,
+                remote_name,
+                upstream,
+                upstream_gone,
+                ahead_count,
+                behind_count,
+            });
+        }
+
+        Ok(result)
+    }
+
+    fn create_branch(&self, name: &str) -> GitResult<()> {
+        self.run(&["branch", "--no-track", name, "HEAD"])
+            .map_err(GitError::BranchCreateFailed)?;
+        Ok(())
+    }
+
+    fn checkout_branch(&self, name: &str) -> GitResult<()> {
+        if !self.ref_exists(&format!("refs/heads/{name}")) {
+            return Err(GitError::CheckoutFailed(
+                format!("branch not found: {name}").into(),
+            ));
+        }
+        self.run(&["checkout", "-q", "-f", "--no-guess", name, "--"])
+            .map_err(GitError::CheckoutFailed)?;
+        Ok(())
+    }
+
+    fn delete_branch(
+        &self,
+        name: &str,
+        options: &DeleteBranchOptions,
+        control: &TransferControl,
+    ) -> GitResult<DeleteBranchResult> {
+        let unmerged_commits = |upstream: Option<&str>| self.unmerged_commits(name, upstream);
+        let delete_local = || {
+            let plan = SnapshotPlan {
+                refs: vec![format!("refs/heads/{name}")],
+                ..SnapshotPlan::default()
+            };
+            let description = format!("Delete branch {name}");
+            journal::journaled(self, OperationKind::DeleteBranch, description, plan, || {
+                self.run(&["branch", "-D", name])
+                    .map_err(GitError::BranchDeleteFailed)?;
+                Ok(())
+            })
+        };
+        branch::delete_branch(self, name, options, control, unmerged_commits, delete_local)
+    }
+
+    fn delete_upstream_branch(
+        &self,
+        name: &str,
+        options: &DeleteBranchOptions,
+        control: &TransferControl,
+    ) -> GitResult<DeleteBranchResult> {
+        branch::delete_upstream(self, name, options, control, |upstream| {
+            self.unmerged_commits(name, upstream)
+        })
+    }
+
+    fn list_prune_candidates(&self, base: Option<&str>) -> GitResult<Vec<PruneCandidate>> {
+        branch::prune_candidates(self, base, |name, base| {
+            self.is_ancestor(&format!("refs/heads/{name}"), &format!("refs/heads/{base}"))
+        })
+    }
+
+    fn rename_branch(&self, old_name: &str, new_name: &str) -> GitResult<()> {
+        self.run(&["branch", "-m", old_name, new_name])
+            .map_err(GitError::BranchRenameFailed)?;
+        Ok(())
+    }
+
+    fn create_branch_from(&self, name: &str, start_point: &str) -> GitResult<()> {
+        self.run(&["branch", "--no-track", name, start_point])
+            .map_err(GitError::BranchCreateFailed)?;
+
+        let remote_branch = start_point
+            .strip_prefix("refs/remotes/")
+            .unwrap_or(start_point);
+        if self.ref_exists(&format!("refs/remotes/{remote_branch}")) {
+            let arg = format!("--set-upstream-to={remote_branch}");
+            self.run(&["branch", &arg, name])
+                .map_err(GitError::BranchCreateFailed)?;
+        }
+        Ok(())
+    }
+
+    fn set_upstream(&self, branch: &str, upstream: Option<&str>) -> GitResult<()> {
+        if !self.ref_exists(&format!("refs/heads/{branch}")) {
+            return Err(GitError::UpstreamFailed(
+                format!("branch not found: {branch}").into(),
+            ));
+        }
+        match upstream {
+            Some(upstream) => {
+                let upstream = upstream.strip_prefix("refs/remotes/").unwrap_or(upstream);
+                let arg = format!("--set-upstream-to={upstream}");
+                self.run(&["branch", &arg, branch])
+                    .map_err(GitError::UpstreamFailed)?;
+            }
+            None => {
+                // `--unset-upstream` fails when nothing is configured
+                let key = format!("branch.{branch}.merge");
+                if self.succeeds(&["config", "--get", &key]) {
+                    self.run(&["branch", "--unset-upstream", branch])
+                        .map_err(GitError::UpstreamFailed)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn checkout_remote_branch(
+        &self,
+        remote_branch: &str,
+        local_name: Option<&str>,
+    ) -> GitResult<String> {
+        branch::checkout_remote_branch(self, remote_branch, local_name)
+    }
+
+    fn merge_branch(&self, branch_name: &str, option: MergeOption) -> GitResult<MergeResult> {
+        let branch_ref = format!("refs/heads/{branch_name}");
+        if !self.ref_exists(&branch_ref) {
+            return Err(GitError::MergeFailed(
+                format!("branch not found: {branch_name}").into(),
+            ));
+        }
+        self.merge_ref(&branch_ref, branch_name, option, GitError::MergeFailed)
+    }
+
+    fn fetch(
+        &self,
+        remote_name: &str,
+        options: &FetchOptions,
+        control: &TransferControl,
+    ) -> GitResult<FetchResult> {
+        let depth = options.depth.map(|d| format!("--depth={d}"));
+        let mut args = vec!["fetch", "--progress"];
+        args.push(if options.prune {
+            "--prune"
+        } else {
+            "--no-prune"
+        });
+        match options.tags {
+            FetchTagMode::Auto => {}
+            FetchTagMode::All => args.push("--tags"),
+            FetchTagMode::None => args.push("--no-tags"),
+        }
+        if let Some(depth) = &depth {
+            args.push(depth);
+        }
+        if options.all_remotes {
+            args.push("--all");
+        } else {
+            args.push(remote_name);
+        }
+
+        let before = self.ref_snapshot().map_err(GitError::FetchFailed)?;
+        self.run_transfer(&args, control, GitError::FetchFailed)?;
+        let after = self.ref_snapshot().map_err(GitError::FetchFailed)?;
+
+        let (updates, new_commits) = fetch::collect_fetch_updates(
+            &before,
+            &after,
+            |new, old| self.is_ancestor(old, new),
+            |tips, hide| self.count_new_commits(tips, hide),
+        );
+        Ok(FetchResult {
+            remote_name: remote_name.to_string(),
+            updates,
+            new_commits,
+        })
+    }
+
+    fn pull(
+        &self,
+        remote_name: &str,
+        option: PullOption,
+        control: &TransferControl,
+    ) -> GitResult<MergeResult> {
+        self.fetch(remote_name, &FetchOptions::default(), control)?;
+        if control.is_cancelled() {
+            return Err(GitError::Cancelled);
+        }
+        self.pull_fetched(remote_name, option)
+    }
+
+    fn pull_fetched(&self, remote_name: &str, option: PullOption) -> GitResult<MergeResult> {
+        let branch_name = self
+            .head_branch()
+            .ok_or_else(|| GitError::PullFailed("HEAD has no name".into()))?;
+        let remote_ref_name = format!("{remote_name}/{branch_name}");
+        let remote_ref = format!("refs/remotes/{remote_ref_name}");
+        if !self.ref_exists(&remote_ref) {
+            return Err(GitError::PullFailed(
+                format!("remote branch not found: {remote_ref_name}").into(),
+            ));
+        }
+
+        match option {
+            PullOption::Merge => self.merge_ref(
+                &remote_ref,
+                &remote_ref_name,
+                MergeOption::Default,
+                GitError::PullFailed,
+            ),
+            PullOption::Rebase => {
+                if self.is_ancestor(&remote_ref, "HEAD") {
+                    return Ok(MergeResult {
+                        kind: MergeKind::UpToDate,
+                        oid: None,
+                        conflicts: vec![],
+                    });
+                }
+
+                let output = self
+                    .output(&["rebase", "-q", &remote_ref], None)
+                    .map_err(GitError::PullFailed)?;
+                if !output.status.success() {
+                    let _ = self.output(&["rebase", "--abort"], None);
+                    return Err(GitError::PullFailed("rebase conflicts detected".into()));
+                }
+
+                Ok(MergeResult {
+                    kind: MergeKind::Rebase,
+                    oid: self.head_oid(),
+                    conflicts: vec![],
+                })
+            }
+        }
+    }
+
+    fn push(
+        &self,
+        remote_name: &str,
+        options: &PushOptions,
+        control: &TransferControl,
+    ) -> GitResult<PushResult> {
+        let current_branch = self.head_branch();
+        let refs = push::plan_push(
+            options,
+            current_branch.as_deref(),
+            |name| self.ref_exists(name),
+            || {
+                self.run(&["tag", "--list"])
+                    .map(|out| out.lines().map(str::to_string).collect())
+                    .unwrap_or_default()
+            },
+        )
+        .map_err(|e| GitError::PushFailed(e.into()))?;
+
+        if !options.no_verify {
+            let url = self
+                .run(&["remote", "get-url", remote_name])
+                .map(|url| url.trim().to_string())
+                .unwrap_or_default();
+            let updates: Vec<PushUpdate> = refs
+                .iter()
+                .map(|push_ref| PushUpdate {
+                    local: push_ref.src.as_ref().and_then(|src| {
+                        let oid = self.run(&["rev-parse", "--verify", "-q", src]).ok()?;
+                        Some((src.clone(), oid.trim().to_string()))
+                    }),
+                    remote_ref: push_ref.dst.clone(),
+                    remote_oid: push::tracking_ref(remote_name, &push_ref.dst)
+                        .and_then(|tracking| self.resolve_commit(&tracking)),
+                })
+                .collect();
+            hooks::pre_push(&self.workdir, remote_name, &url, &updates)?;
+        }
+
+        let mut args = vec![
+            "push".to_string(),
+            "--progress".to_string(),
+            "--porcelain".to_string(),
+            "--no-verify".to_string(),
+        ];
+        if options.force_with_lease {
+            // `+` を付けると lease が無視されるため refspec には付けない
+            for push_ref in &refs {
+                let expected = push::tracking_ref(remote_name, &push_ref.dst)
+                    .and_then(|tracking| self.resolve_commit(&tracking))
+                    .unwrap_or_default();
+                args.push(format!("--force-with-lease={}:{expected}", push_ref.dst));
+            }
+        }
+        args.push(remote_name.to_string());
+        args.extend(refs.iter().map(|push_ref| push_ref.refspec(false)));
+
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let output = self.transfer_output(&args, control, GitError::PushFailed)?;
+        let reported = parse_porcelain_push(&String::from_utf8_lossy(&output.stdout));
+        if reported.is_empty() && !output.status.success() {
+            return Err(GitError::PushFailed(command_error(&output)));
+        }
+
+        let updates: Vec<PushRefUpdate> = refs
+            .iter()
+            .filter_map(|push_ref| {
+                reported
+                    .iter()
+                    .find(|update| update.remote_ref == push_ref.dst)
+                    .cloned()
+            })
+            .collect();
+
+        // 明示的な対象指定がない通常の push では upstream が未設定の場合に自動設定
+        let auto_upstream = !options.has_explicit_target();
+        for push_ref in &refs {
+            let Some((local, remote_branch)) = push_ref.upstream_pair() else {
+                continue;
+            };
+            let accepted = updates
+                .iter()
+                .any(|u| u.remote_ref == push_ref.dst && !u.status.is_rejected());
+            if !accepted {
+                continue;
+            }
+            let remote_key = format!("branch.{local}.remote");
+            if options.set_upstream || (auto_upstream && !self.succeeds(&["config", &remote_key])) {
+                let _ = self.run(&["config", &remote_key, remote_name]);
+                let _ = self.run(&[
+                    "config",
+                    &format!("branch.{local}.merge"),
+                    &format!("refs/heads/{remote_branch}"),
+                ]);
+            }
+        }
+
+        let branch = refs
+            .iter()
+            .find_map(|r| r.src.as_deref()?.strip_prefix("refs/heads/"))
+            .unwrap_or_default()
+            .to_string();
+        Ok(PushResult {
+            remote_name: remote_name.to_string(),
+            branch,
+            updates,
+        })
+    }
+
+    fn list_remotes(&self) -> GitResult<Vec<RemoteInfo>> {
+        let output = self.run(&["remote"]).map_err(GitError::RemoteFailed)?;
+
+        let mut remotes = Vec::new();
+        for name in output.lines().filter(|l| !l.is_empty()) {
+            let url = self
+                .run(&["config", "--get", &format!("remote.{name}.url")])
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default();
+            remotes.push(RemoteInfo {
+                name: name.to_string(),
+                url,
+            });
+        }
+        Ok(remotes)
+    }
+
+    fn add_remote(&self, name: &str, url: &str) -> GitResult<()> {
+        self.run(&["remote", "add", name, url])
+            .map_err(GitError::RemoteFailed)?;
+        Ok(())
+    }
+
+    fn remove_remote(&self, name: &str) -> GitResult<()> {
+        self.run(&["remote", "remove", name])
+            .map_err(GitError::RemoteFailed)?;
+        Ok(())
+    }
+
+    fn edit_remote(&self, name: &str, new_url: &str) -> GitResult<()> {
+        self.run(&["remote", "set-url", name, new_url])
+            .map_err(GitError::RemoteFailed)?;
+        Ok(())
+    }
+
+    fn get_commit_log(
+        &self,
+        filter: &LogFilter,
+        limit: usize,
+        skip: usize,
+    ) -> GitResult<CommitLogResult> {
+        let head = self
+            .head_oid()
+            .ok_or_else(|| GitError::LogFailed("HEAD does not point to a commit".into()))?;
+
+        let (mut commits, graph) = graph::commit_log_page(
+            &self.log_cache,
+            filter,
+            &head,
+            limit,
+            skip,
+            |tip, hidden| self.log_order(tip, hidden, filter),
+            |oids, wanted| self.walk_commits(oids, filter, wanted),
+        )?;
+
+        // Refs move without touching the walk, so decorate every page afresh.
+        let ref_map = self.build_ref_map();
+        for commit in &mut commits {
+            commit.refs = ref_map.get(&commit.oid).cloned().unwrap_or_default();
+        }
+
+        let oids: Vec<&str> = commits.iter().map(|c| c.oid.as_str()).collect();
+        if let Ok(sig_statuses) = verify_commit_signatures(&self.workdir, &oids) {
+            let status_map: HashMap<String, SignatureStatus> = sig_statuses.into_iter().collect();
+            for commit in &mut commits {
+                if let Some(&status) = status_map.get(&commit.oid) {
+                    commit.signature_status = status;
+                }
+            }
+        }
+
+        Ok(CommitLogResult { commits, graph })
+    }
+
+    fn get_commit_detail(&self, oid: &str) -> GitResult<CommitDetail> {
+        let record = self
+            .read_commit(oid)
+            .ok_or_else(|| GitError::CommitNotFound {
+                oid: oid.to_string(),
+            })?;
+
+        let diff_args = self.diff_commit_args(&record);
+        let mut name_status_args = vec![
+            "diff-tree",
+            "-r",
+            "-z",
+            "--no-renames",
+            "--no-commit-id",
+            "--name-status",
+        ];
+        name_status_args.extend(&diff_args);
+        let name_status = self.run(&name_status_args).map_err(GitError::LogFailed)?;
+
+        let mut numstat_args = vec![
+            "diff-tree",
+            "-r",
+            "-z",
+            "--no-renames",
+            "--no-commit-id",
+            "--numstat",
+        ];
+        numstat_args.extend(&diff_args);
+        let numstat = self.run(&numstat_args).map_err(GitError::LogFailed)?;
+        let line_stats = parse_numstat(&numstat);
+
+        let mut files = Vec::new();
+        let mut tokens = name_status.split('\0').filter(|t| !t.is_empty());
+        while let (Some(status), Some(path)) = (tokens.next(), tokens.next()) {
+            let status = match status.chars().next() {
+                Some('A') => CommitFileStatus::Added,
+                Some('D') => CommitFileStatus::Deleted,
+                Some('R') => CommitFileStatus::Renamed,
+                _ => CommitFileStatus::Modified,
+            };
+            let (additions, deletions) = line_stats.get(path).copied().unwrap_or((0, 0));
+            files.push(CommitFileChange {
+                path: path.to_string(),
+                status,
+                additions,
+                deletions,
+            });
+        }
+
+        let stats = CommitStats {
+            additions: files.iter().map(|f| f.additions).sum(),
+            deletions: files.iter().map(|f| f.deletions).sum(),
+            files_changed: files.len() as u32,
+        };
+
+        let ref_map = self.build_ref_map();
+        let info = record.into_info(&ref_map);
+
+        Ok(CommitDetail { info, files, stats })
+    }
+
+    fn get_commit_file_diff(&self, oid: &str, path: &str) -> GitResult<Vec<FileDiff>> {
+        let record = self
+            .read_commit(oid)
+            .ok_or_else(|| GitError::CommitNotFound {
+                oid: oid.to_string(),
+            })?;
+
+        let mut args = vec![
+            "diff-tree",
+            "-p",
+            "-r",
+            "--no-renames",
+            "--no-commit-id",
+            "--no-ext-diff",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+        ];
+        args.extend(self.diff_commit_args(&record));
+        args.extend(["--", path]);
+
+        let output = self.run(&args).map_err(GitError::DiffFailed)?;
+        Ok(parse_unified_diff(&output))
+    }
+
+    fn get_blame(
+        &self,
+        path: &str,
+        commit_oid: Option<&str>,
+        options: &BlameOptions,
+    ) -> GitResult<BlameResult> {
+        let rev = commit_oid.unwrap_or("HEAD");
+        let args = blame::porcelain_args(&self.workdir, rev, path, options);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let output = self.run(&args).map_err(GitError::BlameFailed)?;
+
+        Ok(BlameResult {
+            path: path.to_string(),
+            commit_oid: commit_oid.map(str::to_string),
+            lines: blame::parse_blame_porcelain(&output),
+        })
+    }
+
+    fn get_blame_before(
+        &self,
+        commit_oid: &str,
+        path: &str,
+        options: &BlameOptions,
+    ) -> GitResult<BlameResult> {
+        let parent_rev = format!("{commit_oid}^");
+        let parent = self
+            .run(&["rev-parse", "--verify", "--quiet", &parent_rev])
+            .map_err(|_| {
+                GitError::BlameFailed(format!("commit {commit_oid} has no parent").into())
+            })?
+            .trim()
+            .to_string();
+        let parent_path = if self
+            .run(&["cat-file", "-e", &format!("{parent}:{path}")])
+            .is_ok()
+        {
+            path.to_string()
+        } else {
+            let output = self
+                .run(&["diff", "-z", "--name-status", "-M", &parent, commit_oid])
+                .map_err(GitError::BlameFailed)?;
+            parse_rename_source(&output, path).ok_or_else(|| {
+                GitError::BlameFailed(format!("{path} does not exist before {commit_oid}").into())
+            })?
+        };
+        self.get_blame(&parent_path, Some(&parent), options)
+    }
+
+    fn get_file_history(
+        &self,
+        path: &str,
+        limit: usize,
+        skip: usize,
+        follow: bool,
+    ) -> GitResult<Vec<FileHistoryEntry>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        // --skip is applied here rather than by git so renames in skipped
+        // commits are still seen.
+        let max_count = format!("--max-count={}", skip.saturating_add(limit));
+        let mut args = vec![
+            "log",
+            "-z",
+            "--name-status",
+            "--format=%x01%H",
+            "--date-order",
+            &max_count,
+        ];
+        if follow {
+            args.extend(["--follow", "-M"]);
+        } else {
+            args.push("--full-history");
+        }
+        args.extend(["HEAD", "--", path]);
+        let output = self.run(&args).map_err(GitError::LogFailed)?;
+        let changes: Vec<_> = parse_path_log(&output, path)
+            .into_iter()
+            .skip(skip)
+            .collect();
+        if changes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut log_args = vec!["--no-walk=unsorted"];
+        log_args.extend(changes.iter().map(|change| change.oid.as_str()));
+        let mut commits: HashMap<String, CommitInfo> = self
+            .collect_log(&log_args, changes.len())
+            .map_err(GitError::LogFailed)?
+            .into_iter()
+            .map(|commit| (commit.oid.clone(), commit))
+            .collect();
+
+        Ok(changes
+            .into_iter()
+            .filter_map(|change| {
+                Some(FileHistoryEntry {
+                    commit: commits.remove(&change.oid)?,
+                    path: change.path,
+                    previous_path: change.previous_path,
+                })
+            })
+            .collect())
+    }
+
+    fn write_commit_graph(&self) -> GitResult<()> {
+        self.run(&["commit-graph", "write", "--reachable", "--changed-paths"])
+            .map(|_| ())
+            .map_err(GitError::CommitGraphFailed)
+    }
+
+    fn get_branch_commits(&self, branch_name: &str, limit: usize) -> GitResult<Vec<CommitInfo>> {
+        let branch_ref = format!("refs/heads/{branch_name}");
+        if !self.ref_exists(&branch_ref) {
+            return Err(GitError::LogFailed(
+                format!("branch not found: {branch_name}").into(),
+            ));
+        }
+        self.collect_log(&["--date-order", &branch_ref], limit)
+            .map_err(GitError::LogFailed)
+    }
+
+    fn stage_hunk(&self, path: &Path, hunk: &HunkIdentifier) -> GitResult<()> {
+        let patch = generate_hunk_patch(self, path, hunk, false)?;
+        run_git_apply(&self.workdir, &patch, &["--cached"]).map_err(GitError::StageFailed)
+    }
+
+    fn unstage_hunk(&self, path: &Path, hunk: &HunkIdentifier) -> GitResult<()> {
+        let patch = generate_hunk_patch(self, path, hunk, true)?;
+        run_git_apply(&self.workdir, &patch, &["--cached", "-R"]).map_err(GitError::UnstageFailed)
+    }
+
+    fn discard_hunk(&self, path: &Path, hunk: &HunkIdentifier) -> GitResult<()> {
+        let plan = SnapshotPlan {
+            files: vec![path.to_string_lossy().to_string()],
+            ..SnapshotPlan::default()
+        };
+        let description = format!("Discard changes in {}", path.display());
+        journal::journaled(self, OperationKind::Discard, description, plan, || {
+            let patch = generate_hunk_patch(self, path, hunk, false)?;
+            run_git_apply(&self.workdir, &patch, &["-R"]).map_err(GitError::DiscardFailed)
+        })
+    }
+
+    fn stage_lines(&self, path: &Path, line_range: &LineRange) -> GitResult<()> {
+        let patch = generate_line_patch(self, path, line_range, false)?;
+        run_git_apply(&self.workdir, &patch, &["--cached"]).map_err(GitError::StageFailed)
+    }
+
+    fn unstage_lines(&self, path: &Path, line_range: &LineRange) -> GitResult<()> {
+        let patch = generate_line_patch(self, path, line_range, true)?;
+        run_git_apply(&self.workdir, &patch, &["--cached", "-R"]).map_err(GitError::UnstageFailed)
+    }
+
+    fn discard_lines(&self, path: &Path, line_range: &LineRange) -> GitResult<()> {
+        let plan = SnapshotPlan {
+            files: vec![path.to_string_lossy().to_string()],
+            ..SnapshotPlan::default()
+        };
+        let description = format!("Discard changes in {}", path.display());
+        journal::journaled(self, OperationKind::Discard, description, plan, || {
+            let patch = generate_line_patch(self, path, line_range, false)?;
+            run_git_apply(&self.workdir, &patch, &["-R"]).map_err(GitError::DiscardFailed)
+        })
+    }
+
+    fn get_head_commit_message(&self) -> GitResult<String> {
+        let raw = self
+            .run(&["cat-file", "commit", "HEAD"])
+            .map_err(GitError::LogFailed)?;
+        let message = raw
+            .split_once("\n\n")
+            .map(|(_, message)| message)
+            .unwrap_or("");
+        Ok(message.to_string())
+    }
+
+    fn stash_save(&self, message: Option<&str>, options: &StashOptions) -> GitResult<()> {
+        stash::validate(options)?;
+        if !options.hunks.is_empty() {
+            return stash::stash_hunks(self, message, &options.hunks);
+        }
+
+        let output = self
+            .output(&stash::push_args(message, options), None)
+            .map_err(GitError::StashFailed)?;
+        if !output.status.success() {
+            return Err(GitError::StashFailed(command_error(&output)));
+        }
+        stash::check_stashed(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn stash_list(&self) -> GitResult<Vec<StashEntry>> {
+        let output = self
+            .run(&["stash", "list", "--format=%gs%x1f%ct"])
+            .map_err(GitError::StashFailed)?;
+
+        let entries = output
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                let (message, date) = line.rsplit_once('\x1f').unwrap_or((line, "0"));
+                StashEntry {
+                    index,
+                    message: message.to_string(),
+                    branch_name: parse_stash_branch_name(message),
+                    author_date: date.parse().unwrap_or(0),
+                }
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    fn stash_apply(&self, index: usize, restore_index: bool) -> GitResult<StashApplyResult> {
+        self.apply_stash("apply", index, restore_index)
+    }
+
+    fn stash_pop(&self, index: usize, restore_index: bool) -> GitResult<StashApplyResult> {
+        self.apply_stash("pop", index, restore_index)
+    }
+
+    fn stash_branch(&self, index: usize, branch_name: &str) -> GitResult<()> {
+        self.run(&[
+            "stash",
+            "branch",
+            branch_name,
+            &format!("stash@{{{index}}}"),
+        ])
+        .map_err(GitError::StashFailed)?;
+        Ok(())
+    }
+
+    fn stash_drop(&self, index: usize) -> GitResult<()> {
+        let stash = format!("stash@{{{index}}}");
+        let oid = self
+            .read_ref(&stash)
+            .ok_or_else(|| GitError::StashFailed(format!("stash not found: {stash}").into()))?;
+        let message = self
+            .stash_list()?
+            .into_iter()
+            .find(|entry| entry.index == index)
+            .map(|entry| entry.message)
+            .unwrap_or_default();
+        let plan = SnapshotPlan {
+            stash: Some(StashState {
+                oid,
+                message: message.clone(),
+            }),
+            ..SnapshotPlan::default()
+        };
+        let description = format!("Drop stash: {message}");
+        journal::journaled(self, OperationKind::StashDrop, description, plan, || {
+            self.run(&["stash", "drop", "-q", &stash])
+                .map_err(GitError::StashFailed)?;
+            Ok(())
+        })
+    }
+
+    fn stash_diff(&self, index: usize) -> GitResult<Vec<FileDiff>> {
+        let stash = format!("stash@{{{index}}}");
+        self.stash_tree_diff(&format!("{stash}^1"), &stash)
+    }
+
+    fn stash_detail(&self, index: usize) -> GitResult<StashDetail> {
+        let entry = self
+            .stash_list()?
+            .into_iter()
+            .find(|entry| entry.index == index)
+            .ok_or_else(|| GitError::StashFailed("stash index out of range".into()))?;
+        let stash = format!("stash@{{{index}}}");
+        let base = format!("{stash}^1");
+        let index_commit = format!("{stash}^2");
+        let untracked = format!("{stash}^3");
+
+        let oids = self
+            .run(&["rev-parse", &stash, &base])
+            .map_err(GitError::StashFailed)?;
+        let mut oids = oids.lines().map(str::to_string);
+        let oid = oids.next().unwrap_or_default();
+        let base_oid = oids.next().unwrap_or_default();
+        let base_summary = self
+            .run(&["log", "-1", "--format=%s", &base_oid])
+            .map_err(GitError::StashFailed)?
+            .trim_end()
+            .to_string();
+
+        let untracked_files = if self.succeeds(&["rev-parse", "--verify", "-q", &untracked]) {
+            let empty_tree = self
+                .run_with_input(&["hash-object", "-t", "tree", "--stdin"], Some(""))
+                .map_err(GitError::StashFailed)?;
+            self.stash_tree_diff(empty_tree.trim(), &untracked)?
+        } else {
+            Vec::new()
+        };
+
+        Ok(StashDetail {
+            entry,
+            oid,
+            base_oid,
+            base_summary,
+            index_changes: self.stash_tree_diff(&base, &index_commit)?,
+            worktree_changes: self.stash_tree_diff(&index_commit, &stash)?,
+            untracked_files,
+        })
+    }
+
+    fn stash_rename(&self, index: usize, message: &str) -> GitResult<()> {
+        stash::rename(&self.git_dir, index, message)
+    }
+
+    fn list_tags(&self) -> GitResult<Vec<TagInfo>> {
+        let output = self
+            .run(&[
+                "for-each-ref",
+                "--format=%(refname)%1f%(objecttype)%1f%(objectname)%1f%(*objecttype)%1f%(*objectname)%1f%(taggername)%1f%(taggerdate:unix)%1f%(contents)%1e",
+                "refs/tags",
+            ])
+            .map_err(GitError::TagFailed)?;
+
+        let mut tags = Vec::new();
+        for record in output.split('\x1e') {
+            let record = record.trim_start_matches('\n');
+            let fields: Vec<&str> = record.splitn(8, '\x1f').collect();
+            let [refname, object_type, oid, peeled_type, peeled_oid, tagger_name, tagger_date, contents] =
+                fields[..]
+            else {
+                continue;
+            };
+            let Some(name) = refname.strip_prefix("refs/tags/") else {
+                continue;
+            };
+
+            let is_annotated = object_type == "tag";
+            let target_oid = match (is_annotated, object_type, peeled_type) {
+                (true, _, "commit") => peeled_oid,
+                (false, "commit", _) => oid,
+                _ => continue,
+            };
+            let target_short_oid = target_oid[..7.min(target_oid.len())].to_string();
+
+            if is_annotated {
+                let (message, signature_status) =
+                    tag::message_and_signature(&self.workdir, name, contents);
+                tags.push(TagInfo {
+                    name: name.to_string(),
+                    target_oid: target_oid.to_string(),
+                    target_short_oid,
+                    is_annotated: true,
+                    tagger_name: Some(tagger_name.to_string()),
+                    tagger_date: tagger_date.parse().ok(),
+                    message: Some(message),
+                    signature_status,
+                });
+            } else {
+                tags.push(TagInfo {
+                    name: name.to_string(),
+                    target_oid: target_oid.to_string(),
+                    target_short_oid,
+                    is_annotated: false,
+                    tagger_name: None,
+                    tagger_date: None,
+                    message: None,
+                    signature_status: SignatureStatus::None,
+                });
+            }
+        }
+
+        tag::sort_by_version(&mut tags);
+        Ok(tags)
+    }
+
+    fn create_tag(
+        &self,
+        name: &str,
+        message: Option<&str>,
+        options: &CreateTagOptions,
+    ) -> GitResult<()> {
+        if options.sign && message.is_none() {
+            return Err(GitError::TagFailed("a signed tag needs a message".into()));
+        }
+        let target = options.target.as_deref().unwrap_or("HEAD");
+        let commit = self
+            .run(&[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{target}^{{commit}}"),
+            ])
+            .map_err(|_| GitError::TagFailed(format!("invalid tag target: {target}").into()))?;
+        let commit = commit.trim();
+
+        match message {
+            Some(msg) => {
+                let kind = if options.sign { "-s" } else { "-a" };
+                self.run_with_input(
+                    &["tag", kind, "--cleanup=verbatim", "-F", "-", name, commit],
+                    Some(msg),
+                )
+            }
+            None => self.run(&["tag", name, commit]),
+        }
+        .map_err(GitError::TagFailed)?;
+        Ok(())
+    }
+
+    fn delete_tag(&self, name: &str) -> GitResult<()> {
+        self.run(&["tag", "-d", name])
+            .map_err(GitError::TagFailed)?;
+        Ok(())
+    }
+
+    fn checkout_tag(&self, name: &str) -> GitResult<()> {
+        let tag_ref = format!("refs/tags/{name}");
+        if !self.ref_exists(&tag_ref) {
+            return Err(GitError::TagFailed(format!("tag not found: {name}").into()));
+        }
+        self.run(&["checkout", "-q", "-f", "--detach", &tag_ref])
+            .map_err(GitError::TagFailed)?;
+        Ok(())
+    }
+
+    fn release_notes(&self, options: &ReleaseNotesOptions) -> GitResult<ReleaseNotes> {
+        let to = options.to.as_deref().unwrap_or("HEAD");
+        let resolve = |rev: &str| {
+            self.resolve_commit(rev).ok_or_else(|| {
+                GitError::ReleaseNotesFailed(format!("revision not found: {rev}").into())
+            })
+        };
+        let mut args = vec!["--date-order".to_string(), resolve(to)?];
+        if let Some(from) = options.from.as_deref() {
+            args.push(format!("^{}", resolve(from)?));
+        }
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let commits = self
+            .collect_log(&args, usize::MAX)
+            .map_err(GitError::ReleaseNotesFailed)?;
+        Ok(release_notes::build(options.from.as_deref(), to, &commits))
+    }
+
+    fn get_conflict_files(&self) -> GitResult<Vec<ConflictFile>> {
+        let mut result = Vec::new();
+        for path in self.conflict_paths() {
+            let full_path = self.workdir.join(&path);
+            let content = std::fs::read_to_string(&full_path).unwrap_or_default();
+            let blocks = parse_conflict_markers(&content);
+            let conflict_count = blocks.len();
+            result.push(ConflictFile {
+                path,
+                conflict_count,
+                conflicts: blocks,
+            });
+        }
+
+        Ok(result)
+    }
+
+    fn resolve_conflict(&self, path: &str, resolution: ConflictResolution) -> GitResult<()> {
+        let full_path = self.workdir.join(path);
+
+        let resolved_content = match resolution {
+            ConflictResolution::Ours => self.stage_blob_content(path, 2)?,
+            ConflictResolution::Theirs => self.stage_blob_content(path, 3)?,
+            ConflictResolution::Both => {
+                let ours = self.stage_blob_content(path, 2)?;
+                let theirs = self.stage_blob_content(path, 3)?;
+                format!("{ours}{theirs}")
+            }
+            ConflictResolution::Manual(content) => content,
+        };
+
+        std::fs::write(&full_path, resolved_content)
+            .map_err(|e| GitError::ConflictFailed(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    fn resolve_conflict_block(
+        &self,
+        path: &str,
+        block_index: usize,
+        resolution: ConflictResolution,
+    ) -> GitResult<()> {
+        let full_path = self.workdir.join(path);
+        let content = std::fs::read_to_string(&full_path)
+            .map_err(|e| GitError::ConflictFailed(Box::new(e)))?;
+
+        let resolved = resolve_single_block(&content, block_index, &resolution)?;
+
+        std::fs::write(&full_path, resolved).map_err(|e| GitError::ConflictFailed(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    fn mark_resolved(&self, path: &str) -> GitResult<()> {
+        self.run(&["add", "--", path])
+            .map_err(GitError::ConflictFailed)?;
+        Ok(())
+    }
+
+    fn abort_merge(&self) -> GitResult<()> {
+        self.abort_in_progress("merge")
+            .map_err(GitError::ConflictFailed)
+    }
+
+    fn continue_merge(&self, message: &str) -> GitResult<CommitResult> {
+        if !self.conflict_paths().is_empty() {
+            return Err(GitError::ConflictFailed(
+                "unresolved conflicts remain".into(),
+            ));
+        }
+        if !self.git_dir.join("MERGE_HEAD").exists() {
+            return Err(GitError::ConflictFailed("no merge in progress".into()));
+        }
+
+        // 空のメッセージは git が用意した MERGE_MSG を使う
+        let (message, cleanup) = if message.is_empty() {
+            let prepared =
+                std::fs::read_to_string(self.git_dir.join("MERGE_MSG")).unwrap_or_default();
+            (prepared, "--cleanup=strip")
+        } else {
+            (message.to_string(), "--cleanup=verbatim")
+        };
+        let message =
+            hooks::prepare_linted_commit(&self.workdir, &message, CommitSource::InProgress, true)?;
+        self.run_with_input(
+            &["-c", NO_HOOKS, "commit", "-q", cleanup, "-F", "-"],
+            Some(&message),
+        )
+        .map_err(GitError::ConflictFailed)?;
+        hooks::post_commit(&self.workdir);
+
+        let oid = self
+            .head_oid()
+            .ok_or_else(|| GitError::ConflictFailed("HEAD not found after commit".into()))?;
+        Ok(CommitResult { oid })
+    }
+
+    fn is_merging(&self) -> GitResult<bool> {
+        Ok(self.in_progress_marker() == Some("MERGE_HEAD"))
+    }
+
+    fn rebase(
+        &self,
+        onto: &str,
+        options: &RebaseOptions,
+        _control: &RebaseControl,
+    ) -> GitResult<RebaseResult> {
+        let plan = SnapshotPlan {
+            refs: stack::rebased_refs_onto(&self.workdir, onto)?,
+            worktree: true,
+            ..SnapshotPlan::default()
+        };
+        let description = format!("Rebase onto {onto}");
+        journal::journaled(self, OperationKind::Rebase, description, plan, || {
+            rebase::rebase(&self.workdir, onto, options)
+        })
+    }
+
+    fn interactive_rebase(
+        &self,
+        onto: &str,
+        todo: &[RebaseTodoEntry],
+        _control: &RebaseControl,
+    ) -> GitResult<RebaseResult> {
+        let todo = stack::with_update_refs(&self.workdir, onto, todo.to_vec())?;
+        let plan = SnapshotPlan {
+            refs: stack::rebased_refs(&todo),
+            worktree: true,
+            ..SnapshotPlan::default()
+        };
+        let description = format!("Interactive rebase onto {onto}");
+        journal::journaled(self, OperationKind::Rebase, description, plan, || {
+            rebase::interactive_rebase(&self.workdir, onto, &todo)
+        })
+    }
+
+    fn edit_commit(&self, edit: &CommitEdit) -> GitResult<RebaseResult> {
+        let (base, todo) = rebase::plan_commit_edit(self, edit)?;
+        self.interactive_rebase(&base, &todo, &RebaseControl::default())
+    }
+
+    fn is_rebasing(&self) -> GitResult<bool> {
+        Ok(self.in_progress_marker() == Some("rebase"))
+    }
+
+    fn abort_rebase(&self) -> GitResult<()> {
+        rebase::abort_rebase(&self.workdir)
+    }
+
+    fn continue_rebase(&self, _control: &RebaseControl) -> GitResult<RebaseResult> {
+        rebase::continue_rebase(&self.workdir)
+    }
+
+    fn get_rebase_state(&self) -> GitResult<Option<RebaseState>> {
+        let has_conflicts = !self.conflict_paths().is_empty();
+        Ok(rebase::read_rebase_state(&self.git_dir, has_conflicts))
+    }
+
+    fn get_rebase_todo(&self, onto: &str, limit: usize) -> GitResult<Vec<RebaseTodoEntry>> {
+        let merge_base = self
+            .run(&["merge-base", onto, "HEAD"])
+            .map_err(GitError::RebaseFailed)?;
+        let hide = format!("^{}", merge_base.trim());
+
+        let output = self
+            .run(&[
+                "log",
+                "--topo-order",
+                "--reverse",
+                "--format=%H%x1f%an%x1f%s",
+                "HEAD",
+                &hide,
+            ])
+            .map_err(GitError::RebaseFailed)?;
+
+        let entries = output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\x1f');
+                let oid = fields.next()?.to_string();
+                let author_name = fields.next()?.to_string();
+                let message = fields.next().unwrap_or("").to_string();
+                Some(RebaseTodoEntry {
+                    action: RebaseAction::Pick,
+                    short_oid: oid[..7.min(oid.len())].to_string(),
+                    oid,
+                    message,
+                    author_name,
+                    argument: None,
+                })
+            })
+            .take(limit)
+            .collect();
+
+        Ok(entries)
+    }
+
+    fn get_merge_base_content(&self, path: &str) -> GitResult<MergeBaseContent> {
+        let base_content = self.stage_blob_content(path, 1).ok();
+        let ours_content = self.stage_blob_content(path, 2)?;
+        let theirs_content = self.stage_blob_content(path, 3)?;
+
+        Ok(MergeBaseContent {
+            path: path.to_string(),
+            base_content,
+            ours_content,
+            theirs_content,
+        })
+    }
+
+    fn cherry_pick(&self, oids: &[&str], mode: CherryPickMode) -> GitResult<CherryPickResult> {
+        let mode_args: &[&str] = match mode {
+            CherryPickMode::Normal => &["-x"],
+            CherryPickMode::NoCommit => &[],
+            CherryPickMode::Merge => &["-m", "1"],
+        };
+        let commits = mode != CherryPickMode::NoCommit;
+        // 各コミットは --no-commit で適用してからフックを通して作成するため、
+        // 既にステージされている変更が混ざらないようにする
+        if commits && !self.succeeds(&["diff", "--cached", "--quiet"]) {
+            return Err(GitError::CherryPickFailed(
+                "your index contains uncommitted changes".into(),
+            ));
+        }
+
+        for oid_str in oids {
+            let oid = self.resolve_commit(oid_str).ok_or_else(|| {
+                GitError::CherryPickFailed(format!("commit not found: {oid_str}").into())
+            })?;
+
+            let mut args = vec![
+                "-c",
+                NO_HOOKS,
+                "cherry-pick",
+                "--allow-empty",
+                "--no-commit",
+            ];
+            args.extend(mode_args);
+            args.push(&oid);
+            let output = self
+                .output(&args, None)
+                .map_err(GitError::CherryPickFailed)?;
+            let conflicts = self
+                .conflicts_after(&output)
+                .map_err(GitError::CherryPickFailed)?;
+
+            if commits {
+                // --no-commit は CHERRY_PICK_HEAD を残さないので作成中として記録する
+                self.run(&["update-ref", "CHERRY_PICK_HEAD", &oid])
+                    .map_err(GitError::CherryPickFailed)?;
+            }
+            if let Some(conflicts) = conflicts {
+                return Ok(CherryPickResult {
+                    completed: false,
+                    conflicts,
+                    oid: None,
+                });
+            }
+            if commits {
+                self.commit_in_progress(CommitSource::Message, GitError::CherryPickFailed)?;
+            }
+        }
+
+        let head_oid = if mode == CherryPickMode::NoCommit {
+            None
+        } else {
+            self.head_oid()
+        };
+
+        Ok(CherryPickResult {
+            completed: true,
+            conflicts: Vec::new(),
+            oid: head_oid,
+        })
+    }
+
+    fn is_cherry_picking(&self) -> GitResult<bool> {
+        Ok(self.in_progress_marker() == Some("CHERRY_PICK_HEAD"))
+    }
+
+    fn abort_cherry_pick(&self) -> GitResult<()> {
+        self.abort_in_progress("cherry-pick")
+            .map_err(GitError::CherryPickFailed)
+    }
+
+    fn continue_cherry_pick(&self) -> GitResult<CherryPickResult> {
+        if !self.git_dir.join("CHERRY_PICK_HEAD").exists() {
+            return Err(GitError::CherryPickFailed(
+                "no cherry-pick in progress".into(),
+            ));
+        }
+        let oid = self.commit_in_progress(CommitSource::InProgress, GitError::CherryPickFailed)?;
+
+        Ok(CherryPickResult {
+            completed: true,
+            conflicts: Vec::new(),
+            oid: Some(oid),
+        })
+    }
+
+    fn revert(&self, oid_str: &str, mode: RevertMode) -> GitResult<RevertResult> {
+        let oid = self
+            .resolve_commit(oid_str)
+            .ok_or_else(|| GitError::RevertFailed(format!("commit not found: {oid_str}").into()))?;
+
+        if mode == RevertMode::Auto && !self.succeeds(&["diff", "--cached", "--quiet"]) {
+            return Err(GitError::RevertFailed(
+                "your index contains uncommitted changes".into(),
+            ));
+        }
+
+        let output = self
+            .output(&["-c", NO_HOOKS, "revert", "--no-commit", &oid], None)
+            .map_err(GitError::RevertFailed)?;
+        let conflicts = self
+            .conflicts_after(&output)
+            .map_err(GitError::RevertFailed)?;
+
+        if mode == RevertMode::Auto {
+            self.run(&["update-ref", "REVERT_HEAD", &oid])
+                .map_err(GitError::RevertFailed)?;
+        }
+        if let Some(conflicts) = conflicts {
+            return Ok(RevertResult {
+                completed: false,
+                conflicts,
+                oid: None,
+            });
+        }
+
+        let new_oid = if mode == RevertMode::Auto {
+            Some(self.commit_in_progress(CommitSource::Message, GitError::RevertFailed)?)
+        } else {
+            None
+        };
+
+        Ok(RevertResult {
+            completed: true,
+            conflicts: Vec::new(),
+            oid: new_oid,
+        })
+    }
+
+    fn is_reverting(&self) -> GitResult<bool> {
+        Ok(self.in_progress_marker() == Some("REVERT_HEAD"))
+    }
+
+    fn abort_revert(&self) -> GitResult<()> {
+        self.abort_in_progress("revert")
+            .map_err(GitError::RevertFailed)
+    }
+
+    fn continue_revert(&self) -> GitResult<RevertResult> {
+        if !self.git_dir.join("REVERT_HEAD").exists() {
+            return Err(GitError::RevertFailed("no revert in progress".into()));
+        }
+        let oid = self.commit_in_progress(CommitSource::InProgress, GitError::RevertFailed)?;
+
+        Ok(RevertResult {
+            completed: true,
+            conflicts: Vec::new(),
+            oid: Some(oid),
+        })
+    }
+
+    fn reset(&self, oid_str: &str, mode: ResetMode) -> GitResult<ResetResult> {
+        let plan = SnapshotPlan {
+            refs: vec!["HEAD".to_string()],
+            index: true,
+            worktree: mode == ResetMode::Hard,
+            ..SnapshotPlan::default()
+        };
+        let description = format!("Reset ({mode:?}) to {oid_str}");
+        journal::journaled(self, OperationKind::Reset, description, plan, || {
+            let oid = self.resolve_commit(oid_str).ok_or_else(|| {
+                GitError::ResetFailed(format!("commit not found: {oid_str}").into())
+            })?;
+
+            let mode_flag = match mode {
+                ResetMode::Soft => "--soft",
+                ResetMode::Mixed => "--mixed",
+                ResetMode::Hard => "--hard",
+            };
+
+            self.run(&["reset", "-q", mode_flag, &oid])
+                .map_err(GitError::ResetFailed)?;
+
+            Ok(ResetResult {
+                oid: oid_str.to_string(),
+            })
+        })
+    }
+
+    fn reset_file(&self, path: &str, oid_str: &str) -> GitResult<()> {
+        let oid = self
+            .resolve_commit(oid_str)
+            .ok_or_else(|| GitError::ResetFailed(format!("commit not found: {oid_str}").into()))?;
+
+        self.run(&["reset", "-q", &oid, "--", path])
+            .map_err(GitError::ResetFailed)?;
+
+        Ok(())
+    }
+
+    fn list_operations(&self) -> GitResult<Vec<OperationEntry>> {
+        journal::list(self)
+    }
+
+    fn undo_last_operation(&self) -> GitResult<OperationEntry> {
+        journal::undo_last(self)
+    }
+
+    fn get_reflog(&self, ref_name: &str, limit: usize) -> GitResult<Vec<ReflogEntry>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        // old_oid は 1 つ古いエントリの new_oid になるため、1 件多く読む
+        let max_count = format!("--max-count={}", limit + 1);
+        let output = self
+            .output(
+                &[
+                    "log",
+                    "-g",
+                    "--date=unix",
+                    "--format=%H%x1f%gs%x1f%gn%x1f%gd",
+                    &max_count,
+                    ref_name,
+                    "--",
+                ],
+                None,
+            )
+            .map_err(GitError::ReflogFailed)?;
+        if !output.status.success() {
+            // 参照が存在しない場合は空の reflog として扱う
+            if !self.ref_exists(ref_name) && ref_name != "HEAD" {
+                return Ok(Vec::new());
+            }
+            return Err(GitError::ReflogFailed(command_error(&output)));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let raw: Vec<Vec<&str>> = stdout
+            .lines()
+            .map(|line| line.splitn(4, '\x1f').collect())
+            .filter(|fields: &Vec<&str>| fields.len() == 4)
+            .collect();
+
+        let mut entries = Vec::new();
+        for (i, fields) in raw.iter().enumerate().take(limit) {
+            let new_oid = fields[0].to_string();
+            let old_oid = raw
+                .get(i + 1)
+                .map(|next| next[0].to_string())
+                .unwrap_or_else(|| NULL_OID.to_string());
+            let (action, message) = parse_reflog_message(fields[1]);
+            let committer_date = fields[3]
+                .rsplit_once("@{")
+                .and_then(|(_, date)| date.trim_end_matches('}').parse().ok())
+                .unwrap_or(0);
+
+            entries.push(ReflogEntry {
+                index: i,
+                old_oid,
+                new_short_oid: new_oid[..7.min(new_oid.len())].to_string(),
+                new_oid,
+                action,
+                message,
+                committer_name: fields[2].to_string(),
+                committer_date,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    fn search_code(&self, query: &str, is_regex: bool) -> GitResult<Vec<CodeSearchResult>> {
+        search::search_code(&self.workdir, query, is_regex)
+    }
+
+    fn search_commits(&self, query: &str, search_diff: bool) -> GitResult<Vec<CommitSearchResult>> {
+        search::search_commits(&self.workdir, query, search_diff)
+    }
+
+    fn search_filenames(&self, query: &str) -> GitResult<Vec<FilenameSearchResult>> {
+        search::search_filenames(&self.workdir, query)
+    }
+
+    fn list_submodules(&self) -> GitResult<Vec<SubmoduleInfo>> {
+        submodule::list_submodules(&self.workdir)
+    }
+
+    fn add_submodule(&self, url: &str, path: &str) -> GitResult<()> {
+        submodule::add_submodule(&self.workdir, url, path)
+    }
+
+    fn update_submodule(&self, path: &str) -> GitResult<()> {
+        submodule::update_submodule(&self.workdir, path)
+    }
+
+    fn update_all_submodules(&self) -> GitResult<()> {
+        submodule::update_all_submodules(&self.workdir)
+    }
+
+    fn remove_submodule(&self, path: &str) -> GitResult<()> {
+        submodule::remove_submodule(&self.workdir, path)
+    }
+
+    fn list_worktrees(&self) -> GitResult<Vec<WorktreeInfo>> {
+        worktree::list_worktrees(&self.workdir)
+    }
+
+    fn add_worktree(&self, path: &str, branch: &str) -> GitResult<()> {
+        worktree::add_worktree(&self.workdir, path, branch)
+    }
+
+    fn remove_worktree(&self, path: &str) -> GitResult<()> {
+        worktree::remove_worktree(&self.workdir, path)
+    }
+
+    fn get_gitconfig_entries(&self, scope: GitConfigScope) -> GitResult<Vec<GitConfigEntry>> {
+        let output = self
+            .run(&["config", Self::config_scope_flag(scope), "--list", "-z"])
+            .map_err(GitError::GitConfigFailed)?;
+
+        let entries = output
+            .split('\0')
+            .filter_map(|entry| entry.split_once('\n'))
+            .map(|(key, value)| GitConfigEntry {
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    fn get_gitconfig_value(&self, scope: GitConfigScope, key: &str) -> GitResult<Option<String>> {
+        let output = self
+            .output(
+                &["config", Self::config_scope_flag(scope), "--get", key],
+                None,
+            )
+            .map_err(GitError::GitConfigFailed)?;
+
+        match output.status.code() {
+            Some(0) => {
+                let value = String::from_utf8_lossy(&output.stdout);
+                Ok(Some(value.trim_end_matches('\n').to_string()))
+            }
+            // exit 1: key not found
+            Some(1) => Ok(None),
+            _ => Err(GitError::GitConfigFailed(command_error(&output))),
+        }
+    }
+
+    fn set_gitconfig_value(&self, scope: GitConfigScope, key: &str, value: &str) -> GitResult<()> {
+        self.run(&["config", Self::config_scope_flag(scope), key, value])
+            .map_err(GitError::GitConfigFailed)?;
+        Ok(())
+    }
+
+    fn unset_gitconfig_value(&self, scope: GitConfigScope, key: &str) -> GitResult<()> {
+        let output = self
+            .output(
+                &["config", Self::config_scope_flag(scope), "--unset", key],
+                None,
+            )
+            .map_err(GitError::GitConfigFailed)?;
+
+        match output.status.code() {
+            // exit 5: key not found
+            Some(0) | Some(5) => Ok(()),
+            _ => Err(GitError::GitConfigFailed(command_error(&output))),
+        }
+    }
+
+    fn get_gitconfig_path(&self, scope: GitConfigScope) -> GitResult<String> {
+        match scope {
+            GitConfigScope::Local => {
+                let config_path = self.git_dir.join("config");
+                Ok(config_path.to_string_lossy().to_string())
+            }
+            GitConfigScope::Global => {
+                if let Some(path) = std::env::var_os("GIT_CONFIG_GLOBAL") {
+                    return Ok(PathBuf::from(path).to_string_lossy().to_string());
+                }
+                let home = dirs::home_dir()
+                    .ok_or_else(|| GitError::GitConfigFailed("home directory not found".into()))?;
+                Ok(home.join(".gitconfig").to_string_lossy().to_string())
+            }
+        }
+    }
+
+    fn verify_commit_signatures(&self, oids: &[&str]) -> GitResult<Vec<(String, SignatureStatus)>> {
+        verify_commit_signatures(&self.workdir, oids)
+    }
+}
+
+impl JournalRepo for CliBackend {
+    fn git_dir(&self) -> GitResult<PathBuf> {
+        let dir = self
+            .run(&["rev-parse", "--absolute-git-dir"])
+            .map_err(GitError::UndoFailed)?;
+        Ok(PathBuf::from(dir.trim()))
+    }
+
+    fn head_ref_name(&self) -> Option<String> {
+        self.run(&["symbolic-ref", "-q", "HEAD"])
+            .ok()
+            .map(|s| s.trim().to_string())
+    }
+
+    fn read_ref(&self, name: &str) -> Option<String> {
+        self.run(&["rev-parse", "--verify", "-q", name])
+            .ok()
+            .map(|s| s.trim().to_string())
+    }
+
+    fn write_index_tree(&self) -> GitResult<String> {
+        let tree = self.run(&["write-tree"]).map_err(GitError::UndoFailed)?;
+        Ok(tree.trim().to_string())
+    }
+
+    fn unstaged_files(&self) -> GitResult<Vec<String>> {
+        let output = self
+            .run(&["diff", "--name-only", "-z", "--no-renames"])
+            .map_err(GitError::UndoFailed)?;
+        Ok(output
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(|path| path.to_string())
+            .collect())
+    }
+
+    fn write_worktree_blob(&self, path: &str) -> GitResult<Option<String>> {
+        if !self.workdir.join(path).is_file() {
+            return Ok(None);
+        }
+        let oid = self
+            .run(&["hash-object", "-w", "--no-filters", "--", path])
+            .map_err(GitError::UndoFailed)?;
+        Ok(Some(oid.trim().to_string()))
+    }
+
+    fn set_ref(&self, name: &str, oid: Option<&str>, message: &str) -> GitResult<()> {
+        match oid {
+            Some(oid) => self.run(&["update-ref", "-m", message, name, oid]),
+            None if self.ref_exists(name) => self.run(&["update-ref", "-d", name]),
+            None => return Ok(()),
+        }
+        .map_err(GitError::UndoFailed)?;
+        Ok(())
+    }
+
+    fn reset_head(&self, oid: &str, hard: bool) -> GitResult<()> {
+        let mode = if hard { "--hard" } else { "--soft" };
+        self.run(&["reset", "-q", mode, oid])
+            .map_err(GitError::UndoFailed)?;
+        Ok(())
+    }
+
+    fn read_index_tree(&self, tree: &str) -> GitResult<()> {
+        self.run(&["read-tree", tree])
+            .map_err(GitError::UndoFailed)?;
+        Ok(())
+    }
+
+    fn checkout_index(&self) -> GitResult<()> {
+        self.run(&["checkout-index", "-a", "-f"])
+            .map_err(GitError::UndoFailed)?;
+        Ok(())
+    }
+
+    fn read_blob(&self, oid: &str) -> GitResult<Vec<u8>> {
+        let output = self
+            .output(&["cat-file", "blob", oid], None)
+            .map_err(GitError::UndoFailed)?;
+        if !output.status.success() {
+            return Err(GitError::UndoFailed(command_error(&output)));
+        }
+        Ok(output.stdout)
+    }
+
+    fn store_stash(&self, oid: &str, message: &str) -> GitResult<()> {
+        self.run(&["stash", "store", "-m", message, oid])
+            .map_err(GitError::UndoFailed)?;
+        Ok(())
+    }
+}
+
+struct LogRecord {
+    oid: String,
+    parent_oids: Vec<String>,
+    author_name: String,
+    author_email: String,
+    author_date: i64,
+    commit_time: i64,
+    raw_message: String,
+}
+
+impl LogRecord {
+    fn matches_filter(&self, filter: &LogFilter) -> bool {
+        if let Some(ref author) = filter.author {
+            if !self
+                .author_name
+                .to_lowercase()
+                .contains(&author.to_lowercase())
+            {
+                return false;
+            }
+        }
+
+        if let Some(since) = filter.since {
+            if self.commit_time < since {
+                return false;
+            }
+        }
+
+        if let Some(until) = filter.until {
+            if self.commit_time > until {
+                return false;
+            }
+        }
+
+        if let Some(ref msg) = filter.message {
+            if !self
+                .raw_message
+                .to_lowercase()
+                .contains(&msg.to_lowercase())
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn into_info(self, ref_map: &RefMap) -> CommitInfo {
+        let mut parts = self.raw_message.splitn(2, '\n');
+        let message = parts.next().unwrap_or("").to_string();
+        let body = parts.next().unwrap_or("").trim().to_string();
+        let refs = ref_map.get(&self.oid).cloned().unwrap_or_default();
+        let commit_trailers = trailers::parse(&self.raw_message);
+
+        CommitInfo {
+            short_oid: self.oid[..7.min(self.oid.len())].to_string(),
+            oid: self.oid,
+            message,
+            body,
+            author_name: self.author_name,
+            author_email: self.author_email,
+            author_date: self.author_date,
+            parent_oids: self.parent_oids,
+            refs,
+            signature_status: SignatureStatus::None,
+            co_authors: trailers::co_authors(&commit_trailers),
+            trailers: commit_trailers,
+        }
+    }
+}
+
+fn parse_log_record(record: &str) -> Option<LogRecord> {
+    let record = record.trim_start_matches('\n');
+    if record.is_empty() {
+        return None;
+    }
+
+    let fields: Vec<&str> = record.splitn(7, '\x1f').collect();
+    let [oid, parents, author_name, author_email, author_date, commit_time, raw_message] =
+        fields[..]
+    else {
+        return None;
+    };
+
+    Some(LogRecord {
+        oid: oid.to_string(),
+        parent_oids: parents.split_whitespace().map(|p| p.to_string()).collect(),
+        author_name: author_name.to_string(),
+        author_email: author_email.to_string(),
+        author_date: author_date.parse().unwrap_or(0),
+        commit_time: commit_time.parse().unwrap_or(0),
+        raw_message: raw_message.to_string(),
+    })
+}
+
+/// A date `git commit --date` and `GIT_COMMITTER_DATE` accept.
+fn git_date(time: i64, offset_minutes: i32) -> String {
+    let sign = if offset_minutes < 0 { '-' } else { '+' };
+    let offset = offset_minutes.abs();
+    format!("@{time} {sign}{:02}{:02}", offset / 60, offset % 60)
+}
+
+fn command_error(output: &Output) -> Box<dyn std::error::Error + Send + Sync> {
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if stderr.is_empty() {
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .to_string()
+            .into()
+    } else {
+        stderr.into()
+    }
+}
+
+fn parse_porcelain_status(output: &str) -> Vec<FileStatus> {
+    let mut files = Vec::new();
+    let mut records = output.split('\0');
+
+    while let Some(record) = records.next() {
+        let (xy, path) = match record.as_bytes().first() {
+            Some(b'1') => {
+                let fields: Vec<&str> = record.splitn(9, ' ').collect();
+                match fields[..] {
+                    [_, xy, .., path] if fields.len() == 9 => (xy, path),
+                    _ => continue,
+                }
+            }
+            Some(b'2') => {
+                // rename/copy entries are followed by the original path
+                records.next();
+                let fields: Vec<&str> = record.splitn(10, ' ').collect();
+                match fields[..] {
+                    [_, xy, .., path] if fields.len() == 10 => (xy, path),
+                    _ => continue,
+                }
+            }
+            Some(b'u') => {
+                if let Some(path) = record.splitn(11, ' ').nth(10) {
+                    files.push(FileStatus {
+                        path: path.to_string(),
+                        kind: FileStatusKind::Conflicted,
+                        staging: StagingState::Unstaged,
+                    });
+                }
+                continue;
+            }
+            Some(b'?') => {
+                if let Some(path) = record.strip_prefix("? ") {
+                    files.push(FileStatus {
+                        path: path.to_string(),
+                        kind: FileStatusKind::Untracked,
+                        staging: StagingState::Unstaged,
+                    });
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        let xy = xy.as_bytes();
+        if xy.len() != 2 {
+            continue;
+        }
+
+        if let Some(kind) = status_code_to_kind(xy[0]) {
+            files.push(FileStatus {
+                path: path.to_string(),
+                kind,
+                staging: StagingState::Staged,
+            });
+        }
+        if let Some(kind) = status_code_to_kind(xy[1]) {
+            files.push(FileStatus {
+                path: path.to_string(),
+                kind,
+                staging: StagingState::Unstaged,
+            });
+        }
+    }
+
+    files
+}
+
+fn status_code_to_kind(code: u8) -> Option<FileStatusKind> {
+    match code {
+        b'A' | b'C' => Some(FileStatusKind::Untracked),
+        b'M' => Some(FileStatusKind::Modified),
+        b'D' => Some(FileStatusKind::Deleted),
+        b'R' => Some(FileStatusKind::Renamed),
+        b'T' => Some(FileStatusKind::Typechange),
+        _ => None,
+    }
+}
+
+/// `git push --porcelain` の `<flag>\t<from>:<to>\t<summary>` 行を解析する。
+fn parse_porcelain_push(stdout: &str) -> Vec<PushRefUpdate> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let flag = fields.next()?;
+            let (from, to) = fields.next()?.split_once(':')?;
+            let summary = fields.next().unwrap_or_default();
+            let reason = summary
+                .rsplit_once('(')
+                .map(|(_, rest)| rest.trim_end_matches(')').to_string());
+
+            let status = match flag {
+                "*" => PushRefStatus::Created,
+                " " => PushRefStatus::Updated,
+                "+" => PushRefStatus::Forced,
+                "-" => PushRefStatus::Deleted,
+                "=" => PushRefStatus::UpToDate,
+                "!" => match reason.as_deref() {
+                    Some("non-fast-forward" | "fetch first") => {
+                        PushRefStatus::RejectedNonFastForward
+                    }
+                    Some("stale info") => PushRefStatus::RejectedStale,
+                    _ => PushRefStatus::Rejected,
+                },
+                _ => return None,
+            };
+            let message = (status == PushRefStatus::Rejected)
+                .then_some(reason)
+                .flatten();
+            Some(PushRefUpdate {
+                local_ref: (!from.is_empty()).then(|| from.to_string()),
+                remote_ref: to.to_string(),
+                status,
+                message,
+            })
+        })
+        .collect()
+}
+
+/// `ahead 1, behind 2` 形式の `%(upstream:track,nobracket)` を解析する。
+fn parse_upstream_track(track: &str) -> (u32, u32) {
+    let mut ahead = 0;
+    let mut behind = 0;
+    for part in track.split(", ") {
+        if let Some(n) = part.strip_prefix("ahead ") {
+            ahead = n.parse().unwrap_or(0);
+        } else if let Some(n) = part.strip_prefix("behind ") {
+            behind = n.parse().unwrap_or(0);
+        }
+    }
+    (ahead, behind)
+}
+
+fn parse_numstat(output: &str) -> HashMap<String, (u32, u32)> {
+    output
+        .split('\0')
+        .filter_map(|entry| {
+            let mut fields = entry.trim_start_matches('\n').splitn(3, '\t');
+            let additions = fields.next()?.parse().unwrap_or(0);
+            let deletions = fields.next()?.parse().unwrap_or(0);
+            let path = fields.next()?;
+            Some((path.to_string(), (additions, deletions)))
+        })
+        .collect()
+}
+
+struct PathChange {
+    oid: String,
+    path: String,
+    previous_path: Option<String>,
+}
+
+/// Parse `git log -z --name-status --format=%x01%H -- <path>`, tracking the
+/// path backwards through renames. Merges list no changes and keep the path.
+fn parse_path_log(output: &str, path: &str) -> Vec<PathChange> {
+    let mut current = path.to_string();
+    let mut changes = Vec::new();
+
+    for record in output.split('\x01').filter(|r| !r.is_empty()) {
+        let mut fields = record
+            .split('\0')
+            .map(|field| field.trim_matches('\n'))
+            .filter(|field| !field.is_empty());
+        let Some(oid) = fields.next() else {
+            continue;
+        };
+        let status = fields.next().unwrap_or("");
+        let paths: Vec<&str> = fields.collect();
+
+        let mut previous_path = None;
+        match (status.chars().next(), paths.as_slice()) {
+            (Some('R' | 'C'), [old, new]) => {
+                current = new.to_string();
+                previous_path = Some(old.to_string());
+            }
+            (Some(_), [changed]) => current = changed.to_string(),
+            _ => {}
+        }
+        changes.push(PathChange {
+            oid: oid.to_string(),
+            path: current.clone(),
+            previous_path: previous_path.clone(),
+        });
+        if let Some(previous) = previous_path {
+            current = previous;
+        }
+    }
+
+    changes
+}
+
+/// Find the path `path` was renamed from in `git diff -z --name-status -M`.
+fn parse_rename_source(output: &str, path: &str) -> Option<String> {
+    let mut fields = output.split('\0').filter(|field| !field.is_empty());
+    while let Some(status) = fields.next() {
+        if status.starts_with('R') {
+            let old = fields.next()?;
+            if fields.next()? == path {
+                return Some(old.to_string());
+            }
+        } else if status.starts_with('C') {
+            fields.nth(1)?;
+        } else {
+            fields.next()?;
+        }
+    }
+    None
+}
+
+/// Parse `git diff` unified output into the same shape git2 produces.
+pub(crate) fn parse_unified_diff(output: &str) -> Vec<FileDiff> {
+    let mut file_diffs: Vec<FileDiff> = Vec::new();
+    let mut old_lineno: u32 = 0;
+    let mut new_lineno: u32 = 0;
+    let mut old_remaining: u32 = 0;
+    let mut new_remaining: u32 = 0;
+
+    for line in output.split_inclusive('\n') {
+        if line.starts_with('\\') {
+            // "\ No newline at end of file": the previous line has no trailing newline
+            if let Some(prev) = file_diffs
+                .last_mut()
+                .and_then(|fd| fd.hunks.last_mut())
+                .and_then(|h| h.lines.last_mut())
+            {
+                if prev.content.ends_with('\n') {
+                    prev.content.pop();
+                }
+            }
+            continue;
+        }
+
+        if old_remaining > 0 || new_remaining > 0 {
+            let Some(current_hunk) = file_diffs.last_mut().and_then(|fd| fd.hunks.last_mut())
+            else {
+                continue;
+            };
+            let (kind, content) = match line.as_bytes().first() {
+                Some(b'+') => (DiffLineKind::Addition, &line[1..]),
+                Some(b'-') => (DiffLineKind::Deletion, &line[1..]),
+                Some(b' ') => (DiffLineKind::Context, &line[1..]),
+                _ => (DiffLineKind::Context, line),
+            };
+            let (old, new) = match kind {
+                DiffLineKind::Addition => {
+                    new_remaining = new_remaining.saturating_sub(1);
+                    new_lineno += 1;
+                    (None, Some(new_lineno - 1))
+                }
+                DiffLineKind::Deletion => {
+                    old_remaining = old_remaining.saturating_sub(1);
+                    old_lineno += 1;
+                    (Some(old_lineno - 1), None)
+                }
+                _ => {
+                    old_remaining = old_remaining.saturating_sub(1);
+                    new_remaining = new_remaining.saturating_sub(1);
+                    old_lineno += 1;
+                    new_lineno += 1;
+                    (Some(old_lineno - 1), Some(new_lineno - 1))
+                }
+            };
+            current_hunk.lines.push(DiffLine {
+                kind,
+                content: content.to_string(),
+                old_lineno: old,
+                new_lineno: new,
+                word_diff: None,
+            });
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            let (old_path, new_path) = parse_diff_header_paths(rest.trim_end_matches('\n'));
+            file_diffs.push(FileDiff {
+                old_path,
+                new_path,
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(file_diff) = file_diffs.last_mut() else {
+            continue;
+        };
+
+        if let Some(path) = line.strip_prefix("--- ") {
+            if let Some(path) = parse_patch_path(path, "a/") {
+                file_diff.old_path = Some(path);
+            }
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            if let Some(path) = parse_patch_path(path, "b/") {
+                file_diff.new_path = Some(path);
+            }
+        } else if let Some((old_start, old_lines, new_start, new_lines)) = parse_hunk_header(line) {
+            file_diff.hunks.push(DiffHunk {
+                header: line.to_string(),
+                old_start,
+                old_lines,
+                new_start,
+                new_lines,
+                lines: Vec::new(),
+            });
+            old_lineno = old_start;
+            new_lineno = new_start;
+            old_remaining = old_lines;
+            new_remaining = new_lines;
+        }
+    }
+
+    file_diffs
+}
+
+fn parse_hunk_header(line: &str) -> Option<(u32, u32, u32, u32)> {
+    let rest = line.strip_prefix("@@ -")?;
+    let end = rest.find(" @@")?;
+    let (old, new) = rest[..end].split_once(" +")?;
+
+    let parse_range = |range: &str| -> Option<(u32, u32)> {
+        match range.split_once(',') {
+            Some((start, lines)) => Some((start.parse().ok()?, lines.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+
+    let (old_start, old_lines) = parse_range(old)?;
+    let (new_start, new_lines) = parse_range(new)?;
+    Some((old_start, old_lines, new_start, new_lines))
+}
+
+/// `--- a/path` / `+++ b/path` のパスを取り出す。`/dev/null` は `None`。
+fn parse_patch_path(raw: &str, prefix: &str) -> Option<String> {
+    let raw = raw.trim_end_matches('\n').trim_end_matches('\t');
+    if raw == "/dev/null" {
+        return None;
+    }
+    let path = unquote_path(raw);
+    Some(
+        path.strip_prefix(prefix)
+            .map(str::to_string)
+            .unwrap_or(path),
+    )
+}
+
+fn parse_diff_header_paths(rest: &str) -> (Option<String>, Option<String>) {
+    if rest.starts_with('"') {
+        let (old, new) = split_quoted_pair(rest);
+        let old = old.strip_prefix("a/").map(str::to_string).unwrap_or(old);
+        let new = new.strip_prefix("b/").map(str::to_string).unwrap_or(new);
+        return (Some(old), Some(new));
+    }
+
+    // "a/<path> b/<path>": both sides are identical unless the file was renamed
+    let half = rest.len().saturating_sub(1) / 2;
+    if rest.is_char_boundary(half) && rest.is_char_boundary(half + 1) {
+        let (old, new) = (&rest[..half], &rest[(half + 1).min(rest.len())..]);
+        if let (Some(old), Some(new)) = (old.strip_prefix("a/"), new.strip_prefix("b/")) {
+            if old == new {
+                return (Some(old.to_string()), Some(new.to_string()));
+            }
+        }
+    }
+
+    match rest.split_once(" b/") {
+        Some((old, new)) => (
+            Some(old.trim_start_matches("a/").to_string()),
+            Some(new.to_string()),
+        ),
+        None => (None, None),
+    }
+}
+
+fn split_quoted_pair(rest: &str) -> (String, String) {
+    let mut end = 1;
+    let bytes = rest.as_bytes();
+    while end < bytes.len() {
+        match bytes[end] {
+            b'\\' => end += 2,
+            b'"' => break,
+            _ => end += 1,
+        }
+    }
+    let end = (end + 1).min(rest.len());
+    let old = unquote_path(&rest[..end]);
+    let new = unquote_path(rest[end..].trim_start());
+    (old, new)
+}
+
+/// git の C 形式クォート (`"a/t\tab"`) を解除する。
+fn unquote_path(raw: &str) -> String {
+    let Some(inner) = raw.strip_prefix('"').and_then(|s| s.strip_suffix('"')) else {
+        return raw.to_string();
+    };
+
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.bytes().peekable();
+    while let Some(b) = chars.next() {
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        match chars.next() {
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'r') => bytes.push(b'\r'),
+            Some(d @ b'0'..=b'7') => {
+                let mut value = u32::from(d - b'0');
+                for _ in 0..2 {
+                    if let Some(&next @ b'0'..=b'7') = chars.peek() {
+                        value = value * 8 + u32::from(next - b'0');
+                        chars.next();
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => bytes.push(other),
+            None => {}
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_unified_diff_modified_file() {
+        let output = "diff --git a/src/main.rs b/src/main.rs\n\
+index 1111111..2222222 100644\n\
+--- a/src/main.rs\n\
++++ b/src/main.rs\n\
+@@ -1,2 +1,2 @@ fn main()\n\
+ keep\n\
+-old\n\
++new\n";
+        let diffs = parse_unified_diff(output);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].old_path.as_deref(), Some("src/main.rs"));
+        assert_eq!(diffs[0].new_path.as_deref(), Some("src/main.rs"));
+
+        let hunk = &diffs[0].hunks[0];
+        assert_eq!(hunk.header, "@@ -1,2 +1,2 @@ fn main()\n");
+        assert_eq!((hunk.old_start, hunk.old_lines), (1, 2));
+        assert_eq!(hunk.lines.len(), 3);
+        assert_eq!(hunk.lines[1].kind, DiffLineKind::Deletion);
+        assert_eq!(hunk.lines[1].old_lineno, Some(2));
+        assert_eq!(hunk.lines[2].kind, DiffLineKind::Addition);
+        assert_eq!(hunk.lines[2].new_lineno, Some(2));
+        assert_eq!(hunk.lines[2].content, "new\n");
+    }
+
+    #[test]
+    fn parse_unified_diff_new_file_without_trailing_newline() {
+        let output = "diff --git a/new.txt b/new.txt\n\
+new file mode 100644\n\
+--- /dev/null\n\
++++ b/new.txt\n\
+@@ -0,0 +1 @@\n\
++hello\n\
+\\ No newline at end of file\n";
+        let diffs = parse_unified_diff(output);
+        assert_eq!(diffs[0].old_path.as_deref(), Some("new.txt"));
+        assert_eq!(diffs[0].new_path.as_deref(), Some("new.txt"));
+        let hunk = &diffs[0].hunks[0];
+        assert_eq!((hunk.new_start, hunk.new_lines), (1, 1));
+        assert_eq!(hunk.lines.len(), 1);
+        assert_eq!(hunk.lines[0].content, "hello");
+    }
+
+    #[test]
+    fn parse_unified_diff_lines_that_look_like_headers() {
+        let output = "diff --git a/a.txt b/a.txt\n\
+--- a/a.txt\n\
++++ b/a.txt\n\
+@@ -1 +1 @@\n\
+---- a/old\n\
+++++ b/new\n";
+        let diffs = parse_unified_diff(output);
+        let lines = &diffs[0].hunks[0].lines;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].content, "--- a/old\n");
+        assert_eq!(lines[1].content, "+++ b/new\n");
+    }
+
+    #[test]
+    fn parse_diff_header_paths_with_spaces_and_quotes() {
+        assert_eq!(
+            parse_diff_header_paths("a/my file.txt b/my file.txt"),
+            (Some("my file.txt".into()), Some("my file.txt".into()))
+        );
+        assert_eq!(
+            parse_diff_header_paths("\"a/tab\\there\" \"b/tab\\there\""),
+            (Some("tab\there".into()), Some("tab\there".into()))
+        );
+    }
+
+    #[test]
+    fn parse_porcelain_status_staged_and_unstaged() {
+        let output = "1 MM N... 100644 100644 100644 aaa bbb both.txt\0\
+1 A. N... 000000 100644 100644 000 ccc added.txt\0\
+u UU N... 100644 100644 100644 100644 a b c conflict.txt\0\
+? new dir/file.txt\0";
+        let files = parse_porcelain_status(output);
+        let summary: Vec<(&str, FileStatusKind, StagingState)> = files
+            .iter()
+            .map(|f| (f.path.as_str(), f.kind, f.staging))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("both.txt", FileStatusKind::Modified, StagingState::Staged),
+                ("both.txt", FileStatusKind::Modified, StagingState::Unstaged),
+                ("added.txt", FileStatusKind::Untracked, StagingState::Staged),
+                (
+                    "conflict.txt",
+                    FileStatusKind::Conflicted,
+                    StagingState::Unstaged
+                ),
+                (
+                    "new dir/file.txt",
+                    FileStatusKind::Untracked,
+                    StagingState::Unstaged
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_upstream_track_variants() {
+        assert_eq!(parse_upstream_track(""), (0, 0));
+        assert_eq!(parse_upstream_track("ahead 2"), (2, 0));
+        assert_eq!(parse_upstream_track("ahead 1, behind 3"), (1, 3));
+        assert_eq!(parse_upstream_track("gone"), (0, 0));
+    }
+
+    #[test]
+    fn parse_path_log_follows_renames() {
+        let output = "\x01ccc\0\nM\0dir/new.txt\0\x01bbb\0\nR100\0old.txt\0dir/new.txt\0\
+\x01mmm\0\x01aaa\0\nA\0old.txt\0";
+        let changes = parse_path_log(output, "dir/new.txt");
+        let paths: Vec<_> = changes
+            .iter()
+            .map(|c| (c.oid.as_str(), c.path.as_str(), c.previous_path.as_deref()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("ccc", "dir/new.txt", None),
+                ("bbb", "dir/new.txt", Some("old.txt")),
+                ("mmm", "old.txt", None),
+                ("aaa", "old.txt", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_rename_source_skips_other_changes() {
+        let output = "M\0a.txt\0C75\0b.txt\0c.txt\0R090\0old.txt\0new.txt\0";
+        assert_eq!(
+            parse_rename_source(output, "new.txt").as_deref(),
+            Some("old.txt")
+        );
+        assert_eq!(parse_rename_source(output, "c.txt"), None);
+    }
+
+    #[test]
+    fn parse_porcelain_push_statuses() {
+        let stdout = "To ../remote.git\n\
+*\trefs/heads/new:refs/heads/new\t[new branch]\n\
+!\trefs/heads/main:refs/heads/main\t[rejected] (non-fast-forward)\n\
+-\t:refs/heads/old\t[deleted]\n\
+!\trefs/tags/v1:refs/tags/v1\t[remote rejected] (hook declined)\n\
+Done\n";
+        let updates = parse_porcelain_push(stdout);
+        assert_eq!(updates.len(), 4);
+        assert_eq!(updates[0].status, PushRefStatus::Created);
+        assert_eq!(updates[1].status, PushRefStatus::RejectedNonFastForward);
+        assert_eq!(updates[2].status, PushRefStatus::Deleted);
+        assert_eq!(updates[2].local_ref, None);
+        assert_eq!(updates[3].status, PushRefStatus::Rejected);
+        assert_eq!(updates[3].message.as_deref(), Some("hook declined"));
+    }
+}
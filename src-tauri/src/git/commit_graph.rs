@@ -0,0 +1,429 @@
+//! Reader for git's commit-graph file (`objects/info/commit-graph` or a split
+//! `commit-graphs/` chain). libgit2 does not expose generation numbers or the
+//! changed-path Bloom filters, so they are read here directly.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::git::error::{GitError, GitResult};
+
+const SIGNATURE: &[u8; 4] = b"CGPH";
+const HEADER_LEN: usize = 8;
+const CHUNK_ENTRY_LEN: usize = 12;
+const BLOOM_HEADER_LEN: usize = 12;
+const BLOOM_SEEDS: (u32, u32) = (0x293a_e76f, 0x7e64_6e2c);
+
+/// One commit-graph file. A split graph has one layer per file in the chain.
+#[derive(Debug)]
+struct Layer {
+    data: Vec<u8>,
+    hash_len: usize,
+    commits: usize,
+    fanout: usize,
+    oids: usize,
+    commit_data: usize,
+    bloom: Option<BloomChunks>,
+}
+
+#[derive(Debug)]
+struct BloomChunks {
+    index: usize,
+    data: usize,
+    data_len: usize,
+    version: u32,
+    num_hashes: u32,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset + 8)?;
+    Some(u64::from_be_bytes(bytes.try_into().ok()?))
+}
+
+impl Layer {
+    fn parse(data: Vec<u8>) -> Option<Self> {
+        if data.len() < HEADER_LEN || &data[..4] != SIGNATURE || data[4] != 1 {
+            return None;
+        }
+        let hash_len = match data[5] {
+            1 => 20,
+            2 => 32,
+            _ => return None,
+        };
+        let chunk_count = data[6] as usize;
+
+        let mut chunks = Vec::with_capacity(chunk_count);
+        for i in 0..=chunk_count {
+            let entry = HEADER_LEN + i * CHUNK_ENTRY_LEN;
+            let id: [u8; 4] = data.get(entry..entry + 4)?.try_into().ok()?;
+            let offset = usize::try_from(read_u64(&data, entry + 4)?).ok()?;
+            chunks.push((id, offset));
+        }
+        let chunk = |name: &[u8; 4]| {
+            chunks
+                .windows(2)
+                .find(|pair| &pair[0].0 == name)
+                .map(|pair| (pair[0].1, pair[1].1.saturating_sub(pair[0].1)))
+        };
+
+        let (fanout, _) = chunk(b"OIDF")?;
+        let (oids, _) = chunk(b"OIDL")?;
+        let (commit_data, _) = chunk(b"CDAT")?;
+        let commits = read_u32(&data, fanout + 255 * 4)? as usize;
+        if data.len() < oids + commits * hash_len {
+            return None;
+        }
+
+        // 読めない Bloom フィルタは無いものとして扱い、世代番号は使う
+        let bloom = match (chunk(b"BIDX"), chunk(b"BDAT")) {
+            (Some((index, _)), Some((bdat, len))) if len >= BLOOM_HEADER_LEN => {
+                match (read_u32(&data, bdat), read_u32(&data, bdat + 4)) {
+                    (Some(version), Some(num_hashes)) => Some(BloomChunks {
+                        index,
+                        data: bdat + BLOOM_HEADER_LEN,
+                        data_len: len - BLOOM_HEADER_LEN,
+                        version,
+                        num_hashes,
+                    }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        Some(Self {
+            data,
+            hash_len,
+            commits,
+            fanout,
+            oids,
+            commit_data,
+            bloom,
+        })
+    }
+
+    fn position(&self, oid: &[u8]) -> Option<usize> {
+        if oid.len() != self.hash_len {
+            return None;
+        }
+        let first = oid[0] as usize;
+        let start = match first {
+            0 => 0,
+            _ => read_u32(&self.data, self.fanout + (first - 1) * 4)? as usize,
+        };
+        let end = read_u32(&self.data, self.fanout + first * 4)? as usize;
+
+        let (mut low, mut high) = (start, end.min(self.commits));
+        while low < high {
+            let mid = (low + high) / 2;
+            let at = self.oids + mid * self.hash_len;
+            match self.data[at..at + self.hash_len].cmp(oid) {
+                std::cmp::Ordering::Equal => return Some(mid),
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+            }
+        }
+        None
+    }
+
+    fn generation(&self, position: usize) -> Option<u32> {
+        // Each entry is the root tree, two parent positions and 8 bytes whose
+        // top 30 bits are the topological level.
+        let entry = self.commit_data + position * (self.hash_len + 16);
+        Some(read_u32(&self.data, entry + self.hash_len + 8)? >> 2)
+    }
+
+    fn bloom_filter(&self, position: usize) -> Option<(&[u8], &BloomChunks)> {
+        let bloom = self.bloom.as_ref()?;
+        let end = read_u32(&self.data, bloom.index + position * 4)? as usize;
+        let start = match position {
+            0 => 0,
+            _ => read_u32(&self.data, bloom.index + (position - 1) * 4)? as usize,
+        };
+        if start > end || end > bloom.data_len {
+            return None;
+        }
+        // 壊れたファイルではチャンク表の長さが実際の中身を超えることがある
+        let filter = self.data.get(bloom.data + start..bloom.data + end)?;
+        Some((filter, bloom))
+    }
+}
+
+/// Parsed commit-graph of a repository.
+#[derive(Debug)]
+pub(crate) struct CommitGraph {
+    layers: Vec<Layer>,
+}
+
+impl CommitGraph {
+    /// Load the commit-graph under `objects_dir`, `None` when there is none.
+    pub(crate) fn open(objects_dir: &Path) -> Option<Self> {
+        let info = objects_dir.join("info");
+        let chain = info.join("commit-graphs").join("commit-graph-chain");
+        let files: Vec<PathBuf> = match fs::read_to_string(&chain) {
+            Ok(content) => content
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(|hash| {
+                    info.join("commit-graphs")
+                        .join(format!("graph-{hash}.graph"))
+                })
+                .collect(),
+            Err(_) => vec![info.join("commit-graph")],
+        };
+
+        let layers = files
+            .iter()
+            .filter_map(|path| Layer::parse(fs::read(path).ok()?))
+            .collect::<Vec<_>>();
+        (!layers.is_empty()).then_some(Self { layers })
+    }
+
+    fn find(&self, oid: &[u8]) -> Option<(&Layer, usize)> {
+        self.layers
+            .iter()
+            .find_map(|layer| Some((layer, layer.position(oid)?)))
+    }
+
+    /// Topological level of a commit: always greater than its parents'.
+    pub(crate) fn generation(&self, oid: &[u8]) -> Option<u32> {
+        let (layer, position) = self.find(oid)?;
+        layer.generation(position)
+    }
+
+    /// Whether `new` can't be a descendant of `old` according to their
+    /// generation numbers. `false` means the graph can't tell.
+    pub(crate) fn cannot_descend(&self, new: &[u8], old: &[u8]) -> bool {
+        match (self.generation(new), self.generation(old)) {
+            (Some(new_gen), Some(old_gen)) => new != old && new_gen <= old_gen,
+            _ => false,
+        }
+    }
+
+    /// Ask the changed-path Bloom filter whether `path` may differ between a
+    /// commit and its first parent. `Some(false)` is definite; `None` means
+    /// the commit or its filter is not in the graph.
+    pub(crate) fn maybe_changed(&self, oid: &[u8], path: &str) -> Option<bool> {
+        let (layer, position) = self.find(oid)?;
+        let (filter, bloom) = layer.bloom_filter(position)?;
+        if filter.is_empty() {
+            return None;
+        }
+
+        let key = path.trim_end_matches('/').as_bytes();
+        let signed = bloom.version == 1;
+        let hash0 = murmur3(BLOOM_SEEDS.0, key, signed);
+        let hash1 = murmur3(BLOOM_SEEDS.1, key, signed);
+        let bits = filter.len() as u64 * 8;
+        let all_set = (0..bloom.num_hashes).all(|i| {
+            let hash = hash0.wrapping_add(i.wrapping_mul(hash1));
+            let bit = u64::from(hash) % bits;
+            filter[(bit / 8) as usize] & (1 << (bit % 8)) != 0
+        });
+        Some(all_set)
+    }
+}
+
+/// Seeded 32-bit murmur3. Version 1 of git's Bloom filters hashed bytes as
+/// signed chars, which only matters for paths with non-ASCII bytes.
+fn murmur3(seed: u32, data: &[u8], signed: bool) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+
+    let byte = |b: u8| if signed { b as i8 as u32 } else { b as u32 };
+    let mut hash = seed;
+    let mut blocks = data.chunks_exact(4);
+    for block in &mut blocks {
+        let mut k = byte(block[0])
+            | (byte(block[1]) << 8)
+            | (byte(block[2]) << 16)
+            | (byte(block[3]) << 24);
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        hash ^= k;
+        hash = hash
+            .rotate_left(13)
+            .wrapping_mul(5)
+            .wrapping_add(0xe654_6b64);
+    }
+
+    let tail = blocks.remainder();
+    if !tail.is_empty() {
+        let mut k = 0u32;
+        for (i, &b) in tail.iter().enumerate().rev() {
+            k ^= byte(b) << (8 * i);
+        }
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        hash ^= k;
+    }
+
+    hash ^= data.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^= hash >> 16;
+    hash
+}
+
+type Stamp = [Option<SystemTime>; 2];
+
+/// Keeps the parsed graph until git rewrites it.
+#[derive(Debug, Default)]
+pub(crate) struct CommitGraphCache {
+    loaded: Mutex<Option<(Stamp, Option<Arc<CommitGraph>>)>>,
+}
+
+impl CommitGraphCache {
+    pub(crate) fn get(&self, objects_dir: &Path) -> Option<Arc<CommitGraph>> {
+        let info = objects_dir.join("info");
+        let modified = |path: PathBuf| fs::metadata(path).and_then(|m| m.modified()).ok();
+        let stamp = [
+            modified(info.join("commit-graph")),
+            modified(info.join("commit-graphs").join("commit-graph-chain")),
+        ];
+
+        let mut loaded = self.loaded.lock().unwrap();
+        match loaded.as_ref() {
+            Some((cached, graph)) if *cached == stamp => graph.clone(),
+            _ => {
+                let graph = CommitGraph::open(objects_dir).map(Arc::new);
+                *loaded = Some((stamp, graph.clone()));
+                graph
+            }
+        }
+    }
+}
+
+/// Write a commit-graph of every reachable commit with changed-path Bloom
+/// filters.
+pub fn write(workdir: &Path) -> GitResult<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(workdir)
+        .args(["commit-graph", "write", "--reachable", "--changed-paths"])
+        .output()
+        .map_err(|e| GitError::CommitGraphFailed(Box::new(e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(GitError::CommitGraphFailed(stderr.into()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn oid(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn murmur3_matches_git() {
+        // Values from git's t0095-bloom.sh
+        assert_eq!(murmur3(0, b"", false), 0x0000_0000);
+        assert_eq!(murmur3(0, b"Hello world!", false), 0x627b_0c2c);
+        assert_eq!(
+            murmur3(0, b"The quick brown fox jumps over the lazy dog", false),
+            0x2e4f_f723
+        );
+    }
+
+    /// A repository with a commit-graph of three commits, each adding the
+    /// file it is paired with.
+    fn repo_with_graph() -> (tempfile::TempDir, Vec<(Vec<u8>, &'static str)>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        git(path, &["init", "-q"]);
+        git(path, &["config", "user.name", "Test User"]);
+        git(path, &["config", "user.email", "test@example.com"]);
+        let mut commits = Vec::new();
+        for file in ["a.txt", "src/b.txt", "src/c.txt"] {
+            let file_path = path.join(file);
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(&file_path, file).unwrap();
+            git(path, &["add", "."]);
+            git(path, &["commit", "-q", "-m", file]);
+            commits.push((oid(&git(path, &["rev-parse", "HEAD"])), file));
+        }
+        let objects = path.join(".git").join("objects");
+        assert!(CommitGraph::open(&objects).is_none());
+        write(path).unwrap();
+        (dir, commits)
+    }
+
+    #[test]
+    fn reads_generations_and_bloom_filters() {
+        let (dir, commits) = repo_with_graph();
+        let objects = dir.path().join(".git").join("objects");
+        let graph = CommitGraph::open(&objects).unwrap();
+
+        let generations: Vec<_> = commits
+            .iter()
+            .map(|(oid, _)| graph.generation(oid).unwrap())
+            .collect();
+        assert_eq!(generations, vec![1, 2, 3]);
+        assert!(graph.cannot_descend(&commits[0].0, &commits[2].0));
+        assert!(!graph.cannot_descend(&commits[2].0, &commits[0].0));
+
+        for (oid, file) in &commits {
+            assert_eq!(graph.maybe_changed(oid, file), Some(true));
+        }
+        assert_eq!(graph.maybe_changed(&commits[1].0, "src"), Some(true));
+        // Bloom filters may report false positives, but not for every path.
+        let misses = (0..20)
+            .filter(|i| graph.maybe_changed(&commits[2].0, &format!("other{i}.txt")) == Some(false))
+            .count();
+        assert!(misses > 0);
+    }
+
+    #[test]
+    fn truncated_bloom_chunks_are_not_read() {
+        let (dir, commits) = repo_with_graph();
+        let file = dir
+            .path()
+            .join(".git")
+            .join("objects")
+            .join("info")
+            .join("commit-graph");
+        let data = fs::read(file).unwrap();
+        let layer = Layer::parse(data.clone()).unwrap();
+        let bloom = layer.bloom.as_ref().unwrap();
+        let (index, filters) = (bloom.index, bloom.data);
+        let (last, file) = &commits[2];
+
+        // BDAT の途中で切れていると、その先のフィルタは読まない
+        let graph = CommitGraph {
+            layers: vec![Layer::parse(data[..filters + 1].to_vec()).unwrap()],
+        };
+        assert_eq!(graph.maybe_changed(last, file), None);
+        assert_eq!(graph.generation(last), Some(3));
+
+        // BIDX の途中で切れていても世代番号は使える
+        let graph = CommitGraph {
+            layers: vec![Layer::parse(data[..index + 4].to_vec()).unwrap()],
+        };
+        assert_eq!(graph.maybe_changed(last, file), None);
+        assert_eq!(graph.generation(last), Some(3));
+    }
+}
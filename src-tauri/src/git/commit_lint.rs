@@ -0,0 +1,298 @@
+use std::fmt;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::git::error::{GitError, GitResult};
+use crate::git::release_notes::parse_subject;
+use crate::git::trailers;
+use crate::git::types::{CommitLintConfig, CommitLintRule, CommitLintViolation, LintSeverity};
+
+/// Repository-local rule file, read from the top of the working tree.
+pub const CONFIG_FILE: &str = ".commitlint.toml";
+
+/// Messages git writes itself are never linted.
+const GENERATED_PREFIXES: [&str; 5] = ["Merge ", "Revert \"", "fixup! ", "squash! ", "amend! "];
+
+/// Violations returned when a commit is rejected.
+#[derive(Debug)]
+pub struct CommitLintError(pub Vec<CommitLintViolation>);
+
+impl fmt::Display for CommitLintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<&str> = self.0.iter().map(|v| v.message.as_str()).collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+impl std::error::Error for CommitLintError {}
+
+/// `None` when the repository has no rule file.
+pub fn load_config(workdir: &Path) -> GitResult<Option<CommitLintConfig>> {
+    let path = workdir.join(CONFIG_FILE);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| GitError::ConfigReadFailed(Box::new(e)))?;
+    let config = toml::from_str(&content).map_err(|e| GitError::ConfigReadFailed(Box::new(e)))?;
+    Ok(Some(config))
+}
+
+/// Lint a message against the repository's rules. No rule file means no
+/// violations.
+pub fn lint_message(workdir: &Path, message: &str) -> GitResult<Vec<CommitLintViolation>> {
+    match load_config(workdir)? {
+        Some(config) => lint(message, &config),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Reject the message when any violation is an error. Warnings never block.
+pub(crate) fn enforce(workdir: &Path, message: &str) -> GitResult<()> {
+    let errors: Vec<CommitLintViolation> = lint_message(workdir, message)?
+        .into_iter()
+        .filter(|v| v.severity == LintSeverity::Error)
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(GitError::CommitLintFailed(Box::new(CommitLintError(
+            errors,
+        ))))
+    }
+}
+
+pub fn lint(message: &str, config: &CommitLintConfig) -> GitResult<Vec<CommitLintViolation>> {
+    let ticket = config
+        .ticket_pattern
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(|e| GitError::ConfigReadFailed(Box::new(e)))?;
+
+    let message = message.trim_end();
+    if GENERATED_PREFIXES.iter().any(|p| message.starts_with(p)) {
+        return Ok(Vec::new());
+    }
+
+    let mut violations = Vec::new();
+    let mut report = |rule: CommitLintRule, message: String, line: Option<usize>| {
+        let severity = if config.warnings.contains(&rule) {
+            LintSeverity::Warning
+        } else {
+            LintSeverity::Error
+        };
+        violations.push(CommitLintViolation {
+            rule,
+            severity,
+            message,
+            line,
+        });
+    };
+
+    let lines: Vec<&str> = message.lines().collect();
+    let subject = lines.first().copied().unwrap_or("").trim_end();
+    if subject.trim().is_empty() {
+        report(
+            CommitLintRule::SubjectEmpty,
+            "subject must not be empty".to_string(),
+            Some(1),
+        );
+        return Ok(violations);
+    }
+
+    if let Some(max) = config.subject_max_length {
+        let length = subject.chars().count();
+        if length > max {
+            report(
+                CommitLintRule::SubjectMaxLength,
+                format!("subject is {length} characters, longer than {max}"),
+                Some(1),
+            );
+        }
+    }
+
+    let conventional = config.conventional
+        || !config.types.is_empty()
+        || !config.scopes.is_empty()
+        || config.scope_required;
+    if conventional {
+        match parse_subject(subject) {
+            None => report(
+                CommitLintRule::HeaderFormat,
+                "subject must look like `type(scope): description`".to_string(),
+                Some(1),
+            ),
+            Some(header) => {
+                if !config.types.is_empty() && !config.types.iter().any(|t| t == header.kind) {
+                    report(
+                        CommitLintRule::TypeEnum,
+                        format!(
+                            "type `{}` is not one of {}",
+                            header.kind,
+                            config.types.join(", ")
+                        ),
+                        Some(1),
+                    );
+                }
+                match header.scope {
+                    Some(scope)
+                        if !config.scopes.is_empty()
+                            && !config.scopes.iter().any(|s| s == scope) =>
+                    {
+                        report(
+                            CommitLintRule::ScopeEnum,
+                            format!("scope `{scope}` is not one of {}", config.scopes.join(", ")),
+                            Some(1),
+                        );
+                    }
+                    None if config.scope_required => report(
+                        CommitLintRule::ScopeRequired,
+                        "subject must have a scope".to_string(),
+                        Some(1),
+                    ),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if lines.len() > 1 && !lines[1].trim().is_empty() {
+        report(
+            CommitLintRule::BodyLeadingBlank,
+            "body must be separated from the subject by a blank line".to_string(),
+            Some(2),
+        );
+    }
+
+    if let Some(max) = config.body_max_line_length {
+        for (i, line) in lines.iter().enumerate().skip(1) {
+            // URL は折り返せないので対象外
+            if line.chars().count() > max && !line.contains("://") {
+                report(
+                    CommitLintRule::BodyMaxLineLength,
+                    format!("line {} is longer than {max} characters", i + 1),
+                    Some(i + 1),
+                );
+            }
+        }
+    }
+
+    let trailers = trailers::parse(message);
+    for required in &config.required_trailers {
+        if !trailers
+            .iter()
+            .any(|t| t.key.eq_ignore_ascii_case(required))
+        {
+            report(
+                CommitLintRule::TrailerRequired,
+                format!("missing `{required}` trailer"),
+                None,
+            );
+        }
+    }
+
+    if let Some(ticket) = ticket {
+        if !ticket.is_match(message) {
+            report(
+                CommitLintRule::TicketReference,
+                format!("message must reference a ticket matching `{ticket}`"),
+                None,
+            );
+        }
+    }
+
+    Ok(violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(violations: &[CommitLintViolation]) -> Vec<CommitLintRule> {
+        violations.iter().map(|v| v.rule).collect()
+    }
+
+    #[test]
+    fn lint_checks_conventional_header() {
+        let config = CommitLintConfig {
+            types: vec!["feat".to_string(), "fix".to_string()],
+            scopes: vec!["ui".to_string()],
+            subject_max_length: Some(30),
+            ..Default::default()
+        };
+
+        assert!(lint("feat(ui): add button", &config).unwrap().is_empty());
+        assert_eq!(
+            rules(&lint("add button", &config).unwrap()),
+            vec![CommitLintRule::HeaderFormat]
+        );
+        assert_eq!(
+            rules(&lint("chore(api): tidy up the build scripts", &config).unwrap()),
+            vec![
+                CommitLintRule::SubjectMaxLength,
+                CommitLintRule::TypeEnum,
+                CommitLintRule::ScopeEnum,
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_checks_body_trailers_and_ticket() {
+        let config = CommitLintConfig {
+            body_max_line_length: Some(30),
+            required_trailers: vec!["Signed-off-by".to_string()],
+            ticket_pattern: Some(r"[A-Z]+-\d+".to_string()),
+            warnings: vec![CommitLintRule::BodyMaxLineLength],
+            ..Default::default()
+        };
+
+        let ok = "Fix crash\n\nRefs ABC-12\n\nsigned-off-by: A <a@x.org>";
+        assert!(lint(ok, &config).unwrap().is_empty());
+
+        let violations = lint(
+            "Fix crash\nthis body line is far too long to fit\nsee https://example.com/a/very/long/url",
+            &config,
+        )
+        .unwrap();
+        assert_eq!(
+            rules(&violations),
+            vec![
+                CommitLintRule::BodyLeadingBlank,
+                CommitLintRule::BodyMaxLineLength,
+                CommitLintRule::TrailerRequired,
+                CommitLintRule::TicketReference,
+            ]
+        );
+        assert_eq!(violations[1].severity, LintSeverity::Warning);
+        assert_eq!(violations[1].line, Some(2));
+        assert_eq!(violations[2].severity, LintSeverity::Error);
+    }
+
+    #[test]
+    fn lint_skips_generated_messages() {
+        let config = CommitLintConfig {
+            conventional: true,
+            ..Default::default()
+        };
+        assert!(lint("Merge branch 'feature'", &config).unwrap().is_empty());
+        assert!(lint("fixup! feat: add button", &config).unwrap().is_empty());
+        assert_eq!(
+            rules(&lint("  \n", &config).unwrap()),
+            vec![CommitLintRule::SubjectEmpty]
+        );
+    }
+
+    #[test]
+    fn lint_rejects_invalid_ticket_pattern() {
+        let config = CommitLintConfig {
+            ticket_pattern: Some("[".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            lint("Fix", &config),
+            Err(GitError::ConfigReadFailed(_))
+        ));
+    }
+}
@@ -1,13 +1,19 @@
 use std::path::Path;
 
+use serde::{Deserialize, Serialize};
+
 use crate::git::backend::GitBackend;
+use crate::git::cli_backend::CliBackend;
 use crate::git::error::{GitError, GitResult};
 use crate::git::git2_backend::Git2Backend;
+use crate::git::transfer::{self, TransferControl};
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum BackendKind {
     #[default]
     Git2,
+    Cli,
 }
 
 pub struct GitDispatcher;
@@ -16,6 +22,7 @@ impl GitDispatcher {
     pub fn open(path: impl AsRef<Path>, kind: BackendKind) -> GitResult<Box<dyn GitBackend>> {
         match kind {
             BackendKind::Git2 => Ok(Box::new(Git2Backend::open(path)?)),
+            BackendKind::Cli => Ok(Box::new(CliBackend::open(path)?)),
         }
     }
 
@@ -23,25 +30,40 @@ impl GitDispatcher {
         Self::open(path, BackendKind::default())
     }
 
-    pub fn init(path: impl AsRef<Path>) -> GitResult<Box<dyn GitBackend>> {
+    pub fn init(path: impl AsRef<Path>, kind: BackendKind) -> GitResult<Box<dyn GitBackend>> {
         let path = path.as_ref();
         git2::Repository::init(path).map_err(|e| GitError::InitFailed(Box::new(e)))?;
-        Self::open_default(path)
+        Self::open(path, kind)
     }
 
-    pub fn clone_repo(url: &str, path: impl AsRef<Path>) -> GitResult<Box<dyn GitBackend>> {
+    pub fn clone_repo(
+        url: &str,
+        path: impl AsRef<Path>,
+        kind: BackendKind,
+        control: &TransferControl,
+    ) -> GitResult<Box<dyn GitBackend>> {
         let path = path.as_ref();
-        let output = std::process::Command::new("git")
-            .args(["clone", url, &path.to_string_lossy()])
-            .output()
+        let existed = path.exists();
+        let mut cmd = std::process::Command::new("git");
+        cmd.args(["clone", "--progress", url, &path.to_string_lossy()])
+            .env("GIT_TERMINAL_PROMPT", "0");
+
+        let output = transfer::run_with_progress(&mut cmd, control)
             .map_err(|e| GitError::CloneFailed(Box::new(e)))?;
+        let Some(output) = output else {
+            // 中断された clone が残した作りかけのディレクトリを片付ける
+            if !existed {
+                let _ = std::fs::remove_dir_all(path);
+            }
+            return Err(GitError::Cancelled);
+        };
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr).to_string();
             return Err(GitError::CloneFailed(stderr.into()));
         }
 
-        Self::open_default(path)
+        Self::open(path, kind)
     }
 }
 
@@ -52,14 +74,17 @@ mod tests {
     #[test]
     fn init_creates_repository() {
         let dir = tempfile::tempdir().unwrap();
-        let backend = GitDispatcher::init(dir.path()).unwrap();
+        let backend = GitDispatcher::init(dir.path(), BackendKind::default()).unwrap();
         assert!(backend.workdir().exists());
         assert!(dir.path().join(".git").exists());
     }
 
     #[test]
     fn init_returns_error_for_invalid_path() {
-        let result = GitDispatcher::init("/nonexistent/deeply/nested/path/that/should/fail");
+        let result = GitDispatcher::init(
+            "/nonexistent/deeply/nested/path/that/should/fail",
+            BackendKind::default(),
+        );
         assert!(result.is_err());
     }
 
@@ -75,6 +100,68 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn open_cli_on_initialized_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let canonical = std::fs::canonicalize(dir.path()).unwrap();
+        let backend = GitDispatcher::init(&canonical, BackendKind::Cli).unwrap();
+        let actual = std::fs::canonicalize(backend.workdir()).unwrap();
+        assert_eq!(actual, canonical);
+        assert!(backend.status().unwrap().files.is_empty());
+    }
+
+    #[test]
+    fn open_cli_fails_for_non_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = GitDispatcher::open(dir.path(), BackendKind::Cli);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn clone_repo_reports_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        let backend = GitDispatcher::init(&source, BackendKind::default()).unwrap();
+        let mut config = git2::Repository::open(&source).unwrap().config().unwrap();
+        config.set_str("user.name", "Test User").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        std::fs::write(source.join("file.txt"), "content").unwrap();
+        backend.stage(Path::new("file.txt")).unwrap();
+        backend.commit("initial", false, false, false).unwrap();
+
+        let updates = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = std::sync::Arc::clone(&updates);
+        let control = TransferControl::default().with_progress(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        });
+        let url = format!("file://{}", source.display());
+        let dest = dir.path().join("dest");
+        let cloned = GitDispatcher::clone_repo(&url, &dest, BackendKind::Cli, &control).unwrap();
+
+        assert!(cloned.workdir().join("file.txt").exists());
+        assert!(updates.load(std::sync::atomic::Ordering::SeqCst) > 0);
+    }
+
+    #[test]
+    fn clone_repo_cancelled_leaves_no_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        GitDispatcher::init(&source, BackendKind::default()).unwrap();
+
+        let token = transfer::CancelToken::new();
+        token.cancel();
+        let dest = dir.path().join("dest");
+        let result = GitDispatcher::clone_repo(
+            &source.to_string_lossy(),
+            &dest,
+            BackendKind::default(),
+            &TransferControl::new(token),
+        );
+
+        assert!(matches!(result, Err(GitError::Cancelled)));
+        assert!(!dest.exists());
+    }
+
     #[test]
     fn open_default_fails_for_non_repo() {
         let dir = tempfile::tempdir().unwrap();
@@ -23,6 +23,15 @@ pub enum GitError {
     #[error("failed to commit: {0}")]
     CommitFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
 
+    #[error("commit message rejected: {0}")]
+    CommitLintFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("hook rejected: {0}")]
+    HookRejected(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("identity does not match profile: {0}")]
+    IdentityMismatch(#[source] Box<dyn std::error::Error + Send + Sync>),
+
     #[error("failed to get current branch: {0}")]
     BranchNotFound(#[source] Box<dyn std::error::Error + Send + Sync>),
 
@@ -47,6 +56,9 @@ pub enum GitError {
     #[error("failed to rename branch: {0}")]
     BranchRenameFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
 
+    #[error("failed to set upstream: {0}")]
+    UpstreamFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
+
     #[error("failed to merge branch: {0}")]
     MergeFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
 
@@ -62,6 +74,9 @@ pub enum GitError {
     #[error("failed to manage remote: {0}")]
     RemoteFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
 
+    #[error("operation cancelled")]
+    Cancelled,
+
     #[error("authentication failed: {0}")]
     AuthFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
 
@@ -92,6 +107,15 @@ pub enum GitError {
     #[error("failed to rebase: {0}")]
     RebaseFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
 
+    #[error("failed to absorb: {0}")]
+    AbsorbFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("failed to split commit: {0}")]
+    SplitFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("failed to manage branch stack: {0}")]
+    StackFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
+
     #[error("failed to cherry-pick: {0}")]
     CherryPickFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
 
@@ -119,6 +143,15 @@ pub enum GitError {
     #[error("signing failed: {0}")]
     SigningFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
 
+    #[error("failed to write commit-graph: {0}")]
+    CommitGraphFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("failed to generate release notes: {0}")]
+    ReleaseNotesFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("undo failed: {0}")]
+    UndoFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
+
     #[error("clone failed: {0}")]
     CloneFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
 
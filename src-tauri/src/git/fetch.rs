@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+use crate::git::types::{FetchRefStatus, FetchRefUpdate};
+
+/// Full ref name -> target oid. Symbolic refs are left out.
+pub(crate) type RefSnapshot = BTreeMap<String, String>;
+
+fn is_fetched_ref(name: &str) -> bool {
+    name.starts_with("refs/remotes/") || name.starts_with("refs/tags/")
+}
+
+/// Diff the ref snapshots taken around a fetch into per-ref updates and the
+/// number of commits the fetch brought in.
+///
+/// `is_descendant(new, old)` and `count_new(tips, hide)` are supplied by the
+/// backend; `count_new` counts commits reachable from `tips` but not `hide`.
+pub(crate) fn collect_fetch_updates(
+    before: &RefSnapshot,
+    after: &RefSnapshot,
+    is_descendant: impl Fn(&str, &str) -> bool,
+    count_new: impl Fn(&[&str], &[&str]) -> u32,
+) -> (Vec<FetchRefUpdate>, u32) {
+    let known: Vec<&str> = before.values().map(String::as_str).collect();
+    let mut updates = Vec::new();
+
+    for (name, new) in after.iter().filter(|(name, _)| is_fetched_ref(name)) {
+        let old = before.get(name);
+        let (status, hide) = match old {
+            Some(old) if old == new => continue,
+            Some(old) if is_descendant(new, old) => {
+                (FetchRefStatus::FastForward, vec![old.as_str()])
+            }
+            Some(old) => (FetchRefStatus::Forced, vec![old.as_str()]),
+            None => (FetchRefStatus::Created, known.clone()),
+        };
+        updates.push(FetchRefUpdate {
+            ref_name: name.clone(),
+            old_oid: old.cloned(),
+            new_oid: Some(new.clone()),
+            status,
+            new_commits: count_new(&[new.as_str()], &hide),
+        });
+    }
+
+    for (name, old) in before.iter().filter(|(name, _)| is_fetched_ref(name)) {
+        if !after.contains_key(name) {
+            updates.push(FetchRefUpdate {
+                ref_name: name.clone(),
+                old_oid: Some(old.clone()),
+                new_oid: None,
+                status: FetchRefStatus::Pruned,
+                new_commits: 0,
+            });
+        }
+    }
+
+    let tips: Vec<&str> = updates
+        .iter()
+        .filter_map(|u| u.new_oid.as_deref())
+        .collect();
+    let total = if tips.is_empty() {
+        0
+    } else {
+        count_new(&tips, &known)
+    };
+    (updates, total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entries: &[(&str, &str)]) -> RefSnapshot {
+        entries
+            .iter()
+            .map(|(name, oid)| (name.to_string(), oid.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn classifies_created_updated_forced_and_pruned_refs() {
+        let before = snapshot(&[
+            ("refs/heads/main", "a"),
+            ("refs/remotes/origin/main", "a"),
+            ("refs/remotes/origin/topic", "t"),
+            ("refs/remotes/origin/gone", "g"),
+        ]);
+        let after = snapshot(&[
+            ("refs/heads/main", "a"),
+            ("refs/remotes/origin/main", "b"),
+            ("refs/remotes/origin/topic", "u"),
+            ("refs/remotes/origin/new", "n"),
+            ("refs/tags/v1.0", "v"),
+        ]);
+
+        let (updates, total) = collect_fetch_updates(
+            &before,
+            &after,
+            |new, old| (new, old) == ("b", "a"),
+            |tips, _| tips.len() as u32,
+        );
+
+        let statuses: Vec<_> = updates
+            .iter()
+            .map(|u| (u.ref_name.as_str(), u.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("refs/remotes/origin/main", FetchRefStatus::FastForward),
+                ("refs/remotes/origin/new", FetchRefStatus::Created),
+                ("refs/remotes/origin/topic", FetchRefStatus::Forced),
+                ("refs/tags/v1.0", FetchRefStatus::Created),
+                ("refs/remotes/origin/gone", FetchRefStatus::Pruned),
+            ]
+        );
+        assert_eq!(total, 4);
+    }
+}
@@ -1,5 +1,7 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 use git2::{
     BranchType, DiffFormat, DiffOptions as Git2DiffOptions, Oid, Repository, Sort, StatusOptions,
@@ -7,25 +9,47 @@ use git2::{
 
 use crate::git::auth::create_credentials_callback;
 use crate::git::backend::GitBackend;
+use crate::git::blame;
+use crate::git::branch;
+use crate::git::commit_graph::{self, CommitGraph, CommitGraphCache};
 use crate::git::error::{GitError, GitResult};
+use crate::git::fetch;
+use crate::git::graph::{self, LogCache, WalkChunk};
+use crate::git::hooks::{self, CommitSource, PushUpdate};
+use crate::git::journal::{self, JournalRepo, SnapshotPlan};
+use crate::git::push;
+use crate::git::rebase::{self, RebaseControl};
+use crate::git::rebase_engine;
+use crate::git::release_notes;
 use crate::git::search::{self, CodeSearchResult, CommitSearchResult, FilenameSearchResult};
+use crate::git::stack;
+use crate::git::stash;
 use crate::git::submodule;
+use crate::git::tag;
+use crate::git::trailers;
+use crate::git::transfer::{TransferControl, TransferProgress};
 use crate::git::types::{
-    BlameLine, BlameResult, BranchInfo, CherryPickMode, CherryPickResult, CommitDetail,
-    CommitFileChange, CommitFileStatus, CommitGraphRow, CommitInfo, CommitLogResult, CommitRef,
-    CommitRefKind, CommitResult, CommitStats, ConflictBlock, ConflictFile, ConflictResolution,
-    DiffHunk, DiffLine, DiffLineKind, DiffOptions, FetchResult, FileDiff, FileStatus,
-    FileStatusKind, GitConfigEntry, GitConfigScope, GraphEdge, GraphNodeType, HunkIdentifier,
-    LineRange, LogFilter, MergeBaseContent, MergeKind, MergeOption, MergeResult, PullOption,
-    PushResult, RebaseAction, RebaseResult, RebaseState, RebaseTodoEntry, ReflogEntry, RemoteInfo,
-    RepoStatus, ResetMode, ResetResult, RevertMode, RevertResult, SignatureStatus, StagingState,
-    StashEntry, SubmoduleInfo, TagInfo, WordSegment, WorktreeInfo,
+    BlameLine, BlameOptions, BlameResult, BranchInfo, CherryPickMode, CherryPickResult,
+    CommitDetail, CommitEdit, CommitFileChange, CommitFileStatus, CommitIdentity, CommitInfo,
+    CommitLogResult, CommitRef, CommitRefKind, CommitResult, CommitStats, ConflictBlock,
+    ConflictFile, ConflictResolution, CreateTagOptions, DeleteBranchOptions, DeleteBranchResult,
+    DiffHunk, DiffLine, DiffLineKind, DiffOptions, FetchOptions, FetchResult, FetchTagMode,
+    FileDiff, FileHistoryEntry, FileStatus, FileStatusKind, GitConfigEntry, GitConfigScope,
+    HunkIdentifier, LineRange, LogFilter, MergeBaseContent, MergeKind, MergeOption, MergeResult,
+    OperationEntry, OperationKind, PruneCandidate, PullOption, PushOptions, PushRefStatus,
+    PushRefUpdate, PushResult, RebaseAction, RebaseOptions, RebaseResult, RebaseState,
+    RebaseTodoEntry, ReflogEntry, ReleaseNotes, ReleaseNotesOptions, RemoteInfo, RepoStatus,
+    ResetMode, ResetResult, RevertMode, RevertResult, SignatureOverride, SignatureStatus,
+    StagingState, StashApplyResult, StashDetail, StashEntry, StashOptions, StashState,
+    SubmoduleInfo, TagInfo, WordSegment, WorktreeInfo,
 };
 use crate::git::worktree;
 
 pub struct Git2Backend {
     repo: Mutex<Repository>,
     workdir: PathBuf,
+    log_cache: Mutex<Option<LogCache>>,
+    commit_graph: CommitGraphCache,
 }
 
 impl Git2Backend {
@@ -49,6 +73,110 @@ impl Git2Backend {
         Ok(Self {
             repo: Mutex::new(repo),
             workdir,
+            log_cache: Mutex::new(None),
+            commit_graph: CommitGraphCache::default(),
+        })
+    }
+
+    fn write_commit(
+        &self,
+        message: &str,
+        amend: bool,
+        sign: bool,
+        identity: &CommitIdentity,
+    ) -> GitResult<CommitResult> {
+        let repo = self.repo.lock().unwrap();
+
+        let mut index = repo
+            .index()
+            .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
+
+        let tree_oid = index
+            .write_tree()
+            .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
+
+        let tree = repo
+            .find_tree(tree_oid)
+            .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
+
+        let sig = repo
+            .signature()
+            .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
+        let author = override_signature(&sig, identity.author.as_ref())
+            .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
+        let committer = override_signature(&sig, identity.committer.as_ref())
+            .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
+
+        if amend {
+            let head = repo
+                .head()
+                .map_err(|e| GitError::AmendFailed(Box::new(e)))?;
+            let head_commit = head
+                .peel_to_commit()
+                .map_err(|e| GitError::AmendFailed(Box::new(e)))?;
+
+            if sign {
+                let parents: Vec<git2::Commit> = (0..head_commit.parent_count())
+                    .map(|i| head_commit.parent(i))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| GitError::AmendFailed(Box::new(e)))?;
+                let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+
+                return create_signed_commit(
+                    &repo,
+                    &author,
+                    &committer,
+                    message,
+                    &tree,
+                    &parent_refs,
+                );
+            }
+
+            let oid = head_commit
+                .amend(
+                    Some("HEAD"),
+                    Some(&author),
+                    Some(&committer),
+                    None,
+                    Some(message),
+                    Some(&tree),
+                )
+                .map_err(|e| GitError::AmendFailed(Box::new(e)))?;
+
+            return Ok(CommitResult {
+                oid: oid.to_string(),
+            });
+        }
+
+        let parents: Vec<git2::Commit> = match repo.head() {
+            Ok(head) => {
+                let commit = head
+                    .peel_to_commit()
+                    .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
+                vec![commit]
+            }
+            Err(_) => vec![], // Initial commit -- no parents
+        };
+
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+
+        if sign {
+            return create_signed_commit(&repo, &author, &committer, message, &tree, &parent_refs);
+        }
+
+        let oid = repo
+            .commit(
+                Some("HEAD"),
+                &author,
+                &committer,
+                message,
+                &tree,
+                &parent_refs,
+            )
+            .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
+
+        Ok(CommitResult {
+            oid: oid.to_string(),
         })
     }
 }
@@ -260,82 +388,44 @@ impl GitBackend for Git2Backend {
         Ok(name)
     }
 
-    fn commit(&self, message: &str, amend: bool, sign: bool) -> GitResult<CommitResult> {
-        let repo = self.repo.lock().unwrap();
-
-        let mut index = repo
-            .index()
-            .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
-
-        let tree_oid = index
-            .write_tree()
-            .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
-
-        let tree = repo
-            .find_tree(tree_oid)
-            .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
-
-        let sig = repo
-            .signature()
-            .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
-
-        if amend {
-            let head = repo
-                .head()
-                .map_err(|e| GitError::AmendFailed(Box::new(e)))?;
-            let head_commit = head
-                .peel_to_commit()
-                .map_err(|e| GitError::AmendFailed(Box::new(e)))?;
-
-            if sign {
-                let parents: Vec<git2::Commit> = (0..head_commit.parent_count())
-                    .map(|i| head_commit.parent(i))
-                    .collect::<Result<Vec<_>, _>>()
-                    .map_err(|e| GitError::AmendFailed(Box::new(e)))?;
-                let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
-
-                return create_signed_commit(&repo, &sig, message, &tree, &parent_refs);
-            }
-
-            let oid = head_commit
-                .amend(
-                    Some("HEAD"),
-                    Some(&sig),
-                    Some(&sig),
-                    None,
-                    Some(message),
-                    Some(&tree),
-                )
-                .map_err(|e| GitError::AmendFailed(Box::new(e)))?;
-
-            return Ok(CommitResult {
-                oid: oid.to_string(),
-            });
-        }
+    fn commit(
+        &self,
+        message: &str,
+        amend: bool,
+        sign: bool,
+        skip_hooks: bool,
+    ) -> GitResult<CommitResult> {
+        self.commit_as(message, amend, sign, skip_hooks, &CommitIdentity::default())
+    }
 
-        let parents: Vec<git2::Commit> = match repo.head() {
-            Ok(head) => {
-                let commit = head
-                    .peel_to_commit()
-                    .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
-                vec![commit]
-            }
-            Err(_) => vec![], // Initial commit -- no parents
+    fn commit_as(
+        &self,
+        message: &str,
+        amend: bool,
+        sign: bool,
+        skip_hooks: bool,
+        identity: &CommitIdentity,
+    ) -> GitResult<CommitResult> {
+        let source = if amend {
+            CommitSource::Amend
+        } else {
+            CommitSource::Message
         };
-
-        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
-
-        if sign {
-            return create_signed_commit(&repo, &sig, message, &tree, &parent_refs);
-        }
-
-        let oid = repo
-            .commit(Some("HEAD"), &sig, &sig, message, &tree, &parent_refs)
-            .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
-
-        Ok(CommitResult {
-            oid: oid.to_string(),
-        })
+        let message = hooks::prepare_linted_commit(&self.workdir, message, source, !skip_hooks)?;
+        let result = if amend {
+            let plan = SnapshotPlan {
+                refs: vec!["HEAD".to_string()],
+                index: true,
+                ..SnapshotPlan::default()
+            };
+            journal::journaled(self, OperationKind::Amend, "Amend commit", plan, || {
+                self.write_commit(&message, true, sign, identity)
+            })?
+        } else {
+            self.write_commit(&message, false, sign, identity)?
+        };
+        hooks::post_commit(&self.workdir);
+        Ok(result)
     }
 
     fn list_branches(&self) -> GitResult<Vec<BranchInfo>> {
@@ -367,6 +457,13 @@ impl GitBackend for Git2Backend {
                 .ok()
                 .and_then(|u| u.name().ok().flatten().map(|s| s.to_string()));
 
+            let upstream_gone = !is_remote
+                && upstream.is_none()
+                && branch
+                    .get()
+                    .name()
+                    .is_some_and(|refname| repo.branch_upstream_name(refname).is_ok());
+
             let (ahead_count, behind_count) = if !is_remote {
                 branch
                     .get()
@@ -391,6 +488,7 @@ impl GitBackend for Git2Backend {
                 is_remote,
                 remote_name,
                 upstream,
+                upstream_gone,
                 ahead_count: ahead_count as u32,
                 behind_count: behind_count as u32,
             });
@@ -420,15 +518,59 @@ impl GitBackend for Git2Backend {
         Ok(())
     }
 
-    fn delete_branch(&self, name: &str) -> GitResult<()> {
-        let repo = self.repo.lock().unwrap();
-        let mut branch = repo
-            .find_branch(name, BranchType::Local)
-            .map_err(|e| GitError::BranchDeleteFailed(Box::new(e)))?;
-        branch
-            .delete()
-            .map_err(|e| GitError::BranchDeleteFailed(Box::new(e)))?;
-        Ok(())
+    fn delete_branch(
+        &self,
+        name: &str,
+        options: &DeleteBranchOptions,
+        control: &TransferControl,
+    ) -> GitResult<DeleteBranchResult> {
+        let unmerged_commits = |upstream: Option<&str>| self.unmerged_commits(name, upstream);
+        let delete_local = || {
+            let plan = SnapshotPlan {
+                refs: vec![format!("refs/heads/{name}")],
+                ..SnapshotPlan::default()
+            };
+            let description = format!("Delete branch {name}");
+            journal::journaled(self, OperationKind::DeleteBranch, description, plan, || {
+                let repo = self.repo.lock().unwrap();
+                let mut branch = repo
+                    .find_branch(name, BranchType::Local)
+                    .map_err(|e| GitError::BranchDeleteFailed(Box::new(e)))?;
+                branch
+                    .delete()
+                    .map_err(|e| GitError::BranchDeleteFailed(Box::new(e)))?;
+                Ok(())
+            })
+        };
+        branch::delete_branch(self, name, options, control, unmerged_commits, delete_local)
+    }
+
+    fn delete_upstream_branch(
+        &self,
+        name: &str,
+        options: &DeleteBranchOptions,
+        control: &TransferControl,
+    ) -> GitResult<DeleteBranchResult> {
+        branch::delete_upstream(self, name, options, control, |upstream| {
+            self.unmerged_commits(name, upstream)
+        })
+    }
+
+    fn list_prune_candidates(&self, base: Option<&str>) -> GitResult<Vec<PruneCandidate>> {
+        branch::prune_candidates(self, base, |name, base| {
+            let repo = self.repo.lock().unwrap();
+            let oid = |name: &str| {
+                repo.find_branch(name, BranchType::Local)
+                    .ok()
+                    .and_then(|b| b.get().target())
+            };
+            match (oid(name), oid(base)) {
+                (Some(branch), Some(base)) => {
+                    branch == base || repo.graph_descendant_of(base, branch).unwrap_or(false)
+                }
+                _ => false,
+            }
+        })
     }
 
     fn rename_branch(&self, old_name: &str, new_name: &str) -> GitResult<()> {
@@ -442,6 +584,58 @@ impl GitBackend for Git2Backend {
         Ok(())
     }
 
+    fn create_branch_from(&self, name: &str, start_point: &str) -> GitResult<()> {
+        let repo = self.repo.lock().unwrap();
+        let commit = repo
+            .revparse_single(start_point)
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(|e| GitError::BranchCreateFailed(Box::new(e)))?;
+        let mut branch = repo
+            .branch(name, &commit, false)
+            .map_err(|e| GitError::BranchCreateFailed(Box::new(e)))?;
+
+        let remote_branch = start_point
+            .strip_prefix("refs/remotes/")
+            .unwrap_or(start_point);
+        if repo.find_branch(remote_branch, BranchType::Remote).is_ok() {
+            branch
+                .set_upstream(Some(remote_branch))
+                .map_err(|e| GitError::BranchCreateFailed(Box::new(e)))?;
+        }
+        Ok(())
+    }
+
+    fn set_upstream(&self, branch_name: &str, upstream: Option<&str>) -> GitResult<()> {
+        let repo = self.repo.lock().unwrap();
+        let mut branch = repo
+            .find_branch(branch_name, BranchType::Local)
+            .map_err(|e| GitError::UpstreamFailed(Box::new(e)))?;
+        let upstream = upstream.map(|u| u.strip_prefix("refs/remotes/").unwrap_or(u));
+        if upstream.is_none() {
+            // Unsetting a branch without upstream is a no-op
+            let key = format!("branch.{branch_name}.merge");
+            let configured = repo
+                .config()
+                .map(|config| config.get_string(&key).is_ok())
+                .map_err(|e| GitError::UpstreamFailed(Box::new(e)))?;
+            if !configured {
+                return Ok(());
+            }
+        }
+        branch
+            .set_upstream(upstream)
+            .map_err(|e| GitError::UpstreamFailed(Box::new(e)))?;
+        Ok(())
+    }
+
+    fn checkout_remote_branch(
+        &self,
+        remote_branch: &str,
+        local_name: Option<&str>,
+    ) -> GitResult<String> {
+        branch::checkout_remote_branch(self, remote_branch, local_name)
+    }
+
     fn merge_branch(&self, branch_name: &str, option: MergeOption) -> GitResult<MergeResult> {
         let repo = self.repo.lock().unwrap();
 
@@ -490,6 +684,7 @@ impl GitBackend for Git2Backend {
             .map_err(|e| GitError::MergeFailed(Box::new(e)))?;
             repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))
                 .map_err(|e| GitError::MergeFailed(Box::new(e)))?;
+            hooks::post_merge(&self.workdir);
 
             return Ok(MergeResult {
                 kind: MergeKind::FastForward,
@@ -505,30 +700,93 @@ impl GitBackend for Git2Backend {
         self.merge_normal_commit(&repo, branch_name, &annotated)
     }
 
-    fn fetch(&self, remote_name: &str) -> GitResult<FetchResult> {
+    fn fetch(
+        &self,
+        remote_name: &str,
+        options: &FetchOptions,
+        control: &TransferControl,
+    ) -> GitResult<FetchResult> {
         let repo = self.repo.lock().unwrap();
-        let mut remote = repo
-            .find_remote(remote_name)
-            .map_err(|e| GitError::FetchFailed(Box::new(e)))?;
+        let remote_names: Vec<String> = if options.all_remotes {
+            repo.remotes()
+                .map_err(|e| GitError::FetchFailed(Box::new(e)))?
+                .iter()
+                .flatten()
+                .map(str::to_string)
+                .collect()
+        } else {
+            vec![remote_name.to_string()]
+        };
+
+        let before = ref_snapshot(&repo).map_err(|e| GitError::FetchFailed(Box::new(e)))?;
+        for name in &remote_names {
+            let mut remote = repo
+                .find_remote(name)
+                .map_err(|e| GitError::FetchFailed(Box::new(e)))?;
+
+            let mut fetch_opts = git2::FetchOptions::new();
+            fetch_opts.remote_callbacks(transfer_callbacks(control));
+            fetch_opts.prune(if options.prune {
+                git2::FetchPrune::On
+            } else {
+                git2::FetchPrune::Off
+            });
+            fetch_opts.download_tags(match options.tags {
+                FetchTagMode::Auto => git2::AutotagOption::Auto,
+                FetchTagMode::All => git2::AutotagOption::All,
+                FetchTagMode::None => git2::AutotagOption::None,
+            });
+            if let Some(depth) = options.depth {
+                fetch_opts.depth(depth as i32);
+            }
 
-        let mut fetch_opts = git2::FetchOptions::new();
-        let mut callbacks = git2::RemoteCallbacks::new();
-        callbacks.credentials(create_credentials_callback());
-        fetch_opts.remote_callbacks(callbacks);
-        fetch_opts.prune(git2::FetchPrune::On);
+            if control.is_cancelled() {
+                return Err(GitError::Cancelled);
+            }
+            remote
+                .fetch(&[] as &[&str], Some(&mut fetch_opts), None)
+                .map_err(|e| transfer_error(control, e, GitError::FetchFailed))?;
+        }
+        let after = ref_snapshot(&repo).map_err(|e| GitError::FetchFailed(Box::new(e)))?;
+
+        let graph = self.commit_graph(&repo);
+        let (updates, new_commits) = fetch::collect_fetch_updates(
+            &before,
+            &after,
+            |new, old| is_descendant(&repo, graph.as_deref(), new, old),
+            |tips, hide| count_new_commits(&repo, tips, hide),
+        );
 
-        remote
-            .fetch(&[] as &[&str], Some(&mut fetch_opts), None)
-            .map_err(|e| GitError::FetchFailed(Box::new(e)))?;
+        // libgit2 ignores fetch.writeCommitGraph, so honour it here like git does.
+        let write_graph = repo
+            .config()
+            .and_then(|config| config.get_bool("fetch.writeCommitGraph"))
+            .unwrap_or(false);
+        if write_graph && new_commits > 0 {
+            let _ = commit_graph::write(&self.workdir);
+        }
 
         Ok(FetchResult {
             remote_name: remote_name.to_string(),
+            updates,
+            new_commits,
         })
     }
 
-    fn pull(&self, remote_name: &str, option: PullOption) -> GitResult<MergeResult> {
-        self.fetch(remote_name)?;
+    fn pull(
+        &self,
+        remote_name: &str,
+        option: PullOption,
+        control: &TransferControl,
+    ) -> GitResult<MergeResult> {
+        self.fetch(remote_name, &FetchOptions::default(), control)?;
+        if control.is_cancelled() {
+            return Err(GitError::Cancelled);
+        }
+        self.pull_fetched(remote_name, option)
+    }
 
+    fn pull_fetched(&self, remote_name: &str, option: PullOption) -> GitResult<MergeResult> {
         let (remote_ref_name, target_oid, analysis) = {
             let repo = self.repo.lock().unwrap();
             let head = repo.head().map_err(|e| GitError::PullFailed(Box::new(e)))?;
@@ -570,41 +828,182 @@ impl GitBackend for Git2Backend {
         }
     }
 
-    fn push(&self, remote_name: &str) -> GitResult<PushResult> {
+    fn push(
+        &self,
+        remote_name: &str,
+        options: &PushOptions,
+        control: &TransferControl,
+    ) -> GitResult<PushResult> {
         let repo = self.repo.lock().unwrap();
+        let graph = self.commit_graph(&repo);
         let mut remote = repo
             .find_remote(remote_name)
             .map_err(|e| GitError::PushFailed(Box::new(e)))?;
 
-        let head = repo.head().map_err(|e| GitError::PushFailed(Box::new(e)))?;
-        let branch_name = head
-            .shorthand()
-            .ok_or_else(|| GitError::PushFailed("HEAD has no name".into()))?
-            .to_string();
+        let current_branch = repo
+            .head()
+            .ok()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand().map(str::to_string));
+        let refs = push::plan_push(
+            options,
+            current_branch.as_deref(),
+            |name| repo.find_reference(name).is_ok(),
+            || {
+                repo.tag_names(None)
+                    .map(|names| names.iter().flatten().map(str::to_string).collect())
+                    .unwrap_or_default()
+            },
+        )
+        .map_err(|e| GitError::PushFailed(e.into()))?;
 
-        let refspec = format!("refs/heads/{branch_name}:refs/heads/{branch_name}");
+        if control.is_cancelled() {
+            return Err(GitError::Cancelled);
+        }
+        let remote_heads = probe_push_targets(&mut remote, &refs, control)?;
+        let url = remote.url().unwrap_or("").to_string();
+
+        // git と同様に送信前に各 ref の結果を判定し、受理されるものだけを送る
+        let mut updates = Vec::new();
+        let mut refspecs = Vec::new();
+        let mut hook_updates = Vec::new();
+        let mut leases: HashMap<String, Option<String>> = HashMap::new();
+        for push_ref in &refs {
+            let new = match &push_ref.src {
+                Some(src) => Some(
+                    repo.revparse_single(src)
+                        .map_err(|e| GitError::PushFailed(Box::new(e)))?
+                        .id()
+                        .to_string(),
+                ),
+                None => None,
+            };
+            let lease = options.force_with_lease.then(|| {
+                push::tracking_ref(remote_name, &push_ref.dst)
+                    .and_then(|tracking| repo.refname_to_id(&tracking).ok())
+                    .map(|oid| oid.to_string())
+            });
+            let remote_old = remote_heads.get(&push_ref.dst);
+            let (status, message) = push::classify_update(
+                push_ref,
+                remote_old.map(String::as_str),
+                new.as_deref(),
+                lease.as_ref().map(Option::as_deref),
+                |new, old| is_descendant(&repo, graph.as_deref(), new, old),
+            );
+
+            let sends = match status {
+                PushRefStatus::Created | PushRefStatus::Updated | PushRefStatus::Forced => true,
+                PushRefStatus::Deleted => remote_old.is_some(),
+                _ => false,
+            };
+            if sends {
+                refspecs.push(push_ref.refspec(options.force_with_lease));
+                if let Some(expected) = lease {
+                    leases.insert(push_ref.dst.clone(), expected);
+                }
+                hook_updates.push(PushUpdate {
+                    local: push_ref.src.clone().zip(new),
+                    remote_ref: push_ref.dst.clone(),
+                    remote_oid: remote_old.cloned(),
+                });
+            }
+            updates.push(PushRefUpdate {
+                local_ref: push_ref.src.clone(),
+                remote_ref: push_ref.dst.clone(),
+                status,
+                message,
+            });
+        }
 
-        let mut push_opts = git2::PushOptions::new();
-        let mut callbacks = git2::RemoteCallbacks::new();
-        callbacks.credentials(create_credentials_callback());
-        push_opts.remote_callbacks(callbacks);
+        if !options.no_verify && !hook_updates.is_empty() {
+            hooks::pre_push(&self.workdir, remote_name, &url, &hook_updates)?;
+        }
 
-        remote
-            .push(&[&refspec], Some(&mut push_opts))
-            .map_err(|e| GitError::PushFailed(Box::new(e)))?;
+        if !refspecs.is_empty() {
+            let rejections = RefCell::new(HashMap::new());
+            let mut callbacks = transfer_callbacks(control);
+            callbacks.push_negotiation(|proposed| {
+                if control.is_cancelled() {
+                    return Err(git2::Error::from_str("operation cancelled"));
+                }
+                // 判定から送信までの間にリモートが動いていないかを再確認する
+                for update in proposed {
+                    let Some(expected) = update.dst_refname().and_then(|dst| leases.get(dst))
+                    else {
+                        continue;
+                    };
+                    let current = (!update.src().is_zero()).then(|| update.src().to_string());
+                    if current != *expected {
+                        return Err(git2::Error::from_str("stale info"));
+                    }
+                }
+                Ok(())
+            });
+            callbacks.push_update_reference(|refname, status| {
+                if let Some(message) = status {
+                    rejections
+                        .borrow_mut()
+                        .insert(refname.to_string(), message.to_string());
+                }
+                Ok(())
+            });
+            let mut push_opts = git2::PushOptions::new();
+            push_opts.remote_callbacks(callbacks);
+
+            remote
+                .push(&refspecs, Some(&mut push_opts))
+                .map_err(|e| transfer_error(control, e, GitError::PushFailed))?;
+            drop(push_opts);
+
+            let mut rejections = rejections.into_inner();
+            for update in &mut updates {
+                if let Some(message) = rejections.remove(&update.remote_ref) {
+                    update.status = PushRefStatus::Rejected;
+                    update.message = Some(message);
+                }
+            }
+        }
 
-        // upstream が未設定の場合は自動設定
-        drop(head);
-        if let Ok(mut local_branch) = repo.find_branch(&branch_name, BranchType::Local) {
-            if local_branch.upstream().is_err() {
-                let upstream_name = format!("{remote_name}/{branch_name}");
-                let _ = local_branch.set_upstream(Some(&upstream_name));
+        // 明示的な対象指定がない通常の push では upstream が未設定の場合に自動設定
+        let auto_upstream = !options.has_explicit_target();
+        for (push_ref, update) in refs.iter().zip(&updates) {
+            if update.status == PushRefStatus::Deleted {
+                if let Some(mut tracking) = push::tracking_ref(remote_name, &push_ref.dst)
+                    .and_then(|name| repo.find_reference(&name).ok())
+                {
+                    let _ = tracking.delete();
+                }
+                continue;
+            }
+            if update.status.is_rejected() {
+                continue;
+            }
+            let Some((local, remote_branch)) = push_ref.upstream_pair() else {
+                continue;
+            };
+            let Ok(mut config) = repo.config() else {
+                continue;
+            };
+            let remote_key = format!("branch.{local}.remote");
+            if options.set_upstream || (auto_upstream && config.get_string(&remote_key).is_err()) {
+                let _ = config.set_str(&remote_key, remote_name);
+                let _ = config.set_str(
+                    &format!("branch.{local}.merge"),
+                    &format!("refs/heads/{remote_branch}"),
+                );
             }
         }
 
+        let branch = refs
+            .iter()
+            .find_map(|r| r.src.as_deref()?.strip_prefix("refs/heads/"))
+            .unwrap_or_default()
+            .to_string();
         Ok(PushResult {
             remote_name: remote_name.to_string(),
-            branch: branch_name,
+            branch,
+            updates,
         })
     }
 
@@ -655,47 +1054,32 @@ impl GitBackend for Git2Backend {
         limit: usize,
         skip: usize,
     ) -> GitResult<CommitLogResult> {
-        let mut commits = {
+        let (mut commits, graph) = {
             let repo = self.repo.lock().unwrap();
-
-            let mut revwalk = repo
-                .revwalk()
-                .map_err(|e| GitError::LogFailed(Box::new(e)))?;
-            revwalk
-                .set_sorting(Sort::TIME | Sort::TOPOLOGICAL)
-                .map_err(|e| GitError::LogFailed(Box::new(e)))?;
-            revwalk
-                .push_head()
+            let head = repo
+                .refname_to_id("HEAD")
                 .map_err(|e| GitError::LogFailed(Box::new(e)))?;
-
+            let graph = self.commit_graph(&repo);
+            let graph = graph.as_deref();
+
+            let (mut commits, graph) = graph::commit_log_page(
+                &self.log_cache,
+                filter,
+                &head.to_string(),
+                limit,
+                skip,
+                |tip, hidden| log_order(&repo, tip, hidden),
+                |oids, wanted| walk_commits(&repo, graph, oids, filter, wanted),
+            )?;
+
+            // Refs move without touching the walk, so decorate every page afresh.
             let ref_map = build_ref_map(&repo);
-            let mut commits = Vec::new();
-            let mut skipped = 0;
-
-            for oid_result in revwalk {
-                let oid = oid_result.map_err(|e| GitError::LogFailed(Box::new(e)))?;
-                let commit = repo
-                    .find_commit(oid)
-                    .map_err(|e| GitError::LogFailed(Box::new(e)))?;
-
-                if !matches_filter(&repo, &commit, filter) {
-                    continue;
-                }
-
-                if skipped < skip {
-                    skipped += 1;
-                    continue;
-                }
-
-                let info = commit_to_info(&commit, &ref_map);
-                commits.push(info);
-
-                if commits.len() >= limit {
-                    break;
+            for commit in &mut commits {
+                if let Ok(oid) = Oid::from_str(&commit.oid) {
+                    commit.refs = ref_map.get(&oid).cloned().unwrap_or_default();
                 }
             }
-
-            commits
+            (commits, graph)
         };
 
         let oids: Vec<String> = commits.iter().map(|c| c.oid.clone()).collect();
@@ -712,8 +1096,6 @@ impl GitBackend for Git2Backend {
             }
         }
 
-        let graph = build_graph(&commits);
-
         Ok(CommitLogResult { commits, graph })
     }
 
@@ -821,9 +1203,23 @@ impl GitBackend for Git2Backend {
         Ok(file_diffs)
     }
 
-    fn get_blame(&self, path: &str, commit_oid: Option<&str>) -> GitResult<BlameResult> {
+    fn get_blame(
+        &self,
+        path: &str,
+        commit_oid: Option<&str>,
+        options: &BlameOptions,
+    ) -> GitResult<BlameResult> {
         let repo = self.repo.lock().unwrap();
 
+        if blame::needs_git(&self.workdir, options) {
+            let rev = commit_oid.unwrap_or("HEAD");
+            return Ok(BlameResult {
+                path: path.to_string(),
+                commit_oid: commit_oid.map(str::to_string),
+                lines: blame::blame_with_git(&self.workdir, rev, path, options)?,
+            });
+        }
+
         let mut opts = git2::BlameOptions::new();
         if let Some(oid_str) = commit_oid {
             let oid = Oid::from_str(oid_str).map_err(|e| GitError::BlameFailed(Box::new(e)))?;
@@ -836,6 +1232,7 @@ impl GitBackend for Git2Backend {
 
         let mut lines = Vec::new();
         let mut last_oid: Option<Oid> = None;
+        let mut summaries: HashMap<Oid, String> = HashMap::new();
 
         // AR-002: Use the specified commit's tree, not always HEAD
         let tree = if let Some(oid_str) = commit_oid {
@@ -875,6 +1272,19 @@ impl GitBackend for Git2Backend {
             let author_date = sig.when().seconds();
             let oid_str = hunk_oid.to_string();
             let short_oid = oid_str[..7].to_string();
+            let original_path = hunk
+                .path()
+                .map_or_else(|| path.to_string(), |p| p.to_string_lossy().into_owned());
+            let original_start = hunk.orig_start_line();
+            let summary = summaries
+                .entry(hunk_oid)
+                .or_insert_with(|| {
+                    repo.find_commit(hunk_oid)
+                        .ok()
+                        .and_then(|commit| commit.summary().map(str::to_string))
+                        .unwrap_or_default()
+                })
+                .clone();
 
             for j in 0..num_lines {
                 let line_num = start_line + j;
@@ -887,23 +1297,60 @@ impl GitBackend for Git2Backend {
                     commit_short_oid: short_oid.clone(),
                     author_name: author_name.clone(),
                     author_date,
+                    summary: summary.clone(),
                     is_block_start: is_new_block && j == 0,
+                    original_path: original_path.clone(),
+                    original_line_number: (original_start + j) as u32,
                 });
             }
         }
 
         Ok(BlameResult {
             path: path.to_string(),
+            commit_oid: commit_oid.map(str::to_string),
             lines,
         })
     }
 
+    fn get_blame_before(
+        &self,
+        commit_oid: &str,
+        path: &str,
+        options: &BlameOptions,
+    ) -> GitResult<BlameResult> {
+        let (parent_oid, parent_path) = {
+            let repo = self.repo.lock().unwrap();
+            let oid = Oid::from_str(commit_oid).map_err(|e| GitError::BlameFailed(Box::new(e)))?;
+            let commit = repo
+                .find_commit(oid)
+                .map_err(|e| GitError::BlameFailed(Box::new(e)))?;
+            let parent = commit.parent(0).map_err(|_| {
+                GitError::BlameFailed(format!("commit {commit_oid} has no parent").into())
+            })?;
+            let parent_tree = parent
+                .tree()
+                .map_err(|e| GitError::BlameFailed(Box::new(e)))?;
+            let parent_path = if parent_tree.get_path(Path::new(path)).is_ok() {
+                path.to_string()
+            } else {
+                rename_source(&repo, &commit, path).ok_or_else(|| {
+                    GitError::BlameFailed(
+                        format!("{path} does not exist before {commit_oid}").into(),
+                    )
+                })?
+            };
+            (parent.id().to_string(), parent_path)
+        };
+        self.get_blame(&parent_path, Some(&parent_oid), options)
+    }
+
     fn get_file_history(
         &self,
         path: &str,
         limit: usize,
         skip: usize,
-    ) -> GitResult<Vec<CommitInfo>> {
+        follow: bool,
+    ) -> GitResult<Vec<FileHistoryEntry>> {
         let repo = self.repo.lock().unwrap();
 
         let mut revwalk = repo
@@ -917,7 +1364,9 @@ impl GitBackend for Git2Backend {
             .map_err(|e| GitError::LogFailed(Box::new(e)))?;
 
         let ref_map = build_ref_map(&repo);
-        let mut commits = Vec::new();
+        let graph = self.commit_graph(&repo);
+        let mut current = path.to_string();
+        let mut entries = Vec::new();
         let mut skipped = 0;
 
         for oid_result in revwalk {
@@ -926,23 +1375,41 @@ impl GitBackend for Git2Backend {
                 .find_commit(oid)
                 .map_err(|e| GitError::LogFailed(Box::new(e)))?;
 
-            if !commit_touches_path(&repo, &commit, path) {
+            if !commit_touches_path(&repo, graph.as_deref(), &commit, &current) {
                 continue;
             }
 
+            let entry_path = current.clone();
+            let previous_path = if follow {
+                rename_source(&repo, &commit, &current)
+            } else {
+                None
+            };
+            if let Some(previous) = &previous_path {
+                current = previous.clone();
+            }
+
             if skipped < skip {
                 skipped += 1;
                 continue;
             }
 
-            commits.push(commit_to_info(&commit, &ref_map));
+            entries.push(FileHistoryEntry {
+                commit: commit_to_info(&commit, &ref_map),
+                path: entry_path,
+                previous_path,
+            });
 
-            if commits.len() >= limit {
+            if entries.len() >= limit {
                 break;
             }
         }
 
-        Ok(commits)
+        Ok(entries)
+    }
+
+    fn write_commit_graph(&self) -> GitResult<()> {
+        commit_graph::write(&self.workdir)
     }
 
     fn get_branch_commits(&self, branch_name: &str, limit: usize) -> GitResult<Vec<CommitInfo>> {
@@ -986,33 +1453,47 @@ impl GitBackend for Git2Backend {
     }
 
     fn stage_hunk(&self, path: &Path, hunk: &HunkIdentifier) -> GitResult<()> {
-        let patch = self.generate_hunk_patch(path, hunk, false)?;
+        let patch = generate_hunk_patch(self, path, hunk, false)?;
         run_git_apply(&self.workdir, &patch, &["--cached"]).map_err(GitError::StageFailed)
     }
 
     fn unstage_hunk(&self, path: &Path, hunk: &HunkIdentifier) -> GitResult<()> {
-        let patch = self.generate_hunk_patch(path, hunk, true)?;
+        let patch = generate_hunk_patch(self, path, hunk, true)?;
         run_git_apply(&self.workdir, &patch, &["--cached", "-R"]).map_err(GitError::UnstageFailed)
     }
 
     fn discard_hunk(&self, path: &Path, hunk: &HunkIdentifier) -> GitResult<()> {
-        let patch = self.generate_hunk_patch(path, hunk, false)?;
-        run_git_apply(&self.workdir, &patch, &["-R"]).map_err(GitError::DiscardFailed)
+        let plan = SnapshotPlan {
+            files: vec![path.to_string_lossy().to_string()],
+            ..SnapshotPlan::default()
+        };
+        let description = format!("Discard changes in {}", path.display());
+        journal::journaled(self, OperationKind::Discard, description, plan, || {
+            let patch = generate_hunk_patch(self, path, hunk, false)?;
+            run_git_apply(&self.workdir, &patch, &["-R"]).map_err(GitError::DiscardFailed)
+        })
     }
 
     fn stage_lines(&self, path: &Path, line_range: &LineRange) -> GitResult<()> {
-        let patch = self.generate_line_patch(path, line_range, false)?;
+        let patch = generate_line_patch(self, path, line_range, false)?;
         run_git_apply(&self.workdir, &patch, &["--cached"]).map_err(GitError::StageFailed)
     }
 
     fn unstage_lines(&self, path: &Path, line_range: &LineRange) -> GitResult<()> {
-        let patch = self.generate_line_patch(path, line_range, true)?;
+        let patch = generate_line_patch(self, path, line_range, true)?;
         run_git_apply(&self.workdir, &patch, &["--cached", "-R"]).map_err(GitError::UnstageFailed)
     }
 
     fn discard_lines(&self, path: &Path, line_range: &LineRange) -> GitResult<()> {
-        let patch = self.generate_line_patch(path, line_range, false)?;
-        run_git_apply(&self.workdir, &patch, &["-R"]).map_err(GitError::DiscardFailed)
+        let plan = SnapshotPlan {
+            files: vec![path.to_string_lossy().to_string()],
+            ..SnapshotPlan::default()
+        };
+        let description = format!("Discard changes in {}", path.display());
+        journal::journaled(self, OperationKind::Discard, description, plan, || {
+            let patch = generate_line_patch(self, path, line_range, false)?;
+            run_git_apply(&self.workdir, &patch, &["-R"]).map_err(GitError::DiscardFailed)
+        })
     }
 
     fn get_head_commit_message(&self) -> GitResult<String> {
@@ -1025,13 +1506,33 @@ impl GitBackend for Git2Backend {
         Ok(message)
     }
 
-    fn stash_save(&self, message: Option<&str>) -> GitResult<()> {
+    fn stash_save(&self, message: Option<&str>, options: &StashOptions) -> GitResult<()> {
+        stash::validate(options)?;
+        if !options.hunks.is_empty() {
+            return stash::stash_hunks(self, message, &options.hunks);
+        }
+
+        // libgit2 resets files outside the pathspec as well.
+        if !options.paths.is_empty() {
+            return stash::push_with_git(&self.workdir, message, options);
+        }
+
         let mut repo = self.repo.lock().unwrap();
         let signature = repo
             .signature()
             .map_err(|e| GitError::StashFailed(Box::new(e)))?;
-        let flags = git2::StashFlags::DEFAULT;
-        repo.stash_save(&signature, message.unwrap_or(""), Some(flags))
+        let mut flags = git2::StashFlags::DEFAULT;
+        if options.include_untracked || options.include_ignored {
+            flags |= git2::StashFlags::INCLUDE_UNTRACKED;
+        }
+        if options.include_ignored {
+            flags |= git2::StashFlags::INCLUDE_IGNORED;
+        }
+        if options.keep_index {
+            flags |= git2::StashFlags::KEEP_INDEX;
+        }
+        let message = message.filter(|m| !m.is_empty());
+        repo.stash_save2(&signature, message, Some(flags))
             .map_err(|e| GitError::StashFailed(Box::new(e)))?;
         Ok(())
     }
@@ -1064,25 +1565,76 @@ impl GitBackend for Git2Backend {
         Ok(entries)
     }
 
-    fn stash_apply(&self, index: usize) -> GitResult<()> {
+    fn stash_apply(&self, index: usize, restore_index: bool) -> GitResult<StashApplyResult> {
         let mut repo = self.repo.lock().unwrap();
-        repo.stash_apply(index, None)
-            .map_err(|e| GitError::StashFailed(Box::new(e)))?;
-        Ok(())
+        apply_stash(&mut repo, index, restore_index)
     }
 
-    fn stash_pop(&self, index: usize) -> GitResult<()> {
+    fn stash_pop(&self, index: usize, restore_index: bool) -> GitResult<StashApplyResult> {
         let mut repo = self.repo.lock().unwrap();
-        repo.stash_pop(index, None)
+        // libgit2's own pop drops the stash even when applying it conflicted.
+        let result = apply_stash(&mut repo, index, restore_index)?;
+        if result.completed {
+            repo.stash_drop(index)
+                .map_err(|e| GitError::StashFailed(Box::new(e)))?;
+        }
+        Ok(result)
+    }
+
+    fn stash_branch(&self, index: usize, branch_name: &str) -> GitResult<()> {
+        {
+            let repo = self.repo.lock().unwrap();
+            let stash = repo
+                .revparse_single(&format!("stash@{{{index}}}"))
+                .and_then(|obj| obj.peel_to_commit())
+                .map_err(|e| GitError::StashFailed(Box::new(e)))?;
+            let base = stash
+                .parent(0)
+                .map_err(|e| GitError::StashFailed(Box::new(e)))?;
+            let branch = repo
+                .branch(branch_name, &base, false)
+                .map_err(|e| GitError::StashFailed(Box::new(e)))?;
+            let refname = branch
+                .get()
+                .name()
+                .ok_or_else(|| GitError::StashFailed("invalid branch name".into()))?
+                .to_string();
+            repo.checkout_tree(
+                base.as_object(),
+                Some(git2::build::CheckoutBuilder::new().safe()),
+            )
             .map_err(|e| GitError::StashFailed(Box::new(e)))?;
-        Ok(())
+            repo.set_head(&refname)
+                .map_err(|e| GitError::StashFailed(Box::new(e)))?;
+        }
+        self.stash_pop(index, true).map(|_| ())
     }
 
     fn stash_drop(&self, index: usize) -> GitResult<()> {
-        let mut repo = self.repo.lock().unwrap();
-        repo.stash_drop(index)
-            .map_err(|e| GitError::StashFailed(Box::new(e)))?;
-        Ok(())
+        let stash = format!("stash@{{{index}}}");
+        let oid = self
+            .read_ref(&stash)
+            .ok_or_else(|| GitError::StashFailed(format!("stash not found: {stash}").into()))?;
+        let message = self
+            .stash_list()?
+            .into_iter()
+            .find(|entry| entry.index == index)
+            .map(|entry| entry.message)
+            .unwrap_or_default();
+        let plan = SnapshotPlan {
+            stash: Some(StashState {
+                oid,
+                message: message.clone(),
+            }),
+            ..SnapshotPlan::default()
+        };
+        let description = format!("Drop stash: {message}");
+        journal::journaled(self, OperationKind::StashDrop, description, plan, || {
+            let mut repo = self.repo.lock().unwrap();
+            repo.stash_drop(index)
+                .map_err(|e| GitError::StashFailed(Box::new(e)))?;
+            Ok(())
+        })
     }
 
     fn stash_diff(&self, index: usize) -> GitResult<Vec<FileDiff>> {
@@ -1116,6 +1668,54 @@ impl GitBackend for Git2Backend {
         Ok(file_diffs)
     }
 
+    fn stash_detail(&self, index: usize) -> GitResult<StashDetail> {
+        let entry = self
+            .stash_list()?
+            .into_iter()
+            .find(|entry| entry.index == index)
+            .ok_or_else(|| GitError::StashFailed("stash index out of range".into()))?;
+        let repo = self.repo.lock().unwrap();
+        let stash_error = |e: git2::Error| GitError::StashFailed(Box::new(e));
+
+        let stash = repo
+            .revparse_single(&format!("stash@{{{index}}}"))
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(stash_error)?;
+        let base = stash.parent(0).map_err(stash_error)?;
+        let index_commit = stash.parent(1).map_err(stash_error)?;
+        let untracked = stash.parent(2).ok();
+
+        let diff = |old: Option<&git2::Tree>, new: &git2::Tree| -> GitResult<Vec<FileDiff>> {
+            let diff = repo
+                .diff_tree_to_tree(old, Some(new), None)
+                .map_err(stash_error)?;
+            let mut file_diffs = parse_diff_to_file_diffs(&diff).map_err(stash_error)?;
+            compute_word_diffs(&mut file_diffs);
+            Ok(file_diffs)
+        };
+        let base_tree = base.tree().map_err(stash_error)?;
+        let index_tree = index_commit.tree().map_err(stash_error)?;
+        let stash_tree = stash.tree().map_err(stash_error)?;
+        let untracked_files = match &untracked {
+            Some(commit) => diff(None, &commit.tree().map_err(stash_error)?)?,
+            None => Vec::new(),
+        };
+
+        Ok(StashDetail {
+            entry,
+            oid: stash.id().to_string(),
+            base_oid: base.id().to_string(),
+            base_summary: base.summary().unwrap_or("").to_string(),
+            index_changes: diff(Some(&base_tree), &index_tree)?,
+            worktree_changes: diff(Some(&index_tree), &stash_tree)?,
+            untracked_files,
+        })
+    }
+
+    fn stash_rename(&self, index: usize, message: &str) -> GitResult<()> {
+        stash::rename(&self.git_dir()?, index, message)
+    }
+
     fn list_tags(&self) -> GitResult<Vec<TagInfo>> {
         let repo = self.repo.lock().unwrap();
         let tag_names = repo
@@ -1145,6 +1745,11 @@ impl GitBackend for Git2Backend {
             };
 
             if let Ok(tag_obj) = repo.find_tag(direct_oid) {
+                let (message, signature_status) = tag::message_and_signature(
+                    &self.workdir,
+                    name,
+                    tag_obj.message().unwrap_or(""),
+                );
                 tags.push(TagInfo {
                     name: name.to_string(),
                     target_oid: commit_oid.to_string(),
@@ -1152,7 +1757,8 @@ impl GitBackend for Git2Backend {
                     is_annotated: true,
                     tagger_name: tag_obj.tagger().map(|t| t.name().unwrap_or("").to_string()),
                     tagger_date: tag_obj.tagger().map(|t| t.when().seconds()),
-                    message: tag_obj.message().map(|m| m.to_string()),
+                    message: Some(message),
+                    signature_status,
                 });
             } else {
                 tags.push(TagInfo {
@@ -1163,29 +1769,46 @@ impl GitBackend for Git2Backend {
                     tagger_name: None,
                     tagger_date: None,
                     message: None,
+                    signature_status: SignatureStatus::None,
                 });
             }
         }
 
+        tag::sort_by_version(&mut tags);
         Ok(tags)
     }
 
-    fn create_tag(&self, name: &str, message: Option<&str>) -> GitResult<()> {
+    fn create_tag(
+        &self,
+        name: &str,
+        message: Option<&str>,
+        options: &CreateTagOptions,
+    ) -> GitResult<()> {
+        if options.sign && message.is_none() {
+            return Err(GitError::TagFailed("a signed tag needs a message".into()));
+        }
         let repo = self.repo.lock().unwrap();
-        let head = repo.head().map_err(|e| GitError::TagFailed(Box::new(e)))?;
-        let target = head
-            .peel(git2::ObjectType::Commit)
+        let target = repo
+            .revparse_single(options.target.as_deref().unwrap_or("HEAD"))
+            .and_then(|obj| obj.peel(git2::ObjectType::Commit))
             .map_err(|e| GitError::TagFailed(Box::new(e)))?;
 
-        if let Some(msg) = message {
-            let sig = repo
-                .signature()
-                .map_err(|e| GitError::TagFailed(Box::new(e)))?;
-            repo.tag(name, &target, &sig, msg, false)
-                .map_err(|e| GitError::TagFailed(Box::new(e)))?;
-        } else {
-            repo.tag_lightweight(name, &target, false)
-                .map_err(|e| GitError::TagFailed(Box::new(e)))?;
+        match message {
+            Some(msg) => {
+                let sig = repo
+                    .signature()
+                    .map_err(|e| GitError::TagFailed(Box::new(e)))?;
+                if options.sign {
+                    create_signed_tag(&repo, name, &target, &sig, msg)?;
+                } else {
+                    repo.tag(name, &target, &sig, msg, false)
+                        .map_err(|e| GitError::TagFailed(Box::new(e)))?;
+                }
+            }
+            None => {
+                repo.tag_lightweight(name, &target, false)
+                    .map_err(|e| GitError::TagFailed(Box::new(e)))?;
+            }
         }
 
         Ok(())
@@ -1220,6 +1843,42 @@ impl GitBackend for Git2Backend {
         Ok(())
     }
 
+    fn release_notes(&self, options: &ReleaseNotesOptions) -> GitResult<ReleaseNotes> {
+        let to = options.to.as_deref().unwrap_or("HEAD");
+        let repo = self.repo.lock().unwrap();
+        let resolve = |rev: &str| {
+            repo.revparse_single(rev)
+                .and_then(|obj| obj.peel(git2::ObjectType::Commit))
+                .map(|obj| obj.id())
+                .map_err(|e| GitError::ReleaseNotesFailed(Box::new(e)))
+        };
+        let mut revwalk = repo
+            .revwalk()
+            .map_err(|e| GitError::ReleaseNotesFailed(Box::new(e)))?;
+        revwalk
+            .set_sorting(Sort::TIME | Sort::TOPOLOGICAL)
+            .map_err(|e| GitError::ReleaseNotesFailed(Box::new(e)))?;
+        revwalk
+            .push(resolve(to)?)
+            .map_err(|e| GitError::ReleaseNotesFailed(Box::new(e)))?;
+        if let Some(from) = options.from.as_deref() {
+            revwalk
+                .hide(resolve(from)?)
+                .map_err(|e| GitError::ReleaseNotesFailed(Box::new(e)))?;
+        }
+
+        let ref_map = build_ref_map(&repo);
+        let mut commits = Vec::new();
+        for oid in revwalk {
+            let oid = oid.map_err(|e| GitError::ReleaseNotesFailed(Box::new(e)))?;
+            let commit = repo
+                .find_commit(oid)
+                .map_err(|e| GitError::ReleaseNotesFailed(Box::new(e)))?;
+            commits.push(commit_to_info(&commit, &ref_map));
+        }
+        Ok(release_notes::build(options.from.as_deref(), to, &commits))
+    }
+
     fn get_conflict_files(&self) -> GitResult<Vec<ConflictFile>> {
         let repo = self.repo.lock().unwrap();
         let index = repo
@@ -1320,6 +1979,21 @@ impl GitBackend for Git2Backend {
             ));
         }
 
+        let commit_message = if message.is_empty() {
+            let merge_msg_path = repo.path().join("MERGE_MSG");
+            std::fs::read_to_string(&merge_msg_path)
+                .map(|s| s.trim().to_string())
+                .unwrap_or_else(|_| "Merge commit".to_string())
+        } else {
+            message.to_string()
+        };
+        let commit_message = hooks::prepare_linted_commit(
+            &self.workdir,
+            &commit_message,
+            CommitSource::InProgress,
+            true,
+        )?;
+
         let mut index = repo
             .index()
             .map_err(|e| GitError::ConflictFailed(Box::new(e)))?;
@@ -1348,15 +2022,6 @@ impl GitBackend for Git2Backend {
             .find_commit(merge_head_oid)
             .map_err(|e| GitError::ConflictFailed(Box::new(e)))?;
 
-        let commit_message = if message.is_empty() {
-            let merge_msg_path = repo.path().join("MERGE_MSG");
-            std::fs::read_to_string(&merge_msg_path)
-                .map(|s| s.trim().to_string())
-                .unwrap_or_else(|_| "Merge commit".to_string())
-        } else {
-            message.to_string()
-        };
-
         let oid = repo
             .commit(
                 Some("HEAD"),
@@ -1369,6 +2034,7 @@ impl GitBackend for Git2Backend {
             .map_err(|e| GitError::ConflictFailed(Box::new(e)))?;
 
         let _ = repo.cleanup_state();
+        hooks::post_commit(&self.workdir);
 
         Ok(CommitResult {
             oid: oid.to_string(),
@@ -1380,136 +2046,73 @@ impl GitBackend for Git2Backend {
         Ok(repo.state() == git2::RepositoryState::Merge)
     }
 
-    fn rebase(&self, onto: &str) -> GitResult<RebaseResult> {
-        let output = std::process::Command::new("git")
-            .args(["rebase", onto])
-            .current_dir(&self.workdir)
-            .output()
-            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
-
-        if output.status.success() {
-            return Ok(RebaseResult {
-                completed: true,
-                conflicts: Vec::new(),
-            });
-        }
-
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("CONFLICT") || stderr.contains("conflict") {
-            let conflicts = collect_conflict_paths_from_workdir(&self.workdir);
-            return Ok(RebaseResult {
-                completed: false,
-                conflicts,
-            });
-        }
-
-        Err(GitError::RebaseFailed(stderr.to_string().into()))
-    }
-
-    fn interactive_rebase(&self, onto: &str, todo: &[RebaseTodoEntry]) -> GitResult<RebaseResult> {
-        let todo_content = todo
-            .iter()
-            .map(|entry| {
-                let action = match entry.action {
-                    RebaseAction::Pick => "pick",
-                    RebaseAction::Reword => "reword",
-                    RebaseAction::Edit => "edit",
-                    RebaseAction::Squash => "squash",
-                    RebaseAction::Fixup => "fixup",
-                    RebaseAction::Drop => "drop",
-                };
-                format!("{} {} {}", action, entry.short_oid, entry.message)
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        let git_dir = self.workdir.join(".git");
-        let todo_file = git_dir.join("rocket-rebase-todo");
-        std::fs::write(&todo_file, &todo_content)
-            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
-
-        let seq_editor_file = git_dir.join("rocket-rebase-editor.sh");
-        let seq_editor_content = format!(
-            "#!/bin/sh\ncp '{}' \"$1\"\n",
-            todo_file.display().to_string().replace("'", "'\\''")
-        );
-        std::fs::write(&seq_editor_file, &seq_editor_content)
-            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
-
-        let reword_messages: Vec<&str> = todo
-            .iter()
-            .filter(|e| e.action == RebaseAction::Reword)
-            .map(|e| e.message.as_str())
-            .collect();
-
-        let commit_editor_file = git_dir.join("rocket-commit-editor.sh");
-        let commit_editor_content = if reword_messages.is_empty() {
-            "#!/bin/sh\ntrue\n".to_string()
-        } else {
-            let counter_file = git_dir.join("rocket-reword-counter");
-            std::fs::write(&counter_file, "0").map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
-
-            let msgs_file = git_dir.join("rocket-reword-msgs");
-            std::fs::write(&msgs_file, reword_messages.join("\n---ROCKET_MSG_SEP---\n"))
-                .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
-
-            format!(
-                "#!/bin/sh\n\
-                COUNTER=$(cat '{counter}')\n\
-                MSG=$(awk -v n=\"$COUNTER\" 'BEGIN{{found=0}} /^---ROCKET_MSG_SEP---$/{{found++; next}} found==n{{print}}' '{msgs}')\n\
-                printf '%s\\n' \"$MSG\" > \"$1\"\n\
-                echo $((COUNTER + 1)) > '{counter}'\n",
-                counter = counter_file.display().to_string().replace("'", "'\\''"),
-                msgs = msgs_file.display().to_string().replace("'", "'\\''"),
-            )
+    fn rebase(
+        &self,
+        onto: &str,
+        options: &RebaseOptions,
+        control: &RebaseControl,
+    ) -> GitResult<RebaseResult> {
+        let todo = rebase::rebase_todo(self, onto, options)?;
+        let todo = stack::with_update_refs(&self.workdir, onto, todo)?;
+        let plan = SnapshotPlan {
+            refs: stack::rebased_refs(&todo),
+            worktree: true,
+            ..SnapshotPlan::default()
         };
-        std::fs::write(&commit_editor_file, &commit_editor_content)
-            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
-
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mode = std::fs::Permissions::from_mode(0o755);
-            std::fs::set_permissions(&seq_editor_file, mode.clone())
-                .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
-            std::fs::set_permissions(&commit_editor_file, mode)
-                .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
-        }
-
-        let output = std::process::Command::new("git")
-            .args(["rebase", "-i", onto])
-            .env("GIT_SEQUENCE_EDITOR", seq_editor_file.display().to_string())
-            .env("GIT_EDITOR", commit_editor_file.display().to_string())
-            .current_dir(&self.workdir)
-            .output()
-            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
-
-        let _ = std::fs::remove_file(&todo_file);
-        let _ = std::fs::remove_file(&seq_editor_file);
-        let _ = std::fs::remove_file(&commit_editor_file);
-        let _ = std::fs::remove_file(git_dir.join("rocket-reword-counter"));
-        let _ = std::fs::remove_file(git_dir.join("rocket-reword-msgs"));
-
-        if output.status.success() {
-            return Ok(RebaseResult {
-                completed: true,
-                conflicts: Vec::new(),
-            });
-        }
+        let description = format!("Rebase onto {onto}");
+        journal::journaled(self, OperationKind::Rebase, description, plan, || {
+            let repo = self.repo.lock().unwrap();
+            rebase_engine::start(&repo, onto, &todo, control)
+        })
+    }
 
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("CONFLICT")
-            || stderr.contains("conflict")
-            || stderr.contains("Stopped at")
-        {
-            let conflicts = collect_conflict_paths_from_workdir(&self.workdir);
-            return Ok(RebaseResult {
-                completed: false,
-                conflicts,
-            });
-        }
+    fn interactive_rebase(
+        &self,
+        onto: &str,
+        todo: &[RebaseTodoEntry],
+        control: &RebaseControl,
+    ) -> GitResult<RebaseResult> {
+        let todo = stack::with_update_refs(&self.workdir, onto, todo.to_vec())?;
+        let plan = SnapshotPlan {
+            refs: stack::rebased_refs(&todo),
+            worktree: true,
+            ..SnapshotPlan::default()
+        };
+        let description = format!("Interactive rebase onto {onto}");
+        journal::journaled(self, OperationKind::Rebase, description, plan, || {
+            let repo = self.repo.lock().unwrap();
+            rebase_engine::start(&repo, onto, &todo, control)
+        })
+    }
 
-        Err(GitError::RebaseFailed(stderr.to_string().into()))
+    fn edit_commit(&self, edit: &CommitEdit) -> GitResult<RebaseResult> {
+        let (base, todo) = rebase::plan_commit_edit(self, edit)?;
+        let plan = SnapshotPlan {
+            refs: stack::rebased_refs(&todo),
+            worktree: true,
+            ..SnapshotPlan::default()
+        };
+        let description = match edit {
+            CommitEdit::Reword { oid, .. } => format!("Reword {oid}"),
+            CommitEdit::Drop { oid } => format!("Drop {oid}"),
+            CommitEdit::Move { oid, .. } => format!("Move {oid}"),
+        };
+        journal::journaled(self, OperationKind::Rebase, description, plan, || {
+            match self.replay_in_memory(&base, &todo)? {
+                Some((tip, update_refs)) => {
+                    self.finish_in_memory_rebase(&base, tip, &update_refs)?;
+                    Ok(RebaseResult {
+                        completed: true,
+                        conflicts: Vec::new(),
+                    })
+                }
+                // 衝突やマージがあれば作業ツリー上でやり直し、衝突した所で止める
+                None => {
+                    let repo = self.repo.lock().unwrap();
+                    rebase_engine::start(&repo, &base, &todo, &RebaseControl::default())
+                }
+            }
+        })
     }
 
     fn is_rebasing(&self) -> GitResult<bool> {
@@ -1523,97 +2126,31 @@ impl GitBackend for Git2Backend {
     }
 
     fn abort_rebase(&self) -> GitResult<()> {
-        let output = std::process::Command::new("git")
-            .args(["rebase", "--abort"])
-            .current_dir(&self.workdir)
-            .output()
-            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(GitError::RebaseFailed(stderr.to_string().into()));
+        let repo = self.repo.lock().unwrap();
+        if rebase_engine::owns(&repo) {
+            return rebase_engine::abort(&repo);
         }
-
-        Ok(())
+        rebase::abort_rebase(&self.workdir)
     }
 
-    fn continue_rebase(&self) -> GitResult<RebaseResult> {
-        let output = std::process::Command::new("git")
-            .args(["rebase", "--continue"])
-            .env("GIT_EDITOR", "true")
-            .current_dir(&self.workdir)
-            .output()
-            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
-
-        if output.status.success() {
-            return Ok(RebaseResult {
-                completed: true,
-                conflicts: Vec::new(),
-            });
-        }
-
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("CONFLICT") || stderr.contains("conflict") {
-            let conflicts = collect_conflict_paths_from_workdir(&self.workdir);
-            return Ok(RebaseResult {
-                completed: false,
-                conflicts,
-            });
+    fn continue_rebase(&self, control: &RebaseControl) -> GitResult<RebaseResult> {
+        let repo = self.repo.lock().unwrap();
+        if rebase_engine::owns(&repo) {
+            return rebase_engine::resume(&repo, control);
         }
-
-        Err(GitError::RebaseFailed(stderr.to_string().into()))
+        // git が始めた rebase は git に続けさせる
+        rebase::continue_rebase(&self.workdir)
     }
 
     fn get_rebase_state(&self) -> GitResult<Option<RebaseState>> {
         let repo = self.repo.lock().unwrap();
-        let git_dir = repo.path();
-
-        let rebase_merge = git_dir.join("rebase-merge");
-        let rebase_apply = git_dir.join("rebase-apply");
-
-        let rebase_dir = if rebase_merge.is_dir() {
-            rebase_merge
-        } else if rebase_apply.is_dir() {
-            rebase_apply
-        } else {
-            return Ok(None);
-        };
-
-        let onto_oid = std::fs::read_to_string(rebase_dir.join("onto"))
-            .unwrap_or_default()
-            .trim()
-            .to_string();
-
-        let onto_branch = std::fs::read_to_string(rebase_dir.join("onto_name"))
-            .unwrap_or_else(|_| onto_oid.clone())
-            .trim()
-            .trim_start_matches("refs/heads/")
-            .to_string();
-
-        let current_step = std::fs::read_to_string(rebase_dir.join("msgnum"))
-            .unwrap_or_else(|_| "0".to_string())
-            .trim()
-            .parse::<usize>()
-            .unwrap_or(0);
-
-        let total_steps = std::fs::read_to_string(rebase_dir.join("end"))
-            .unwrap_or_else(|_| "0".to_string())
-            .trim()
-            .parse::<usize>()
-            .unwrap_or(0);
-
         let index = repo
             .index()
             .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
-        let has_conflicts = index.has_conflicts();
-
-        Ok(Some(RebaseState {
-            onto_branch,
-            onto_oid,
-            current_step,
-            total_steps,
-            has_conflicts,
-        }))
+        Ok(rebase::read_rebase_state(
+            repo.path(),
+            index.has_conflicts(),
+        ))
     }
 
     fn get_rebase_todo(&self, onto: &str, limit: usize) -> GitResult<Vec<RebaseTodoEntry>> {
@@ -1669,6 +2206,7 @@ impl GitBackend for Git2Backend {
                 short_oid,
                 message,
                 author_name,
+                argument: None,
             });
         }
 
@@ -1725,6 +2263,17 @@ impl GitBackend for Git2Backend {
                 continue;
             }
 
+            let original_msg = commit.message().unwrap_or("");
+            let message = match mode {
+                CherryPickMode::Normal => {
+                    format!("{original_msg}\n\n(cherry picked from commit {oid_str})")
+                }
+                CherryPickMode::Merge => original_msg.to_string(),
+                CherryPickMode::NoCommit => unreachable!(),
+            };
+            let message =
+                hooks::prepare_commit(&self.workdir, &message, CommitSource::Message, true)?;
+
             // Create commit manually since git2 cherrypick only applies to worktree
             let mut index = repo
                 .index()
@@ -1743,19 +2292,11 @@ impl GitBackend for Git2Backend {
                 .and_then(|h| h.peel_to_commit())
                 .map_err(|e| GitError::CherryPickFailed(Box::new(e)))?;
 
-            let original_msg = commit.message().unwrap_or("");
-            let message = match mode {
-                CherryPickMode::Normal => {
-                    format!("{original_msg}\n\n(cherry picked from commit {oid_str})")
-                }
-                CherryPickMode::Merge => original_msg.to_string(),
-                CherryPickMode::NoCommit => unreachable!(),
-            };
-
             repo.commit(Some("HEAD"), &sig, &sig, &message, &tree, &[&head_commit])
                 .map_err(|e| GitError::CherryPickFailed(Box::new(e)))?;
 
             let _ = repo.cleanup_state();
+            hooks::post_commit(&self.workdir);
         }
 
         let head_oid = if mode == CherryPickMode::NoCommit {
@@ -1801,6 +2342,23 @@ impl GitBackend for Git2Backend {
             ));
         }
 
+        let cherry_head_path = repo.path().join("CHERRY_PICK_HEAD");
+        let cherry_head_content = std::fs::read_to_string(&cherry_head_path)
+            .map_err(|e| GitError::CherryPickFailed(Box::new(e)))?;
+        let cherry_oid = Oid::from_str(cherry_head_content.trim())
+            .map_err(|e| GitError::CherryPickFailed(Box::new(e)))?;
+        let cherry_commit = repo
+            .find_commit(cherry_oid)
+            .map_err(|e| GitError::CherryPickFailed(Box::new(e)))?;
+
+        let original_msg = cherry_commit.message().unwrap_or("");
+        let message = format!(
+            "{original_msg}\n\n(cherry picked from commit {})",
+            cherry_oid
+        );
+        let message =
+            hooks::prepare_commit(&self.workdir, &message, CommitSource::InProgress, true)?;
+
         let mut index = repo
             .index()
             .map_err(|e| GitError::CherryPickFailed(Box::new(e)))?;
@@ -1818,26 +2376,12 @@ impl GitBackend for Git2Backend {
             .and_then(|h| h.peel_to_commit())
             .map_err(|e| GitError::CherryPickFailed(Box::new(e)))?;
 
-        let cherry_head_path = repo.path().join("CHERRY_PICK_HEAD");
-        let cherry_head_content = std::fs::read_to_string(&cherry_head_path)
-            .map_err(|e| GitError::CherryPickFailed(Box::new(e)))?;
-        let cherry_oid = Oid::from_str(cherry_head_content.trim())
-            .map_err(|e| GitError::CherryPickFailed(Box::new(e)))?;
-        let cherry_commit = repo
-            .find_commit(cherry_oid)
-            .map_err(|e| GitError::CherryPickFailed(Box::new(e)))?;
-
-        let original_msg = cherry_commit.message().unwrap_or("");
-        let message = format!(
-            "{original_msg}\n\n(cherry picked from commit {})",
-            cherry_oid
-        );
-
         let oid = repo
             .commit(Some("HEAD"), &sig, &sig, &message, &tree, &[&head_commit])
             .map_err(|e| GitError::CherryPickFailed(Box::new(e)))?;
 
         let _ = repo.cleanup_state();
+        hooks::post_commit(&self.workdir);
 
         Ok(CherryPickResult {
             completed: true,
@@ -1879,6 +2423,10 @@ impl GitBackend for Git2Backend {
             });
         }
 
+        let original_msg = commit.message().unwrap_or("").lines().next().unwrap_or("");
+        let message = format!("Revert \"{original_msg}\"\n\nThis reverts commit {oid_str}.");
+        let message = hooks::prepare_commit(&self.workdir, &message, CommitSource::Message, true)?;
+
         let mut index = repo
             .index()
             .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
@@ -1896,14 +2444,12 @@ impl GitBackend for Git2Backend {
             .and_then(|h| h.peel_to_commit())
             .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
 
-        let original_msg = commit.message().unwrap_or("").lines().next().unwrap_or("");
-        let message = format!("Revert \"{original_msg}\"\n\nThis reverts commit {oid_str}.");
-
         let new_oid = repo
             .commit(Some("HEAD"), &sig, &sig, &message, &tree, &[&head_commit])
             .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
 
         let _ = repo.cleanup_state();
+        hooks::post_commit(&self.workdir);
 
         Ok(RevertResult {
             completed: true,
@@ -1937,23 +2483,6 @@ impl GitBackend for Git2Backend {
             return Err(GitError::RevertFailed("unresolved conflicts remain".into()));
         }
 
-        let mut index = repo
-            .index()
-            .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
-        let tree_oid = index
-            .write_tree()
-            .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
-        let tree = repo
-            .find_tree(tree_oid)
-            .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
-        let sig = repo
-            .signature()
-            .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
-        let head_commit = repo
-            .head()
-            .and_then(|h| h.peel_to_commit())
-            .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
-
         let revert_head_path = repo.path().join("REVERT_HEAD");
         let revert_head_content = std::fs::read_to_string(&revert_head_path)
             .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
@@ -1973,12 +2502,32 @@ impl GitBackend for Git2Backend {
             "Revert \"{original_msg}\"\n\nThis reverts commit {}.",
             revert_oid
         );
+        let message =
+            hooks::prepare_commit(&self.workdir, &message, CommitSource::InProgress, true)?;
+
+        let mut index = repo
+            .index()
+            .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
+        let tree_oid = index
+            .write_tree()
+            .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
+        let tree = repo
+            .find_tree(tree_oid)
+            .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
+        let sig = repo
+            .signature()
+            .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
+        let head_commit = repo
+            .head()
+            .and_then(|h| h.peel_to_commit())
+            .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
 
         let oid = repo
             .commit(Some("HEAD"), &sig, &sig, &message, &tree, &[&head_commit])
             .map_err(|e| GitError::RevertFailed(Box::new(e)))?;
 
         let _ = repo.cleanup_state();
+        hooks::post_commit(&self.workdir);
 
         Ok(RevertResult {
             completed: true,
@@ -1988,24 +2537,33 @@ impl GitBackend for Git2Backend {
     }
 
     fn reset(&self, oid_str: &str, mode: ResetMode) -> GitResult<ResetResult> {
-        let repo = self.repo.lock().unwrap();
+        let plan = SnapshotPlan {
+            refs: vec!["HEAD".to_string()],
+            index: true,
+            worktree: mode == ResetMode::Hard,
+            ..SnapshotPlan::default()
+        };
+        let description = format!("Reset ({mode:?}) to {oid_str}");
+        journal::journaled(self, OperationKind::Reset, description, plan, || {
+            let repo = self.repo.lock().unwrap();
 
-        let oid = Oid::from_str(oid_str).map_err(|e| GitError::ResetFailed(Box::new(e)))?;
-        let commit = repo
-            .find_commit(oid)
-            .map_err(|e| GitError::ResetFailed(Box::new(e)))?;
+            let oid = Oid::from_str(oid_str).map_err(|e| GitError::ResetFailed(Box::new(e)))?;
+            let commit = repo
+                .find_commit(oid)
+                .map_err(|e| GitError::ResetFailed(Box::new(e)))?;
 
-        let reset_type = match mode {
-            ResetMode::Soft => git2::ResetType::Soft,
-            ResetMode::Mixed => git2::ResetType::Mixed,
-            ResetMode::Hard => git2::ResetType::Hard,
-        };
+            let reset_type = match mode {
+                ResetMode::Soft => git2::ResetType::Soft,
+                ResetMode::Mixed => git2::ResetType::Mixed,
+                ResetMode::Hard => git2::ResetType::Hard,
+            };
 
-        repo.reset(commit.as_object(), reset_type, None)
-            .map_err(|e| GitError::ResetFailed(Box::new(e)))?;
+            repo.reset(commit.as_object(), reset_type, None)
+                .map_err(|e| GitError::ResetFailed(Box::new(e)))?;
 
-        Ok(ResetResult {
-            oid: oid_str.to_string(),
+            Ok(ResetResult {
+                oid: oid_str.to_string(),
+            })
         })
     }
 
@@ -2023,6 +2581,14 @@ impl GitBackend for Git2Backend {
         Ok(())
     }
 
+    fn list_operations(&self) -> GitResult<Vec<OperationEntry>> {
+        journal::list(self)
+    }
+
+    fn undo_last_operation(&self) -> GitResult<OperationEntry> {
+        journal::undo_last(self)
+    }
+
     fn get_reflog(&self, ref_name: &str, limit: usize) -> GitResult<Vec<ReflogEntry>> {
         let repo = self.repo.lock().unwrap();
 
@@ -2208,176 +2774,188 @@ impl GitBackend for Git2Backend {
     }
 
     fn verify_commit_signatures(&self, oids: &[&str]) -> GitResult<Vec<(String, SignatureStatus)>> {
-        if oids.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        let output = std::process::Command::new("git")
-            .args(["log", "--no-walk", "--format=%H %G?"])
-            .args(oids)
-            .current_dir(&self.workdir)
-            .output()
-            .map_err(|e| GitError::SigningFailed(Box::new(e)))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(GitError::SigningFailed(stderr.to_string().into()));
-        }
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut results = Vec::new();
+        verify_commit_signatures(&self.workdir, oids)
+    }
+}
 
-        for line in stdout.lines() {
-            let Some((hash, status_char)) = line.split_once(' ') else {
-                continue;
-            };
-            let status = parse_signature_status(status_char);
-            results.push((hash.to_string(), status));
-        }
+impl JournalRepo for Git2Backend {
+    fn git_dir(&self) -> GitResult<PathBuf> {
+        Ok(self.repo.lock().unwrap().path().to_path_buf())
+    }
 
-        Ok(results)
+    fn head_ref_name(&self) -> Option<String> {
+        let repo = self.repo.lock().unwrap();
+        let head = repo.find_reference("HEAD").ok()?;
+        head.symbolic_target().map(|s| s.to_string())
     }
-}
 
-impl Git2Backend {
-    fn generate_hunk_patch(
-        &self,
-        path: &Path,
-        hunk: &HunkIdentifier,
-        staged: bool,
-    ) -> GitResult<String> {
-        let options = DiffOptions {
-            staged,
-            ..Default::default()
-        };
-        let diffs = self.diff(Some(path), &options)?;
+    fn read_ref(&self, name: &str) -> Option<String> {
+        let repo = self.repo.lock().unwrap();
+        repo.revparse_single(name)
+            .ok()
+            .map(|obj| obj.id().to_string())
+    }
 
-        let file_diff = diffs
-            .first()
-            .ok_or_else(|| GitError::DiffFailed("no diff found for path".into()))?;
+    fn write_index_tree(&self) -> GitResult<String> {
+        let repo = self.repo.lock().unwrap();
+        let mut index = repo
+            .index()
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        let tree = index
+            .write_tree()
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        Ok(tree.to_string())
+    }
 
-        let matched_hunk = file_diff
-            .hunks
+    fn unstaged_files(&self) -> GitResult<Vec<String>> {
+        let repo = self.repo.lock().unwrap();
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(false);
+        let statuses = repo
+            .statuses(Some(&mut opts))
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        let changed =
+            git2::Status::WT_MODIFIED | git2::Status::WT_DELETED | git2::Status::WT_TYPECHANGE;
+        Ok(statuses
             .iter()
-            .find(|h| {
-                h.old_start == hunk.old_start
-                    && h.old_lines == hunk.old_lines
-                    && h.new_start == hunk.new_start
-                    && h.new_lines == hunk.new_lines
-            })
-            .ok_or_else(|| GitError::DiffFailed("hunk not found".into()))?;
-
-        let path_str = path.to_string_lossy();
-        let mut patch = String::new();
-        patch.push_str(&format!("diff --git a/{path_str} b/{path_str}\n"));
-        patch.push_str(&format!("--- a/{path_str}\n"));
-        patch.push_str(&format!("+++ b/{path_str}\n"));
-        patch.push_str(&format!(
-            "@@ -{},{} +{},{} @@\n",
-            hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
-        ));
+            .filter(|entry| entry.status().intersects(changed))
+            .filter_map(|entry| entry.path().map(|p| p.to_string()))
+            .collect())
+    }
 
-        for line in &matched_hunk.lines {
-            let prefix = match line.kind {
-                DiffLineKind::Addition => "+",
-                DiffLineKind::Deletion => "-",
-                DiffLineKind::Context => " ",
-                _ => continue,
-            };
-            let content = &line.content;
-            if content.ends_with('\n') {
-                patch.push_str(&format!("{prefix}{content}"));
-            } else {
-                patch.push_str(&format!("{prefix}{content}\n"));
-                patch.push_str("\\ No newline at end of file\n");
-            }
+    fn write_worktree_blob(&self, path: &str) -> GitResult<Option<String>> {
+        let full_path = self.workdir.join(path);
+        if !full_path.is_file() {
+            return Ok(None);
         }
+        let repo = self.repo.lock().unwrap();
+        let oid = repo
+            .blob_path(&full_path)
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        Ok(Some(oid.to_string()))
+    }
 
-        Ok(patch)
+    fn set_ref(&self, name: &str, oid: Option<&str>, message: &str) -> GitResult<()> {
+        let repo = self.repo.lock().unwrap();
+        match oid {
+            Some(oid) => {
+                let oid = Oid::from_str(oid).map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+                repo.reference(name, oid, true, message)
+                    .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+            }
+            None => {
+                if let Ok(mut reference) = repo.find_reference(name) {
+                    reference
+                        .delete()
+                        .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+                }
+            }
+        }
+        Ok(())
     }
 
-    fn generate_line_patch(
-        &self,
-        path: &Path,
-        line_range: &LineRange,
-        staged: bool,
-    ) -> GitResult<String> {
-        let options = DiffOptions {
-            staged,
-            ..Default::default()
+    fn reset_head(&self, oid: &str, hard: bool) -> GitResult<()> {
+        let repo = self.repo.lock().unwrap();
+        let oid = Oid::from_str(oid).map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        let commit = repo
+            .find_commit(oid)
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        let reset_type = if hard {
+            git2::ResetType::Hard
+        } else {
+            git2::ResetType::Soft
         };
-        let diffs = self.diff(Some(path), &options)?;
-
-        let file_diff = diffs
-            .first()
-            .ok_or_else(|| GitError::DiffFailed("no diff found for path".into()))?;
+        repo.reset(commit.as_object(), reset_type, None)
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        Ok(())
+    }
 
-        let hunk = &line_range.hunk;
-        let matched_hunk = file_diff
-            .hunks
-            .iter()
-            .find(|h| {
-                h.old_start == hunk.old_start
-                    && h.old_lines == hunk.old_lines
-                    && h.new_start == hunk.new_start
-                    && h.new_lines == hunk.new_lines
-            })
-            .ok_or_else(|| GitError::DiffFailed("hunk not found".into()))?;
+    fn read_index_tree(&self, tree: &str) -> GitResult<()> {
+        let repo = self.repo.lock().unwrap();
+        let oid = Oid::from_str(tree).map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        let tree = repo
+            .find_tree(oid)
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        let mut index = repo
+            .index()
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        index
+            .read_tree(&tree)
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        index
+            .write()
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        Ok(())
+    }
 
-        let selected: std::collections::HashSet<usize> =
-            line_range.line_indices.iter().copied().collect();
+    fn checkout_index(&self) -> GitResult<()> {
+        let repo = self.repo.lock().unwrap();
+        repo.checkout_index(None, Some(git2::build::CheckoutBuilder::new().force()))
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        Ok(())
+    }
 
-        let mut old_lines_count: u32 = 0;
-        let mut new_lines_count: u32 = 0;
-        let mut patch_lines = Vec::new();
+    fn read_blob(&self, oid: &str) -> GitResult<Vec<u8>> {
+        let repo = self.repo.lock().unwrap();
+        let oid = Oid::from_str(oid).map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        let blob = repo
+            .find_blob(oid)
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        Ok(blob.content().to_vec())
+    }
 
-        for (idx, line) in matched_hunk.lines.iter().enumerate() {
-            match line.kind {
-                DiffLineKind::Context => {
-                    old_lines_count += 1;
-                    new_lines_count += 1;
-                    patch_lines.push((' ', &line.content));
-                }
-                DiffLineKind::Addition => {
-                    if selected.contains(&idx) {
-                        new_lines_count += 1;
-                        patch_lines.push(('+', &line.content));
-                    }
-                }
-                DiffLineKind::Deletion => {
-                    if selected.contains(&idx) {
-                        old_lines_count += 1;
-                        patch_lines.push(('-', &line.content));
-                    } else {
-                        old_lines_count += 1;
-                        new_lines_count += 1;
-                        patch_lines.push((' ', &line.content));
-                    }
-                }
-                _ => continue,
-            }
-        }
+    fn store_stash(&self, oid: &str, message: &str) -> GitResult<()> {
+        let repo = self.repo.lock().unwrap();
+        let oid = Oid::from_str(oid).map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        // Make sure the entry lands in the stash reflog, which is the stash list
+        repo.reference_ensure_log("refs/stash")
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        repo.reference("refs/stash", oid, true, message)
+            .map_err(|e| GitError::UndoFailed(Box::new(e)))?;
+        Ok(())
+    }
+}
 
-        let path_str = path.to_string_lossy();
-        let mut patch = String::new();
-        patch.push_str(&format!("diff --git a/{path_str} b/{path_str}\n"));
-        patch.push_str(&format!("--- a/{path_str}\n"));
-        patch.push_str(&format!("+++ b/{path_str}\n"));
-        patch.push_str(&format!(
-            "@@ -{},{} +{},{} @@\n",
-            hunk.old_start, old_lines_count, hunk.new_start, new_lines_count
-        ));
+impl Git2Backend {
+    fn commit_graph(&self, repo: &Repository) -> Option<Arc<CommitGraph>> {
+        self.commit_graph.get(&repo.path().join("objects"))
+    }
 
-        for (prefix, content) in &patch_lines {
-            if content.ends_with('\n') {
-                patch.push_str(&format!("{prefix}{content}"));
-            } else {
-                patch.push_str(&format!("{prefix}{content}\n"));
-                patch.push_str("\\ No newline at end of file\n");
+    /// Commits on `name` that are neither in HEAD nor in `upstream`.
+    fn unmerged_commits(&self, name: &str, upstream: Option<&str>) -> GitResult<Vec<CommitInfo>> {
+        let repo = self.repo.lock().unwrap();
+        let mut revwalk = repo
+            .revwalk()
+            .map_err(|e| GitError::BranchDeleteFailed(Box::new(e)))?;
+        revwalk
+            .set_sorting(Sort::TIME | Sort::TOPOLOGICAL)
+            .map_err(|e| GitError::BranchDeleteFailed(Box::new(e)))?;
+        revwalk
+            .push_ref(&format!("refs/heads/{name}"))
+            .map_err(|e| GitError::BranchDeleteFailed(Box::new(e)))?;
+        if let Ok(head) = repo.head() {
+            if let Some(oid) = head.target() {
+                revwalk
+                    .hide(oid)
+                    .map_err(|e| GitError::BranchDeleteFailed(Box::new(e)))?;
             }
         }
+        if let Some(upstream) = upstream {
+            revwalk
+                .hide_ref(&format!("refs/remotes/{upstream}"))
+                .map_err(|e| GitError::BranchDeleteFailed(Box::new(e)))?;
+        }
 
-        Ok(patch)
+        let ref_map = build_ref_map(&repo);
+        let mut commits = Vec::new();
+        for oid in revwalk {
+            let oid = oid.map_err(|e| GitError::BranchDeleteFailed(Box::new(e)))?;
+            let commit = repo
+                .find_commit(oid)
+                .map_err(|e| GitError::BranchDeleteFailed(Box::new(e)))?;
+            commits.push(commit_to_info(&commit, &ref_map));
+        }
+        Ok(commits)
     }
 
     fn merge_after_fetch(
@@ -2473,6 +3051,157 @@ impl Git2Backend {
         })
     }
 
+    /// Replay `todo` onto `base` with in-memory rebases, one commit at a time
+    /// so the order can differ from the history. Returns the new tip and the
+    /// commits the `update-ref` steps point their refs at, or `None` when a
+    /// commit does not apply cleanly or the todo needs more than picks, e.g.
+    /// to recreate merges. Nothing is written to the working tree or refs.
+    fn replay_in_memory(
+        &self,
+        base: &str,
+        todo: &[RebaseTodoEntry],
+    ) -> GitResult<Option<(Oid, UpdatedRefs)>> {
+        let repo = self.repo.lock().unwrap();
+        let committer = repo
+            .signature()
+            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+        let mut tip = repo
+            .revparse_single(base)
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?
+            .id();
+
+        let mut update_refs = Vec::new();
+        for entry in todo {
+            let message = match entry.action {
+                RebaseAction::Pick => None,
+                RebaseAction::Reword => Some(entry.message.as_str()),
+                RebaseAction::Drop => continue,
+                RebaseAction::UpdateRef => {
+                    update_refs.push((entry.argument.clone().unwrap_or_default(), tip));
+                    continue;
+                }
+                // ラベルやマージなどは作業ツリー上のエンジンに任せる
+                _ => return Ok(None),
+            };
+            let commit = Oid::from_str(&entry.oid)
+                .and_then(|oid| repo.find_commit(oid))
+                .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+            if commit.parent_count() != 1 {
+                return Ok(None);
+            }
+            let parent = commit
+                .parent(0)
+                .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+
+            // 空のコミットは git rebase と同様に残す。Rebase では適用済み扱いになる
+            if commit.tree_id() == parent.tree_id() {
+                let onto = repo
+                    .find_commit(tip)
+                    .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+                let tree = onto
+                    .tree()
+                    .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+                tip = repo
+                    .commit(
+                        None,
+                        &commit.author(),
+                        &committer,
+                        message.unwrap_or(commit.message().unwrap_or_default()),
+                        &tree,
+                        &[&onto],
+                    )
+                    .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+                continue;
+            }
+
+            let branch = repo
+                .find_annotated_commit(commit.id())
+                .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+            let upstream = repo
+                .find_annotated_commit(parent.id())
+                .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+            let onto = repo
+                .find_annotated_commit(tip)
+                .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+            let mut options = git2::RebaseOptions::new();
+            options.inmemory(true);
+            let mut rebase = repo
+                .rebase(
+                    Some(&branch),
+                    Some(&upstream),
+                    Some(&onto),
+                    Some(&mut options),
+                )
+                .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+
+            while let Some(operation) = rebase.next() {
+                operation.map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+                let index = rebase
+                    .inmemory_index()
+                    .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+                if index.has_conflicts() {
+                    let _ = rebase.abort();
+                    return Ok(None);
+                }
+                match rebase.commit(None, &committer, message) {
+                    Ok(oid) => tip = oid,
+                    // 前のコミットの移動や削除で変更がなくなった場合は git と同様に捨てる
+                    Err(e) if e.code() == git2::ErrorCode::Applied => {}
+                    Err(e) => return Err(GitError::RebaseFailed(Box::new(e))),
+                }
+            }
+            rebase
+                .finish(None)
+                .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+        }
+        Ok(Some((tip, update_refs)))
+    }
+
+    /// Check out `tip` and point the current branch, or a detached `HEAD`, at
+    /// it, then move the `update_refs`.
+    fn finish_in_memory_rebase(
+        &self,
+        base: &str,
+        tip: Oid,
+        update_refs: &[(String, Oid)],
+    ) -> GitResult<()> {
+        let repo = self.repo.lock().unwrap();
+        let commit = repo
+            .find_commit(tip)
+            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+        let head = repo
+            .head()
+            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+        let head_tree = head
+            .peel_to_tree()
+            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+        if commit.tree_id() != head_tree.id() {
+            repo.checkout_tree(
+                commit.as_object(),
+                Some(git2::build::CheckoutBuilder::new().safe()),
+            )
+            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+        }
+
+        match head.name().filter(|_| head.is_branch()) {
+            Some(name) => {
+                let message = format!("rebase (finish): {name} onto {base}");
+                repo.reference(name, tip, true, &message)
+                    .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+            }
+            None => repo
+                .set_head_detached(tip)
+                .map_err(|e| GitError::RebaseFailed(Box::new(e)))?,
+        }
+        for (name, oid) in update_refs {
+            let message = format!("rebase (update-refs): {name}");
+            repo.reference(name, *oid, true, &message)
+                .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+        }
+        Ok(())
+    }
+
     fn merge_normal_commit(
         &self,
         repo: &Repository,
@@ -2503,6 +3232,10 @@ impl Git2Backend {
             });
         }
 
+        // フックに拒否された場合は git と同様にマージ中の状態を残す
+        let message = format!("Merge branch '{branch_name}'");
+        let message = hooks::prepare_commit(&self.workdir, &message, CommitSource::Merge, true)?;
+
         let mut index = repo
             .index()
             .map_err(|e| GitError::MergeFailed(Box::new(e)))?;
@@ -2525,7 +3258,6 @@ impl Git2Backend {
             .find_commit(annotated.id())
             .map_err(|e| GitError::MergeFailed(Box::new(e)))?;
 
-        let message = format!("Merge branch '{branch_name}'");
         let oid = repo
             .commit(
                 Some("HEAD"),
@@ -2538,6 +3270,7 @@ impl Git2Backend {
             .map_err(|e| GitError::MergeFailed(Box::new(e)))?;
 
         let _ = repo.cleanup_state();
+        hooks::post_merge(&self.workdir);
 
         Ok(MergeResult {
             kind: MergeKind::Normal,
@@ -2547,6 +3280,292 @@ impl Git2Backend {
     }
 }
 
+pub(crate) fn generate_hunk_patch(
+    backend: &dyn GitBackend,
+    path: &Path,
+    hunk: &HunkIdentifier,
+    staged: bool,
+) -> GitResult<String> {
+    let options = DiffOptions {
+        staged,
+        ..Default::default()
+    };
+    let diffs = backend.diff(Some(path), &options)?;
+
+    let file_diff = diffs
+        .first()
+        .ok_or_else(|| GitError::DiffFailed("no diff found for path".into()))?;
+
+    let matched_hunk = file_diff
+        .hunks
+        .iter()
+        .find(|h| {
+            h.old_start == hunk.old_start
+                && h.old_lines == hunk.old_lines
+                && h.new_start == hunk.new_start
+                && h.new_lines == hunk.new_lines
+        })
+        .ok_or_else(|| GitError::DiffFailed("hunk not found".into()))?;
+
+    Ok(format_hunk_patch(&path.to_string_lossy(), matched_hunk))
+}
+
+/// A patch of a single hunk, for `git apply`.
+pub(crate) fn format_hunk_patch(path_str: &str, hunk: &DiffHunk) -> String {
+    let mut patch = String::new();
+    patch.push_str(&format!("diff --git a/{path_str} b/{path_str}\n"));
+    patch.push_str(&format!("--- a/{path_str}\n"));
+    patch.push_str(&format!("+++ b/{path_str}\n"));
+    patch.push_str(&format!(
+        "@@ -{},{} +{},{} @@\n",
+        hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+    ));
+
+    for line in &hunk.lines {
+        let prefix = match line.kind {
+            DiffLineKind::Addition => "+",
+            DiffLineKind::Deletion => "-",
+            DiffLineKind::Context => " ",
+            _ => continue,
+        };
+        let content = &line.content;
+        if content.ends_with('\n') {
+            patch.push_str(&format!("{prefix}{content}"));
+        } else {
+            patch.push_str(&format!("{prefix}{content}\n"));
+            patch.push_str("\\ No newline at end of file\n");
+        }
+    }
+
+    patch
+}
+
+pub(crate) fn generate_line_patch(
+    backend: &dyn GitBackend,
+    path: &Path,
+    line_range: &LineRange,
+    staged: bool,
+) -> GitResult<String> {
+    let options = DiffOptions {
+        staged,
+        ..Default::default()
+    };
+    let diffs = backend.diff(Some(path), &options)?;
+
+    let file_diff = diffs
+        .first()
+        .ok_or_else(|| GitError::DiffFailed("no diff found for path".into()))?;
+
+    let hunk = &line_range.hunk;
+    let matched_hunk = file_diff
+        .hunks
+        .iter()
+        .find(|h| {
+            h.old_start == hunk.old_start
+                && h.old_lines == hunk.old_lines
+                && h.new_start == hunk.new_start
+                && h.new_lines == hunk.new_lines
+        })
+        .ok_or_else(|| GitError::DiffFailed("hunk not found".into()))?;
+
+    let selected: std::collections::HashSet<usize> =
+        line_range.line_indices.iter().copied().collect();
+
+    Ok(format_line_patch(
+        &path.to_string_lossy(),
+        matched_hunk,
+        &selected,
+    ))
+}
+
+/// A patch of the lines of `hunk` at `selected` indices, for `git apply`.
+/// Unselected deletions stay as context and unselected additions are left out.
+pub(crate) fn format_line_patch(
+    path_str: &str,
+    hunk: &DiffHunk,
+    selected: &std::collections::HashSet<usize>,
+) -> String {
+    let mut old_lines_count: u32 = 0;
+    let mut new_lines_count: u32 = 0;
+    let mut patch_lines = Vec::new();
+
+    for (idx, line) in hunk.lines.iter().enumerate() {
+        match line.kind {
+            DiffLineKind::Context => {
+                old_lines_count += 1;
+                new_lines_count += 1;
+                patch_lines.push((' ', &line.content));
+            }
+            DiffLineKind::Addition => {
+                if selected.contains(&idx) {
+                    new_lines_count += 1;
+                    patch_lines.push(('+', &line.content));
+                }
+            }
+            DiffLineKind::Deletion => {
+                if selected.contains(&idx) {
+                    old_lines_count += 1;
+                    patch_lines.push(('-', &line.content));
+                } else {
+                    old_lines_count += 1;
+                    new_lines_count += 1;
+                    patch_lines.push((' ', &line.content));
+                }
+            }
+            _ => continue,
+        }
+    }
+
+    let mut patch = String::new();
+    patch.push_str(&format!("diff --git a/{path_str} b/{path_str}\n"));
+    patch.push_str(&format!("--- a/{path_str}\n"));
+    patch.push_str(&format!("+++ b/{path_str}\n"));
+    patch.push_str(&format!(
+        "@@ -{},{} +{},{} @@\n",
+        hunk.old_start, old_lines_count, hunk.new_start, new_lines_count
+    ));
+
+    for (prefix, content) in &patch_lines {
+        if content.ends_with('\n') {
+            patch.push_str(&format!("{prefix}{content}"));
+        } else {
+            patch.push_str(&format!("{prefix}{content}\n"));
+            patch.push_str("\\ No newline at end of file\n");
+        }
+    }
+
+    patch
+}
+
+/// Remote callbacks that report progress to `control` and abort the transfer
+/// once it is cancelled. libgit2 cannot interrupt a push mid-upload, so push
+/// checks the token in its `push_negotiation` callback before sending the pack.
+fn transfer_callbacks(control: &TransferControl) -> git2::RemoteCallbacks<'_> {
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.credentials(create_credentials_callback());
+    callbacks.transfer_progress(move |stats| {
+        control.report(&TransferProgress {
+            total_objects: stats.total_objects() as u64,
+            transferred_objects: stats.received_objects() as u64,
+            indexed_objects: stats.indexed_objects() as u64,
+            total_deltas: stats.total_deltas() as u64,
+            indexed_deltas: stats.indexed_deltas() as u64,
+            transferred_bytes: stats.received_bytes() as u64,
+            message: None,
+        });
+        !control.is_cancelled()
+    });
+    callbacks.sideband_progress(move |data| {
+        let text = String::from_utf8_lossy(data);
+        for line in text.split(['\r', '\n']).map(str::trim) {
+            if !line.is_empty() {
+                control.report(&TransferProgress {
+                    message: Some(line.to_string()),
+                    ..TransferProgress::default()
+                });
+            }
+        }
+        !control.is_cancelled()
+    });
+    callbacks.push_transfer_progress(move |current, total, bytes| {
+        control.report(&TransferProgress {
+            total_objects: total as u64,
+            transferred_objects: current as u64,
+            transferred_bytes: bytes as u64,
+            ..TransferProgress::default()
+        });
+    });
+    callbacks
+}
+
+/// Current remote oids of the refs `push` is about to update.
+///
+/// `RemoteConnection::list` can't be used here: it builds a slice from a null
+/// pointer when the remote has no refs. Instead a push is started with every
+/// ref forced and aborted in `push_negotiation`, which receives each ref's
+/// remote oid before anything is sent.
+fn probe_push_targets(
+    remote: &mut git2::Remote,
+    refs: &[push::PushRef],
+    control: &TransferControl,
+) -> GitResult<HashMap<String, String>> {
+    let probed = RefCell::new(None);
+    let mut callbacks = transfer_callbacks(control);
+    callbacks.push_negotiation(|updates| {
+        let heads: HashMap<String, String> = updates
+            .iter()
+            .filter(|update| !update.src().is_zero())
+            .filter_map(|update| {
+                Some((update.dst_refname()?.to_string(), update.src().to_string()))
+            })
+            .collect();
+        *probed.borrow_mut() = Some(heads);
+        Err(git2::Error::from_str("probe only"))
+    });
+    let mut push_opts = git2::PushOptions::new();
+    push_opts.remote_callbacks(callbacks);
+
+    let refspecs: Vec<String> = refs.iter().map(|r| r.refspec(true)).collect();
+    let result = remote.push(&refspecs, Some(&mut push_opts));
+    drop(push_opts);
+
+    match (probed.into_inner(), result) {
+        (Some(heads), _) => Ok(heads),
+        (None, Err(e)) => Err(transfer_error(control, e, GitError::PushFailed)),
+        (None, Ok(())) => Err(GitError::PushFailed(
+            "remote did not negotiate the push".into(),
+        )),
+    }
+}
+
+fn ref_snapshot(repo: &Repository) -> Result<fetch::RefSnapshot, git2::Error> {
+    let mut snapshot = fetch::RefSnapshot::new();
+    for reference in repo.references()? {
+        let reference = reference?;
+        if let (Some(name), Some(target)) = (reference.name(), reference.target()) {
+            snapshot.insert(name.to_string(), target.to_string());
+        }
+    }
+    Ok(snapshot)
+}
+
+fn count_new_commits(repo: &Repository, tips: &[&str], hide: &[&str]) -> u32 {
+    let Ok(mut revwalk) = repo.revwalk() else {
+        return 0;
+    };
+    for oid in tips.iter().filter_map(|oid| Oid::from_str(oid).ok()) {
+        let _ = revwalk.push(oid);
+    }
+    for oid in hide.iter().filter_map(|oid| Oid::from_str(oid).ok()) {
+        let _ = revwalk.hide(oid);
+    }
+    revwalk.count() as u32
+}
+
+/// `true` when commit `new` contains `old`. Unknown objects count as "not a
+/// descendant" so that pushing over them is rejected as non-fast-forward.
+fn is_descendant(repo: &Repository, graph: Option<&CommitGraph>, new: &str, old: &str) -> bool {
+    let (Ok(new), Ok(old)) = (Oid::from_str(new), Oid::from_str(old)) else {
+        return false;
+    };
+    if graph.is_some_and(|graph| graph.cannot_descend(new.as_bytes(), old.as_bytes())) {
+        return false;
+    }
+    repo.graph_descendant_of(new, old).unwrap_or(false)
+}
+
+fn transfer_error(
+    control: &TransferControl,
+    error: git2::Error,
+    wrap: fn(Box<dyn std::error::Error + Send + Sync>) -> GitError,
+) -> GitError {
+    if control.is_cancelled() {
+        GitError::Cancelled
+    } else {
+        wrap(Box::new(error))
+    }
+}
+
 fn index_status_to_kind(status: git2::Status) -> FileStatusKind {
     if status.contains(git2::Status::INDEX_NEW) {
         FileStatusKind::Untracked
@@ -2577,6 +3596,9 @@ fn wt_status_to_kind(status: git2::Status) -> FileStatusKind {
 
 type RefMap = std::collections::HashMap<Oid, Vec<CommitRef>>;
 
+/// Refs moved by the `update-ref` steps of a rebase and their new commits.
+type UpdatedRefs = Vec<(String, Oid)>;
+
 fn build_ref_map(repo: &Repository) -> RefMap {
     let mut map: RefMap = std::collections::HashMap::new();
 
@@ -2651,6 +3673,7 @@ fn commit_to_info(commit: &git2::Commit, ref_map: &RefMap) -> CommitInfo {
         .collect();
 
     let refs = ref_map.get(&oid).cloned().unwrap_or_default();
+    let commit_trailers = trailers::parse(&message_full);
 
     CommitInfo {
         oid: oid_str,
@@ -2663,10 +3686,66 @@ fn commit_to_info(commit: &git2::Commit, ref_map: &RefMap) -> CommitInfo {
         parent_oids,
         refs,
         signature_status: SignatureStatus::None,
+        co_authors: trailers::co_authors(&commit_trailers),
+        trailers: commit_trailers,
+    }
+}
+
+/// The commits of `tip`, excluding those of `hidden`, in log order.
+fn log_order(repo: &Repository, tip: &str, hidden: Option<&str>) -> GitResult<Vec<String>> {
+    let parse = |oid: &str| Oid::from_str(oid).map_err(|e| GitError::LogFailed(Box::new(e)));
+    let mut revwalk = repo
+        .revwalk()
+        .map_err(|e| GitError::LogFailed(Box::new(e)))?;
+    revwalk
+        .set_sorting(Sort::TIME | Sort::TOPOLOGICAL)
+        .map_err(|e| GitError::LogFailed(Box::new(e)))?;
+    revwalk
+        .push(parse(tip)?)
+        .map_err(|e| GitError::LogFailed(Box::new(e)))?;
+    if let Some(hidden) = hidden {
+        revwalk
+            .hide(parse(hidden)?)
+            .map_err(|e| GitError::LogFailed(Box::new(e)))?;
+    }
+    revwalk
+        .map(|oid| oid.map(|oid| oid.to_string()))
+        .collect::<Result<_, _>>()
+        .map_err(|e| GitError::LogFailed(Box::new(e)))
+}
+
+/// Read the commits `oids` in order until `wanted` of them match the filter.
+fn walk_commits(
+    repo: &Repository,
+    graph: Option<&CommitGraph>,
+    oids: &[String],
+    filter: &LogFilter,
+    wanted: usize,
+) -> GitResult<WalkChunk> {
+    let no_refs = RefMap::new();
+    let mut chunk = WalkChunk::default();
+    for oid in oids {
+        if chunk.commits.len() >= wanted {
+            break;
+        }
+        let commit = Oid::from_str(oid)
+            .and_then(|oid| repo.find_commit(oid))
+            .map_err(|e| GitError::LogFailed(Box::new(e)))?;
+
+        chunk.visit(commit.time().seconds());
+        if matches_filter(repo, graph, &commit, filter) {
+            chunk.commits.push(commit_to_info(&commit, &no_refs));
+        }
     }
+    Ok(chunk)
 }
 
-fn matches_filter(repo: &Repository, commit: &git2::Commit, filter: &LogFilter) -> bool {
+fn matches_filter(
+    repo: &Repository,
+    graph: Option<&CommitGraph>,
+    commit: &git2::Commit,
+    filter: &LogFilter,
+) -> bool {
     if let Some(ref author) = filter.author {
         let name = commit.author().name().unwrap_or("").to_lowercase();
         if !name.contains(&author.to_lowercase()) {
@@ -2694,7 +3773,7 @@ fn matches_filter(repo: &Repository, commit: &git2::Commit, filter: &LogFilter)
     }
 
     if let Some(ref path) = filter.path {
-        if !commit_touches_path(repo, commit, path) {
+        if !commit_touches_path(repo, graph, commit, path) {
             return false;
         }
     }
@@ -2702,7 +3781,63 @@ fn matches_filter(repo: &Repository, commit: &git2::Commit, filter: &LogFilter)
     true
 }
 
-fn commit_touches_path(repo: &Repository, commit: &git2::Commit, path: &str) -> bool {
+/// Path `path` was renamed from in `commit`, when the commit added it by
+/// renaming another file.
+fn rename_source(repo: &Repository, commit: &git2::Commit, path: &str) -> Option<String> {
+    let parent_tree = commit.parent(0).ok()?.tree().ok()?;
+    if parent_tree.get_path(Path::new(path)).is_ok() {
+        return None;
+    }
+    let tree = commit.tree().ok()?;
+    let mut diff = repo
+        .diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)
+        .ok()?;
+    diff.find_similar(Some(git2::DiffFindOptions::new().renames(true)))
+        .ok()?;
+    let renamed = diff.deltas().find(|delta| {
+        delta.status() == git2::Delta::Renamed && delta.new_file().path() == Some(Path::new(path))
+    })?;
+    renamed
+        .old_file()
+        .path()
+        .map(|old| old.to_string_lossy().into_owned())
+}
+
+fn apply_stash(
+    repo: &mut Repository,
+    index: usize,
+    restore_index: bool,
+) -> GitResult<StashApplyResult> {
+    let mut opts = git2::StashApplyOptions::new();
+    if restore_index {
+        opts.reinstantiate_index();
+    }
+    repo.stash_apply(index, Some(&mut opts))
+        .map_err(|e| GitError::StashFailed(Box::new(e)))?;
+    let repo_index = repo
+        .index()
+        .map_err(|e| GitError::StashFailed(Box::new(e)))?;
+    let conflicts = if repo_index.has_conflicts() {
+        collect_conflict_paths(&repo_index)
+    } else {
+        Vec::new()
+    };
+    Ok(StashApplyResult {
+        completed: conflicts.is_empty(),
+        conflicts,
+    })
+}
+
+fn commit_touches_path(
+    repo: &Repository,
+    graph: Option<&CommitGraph>,
+    commit: &git2::Commit,
+    path: &str,
+) -> bool {
+    // A negative Bloom filter answer is exact and saves the tree diff.
+    if graph.and_then(|graph| graph.maybe_changed(commit.id().as_bytes(), path)) == Some(false) {
+        return false;
+    }
     let commit_tree = match commit.tree() {
         Ok(t) => t,
         Err(_) => return false,
@@ -2724,90 +3859,7 @@ fn commit_touches_path(repo: &Repository, commit: &git2::Commit, path: &str) ->
     diff.deltas().len() > 0
 }
 
-fn build_graph(commits: &[CommitInfo]) -> Vec<CommitGraphRow> {
-    let mut lanes: Vec<Option<String>> = Vec::new();
-    let mut rows = Vec::new();
-
-    for commit in commits {
-        // Find the lane for this commit
-        let column = lanes
-            .iter()
-            .position(|lane| lane.as_deref() == Some(&commit.oid));
-
-        let column = match column {
-            Some(col) => {
-                lanes[col] = None;
-                col
-            }
-            None => {
-                let empty = lanes.iter().position(|l| l.is_none());
-                match empty {
-                    Some(col) => col,
-                    None => {
-                        lanes.push(None);
-                        lanes.len() - 1
-                    }
-                }
-            }
-        };
-
-        let node_type = if commit.parent_oids.len() > 1 {
-            GraphNodeType::Merge
-        } else {
-            GraphNodeType::Normal
-        };
-
-        let mut edges = Vec::new();
-
-        for (i, parent_oid) in commit.parent_oids.iter().enumerate() {
-            if i == 0 {
-                // First parent goes to same lane
-                lanes[column] = Some(parent_oid.clone());
-                edges.push(GraphEdge {
-                    from_column: column,
-                    to_column: column,
-                    color_index: column,
-                });
-            } else {
-                // Additional parents get new lanes
-                let existing = lanes.iter().position(|l| l.as_deref() == Some(parent_oid));
-                let to_col = match existing {
-                    Some(col) => col,
-                    None => {
-                        let empty = lanes.iter().position(|l| l.is_none());
-                        match empty {
-                            Some(col) => {
-                                lanes[col] = Some(parent_oid.clone());
-                                col
-                            }
-                            None => {
-                                lanes.push(Some(parent_oid.clone()));
-                                lanes.len() - 1
-                            }
-                        }
-                    }
-                };
-                edges.push(GraphEdge {
-                    from_column: column,
-                    to_column: to_col,
-                    color_index: to_col,
-                });
-            }
-        }
-
-        rows.push(CommitGraphRow {
-            oid: commit.oid.clone(),
-            column,
-            node_type,
-            edges,
-        });
-    }
-
-    // Trim empty trailing lanes from each row
-    rows
-}
-
-fn run_git_apply(
+pub(crate) fn run_git_apply(
     workdir: &Path,
     patch: &str,
     extra_args: &[&str],
@@ -2957,7 +4009,7 @@ fn compute_word_diff_pair(
 
 /// Parse reflog message into action and description.
 /// Format: "action: description" (e.g. "commit: initial commit", "checkout: moving from main to feature")
-fn parse_reflog_message(message: &str) -> (String, String) {
+pub(crate) fn parse_reflog_message(message: &str) -> (String, String) {
     match message.find(": ") {
         Some(pos) => (message[..pos].to_string(), message[pos + 2..].to_string()),
         None => (message.to_string(), String::new()),
@@ -2966,7 +4018,7 @@ fn parse_reflog_message(message: &str) -> (String, String) {
 
 /// Parse branch name from git stash message.
 /// Format: "WIP on <branch>: ..." or "On <branch>: <msg>"
-fn parse_stash_branch_name(message: &str) -> String {
+pub(crate) fn parse_stash_branch_name(message: &str) -> String {
     if let Some(rest) = message.strip_prefix("WIP on ") {
         if let Some(colon_pos) = rest.find(':') {
             return rest[..colon_pos].to_string();
@@ -3057,23 +4109,7 @@ fn parse_diff_to_file_diffs(diff: &git2::Diff) -> Result<Vec<FileDiff>, git2::Er
     Ok(file_diffs)
 }
 
-fn collect_conflict_paths_from_workdir(workdir: &Path) -> Vec<String> {
-    let output = std::process::Command::new("git")
-        .args(["diff", "--name-only", "--diff-filter=U"])
-        .current_dir(workdir)
-        .output();
-
-    match output {
-        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout)
-            .lines()
-            .filter(|l| !l.is_empty())
-            .map(|l| l.to_string())
-            .collect(),
-        _ => Vec::new(),
-    }
-}
-
-fn collect_conflict_paths(index: &git2::Index) -> Vec<String> {
+pub(crate) fn collect_conflict_paths(index: &git2::Index) -> Vec<String> {
     let mut paths = Vec::new();
     if let Ok(conflicts) = index.conflicts() {
         for conflict in conflicts.flatten() {
@@ -3124,7 +4160,7 @@ fn parse_one_block(lines: &[&str], start: usize) -> (String, Option<String>, Str
     (ours, base, theirs, i)
 }
 
-fn parse_conflict_markers(content: &str) -> Vec<ConflictBlock> {
+pub(crate) fn parse_conflict_markers(content: &str) -> Vec<ConflictBlock> {
     let mut blocks = Vec::new();
     let lines: Vec<&str> = content.lines().collect();
     let mut i = 0;
@@ -3170,7 +4206,7 @@ fn get_stage_blob_content(repo: &Repository, path: &str, stage: i32) -> GitResul
     ))
 }
 
-fn resolve_single_block(
+pub(crate) fn resolve_single_block(
     content: &str,
     block_index: usize,
     resolution: &ConflictResolution,
@@ -3225,7 +4261,7 @@ fn resolve_single_block(
     Ok(result)
 }
 
-fn compute_word_diffs(file_diffs: &mut [FileDiff]) {
+pub(crate) fn compute_word_diffs(file_diffs: &mut [FileDiff]) {
     for file_diff in file_diffs.iter_mut() {
         for hunk in file_diff.hunks.iter_mut() {
             let mut i = 0;
@@ -3271,7 +4307,41 @@ fn scope_to_level(scope: GitConfigScope) -> git2::ConfigLevel {
     }
 }
 
-fn parse_signature_status(status_char: &str) -> SignatureStatus {
+pub(crate) fn verify_commit_signatures(
+    workdir: &Path,
+    oids: &[&str],
+) -> GitResult<Vec<(String, SignatureStatus)>> {
+    if oids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let output = std::process::Command::new("git")
+        .args(["log", "--no-walk", "--format=%H %G?"])
+        .args(oids)
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| GitError::SigningFailed(Box::new(e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::SigningFailed(stderr.to_string().into()));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut results = Vec::new();
+
+    for line in stdout.lines() {
+        let Some((hash, status_char)) = line.split_once(' ') else {
+            continue;
+        };
+        let status = parse_signature_status(status_char);
+        results.push((hash.to_string(), status));
+    }
+
+    Ok(results)
+}
+
+pub(crate) fn parse_signature_status(status_char: &str) -> SignatureStatus {
     match status_char.trim() {
         "G" => SignatureStatus::Good,
         "B" => SignatureStatus::Bad,
@@ -3283,35 +4353,43 @@ fn parse_signature_status(status_char: &str) -> SignatureStatus {
     }
 }
 
+/// `base` with the fields set in `over` replaced.
+fn override_signature(
+    base: &git2::Signature,
+    over: Option<&SignatureOverride>,
+) -> Result<git2::Signature<'static>, git2::Error> {
+    let Some(over) = over else {
+        return Ok(base.to_owned());
+    };
+    let name = over.name.as_deref().unwrap_or(base.name().unwrap_or(""));
+    let email = over.email.as_deref().unwrap_or(base.email().unwrap_or(""));
+    let when = match over.time {
+        Some(time) => git2::Time::new(
+            time,
+            over.offset_minutes
+                .unwrap_or_else(|| base.when().offset_minutes()),
+        ),
+        None => base.when(),
+    };
+    git2::Signature::new(name, email, &when)
+}
+
 fn create_signed_commit(
     repo: &Repository,
-    sig: &git2::Signature,
+    author: &git2::Signature,
+    committer: &git2::Signature,
     message: &str,
     tree: &git2::Tree,
     parents: &[&git2::Commit],
 ) -> GitResult<CommitResult> {
     let commit_buf = repo
-        .commit_create_buffer(sig, sig, message, tree, parents)
+        .commit_create_buffer(author, committer, message, tree, parents)
         .map_err(|e| GitError::SigningFailed(Box::new(e)))?;
 
     let commit_content =
         std::str::from_utf8(&commit_buf).map_err(|e| GitError::SigningFailed(Box::new(e)))?;
 
-    let config = repo
-        .config()
-        .map_err(|e| GitError::SigningFailed(Box::new(e)))?;
-
-    let gpg_format = config
-        .get_string("gpg.format")
-        .unwrap_or_else(|_| "openpgp".to_string());
-    let signing_key = config
-        .get_string("user.signingKey")
-        .map_err(|_| GitError::SigningFailed("user.signingKey not configured".into()))?;
-
-    let signature = match gpg_format.as_str() {
-        "ssh" => sign_with_ssh(commit_content, &signing_key)?,
-        _ => sign_with_gpg(commit_content, &signing_key)?,
-    };
+    let signature = sign_with_config(repo, commit_content)?;
 
     let oid = repo
         .commit_signed(commit_content, &signature, Some("gpgsig"))
@@ -3330,7 +4408,69 @@ fn create_signed_commit(
     })
 }
 
-fn sign_with_gpg(content: &str, key: &str) -> GitResult<String> {
+/// Sign `content` with `user.signingKey`, using GPG or SSH per `gpg.format`.
+fn sign_with_config(repo: &Repository, content: &str) -> GitResult<String> {
+    let config = repo
+        .config()
+        .map_err(|e| GitError::SigningFailed(Box::new(e)))?;
+
+    let gpg_format = config
+        .get_string("gpg.format")
+        .unwrap_or_else(|_| "openpgp".to_string());
+    let signing_key = config
+        .get_string("user.signingKey")
+        .map_err(|_| GitError::SigningFailed("user.signingKey not configured".into()))?;
+
+    match gpg_format.as_str() {
+        "ssh" => sign_with_ssh(content, &signing_key),
+        _ => sign_with_gpg(content, &signing_key),
+    }
+}
+
+/// libgit2 cannot sign tags, so the tag object is written by hand the way
+/// `git tag -s` does: the signature is appended to the message.
+fn create_signed_tag(
+    repo: &Repository,
+    name: &str,
+    target: &git2::Object,
+    tagger: &git2::Signature,
+    message: &str,
+) -> GitResult<()> {
+    let ref_name = format!("refs/tags/{name}");
+    if repo.find_reference(&ref_name).is_ok() {
+        return Err(GitError::TagFailed(
+            format!("tag '{name}' already exists").into(),
+        ));
+    }
+
+    let when = tagger.when();
+    let offset = when.offset_minutes().abs();
+    let mut content = format!(
+        "object {}\ntype {}\ntag {name}\ntagger {} <{}> {} {}{:02}{:02}\n\n{message}",
+        target.id(),
+        target.kind().map_or("commit", |kind| kind.str()),
+        tagger.name().unwrap_or(""),
+        tagger.email().unwrap_or(""),
+        when.seconds(),
+        when.sign(),
+        offset / 60,
+        offset % 60,
+    );
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&sign_with_config(repo, &content)?);
+
+    let oid = repo
+        .odb()
+        .and_then(|odb| odb.write(git2::ObjectType::Tag, content.as_bytes()))
+        .map_err(|e| GitError::TagFailed(Box::new(e)))?;
+    repo.reference(&ref_name, oid, false, "tag (signed)")
+        .map_err(|e| GitError::TagFailed(Box::new(e)))?;
+    Ok(())
+}
+
+pub(crate) fn sign_with_gpg(content: &str, key: &str) -> GitResult<String> {
     let mut child = std::process::Command::new("gpg")
         .args(["--status-fd=2", "-bsau", key])
         .stdin(std::process::Stdio::piped())
@@ -3361,7 +4501,7 @@ fn sign_with_gpg(content: &str, key: &str) -> GitResult<String> {
     String::from_utf8(output.stdout).map_err(|e| GitError::SigningFailed(Box::new(e)))
 }
 
-fn sign_with_ssh(content: &str, key_path: &str) -> GitResult<String> {
+pub(crate) fn sign_with_ssh(content: &str, key_path: &str) -> GitResult<String> {
     let buf_path = std::env::temp_dir().join(format!("rocket-ssh-sign-{}", std::process::id()));
 
     std::fs::write(&buf_path, content.as_bytes())
@@ -0,0 +1,536 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use crate::git::error::GitResult;
+use crate::git::types::{CommitGraphRow, CommitInfo, GraphEdge, GraphNodeType, LogFilter};
+
+/// Lane assignment that can be extended one commit at a time, so later pages
+/// continue the lanes of earlier ones.
+#[derive(Debug, Default)]
+pub(crate) struct GraphLayout {
+    lanes: Vec<Option<String>>,
+    rows: Vec<CommitGraphRow>,
+}
+
+impl GraphLayout {
+    pub(crate) fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub(crate) fn rows(&self) -> &[CommitGraphRow] {
+        &self.rows
+    }
+
+    pub(crate) fn push(&mut self, commit: &CommitInfo) {
+        let lanes = &mut self.lanes;
+
+        // Find the lane for this commit
+        let column = lanes
+            .iter()
+            .position(|lane| lane.as_deref() == Some(&commit.oid));
+
+        let column = match column {
+            Some(col) => {
+                lanes[col] = None;
+                col
+            }
+            None => {
+                let empty = lanes.iter().position(|l| l.is_none());
+                match empty {
+                    Some(col) => col,
+                    None => {
+                        lanes.push(None);
+                        lanes.len() - 1
+                    }
+                }
+            }
+        };
+
+        let node_type = if commit.parent_oids.len() > 1 {
+            GraphNodeType::Merge
+        } else {
+            GraphNodeType::Normal
+        };
+
+        let mut edges = Vec::new();
+
+        for (i, parent_oid) in commit.parent_oids.iter().enumerate() {
+            if i == 0 {
+                // First parent goes to same lane
+                lanes[column] = Some(parent_oid.clone());
+                edges.push(GraphEdge {
+                    from_column: column,
+                    to_column: column,
+                    color_index: column,
+                });
+            } else {
+                // Additional parents get new lanes
+                let existing = lanes.iter().position(|l| l.as_deref() == Some(parent_oid));
+                let to_col = match existing {
+                    Some(col) => col,
+                    None => {
+                        let empty = lanes.iter().position(|l| l.is_none());
+                        match empty {
+                            Some(col) => {
+                                lanes[col] = Some(parent_oid.clone());
+                                col
+                            }
+                            None => {
+                                lanes.push(Some(parent_oid.clone()));
+                                lanes.len() - 1
+                            }
+                        }
+                    }
+                };
+                edges.push(GraphEdge {
+                    from_column: column,
+                    to_column: to_col,
+                    color_index: to_col,
+                });
+            }
+        }
+
+        self.rows.push(CommitGraphRow {
+            oid: commit.oid.clone(),
+            column,
+            node_type,
+            edges,
+        });
+    }
+}
+
+/// Result of reading part of the history in log order.
+#[derive(Debug, Default)]
+pub(crate) struct WalkChunk {
+    /// Commits that matched the filter, in log order.
+    pub commits: Vec<CommitInfo>,
+    /// Number of commits read, matching or not.
+    pub visited: usize,
+    /// Committer time range of the visited commits.
+    pub newest: Option<i64>,
+    pub oldest: Option<i64>,
+}
+
+impl WalkChunk {
+    pub(crate) fn visit(&mut self, commit_time: i64) {
+        self.visited += 1;
+        self.newest = Some(self.newest.map_or(commit_time, |t| t.max(commit_time)));
+        self.oldest = Some(self.oldest.map_or(commit_time, |t| t.min(commit_time)));
+    }
+}
+
+/// Commits and graph rows of the history walked so far for one filter.
+#[derive(Debug)]
+pub(crate) struct LogCache {
+    filter: LogFilter,
+    head: String,
+    /// Every commit of the history from `head` in log order, listed once so
+    /// later pages continue at `visited` instead of walking again.
+    order: Vec<String>,
+    /// The commits of `order[..visited]` that matched the filter.
+    commits: Vec<CommitInfo>,
+    visited: usize,
+    newest: Option<i64>,
+    layout: GraphLayout,
+}
+
+impl LogCache {
+    fn new(filter: &LogFilter, head: &str, order: Vec<String>) -> Self {
+        Self {
+            filter: filter.clone(),
+            head: head.to_string(),
+            order,
+            commits: Vec::new(),
+            visited: 0,
+            newest: None,
+            layout: GraphLayout::default(),
+        }
+    }
+
+    fn append(&mut self, chunk: WalkChunk) {
+        self.commits.extend(chunk.commits);
+        self.visited += chunk.visited;
+        self.newest = self.newest.max(chunk.newest);
+    }
+
+    /// Follow HEAD to `head`: put the commits only it reaches in front and
+    /// drop the ones only the old HEAD reached. Returns `false` when the log
+    /// order after the move can't be derived from the cache and it has to be
+    /// rebuilt.
+    fn move_head(
+        &mut self,
+        head: &str,
+        list: &mut impl FnMut(&str, Option<&str>) -> GitResult<Vec<String>>,
+        walk: &mut impl FnMut(&[String], usize) -> GitResult<WalkChunk>,
+    ) -> GitResult<bool> {
+        let mut added = list(head, Some(&self.head))?;
+        let removed: HashSet<String> = list(&self.head, Some(head))?.into_iter().collect();
+        let chunk = walk(&added, usize::MAX)?;
+        // New commits sort in front of every cached one only when none of
+        // them is older. Ties go to the new commits, as a topological walk
+        // does for descendants.
+        if let (Some(oldest_added), Some(newest)) = (chunk.oldest, self.newest) {
+            if oldest_added < newest {
+                return Ok(false);
+            }
+        }
+
+        let removed_visited = self.order[..self.visited]
+            .iter()
+            .filter(|oid| removed.contains(*oid))
+            .count();
+        self.order.retain(|oid| !removed.contains(oid));
+        self.commits.retain(|commit| !removed.contains(&commit.oid));
+
+        added.append(&mut self.order);
+        self.order = added;
+        let mut commits = chunk.commits;
+        commits.append(&mut self.commits);
+        self.commits = commits;
+        self.visited = self.visited - removed_visited + chunk.visited;
+        self.newest = self.newest.max(chunk.newest);
+        self.head = head.to_string();
+        // Lanes depend on every row above them, so the layout restarts from
+        // the top and is rebuilt lazily as pages are requested again.
+        self.layout = GraphLayout::default();
+        Ok(true)
+    }
+}
+
+/// Return `limit` commits after `skip` and their graph rows, reading only the
+/// part of the history not cached yet.
+///
+/// `list(tip, hidden)` returns the commits of `tip ^hidden` in log order and
+/// is called once per HEAD; `walk(oids, wanted)` reads `oids` in order until
+/// `wanted` of them match the filter. A HEAD move keeps the cached commits
+/// where possible.
+pub(crate) fn commit_log_page(
+    cache: &Mutex<Option<LogCache>>,
+    filter: &LogFilter,
+    head: &str,
+    limit: usize,
+    skip: usize,
+    mut list: impl FnMut(&str, Option<&str>) -> GitResult<Vec<String>>,
+    mut walk: impl FnMut(&[String], usize) -> GitResult<WalkChunk>,
+) -> GitResult<(Vec<CommitInfo>, Vec<CommitGraphRow>)> {
+    let mut guard = cache.lock().unwrap();
+    let mut entry = match guard.take().filter(|entry| entry.filter == *filter) {
+        Some(entry) if entry.head == head => entry,
+        Some(mut entry) => {
+            // The old HEAD may be gone (e.g. after gc); start over in that case.
+            match entry.move_head(head, &mut list, &mut walk) {
+                Ok(true) => entry,
+                _ => LogCache::new(filter, head, list(head, None)?),
+            }
+        }
+        None => LogCache::new(filter, head, list(head, None)?),
+    };
+
+    let end = skip.saturating_add(limit);
+    if entry.commits.len() < end && entry.visited < entry.order.len() {
+        let chunk = walk(&entry.order[entry.visited..], end - entry.commits.len())?;
+        entry.append(chunk);
+    }
+
+    let end = end.min(entry.commits.len());
+    let start = skip.min(end);
+    while entry.layout.len() < end {
+        let next = &entry.commits[entry.layout.len()];
+        entry.layout.push(next);
+    }
+
+    let commits = entry.commits[start..end].to_vec();
+    let graph = entry.layout.rows()[start..end].to_vec();
+    *guard = Some(entry);
+    Ok((commits, graph))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::types::SignatureStatus;
+
+    fn commit(oid: &str, parents: &[&str]) -> CommitInfo {
+        CommitInfo {
+            oid: oid.to_string(),
+            short_oid: oid.to_string(),
+            message: String::new(),
+            body: String::new(),
+            author_name: String::new(),
+            author_email: String::new(),
+            author_date: 0,
+            parent_oids: parents.iter().map(|p| p.to_string()).collect(),
+            refs: Vec::new(),
+            signature_status: SignatureStatus::None,
+            trailers: Vec::new(),
+            co_authors: Vec::new(),
+        }
+    }
+
+    fn dated(oid: &str, parents: &[&str], time: i64) -> CommitInfo {
+        CommitInfo {
+            author_date: time,
+            ..commit(oid, parents)
+        }
+    }
+
+    fn oids(list: &[&str]) -> Vec<String> {
+        list.iter().map(|oid| oid.to_string()).collect()
+    }
+
+    /// Read `oids` from `history` the way a backend's `walk` does, with
+    /// `author_date` standing in for the commit time.
+    fn read(
+        history: &[CommitInfo],
+        oids: &[String],
+        wanted: usize,
+        keep: impl Fn(&CommitInfo) -> bool,
+    ) -> WalkChunk {
+        let mut chunk = WalkChunk::default();
+        for oid in oids {
+            if chunk.commits.len() >= wanted {
+                break;
+            }
+            let commit = history.iter().find(|c| c.oid == *oid).unwrap();
+            chunk.visit(commit.author_date);
+            if keep(commit) {
+                chunk.commits.push(commit.clone());
+            }
+        }
+        chunk
+    }
+
+    fn history() -> Vec<CommitInfo> {
+        vec![
+            commit("m", &["c", "f"]),
+            commit("f", &["b"]),
+            commit("c", &["b"]),
+            commit("b", &["a"]),
+            commit("a", &[]),
+        ]
+    }
+
+    #[test]
+    fn pages_continue_the_lanes_of_earlier_pages() {
+        let cache = Mutex::new(None);
+        let filter = LogFilter::default();
+        let mut full = GraphLayout::default();
+        for commit in &history() {
+            full.push(commit);
+        }
+        let full = full.rows();
+        let mut lists = 0;
+        let mut walks = Vec::new();
+
+        let mut page = |skip| {
+            commit_log_page(
+                &cache,
+                &filter,
+                "m",
+                2,
+                skip,
+                |tip, hidden| {
+                    lists += 1;
+                    assert_eq!((tip, hidden), ("m", None));
+                    Ok(oids(&["m", "f", "c", "b", "a"]))
+                },
+                |oids, wanted| {
+                    walks.push(oids[0].clone());
+                    Ok(read(&history(), oids, wanted, |_| true))
+                },
+            )
+            .unwrap()
+        };
+
+        let (first, _) = page(0);
+        let (second, graph) = page(2);
+        let (again, _) = page(0);
+
+        assert_eq!(first[0].oid, "m");
+        assert_eq!(second[0].oid, "c");
+        assert_eq!(again[1].oid, "f");
+        assert_eq!(graph[0].column, full[2].column);
+        assert_eq!(graph[1].column, full[3].column);
+        // The history is listed once and the second page resumes where the
+        // first one stopped.
+        assert_eq!(lists, 1);
+        assert_eq!(walks, vec!["m", "c"]);
+    }
+
+    #[test]
+    fn new_commits_on_top_keep_the_cached_history() {
+        let cache = Mutex::new(None);
+        let filter = LogFilter::default();
+        let history = vec![
+            dated("c", &["b"], 2),
+            dated("b", &["a"], 1),
+            dated("a", &[], 1),
+        ];
+        commit_log_page(
+            &cache,
+            &filter,
+            "b",
+            10,
+            0,
+            |_, _| Ok(oids(&["b", "a"])),
+            |oids, wanted| Ok(read(&history, oids, wanted, |_| true)),
+        )
+        .unwrap();
+
+        let (commits, graph) = commit_log_page(
+            &cache,
+            &filter,
+            "c",
+            10,
+            0,
+            |tip, hidden| match (tip, hidden) {
+                ("c", Some("b")) => Ok(oids(&["c"])),
+                ("b", Some("c")) => Ok(Vec::new()),
+                _ => panic!("the cached history should be reused"),
+            },
+            |oids, wanted| {
+                assert_eq!(oids, ["c"]);
+                Ok(read(&history, oids, wanted, |_| true))
+            },
+        )
+        .unwrap();
+
+        let oids: Vec<_> = commits.iter().map(|c| c.oid.as_str()).collect();
+        assert_eq!(oids, vec!["c", "b", "a"]);
+        assert_eq!(graph.len(), 3);
+    }
+
+    #[test]
+    fn rewritten_commits_are_replaced() {
+        let cache = Mutex::new(None);
+        let filter = LogFilter::default();
+        let history = vec![
+            dated("b2", &["a"], 1),
+            dated("b", &["a"], 1),
+            dated("a", &[], 1),
+        ];
+        commit_log_page(
+            &cache,
+            &filter,
+            "b",
+            10,
+            0,
+            |_, _| Ok(oids(&["b", "a"])),
+            |oids, wanted| Ok(read(&history, oids, wanted, |_| true)),
+        )
+        .unwrap();
+
+        let (commits, _) = commit_log_page(
+            &cache,
+            &filter,
+            "b2",
+            10,
+            0,
+            |tip, _| match tip {
+                "b2" => Ok(oids(&["b2"])),
+                _ => Ok(oids(&["b"])),
+            },
+            |oids, wanted| Ok(read(&history, oids, wanted, |_| true)),
+        )
+        .unwrap();
+
+        let oids: Vec<_> = commits.iter().map(|c| c.oid.as_str()).collect();
+        assert_eq!(oids, vec!["b2", "a"]);
+    }
+
+    #[test]
+    fn older_new_commits_rebuild_the_cache() {
+        let cache = Mutex::new(None);
+        let filter = LogFilter::default();
+        let history = vec![
+            dated("m", &["b", "x"], 3),
+            dated("b", &["a"], 5),
+            dated("x", &["a"], 3),
+            dated("a", &[], 5),
+        ];
+        commit_log_page(
+            &cache,
+            &filter,
+            "b",
+            10,
+            0,
+            |_, _| Ok(oids(&["b", "a"])),
+            |oids, wanted| Ok(read(&history, oids, wanted, |_| true)),
+        )
+        .unwrap();
+
+        let (commits, _) = commit_log_page(
+            &cache,
+            &filter,
+            "m",
+            10,
+            0,
+            |tip, hidden| match (tip, hidden) {
+                ("m", Some("b")) => Ok(oids(&["m", "x"])),
+                ("b", Some("m")) => Ok(Vec::new()),
+                _ => Ok(oids(&["m", "b", "x", "a"])),
+            },
+            |oids, wanted| Ok(read(&history, oids, wanted, |_| true)),
+        )
+        .unwrap();
+
+        let oids: Vec<_> = commits.iter().map(|c| c.oid.as_str()).collect();
+        assert_eq!(oids, vec!["m", "b", "x", "a"]);
+    }
+
+    #[test]
+    fn filtered_views_follow_head_moves() {
+        let cache = Mutex::new(None);
+        let filter = LogFilter {
+            message: Some("keep".to_string()),
+            ..LogFilter::default()
+        };
+        let keep = |commit: &CommitInfo| commit.message == "keep";
+        let kept = |oid, parents, time| CommitInfo {
+            message: "keep".to_string(),
+            ..dated(oid, parents, time)
+        };
+        let history = vec![
+            kept("c2", &["b2"], 2),
+            dated("b2", &["a"], 2),
+            dated("b", &["a"], 1),
+            kept("a", &[], 1),
+        ];
+        let (commits, _) = commit_log_page(
+            &cache,
+            &filter,
+            "b",
+            10,
+            0,
+            |_, _| Ok(oids(&["b", "a"])),
+            |oids, wanted| Ok(read(&history, oids, wanted, keep)),
+        )
+        .unwrap();
+        assert_eq!(commits.len(), 1);
+
+        let mut walks = Vec::new();
+        let (commits, _) = commit_log_page(
+            &cache,
+            &filter,
+            "c2",
+            10,
+            0,
+            |tip, hidden| match (tip, hidden) {
+                ("c2", Some("b")) => Ok(oids(&["c2", "b2"])),
+                ("b", Some("c2")) => Ok(oids(&["b"])),
+                _ => panic!("the cached history should be reused"),
+            },
+            |oids, wanted| {
+                walks.push(oids.to_vec());
+                Ok(read(&history, oids, wanted, keep))
+            },
+        )
+        .unwrap();
+
+        let oids: Vec<_> = commits.iter().map(|c| c.oid.as_str()).collect();
+        assert_eq!(oids, vec!["c2", "a"]);
+        // Only the new commits are read; the rest of the history is known to
+        // be walked already.
+        assert_eq!(walks, vec![vec!["c2".to_string(), "b2".to_string()]]);
+    }
+}
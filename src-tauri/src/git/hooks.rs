@@ -0,0 +1,319 @@
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::git::commit_lint;
+use crate::git::error::{GitError, GitResult};
+
+/// Output of a hook that exited with a non-zero status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookError {
+    pub hook: String,
+    /// `None` when the hook could not be started or was killed by a signal.
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl fmt::Display for HookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.exit_code {
+            Some(code) => write!(f, "{} hook exited with status {code}", self.hook)?,
+            None => write!(f, "{} hook did not run to completion", self.hook)?,
+        }
+        let output = [self.stderr.trim(), self.stdout.trim()]
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !output.is_empty() {
+            write!(f, ": {output}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for HookError {}
+
+/// How a commit comes about, which decides the hooks run before it and the
+/// arguments `prepare-commit-msg` receives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CommitSource {
+    /// A new commit from a message, including commits created by cherry-pick
+    /// and revert.
+    Message,
+    /// `commit --amend`.
+    Amend,
+    /// The merge commit written by a merge that did not conflict.
+    Merge,
+    /// Finishing a merge, cherry-pick or revert that stopped on conflicts.
+    InProgress,
+}
+
+impl CommitSource {
+    fn pre_hook(self) -> &'static str {
+        match self {
+            CommitSource::Merge => "pre-merge-commit",
+            _ => "pre-commit",
+        }
+    }
+
+    fn prepare_args(self) -> &'static [&'static str] {
+        match self {
+            CommitSource::Message => &["message"],
+            CommitSource::Amend => &["commit", "HEAD"],
+            CommitSource::Merge | CommitSource::InProgress => &["merge"],
+        }
+    }
+}
+
+/// `git rev-parse --git-path` so that `core.hooksPath` and linked worktrees
+/// resolve the same way git resolves them.
+fn git_path(workdir: &Path, name: &str) -> Option<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", name])
+        .current_dir(workdir)
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Some(workdir.join(path))
+}
+
+fn hooks_dir(workdir: &Path) -> Option<PathBuf> {
+    git_path(workdir, "hooks")
+}
+
+fn find_hook(dir: &Path, name: &str) -> Option<PathBuf> {
+    let path = dir.join(name);
+    let metadata = path.metadata().ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        // git は実行権限のないフックを無視する
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return None;
+        }
+    }
+    Some(path)
+}
+
+fn run_hook(
+    workdir: &Path,
+    path: &Path,
+    name: &str,
+    args: &[&str],
+    input: Option<&str>,
+) -> GitResult<()> {
+    let failed = |exit_code, stdout: &[u8], stderr: &[u8]| {
+        GitError::HookRejected(Box::new(HookError {
+            hook: name.to_string(),
+            exit_code,
+            stdout: String::from_utf8_lossy(stdout).into_owned(),
+            stderr: String::from_utf8_lossy(stderr).into_owned(),
+        }))
+    };
+
+    // Windows ではスクリプトを直接起動できないので Git for Windows の sh に渡す
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("sh");
+        command.arg(path);
+        command
+    } else {
+        Command::new(path)
+    };
+    command
+        .args(args)
+        .current_dir(workdir)
+        .env("GIT_EDITOR", ":")
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| failed(None, b"", e.to_string().as_bytes()))?;
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        // フックが入力を読まずに終了しても失敗扱いにしない
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| failed(None, b"", e.to_string().as_bytes()))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(failed(output.status.code(), &output.stdout, &output.stderr))
+    }
+}
+
+/// Run the hooks git runs before writing a commit and return the message as
+/// the hooks left it. `verify = false` skips `pre-commit`/`pre-merge-commit`
+/// and `commit-msg`, like `git commit --no-verify`.
+pub(crate) fn prepare_commit(
+    workdir: &Path,
+    message: &str,
+    source: CommitSource,
+    verify: bool,
+) -> GitResult<String> {
+    let Some(dir) = hooks_dir(workdir) else {
+        return Ok(message.to_string());
+    };
+    if let Some(pre) = verify.then(|| find_hook(&dir, source.pre_hook())).flatten() {
+        run_hook(workdir, &pre, source.pre_hook(), &[], None)?;
+    }
+    let prepare = find_hook(&dir, "prepare-commit-msg");
+    let commit_msg = verify.then(|| find_hook(&dir, "commit-msg")).flatten();
+    if prepare.is_none() && commit_msg.is_none() {
+        return Ok(message.to_string());
+    }
+
+    let file = git_path(workdir, "COMMIT_EDITMSG")
+        .ok_or_else(|| GitError::CommitFailed("git directory not found".into()))?;
+    std::fs::write(&file, message).map_err(|e| GitError::CommitFailed(Box::new(e)))?;
+    let file_arg = file.to_string_lossy();
+    if let Some(prepare) = prepare {
+        let mut args = vec![file_arg.as_ref()];
+        args.extend(source.prepare_args());
+        run_hook(workdir, &prepare, "prepare-commit-msg", &args, None)?;
+    }
+    if let Some(commit_msg) = commit_msg {
+        run_hook(workdir, &commit_msg, "commit-msg", &[&file_arg], None)?;
+    }
+    std::fs::read_to_string(&file).map_err(|e| GitError::CommitFailed(Box::new(e)))
+}
+
+/// [`prepare_commit`] for a message the commit lint rules must allow. The
+/// message is checked before any hook runs, so a rejected one leaves no hook
+/// side effects behind, and checked again if the hooks changed it.
+pub(crate) fn prepare_linted_commit(
+    workdir: &Path,
+    message: &str,
+    source: CommitSource,
+    verify: bool,
+) -> GitResult<String> {
+    commit_lint::enforce(workdir, message)?;
+    let prepared = prepare_commit(workdir, message, source, verify)?;
+    if prepared != message {
+        commit_lint::enforce(workdir, &prepared)?;
+    }
+    Ok(prepared)
+}
+
+/// `post-commit` cannot undo the commit, so its result is ignored as git does.
+pub(crate) fn post_commit(workdir: &Path) {
+    run_notification(workdir, "post-commit", &[]);
+}
+
+/// Runs after a merge that updated HEAD, fast-forwards included.
+pub(crate) fn post_merge(workdir: &Path) {
+    run_notification(workdir, "post-merge", &["0"]);
+}
+
+fn run_notification(workdir: &Path, name: &str, args: &[&str]) {
+    if let Some(hook) = hooks_dir(workdir).and_then(|dir| find_hook(&dir, name)) {
+        let _ = run_hook(workdir, &hook, name, args, None);
+    }
+}
+
+/// One line of `pre-push`'s standard input.
+pub(crate) struct PushUpdate {
+    /// `None` when the remote ref is deleted.
+    pub local: Option<(String, String)>,
+    pub remote_ref: String,
+    /// `None` when the remote ref does not exist yet, or is not known.
+    pub remote_oid: Option<String>,
+}
+
+const ZERO_OID: &str = "0000000000000000000000000000000000000000";
+
+fn pre_push_input(updates: &[PushUpdate]) -> String {
+    updates
+        .iter()
+        .map(|update| {
+            let (local_ref, local_oid) = match &update.local {
+                Some((name, oid)) => (name.as_str(), oid.as_str()),
+                None => ("(delete)", ZERO_OID),
+            };
+            let remote_oid = update.remote_oid.as_deref().unwrap_or(ZERO_OID);
+            format!(
+                "{local_ref} {local_oid} {} {remote_oid}\n",
+                update.remote_ref
+            )
+        })
+        .collect()
+}
+
+/// Run `pre-push` with the remote's name and URL, and one line per ref that is
+/// about to be sent.
+pub(crate) fn pre_push(
+    workdir: &Path,
+    remote: &str,
+    url: &str,
+    updates: &[PushUpdate],
+) -> GitResult<()> {
+    let Some(hook) = hooks_dir(workdir).and_then(|dir| find_hook(&dir, "pre-push")) else {
+        return Ok(());
+    };
+    run_hook(
+        workdir,
+        &hook,
+        "pre-push",
+        &[remote, url],
+        Some(&pre_push_input(updates)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pre_push_input_lists_updates_and_deletes() {
+        let updates = [
+            PushUpdate {
+                local: Some(("refs/heads/main".to_string(), "a".repeat(40))),
+                remote_ref: "refs/heads/main".to_string(),
+                remote_oid: Some("b".repeat(40)),
+            },
+            PushUpdate {
+                local: None,
+                remote_ref: "refs/tags/v1".to_string(),
+                remote_oid: None,
+            },
+        ];
+        assert_eq!(
+            pre_push_input(&updates),
+            format!(
+                "refs/heads/main {} refs/heads/main {}\n(delete) {ZERO_OID} refs/tags/v1 {ZERO_OID}\n",
+                "a".repeat(40),
+                "b".repeat(40)
+            )
+        );
+    }
+
+    #[test]
+    fn hook_error_display_includes_output() {
+        let err = HookError {
+            hook: "pre-commit".to_string(),
+            exit_code: Some(1),
+            stdout: "checking\n".to_string(),
+            stderr: "secret found\n".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "pre-commit hook exited with status 1: secret found\nchecking"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::git::backend::GitBackend;
+use crate::git::error::{GitError, GitResult};
+use crate::git::types::{GitConfigScope, IdentityCheck, IdentityMismatchAction, IdentityProfile};
+
+/// `*` matches any run of characters, `/` included, and `?` any one character.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // 直前の `*` の位置と、そこから試しているテキスト位置
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn expand_home(pattern: &str) -> String {
+    match (pattern.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) => format!("{}{rest}", home.to_string_lossy()),
+        _ => pattern.to_string(),
+    }
+}
+
+/// A path pattern matches the repository or any directory above it, so
+/// `~/work` covers every repository under it.
+pub fn profile_matches(profile: &IdentityProfile, path: &Path, remote_urls: &[String]) -> bool {
+    let path_match = profile.path_patterns.iter().any(|pattern| {
+        let pattern = expand_home(pattern);
+        let pattern = pattern.trim_end_matches('/');
+        path.ancestors()
+            .any(|dir| glob_match(pattern, &dir.to_string_lossy()))
+    });
+    path_match
+        || profile
+            .remote_patterns
+            .iter()
+            .any(|pattern| remote_urls.iter().any(|url| glob_match(pattern, url)))
+}
+
+/// The first profile, in list order, that matches the repository.
+pub fn select_profile<'a>(
+    profiles: &'a [IdentityProfile],
+    path: &Path,
+    remote_urls: &[String],
+) -> Option<&'a IdentityProfile> {
+    profiles
+        .iter()
+        .find(|profile| profile_matches(profile, path, remote_urls))
+}
+
+/// `git config --get`, so `includeIf` and the environment count as they do
+/// for `git commit`.
+fn config_value(workdir: &Path, key: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["config", "--get", key])
+        .current_dir(workdir)
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
+
+/// Compare the repository's identity with `profile`. `author_email`, when
+/// given, is checked in place of `user.email`.
+pub fn check(
+    workdir: &Path,
+    profile: Option<&IdentityProfile>,
+    action: IdentityMismatchAction,
+    author_email: Option<&str>,
+) -> IdentityCheck {
+    let email = author_email
+        .map(str::to_string)
+        .or_else(|| config_value(workdir, "user.email"));
+    let matches = match profile {
+        Some(profile) => email
+            .as_deref()
+            .is_some_and(|email| email.eq_ignore_ascii_case(&profile.email)),
+        None => true,
+    };
+    IdentityCheck {
+        profile: profile.cloned(),
+        name: config_value(workdir, "user.name"),
+        email,
+        matches,
+        action,
+    }
+}
+
+/// Reject a commit when the check failed and the profile blocks mismatches.
+pub fn enforce(check: &IdentityCheck) -> GitResult<()> {
+    match &check.profile {
+        Some(profile) if !check.matches && check.action == IdentityMismatchAction::Block => {
+            Err(GitError::IdentityMismatch(
+                format!(
+                    "committing as {} but profile '{}' expects {}",
+                    check.email.as_deref().unwrap_or("(no user.email)"),
+                    profile.label,
+                    profile.email
+                )
+                .into(),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Write the profile's identity and signing key to the repository's config.
+pub fn apply_profile(backend: &dyn GitBackend, profile: &IdentityProfile) -> GitResult<()> {
+    backend.set_gitconfig_value(GitConfigScope::Local, "user.name", &profile.name)?;
+    backend.set_gitconfig_value(GitConfigScope::Local, "user.email", &profile.email)?;
+    if let Some(key) = &profile.signing_key {
+        backend.set_gitconfig_value(GitConfigScope::Local, "user.signingKey", key)?;
+    }
+    if let Some(format) = profile.signing_format {
+        backend.set_gitconfig_value(
+            GitConfigScope::Local,
+            "gpg.format",
+            format.as_config_value(),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(label: &str, paths: &[&str], remotes: &[&str]) -> IdentityProfile {
+        IdentityProfile {
+            label: label.to_string(),
+            name: label.to_string(),
+            email: format!("{label}@example.com"),
+            signing_key: None,
+            signing_format: None,
+            remote_patterns: remotes.iter().map(|s| s.to_string()).collect(),
+            path_patterns: paths.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn glob_match_handles_stars_and_question_marks() {
+        assert!(glob_match(
+            "git@github.com:acme/*",
+            "git@github.com:acme/api.git"
+        ));
+        assert!(glob_match(
+            "*github.com*acme/*",
+            "https://github.com/acme/web"
+        ));
+        assert!(glob_match("/srv/repo?", "/srv/repo1"));
+        assert!(!glob_match(
+            "git@github.com:acme/*",
+            "git@github.com:me/api.git"
+        ));
+        assert!(!glob_match("/srv/repo?", "/srv/repo"));
+    }
+
+    #[test]
+    fn select_profile_matches_ancestors_and_remotes_in_order() {
+        let profiles = [
+            profile("work", &["/home/me/work"], &["*github.com*acme/*"]),
+            profile("personal", &["/home/me/*"], &[]),
+        ];
+        let select = |path: &str, urls: &[&str]| {
+            let urls: Vec<String> = urls.iter().map(|s| s.to_string()).collect();
+            select_profile(&profiles, Path::new(path), &urls).map(|p| p.label.as_str())
+        };
+
+        assert_eq!(select("/home/me/work/api", &[]), Some("work"));
+        assert_eq!(
+            select("/home/me/oss/x", &["git@github.com:acme/x.git"]),
+            Some("work")
+        );
+        assert_eq!(select("/home/me/oss/x", &[]), Some("personal"));
+        assert_eq!(select("/tmp/x", &[]), None);
+    }
+
+    #[test]
+    fn enforce_blocks_only_mismatches_with_block_action() {
+        let mut check = IdentityCheck {
+            profile: Some(profile("work", &[], &[])),
+            name: None,
+            email: Some("me@home.org".to_string()),
+            matches: false,
+            action: IdentityMismatchAction::Warn,
+        };
+        assert!(enforce(&check).is_ok());
+
+        check.action = IdentityMismatchAction::Block;
+        assert!(matches!(
+            enforce(&check),
+            Err(GitError::IdentityMismatch(_))
+        ));
+
+        check.matches = true;
+        assert!(enforce(&check).is_ok());
+    }
+}
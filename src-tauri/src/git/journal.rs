@@ -0,0 +1,205 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::git::backend::GitBackend;
+use crate::git::error::{GitError, GitResult};
+use crate::git::types::{FileState, OperationEntry, OperationKind, RefState, StashState};
+
+/// Only the most recent operations are kept.
+const MAX_ENTRIES: usize = 50;
+
+/// Repository primitives the journal needs to take and restore snapshots.
+pub(crate) trait JournalRepo: GitBackend {
+    fn git_dir(&self) -> GitResult<PathBuf>;
+    /// Full name of the branch HEAD points at, `None` when detached.
+    fn head_ref_name(&self) -> Option<String>;
+    fn read_ref(&self, name: &str) -> Option<String>;
+    fn write_index_tree(&self) -> GitResult<String>;
+    /// Tracked files whose working tree content differs from the index.
+    fn unstaged_files(&self) -> GitResult<Vec<String>>;
+    /// Store a working tree file as a blob, `None` when the file is missing.
+    fn write_worktree_blob(&self, path: &str) -> GitResult<Option<String>>;
+
+    fn set_ref(&self, name: &str, oid: Option<&str>, message: &str) -> GitResult<()>;
+    /// Move HEAD (and the branch it points at) to `oid`, like `reset --soft`
+    /// or `reset --hard`.
+    fn reset_head(&self, oid: &str, hard: bool) -> GitResult<()>;
+    fn read_index_tree(&self, tree: &str) -> GitResult<()>;
+    /// Overwrite tracked working tree files with their index content.
+    fn checkout_index(&self) -> GitResult<()>;
+    fn read_blob(&self, oid: &str) -> GitResult<Vec<u8>>;
+    fn store_stash(&self, oid: &str, message: &str) -> GitResult<()>;
+}
+
+/// What to capture before an operation.
+#[derive(Debug, Default)]
+pub(crate) struct SnapshotPlan {
+    /// Refs to restore. `HEAD` stands for the branch HEAD points at.
+    pub refs: Vec<String>,
+    pub index: bool,
+    /// Working tree files the operation overwrites.
+    pub files: Vec<String>,
+    /// The operation rewrites the working tree (`reset --hard`, rebase).
+    pub worktree: bool,
+    pub stash: Option<StashState>,
+}
+
+fn undo_error(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> GitError {
+    GitError::UndoFailed(e.into())
+}
+
+fn journal_path(git_dir: &Path) -> PathBuf {
+    git_dir.join("rocket").join("journal.json")
+}
+
+fn load(git_dir: &Path) -> GitResult<Vec<OperationEntry>> {
+    let path = journal_path(git_dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(undo_error)?;
+    serde_json::from_str(&content).map_err(undo_error)
+}
+
+fn save(git_dir: &Path, entries: &[OperationEntry]) -> GitResult<()> {
+    let path = journal_path(git_dir);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(undo_error)?;
+    }
+    let content = serde_json::to_string_pretty(entries).map_err(undo_error)?;
+    fs::write(&path, content).map_err(undo_error)
+}
+
+fn snapshot(
+    repo: &impl JournalRepo,
+    kind: OperationKind,
+    description: String,
+    plan: SnapshotPlan,
+) -> GitResult<OperationEntry> {
+    let refs = plan
+        .refs
+        .iter()
+        .map(|name| {
+            let name = match name.as_str() {
+                "HEAD" => repo.head_ref_name().unwrap_or_else(|| "HEAD".to_string()),
+                _ => name.clone(),
+            };
+            let oid = repo.read_ref(&name);
+            RefState { name, oid }
+        })
+        .collect();
+
+    let index_tree = if plan.index || plan.worktree {
+        Some(repo.write_index_tree()?)
+    } else {
+        None
+    };
+
+    let mut paths = plan.files;
+    if plan.worktree {
+        for path in repo.unstaged_files()? {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    let files = paths
+        .into_iter()
+        .map(|path| {
+            let blob = repo.write_worktree_blob(&path)?;
+            Ok(FileState { path, blob })
+        })
+        .collect::<GitResult<Vec<_>>>()?;
+
+    Ok(OperationEntry {
+        id: 0,
+        kind,
+        description,
+        timestamp: chrono::Utc::now().timestamp(),
+        refs,
+        index_tree,
+        files,
+        stash: plan.stash,
+        restore_worktree: plan.worktree,
+    })
+}
+
+/// Snapshot the state described by `plan`, then run `operation`. The entry is
+/// only kept in the journal when the operation succeeds.
+pub(crate) fn journaled<T>(
+    repo: &impl JournalRepo,
+    kind: OperationKind,
+    description: impl Into<String>,
+    plan: SnapshotPlan,
+    operation: impl FnOnce() -> GitResult<T>,
+) -> GitResult<T> {
+    let mut entry = snapshot(repo, kind, description.into(), plan)?;
+    let value = operation()?;
+
+    let git_dir = repo.git_dir()?;
+    let mut entries = load(&git_dir)?;
+    entry.id = entries.last().map_or(1, |last| last.id + 1);
+    entries.push(entry);
+    let overflow = entries.len().saturating_sub(MAX_ENTRIES);
+    entries.drain(..overflow);
+    save(&git_dir, &entries)?;
+    Ok(value)
+}
+
+/// Recorded operations, newest first.
+pub(crate) fn list(repo: &impl JournalRepo) -> GitResult<Vec<OperationEntry>> {
+    let mut entries = load(&repo.git_dir()?)?;
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Restore the state captured before the most recent operation and remove it
+/// from the journal.
+pub(crate) fn undo_last(repo: &impl JournalRepo) -> GitResult<OperationEntry> {
+    if repo.is_rebasing()? || repo.is_merging()? {
+        return Err(undo_error(
+            "finish or abort the operation in progress first",
+        ));
+    }
+
+    let git_dir = repo.git_dir()?;
+    let mut entries = load(&git_dir)?;
+    let entry = entries.pop().ok_or_else(|| undo_error("nothing to undo"))?;
+    let message = format!("undo: {}", entry.description);
+
+    let head = repo.head_ref_name().unwrap_or_else(|| "HEAD".to_string());
+    for state in &entry.refs {
+        match state.oid.as_deref() {
+            Some(oid) if state.name == head => repo.reset_head(oid, entry.restore_worktree)?,
+            oid => repo.set_ref(&state.name, oid, &message)?,
+        }
+    }
+
+    if let Some(tree) = &entry.index_tree {
+        repo.read_index_tree(tree)?;
+        if entry.restore_worktree {
+            repo.checkout_index()?;
+        }
+    }
+
+    for file in &entry.files {
+        let path = repo.workdir().join(&file.path);
+        match &file.blob {
+            Some(blob) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(undo_error)?;
+                }
+                fs::write(&path, repo.read_blob(blob)?).map_err(undo_error)?;
+            }
+            None if path.exists() => fs::remove_file(&path).map_err(undo_error)?,
+            None => {}
+        }
+    }
+
+    if let Some(stash) = &entry.stash {
+        repo.store_stash(&stash.oid, &stash.message)?;
+    }
+
+    save(&git_dir, &entries)?;
+    Ok(entry)
+}
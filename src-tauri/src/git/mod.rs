@@ -1,9 +1,30 @@
+pub mod absorb;
 pub mod auth;
 pub mod backend;
+pub mod blame;
+pub mod branch;
+pub mod cli_backend;
+pub mod commit_graph;
+pub mod commit_lint;
 pub mod dispatcher;
 pub mod error;
+pub mod fetch;
 pub mod git2_backend;
+pub mod graph;
+pub mod hooks;
+pub mod identity;
+pub mod journal;
+pub mod push;
+pub mod rebase;
+pub mod rebase_engine;
+pub mod release_notes;
 pub mod search;
+pub mod split;
+pub mod stack;
+pub mod stash;
 pub mod submodule;
+pub mod tag;
+pub mod trailers;
+pub mod transfer;
 pub mod types;
 pub mod worktree;
@@ -0,0 +1,261 @@
+use crate::git::types::{PushOptions, PushRefStatus};
+
+/// One ref update requested through `PushOptions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PushRef {
+    /// Full local ref name, or `None` to delete `dst` on the remote.
+    pub src: Option<String>,
+    pub dst: String,
+    /// Explicit `+` in the refspec (plain force, no lease).
+    pub force: bool,
+}
+
+impl PushRef {
+    pub fn refspec(&self, force: bool) -> String {
+        let plus = if force || self.force { "+" } else { "" };
+        format!("{plus}{}:{}", self.src.as_deref().unwrap_or(""), self.dst)
+    }
+
+    /// Local branch name when a branch is pushed to a remote branch.
+    pub fn upstream_pair(&self) -> Option<(&str, &str)> {
+        let local = self.src.as_deref()?.strip_prefix("refs/heads/")?;
+        let remote = self.dst.strip_prefix("refs/heads/")?;
+        Some((local, remote))
+    }
+}
+
+/// Expand `options` into the list of refs to push.
+///
+/// `ref_exists` is used to resolve short names (`main`, `v1.0`) to full refs;
+/// `local_tags` is only called for `all_tags`.
+pub(crate) fn plan_push(
+    options: &PushOptions,
+    current_branch: Option<&str>,
+    ref_exists: impl Fn(&str) -> bool,
+    local_tags: impl FnOnce() -> Vec<String>,
+) -> Result<Vec<PushRef>, String> {
+    let mut refs = Vec::new();
+
+    if let Some(branch) = &options.delete_branch {
+        refs.push(PushRef {
+            src: None,
+            dst: qualify_dst(branch, None),
+            force: false,
+        });
+    }
+
+    if let Some(tag) = &options.delete_tag {
+        refs.push(PushRef {
+            src: None,
+            dst: format!("refs/tags/{}", tag.trim_start_matches("refs/tags/")),
+            force: false,
+        });
+    }
+
+    if let Some(spec) = &options.refspec {
+        refs.push(parse_refspec(spec, &ref_exists)?);
+    }
+
+    if let Some(tag) = &options.tag {
+        let name = format!("refs/tags/{}", tag.trim_start_matches("refs/tags/"));
+        if !ref_exists(&name) {
+            return Err(format!("tag not found: {tag}"));
+        }
+        refs.push(PushRef {
+            src: Some(name.clone()),
+            dst: name,
+            force: false,
+        });
+    }
+
+    if options.all_tags {
+        for tag in local_tags() {
+            let name = format!("refs/tags/{tag}");
+            if refs.iter().all(|r| r.dst != name) {
+                refs.push(PushRef {
+                    src: Some(name.clone()),
+                    dst: name,
+                    force: false,
+                });
+            }
+        }
+    }
+
+    if !options.has_explicit_target() {
+        let branch = current_branch.ok_or("HEAD has no name")?;
+        let name = format!("refs/heads/{branch}");
+        refs.push(PushRef {
+            src: Some(name.clone()),
+            dst: name,
+            force: false,
+        });
+    }
+
+    Ok(refs)
+}
+
+fn parse_refspec(spec: &str, ref_exists: &impl Fn(&str) -> bool) -> Result<PushRef, String> {
+    let (force, spec) = match spec.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, spec),
+    };
+    let (src, dst) = match spec.split_once(':') {
+        Some((src, dst)) => (src, Some(dst)),
+        None => (spec, None),
+    };
+
+    if src.is_empty() {
+        let dst = dst.filter(|d| !d.is_empty()).ok_or("empty refspec")?;
+        return Ok(PushRef {
+            src: None,
+            dst: qualify_dst(dst, None),
+            force,
+        });
+    }
+
+    let src = qualify_src(src, ref_exists);
+    let dst = match dst.filter(|d| !d.is_empty()) {
+        Some(dst) => qualify_dst(dst, Some(&src)),
+        None if src.starts_with("refs/") => src.clone(),
+        None => return Err(format!("destination required for '{src}'")),
+    };
+    Ok(PushRef {
+        src: Some(src),
+        dst,
+        force,
+    })
+}
+
+fn qualify_src(name: &str, ref_exists: &impl Fn(&str) -> bool) -> String {
+    if name.starts_with("refs/") {
+        return name.to_string();
+    }
+    ["refs/heads/", "refs/tags/"]
+        .iter()
+        .map(|prefix| format!("{prefix}{name}"))
+        .find(|full| ref_exists(full))
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// 短縮名の宛先は送信元と同じ名前空間 (タグならタグ、それ以外はブランチ) に置く。
+fn qualify_dst(name: &str, src: Option<&str>) -> String {
+    if name.starts_with("refs/") {
+        return name.to_string();
+    }
+    match src {
+        Some(src) if src.starts_with("refs/tags/") => format!("refs/tags/{name}"),
+        _ => format!("refs/heads/{name}"),
+    }
+}
+
+/// Remote-tracking ref whose oid is the lease for `dst` on `remote`.
+pub(crate) fn tracking_ref(remote: &str, dst: &str) -> Option<String> {
+    dst.strip_prefix("refs/heads/")
+        .map(|branch| format!("refs/remotes/{remote}/{branch}"))
+}
+
+/// Decide a ref's outcome before sending anything, the same way git does.
+/// `is_descendant(new, old)` must return `false` when `old` is unknown locally.
+pub(crate) fn classify_update(
+    push_ref: &PushRef,
+    remote_old: Option<&str>,
+    new: Option<&str>,
+    lease: Option<Option<&str>>,
+    is_descendant: impl Fn(&str, &str) -> bool,
+) -> (PushRefStatus, Option<String>) {
+    if remote_old == new {
+        let status = if new.is_none() {
+            PushRefStatus::Deleted
+        } else {
+            PushRefStatus::UpToDate
+        };
+        return (status, None);
+    }
+
+    if let Some(expected) = lease {
+        if expected != remote_old {
+            return (PushRefStatus::RejectedStale, None);
+        }
+    }
+
+    let (Some(old), Some(new)) = (remote_old, new) else {
+        let status = if new.is_none() {
+            PushRefStatus::Deleted
+        } else {
+            PushRefStatus::Created
+        };
+        return (status, None);
+    };
+
+    if push_ref.dst.starts_with("refs/tags/") && !push_ref.force && lease.is_none() {
+        return (PushRefStatus::Rejected, Some("already exists".to_string()));
+    }
+    if is_descendant(new, old) {
+        (PushRefStatus::Updated, None)
+    } else if push_ref.force || lease.is_some() {
+        (PushRefStatus::Forced, None)
+    } else {
+        (PushRefStatus::RejectedNonFastForward, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exists(name: &str) -> bool {
+        matches!(name, "refs/heads/main" | "refs/tags/v1.0")
+    }
+
+    #[test]
+    fn plans_current_branch_by_default() {
+        let refs = plan_push(&PushOptions::default(), Some("main"), exists, Vec::new).unwrap();
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].refspec(false), "refs/heads/main:refs/heads/main");
+        assert_eq!(refs[0].upstream_pair(), Some(("main", "main")));
+    }
+
+    #[test]
+    fn plans_explicit_targets() {
+        let options = PushOptions {
+            refspec: Some("+main:release".to_string()),
+            delete_branch: Some("old".to_string()),
+            delete_tag: Some("v0.9".to_string()),
+            all_tags: true,
+            ..PushOptions::default()
+        };
+        let refs = plan_push(&options, None, exists, || vec!["v1.0".to_string()]).unwrap();
+        let specs: Vec<String> = refs.iter().map(|r| r.refspec(false)).collect();
+        assert_eq!(
+            specs,
+            vec![
+                ":refs/heads/old",
+                ":refs/tags/v0.9",
+                "+refs/heads/main:refs/heads/release",
+                "refs/tags/v1.0:refs/tags/v1.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn classifies_lease_and_fast_forward() {
+        let push_ref = PushRef {
+            src: Some("refs/heads/main".to_string()),
+            dst: "refs/heads/main".to_string(),
+            force: false,
+        };
+        let never = |_: &str, _: &str| false;
+
+        let (status, _) = classify_update(&push_ref, Some("a"), Some("b"), None, never);
+        assert_eq!(status, PushRefStatus::RejectedNonFastForward);
+
+        let (status, _) = classify_update(&push_ref, Some("a"), Some("b"), Some(Some("c")), never);
+        assert_eq!(status, PushRefStatus::RejectedStale);
+
+        let (status, _) = classify_update(&push_ref, Some("a"), Some("b"), Some(Some("a")), never);
+        assert_eq!(status, PushRefStatus::Forced);
+
+        let (status, _) = classify_update(&push_ref, None, Some("b"), None, never);
+        assert_eq!(status, PushRefStatus::Created);
+    }
+}
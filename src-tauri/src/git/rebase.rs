@@ -0,0 +1,981 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::git::backend::GitBackend;
+use crate::git::error::{GitError, GitResult};
+use crate::git::rebase_engine;
+use crate::git::stack;
+use crate::git::transfer::CancelToken;
+use crate::git::types::{
+    CommitEdit, CommitResult, FileStatusKind, FixupKind, MoveDirection, RebaseAction,
+    RebaseOptions, RebaseProgress, RebaseResult, RebaseState, RebaseStopReason, RebaseTodoEntry,
+    StagingState,
+};
+
+type ProgressCallback = dyn Fn(&RebaseProgress) + Send + Sync;
+
+/// Progress sink and cancel token handed to rebase, interactive rebase and
+/// continue. Backends that hand the whole rebase to the git CLI report no
+/// steps. Cancelling kills the `exec` step the rebase engine is running,
+/// which stops the rebase there.
+#[derive(Clone, Default)]
+pub struct RebaseControl {
+    cancel: CancelToken,
+    on_progress: Option<Arc<ProgressCallback>>,
+}
+
+impl RebaseControl {
+    pub fn new(cancel: CancelToken) -> Self {
+        Self {
+            cancel,
+            on_progress: None,
+        }
+    }
+
+    pub fn with_progress(
+        mut self,
+        callback: impl Fn(&RebaseProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn report(&self, progress: &RebaseProgress) {
+        if let Some(callback) = &self.on_progress {
+            callback(progress);
+        }
+    }
+
+    pub(crate) fn cancel_token(&self) -> &CancelToken {
+        &self.cancel
+    }
+}
+
+pub fn rebase(workdir: &Path, onto: &str, options: &RebaseOptions) -> GitResult<RebaseResult> {
+    ensure_not_in_progress(workdir)?;
+    let mut args = vec!["rebase", "--update-refs"];
+    if options.rebase_merges {
+        args.push("--rebase-merges");
+    }
+    if let Some(exec) = &options.exec {
+        args.extend(["--exec", exec.as_str()]);
+    }
+    args.push(onto);
+    let output = std::process::Command::new("git")
+        .args(&args)
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+
+    cli_result(workdir, &output)
+}
+
+/// The entry as a line of `git-rebase-todo`.
+pub(crate) fn todo_line(entry: &RebaseTodoEntry) -> String {
+    let argument = entry.argument.as_deref().unwrap_or_default();
+    // todo の行は 1 行に収める。reword の本文はエディタ経由で渡す
+    let subject = entry.message.lines().next().unwrap_or_default();
+    let action = match entry.action {
+        RebaseAction::Pick => "pick",
+        RebaseAction::Reword => "reword",
+        RebaseAction::Edit => "edit",
+        RebaseAction::Squash => "squash",
+        RebaseAction::Fixup => "fixup",
+        RebaseAction::FixupMessage => "fixup -C",
+        RebaseAction::Drop => "drop",
+        RebaseAction::Exec => return format!("exec {argument}"),
+        RebaseAction::Break => return "break".to_string(),
+        RebaseAction::Label => return format!("label {argument}"),
+        RebaseAction::Reset => return format!("reset {argument}"),
+        RebaseAction::UpdateRef => return format!("update-ref {argument}"),
+        RebaseAction::Merge if entry.oid.is_empty() => return format!("merge {argument}"),
+        RebaseAction::Merge => {
+            return format!("merge -C {} {argument} # {subject}", entry.short_oid);
+        }
+    };
+    format!("{} {} {}", action, entry.short_oid, subject)
+}
+
+pub fn interactive_rebase(
+    workdir: &Path,
+    onto: &str,
+    todo: &[RebaseTodoEntry],
+) -> GitResult<RebaseResult> {
+    ensure_not_in_progress(workdir)?;
+    let todo_content = todo.iter().map(todo_line).collect::<Vec<_>>().join("\n");
+
+    let git_dir = workdir.join(".git");
+    let todo_file = git_dir.join("rocket-rebase-todo");
+    std::fs::write(&todo_file, &todo_content).map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+
+    let seq_editor_file = git_dir.join("rocket-rebase-editor.sh");
+    let seq_editor_content = format!(
+        "#!/bin/sh\ncp '{}' \"$1\"\n",
+        todo_file.display().to_string().replace("'", "'\\''")
+    );
+    std::fs::write(&seq_editor_file, &seq_editor_content)
+        .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+
+    let reword_messages: Vec<&str> = todo
+        .iter()
+        .filter(|e| e.action == RebaseAction::Reword)
+        .map(|e| e.message.as_str())
+        .collect();
+
+    let commit_editor_file = git_dir.join("rocket-commit-editor.sh");
+    let commit_editor_content = if reword_messages.is_empty() {
+        "#!/bin/sh\ntrue\n".to_string()
+    } else {
+        let counter_file = git_dir.join("rocket-reword-counter");
+        std::fs::write(&counter_file, "0").map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+
+        let msgs_file = git_dir.join("rocket-reword-msgs");
+        std::fs::write(&msgs_file, reword_messages.join("\n---ROCKET_MSG_SEP---\n"))
+            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+
+        // squash のメッセージ結合でもエディタが呼ばれるので、reword 用の
+        // メッセージを消費しないようにそのまま通す
+        format!(
+            "#!/bin/sh\n\
+            grep -q '^# This is a combination of' \"$1\" && exit 0\n\
+            COUNTER=$(cat '{counter}')\n\
+            MSG=$(awk -v n=\"$COUNTER\" 'BEGIN{{found=0}} /^---ROCKET_MSG_SEP---$/{{found++; next}} found==n{{print}}' '{msgs}')\n\
+            printf '%s\\n' \"$MSG\" > \"$1\"\n\
+            echo $((COUNTER + 1)) > '{counter}'\n",
+            counter = counter_file.display().to_string().replace("'", "'\\''"),
+            msgs = msgs_file.display().to_string().replace("'", "'\\''"),
+        )
+    };
+    std::fs::write(&commit_editor_file, &commit_editor_content)
+        .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::Permissions::from_mode(0o755);
+        std::fs::set_permissions(&seq_editor_file, mode.clone())
+            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+        std::fs::set_permissions(&commit_editor_file, mode)
+            .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+    }
+
+    let output = std::process::Command::new("git")
+        .args(["rebase", "-i", onto])
+        .env("GIT_SEQUENCE_EDITOR", seq_editor_file.display().to_string())
+        .env("GIT_EDITOR", commit_editor_file.display().to_string())
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+
+    let _ = std::fs::remove_file(&todo_file);
+    let _ = std::fs::remove_file(&seq_editor_file);
+
+    cli_result(workdir, &output)
+}
+
+/// The commit editor and the reword messages outlive a stop at `break`,
+/// `edit` or a conflict, so rewords later in the todo keep their message
+/// when the rebase is continued. They are removed once it is over.
+fn clean_commit_editor(workdir: &Path) {
+    if in_progress(workdir) {
+        return;
+    }
+    let git_dir = workdir.join(".git");
+    let _ = std::fs::remove_file(git_dir.join("rocket-commit-editor.sh"));
+    let _ = std::fs::remove_file(git_dir.join("rocket-reword-counter"));
+    let _ = std::fs::remove_file(git_dir.join("rocket-reword-msgs"));
+}
+
+fn in_progress(workdir: &Path) -> bool {
+    let git_dir = workdir.join(".git");
+    git_dir.join("rebase-merge").is_dir() || git_dir.join("rebase-apply").is_dir()
+}
+
+fn ensure_not_in_progress(workdir: &Path) -> GitResult<()> {
+    if in_progress(workdir) {
+        return Err(GitError::RebaseFailed(
+            "a rebase is already in progress".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// `git rebase` exits successfully when it stops at `edit` or `break`, and
+/// with an error on conflicts or a failed `exec`, so whether it stopped is
+/// read from the repository.
+fn cli_result(workdir: &Path, output: &std::process::Output) -> GitResult<RebaseResult> {
+    clean_commit_editor(workdir);
+    if in_progress(workdir) {
+        return Ok(RebaseResult {
+            completed: false,
+            conflicts: collect_conflict_paths_from_workdir(workdir),
+        });
+    }
+    if output.status.success() {
+        return Ok(RebaseResult {
+            completed: true,
+            conflicts: Vec::new(),
+        });
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(GitError::RebaseFailed(stderr.to_string().into()))
+}
+
+pub fn abort_rebase(workdir: &Path) -> GitResult<()> {
+    let output = std::process::Command::new("git")
+        .args(["rebase", "--abort"])
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::RebaseFailed(stderr.to_string().into()));
+    }
+
+    clean_commit_editor(workdir);
+    Ok(())
+}
+
+pub fn continue_rebase(workdir: &Path) -> GitResult<RebaseResult> {
+    let commit_editor_file = workdir.join(".git").join("rocket-commit-editor.sh");
+    let editor = if commit_editor_file.exists() {
+        commit_editor_file.display().to_string()
+    } else {
+        "true".to_string()
+    };
+    let output = std::process::Command::new("git")
+        .args(["rebase", "--continue"])
+        .env("GIT_EDITOR", editor)
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+
+    cli_result(workdir, &output)
+}
+
+const FIXUP_PREFIXES: [(&str, FixupKind); 3] = [
+    ("fixup! ", FixupKind::Fixup),
+    ("squash! ", FixupKind::Squash),
+    ("amend! ", FixupKind::Amend),
+];
+
+fn fixup_prefix(kind: FixupKind) -> &'static str {
+    FIXUP_PREFIXES
+        .iter()
+        .find(|(_, k)| *k == kind)
+        .map(|(prefix, _)| *prefix)
+        .unwrap_or_default()
+}
+
+/// The kind and target of a `fixup!`, `squash!` or `amend!` subject. Nested
+/// prefixes are skipped, so `fixup! fixup! Add x` targets `Add x`.
+pub fn parse_fixup_subject(subject: &str) -> Option<(FixupKind, &str)> {
+    let mut kind = None;
+    let mut rest = subject;
+    while let Some((prefix, k)) = FIXUP_PREFIXES.iter().find(|(p, _)| rest.starts_with(p)) {
+        kind.get_or_insert(*k);
+        rest = &rest[prefix.len()..];
+    }
+    kind.map(|kind| (kind, rest.trim()))
+}
+
+/// Message of a fixup commit, as `git commit --fixup` writes it.
+pub fn fixup_message(kind: FixupKind, target_subject: &str, message: &str) -> String {
+    let prefix = fixup_prefix(kind);
+    let body = message.trim();
+    if body.is_empty() {
+        format!("{prefix}{target_subject}\n")
+    } else {
+        format!("{prefix}{target_subject}\n\n{body}\n")
+    }
+}
+
+/// Commit the staged changes as a fixup of `target`, which must be an
+/// ancestor of `HEAD`. An `amend!` commit without a message carries the
+/// target's message, ready to be edited later.
+pub fn create_fixup_commit(
+    backend: &dyn GitBackend,
+    target: &str,
+    kind: FixupKind,
+    message: &str,
+    sign: bool,
+    skip_hooks: bool,
+) -> GitResult<CommitResult> {
+    let target_info = backend.get_commit_detail(target)?.info;
+    let output = std::process::Command::new("git")
+        .args(["merge-base", "--is-ancestor", &target_info.oid, "HEAD"])
+        .current_dir(backend.workdir())
+        .output()
+        .map_err(|e| GitError::CommitFailed(Box::new(e)))?;
+    if !output.status.success() {
+        return Err(GitError::CommitFailed(
+            format!("{} is not on the current branch", target_info.short_oid).into(),
+        ));
+    }
+
+    if kind != FixupKind::Amend {
+        let has_staged = backend
+            .status()?
+            .files
+            .iter()
+            .any(|f| f.staging == StagingState::Staged);
+        if !has_staged {
+            return Err(GitError::CommitFailed(
+                "nothing staged for the fixup".into(),
+            ));
+        }
+    }
+
+    let message = if kind == FixupKind::Amend && message.trim().is_empty() {
+        if target_info.body.is_empty() {
+            target_info.message.clone()
+        } else {
+            format!("{}\n\n{}", target_info.message, target_info.body)
+        }
+    } else {
+        message.to_string()
+    };
+    let message = fixup_message(kind, &target_info.message, &message);
+    backend.commit(&message, false, sign, skip_hooks)
+}
+
+/// `git rebase` refuses to start over tracked changes.
+pub(crate) fn is_clean(backend: &dyn GitBackend) -> GitResult<bool> {
+    Ok(backend
+        .status()?
+        .files
+        .iter()
+        .all(|file| file.kind == FileStatusKind::Untracked))
+}
+
+fn find_fixup_target(targets: &[(usize, String, String)], rest: &str) -> Option<usize> {
+    let is_oid = rest.len() >= 4 && rest.chars().all(|c| c.is_ascii_hexdigit());
+    targets
+        .iter()
+        .find(|(_, subject, _)| subject == rest)
+        .or_else(|| {
+            targets
+                .iter()
+                .find(|(_, _, oid)| is_oid && oid.starts_with(rest))
+        })
+        .or_else(|| {
+            targets
+                .iter()
+                .find(|(_, subject, _)| subject.starts_with(rest))
+        })
+        .map(|(index, _, _)| *index)
+}
+
+/// Reorder a todo list the way `git rebase --autosquash` does: each `pick`
+/// of a `fixup!`, `squash!` or `amend!` commit moves after its target and
+/// becomes `fixup`, `squash` or `fixup -C`. The target is the earlier entry
+/// whose subject matches, or whose oid starts with, the text after the
+/// prefix; failing that, the earlier entry whose subject starts with it.
+/// Entries without a target, or already given another action, stay put.
+pub fn autosquash(todo: Vec<RebaseTodoEntry>) -> Vec<RebaseTodoEntry> {
+    // 移動先のインデックス -> そこに続けるエントリ
+    let mut followers: HashMap<usize, Vec<RebaseTodoEntry>> = HashMap::new();
+    let mut kept: Vec<(usize, RebaseTodoEntry)> = Vec::new();
+    // 候補になる (インデックス, 件名, oid)
+    let mut targets: Vec<(usize, String, String)> = Vec::new();
+
+    for (index, mut entry) in todo.into_iter().enumerate() {
+        let fixup = match entry.action {
+            RebaseAction::Pick => parse_fixup_subject(&entry.message),
+            _ => None,
+        };
+        let target = fixup.and_then(|(kind, rest)| {
+            find_fixup_target(&targets, rest).map(|target| (kind, target))
+        });
+
+        match target {
+            Some((kind, target)) => {
+                entry.action = match kind {
+                    FixupKind::Fixup => RebaseAction::Fixup,
+                    FixupKind::Squash => RebaseAction::Squash,
+                    FixupKind::Amend => RebaseAction::FixupMessage,
+                };
+                followers.entry(target).or_default().push(entry);
+            }
+            None => {
+                if parse_fixup_subject(&entry.message).is_none() {
+                    targets.push((index, entry.message.clone(), entry.oid.clone()));
+                }
+                kept.push((index, entry));
+            }
+        }
+    }
+
+    let mut result = Vec::new();
+    for (index, entry) in kept {
+        result.push(entry);
+        result.extend(followers.remove(&index).unwrap_or_default());
+    }
+    result
+}
+
+/// A todo step that is not about a commit.
+pub fn todo_step(action: RebaseAction, argument: &str) -> RebaseTodoEntry {
+    RebaseTodoEntry {
+        action,
+        oid: String::new(),
+        short_oid: String::new(),
+        message: String::new(),
+        author_name: String::new(),
+        argument: Some(argument.to_string()),
+    }
+}
+
+fn git(workdir: &Path, args: &[&str]) -> GitResult<String> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| GitError::RebaseFailed(Box::new(e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::RebaseFailed(stderr.trim().to_string().into()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// A commit to replay and its parents.
+struct TodoCommit {
+    entry: RebaseTodoEntry,
+    parents: Vec<String>,
+}
+
+/// The commits `git rebase` replays onto `onto`, parents first, and the
+/// merge base they are replayed from.
+fn todo_commits(workdir: &Path, onto: &str) -> GitResult<(String, Vec<TodoCommit>)> {
+    let base = git(workdir, &["merge-base", onto, "HEAD"])?;
+    let hide = format!("^{base}");
+    let log = git(
+        workdir,
+        &[
+            "log",
+            "--topo-order",
+            "--reverse",
+            "--format=%H%x1f%P%x1f%an%x1f%s",
+            "HEAD",
+            &hide,
+        ],
+    )?;
+    let commits = log
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\x1f');
+            let oid = fields.next()?.to_string();
+            let parents = fields.next()?.split_whitespace().map(str::to_string);
+            let author_name = fields.next()?.to_string();
+            Some(TodoCommit {
+                parents: parents.collect(),
+                entry: RebaseTodoEntry {
+                    action: RebaseAction::Pick,
+                    short_oid: oid[..7.min(oid.len())].to_string(),
+                    oid,
+                    message: fields.next().unwrap_or_default().to_string(),
+                    author_name,
+                    argument: None,
+                },
+            })
+        })
+        .collect();
+    Ok((base, commits))
+}
+
+/// The todo list a non-interactive `git rebase` with `options` runs: merges
+/// are dropped unless `rebase_merges` recreates them.
+pub(crate) fn rebase_todo(
+    backend: &dyn GitBackend,
+    onto: &str,
+    options: &RebaseOptions,
+) -> GitResult<Vec<RebaseTodoEntry>> {
+    let todo = if options.rebase_merges {
+        todo_with_merges(backend, onto)?
+    } else {
+        let (_, commits) = todo_commits(backend.workdir(), onto)?;
+        commits
+            .into_iter()
+            .filter(|commit| commit.parents.len() < 2)
+            .map(|commit| commit.entry)
+            .collect()
+    };
+    Ok(match &options.exec {
+        Some(command) => insert_exec(todo, command),
+        None => todo,
+    })
+}
+
+/// The todo list `git rebase --rebase-merges` would start with: the
+/// branch's commits and merges, with the labels and resets needed to
+/// rebuild its shape on top of `onto`.
+pub fn todo_with_merges(backend: &dyn GitBackend, onto: &str) -> GitResult<Vec<RebaseTodoEntry>> {
+    let (base, commits) = todo_commits(backend.workdir(), onto)?;
+    Ok(script_with_merges(commits, &base))
+}
+
+/// Branch name from the subject of the merge that brought it in.
+fn merged_branch(subject: &str) -> Option<&str> {
+    let name = if let Some((_, rest)) = subject.split_once("branch '") {
+        rest.split('\'').next()?
+    } else {
+        let rest = subject.strip_prefix("Merge pull request #")?;
+        rest.split_once(" from ")?.1.split_whitespace().next()?
+    };
+    name.rsplit('/').next()
+}
+
+fn label_name(hint: &str, used: &mut HashSet<String>) -> String {
+    let name: String = hint
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let name = name.trim_matches(|c| c == '-' || c == '.');
+    let name = if name.is_empty() { "branch" } else { name };
+    let mut candidate = name.to_string();
+    let mut n = 2;
+    while !used.insert(candidate.clone()) {
+        candidate = format!("{name}-{n}");
+        n += 1;
+    }
+    candidate
+}
+
+/// Lay out commits, parents first, as steps that rebuild their graph from
+/// the label `onto`. A commit whose first parent is not the commit just
+/// replayed starts with a `reset`, merges become `merge -C`, and every
+/// commit a later step returns to gets a `label`. Parents outside the range
+/// stay where they are, except the merge base, which becomes `onto`.
+fn script_with_merges(commits: Vec<TodoCommit>, base: &str) -> Vec<RebaseTodoEntry> {
+    let in_range: HashSet<String> = commits.iter().map(|c| c.entry.oid.clone()).collect();
+    let target = |oid: &str| {
+        if oid == base && !in_range.contains(oid) {
+            "onto".to_string()
+        } else {
+            oid.to_string()
+        }
+    };
+
+    // reset / merge の引数は一旦 oid で持ち、最後にラベル名へ置き換える
+    let mut steps: Vec<RebaseTodoEntry> = Vec::new();
+    // ラベルが必要なコミットと、名前の元にする文字列
+    let mut wanted: Vec<(String, String)> = Vec::new();
+    let mut want = |oid: &str, hint: &str| {
+        if in_range.contains(oid) && !wanted.iter().any(|(w, _)| w == oid) {
+            wanted.push((oid.to_string(), hint.to_string()));
+        }
+    };
+    let mut current = "onto".to_string();
+    for commit in commits {
+        let mut entry = commit.entry;
+        let from = commit
+            .parents
+            .first()
+            .map_or_else(|| "onto".to_string(), |p| target(p));
+        if from != current {
+            want(&from, "branch-point");
+            steps.push(todo_step(RebaseAction::Reset, &from));
+        }
+        if commit.parents.len() > 1 {
+            let hint = merged_branch(&entry.message).unwrap_or(&entry.message);
+            let others: Vec<String> = commit.parents[1..].iter().map(|p| target(p)).collect();
+            for other in &others {
+                want(other, hint);
+            }
+            entry.action = RebaseAction::Merge;
+            entry.argument = Some(others.join(" "));
+        }
+        current = entry.oid.clone();
+        steps.push(entry);
+    }
+
+    let mut used: HashSet<String> = HashSet::from(["onto".to_string()]);
+    let labels: HashMap<String, String> = wanted
+        .into_iter()
+        .map(|(oid, hint)| {
+            let name = label_name(&hint, &mut used);
+            (oid, name)
+        })
+        .collect();
+
+    let mut todo = vec![todo_step(RebaseAction::Label, "onto")];
+    for mut step in steps {
+        if let Some(argument) = &step.argument {
+            let renamed: Vec<&str> = argument
+                .split_whitespace()
+                .map(|oid| labels.get(oid).map_or(oid, String::as_str))
+                .collect();
+            step.argument = Some(renamed.join(" "));
+        }
+        let label = labels.get(&step.oid).filter(|_| !step.oid.is_empty());
+        let label = label.map(|name| todo_step(RebaseAction::Label, name));
+        todo.push(step);
+        todo.extend(label);
+    }
+    todo
+}
+
+/// Whether the step folds its commit into the one before it.
+pub(crate) fn is_fold(action: RebaseAction) -> bool {
+    matches!(
+        action,
+        RebaseAction::Squash | RebaseAction::Fixup | RebaseAction::FixupMessage
+    )
+}
+
+/// Run `command` after every step that leaves a commit, like `--exec`: after
+/// each pick and merge, or after the last of the squashes and fixups that
+/// follow it.
+pub fn insert_exec(todo: Vec<RebaseTodoEntry>, command: &str) -> Vec<RebaseTodoEntry> {
+    let mut result = Vec::new();
+    let mut entries = todo.into_iter().peekable();
+    while let Some(entry) = entries.next() {
+        let commits = is_fold(entry.action)
+            || matches!(
+                entry.action,
+                RebaseAction::Pick
+                    | RebaseAction::Reword
+                    | RebaseAction::Edit
+                    | RebaseAction::Merge
+            );
+        result.push(entry);
+        if commits && !entries.peek().is_some_and(|next| is_fold(next.action)) {
+            result.push(todo_step(RebaseAction::Exec, command));
+        }
+    }
+    result
+}
+
+/// The todo list that performs `edit`, and the commit it applies onto: the
+/// parent of the oldest commit the edit changes. Edits other than a reword
+/// change the tree, so they need a clean working tree.
+pub(crate) fn plan_commit_edit(
+    backend: &dyn GitBackend,
+    edit: &CommitEdit,
+) -> GitResult<(String, Vec<RebaseTodoEntry>)> {
+    let (CommitEdit::Reword { oid, .. } | CommitEdit::Drop { oid } | CommitEdit::Move { oid, .. }) =
+        edit;
+    let root = || GitError::RebaseFailed("the root commit cannot be rewritten".into());
+    let mut oldest = backend.get_commit_detail(oid)?.info;
+    if let CommitEdit::Move {
+        direction: MoveDirection::Down,
+        ..
+    } = edit
+    {
+        // 下へ動かすとひとつ前のコミットも書き換わる
+        let parent = oldest.parent_oids.first().ok_or_else(root)?.clone();
+        oldest = backend.get_commit_detail(&parent)?.info;
+    }
+    let base = oldest.parent_oids.first().ok_or_else(root)?.clone();
+    if !matches!(edit, CommitEdit::Reword { .. }) && !is_clean(backend)? {
+        return Err(GitError::RebaseFailed(
+            "commit or stash your changes first".into(),
+        ));
+    }
+    // 範囲にマージがあれば --rebase-merges と同じ todo にして形を保つ
+    let (_, commits) = todo_commits(backend.workdir(), &base)?;
+    let todo = if commits.iter().any(|commit| commit.parents.len() > 1) {
+        script_with_merges(commits, &base)
+    } else {
+        commits.into_iter().map(|commit| commit.entry).collect()
+    };
+    let todo = stack::with_update_refs(backend.workdir(), &base, edit_todo(todo, edit)?)?;
+    Ok((base, todo))
+}
+
+fn edit_todo(mut todo: Vec<RebaseTodoEntry>, edit: &CommitEdit) -> GitResult<Vec<RebaseTodoEntry>> {
+    let (CommitEdit::Reword { oid, .. } | CommitEdit::Drop { oid } | CommitEdit::Move { oid, .. }) =
+        edit;
+    let index = todo
+        .iter()
+        .position(|entry| !oid.is_empty() && entry.oid.starts_with(oid.as_str()))
+        .ok_or_else(|| {
+            GitError::RebaseFailed(format!("{oid} is not on the current branch").into())
+        })?;
+    if todo[index].action != RebaseAction::Pick {
+        return Err(GitError::RebaseFailed(
+            format!("{oid} is a merge commit").into(),
+        ));
+    }
+    // マージの todo ではラベルやリセットを挟んだ先と入れ替えない
+    let movable = |index: usize| {
+        todo.get(index)
+            .is_some_and(|e| e.action == RebaseAction::Pick)
+    };
+    match edit {
+        CommitEdit::Reword { message, .. } => {
+            todo[index].action = RebaseAction::Reword;
+            todo[index].message = message.clone();
+        }
+        CommitEdit::Drop { .. } => todo[index].action = RebaseAction::Drop,
+        CommitEdit::Move {
+            direction: MoveDirection::Up,
+            ..
+        } => {
+            if index + 1 == todo.len() {
+                return Err(GitError::RebaseFailed(
+                    format!("{oid} is already the newest commit").into(),
+                ));
+            }
+            if !movable(index + 1) {
+                return Err(GitError::RebaseFailed(
+                    format!("{oid} cannot be moved across a merge").into(),
+                ));
+            }
+            todo.swap(index, index + 1);
+        }
+        // base は 2 つ前なので、ひとつ前のコミットは必ず todo にある
+        CommitEdit::Move {
+            direction: MoveDirection::Down,
+            ..
+        } => {
+            if !movable(index - 1) {
+                return Err(GitError::RebaseFailed(
+                    format!("{oid} cannot be moved across a merge").into(),
+                ));
+            }
+            todo.swap(index - 1, index);
+        }
+    }
+    Ok(todo)
+}
+
+/// Read the progress of an in-flight rebase from `rebase-merge` / `rebase-apply`.
+pub fn read_rebase_state(git_dir: &Path, has_conflicts: bool) -> Option<RebaseState> {
+    let rebase_merge = git_dir.join("rebase-merge");
+    let rebase_apply = git_dir.join("rebase-apply");
+
+    let rebase_dir = if rebase_merge.is_dir() {
+        rebase_merge
+    } else if rebase_apply.is_dir() {
+        rebase_apply
+    } else {
+        return None;
+    };
+
+    let onto_oid = std::fs::read_to_string(rebase_dir.join("onto"))
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    let onto_branch = std::fs::read_to_string(rebase_dir.join("onto_name"))
+        .unwrap_or_else(|_| onto_oid.clone())
+        .trim()
+        .trim_start_matches("refs/heads/")
+        .to_string();
+
+    let current_step = std::fs::read_to_string(rebase_dir.join("msgnum"))
+        .unwrap_or_else(|_| "0".to_string())
+        .trim()
+        .parse::<usize>()
+        .unwrap_or(0);
+
+    let total_steps = std::fs::read_to_string(rebase_dir.join("end"))
+        .unwrap_or_else(|_| "0".to_string())
+        .trim()
+        .parse::<usize>()
+        .unwrap_or(0);
+
+    let head_name = std::fs::read_to_string(rebase_dir.join("head-name"))
+        .ok()
+        .map(|name| name.trim().trim_start_matches("refs/heads/").to_string())
+        .filter(|name| !name.is_empty() && name != "detached HEAD");
+
+    let done = std::fs::read_to_string(rebase_dir.join("done")).unwrap_or_default();
+    let stop_reason = stop_reason(done.lines().last(), has_conflicts);
+    let exec_failure = match stop_reason {
+        Some(RebaseStopReason::ExecFailed) => {
+            std::fs::read_to_string(rebase_dir.join(rebase_engine::EXEC_FAILURE_FILE))
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+        }
+        _ => None,
+    };
+
+    Some(RebaseState {
+        onto_branch,
+        onto_oid,
+        current_step,
+        total_steps,
+        has_conflicts,
+        head_name,
+        stop_reason,
+        exec_failure,
+    })
+}
+
+/// Why the rebase stopped, judged from the last step it ran. A commit step
+/// only stops on conflicts, which may have been resolved already.
+fn stop_reason(last_done: Option<&str>, has_conflicts: bool) -> Option<RebaseStopReason> {
+    if has_conflicts {
+        return Some(RebaseStopReason::Conflict);
+    }
+    let command = last_done?.split_whitespace().next()?;
+    match command {
+        "edit" | "e" => Some(RebaseStopReason::Edit),
+        "break" | "b" => Some(RebaseStopReason::Break),
+        "exec" | "x" => Some(RebaseStopReason::ExecFailed),
+        "pick" | "p" | "reword" | "r" | "squash" | "s" | "fixup" | "f" | "merge" | "m" => {
+            Some(RebaseStopReason::Conflict)
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn collect_conflict_paths_from_workdir(workdir: &Path) -> Vec<String> {
+    let output = std::process::Command::new("git")
+        .args(["diff", "--name-only", "--diff-filter=U"])
+        .current_dir(workdir)
+        .output();
+
+    match output {
+        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(|l| l.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(action: RebaseAction, oid: &str, message: &str) -> RebaseTodoEntry {
+        RebaseTodoEntry {
+            action,
+            oid: oid.to_string(),
+            short_oid: oid[..7].to_string(),
+            message: message.to_string(),
+            author_name: "Test".to_string(),
+            argument: None,
+        }
+    }
+
+    fn plan(todo: &[RebaseTodoEntry]) -> Vec<(RebaseAction, &str)> {
+        todo.iter()
+            .map(|e| (e.action, e.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_fixup_subject_skips_nested_prefixes() {
+        assert_eq!(
+            parse_fixup_subject("squash! fixup! Add x"),
+            Some((FixupKind::Squash, "Add x"))
+        );
+        assert_eq!(parse_fixup_subject("Add x"), None);
+    }
+
+    #[test]
+    fn autosquash_matches_subjects_oids_and_subject_prefixes() {
+        let todo = vec![
+            entry(RebaseAction::Pick, "aaaaaaa111", "Add parser"),
+            entry(RebaseAction::Pick, "bbbbbbb222", "Add lexer support"),
+            entry(RebaseAction::Pick, "ccccccc333", "fixup! Add lexer"),
+            entry(RebaseAction::Pick, "ddddddd444", "squash! aaaaaaa1"),
+            entry(RebaseAction::Pick, "eeeeeee555", "amend! Add parser"),
+            entry(RebaseAction::Pick, "fffffff666", "fixup! Missing target"),
+            entry(RebaseAction::Drop, "0000000777", "fixup! Add parser"),
+        ];
+        assert_eq!(
+            plan(&autosquash(todo)),
+            vec![
+                (RebaseAction::Pick, "Add parser"),
+                (RebaseAction::Squash, "squash! aaaaaaa1"),
+                (RebaseAction::FixupMessage, "amend! Add parser"),
+                (RebaseAction::Pick, "Add lexer support"),
+                (RebaseAction::Fixup, "fixup! Add lexer"),
+                (RebaseAction::Pick, "fixup! Missing target"),
+                (RebaseAction::Drop, "fixup! Add parser"),
+            ]
+        );
+    }
+
+    #[test]
+    fn autosquash_is_idempotent() {
+        let todo = autosquash(vec![
+            entry(RebaseAction::Pick, "aaaaaaa111", "Add parser"),
+            entry(RebaseAction::Pick, "bbbbbbb222", "Add lexer"),
+            entry(RebaseAction::Pick, "ccccccc333", "fixup! Add parser"),
+        ]);
+        let again = autosquash(todo.clone());
+        assert_eq!(plan(&again), plan(&todo));
+    }
+
+    #[test]
+    fn fixup_message_adds_prefix_and_body() {
+        assert_eq!(
+            fixup_message(FixupKind::Fixup, "Add parser", ""),
+            "fixup! Add parser\n"
+        );
+        assert_eq!(
+            fixup_message(FixupKind::Amend, "Add parser", "Add a parser\n"),
+            "amend! Add parser\n\nAdd a parser\n"
+        );
+    }
+
+    #[test]
+    fn edit_todo_rewords_drops_and_swaps() {
+        let todo = vec![
+            entry(RebaseAction::Pick, "aaaaaaa111", "Add parser"),
+            entry(RebaseAction::Pick, "bbbbbbb222", "Add lexer"),
+        ];
+        let reword = CommitEdit::Reword {
+            oid: "aaaaaaa".to_string(),
+            message: "Add a parser".to_string(),
+        };
+        assert_eq!(
+            plan(&edit_todo(todo.clone(), &reword).unwrap()),
+            vec![
+                (RebaseAction::Reword, "Add a parser"),
+                (RebaseAction::Pick, "Add lexer"),
+            ]
+        );
+        let drop = CommitEdit::Drop {
+            oid: "bbbbbbb222".to_string(),
+        };
+        assert_eq!(
+            plan(&edit_todo(todo.clone(), &drop).unwrap()),
+            vec![
+                (RebaseAction::Pick, "Add parser"),
+                (RebaseAction::Drop, "Add lexer"),
+            ]
+        );
+        let up = CommitEdit::Move {
+            oid: "aaaaaaa111".to_string(),
+            direction: MoveDirection::Up,
+        };
+        assert_eq!(
+            plan(&edit_todo(todo.clone(), &up).unwrap()),
+            vec![
+                (RebaseAction::Pick, "Add lexer"),
+                (RebaseAction::Pick, "Add parser"),
+            ]
+        );
+    }
+
+    #[test]
+    fn edit_todo_rejects_unknown_and_newest_moves() {
+        let todo = vec![entry(RebaseAction::Pick, "aaaaaaa111", "Add parser")];
+        let unknown = CommitEdit::Drop {
+            oid: "ccccccc".to_string(),
+        };
+        assert!(edit_todo(todo.clone(), &unknown).is_err());
+        let up = CommitEdit::Move {
+            oid: "aaaaaaa".to_string(),
+            direction: MoveDirection::Up,
+        };
+        assert!(edit_todo(todo, &up).is_err());
+    }
+}
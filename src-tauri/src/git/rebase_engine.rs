@@ -0,0 +1,859 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use git2::build::CheckoutBuilder;
+use git2::{Commit, Oid, Repository, RepositoryState, Signature};
+use serde::{Deserialize, Serialize};
+
+use crate::git::error::{GitError, GitResult};
+use crate::git::git2_backend::collect_conflict_paths;
+use crate::git::rebase::{is_fold, parse_fixup_subject, todo_line, RebaseControl};
+use crate::git::transfer::run_cancellable;
+use crate::git::types::{
+    RebaseAction, RebaseExecFailure, RebaseProgress, RebaseResult, RebaseStopReason,
+    RebaseTodoEntry,
+};
+
+/// The engine's own state next to the files `git rebase` keeps in
+/// `rebase-merge`. Its presence marks a rebase this engine can continue.
+const STATE_FILE: &str = "rocket-rebase.json";
+const DETACHED: &str = "detached HEAD";
+/// What the `exec` step the rebase stopped at printed, as a
+/// `RebaseExecFailure`.
+pub(crate) const EXEC_FAILURE_FILE: &str = "rocket-exec-failure.json";
+/// How much of a failed `exec` step's stdout and stderr is kept.
+const EXEC_OUTPUT_LIMIT: usize = 64 * 1024;
+
+fn rebase_error(e: impl std::error::Error + Send + Sync + 'static) -> GitError {
+    GitError::RebaseFailed(Box::new(e))
+}
+
+/// The end of an `exec` step's output, where the error usually is.
+fn output_tail(bytes: &[u8]) -> String {
+    let start = bytes.len().saturating_sub(EXEC_OUTPUT_LIMIT);
+    String::from_utf8_lossy(&bytes[start..]).into_owned()
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    onto_name: String,
+    /// Steps not run yet.
+    todo: Vec<RebaseTodoEntry>,
+    done: usize,
+    total: usize,
+    /// The commit step that stopped on conflicts, finished by `resume`.
+    pending: Option<RebaseTodoEntry>,
+    /// `update-ref` steps run so far: the ref and the commit it gets.
+    update_refs: Vec<(String, String)>,
+}
+
+enum Step {
+    Next,
+    Stop(RebaseStopReason),
+}
+
+/// Result of replaying a commit's changes onto another commit.
+enum Applied {
+    Tree(Oid),
+    /// The changes are already there.
+    Unchanged,
+    Conflict,
+}
+
+struct Engine<'r> {
+    repo: &'r Repository,
+    workdir: PathBuf,
+    dir: PathBuf,
+    committer: Signature<'static>,
+    control: &'r RebaseControl,
+}
+
+/// Whether the rebase in progress was started by this engine.
+pub(crate) fn owns(repo: &Repository) -> bool {
+    repo.path().join("rebase-merge").join(STATE_FILE).is_file()
+}
+
+/// Rebase the current branch onto `onto` by running `todo` with `git2`,
+/// reporting each step to `control`. The state is kept in `rebase-merge`
+/// the way `git rebase` keeps it, so the rebase can be inspected, continued
+/// and aborted like one started by git.
+pub(crate) fn start(
+    repo: &Repository,
+    onto: &str,
+    todo: &[RebaseTodoEntry],
+    control: &RebaseControl,
+) -> GitResult<RebaseResult> {
+    if repo.state() != RepositoryState::Clean {
+        return Err(GitError::RebaseFailed(
+            "another operation is in progress".into(),
+        ));
+    }
+    let mut options = git2::StatusOptions::new();
+    options.include_untracked(false).exclude_submodules(true);
+    if !repo
+        .statuses(Some(&mut options))
+        .map_err(rebase_error)?
+        .is_empty()
+    {
+        return Err(GitError::RebaseFailed(
+            "commit or stash your changes first".into(),
+        ));
+    }
+
+    let head = repo.head().map_err(rebase_error)?;
+    let head_name = match head.name().filter(|_| head.is_branch()) {
+        Some(name) => name.to_string(),
+        None => DETACHED.to_string(),
+    };
+    let orig_head = head.peel_to_commit().map_err(rebase_error)?.id();
+    let onto_commit = repo
+        .revparse_single(onto)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(rebase_error)?;
+
+    let engine = Engine::new(repo, control)?;
+    fs::create_dir_all(&engine.dir).map_err(rebase_error)?;
+    engine.write("head-name", &head_name)?;
+    engine.write("onto", &onto_commit.id().to_string())?;
+    engine.write("orig-head", &orig_head.to_string())?;
+    engine.write("interactive", "")?;
+    engine.write("done", "")?;
+    let mut state = State {
+        onto_name: onto.to_string(),
+        todo: todo.to_vec(),
+        total: todo.len(),
+        ..State::default()
+    };
+    engine.save(&state)?;
+
+    // 途中で失敗したら、止まった状態を残さず始める前に戻す
+    let result = engine
+        .move_head(onto_commit.id())
+        .and_then(|()| engine.run(&mut state));
+    if result.is_err() {
+        let _ = abort(repo);
+    }
+    result
+}
+
+/// Continue a stopped rebase: commit the resolved conflicts, if it stopped
+/// on them, then run the remaining steps. A step that fails is put back on
+/// the todo, so the rebase stays stopped and can be continued or aborted.
+pub(crate) fn resume(repo: &Repository, control: &RebaseControl) -> GitResult<RebaseResult> {
+    let engine = Engine::new(repo, control)?;
+    let mut state = engine.load()?;
+
+    let mut index = repo.index().map_err(rebase_error)?;
+    if index.has_conflicts() {
+        return Ok(RebaseResult {
+            completed: false,
+            conflicts: collect_conflict_paths(&index),
+        });
+    }
+    let tree = index.write_tree().map_err(rebase_error)?;
+    let head = engine.head()?;
+    match state.pending.take() {
+        Some(entry) => engine.commit_resolution(&entry, &head, tree)?,
+        None if tree != head.tree_id() => {
+            return Err(GitError::RebaseFailed(
+                "commit or amend the staged changes before continuing".into(),
+            ));
+        }
+        None => {}
+    }
+    engine.clear_stop();
+    engine.run(&mut state)
+}
+
+/// Put the branch and working tree back to where they were before the
+/// rebase started.
+pub(crate) fn abort(repo: &Repository) -> GitResult<()> {
+    let control = RebaseControl::default();
+    let engine = Engine::new(repo, &control)?;
+    let head_name = engine.read("head-name")?;
+    let orig_head = Oid::from_str(&engine.read("orig-head")?).map_err(rebase_error)?;
+
+    // ブランチは finish まで動かさないので、HEAD を戻せば元の位置を指す
+    if head_name == DETACHED {
+        repo.set_head_detached(orig_head).map_err(rebase_error)?;
+    } else {
+        repo.set_head(&head_name).map_err(rebase_error)?;
+    }
+    let orig = repo.find_object(orig_head, None).map_err(rebase_error)?;
+    repo.reset(&orig, git2::ResetType::Hard, None)
+        .map_err(rebase_error)?;
+    engine.delete_labels();
+    // rebase-merge も消える
+    repo.cleanup_state().map_err(rebase_error)?;
+    let _ = fs::remove_file(repo.path().join("REBASE_HEAD"));
+    Ok(())
+}
+
+impl<'r> Engine<'r> {
+    fn new(repo: &'r Repository, control: &'r RebaseControl) -> GitResult<Self> {
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| GitError::RebaseFailed("bare repository".into()))?
+            .to_path_buf();
+        let committer = repo.signature().map_err(rebase_error)?.to_owned();
+        Ok(Self {
+            repo,
+            workdir,
+            dir: repo.path().join("rebase-merge"),
+            committer,
+            control,
+        })
+    }
+
+    fn write(&self, name: &str, content: &str) -> GitResult<()> {
+        fs::write(self.dir.join(name), content).map_err(rebase_error)
+    }
+
+    fn read(&self, name: &str) -> GitResult<String> {
+        fs::read_to_string(self.dir.join(name))
+            .map(|content| content.trim().to_string())
+            .map_err(rebase_error)
+    }
+
+    fn append(&self, name: &str, line: &str) -> GitResult<()> {
+        let mut content = fs::read_to_string(self.dir.join(name)).unwrap_or_default();
+        content.push_str(line);
+        content.push('\n');
+        self.write(name, &content)
+    }
+
+    fn load(&self) -> GitResult<State> {
+        let content = fs::read_to_string(self.dir.join(STATE_FILE)).map_err(rebase_error)?;
+        serde_json::from_str(&content).map_err(rebase_error)
+    }
+
+    /// Write the state, and the files `git rebase` reads for its progress.
+    fn save(&self, state: &State) -> GitResult<()> {
+        let content = serde_json::to_string_pretty(state).map_err(rebase_error)?;
+        self.write(STATE_FILE, &content)?;
+        self.write("onto_name", &state.onto_name)?;
+        self.write("msgnum", &state.done.to_string())?;
+        self.write("end", &state.total.to_string())?;
+        let todo: String = state
+            .todo
+            .iter()
+            .map(|entry| format!("{}\n", todo_line(entry)))
+            .collect();
+        self.write("git-rebase-todo", &todo)?;
+        let update_refs: String = state
+            .update_refs
+            .iter()
+            .map(|(name, oid)| format!("{name}\n{oid}\n"))
+            .collect();
+        self.write("update-refs", &update_refs)
+    }
+
+    fn head(&self) -> GitResult<Commit<'r>> {
+        self.repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(rebase_error)
+    }
+
+    fn find(&self, spec: &str) -> GitResult<Commit<'r>> {
+        self.repo
+            .revparse_single(spec)
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(rebase_error)
+    }
+
+    /// Check out `oid` and detach `HEAD` at it, as git does during a rebase.
+    fn move_head(&self, oid: Oid) -> GitResult<()> {
+        let commit = self.repo.find_commit(oid).map_err(rebase_error)?;
+        if commit.tree_id() != self.head()?.tree_id() {
+            self.repo
+                .checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))
+                .map_err(rebase_error)?;
+        }
+        self.repo.set_head_detached(oid).map_err(rebase_error)
+    }
+
+    fn run(&self, state: &mut State) -> GitResult<RebaseResult> {
+        while !state.todo.is_empty() {
+            let entry = state.todo.remove(0);
+            state.done += 1;
+            self.append("done", &todo_line(&entry))?;
+            self.save(state)?;
+            self.control.report(&RebaseProgress {
+                step: state.done,
+                total: state.total,
+                entry: entry.clone(),
+            });
+
+            let step = match self.step(&entry, state) {
+                Ok(step) => step,
+                Err(e) => {
+                    // git と同様に失敗した手順を todo に戻す
+                    let _ = self.requeue(state, entry);
+                    return Err(e);
+                }
+            };
+            match step {
+                Step::Next => self.save(state)?,
+                Step::Stop(reason) => {
+                    if reason == RebaseStopReason::Conflict {
+                        state.pending = Some(entry);
+                    }
+                    self.save(state)?;
+                    let index = self.repo.index().map_err(rebase_error)?;
+                    return Ok(RebaseResult {
+                        completed: false,
+                        conflicts: collect_conflict_paths(&index),
+                    });
+                }
+            }
+        }
+        self.finish(state)?;
+        Ok(RebaseResult {
+            completed: true,
+            conflicts: Vec::new(),
+        })
+    }
+
+    /// Put `entry`, the step that just failed, back at the head of the todo.
+    fn requeue(&self, state: &mut State, entry: RebaseTodoEntry) -> GitResult<()> {
+        let done = fs::read_to_string(self.dir.join("done")).unwrap_or_default();
+        let mut lines: Vec<&str> = done.lines().collect();
+        lines.pop();
+        let done: String = lines.iter().map(|line| format!("{line}\n")).collect();
+        self.write("done", &done)?;
+        state.todo.insert(0, entry);
+        state.done -= 1;
+        self.save(state)
+    }
+
+    fn argument<'e>(&self, entry: &'e RebaseTodoEntry) -> GitResult<&'e str> {
+        entry
+            .argument
+            .as_deref()
+            .map(str::trim)
+            .filter(|argument| !argument.is_empty())
+            .ok_or_else(|| {
+                GitError::RebaseFailed(format!("`{}` needs an argument", todo_line(entry)).into())
+            })
+    }
+
+    fn step(&self, entry: &RebaseTodoEntry, state: &mut State) -> GitResult<Step> {
+        match entry.action {
+            RebaseAction::Drop => Ok(Step::Next),
+            RebaseAction::Pick
+            | RebaseAction::Reword
+            | RebaseAction::Edit
+            | RebaseAction::Squash
+            | RebaseAction::Fixup
+            | RebaseAction::FixupMessage => self.pick(entry),
+            RebaseAction::Merge => self.merge(entry),
+            RebaseAction::Exec => self.exec(self.argument(entry)?),
+            RebaseAction::Break => Ok(Step::Stop(RebaseStopReason::Break)),
+            RebaseAction::Label => {
+                let name = format!("refs/rewritten/{}", self.argument(entry)?);
+                if !git2::Reference::is_valid_name(&name) {
+                    return Err(GitError::RebaseFailed(
+                        format!("invalid label: {}", self.argument(entry)?).into(),
+                    ));
+                }
+                let head = self.head()?.id();
+                self.repo
+                    .reference(&name, head, true, "rebase (label)")
+                    .map_err(rebase_error)?;
+                self.append("refs-to-delete", &name)?;
+                Ok(Step::Next)
+            }
+            RebaseAction::Reset => {
+                let target = self.resolve(self.argument(entry)?)?;
+                self.move_head(target.id())?;
+                Ok(Step::Next)
+            }
+            RebaseAction::UpdateRef => {
+                let name = self.argument(entry)?.to_string();
+                let head = self.head()?.id().to_string();
+                state.update_refs.retain(|(n, _)| *n != name);
+                state.update_refs.push((name, head));
+                Ok(Step::Next)
+            }
+        }
+    }
+
+    /// A label set by an earlier step, or any commit `git rev-parse` knows.
+    fn resolve(&self, name: &str) -> GitResult<Commit<'r>> {
+        let label = format!("refs/rewritten/{name}");
+        match self.repo.find_reference(&label) {
+            Ok(reference) => reference.peel_to_commit().map_err(rebase_error),
+            Err(_) => self.find(name),
+        }
+    }
+
+    /// Replay `commit`'s changes onto `onto` with an in-memory `git2::Rebase`.
+    fn apply(&self, commit: &Commit, onto: &Commit) -> GitResult<Applied> {
+        let parent = commit.parent(0).map_err(rebase_error)?;
+        // 元から空のコミットは git rebase と同様に空のまま残す
+        if commit.tree_id() == parent.tree_id() {
+            return Ok(Applied::Tree(onto.tree_id()));
+        }
+        let branch = self
+            .repo
+            .find_annotated_commit(commit.id())
+            .map_err(rebase_error)?;
+        let upstream = self
+            .repo
+            .find_annotated_commit(parent.id())
+            .map_err(rebase_error)?;
+        let onto_annotated = self
+            .repo
+            .find_annotated_commit(onto.id())
+            .map_err(rebase_error)?;
+        let mut options = git2::RebaseOptions::new();
+        options.inmemory(true);
+        let mut rebase = self
+            .repo
+            .rebase(
+                Some(&branch),
+                Some(&upstream),
+                Some(&onto_annotated),
+                Some(&mut options),
+            )
+            .map_err(rebase_error)?;
+
+        let applied = match rebase.next() {
+            None => Applied::Unchanged,
+            Some(operation) => {
+                operation.map_err(rebase_error)?;
+                let mut index = rebase.inmemory_index().map_err(rebase_error)?;
+                if index.has_conflicts() {
+                    Applied::Conflict
+                } else {
+                    let tree = index.write_tree_to(self.repo).map_err(rebase_error)?;
+                    if tree == onto.tree_id() {
+                        Applied::Unchanged
+                    } else {
+                        Applied::Tree(tree)
+                    }
+                }
+            }
+        };
+        let _ = rebase.abort();
+        Ok(applied)
+    }
+
+    fn pick(&self, entry: &RebaseTodoEntry) -> GitResult<Step> {
+        let commit = self.find(&entry.oid)?;
+        if commit.parent_count() != 1 {
+            return Err(GitError::RebaseFailed(
+                format!("{} is a root or merge commit", entry.short_oid).into(),
+            ));
+        }
+        let head = self.head()?;
+        let folds = is_fold(entry.action);
+
+        // 親がそのままなら作り直さずに進める
+        if matches!(entry.action, RebaseAction::Pick | RebaseAction::Edit)
+            && commit.parent_id(0).map_err(rebase_error)? == head.id()
+        {
+            self.move_head(commit.id())?;
+        } else {
+            match self.apply(&commit, &head)? {
+                Applied::Conflict => {
+                    self.write_conflict(&commit)?;
+                    return Ok(Step::Stop(RebaseStopReason::Conflict));
+                }
+                // 変更がすでに入っているコミットは git と同様に捨てる
+                Applied::Unchanged if !folds || entry.action == RebaseAction::Fixup => {}
+                Applied::Unchanged => {
+                    let oid = self.record(entry, &commit, &head, head.tree_id())?;
+                    self.move_head(oid)?;
+                }
+                Applied::Tree(tree) => {
+                    let oid = self.record(entry, &commit, &head, tree)?;
+                    self.move_head(oid)?;
+                }
+            }
+        }
+
+        if entry.action == RebaseAction::Edit {
+            self.write("stopped-sha", &commit.id().to_string())?;
+            return Ok(Step::Stop(RebaseStopReason::Edit));
+        }
+        Ok(Step::Next)
+    }
+
+    /// Commit `tree` as the outcome of `entry`: a new commit on `head` for
+    /// picks, `head` amended for squashes and fixups.
+    fn record(
+        &self,
+        entry: &RebaseTodoEntry,
+        commit: &Commit,
+        head: &Commit,
+        tree: Oid,
+    ) -> GitResult<Oid> {
+        let tree = self.repo.find_tree(tree).map_err(rebase_error)?;
+        let original = commit.message().unwrap_or_default();
+        if is_fold(entry.action) {
+            let message =
+                folded_message(entry.action, head.message().unwrap_or_default(), original);
+            let parents: Vec<Commit> = head.parents().collect();
+            let parents: Vec<&Commit> = parents.iter().collect();
+            return self
+                .repo
+                .commit(
+                    None,
+                    &head.author(),
+                    &self.committer,
+                    &message,
+                    &tree,
+                    &parents,
+                )
+                .map_err(rebase_error);
+        }
+        let message = match entry.action {
+            RebaseAction::Reword => cleanup_message(&entry.message),
+            _ => original.to_string(),
+        };
+        self.repo
+            .commit(
+                None,
+                &commit.author(),
+                &self.committer,
+                &message,
+                &tree,
+                &[head],
+            )
+            .map_err(rebase_error)
+    }
+
+    /// Recreate the merge `entry.oid` of `HEAD` and the labelled commits.
+    fn merge(&self, entry: &RebaseTodoEntry) -> GitResult<Step> {
+        let argument = self.argument(entry)?;
+        let others: Vec<&str> = argument.split_whitespace().collect();
+        let [other] = others.as_slice() else {
+            return Err(GitError::RebaseFailed(
+                "octopus merges are not supported".into(),
+            ));
+        };
+        let other = self.resolve(other)?;
+        let head = self.head()?;
+        let original = match entry.oid.is_empty() {
+            true => None,
+            false => Some(self.find(&entry.oid)?),
+        };
+
+        // 親が元のマージと同じなら作り直さずに進める
+        if let Some(original) = &original {
+            let parents: Vec<Oid> = original.parent_ids().collect();
+            if parents == [head.id(), other.id()] {
+                self.move_head(original.id())?;
+                return Ok(Step::Next);
+            }
+        }
+
+        let mut index = self
+            .repo
+            .merge_commits(&head, &other, None)
+            .map_err(rebase_error)?;
+        if index.has_conflicts() {
+            let annotated = self
+                .repo
+                .find_annotated_commit(other.id())
+                .map_err(rebase_error)?;
+            let mut checkout = CheckoutBuilder::new();
+            checkout.allow_conflicts(true);
+            self.repo
+                .merge(&[&annotated], None, Some(&mut checkout))
+                .map_err(rebase_error)?;
+            if let Some(original) = &original {
+                self.write_stop_files(original)?;
+            }
+            return Ok(Step::Stop(RebaseStopReason::Conflict));
+        }
+        let tree = index.write_tree_to(self.repo).map_err(rebase_error)?;
+        let oid = self.commit_merge(entry, original.as_ref(), &head, &other, tree)?;
+        self.move_head(oid)?;
+        Ok(Step::Next)
+    }
+
+    fn commit_merge(
+        &self,
+        entry: &RebaseTodoEntry,
+        original: Option<&Commit>,
+        head: &Commit,
+        other: &Commit,
+        tree: Oid,
+    ) -> GitResult<Oid> {
+        let tree = self.repo.find_tree(tree).map_err(rebase_error)?;
+        let (author, message) = match original {
+            Some(original) => (
+                original.author().to_owned(),
+                original.message().unwrap_or_default().to_string(),
+            ),
+            None => (
+                self.committer.clone(),
+                format!("Merge branch '{}'\n", self.argument(entry)?),
+            ),
+        };
+        self.repo
+            .commit(
+                None,
+                &author,
+                &self.committer,
+                &message,
+                &tree,
+                &[head, other],
+            )
+            .map_err(rebase_error)
+    }
+
+    /// Bring the conflicts of picking `commit` into the index and working
+    /// tree, where the user resolves them.
+    fn write_conflict(&self, commit: &Commit) -> GitResult<()> {
+        let mut options = git2::CherrypickOptions::new();
+        self.repo
+            .cherrypick(commit, Some(&mut options))
+            .map_err(rebase_error)?;
+        // cherry-pick ではなく rebase の途中として見せる
+        let git_dir = self.repo.path();
+        let _ = fs::remove_file(git_dir.join("CHERRY_PICK_HEAD"));
+        let _ = fs::remove_file(git_dir.join("MERGE_MSG"));
+        self.write_stop_files(commit)
+    }
+
+    /// The files git leaves for a step that stopped on `commit`.
+    fn write_stop_files(&self, commit: &Commit) -> GitResult<()> {
+        let oid = commit.id().to_string();
+        fs::write(self.repo.path().join("REBASE_HEAD"), format!("{oid}\n"))
+            .map_err(rebase_error)?;
+        self.write("stopped-sha", &oid)?;
+        self.write("message", commit.message().unwrap_or_default())?;
+        let author = commit.author();
+        let quote = |value: &str| format!("'{}'", value.replace('\'', "'\\''"));
+        let when = author.when();
+        let offset = when.offset_minutes();
+        let date = format!(
+            "@{} {}{:02}{:02}",
+            when.seconds(),
+            if offset < 0 { '-' } else { '+' },
+            offset.abs() / 60,
+            offset.abs() % 60
+        );
+        self.write(
+            "author-script",
+            &format!(
+                "GIT_AUTHOR_NAME={}\nGIT_AUTHOR_EMAIL={}\nGIT_AUTHOR_DATE={}\n",
+                quote(author.name().unwrap_or_default()),
+                quote(author.email().unwrap_or_default()),
+                quote(&date)
+            ),
+        )
+    }
+
+    /// Run an `exec` step's command in the working tree. When it fails or is
+    /// cancelled, what it printed is kept for the UI next to the rebase state.
+    fn exec(&self, command: &str) -> GitResult<Step> {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]).current_dir(&self.workdir);
+        let output =
+            run_cancellable(&mut cmd, self.control.cancel_token()).map_err(rebase_error)?;
+        let failure = match output {
+            Some(output) if output.status.success() => return Ok(Step::Next),
+            Some(output) => RebaseExecFailure {
+                command: command.to_string(),
+                exit_code: output.status.code(),
+                cancelled: false,
+                stdout: output_tail(&output.stdout),
+                stderr: output_tail(&output.stderr),
+            },
+            None => RebaseExecFailure {
+                command: command.to_string(),
+                exit_code: None,
+                cancelled: true,
+                stdout: String::new(),
+                stderr: String::new(),
+            },
+        };
+        let content = serde_json::to_string_pretty(&failure).map_err(rebase_error)?;
+        self.write(EXEC_FAILURE_FILE, &content)?;
+        Ok(Step::Stop(RebaseStopReason::ExecFailed))
+    }
+
+    fn clear_stop(&self) {
+        let _ = fs::remove_file(self.repo.path().join("REBASE_HEAD"));
+        for name in ["stopped-sha", "message", "author-script", EXEC_FAILURE_FILE] {
+            let _ = fs::remove_file(self.dir.join(name));
+        }
+    }
+
+    /// Commit the resolved index for the step that stopped on conflicts.
+    fn commit_resolution(
+        &self,
+        entry: &RebaseTodoEntry,
+        head: &Commit,
+        tree: Oid,
+    ) -> GitResult<()> {
+        let oid = if entry.action == RebaseAction::Merge {
+            let merge_head =
+                fs::read_to_string(self.repo.path().join("MERGE_HEAD")).map_err(rebase_error)?;
+            let other = self.find(merge_head.trim())?;
+            let original = match entry.oid.is_empty() {
+                true => None,
+                false => Some(self.find(&entry.oid)?),
+            };
+            let oid = self.commit_merge(entry, original.as_ref(), head, &other, tree)?;
+            for name in ["MERGE_HEAD", "MERGE_MSG", "MERGE_MODE"] {
+                let _ = fs::remove_file(self.repo.path().join(name));
+            }
+            Some(oid)
+        } else if tree == head.tree_id() && !is_fold(entry.action) {
+            // 解決した結果が空になったコミットは捨てる
+            None
+        } else {
+            let commit = self.find(&entry.oid)?;
+            Some(self.record(entry, &commit, head, tree)?)
+        };
+        if let Some(oid) = oid {
+            // 作業ツリーは解決済みの内容のままでよい
+            self.repo.set_head_detached(oid).map_err(rebase_error)?;
+        }
+        Ok(())
+    }
+
+    fn delete_labels(&self) {
+        let labels = fs::read_to_string(self.dir.join("refs-to-delete")).unwrap_or_default();
+        for name in labels.lines() {
+            if let Ok(mut reference) = self.repo.find_reference(name) {
+                let _ = reference.delete();
+            }
+        }
+    }
+
+    /// Point the rebased branch and the `update-ref` refs at their new
+    /// commits and leave the rebase.
+    fn finish(&self, state: &State) -> GitResult<()> {
+        let head_name = self.read("head-name")?;
+        let onto = self.read("onto")?;
+        let tip = self.head()?.id();
+        if head_name != DETACHED {
+            let message = format!("rebase (finish): {head_name} onto {onto}");
+            self.repo
+                .reference(&head_name, tip, true, &message)
+                .map_err(rebase_error)?;
+            self.repo.set_head(&head_name).map_err(rebase_error)?;
+        }
+        for (name, oid) in state.update_refs.iter().filter(|(n, _)| *n != head_name) {
+            let oid = Oid::from_str(oid).map_err(rebase_error)?;
+            let message = format!("rebase (update-refs): {name}");
+            self.repo
+                .reference(name, oid, true, &message)
+                .map_err(rebase_error)?;
+        }
+        self.delete_labels();
+        let _ = fs::remove_file(self.repo.path().join("REBASE_HEAD"));
+        fs::remove_dir_all(&self.dir).map_err(rebase_error)
+    }
+}
+
+fn cleanup_message(message: &str) -> String {
+    format!("{}\n", message.trim_end())
+}
+
+/// A message without its `fixup!`, `squash!` or `amend!` subject line, which
+/// git comments out when it combines messages.
+fn without_fixup_subject(message: &str) -> &str {
+    let subject = message.lines().next().unwrap_or_default();
+    match parse_fixup_subject(subject) {
+        Some(_) => message[subject.len()..].trim_start(),
+        None => message,
+    }
+}
+
+/// Message of `head` after folding in a commit with `message`.
+fn folded_message(action: RebaseAction, head: &str, message: &str) -> String {
+    match action {
+        RebaseAction::FixupMessage => cleanup_message(without_fixup_subject(message)),
+        RebaseAction::Squash => {
+            let body = without_fixup_subject(message).trim_end();
+            if body.is_empty() {
+                cleanup_message(head)
+            } else {
+                format!("{}\n\n{body}\n", head.trim_end())
+            }
+        }
+        _ => head.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::rebase::{read_rebase_state, todo_step};
+    use crate::git::transfer::CancelToken;
+    use std::time::{Duration, Instant};
+
+    fn git(dir: &std::path::Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?}");
+    }
+
+    #[test]
+    fn cancelling_kills_a_hung_exec_step() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "-q"]);
+        git(dir.path(), &["config", "user.name", "Test User"]);
+        git(dir.path(), &["config", "user.email", "test@example.com"]);
+        git(
+            dir.path(),
+            &["commit", "-q", "--allow-empty", "-m", "Initial"],
+        );
+        let repo = Repository::open(dir.path()).unwrap();
+
+        let token = CancelToken::new();
+        let canceller = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            canceller.cancel();
+        });
+        let todo = [todo_step(RebaseAction::Exec, "exec sleep 30")];
+        let started = Instant::now();
+        let result = start(&repo, "HEAD", &todo, &RebaseControl::new(token)).unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(!result.completed);
+        let state = read_rebase_state(repo.path(), false).unwrap();
+        assert_eq!(state.stop_reason, Some(RebaseStopReason::ExecFailed));
+        let failure = state.exec_failure.unwrap();
+        assert!(failure.cancelled);
+        assert_eq!(failure.command, "exec sleep 30");
+        assert_eq!(failure.exit_code, None);
+    }
+
+    #[test]
+    fn folded_message_drops_fixup_subjects() {
+        assert_eq!(
+            folded_message(RebaseAction::Squash, "Add a\n", "squash! Add a\n\nMore\n"),
+            "Add a\n\nMore\n"
+        );
+        assert_eq!(
+            folded_message(RebaseAction::Squash, "Add a\n", "Add b\n"),
+            "Add a\n\nAdd b\n"
+        );
+        assert_eq!(
+            folded_message(
+                RebaseAction::FixupMessage,
+                "Add a\n",
+                "amend! Add a\n\nAdd x\n"
+            ),
+            "Add x\n"
+        );
+        assert_eq!(
+            folded_message(RebaseAction::Fixup, "Add a\n", "fixup! Add a\n"),
+            "Add a\n"
+        );
+    }
+}
@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+
+use crate::git::error::{GitError, GitResult};
+use crate::git::types::{
+    BreakingChange, CommitInfo, Contributor, ReleaseNoteEntry, ReleaseNotes, ReleaseNotesFormat,
+    ReleaseSection,
+};
+
+/// Conventional commit types in the order they appear in the notes.
+const SECTIONS: [(&str, &str); 11] = [
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance Improvements"),
+    ("refactor", "Code Refactoring"),
+    ("docs", "Documentation"),
+    ("test", "Tests"),
+    ("build", "Build System"),
+    ("ci", "Continuous Integration"),
+    ("style", "Styles"),
+    ("chore", "Chores"),
+    ("revert", "Reverts"),
+];
+
+const OTHER: (&str, &str) = ("other", "Other Changes");
+
+const BREAKING_FOOTERS: [&str; 2] = ["BREAKING CHANGE:", "BREAKING-CHANGE:"];
+
+/// `type(scope)!: description` の subject 部分
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ConventionalSubject<'a> {
+    pub kind: &'a str,
+    pub scope: Option<&'a str>,
+    pub breaking: bool,
+    pub description: &'a str,
+}
+
+pub(crate) fn parse_subject(subject: &str) -> Option<ConventionalSubject<'_>> {
+    let (header, description) = subject.split_once(':')?;
+    let description = description.trim();
+    if description.is_empty() {
+        return None;
+    }
+    let (header, breaking) = match header.strip_suffix('!') {
+        Some(header) => (header, true),
+        None => (header, false),
+    };
+    let (kind, scope) = match header.split_once('(') {
+        Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?.trim())),
+        None => (header, None),
+    };
+    let valid_kind = !kind.is_empty()
+        && kind
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid_kind || scope == Some("") {
+        return None;
+    }
+    Some(ConventionalSubject {
+        kind,
+        scope,
+        breaking,
+        description,
+    })
+}
+
+/// Text of the `BREAKING CHANGE:` footers in a commit body. A footer runs
+/// until the next blank line.
+pub(crate) fn breaking_footers(body: &str) -> Vec<String> {
+    let mut notes: Vec<String> = Vec::new();
+    let mut in_footer = false;
+    for line in body.lines() {
+        if let Some(text) = BREAKING_FOOTERS
+            .iter()
+            .find_map(|footer| line.strip_prefix(footer))
+        {
+            notes.push(text.trim().to_string());
+            in_footer = true;
+        } else if line.trim().is_empty() {
+            in_footer = false;
+        } else if in_footer {
+            let note = notes.last_mut().expect("footer started");
+            if !note.is_empty() {
+                note.push(' ');
+            }
+            note.push_str(line.trim());
+        }
+    }
+    notes
+}
+
+/// PR and issue numbers mentioned as `#12`, `GH-12` or a `/pull/12` or
+/// `/issues/12` URL, in order of appearance.
+pub(crate) fn references(text: &str) -> Vec<u64> {
+    let mut numbers = Vec::new();
+    for (i, _) in text.char_indices() {
+        let rest = &text[i..];
+        let before = text[..i].chars().next_back();
+        let digits = if rest.starts_with('#') {
+            // `&#39;` のような文字参照や `a#1` は除外する
+            let standalone = !matches!(before, Some(c) if c.is_alphanumeric() || c == '&');
+            standalone.then(|| &rest[1..])
+        } else if rest.get(..3).is_some_and(|p| p.eq_ignore_ascii_case("gh-")) {
+            let standalone = !matches!(before, Some(c) if c.is_alphanumeric());
+            standalone.then(|| &rest[3..])
+        } else {
+            ["/pull/", "/issues/"]
+                .iter()
+                .find_map(|path| rest.strip_prefix(path))
+        };
+        let Some(digits) = digits else {
+            continue;
+        };
+        let end = digits
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(digits.len());
+        if let Ok(number) = digits[..end].parse::<u64>() {
+            if !numbers.contains(&number) {
+                numbers.push(number);
+            }
+        }
+    }
+    numbers
+}
+
+/// Group `commits` (newest first, as returned by the log) into release notes.
+/// Merge commits only contribute their references (`Merge pull request #12`);
+/// the commits they bring in are listed instead.
+pub(crate) fn build(from: Option<&str>, to: &str, commits: &[CommitInfo]) -> ReleaseNotes {
+    let mut sections: Vec<ReleaseSection> = Vec::new();
+    let mut breaking_changes = Vec::new();
+    let mut contributors: Vec<Contributor> = Vec::new();
+    let mut contributor_index: HashMap<String, usize> = HashMap::new();
+    let mut all_references = Vec::new();
+
+    for commit in commits {
+        if commit.parent_oids.len() > 1 {
+            all_references.extend(references(&commit.message));
+            continue;
+        }
+        let subject = parse_subject(&commit.message);
+        let (kind, scope, description) = match &subject {
+            Some(s) => (s.kind.to_ascii_lowercase(), s.scope, s.description),
+            None => (OTHER.0.to_string(), None, commit.message.trim()),
+        };
+        let footers = breaking_footers(&commit.body);
+        let breaking = subject.as_ref().is_some_and(|s| s.breaking) || !footers.is_empty();
+        let entry_references = references(&format!("{}\n{}", commit.message, commit.body));
+
+        if footers.is_empty() && breaking {
+            breaking_changes.push(BreakingChange {
+                short_oid: commit.short_oid.clone(),
+                scope: scope.map(str::to_string),
+                description: description.to_string(),
+            });
+        }
+        for footer in footers {
+            breaking_changes.push(BreakingChange {
+                short_oid: commit.short_oid.clone(),
+                scope: scope.map(str::to_string),
+                description: footer,
+            });
+        }
+
+        let entry = ReleaseNoteEntry {
+            oid: commit.oid.clone(),
+            short_oid: commit.short_oid.clone(),
+            scope: scope.map(str::to_string),
+            description: description.to_string(),
+            breaking,
+            references: entry_references.clone(),
+            author_name: commit.author_name.clone(),
+        };
+        match sections.iter_mut().find(|s| s.kind == kind) {
+            Some(section) => section.entries.push(entry),
+            None => sections.push(ReleaseSection {
+                title: section_title(&kind),
+                kind,
+                entries: vec![entry],
+            }),
+        }
+
+        // Co-authored-by で名前が挙がった人も貢献者に数える
+        let authors = std::iter::once((&commit.author_name, &commit.author_email)).chain(
+            commit
+                .co_authors
+                .iter()
+                .filter(|c| !c.email.eq_ignore_ascii_case(&commit.author_email))
+                .map(|c| (&c.name, &c.email)),
+        );
+        for (name, email) in authors {
+            let key = email.to_ascii_lowercase();
+            match contributor_index.get(&key) {
+                Some(&i) => contributors[i].commits += 1,
+                None => {
+                    contributor_index.insert(key, contributors.len());
+                    contributors.push(Contributor {
+                        name: name.clone(),
+                        email: email.clone(),
+                        commits: 1,
+                    });
+                }
+            }
+        }
+
+        all_references.extend(entry_references);
+    }
+
+    sections.sort_by_key(|s| section_rank(&s.kind));
+    contributors.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.name.cmp(&b.name)));
+    all_references.sort_unstable();
+    all_references.dedup();
+
+    ReleaseNotes {
+        from: from.map(str::to_string),
+        to: to.to_string(),
+        sections,
+        breaking_changes,
+        contributors,
+        references: all_references,
+    }
+}
+
+fn section_title(kind: &str) -> String {
+    SECTIONS
+        .iter()
+        .chain([&OTHER])
+        .find(|(k, _)| *k == kind)
+        .map_or_else(|| kind.to_string(), |(_, title)| title.to_string())
+}
+
+/// Known types first, then unknown types by name, then `other`.
+fn section_rank(kind: &str) -> (usize, String) {
+    match SECTIONS.iter().position(|(k, _)| *k == kind) {
+        Some(i) => (i, String::new()),
+        None if kind == OTHER.0 => (SECTIONS.len() + 1, String::new()),
+        None => (SECTIONS.len(), kind.to_string()),
+    }
+}
+
+pub fn render(notes: &ReleaseNotes, format: ReleaseNotesFormat) -> GitResult<String> {
+    match format {
+        ReleaseNotesFormat::Markdown => Ok(render_markdown(notes)),
+        ReleaseNotesFormat::Json => serde_json::to_string_pretty(notes)
+            .map_err(|e| GitError::ReleaseNotesFailed(Box::new(e))),
+    }
+}
+
+fn render_markdown(notes: &ReleaseNotes) -> String {
+    let mut out = match &notes.from {
+        Some(from) => format!("## {} ({from}...{})\n", notes.to, notes.to),
+        None => format!("## {}\n", notes.to),
+    };
+
+    if !notes.breaking_changes.is_empty() {
+        out.push_str("\n### BREAKING CHANGES\n\n");
+        for change in &notes.breaking_changes {
+            out.push_str(&format!(
+                "- {}{} ({})\n",
+                scope_prefix(change.scope.as_deref()),
+                change.description,
+                change.short_oid
+            ));
+        }
+    }
+
+    for section in &notes.sections {
+        out.push_str(&format!("\n### {}\n\n", section.title));
+        for entry in &section.entries {
+            // subject に `(#12)` と書かれていれば重ねて付けない
+            let mentioned = references(&entry.description);
+            let references: Vec<String> = entry
+                .references
+                .iter()
+                .filter(|n| !mentioned.contains(n))
+                .map(|n| format!("#{n}"))
+                .collect();
+            let references = if references.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", references.join(", "))
+            };
+            out.push_str(&format!(
+                "- {}{}{references} ({})\n",
+                scope_prefix(entry.scope.as_deref()),
+                entry.description,
+                entry.short_oid
+            ));
+        }
+    }
+
+    if !notes.contributors.is_empty() {
+        out.push_str("\n### Contributors\n\n");
+        for contributor in &notes.contributors {
+            let plural = if contributor.commits == 1 { "" } else { "s" };
+            out.push_str(&format!(
+                "- {} ({} commit{plural})\n",
+                contributor.name, contributor.commits
+            ));
+        }
+    }
+    out
+}
+
+fn scope_prefix(scope: Option<&str>) -> String {
+    scope.map_or_else(String::new, |scope| format!("**{scope}:** "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::types::{CoAuthor, SignatureStatus};
+
+    fn commit(oid: &str, message: &str, body: &str, author: &str) -> CommitInfo {
+        CommitInfo {
+            oid: oid.repeat(40),
+            short_oid: oid.repeat(7),
+            message: message.to_string(),
+            body: body.to_string(),
+            author_name: author.to_string(),
+            author_email: format!("{}@example.com", author.to_lowercase()),
+            author_date: 0,
+            parent_oids: vec!["0".repeat(40)],
+            refs: Vec::new(),
+            signature_status: SignatureStatus::None,
+            trailers: Vec::new(),
+            co_authors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parse_subject_reads_type_scope_and_bang() {
+        assert_eq!(
+            parse_subject("feat(api)!: drop v1 endpoints"),
+            Some(ConventionalSubject {
+                kind: "feat",
+                scope: Some("api"),
+                breaking: true,
+                description: "drop v1 endpoints",
+            })
+        );
+        assert_eq!(parse_subject("fix: typo").unwrap().scope, None);
+        assert!(parse_subject("Update README").is_none());
+        assert!(parse_subject("Merge branch 'main': sync").is_none());
+        assert!(parse_subject("feat(): empty scope").is_none());
+    }
+
+    #[test]
+    fn breaking_footers_join_continuation_lines() {
+        let body = "Details.\n\nBREAKING CHANGE: config moved\nto settings.toml\n\nRefs: #3";
+        assert_eq!(
+            breaking_footers(body),
+            vec!["config moved to settings.toml"]
+        );
+    }
+
+    #[test]
+    fn references_find_issue_and_pr_numbers() {
+        let text = "fix: crash (#12)\n\n\
+Closes GH-7, see https://github.com/o/r/pull/40 and #12.\n\
+Not &#39; or a#5, but €#9.";
+        assert_eq!(references(text), vec![12, 7, 40, 9]);
+    }
+
+    #[test]
+    fn build_groups_by_type_and_renders_markdown() {
+        let commits = vec![
+            commit("a", "fix(ui): button alignment (#3)", "", "Bob"),
+            commit("b", "feat!: new config format", "", "Alice"),
+            commit("c", "Update README", "", "Alice"),
+            commit(
+                "d",
+                "feat(api): add search",
+                "BREAKING CHANGE: search replaces find\n\nCloses #8",
+                "Alice",
+            ),
+        ];
+        let notes = build(Some("v1.0.0"), "v1.1.0", &commits);
+
+        let kinds: Vec<&str> = notes.sections.iter().map(|s| s.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["feat", "fix", "other"]);
+        assert_eq!(notes.breaking_changes.len(), 2);
+        assert_eq!(notes.contributors[0].name, "Alice");
+        assert_eq!(notes.contributors[0].commits, 3);
+        assert_eq!(notes.references, vec![3, 8]);
+
+        let markdown = render(&notes, ReleaseNotesFormat::Markdown).unwrap();
+        assert_eq!(
+            markdown,
+            "## v1.1.0 (v1.0.0...v1.1.0)
+
+### BREAKING CHANGES
+
+- new config format (bbbbbbb)
+- **api:** search replaces find (ddddddd)
+
+### Features
+
+- new config format (bbbbbbb)
+- **api:** add search (#8) (ddddddd)
+
+### Bug Fixes
+
+- **ui:** button alignment (#3) (aaaaaaa)
+
+### Other Changes
+
+- Update README (ccccccc)
+
+### Contributors
+
+- Alice (3 commits)
+- Bob (1 commit)
+"
+        );
+    }
+
+    #[test]
+    fn build_counts_co_authors_as_contributors() {
+        let mut paired = commit("a", "fix: pairing", "", "Alice");
+        paired.co_authors = vec![
+            CoAuthor {
+                name: "Bob".to_string(),
+                email: "bob@example.com".to_string(),
+            },
+            CoAuthor {
+                name: "Alice".to_string(),
+                email: "ALICE@example.com".to_string(),
+            },
+        ];
+        let notes = build(None, "HEAD", &[paired, commit("b", "fix: solo", "", "Bob")]);
+
+        let contributors: Vec<(&str, usize)> = notes
+            .contributors
+            .iter()
+            .map(|c| (c.name.as_str(), c.commits))
+            .collect();
+        assert_eq!(contributors, vec![("Bob", 2), ("Alice", 1)]);
+    }
+}
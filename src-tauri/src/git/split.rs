@@ -0,0 +1,342 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::git::backend::GitBackend;
+use crate::git::error::{GitError, GitResult};
+use crate::git::git2_backend::format_line_patch;
+use crate::git::rebase::{self, RebaseControl};
+use crate::git::types::{
+    DiffHunk, DiffLineKind, HunkIdentifier, RebaseAction, RebaseTodoEntry, SplitCommitGroup,
+    SplitCommitResult,
+};
+
+fn git(
+    workdir: &Path,
+    args: &[&str],
+    env: &[(&str, &str)],
+    input: Option<&str>,
+) -> GitResult<String> {
+    let mut child = Command::new("git")
+        .args(args)
+        .envs(env.iter().copied())
+        .current_dir(workdir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| GitError::SplitFailed(Box::new(e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.unwrap_or_default().as_bytes())
+            .map_err(|e| GitError::SplitFailed(Box::new(e)))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| GitError::SplitFailed(Box::new(e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::SplitFailed(stderr.trim().to_string().into()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// A private index the new trees are built in, so the split never touches
+/// the real index or working tree.
+struct ScratchIndex<'a> {
+    workdir: &'a Path,
+    path: PathBuf,
+}
+
+impl<'a> ScratchIndex<'a> {
+    fn new(workdir: &'a Path) -> GitResult<Self> {
+        let git_dir = git(workdir, &["rev-parse", "--absolute-git-dir"], &[], None)?;
+        Ok(Self {
+            workdir,
+            path: Path::new(&git_dir).join("rocket-split-index"),
+        })
+    }
+
+    fn git(&self, args: &[&str], input: Option<&str>) -> GitResult<String> {
+        let index = self.path.to_string_lossy();
+        git(self.workdir, args, &[("GIT_INDEX_FILE", &index)], input)
+    }
+}
+
+impl Drop for ScratchIndex<'_> {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn same_hunk(a: &DiffHunk, b: &HunkIdentifier) -> bool {
+    a.old_start == b.old_start
+        && a.old_lines == b.old_lines
+        && a.new_start == b.new_start
+        && a.new_lines == b.new_lines
+}
+
+fn changed_lines(hunk: &DiffHunk) -> impl Iterator<Item = usize> + '_ {
+    hunk.lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| matches!(line.kind, DiffLineKind::Addition | DiffLineKind::Deletion))
+        .map(|(index, _)| index)
+}
+
+/// Hunks of the commit's diff, looked up per file as the groups need them.
+struct CommitHunks<'a> {
+    backend: &'a dyn GitBackend,
+    oid: &'a str,
+    /// `git diff-tree --name-status` letter of every path the commit changes.
+    statuses: HashMap<String, String>,
+    hunks: HashMap<String, Vec<DiffHunk>>,
+}
+
+impl CommitHunks<'_> {
+    fn check_path(&self, path: &str) -> GitResult<&str> {
+        self.statuses.get(path).map(String::as_str).ok_or_else(|| {
+            GitError::SplitFailed(format!("{path} is not changed by the commit").into())
+        })
+    }
+
+    fn find(&mut self, path: &str, hunk: &HunkIdentifier) -> GitResult<(usize, &DiffHunk)> {
+        if self.check_path(path)? != "M" {
+            return Err(GitError::SplitFailed(
+                format!("{path} is added, deleted or retyped; select the whole file").into(),
+            ));
+        }
+        if !self.hunks.contains_key(path) {
+            let hunks = self
+                .backend
+                .get_commit_file_diff(self.oid, path)?
+                .into_iter()
+                .flat_map(|file| file.hunks)
+                .collect();
+            self.hunks.insert(path.to_string(), hunks);
+        }
+        self.hunks[path]
+            .iter()
+            .enumerate()
+            .find(|(_, h)| same_hunk(h, hunk))
+            .ok_or_else(|| {
+                GitError::SplitFailed(
+                    format!(
+                        "hunk -{},{} +{},{} not found in {path}",
+                        hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+                    )
+                    .into(),
+                )
+            })
+    }
+}
+
+/// Rewrite `oid`, a non-merge commit on the current branch, into one commit
+/// per group and replay its descendants on top. Each group's tree is rebuilt
+/// from the parent with every change selected so far, so a hunk's lines can
+/// be spread over several groups. The new commits keep the original author.
+pub fn split_commit(
+    backend: &dyn GitBackend,
+    oid: &str,
+    groups: &[SplitCommitGroup],
+) -> GitResult<SplitCommitResult> {
+    let workdir = backend.workdir();
+    if groups.is_empty() {
+        return Err(GitError::SplitFailed("no groups to split into".into()));
+    }
+    if let Some(n) = groups.iter().position(|g| g.message.trim().is_empty()) {
+        return Err(GitError::SplitFailed(
+            format!("group {} has no message", n + 1).into(),
+        ));
+    }
+
+    let info = backend.get_commit_detail(oid)?.info;
+    let [parent] = info.parent_oids.as_slice() else {
+        return Err(GitError::SplitFailed(
+            format!("{} is a root or merge commit", info.short_oid).into(),
+        ));
+    };
+    git(
+        workdir,
+        &["merge-base", "--is-ancestor", &info.oid, "HEAD"],
+        &[],
+        None,
+    )
+    .map_err(|_| {
+        GitError::SplitFailed(format!("{} is not on the current branch", info.short_oid).into())
+    })?;
+    if !rebase::is_clean(backend)? {
+        return Err(GitError::SplitFailed(
+            "commit or stash your changes first".into(),
+        ));
+    }
+
+    let name_status = git(
+        workdir,
+        &[
+            "diff-tree",
+            "-r",
+            "-z",
+            "--name-status",
+            "--no-renames",
+            parent,
+            &info.oid,
+        ],
+        &[],
+        None,
+    )?;
+    let fields: Vec<&str> = name_status.split('\0').collect();
+    let mut commit_hunks = CommitHunks {
+        backend,
+        oid: &info.oid,
+        statuses: fields
+            .chunks_exact(2)
+            .map(|pair| (pair[1].to_string(), pair[0].to_string()))
+            .collect(),
+        hunks: HashMap::new(),
+    };
+
+    let author = git(
+        workdir,
+        &[
+            "show",
+            "-s",
+            "--date=raw",
+            "--format=%an%x00%ae%x00%ad",
+            &info.oid,
+        ],
+        &[],
+        None,
+    )?;
+    let author: Vec<&str> = author.split('\0').collect();
+    let [name, email, date] = author.as_slice() else {
+        return Err(GitError::SplitFailed(
+            "could not read the commit's author".into(),
+        ));
+    };
+    let author_env = [
+        ("GIT_AUTHOR_NAME", *name),
+        ("GIT_AUTHOR_EMAIL", *email),
+        ("GIT_AUTHOR_DATE", *date),
+    ];
+    let commit_tree = |tree: &str, parent: &str, message: &str| {
+        git(
+            workdir,
+            &["commit-tree", tree, "-p", parent, "-F", "-"],
+            &author_env,
+            Some(message),
+        )
+    };
+
+    let scratch = ScratchIndex::new(workdir)?;
+    let mut whole: BTreeSet<String> = BTreeSet::new();
+    // path -> 元のコミットの hunk の位置 -> ここまでに選ばれた行
+    let mut selected: BTreeMap<String, BTreeMap<usize, HashSet<usize>>> = BTreeMap::new();
+    let mut commits = Vec::new();
+    let mut tip = parent.clone();
+    let mut tip_tree = git(
+        workdir,
+        &["rev-parse", &format!("{parent}^{{tree}}")],
+        &[],
+        None,
+    )?;
+    for (n, group) in groups.iter().enumerate() {
+        for path in &group.files {
+            commit_hunks.check_path(path)?;
+            whole.insert(path.clone());
+        }
+        let ranges = group.hunks.iter().map(|h| (&h.path, &h.hunk, None)).chain(
+            group
+                .lines
+                .iter()
+                .map(|l| (&l.path, &l.range.hunk, Some(&l.range.line_indices))),
+        );
+        for (path, hunk, lines) in ranges {
+            let (index, hunk) = commit_hunks.find(path, hunk)?;
+            let chosen = selected
+                .entry(path.clone())
+                .or_default()
+                .entry(index)
+                .or_default();
+            match lines {
+                Some(lines) => chosen.extend(lines.iter().copied()),
+                None => chosen.extend(changed_lines(hunk)),
+            }
+        }
+
+        scratch.git(&["read-tree", parent], None)?;
+        for path in &whole {
+            let patch = git(
+                workdir,
+                &[
+                    "diff-tree",
+                    "-p",
+                    "-r",
+                    "--binary",
+                    "--no-renames",
+                    parent,
+                    &info.oid,
+                    "--",
+                    path,
+                ],
+                &[],
+                None,
+            )?;
+            scratch.git(&["apply", "--cached"], Some(&format!("{patch}\n")))?;
+        }
+        for (path, hunks) in selected.iter().filter(|(path, _)| !whole.contains(*path)) {
+            for (index, lines) in hunks {
+                let patch = format_line_patch(path, &commit_hunks.hunks[path][*index], lines);
+                scratch.git(&["apply", "--cached"], Some(&patch))?;
+            }
+        }
+        let tree = scratch.git(&["write-tree"], None)?;
+        if tree == tip_tree {
+            return Err(GitError::SplitFailed(
+                format!("group {} selects no new changes", n + 1).into(),
+            ));
+        }
+        tip = commit_tree(&tree, &tip, &group.message)?;
+        tip_tree = tree;
+        commits.push(tip.clone());
+    }
+
+    let tree = git(
+        workdir,
+        &["rev-parse", &format!("{}^{{tree}}", info.oid)],
+        &[],
+        None,
+    )?;
+    if tree != tip_tree {
+        let message = git(
+            workdir,
+            &["show", "-s", "--format=%B", &info.oid],
+            &[],
+            None,
+        )?;
+        commits.push(commit_tree(&tree, &tip, &message)?);
+    }
+
+    // 新しいコミットは親が揃っているので rebase は早送りで通り、oid は変わらない
+    let mut todo: Vec<RebaseTodoEntry> = commits
+        .iter()
+        .zip(
+            groups
+                .iter()
+                .map(|g| g.message.as_str())
+                .chain([info.message.as_str()]),
+        )
+        .map(|(oid, message)| RebaseTodoEntry {
+            action: RebaseAction::Pick,
+            oid: oid.clone(),
+            short_oid: oid[..7].to_string(),
+            message: message.lines().next().unwrap_or_default().to_string(),
+            author_name: name.to_string(),
+            argument: None,
+        })
+        .collect();
+    todo.extend(backend.get_rebase_todo(&info.oid, usize::MAX)?);
+    let rebase = backend.interactive_rebase(parent, &todo, &RebaseControl::default())?;
+    Ok(SplitCommitResult { commits, rebase })
+}
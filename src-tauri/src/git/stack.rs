@@ -0,0 +1,394 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::process::Command;
+
+use crate::git::backend::GitBackend;
+use crate::git::branch::{default_base, split_remote_branch};
+use crate::git::error::{GitError, GitResult};
+use crate::git::rebase::{is_fold, todo_step};
+use crate::git::transfer::TransferControl;
+use crate::git::types::{
+    BranchStack, PushOptions, PushRefStatus, PushResult, RebaseAction, RebaseTodoEntry,
+    StackBranch, StackPushFailure, StackPushResult,
+};
+
+fn git(workdir: &Path, args: &[&str]) -> GitResult<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(workdir)
+        .output()
+        .map_err(|e| GitError::StackFailed(Box::new(e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::StackFailed(stderr.trim().to_string().into()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Local branch tips by short name, from one `for-each-ref`.
+fn branch_tips(workdir: &Path) -> GitResult<HashMap<String, String>> {
+    let tips = git(
+        workdir,
+        &[
+            "for-each-ref",
+            "--format=%(objectname) %(refname)",
+            "refs/heads",
+        ],
+    )?;
+    Ok(tips
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .filter_map(|(oid, name)| {
+            let name = name.strip_prefix("refs/heads/")?;
+            Some((name.to_string(), oid.to_string()))
+        })
+        .collect())
+}
+
+/// The commits on local branches that `fork` does not contain, with their
+/// parents, from a single walk.
+struct Graph {
+    parents: HashMap<String, Vec<String>>,
+}
+
+impl Graph {
+    fn since(workdir: &Path, fork: &str) -> GitResult<Self> {
+        let walk = git(
+            workdir,
+            &["rev-list", "--parents", "--branches", &format!("^{fork}")],
+        )?;
+        let parents = walk
+            .lines()
+            .filter_map(|line| {
+                let mut oids = line.split(' ').map(str::to_string);
+                Some((oids.next()?, oids.collect()))
+            })
+            .collect();
+        Ok(Self { parents })
+    }
+
+    /// The walked commits `tip` contains, itself included.
+    fn ancestors(&self, tip: &str) -> HashSet<&str> {
+        let mut seen = HashSet::new();
+        let mut queue = vec![tip];
+        while let Some(oid) = queue.pop() {
+            let Some((oid, parents)) = self.parents.get_key_value(oid) else {
+                continue;
+            };
+            if seen.insert(oid.as_str()) {
+                queue.extend(parents.iter().map(String::as_str));
+            }
+        }
+        seen
+    }
+
+    /// Whether `ancestors` of some tip reach `oid`, which may be the fork
+    /// the walk stopped at.
+    fn reaches(&self, ancestors: &HashSet<&str>, oid: &str) -> bool {
+        ancestors.contains(oid)
+            || ancestors
+                .iter()
+                .any(|commit| self.parents[*commit].iter().any(|p| p == oid))
+    }
+}
+
+/// Branches checked out in any worktree, the current one included. Like
+/// `git rebase --update-refs`, a rebase never moves them.
+fn checked_out(workdir: &Path) -> GitResult<HashSet<String>> {
+    let list = git(workdir, &["worktree", "list", "--porcelain"])?;
+    Ok(list
+        .lines()
+        .filter_map(|line| line.strip_prefix("branch "))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Local branches whose tips lie in `onto..HEAD`, by tip oid.
+fn stacked_refs(workdir: &Path, onto: &str) -> GitResult<HashMap<String, Vec<String>>> {
+    let range = git(workdir, &["rev-list", &format!("{onto}..HEAD")])?;
+    let commits: HashSet<&str> = range.lines().collect();
+    let skip = checked_out(workdir)?;
+    let tips = git(
+        workdir,
+        &[
+            "for-each-ref",
+            "--format=%(objectname) %(refname)",
+            "refs/heads",
+        ],
+    )?;
+    let mut refs: HashMap<String, Vec<String>> = HashMap::new();
+    for (oid, name) in tips.lines().filter_map(|line| line.split_once(' ')) {
+        if commits.contains(oid) && !skip.contains(name) {
+            refs.entry(oid.to_string())
+                .or_default()
+                .push(name.to_string());
+        }
+    }
+    Ok(refs)
+}
+
+/// Add an `update-ref` step for each of `refs` (tip oid -> ref names) after
+/// the step that rewrites its tip, or after the fixups folded into it. Refs
+/// the todo already updates are left where they are.
+fn place_update_refs(
+    todo: Vec<RebaseTodoEntry>,
+    mut refs: HashMap<String, Vec<String>>,
+) -> Vec<RebaseTodoEntry> {
+    let planned: HashSet<String> = todo
+        .iter()
+        .filter(|entry| entry.action == RebaseAction::UpdateRef)
+        .filter_map(|entry| entry.argument.clone())
+        .collect();
+    let mut result = Vec::new();
+    let mut pending = Vec::new();
+    let mut entries = todo.into_iter().peekable();
+    while let Some(entry) = entries.next() {
+        if let Some(names) = refs.remove(&entry.oid) {
+            pending.extend(names.into_iter().filter(|name| !planned.contains(name)));
+        }
+        result.push(entry);
+        if !entries.peek().is_some_and(|next| is_fold(next.action)) {
+            result.extend(
+                pending
+                    .drain(..)
+                    .map(|name| todo_step(RebaseAction::UpdateRef, &name)),
+            );
+        }
+    }
+    result
+}
+
+/// `todo` with `update-ref` steps for the other branches whose tips it
+/// rewrites, so a stack of branches stays stacked after the rebase, like
+/// `git rebase --update-refs`.
+pub fn with_update_refs(
+    workdir: &Path,
+    onto: &str,
+    todo: Vec<RebaseTodoEntry>,
+) -> GitResult<Vec<RebaseTodoEntry>> {
+    Ok(place_update_refs(todo, stacked_refs(workdir, onto)?))
+}
+
+/// The refs a rebase running `todo` moves: `HEAD`'s branch and the targets
+/// of its `update-ref` steps, so the operation journal can restore them.
+pub(crate) fn rebased_refs(todo: &[RebaseTodoEntry]) -> Vec<String> {
+    std::iter::once("HEAD".to_string())
+        .chain(
+            todo.iter()
+                .filter(|entry| entry.action == RebaseAction::UpdateRef)
+                .filter_map(|entry| entry.argument.clone()),
+        )
+        .collect()
+}
+
+/// Like [`rebased_refs`] for `git rebase --update-refs`, which plans its
+/// own todo.
+pub(crate) fn rebased_refs_onto(workdir: &Path, onto: &str) -> GitResult<Vec<String>> {
+    let mut refs: Vec<String> = stacked_refs(workdir, onto)?
+        .into_values()
+        .flatten()
+        .collect();
+    refs.sort();
+    refs.insert(0, "HEAD".to_string());
+    Ok(refs)
+}
+
+/// The stack the checked out branch belongs to: the local branches between
+/// `base` (by default `main` or `master`) and it, and those built on top of
+/// it. Only a straight line of branches is followed; a branch that forks
+/// off above another one is left out.
+pub fn list_stack(backend: &dyn GitBackend, base: Option<&str>) -> GitResult<BranchStack> {
+    let workdir = backend.workdir();
+    let branches: Vec<_> = backend
+        .list_branches()?
+        .into_iter()
+        .filter(|b| !b.is_remote)
+        .collect();
+    let base = match base {
+        Some(base) => base.to_string(),
+        None => {
+            default_base(&branches).ok_or_else(|| GitError::StackFailed("no base branch".into()))?
+        }
+    };
+    let head = branches
+        .iter()
+        .find(|b| b.is_head)
+        .ok_or_else(|| GitError::StackFailed("HEAD is not on a branch".into()))?;
+    if head.name == base {
+        return Err(GitError::StackFailed(
+            format!("{base} is the base; check out a branch of the stack").into(),
+        ));
+    }
+    let fork = git(workdir, &["merge-base", &base, "HEAD"])?;
+    let tips = branch_tips(workdir)?;
+    let graph = Graph::since(workdir, &fork)?;
+    let head_oid = tips
+        .get(&head.name)
+        .ok_or_else(|| GitError::StackFailed("HEAD is not on a branch".into()))?;
+    let below_head = graph.ancestors(head_oid);
+
+    let mut members = Vec::new();
+    for branch in branches.into_iter().filter(|b| b.name != base) {
+        let Some(tip) = tips.get(&branch.name).cloned() else {
+            continue;
+        };
+        let ancestors = graph.ancestors(&tip);
+        let below = below_head.contains(tip.as_str());
+        let above = tip == *head_oid || ancestors.contains(head_oid.as_str());
+        if !below && !above {
+            continue;
+        }
+        members.push((ancestors.len() as u32, tip, branch, ancestors));
+    }
+    members.sort_by(|a, b| (a.0, &a.2.name).cmp(&(b.0, &b.2.name)));
+
+    let mut stack = Vec::new();
+    let (mut last_count, mut last_tip) = (0, fork);
+    for (count, tip, branch, ancestors) in members {
+        // base より古いブランチや、HEAD より上で枝分かれしたブランチはここで外れる
+        if tip != last_tip && !graph.reaches(&ancestors, &last_tip) {
+            continue;
+        }
+        stack.push(StackBranch {
+            name: branch.name,
+            oid: tip.clone(),
+            commit_count: count - last_count,
+            is_head: branch.is_head,
+            upstream: branch.upstream,
+            ahead_count: branch.ahead_count,
+            behind_count: branch.behind_count,
+        });
+        (last_count, last_tip) = (count, tip);
+    }
+    Ok(BranchStack {
+        base,
+        branches: stack,
+    })
+}
+
+/// Why the remote rejected a branch in `result`, if it did.
+fn rejection(result: &PushResult) -> Option<String> {
+    let update = result.updates.iter().find(|u| u.status.is_rejected())?;
+    let reason = match (&update.message, update.status) {
+        (Some(message), _) => message.as_str(),
+        (None, PushRefStatus::RejectedStale) => "stale info",
+        (None, PushRefStatus::RejectedNonFastForward) => "non-fast-forward",
+        (None, _) => "rejected",
+    };
+    Some(format!("{} was rejected ({reason})", update.remote_ref))
+}
+
+/// Push every branch of the stack to `remote_name` with `--force-with-lease`,
+/// bottom first. A branch goes to the branch it tracks on that remote, or to
+/// one of the same name, which it then tracks if it tracked nothing yet.
+/// Stops at the first branch that fails, reporting it with those pushed.
+pub fn push_stack(
+    backend: &dyn GitBackend,
+    base: Option<&str>,
+    remote_name: &str,
+    control: &TransferControl,
+) -> GitResult<StackPushResult> {
+    let stack = list_stack(backend, base)?;
+    let remotes: Vec<String> = backend
+        .list_remotes()?
+        .into_iter()
+        .map(|r| r.name)
+        .collect();
+    let mut pushed = Vec::new();
+    for branch in &stack.branches {
+        let target = branch
+            .upstream
+            .as_deref()
+            .and_then(|upstream| split_remote_branch(upstream, &remotes))
+            .filter(|(remote, _)| *remote == remote_name)
+            .map_or(branch.name.as_str(), |(_, name)| name);
+        let options = PushOptions {
+            refspec: Some(format!("refs/heads/{}:refs/heads/{target}", branch.name)),
+            set_upstream: branch.upstream.is_none(),
+            force_with_lease: true,
+            ..PushOptions::default()
+        };
+        let (result, message) = match backend.push(remote_name, &options, control) {
+            Ok(result) => match rejection(&result) {
+                Some(message) => (Some(result), message),
+                None => {
+                    pushed.push(result);
+                    continue;
+                }
+            },
+            Err(e) => (None, e.to_string()),
+        };
+        return Ok(StackPushResult {
+            pushed,
+            failed: Some(StackPushFailure {
+                branch: branch.name.clone(),
+                result,
+                message,
+            }),
+        });
+    }
+    Ok(StackPushResult {
+        pushed,
+        failed: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pick(oid: &str, action: RebaseAction) -> RebaseTodoEntry {
+        RebaseTodoEntry {
+            action,
+            oid: oid.to_string(),
+            short_oid: oid.to_string(),
+            message: String::new(),
+            author_name: String::new(),
+            argument: None,
+        }
+    }
+
+    fn plan(todo: &[RebaseTodoEntry]) -> Vec<String> {
+        todo.iter()
+            .map(|entry| match entry.action {
+                RebaseAction::UpdateRef => {
+                    format!("update-ref {}", entry.argument.as_deref().unwrap())
+                }
+                _ => entry.oid.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn update_refs_follow_their_commit_and_its_fixups() {
+        let refs: HashMap<String, Vec<String>> = [
+            ("a".to_string(), vec!["refs/heads/one".to_string()]),
+            (
+                "b".to_string(),
+                vec!["refs/heads/two".to_string(), "refs/heads/done".to_string()],
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let mut todo = vec![
+            pick("a", RebaseAction::Pick),
+            pick("f", RebaseAction::Fixup),
+            pick("b", RebaseAction::Pick),
+            pick("c", RebaseAction::Pick),
+        ];
+        todo.push(todo_step(RebaseAction::UpdateRef, "refs/heads/done"));
+
+        assert_eq!(
+            plan(&place_update_refs(todo, refs)),
+            vec![
+                "a",
+                "f",
+                "update-ref refs/heads/one",
+                "b",
+                "update-ref refs/heads/two",
+                "c",
+                "update-ref refs/heads/done",
+            ]
+        );
+    }
+}
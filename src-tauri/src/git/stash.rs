@@ -0,0 +1,274 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::git::backend::GitBackend;
+use crate::git::error::{GitError, GitResult};
+use crate::git::git2_backend::{generate_hunk_patch, parse_stash_branch_name};
+use crate::git::types::{StashHunk, StashOptions};
+
+fn stash_error(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> GitError {
+    GitError::StashFailed(e.into())
+}
+
+/// Run git in `workdir`, optionally against another index file.
+fn git(workdir: &Path, args: &[&str], index: Option<&Path>) -> GitResult<String> {
+    git_with_input(workdir, args, index, None)
+}
+
+fn git_with_input(
+    workdir: &Path,
+    args: &[&str],
+    index: Option<&Path>,
+    input: Option<&str>,
+) -> GitResult<String> {
+    let mut cmd = Command::new("git");
+    cmd.args(args)
+        .current_dir(workdir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(index) = index {
+        cmd.env("GIT_INDEX_FILE", index);
+    }
+    let mut child = cmd.spawn().map_err(stash_error)?;
+    if let (Some(stdin), Some(input)) = (child.stdin.as_mut(), input) {
+        stdin.write_all(input.as_bytes()).map_err(stash_error)?;
+    }
+    let output = child.wait_with_output().map_err(stash_error)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stash_error(stderr.trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Hunks are stashed on their own; the other options select whole files.
+pub(crate) fn validate(options: &StashOptions) -> GitResult<()> {
+    let whole_files = options.include_untracked
+        || options.include_ignored
+        || options.keep_index
+        || !options.paths.is_empty();
+    if !options.hunks.is_empty() && whole_files {
+        return Err(stash_error(
+            "hunks cannot be combined with paths, untracked files or keep-index",
+        ));
+    }
+    Ok(())
+}
+
+/// Arguments for `git stash push` with the whole-file options.
+pub(crate) fn push_args<'a>(message: Option<&'a str>, options: &'a StashOptions) -> Vec<&'a str> {
+    let mut args = vec!["stash", "push"];
+    if options.include_ignored {
+        args.push("--all");
+    } else if options.include_untracked {
+        args.push("--include-untracked");
+    }
+    if options.keep_index {
+        args.push("--keep-index");
+    }
+    if let Some(msg) = message.filter(|m| !m.is_empty()) {
+        args.extend(["-m", msg]);
+    }
+    if !options.paths.is_empty() {
+        args.push("--");
+        args.extend(options.paths.iter().map(String::as_str));
+    }
+    args
+}
+
+/// `git stash push` exits successfully when there is nothing to stash.
+pub(crate) fn check_stashed(stdout: &str) -> GitResult<()> {
+    if stdout.contains("No local changes to save") {
+        return Err(stash_error("there is nothing to stash"));
+    }
+    Ok(())
+}
+
+pub(crate) fn push_with_git(
+    workdir: &Path,
+    message: Option<&str>,
+    options: &StashOptions,
+) -> GitResult<()> {
+    let stdout = git(workdir, &push_args(message, options), None)?;
+    check_stashed(&stdout)
+}
+
+/// Stash the selected unstaged hunks and remove them from the working tree.
+///
+/// `git stash --patch` is interactive and libgit2 has no equivalent, so the
+/// stash commits are written by hand: the index commit records the index as
+/// is and the working tree commit adds the selected hunks on top of it,
+/// applied in a scratch index so the real one is left alone.
+pub(crate) fn stash_hunks(
+    backend: &dyn GitBackend,
+    message: Option<&str>,
+    hunks: &[StashHunk],
+) -> GitResult<()> {
+    let workdir = backend.workdir();
+    let patches = hunks
+        .iter()
+        .map(|h| generate_hunk_patch(backend, Path::new(&h.path), &h.hunk, false))
+        .collect::<GitResult<Vec<_>>>()?;
+
+    let head = git(workdir, &["rev-parse", "HEAD"], None)?;
+    let subject = git(workdir, &["log", "-1", "--format=%h %s", "HEAD"], None)?;
+    let branch = git(
+        workdir,
+        &["symbolic-ref", "--quiet", "--short", "HEAD"],
+        None,
+    )
+    .unwrap_or_else(|_| "(no branch)".to_string());
+
+    let index_tree = git(workdir, &["write-tree"], None)?;
+    let index_message = format!("index on {branch}: {subject}");
+    let index_commit = git(
+        workdir,
+        &[
+            "commit-tree",
+            &index_tree,
+            "-p",
+            &head,
+            "-m",
+            &index_message,
+        ],
+        None,
+    )?;
+
+    let scratch = scratch_index(workdir)?;
+    let worktree_tree = write_patched_tree(workdir, &scratch, &patches);
+    let _ = fs::remove_file(&scratch);
+    let worktree_tree = worktree_tree?;
+
+    let stash_message = match message.filter(|m| !m.is_empty()) {
+        Some(message) => format!("On {branch}: {message}"),
+        None => format!("WIP on {branch}: {subject}"),
+    };
+    let stash_commit = git(
+        workdir,
+        &[
+            "commit-tree",
+            &worktree_tree,
+            "-p",
+            &head,
+            "-p",
+            &index_commit,
+            "-m",
+            &stash_message,
+        ],
+        None,
+    )?;
+    git(
+        workdir,
+        &["stash", "store", "-m", &stash_message, &stash_commit],
+        None,
+    )?;
+
+    for patch in patches.iter().rev() {
+        git_with_input(workdir, &["apply", "-R"], None, Some(patch))?;
+    }
+    Ok(())
+}
+
+/// A copy of the index to apply hunks to.
+fn scratch_index(workdir: &Path) -> GitResult<PathBuf> {
+    let git_dir = PathBuf::from(git(workdir, &["rev-parse", "--absolute-git-dir"], None)?);
+    let dir = git_dir.join("rocket");
+    fs::create_dir_all(&dir).map_err(stash_error)?;
+    let scratch = dir.join("stash-index");
+    fs::copy(git_dir.join("index"), &scratch).map_err(stash_error)?;
+    Ok(scratch)
+}
+
+fn write_patched_tree(workdir: &Path, index: &Path, patches: &[String]) -> GitResult<String> {
+    for patch in patches {
+        git_with_input(workdir, &["apply", "--cached"], Some(index), Some(patch))?;
+    }
+    git(workdir, &["write-tree"], Some(index))
+}
+
+/// Reword entry `index` of the stash reflog in place. Neither git nor
+/// libgit2 can do this without dropping and re-storing the entry, which would
+/// move it to the top of the list.
+pub(crate) fn rename(git_dir: &Path, index: usize, message: &str) -> GitResult<()> {
+    let log_path = git_dir.join("logs").join("refs").join("stash");
+    let content = fs::read_to_string(&log_path).map_err(stash_error)?;
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    // The reflog is oldest first; stash@{0} is the last line.
+    let position = lines
+        .len()
+        .checked_sub(index + 1)
+        .ok_or_else(|| stash_error(format!("stash not found: stash@{{{index}}}")))?;
+    let line = &mut lines[position];
+    let (header, old_message) = line
+        .split_once('\t')
+        .ok_or_else(|| stash_error("malformed stash reflog"))?;
+    let branch = parse_stash_branch_name(old_message);
+    *line = format!("{header}\tOn {branch}: {message}");
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    fs::write(&log_path, content).map_err(stash_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::types::HunkIdentifier;
+
+    #[test]
+    fn validate_rejects_hunks_with_whole_file_options() {
+        let hunk = StashHunk {
+            path: "a.txt".to_string(),
+            hunk: HunkIdentifier {
+                old_start: 1,
+                old_lines: 1,
+                new_start: 1,
+                new_lines: 1,
+            },
+        };
+        let options = StashOptions {
+            hunks: vec![hunk.clone()],
+            ..StashOptions::default()
+        };
+        assert!(validate(&options).is_ok());
+
+        let options = StashOptions {
+            hunks: vec![hunk],
+            paths: vec!["b.txt".to_string()],
+            ..StashOptions::default()
+        };
+        assert!(validate(&options).is_err());
+    }
+
+    #[test]
+    fn rename_rewrites_one_reflog_entry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let logs = tmp.path().join("logs").join("refs");
+        fs::create_dir_all(&logs).unwrap();
+        let a = "a".repeat(40);
+        let b = "b".repeat(40);
+        let z = "0".repeat(40);
+        fs::write(
+            logs.join("stash"),
+            format!(
+                "{z} {a} T <t@example.com> 1 +0000\tWIP on main: 123 subject\n\
+{a} {b} T <t@example.com> 2 +0000\tOn feature: newest\n"
+            ),
+        )
+        .unwrap();
+
+        rename(tmp.path(), 1, "renamed").unwrap();
+
+        let content = fs::read_to_string(logs.join("stash")).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines[0],
+            format!("{z} {a} T <t@example.com> 1 +0000\tOn main: renamed")
+        );
+        assert!(lines[1].ends_with("\tOn feature: newest"));
+        assert!(rename(tmp.path(), 2, "missing").is_err());
+    }
+}
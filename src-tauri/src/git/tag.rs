@@ -0,0 +1,259 @@
+use std::cmp::Ordering;
+use std::path::Path;
+use std::process::Command;
+
+use crate::git::types::{SignatureStatus, TagInfo};
+
+const SIGNATURE_MARKERS: [&str; 3] = [
+    "-----BEGIN PGP SIGNATURE-----",
+    "-----BEGIN PGP MESSAGE-----",
+    "-----BEGIN SSH SIGNATURE-----",
+];
+
+/// Split an annotated tag's message into the message and the signature block
+/// appended after it.
+pub(crate) fn split_signature(message: &str) -> (&str, Option<&str>) {
+    let start = SIGNATURE_MARKERS
+        .iter()
+        .filter_map(|marker| {
+            if message.starts_with(marker) {
+                Some(0)
+            } else {
+                message.find(&format!("\n{marker}")).map(|i| i + 1)
+            }
+        })
+        .min();
+    match start {
+        Some(start) => (&message[..start], Some(&message[start..])),
+        None => (message, None),
+    }
+}
+
+/// Message shown for a tag and the status of its signature, checked with
+/// `git verify-tag` only when the message carries one.
+pub(crate) fn message_and_signature(
+    workdir: &Path,
+    name: &str,
+    message: &str,
+) -> (String, SignatureStatus) {
+    match split_signature(message) {
+        (message, Some(_)) => (message.to_string(), verify(workdir, name)),
+        (message, None) => (message.to_string(), SignatureStatus::None),
+    }
+}
+
+fn verify(workdir: &Path, name: &str) -> SignatureStatus {
+    let output = Command::new("git")
+        .args(["verify-tag", "--raw", &format!("refs/tags/{name}")])
+        .current_dir(workdir)
+        .output();
+    match output {
+        Ok(output) => parse_verify_output(
+            output.status.success(),
+            &String::from_utf8_lossy(&output.stderr),
+        ),
+        Err(_) => SignatureStatus::Error,
+    }
+}
+
+/// `git verify-tag --raw` prints gpg's status lines or ssh-keygen's messages
+/// on stderr. Mapped the same way git maps them for `%G?`.
+fn parse_verify_output(success: bool, stderr: &str) -> SignatureStatus {
+    let gpg = |status: &str| {
+        stderr
+            .lines()
+            .any(|line| line.starts_with(&format!("[GNUPG:] {status} ")))
+    };
+    if gpg("BADSIG") || gpg("REVKEYSIG") {
+        SignatureStatus::Bad
+    } else if gpg("EXPSIG") || gpg("EXPKEYSIG") {
+        SignatureStatus::Expired
+    } else if gpg("ERRSIG") {
+        SignatureStatus::Error
+    } else if gpg("GOODSIG") {
+        if gpg("TRUST_FULLY") || gpg("TRUST_ULTIMATE") {
+            SignatureStatus::Good
+        } else {
+            SignatureStatus::Untrusted
+        }
+    } else if stderr.contains("No principal matched") {
+        SignatureStatus::Untrusted
+    } else if success && stderr.contains("Good \"git\" signature") {
+        SignatureStatus::Good
+    } else if success {
+        SignatureStatus::Untrusted
+    } else {
+        SignatureStatus::Error
+    }
+}
+
+/// A tag name read as a version: `v1.2.3-rc.1+build` has the numbers
+/// `[1, 2, 3]` and the pre-release identifiers `["rc", "1"]`.
+struct Version<'a> {
+    numbers: Vec<u64>,
+    pre_release: Vec<&'a str>,
+}
+
+fn parse_version(name: &str) -> Option<Version<'_>> {
+    let version = name
+        .strip_prefix('v')
+        .or_else(|| name.strip_prefix('V'))
+        .unwrap_or(name);
+    let version = version.split_once('+').map_or(version, |(v, _)| v);
+    let (core, pre_release) = match version.split_once('-') {
+        Some((core, pre)) => (core, pre.split('.').collect()),
+        None => (version, Vec::new()),
+    };
+    let numbers = core
+        .split('.')
+        .map(|n| n.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    Some(Version {
+        numbers,
+        pre_release,
+    })
+}
+
+/// Semantic version precedence. Missing components count as zero, so `v1.2`
+/// and `v1.2.0` only differ by name.
+fn compare_versions(a: &Version, b: &Version) -> Ordering {
+    let len = a.numbers.len().max(b.numbers.len());
+    let number = |v: &Version, i: usize| v.numbers.get(i).copied().unwrap_or(0);
+    (0..len)
+        .map(|i| number(a, i).cmp(&number(b, i)))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+        .then_with(|| compare_pre_release(&a.pre_release, &b.pre_release))
+}
+
+fn compare_pre_release(a: &[&str], b: &[&str]) -> Ordering {
+    // リリース版はプレリリースより新しい
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        (false, false) => {}
+    }
+    for (x, y) in a.iter().zip(b) {
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => x.cmp(y),
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// Order tags oldest version first. Names that are not versions follow the
+/// versions in name order.
+pub(crate) fn sort_by_version(tags: &mut [TagInfo]) {
+    tags.sort_by(|a, b| {
+        let ordering = match (parse_version(&a.name), parse_version(&b.name)) {
+            (Some(x), Some(y)) => compare_versions(&x, &y),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        ordering.then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(name: &str) -> TagInfo {
+        TagInfo {
+            name: name.to_string(),
+            target_oid: String::new(),
+            target_short_oid: String::new(),
+            is_annotated: false,
+            tagger_name: None,
+            tagger_date: None,
+            message: None,
+            signature_status: SignatureStatus::None,
+        }
+    }
+
+    #[test]
+    fn sort_by_version_follows_semver_precedence() {
+        let mut tags: Vec<TagInfo> = [
+            "v1.10.0",
+            "latest",
+            "v1.2.0",
+            "v1.0.0",
+            "v1.0.0-rc.10",
+            "v1.0.0-alpha",
+            "v1.0.0-rc.2",
+            "1.9",
+            "v1.0.0-alpha.1",
+            "nightly",
+        ]
+        .into_iter()
+        .map(tag)
+        .collect();
+
+        sort_by_version(&mut tags);
+
+        let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "v1.0.0-alpha",
+                "v1.0.0-alpha.1",
+                "v1.0.0-rc.2",
+                "v1.0.0-rc.10",
+                "v1.0.0",
+                "v1.2.0",
+                "1.9",
+                "v1.10.0",
+                "latest",
+                "nightly",
+            ]
+        );
+    }
+
+    #[test]
+    fn split_signature_separates_trailing_block() {
+        let message =
+            "Release 1.0\n-----BEGIN SSH SIGNATURE-----\nabc\n-----END SSH SIGNATURE-----\n";
+        let (message, signature) = split_signature(message);
+        assert_eq!(message, "Release 1.0\n");
+        assert!(signature
+            .unwrap()
+            .starts_with("-----BEGIN SSH SIGNATURE-----"));
+
+        assert_eq!(split_signature("plain\n"), ("plain\n", None));
+    }
+
+    #[test]
+    fn parse_verify_output_maps_gpg_and_ssh_results() {
+        let good = "[GNUPG:] GOODSIG ABC Test\n[GNUPG:] TRUST_ULTIMATE 0 pgp\n";
+        assert_eq!(parse_verify_output(true, good), SignatureStatus::Good);
+        let unknown = "[GNUPG:] GOODSIG ABC Test\n[GNUPG:] TRUST_UNDEFINED 0 pgp\n";
+        assert_eq!(
+            parse_verify_output(true, unknown),
+            SignatureStatus::Untrusted
+        );
+        let bad = "[GNUPG:] BADSIG ABC Test\n";
+        assert_eq!(parse_verify_output(false, bad), SignatureStatus::Bad);
+        let missing_key = "[GNUPG:] ERRSIG ABC 1 8 00 1 9\n[GNUPG:] NO_PUBKEY ABC\n";
+        assert_eq!(
+            parse_verify_output(false, missing_key),
+            SignatureStatus::Error
+        );
+
+        let ssh = "Good \"git\" signature for test@example.com with ED25519 key SHA256:x\n";
+        assert_eq!(parse_verify_output(true, ssh), SignatureStatus::Good);
+        let ssh_unknown =
+            "Good \"git\" signature with ED25519 key SHA256:x\nNo principal matched.\n";
+        assert_eq!(
+            parse_verify_output(true, ssh_unknown),
+            SignatureStatus::Untrusted
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::git::error::{GitError, GitResult};
+use crate::git::types::{CoAuthor, CommitTrailer};
+
+pub const CO_AUTHORED_BY: &str = "Co-authored-by";
+pub const SIGNED_OFF_BY: &str = "Signed-off-by";
+pub const REVIEWED_BY: &str = "Reviewed-by";
+pub const REFS: &str = "Refs";
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn parse_line(line: &str) -> Option<CommitTrailer> {
+    let (key, value) = line.split_once(':')?;
+    is_valid_key(key).then(|| CommitTrailer {
+        key: key.to_string(),
+        value: value.trim().to_string(),
+    })
+}
+
+/// Trailers of a commit message: the last paragraph, when it is not the
+/// subject and every line in it is a `Key: value` line or an indented
+/// continuation of one.
+pub fn parse(message: &str) -> Vec<CommitTrailer> {
+    let lines: Vec<&str> = message
+        .trim_end()
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .collect();
+    let Some(start) = lines.iter().rposition(|l| l.trim().is_empty()) else {
+        return Vec::new();
+    };
+
+    let mut trailers: Vec<CommitTrailer> = Vec::new();
+    for line in &lines[start + 1..] {
+        if line.starts_with([' ', '\t']) {
+            // 折り返された値は直前のトレーラーに連結する
+            let Some(last) = trailers.last_mut() else {
+                return Vec::new();
+            };
+            last.value.push(' ');
+            last.value.push_str(line.trim());
+            continue;
+        }
+        match parse_line(line) {
+            Some(trailer) => trailers.push(trailer),
+            None => return Vec::new(),
+        }
+    }
+    trailers
+}
+
+/// `Name <email>`, as written in `Co-authored-by`.
+pub fn parse_identity(value: &str) -> Option<CoAuthor> {
+    let (name, rest) = value.split_once('<')?;
+    let email = rest.strip_suffix('>')?.trim();
+    if email.is_empty() {
+        return None;
+    }
+    Some(CoAuthor {
+        name: name.trim().to_string(),
+        email: email.to_string(),
+    })
+}
+
+pub fn co_authors(trailers: &[CommitTrailer]) -> Vec<CoAuthor> {
+    trailers
+        .iter()
+        .filter(|t| t.key.eq_ignore_ascii_case(CO_AUTHORED_BY))
+        .filter_map(|t| parse_identity(&t.value))
+        .collect()
+}
+
+pub fn co_author_trailer(co_author: &CoAuthor) -> CommitTrailer {
+    CommitTrailer {
+        key: CO_AUTHORED_BY.to_string(),
+        value: format!("{} <{}>", co_author.name, co_author.email),
+    }
+}
+
+/// Append trailers to a message, joining an existing trailer block if there
+/// is one. Trailers the message already has are not repeated.
+pub fn append(message: &str, trailers: &[CommitTrailer]) -> GitResult<String> {
+    let existing = parse(message);
+    let mut lines: Vec<String> = Vec::new();
+    for trailer in trailers {
+        let value = trailer.value.trim();
+        if !is_valid_key(&trailer.key) || value.is_empty() || value.contains('\n') {
+            return Err(GitError::CommitFailed(
+                format!("invalid trailer `{}: {}`", trailer.key, trailer.value).into(),
+            ));
+        }
+        let duplicate =
+            |t: &CommitTrailer| t.key.eq_ignore_ascii_case(&trailer.key) && t.value == value;
+        if existing.iter().any(duplicate) {
+            continue;
+        }
+        let line = format!("{}: {value}", trailer.key);
+        if !lines.contains(&line) {
+            lines.push(line);
+        }
+    }
+    if lines.is_empty() {
+        return Ok(message.to_string());
+    }
+
+    let body = message.trim_end();
+    let separator = if body.is_empty() {
+        ""
+    } else if existing.is_empty() {
+        "\n\n"
+    } else {
+        "\n"
+    };
+    Ok(format!("{body}{separator}{}\n", lines.join("\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trailer(key: &str, value: &str) -> CommitTrailer {
+        CommitTrailer {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn parse_reads_last_paragraph_only() {
+        let message = "Fix crash\n\nSee: the docs\nfor details\n\nRefs: #12\nCo-authored-by: Ann <ann@x.org>\n  (pairing)\n";
+        assert_eq!(
+            parse(message),
+            vec![
+                trailer("Refs", "#12"),
+                trailer("Co-authored-by", "Ann <ann@x.org> (pairing)"),
+            ]
+        );
+        assert!(parse("Refs: #12").is_empty());
+        assert!(parse("Fix\n\nRefs: #12\nnot a trailer").is_empty());
+    }
+
+    #[test]
+    fn co_authors_come_from_co_authored_by_trailers() {
+        let trailers = parse(
+            "Pair\n\nco-authored-by: Ann <ann@x.org>\nCo-authored-by: broken\nReviewed-by: Bo <bo@x.org>",
+        );
+        assert_eq!(
+            co_authors(&trailers),
+            vec![CoAuthor {
+                name: "Ann".to_string(),
+                email: "ann@x.org".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn append_joins_existing_block_without_duplicates() {
+        let ann = CoAuthor {
+            name: "Ann".to_string(),
+            email: "ann@x.org".to_string(),
+        };
+        assert_eq!(
+            append("Fix crash\n", &[co_author_trailer(&ann)]).unwrap(),
+            "Fix crash\n\nCo-authored-by: Ann <ann@x.org>\n"
+        );
+        assert_eq!(
+            append(
+                "Fix crash\n\nRefs: #12\n",
+                &[
+                    trailer(REFS, "#12"),
+                    trailer(SIGNED_OFF_BY, "Bo <bo@x.org>")
+                ]
+            )
+            .unwrap(),
+            "Fix crash\n\nRefs: #12\nSigned-off-by: Bo <bo@x.org>\n"
+        );
+        assert!(append("Fix", &[trailer("Bad key", "x")]).is_err());
+    }
+}
@@ -0,0 +1,372 @@
+use std::io::Read;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Snapshot of a network transfer. Fetch/clone report received objects,
+/// push reports written objects in the same fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferProgress {
+    pub total_objects: u64,
+    pub transferred_objects: u64,
+    pub indexed_objects: u64,
+    pub total_deltas: u64,
+    pub indexed_deltas: u64,
+    pub transferred_bytes: u64,
+    /// Sideband message from the remote (`remote: ...`), if this update carries one.
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+type ProgressCallback = dyn Fn(&TransferProgress) + Send + Sync;
+
+/// Progress sink and cancellation token handed to fetch/pull/push/clone.
+#[derive(Clone, Default)]
+pub struct TransferControl {
+    cancel: CancelToken,
+    on_progress: Option<Arc<ProgressCallback>>,
+}
+
+impl TransferControl {
+    pub fn new(cancel: CancelToken) -> Self {
+        Self {
+            cancel,
+            on_progress: None,
+        }
+    }
+
+    pub fn with_progress(
+        mut self,
+        callback: impl Fn(&TransferProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn report(&self, progress: &TransferProgress) {
+        if let Some(callback) = &self.on_progress {
+            callback(progress);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+}
+
+/// Run a git network command with `--progress` output parsed into
+/// `TransferProgress` updates. Returns `Ok(None)` when the command was
+/// cancelled; the child process is killed in that case.
+///
+/// Progress lines are stripped from the returned stderr so that error
+/// messages stay readable.
+pub(crate) fn run_with_progress(
+    cmd: &mut Command,
+    control: &TransferControl,
+) -> std::io::Result<Option<Output>> {
+    if control.is_cancelled() {
+        return Ok(None);
+    }
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout_reader = read_to_end(child.stdout.take());
+    let stderr = child.stderr.take();
+    let stderr_control = control.clone();
+    let stderr_reader = thread::spawn(move || match stderr {
+        Some(stderr) => read_progress(stderr, &stderr_control),
+        None => Vec::new(),
+    });
+
+    let Some(status) = wait_or_kill(&mut child, || control.is_cancelled())? else {
+        return Ok(None);
+    };
+    Ok(Some(Output {
+        status,
+        stdout: stdout_reader.join().unwrap_or_default(),
+        stderr: stderr_reader.join().unwrap_or_default(),
+    }))
+}
+
+/// Run `cmd` to completion, capturing its output, unless `cancel` fires
+/// first. Returns `Ok(None)` when cancelled; the child is killed then.
+pub(crate) fn run_cancellable(
+    cmd: &mut Command,
+    cancel: &CancelToken,
+) -> std::io::Result<Option<Output>> {
+    if cancel.is_cancelled() {
+        return Ok(None);
+    }
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout_reader = read_to_end(child.stdout.take());
+    let stderr_reader = read_to_end(child.stderr.take());
+
+    let Some(status) = wait_or_kill(&mut child, || cancel.is_cancelled())? else {
+        return Ok(None);
+    };
+    Ok(Some(Output {
+        status,
+        stdout: stdout_reader.join().unwrap_or_default(),
+        stderr: stderr_reader.join().unwrap_or_default(),
+    }))
+}
+
+/// パイプを別スレッドで読み切る。読まないと子プロセスが書き込みで止まる。
+fn read_to_end(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Wait for `child`, killing it once `is_cancelled` returns true. Returns
+/// `None` when it was killed.
+///
+/// The pipe readers are not joined after a kill: a grandchild the command
+/// started may still hold the pipes open.
+fn wait_or_kill(
+    child: &mut Child,
+    is_cancelled: impl Fn() -> bool,
+) -> std::io::Result<Option<ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// git はプログレス行を `\r` で上書きするため、`\r` と `\n` の両方で区切る。
+fn read_progress(mut stderr: impl Read, control: &TransferControl) -> Vec<u8> {
+    let mut progress = TransferProgress::default();
+    let mut remaining = Vec::new();
+    let mut line = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    loop {
+        let n = match stderr.read(&mut chunk) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        for &byte in &chunk[..n] {
+            if byte != b'\r' && byte != b'\n' {
+                line.push(byte);
+                continue;
+            }
+            let text = String::from_utf8_lossy(&line).into_owned();
+            line.clear();
+            if apply_progress_line(&mut progress, &text) {
+                control.report(&progress);
+            } else if !text.trim().is_empty() {
+                remaining.extend_from_slice(text.as_bytes());
+                remaining.push(b'\n');
+            }
+        }
+    }
+
+    let text = String::from_utf8_lossy(&line).into_owned();
+    if apply_progress_line(&mut progress, &text) {
+        control.report(&progress);
+    } else if !text.trim().is_empty() {
+        remaining.extend_from_slice(text.as_bytes());
+    }
+    remaining
+}
+
+/// Update `progress` from one line of git's progress output. Returns `false`
+/// for lines that are not progress output (errors, ref updates, ...).
+fn apply_progress_line(progress: &mut TransferProgress, line: &str) -> bool {
+    let line = line.trim();
+    progress.message = None;
+
+    if let Some(message) = line.strip_prefix("remote:") {
+        let message = message.trim();
+        if message.is_empty() {
+            return true;
+        }
+        progress.message = Some(message.to_string());
+        return true;
+    }
+
+    let Some((label, rest)) = line.split_once(':') else {
+        return false;
+    };
+    if matches!(
+        label,
+        "Enumerating objects" | "Counting objects" | "Compressing objects"
+    ) {
+        progress.message = Some(line.to_string());
+        return true;
+    }
+    let Some((current, total)) = parse_counts(rest) else {
+        return false;
+    };
+
+    match label {
+        "Receiving objects" | "Unpacking objects" | "Writing objects" => {
+            progress.transferred_objects = current;
+            progress.total_objects = total;
+            if let Some(bytes) = parse_bytes(rest) {
+                progress.transferred_bytes = bytes;
+            }
+            if label == "Unpacking objects" {
+                progress.indexed_objects = current;
+            }
+        }
+        "Resolving deltas" => {
+            progress.indexed_deltas = current;
+            progress.total_deltas = total;
+            if current == total {
+                progress.indexed_objects = progress.total_objects;
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// `"  45% (450/1000), 1.20 MiB | ..."` から `(450, 1000)` を取り出す。
+fn parse_counts(rest: &str) -> Option<(u64, u64)> {
+    let start = rest.find('(')?;
+    let end = start + rest[start..].find(')')?;
+    let (current, total) = rest[start + 1..end].split_once('/')?;
+    Some((current.trim().parse().ok()?, total.trim().parse().ok()?))
+}
+
+fn parse_bytes(rest: &str) -> Option<u64> {
+    let after_counts = rest.split_once("),")?.1;
+    let size = after_counts.split('|').next()?.trim();
+    let size = size.trim_end_matches(", done.").trim_end_matches(", done");
+    let (value, unit) = size.split_once(' ')?;
+    let value: f64 = value.parse().ok()?;
+    let multiplier = match unit.trim() {
+        "bytes" | "byte" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((value * multiplier) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn parses_receiving_and_resolving_lines() {
+        let mut progress = TransferProgress::default();
+        assert!(apply_progress_line(
+            &mut progress,
+            "Receiving objects:  45% (450/1000), 1.50 MiB | 2.00 MiB/s"
+        ));
+        assert_eq!(progress.transferred_objects, 450);
+        assert_eq!(progress.total_objects, 1000);
+        assert_eq!(progress.transferred_bytes, 1_572_864);
+
+        assert!(apply_progress_line(
+            &mut progress,
+            "Resolving deltas: 100% (20/20), done."
+        ));
+        assert_eq!(progress.indexed_deltas, 20);
+        assert_eq!(progress.indexed_objects, 1000);
+    }
+
+    #[test]
+    fn parses_sideband_messages() {
+        let mut progress = TransferProgress::default();
+        assert!(apply_progress_line(
+            &mut progress,
+            "remote: Counting objects: 100% (3/3), done."
+        ));
+        assert_eq!(
+            progress.message.as_deref(),
+            Some("Counting objects: 100% (3/3), done.")
+        );
+    }
+
+    #[test]
+    fn keeps_non_progress_lines_out() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let control =
+            TransferControl::default().with_progress(move |p| sink.lock().unwrap().push(p.clone()));
+
+        let stderr = b"Writing objects:  50% (1/2)\rWriting objects: 100% (2/2), 250 bytes | 250.00 KiB/s, done.\nfatal: something went wrong\n";
+        let remaining = read_progress(&stderr[..], &control);
+
+        assert_eq!(
+            String::from_utf8(remaining).unwrap(),
+            "fatal: something went wrong\n"
+        );
+        let reported = reported.lock().unwrap();
+        assert_eq!(reported.len(), 2);
+        assert_eq!(reported[1].transferred_objects, 2);
+        assert_eq!(reported[1].transferred_bytes, 250);
+    }
+
+    #[test]
+    fn cancelled_control_does_not_spawn() {
+        let token = CancelToken::new();
+        token.cancel();
+        let control = TransferControl::new(token);
+        let mut cmd = Command::new("git");
+        cmd.arg("--version");
+        assert!(run_with_progress(&mut cmd, &control).unwrap().is_none());
+    }
+
+    #[test]
+    fn cancelling_kills_the_running_command() {
+        let token = CancelToken::new();
+        let canceller = token.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            canceller.cancel();
+        });
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "exec sleep 30"]);
+        let started = std::time::Instant::now();
+        assert!(run_cancellable(&mut cmd, &token).unwrap().is_none());
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
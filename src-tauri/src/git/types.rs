@@ -113,19 +113,143 @@ pub struct BranchInfo {
     pub is_remote: bool,
     pub remote_name: Option<String>,
     pub upstream: Option<String>,
+    /// Upstream is configured but its remote branch no longer exists.
+    pub upstream_gone: bool,
     pub ahead_count: u32,
     pub behind_count: u32,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeleteBranchOptions {
+    /// Delete even when commits are not merged into HEAD or the upstream.
+    pub force: bool,
+    /// Also delete the upstream branch on its remote.
+    pub delete_remote: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteBranchResult {
+    pub branch: String,
+    /// `false` when the branch was kept because it has unmerged commits.
+    pub deleted: bool,
+    pub remote_deleted: bool,
+    /// Commits reachable only from the branch (not from HEAD or its upstream).
+    pub unmerged_commits: Vec<CommitInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PruneCandidate {
+    pub name: String,
+    /// Fully merged into the base branch.
+    pub merged: bool,
+    pub upstream_gone: bool,
+}
+
+/// A local branch in a stack of dependent branches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackBranch {
+    pub name: String,
+    pub oid: String,
+    /// Commits on top of the branch below it, or of the base for the first.
+    pub commit_count: u32,
+    pub is_head: bool,
+    pub upstream: Option<String>,
+    pub ahead_count: u32,
+    pub behind_count: u32,
+}
+
+/// The branches stacked on `base`, bottom first. The checked out branch is
+/// always one of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchStack {
+    pub base: String,
+    pub branches: Vec<StackBranch>,
+}
+
+/// Outcome of pushing a stack. Pushing stops at the first branch that
+/// fails, so no branch goes to the remote without the ones below it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackPushResult {
+    /// Branches pushed, bottom first.
+    pub pushed: Vec<PushResult>,
+    pub failed: Option<StackPushFailure>,
+}
+
+/// The branch that stopped a stack push.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackPushFailure {
+    pub branch: String,
+    /// The remote's answer when it rejected the branch, `None` when the push
+    /// itself failed.
+    pub result: Option<PushResult>,
+    pub message: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemoteInfo {
     pub name: String,
     pub url: String,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchTagMode {
+    /// Tags pointing into fetched history (git's default).
+    #[default]
+    Auto,
+    All,
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FetchOptions {
+    pub prune: bool,
+    /// Fetch every configured remote instead of only the given one.
+    pub all_remotes: bool,
+    pub tags: FetchTagMode,
+    pub depth: Option<u32>,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            prune: true,
+            all_remotes: false,
+            tags: FetchTagMode::Auto,
+            depth: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchRefStatus {
+    Created,
+    FastForward,
+    Forced,
+    Pruned,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchRefUpdate {
+    /// Full local ref name (`refs/remotes/origin/main`, `refs/tags/v1.0`).
+    pub ref_name: String,
+    pub old_oid: Option<String>,
+    pub new_oid: Option<String>,
+    pub status: FetchRefStatus,
+    /// Commits reachable from `new_oid` but not from `old_oid`, or for a
+    /// created ref, not from any ref that existed before the fetch.
+    pub new_commits: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FetchResult {
     pub remote_name: String,
+    pub updates: Vec<FetchRefUpdate>,
+    /// Commits that were not in the repository before the fetch.
+    pub new_commits: u32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -135,10 +259,72 @@ pub enum PullOption {
     Rebase,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PushOptions {
+    /// `[+]<src>[:<dst>]`. When no target is given the current branch is pushed.
+    pub refspec: Option<String>,
+    /// Record the pushed branches' upstream even when one is already set.
+    pub set_upstream: bool,
+    /// Allow non-fast-forward updates as long as the remote ref still points
+    /// at the last-fetched remote-tracking oid.
+    pub force_with_lease: bool,
+    pub tag: Option<String>,
+    pub all_tags: bool,
+    pub delete_branch: Option<String>,
+    pub delete_tag: Option<String>,
+    /// Skip the `pre-push` hook, like `git push --no-verify`.
+    pub no_verify: bool,
+}
+
+impl PushOptions {
+    pub(crate) fn has_explicit_target(&self) -> bool {
+        self.refspec.is_some()
+            || self.tag.is_some()
+            || self.all_tags
+            || self.delete_branch.is_some()
+            || self.delete_tag.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushRefStatus {
+    Created,
+    Updated,
+    Forced,
+    Deleted,
+    UpToDate,
+    RejectedNonFastForward,
+    /// `--force-with-lease` の期待値とリモートの現在値が一致しなかった
+    RejectedStale,
+    Rejected,
+}
+
+impl PushRefStatus {
+    pub fn is_rejected(self) -> bool {
+        matches!(
+            self,
+            Self::RejectedNonFastForward | Self::RejectedStale | Self::Rejected
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushRefUpdate {
+    /// `None` when the remote ref is being deleted.
+    pub local_ref: Option<String>,
+    pub remote_ref: String,
+    pub status: PushRefStatus,
+    pub message: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PushResult {
     pub remote_name: String,
+    /// First pushed branch (short name), empty for tag-only or delete pushes.
     pub branch: String,
+    pub updates: Vec<PushRefUpdate>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -180,6 +366,22 @@ pub struct CommitInfo {
     pub parent_oids: Vec<String>,
     pub refs: Vec<CommitRef>,
     pub signature_status: SignatureStatus,
+    /// `Key: value` lines of the message's final paragraph, in order.
+    pub trailers: Vec<CommitTrailer>,
+    /// People named by `Co-authored-by` trailers.
+    pub co_authors: Vec<CoAuthor>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitTrailer {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoAuthor {
+    pub name: String,
+    pub email: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -253,6 +455,8 @@ pub struct CommitStats {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlameResult {
     pub path: String,
+    /// Revision that was blamed; `None` for HEAD.
+    pub commit_oid: Option<String>,
     pub lines: Vec<BlameLine>,
 }
 
@@ -264,10 +468,45 @@ pub struct BlameLine {
     pub commit_short_oid: String,
     pub author_name: String,
     pub author_date: i64,
+    /// First line of the message of the commit the line is attributed to.
+    pub summary: String,
     pub is_block_start: bool,
+    /// Path and line number of the line in the commit it is attributed to,
+    /// which differ from the blamed file after a rename, move or copy.
+    pub original_path: String,
+    pub original_line_number: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlameOptions {
+    /// Attribute lines moved within a file to their original commit (`-M`).
+    pub detect_moves: bool,
+    /// Attribute lines moved or copied from other files (`-C`).
+    pub detect_copies: bool,
+    /// Revisions whose changes are skipped, e.g. reformatting commits.
+    pub ignore_revs: Vec<String>,
+    /// File listing revisions to skip, relative to the working directory.
+    /// Defaults to `.git-blame-ignore-revs` when it exists, unless
+    /// `blame.ignoreRevsFile` is set to empty; the files that setting names
+    /// apply as well.
+    pub ignore_revs_file: Option<String>,
+    /// Use neither `.git-blame-ignore-revs` nor `blame.ignoreRevsFile`, for
+    /// a plain blame. `ignore_revs` and `ignore_revs_file` still apply.
+    pub no_default_ignore_revs: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileHistoryEntry {
+    #[serde(flatten)]
+    pub commit: CommitInfo,
+    /// Path of the file in this commit.
+    pub path: String,
+    /// Path before this commit when it renamed the file.
+    pub previous_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LogFilter {
     pub author: Option<String>,
     pub since: Option<i64>,
@@ -292,6 +531,51 @@ pub struct StashEntry {
     pub author_date: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashDetail {
+    pub entry: StashEntry,
+    pub oid: String,
+    /// Commit HEAD pointed at when the stash was made.
+    pub base_oid: String,
+    pub base_summary: String,
+    /// Changes that were staged, relative to the base commit.
+    pub index_changes: Vec<FileDiff>,
+    /// Changes that were not staged, relative to the staged state.
+    pub worktree_changes: Vec<FileDiff>,
+    /// Files stashed with `include_untracked` or `include_ignored`.
+    pub untracked_files: Vec<FileDiff>,
+}
+
+/// `completed: false` leaves the conflicts in the working tree, and a popped
+/// stash is kept, as `git stash apply` does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashApplyResult {
+    pub completed: bool,
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StashOptions {
+    /// Also stash untracked files (`--include-untracked`).
+    pub include_untracked: bool,
+    /// Also stash ignored files, and untracked ones with them (`--all`).
+    pub include_ignored: bool,
+    /// Leave staged changes in the index (`--keep-index`).
+    pub keep_index: bool,
+    /// Stash only changes under these paths; empty means every change.
+    pub paths: Vec<String>,
+    /// Stash only these unstaged hunks, leaving the index alone. Cannot be
+    /// combined with the options above.
+    pub hunks: Vec<StashHunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashHunk {
+    pub path: String,
+    pub hunk: HunkIdentifier,
+}
+
 // === Conflict types ===
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -329,7 +613,141 @@ pub struct TagInfo {
     pub is_annotated: bool,
     pub tagger_name: Option<String>,
     pub tagger_date: Option<i64>,
+    /// Message without the signature block.
     pub message: Option<String>,
+    /// Always `None` for lightweight and unsigned tags.
+    pub signature_status: SignatureStatus,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CreateTagOptions {
+    /// Revision to tag, peeled to a commit. Defaults to HEAD.
+    pub target: Option<String>,
+    /// Sign the tag with `user.signingKey` (`gpg.format` selects GPG or SSH).
+    /// Only annotated tags can be signed.
+    pub sign: bool,
+}
+
+// === Commit lint types ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitLintRule {
+    SubjectEmpty,
+    SubjectMaxLength,
+    /// Subject is not `type(scope): description`.
+    HeaderFormat,
+    TypeEnum,
+    ScopeEnum,
+    ScopeRequired,
+    BodyLeadingBlank,
+    BodyMaxLineLength,
+    TrailerRequired,
+    TicketReference,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitLintViolation {
+    pub rule: CommitLintRule,
+    pub severity: LintSeverity,
+    pub message: String,
+    /// 1-based line of the commit message the violation refers to.
+    pub line: Option<usize>,
+}
+
+/// Rules read from `.commitlint.toml` at the repository root. Rules without a
+/// value are not checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitLintConfig {
+    pub subject_max_length: Option<usize>,
+    /// Require Conventional Commits subjects. Implied by `types`, `scopes`
+    /// and `scope_required`.
+    pub conventional: bool,
+    pub types: Vec<String>,
+    pub scopes: Vec<String>,
+    pub scope_required: bool,
+    pub body_max_line_length: Option<usize>,
+    /// Trailer keys such as `Signed-off-by` that must be present.
+    pub required_trailers: Vec<String>,
+    /// Regular expression that must match somewhere in the message.
+    pub ticket_pattern: Option<String>,
+    /// Rules reported as warnings instead of blocking the commit.
+    pub warnings: Vec<CommitLintRule>,
+}
+
+// === Release notes types ===
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReleaseNotesOptions {
+    /// Exclusive start of the range, usually the previous release tag.
+    /// `None` covers the whole history of `to`.
+    pub from: Option<String>,
+    /// Inclusive end of the range. Defaults to HEAD.
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseNotes {
+    pub from: Option<String>,
+    pub to: String,
+    /// Conventional commit types in changelog order; commits that do not
+    /// follow the convention end up in an `other` section.
+    pub sections: Vec<ReleaseSection>,
+    pub breaking_changes: Vec<BreakingChange>,
+    /// Most commits first.
+    pub contributors: Vec<Contributor>,
+    /// Every PR or issue number referenced in the range, ascending.
+    pub references: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseSection {
+    pub kind: String,
+    pub title: String,
+    pub entries: Vec<ReleaseNoteEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseNoteEntry {
+    pub oid: String,
+    pub short_oid: String,
+    pub scope: Option<String>,
+    pub description: String,
+    pub breaking: bool,
+    pub references: Vec<u64>,
+    pub author_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakingChange {
+    pub short_oid: String,
+    pub scope: Option<String>,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contributor {
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleaseNotesFormat {
+    #[default]
+    Markdown,
+    Json,
 }
 
 // === Rebase types ===
@@ -342,9 +760,69 @@ pub enum RebaseAction {
     Edit,
     Squash,
     Fixup,
+    /// `fixup -C`: fold into the previous commit and keep this commit's
+    /// message instead, as autosquash does for `amend!` commits.
+    FixupMessage,
     Drop,
+    /// Run `argument` with `sh -c` in the working tree; the rebase stops
+    /// when it fails.
+    Exec,
+    /// Stop here and resume with `continue_rebase`.
+    Break,
+    /// Name the current commit `argument` for later `reset` and `merge` steps.
+    Label,
+    /// Move to the commit labelled, or named by, `argument`.
+    Reset,
+    /// Recreate the merge `oid` with the commits labelled in `argument`.
+    Merge,
+    /// Point the ref `argument` at the current commit when the rebase ends.
+    UpdateRef,
 }
 
+/// Kind of commit `create_fixup_commit` writes, named after its subject
+/// prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixupKind {
+    /// `fixup!`: the changes are folded in, the target's message is kept.
+    Fixup,
+    /// `squash!`: the changes are folded in and the messages combined.
+    Squash,
+    /// `amend!`: the changes are folded in and the message replaced.
+    Amend,
+}
+
+/// A change to one commit on the current branch; the commits after it are
+/// replayed on top.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CommitEdit {
+    Reword {
+        oid: String,
+        message: String,
+    },
+    Drop {
+        oid: String,
+    },
+    /// Swap the commit with its neighbour in `direction`.
+    Move {
+        oid: String,
+        direction: MoveDirection,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveDirection {
+    /// Towards `HEAD`, i.e. up in the log.
+    Up,
+    /// Towards the root.
+    Down,
+}
+
+/// One line of a rebase todo. Steps that are not about a commit (`exec`,
+/// `break`, `label`, `reset`, `update-ref`) leave `oid` empty and carry
+/// their command, label or ref in `argument`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RebaseTodoEntry {
     pub action: RebaseAction,
@@ -352,6 +830,30 @@ pub struct RebaseTodoEntry {
     pub short_oid: String,
     pub message: String,
     pub author_name: String,
+    #[serde(default)]
+    pub argument: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RebaseOptions {
+    /// Recreate merge commits with `label`, `reset` and `merge` steps, like
+    /// `--rebase-merges`, instead of flattening the history.
+    pub rebase_merges: bool,
+    /// Shell command run after each commit, like `--exec`.
+    pub exec: Option<String>,
+}
+
+/// Why a rebase in progress is waiting for the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RebaseStopReason {
+    Conflict,
+    /// Stopped after an `edit` step so the commit can be amended.
+    Edit,
+    Break,
+    /// An `exec` command exited with an error.
+    ExecFailed,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -361,6 +863,31 @@ pub struct RebaseState {
     pub current_step: usize,
     pub total_steps: usize,
     pub has_conflicts: bool,
+    /// Branch being rebased, `None` when `HEAD` was detached.
+    pub head_name: Option<String>,
+    pub stop_reason: Option<RebaseStopReason>,
+    /// The `exec` step the rebase stopped at, when it stopped on one.
+    pub exec_failure: Option<RebaseExecFailure>,
+}
+
+/// A failed `exec` step, with the output the command printed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RebaseExecFailure {
+    pub command: String,
+    /// `None` when the command was killed by a signal.
+    pub exit_code: Option<i32>,
+    /// Whether the command was killed because the rebase was cancelled.
+    pub cancelled: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Reported as each step of a rebase starts. `step` counts from 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebaseProgress {
+    pub step: usize,
+    pub total: usize,
+    pub entry: RebaseTodoEntry,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -369,6 +896,98 @@ pub struct RebaseResult {
     pub conflicts: Vec<String>,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AbsorbOptions {
+    /// Start of the range whose commits can receive fixups. Defaults to the
+    /// current branch's upstream.
+    pub base: Option<String>,
+    /// Squash the fixups into their targets once they are created. Skipped
+    /// while the working tree has other changes.
+    pub autosquash: bool,
+    /// Only work out where the hunks would go.
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbsorbSkipReason {
+    /// The file does not exist at HEAD, or was renamed or deleted.
+    NotModified,
+    /// No text hunks, e.g. a binary or mode-only change.
+    NoHunks,
+    /// The lines come from commits older than the range.
+    OutsideRange,
+    /// The lines come from more than one commit in the range.
+    Ambiguous,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbsorbFixup {
+    pub target_oid: String,
+    pub target_summary: String,
+    /// The fixup commit; `None` on a dry run.
+    pub oid: Option<String>,
+    pub hunks: Vec<AbsorbHunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbsorbHunk {
+    pub path: String,
+    pub hunk: HunkIdentifier,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbsorbSkipped {
+    pub path: String,
+    pub hunk: Option<HunkIdentifier>,
+    pub reason: AbsorbSkipReason,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbsorbResult {
+    /// In branch order, oldest target first.
+    pub fixups: Vec<AbsorbFixup>,
+    /// Hunks left staged.
+    pub skipped: Vec<AbsorbSkipped>,
+    /// Outcome of the autosquash rebase, when one ran.
+    pub rebase: Option<RebaseResult>,
+}
+
+/// One of the commits a commit is split into. The selections refer to the
+/// original commit's diff against its parent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SplitCommitGroup {
+    pub message: String,
+    /// Files taken whole; the only way to move added, deleted, renamed or
+    /// binary files.
+    pub files: Vec<String>,
+    pub hunks: Vec<SplitHunk>,
+    pub lines: Vec<SplitLines>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitHunk {
+    pub path: String,
+    pub hunk: HunkIdentifier,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitLines {
+    pub path: String,
+    pub range: LineRange,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitCommitResult {
+    /// The new commits in order. Changes no group selected end up in a last
+    /// commit that keeps the original message.
+    pub commits: Vec<String>,
+    /// Outcome of replaying the descendants on top of the new commits.
+    pub rebase: RebaseResult,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MergeBaseContent {
     pub path: String,
@@ -409,6 +1028,55 @@ pub struct ResetResult {
     pub oid: String,
 }
 
+// === Operation journal types ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    Reset,
+    Discard,
+    DeleteBranch,
+    StashDrop,
+    Rebase,
+    Amend,
+}
+
+/// A ref as it was before the operation. `oid: None` means it did not exist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefState {
+    pub name: String,
+    pub oid: Option<String>,
+}
+
+/// Working tree content of a file before the operation, stored as a blob.
+/// `blob: None` means the file did not exist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileState {
+    pub path: String,
+    pub blob: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StashState {
+    pub oid: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationEntry {
+    pub id: u64,
+    pub kind: OperationKind,
+    pub description: String,
+    pub timestamp: i64,
+    pub refs: Vec<RefState>,
+    /// Tree written from the index before the operation.
+    pub index_tree: Option<String>,
+    pub files: Vec<FileState>,
+    pub stash: Option<StashState>,
+    /// The operation rewrote the working tree, so undo resets it as well.
+    pub restore_worktree: bool,
+}
+
 // === Reflog types ===
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -455,6 +1123,95 @@ pub struct GitConfigEntry {
     pub value: String,
 }
 
+// === Identity types ===
+
+/// Author or committer fields that replace the configured identity for one
+/// commit. Unset fields fall back to `user.name`, `user.email` and the
+/// current time.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SignatureOverride {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub time: Option<i64>,
+    /// Offset from UTC. Defaults to the local offset.
+    pub offset_minutes: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitIdentity {
+    pub author: Option<SignatureOverride>,
+    pub committer: Option<SignatureOverride>,
+}
+
+impl CommitIdentity {
+    pub fn is_empty(&self) -> bool {
+        self.author.is_none() && self.committer.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningFormat {
+    #[default]
+    Openpgp,
+    Ssh,
+    X509,
+}
+
+impl SigningFormat {
+    /// Value of `gpg.format`.
+    pub fn as_config_value(self) -> &'static str {
+        match self {
+            SigningFormat::Openpgp => "openpgp",
+            SigningFormat::Ssh => "ssh",
+            SigningFormat::X509 => "x509",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdentityProfile {
+    /// Label shown in the UI, unique among profiles.
+    pub label: String,
+    pub name: String,
+    pub email: String,
+    #[serde(default)]
+    pub signing_key: Option<String>,
+    #[serde(default)]
+    pub signing_format: Option<SigningFormat>,
+    /// Globs matched against the repository's remote URLs.
+    #[serde(default)]
+    pub remote_patterns: Vec<String>,
+    /// Globs matched against the repository path. A leading `~` is the home
+    /// directory.
+    #[serde(default)]
+    pub path_patterns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentityMismatchAction {
+    #[default]
+    Warn,
+    Block,
+}
+
+/// The repository's identity compared with the profile selected for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityCheck {
+    pub profile: Option<IdentityProfile>,
+    /// Effective `user.name`, from every config scope git reads.
+    pub name: Option<String>,
+    /// Effective `user.email`.
+    pub email: Option<String>,
+    /// `false` when a profile applies and its email differs from `email`.
+    pub matches: bool,
+    pub action: IdentityMismatchAction,
+}
+
 // === Signature types ===
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
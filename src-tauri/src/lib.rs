@@ -34,9 +34,12 @@ fn resolve_repo_path() -> Option<std::path::PathBuf> {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let repo_path = resolve_repo_path();
-    let repo = repo_path
-        .as_ref()
-        .and_then(|path| git::dispatcher::GitDispatcher::open_default(path).ok());
+    let repo = repo_path.as_ref().and_then(|path| {
+        let kind = config::load_config()
+            .map(|cfg| cfg.backend.kind_for(&path.to_string_lossy()))
+            .unwrap_or_default();
+        git::dispatcher::GitDispatcher::open(path, kind).ok()
+    });
 
     let default_tab_id = DEFAULT_TAB_ID.to_string();
     let mut tabs = HashMap::new();
@@ -79,6 +82,8 @@ pub fn run() {
             tabs: Mutex::new(tabs),
             active_tab: Mutex::new(active_tab),
             auto_fetch_handle: Mutex::new(None),
+            transfers: Mutex::new(HashMap::new()),
+            rebases: Mutex::new(HashMap::new()),
         })
         .setup(move |app| {
             if cfg!(debug_assertions) {
@@ -132,6 +137,7 @@ pub fn run() {
             commands::git::stage_all,
             commands::git::unstage_all,
             commands::git::commit,
+            commands::git::lint_commit_message,
             commands::git::get_current_branch,
             commands::git::stage_hunk,
             commands::git::unstage_hunk,
@@ -142,16 +148,26 @@ pub fn run() {
             commands::git::get_head_commit_message,
             commands::config::get_config,
             commands::config::save_config,
+            commands::config::get_co_authors,
+            commands::config::add_co_author,
+            commands::config::remove_co_author,
             commands::branch::list_branches,
             commands::branch::create_branch,
             commands::branch::checkout_branch,
             commands::branch::delete_branch,
+            commands::branch::list_prune_candidates,
+            commands::branch::prune_branches,
             commands::branch::rename_branch,
+            commands::branch::set_branch_upstream,
+            commands::branch::checkout_remote_branch,
             commands::branch::merge_branch,
             commands::branch::get_branch_commits,
+            commands::branch::list_stack,
+            commands::branch::push_stack,
             commands::remote::fetch_remote,
             commands::remote::pull_remote,
             commands::remote::push_remote,
+            commands::remote::cancel_remote_operation,
             commands::remote::list_remotes,
             commands::remote::add_remote,
             commands::remote::remove_remote,
@@ -160,17 +176,24 @@ pub fn run() {
             commands::history::get_commit_detail,
             commands::history::get_commit_file_diff,
             commands::history::get_blame,
+            commands::history::get_blame_before,
             commands::history::get_file_history,
+            commands::history::write_commit_graph,
             commands::stash::stash_save,
             commands::stash::list_stashes,
             commands::stash::apply_stash,
             commands::stash::pop_stash,
             commands::stash::drop_stash,
+            commands::stash::stash_branch,
             commands::stash::get_stash_diff,
+            commands::stash::get_stash_detail,
+            commands::stash::rename_stash,
             commands::tag::list_tags,
             commands::tag::create_tag,
             commands::tag::delete_tag,
             commands::tag::checkout_tag,
+            commands::tag::get_release_notes,
+            commands::tag::render_release_notes,
             commands::conflict::get_conflict_files,
             commands::conflict::resolve_conflict,
             commands::conflict::resolve_conflict_block,
@@ -183,8 +206,15 @@ pub fn run() {
             commands::rebase::is_rebasing,
             commands::rebase::abort_rebase,
             commands::rebase::continue_rebase,
+            commands::rebase::cancel_rebase,
             commands::rebase::get_rebase_state,
             commands::rebase::get_rebase_todo,
+            commands::rebase::create_fixup_commit,
+            commands::rebase::absorb,
+            commands::rebase::split_commit,
+            commands::rebase::reword_commit,
+            commands::rebase::drop_commit,
+            commands::rebase::move_commit,
             commands::rebase::get_merge_base_content,
             commands::cherry_pick::cherry_pick,
             commands::cherry_pick::is_cherry_picking,
@@ -196,6 +226,8 @@ pub fn run() {
             commands::revert::continue_revert,
             commands::reset::reset,
             commands::reset::reset_file,
+            commands::reset::list_operations,
+            commands::reset::undo_last_operation,
             commands::reset::get_reflog,
             commands::ai::detect_cli_adapters,
             commands::ai::generate_commit_message,
@@ -226,6 +258,8 @@ pub fn run() {
             commands::gitconfig::set_gitconfig_value,
             commands::gitconfig::unset_gitconfig_value,
             commands::gitconfig::get_gitconfig_path,
+            commands::identity::get_identity_check,
+            commands::identity::apply_identity_profile,
             commands::repo::open_repository,
             commands::repo::init_repository,
             commands::repo::get_recent_repos,
@@ -3,6 +3,7 @@ use std::path::Path;
 use std::sync::Mutex;
 
 use crate::git::backend::GitBackend;
+use crate::git::transfer::CancelToken;
 
 pub const DEFAULT_TAB_ID: &str = "default";
 
@@ -19,6 +20,10 @@ pub struct AppState {
     pub tabs: Mutex<HashMap<TabId, RepoContext>>,
     pub active_tab: Mutex<Option<TabId>>,
     pub auto_fetch_handle: Mutex<Option<Box<dyn std::any::Any + Send>>>,
+    /// Cancel tokens of the network operations currently running per tab.
+    pub transfers: Mutex<HashMap<TabId, CancelToken>>,
+    /// Cancel tokens of the rebases currently running per tab.
+    pub rebases: Mutex<HashMap<TabId, CancelToken>>,
 }
 
 pub fn repo_name_from_path(path: &str) -> String {
@@ -0,0 +1,6 @@
+use app_lib::git::cli_backend::CliBackend;
+
+type TestBackend = CliBackend;
+
+#[path = "common/backend_suite.rs"]
+mod backend_suite;
@@ -0,0 +1,6008 @@
+//! Integration tests shared by every `GitBackend` implementation.
+//!
+//! Each `*_backend_test.rs` defines `TestBackend` and includes this module.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use app_lib::git::absorb;
+use app_lib::git::backend::GitBackend;
+use app_lib::git::error::GitError;
+use app_lib::git::hooks::HookError;
+use app_lib::git::identity;
+use app_lib::git::rebase::{self, RebaseControl};
+use app_lib::git::split;
+use app_lib::git::stack;
+use app_lib::git::trailers;
+use app_lib::git::transfer::{CancelToken, TransferControl};
+use app_lib::git::types::{
+    AbsorbOptions, AbsorbSkipReason, BlameOptions, CherryPickMode, CoAuthor, CommitEdit,
+    CommitIdentity, CommitInfo, CommitTrailer, ConflictResolution, CreateTagOptions,
+    DeleteBranchOptions, DeleteBranchResult, DiffLineKind, DiffOptions, FetchOptions,
+    FetchRefStatus, FetchResult, FetchTagMode, FixupKind, GitConfigScope, HunkIdentifier,
+    IdentityMismatchAction, IdentityProfile, LineRange, LogFilter, MergeOption, MoveDirection,
+    OperationKind, PullOption, PushOptions, PushRefStatus, PushResult, RebaseAction, RebaseOptions,
+    RebaseStopReason, ReleaseNotesOptions, ResetMode, RevertMode, SignatureOverride,
+    SignatureStatus, SigningFormat, SplitCommitGroup, SplitHunk, SplitLines, StagingState,
+    StashHunk, StashOptions,
+};
+
+use super::TestBackend;
+
+fn init_test_repo(dir: &Path) {
+    Command::new("git")
+        .args(["init"])
+        .current_dir(dir)
+        .output()
+        .expect("git init failed");
+
+    Command::new("git")
+        .args(["config", "user.email", "test@example.com"])
+        .current_dir(dir)
+        .output()
+        .expect("git config email failed");
+
+    Command::new("git")
+        .args(["config", "user.name", "Test User"])
+        .current_dir(dir)
+        .output()
+        .expect("git config name failed");
+}
+
+fn create_file_with_multiple_changed_lines(dir: &Path, backend: &TestBackend) {
+    fs::write(dir.join("lines.txt"), "line1\nline2\nline3\nline4\nline5\n").unwrap();
+    backend.stage(Path::new("lines.txt")).unwrap();
+    backend.commit("add lines", false, false, false).unwrap();
+    fs::write(
+        dir.join("lines.txt"),
+        "line1\nchanged2\nline3\nchanged4\nline5\nnew6\n",
+    )
+    .unwrap();
+}
+
+fn create_file_with_two_hunks(dir: &Path, backend: &TestBackend) {
+    let mut content = String::new();
+    for i in 1..=20 {
+        content.push_str(&format!("line{i}\n"));
+    }
+    fs::write(dir.join("twohunk.txt"), &content).unwrap();
+    backend.stage(Path::new("twohunk.txt")).unwrap();
+    backend.commit("add twohunk", false, false, false).unwrap();
+    let mut modified = String::new();
+    for i in 1..=20 {
+        if i == 2 {
+            modified.push_str("modified2\n");
+        } else if i == 18 {
+            modified.push_str("modified18\n");
+        } else {
+            modified.push_str(&format!("line{i}\n"));
+        }
+    }
+    fs::write(dir.join("twohunk.txt"), &modified).unwrap();
+}
+
+fn init_repo_with_commit(dir: &Path) -> TestBackend {
+    init_test_repo(dir);
+    fs::write(dir.join("init.txt"), "init").unwrap();
+    let backend = TestBackend::open(dir).unwrap();
+    backend.stage(Path::new("init.txt")).unwrap();
+    backend
+        .commit("initial commit", false, false, false)
+        .unwrap();
+    backend
+}
+
+/// Run `git args` in `dir` and return its stdout, failing the test if git fails.
+fn git_output(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {args:?} failed");
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn run_git(dir: &Path, args: &[&str]) {
+    git_output(dir, args);
+}
+
+/// Write `content` to `file`, stage it and commit it with `message`.
+fn commit_content(dir: &Path, backend: &TestBackend, file: &str, content: &str, message: &str) {
+    fs::write(dir.join(file), content).unwrap();
+    backend.stage(Path::new(file)).unwrap();
+    backend.commit(message, false, false, false).unwrap();
+}
+
+/// Commit `file`, holding its own name, with the name as the message.
+fn commit_file(dir: &Path, backend: &TestBackend, file: &str) {
+    commit_content(dir, backend, file, file, file);
+}
+
+/// Subjects of the commits in `base..tip`, newest first.
+fn subjects_since(dir: &Path, base: &str, tip: &str) -> Vec<String> {
+    git_output(dir, &["log", "--format=%s", &format!("{base}..{tip}")])
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// `dir/local` with one commit and `origin` pointing at the bare `dir/remote.git`.
+fn init_repo_with_bare_remote(dir: &Path) -> TestBackend {
+    let remote = dir.join("remote.git");
+    Command::new("git")
+        .args(["init", "--bare", &remote.to_string_lossy()])
+        .output()
+        .expect("git init --bare failed");
+
+    let local = dir.join("local");
+    fs::create_dir(&local).unwrap();
+    let backend = init_repo_with_commit(&local);
+    let url = format!("file://{}", remote.display());
+    backend.add_remote("origin", &url).unwrap();
+    backend
+}
+
+fn push_default(backend: &TestBackend) -> PushResult {
+    backend
+        .push(
+            "origin",
+            &PushOptions::default(),
+            &TransferControl::default(),
+        )
+        .unwrap()
+}
+
+/// Push a new commit adding `file` to `dir/remote.git` from a separate clone.
+fn commit_from_other_clone(dir: &Path, file: &str) {
+    let other = dir.join("other");
+    if !other.exists() {
+        Command::new("git")
+            .args([
+                "clone",
+                "-q",
+                &dir.join("remote.git").to_string_lossy(),
+                &other.to_string_lossy(),
+            ])
+            .output()
+            .expect("git clone failed");
+    }
+    fs::write(other.join(file), file).unwrap();
+    for args in [
+        vec!["add", file],
+        vec![
+            "-c",
+            "user.name=Other",
+            "-c",
+            "user.email=other@example.com",
+            "commit",
+            "-q",
+            "-m",
+            "remote commit",
+        ],
+        vec!["push", "-q", "origin", "HEAD"],
+    ] {
+        Command::new("git")
+            .args(&args)
+            .current_dir(&other)
+            .output()
+            .expect("git command failed");
+    }
+}
+
+fn remote_ref_oid(dir: &Path, name: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--verify", "-q", name])
+        .current_dir(dir.join("remote.git"))
+        .output()
+        .unwrap();
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn counting_control() -> (
+    TransferControl,
+    std::sync::Arc<std::sync::atomic::AtomicUsize>,
+) {
+    let updates = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = std::sync::Arc::clone(&updates);
+    let control = TransferControl::default().with_progress(move |_| {
+        counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    });
+    (control, updates)
+}
+
+fn cancelled_control() -> TransferControl {
+    let token = CancelToken::new();
+    token.cancel();
+    TransferControl::new(token)
+}
+
+#[test]
+fn status_empty_repo() {
+    let tmp = tempfile::tempdir().unwrap();
+    init_test_repo(tmp.path());
+
+    let backend = TestBackend::open(tmp.path()).unwrap();
+    let status = backend.status().unwrap();
+
+    assert!(status.files.is_empty());
+}
+
+#[test]
+fn status_shows_untracked_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    init_test_repo(tmp.path());
+
+    fs::write(tmp.path().join("hello.txt"), "hello").unwrap();
+
+    let backend = TestBackend::open(tmp.path()).unwrap();
+    let status = backend.status().unwrap();
+
+    assert_eq!(status.files.len(), 1);
+    assert_eq!(status.files[0].path, "hello.txt");
+    assert_eq!(
+        status.files[0].staging,
+        app_lib::git::types::StagingState::Unstaged
+    );
+}
+
+#[test]
+fn stage_and_unstage_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    init_test_repo(tmp.path());
+
+    fs::write(tmp.path().join("a.txt"), "content").unwrap();
+
+    let backend = TestBackend::open(tmp.path()).unwrap();
+
+    // Stage
+    backend.stage(Path::new("a.txt")).unwrap();
+    let status = backend.status().unwrap();
+    assert!(status
+        .files
+        .iter()
+        .any(|f| f.path == "a.txt" && f.staging == app_lib::git::types::StagingState::Staged));
+
+    // Unstage
+    backend.unstage(Path::new("a.txt")).unwrap();
+    let status = backend.status().unwrap();
+    assert!(status
+        .files
+        .iter()
+        .all(|f| f.staging == app_lib::git::types::StagingState::Unstaged));
+}
+
+#[test]
+fn stage_all_and_unstage_all() {
+    let tmp = tempfile::tempdir().unwrap();
+    init_test_repo(tmp.path());
+
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    fs::write(tmp.path().join("b.txt"), "b").unwrap();
+
+    let backend = TestBackend::open(tmp.path()).unwrap();
+
+    backend.stage_all().unwrap();
+    let status = backend.status().unwrap();
+    assert!(status
+        .files
+        .iter()
+        .all(|f| f.staging == app_lib::git::types::StagingState::Staged));
+
+    backend.unstage_all().unwrap();
+    let status = backend.status().unwrap();
+    assert!(status
+        .files
+        .iter()
+        .all(|f| f.staging == app_lib::git::types::StagingState::Unstaged));
+}
+
+#[test]
+fn commit_creates_oid() {
+    let tmp = tempfile::tempdir().unwrap();
+    init_test_repo(tmp.path());
+
+    fs::write(tmp.path().join("file.txt"), "data").unwrap();
+
+    let backend = TestBackend::open(tmp.path()).unwrap();
+    backend.stage(Path::new("file.txt")).unwrap();
+
+    let result = backend
+        .commit("initial commit", false, false, false)
+        .unwrap();
+    assert!(!result.oid.is_empty());
+}
+
+#[test]
+fn current_branch_after_commit() {
+    let tmp = tempfile::tempdir().unwrap();
+    init_test_repo(tmp.path());
+
+    fs::write(tmp.path().join("file.txt"), "data").unwrap();
+
+    let backend = TestBackend::open(tmp.path()).unwrap();
+    backend.stage(Path::new("file.txt")).unwrap();
+    backend.commit("init", false, false, false).unwrap();
+
+    let branch = backend.current_branch().unwrap();
+    assert!(!branch.is_empty());
+}
+
+#[test]
+fn diff_unstaged_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    init_test_repo(tmp.path());
+
+    fs::write(tmp.path().join("file.txt"), "line1\n").unwrap();
+
+    let backend = TestBackend::open(tmp.path()).unwrap();
+    backend.stage(Path::new("file.txt")).unwrap();
+    backend.commit("init", false, false, false).unwrap();
+
+    fs::write(tmp.path().join("file.txt"), "line1\nline2\n").unwrap();
+
+    let options = DiffOptions {
+        staged: false,
+        ..Default::default()
+    };
+    let diffs = backend.diff(Some(Path::new("file.txt")), &options).unwrap();
+
+    assert!(!diffs.is_empty());
+    assert!(!diffs[0].hunks.is_empty());
+}
+
+#[test]
+fn diff_staged_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    init_test_repo(tmp.path());
+
+    fs::write(tmp.path().join("file.txt"), "original\n").unwrap();
+
+    let backend = TestBackend::open(tmp.path()).unwrap();
+    backend.stage(Path::new("file.txt")).unwrap();
+    backend.commit("init", false, false, false).unwrap();
+
+    fs::write(tmp.path().join("file.txt"), "modified\n").unwrap();
+    backend.stage(Path::new("file.txt")).unwrap();
+
+    let options = DiffOptions {
+        staged: true,
+        ..Default::default()
+    };
+    let diffs = backend.diff(Some(Path::new("file.txt")), &options).unwrap();
+
+    assert!(!diffs.is_empty());
+}
+
+#[test]
+fn open_nonexistent_path_returns_error() {
+    let result = TestBackend::open("/nonexistent/path/to/repo");
+    assert!(result.is_err());
+}
+
+#[test]
+fn list_branches_returns_default_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let branches = backend.list_branches().unwrap();
+
+    assert!(!branches.is_empty());
+    assert!(branches.iter().any(|b| b.is_head));
+}
+
+#[test]
+fn create_and_list_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    backend.create_branch("feature").unwrap();
+    let branches = backend.list_branches().unwrap();
+
+    assert!(branches.iter().any(|b| b.name == "feature"));
+}
+
+#[test]
+fn checkout_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend.create_branch("feature").unwrap();
+
+    backend.checkout_branch("feature").unwrap();
+    let current = backend.current_branch().unwrap();
+
+    assert_eq!(current, "feature");
+}
+
+#[test]
+fn delete_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend.create_branch("to-delete").unwrap();
+
+    let result = delete_branch_default(&backend, "to-delete");
+    assert!(result.deleted);
+    assert!(result.unmerged_commits.is_empty());
+    let branches = backend.list_branches().unwrap();
+
+    assert!(!branches.iter().any(|b| b.name == "to-delete"));
+}
+
+#[test]
+fn rename_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend.create_branch("old-name").unwrap();
+
+    backend.rename_branch("old-name", "new-name").unwrap();
+    let branches = backend.list_branches().unwrap();
+
+    assert!(!branches.iter().any(|b| b.name == "old-name"));
+    assert!(branches.iter().any(|b| b.name == "new-name"));
+}
+
+/// Push HEAD to `origin/<name>` so that a remote-tracking branch exists.
+fn push_remote_branch(backend: &TestBackend, name: &str) {
+    let options = PushOptions {
+        refspec: Some(format!("HEAD:refs/heads/{name}")),
+        ..PushOptions::default()
+    };
+    backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    fetch_default(backend);
+}
+
+fn upstream_of(backend: &TestBackend, name: &str) -> Option<String> {
+    backend
+        .list_branches()
+        .unwrap()
+        .into_iter()
+        .find(|b| !b.is_remote && b.name == name)
+        .and_then(|b| b.upstream)
+}
+
+#[test]
+fn create_branch_from_tag_does_not_track() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend
+        .create_tag("v1.0", None, &CreateTagOptions::default())
+        .unwrap();
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    backend.commit("second", false, false, false).unwrap();
+
+    backend.create_branch_from("from-tag", "v1.0").unwrap();
+
+    let commits = backend.get_branch_commits("from-tag", 10).unwrap();
+    assert_eq!(commits.len(), 1);
+    assert_eq!(upstream_of(&backend, "from-tag"), None);
+}
+
+#[test]
+fn create_branch_from_remote_branch_tracks_it() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_remote_branch(&backend, "feature");
+
+    backend
+        .create_branch_from("review", "origin/feature")
+        .unwrap();
+
+    assert_eq!(
+        upstream_of(&backend, "review").as_deref(),
+        Some("origin/feature")
+    );
+}
+
+#[test]
+fn create_branch_from_unknown_start_point_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    assert!(backend.create_branch_from("x", "no-such-rev").is_err());
+    assert!(!backend
+        .list_branches()
+        .unwrap()
+        .iter()
+        .any(|b| b.name == "x"));
+}
+
+#[test]
+fn set_change_and_unset_upstream() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_remote_branch(&backend, "one");
+    push_remote_branch(&backend, "two");
+    backend.create_branch("topic").unwrap();
+
+    backend.set_upstream("topic", Some("origin/one")).unwrap();
+    assert_eq!(
+        upstream_of(&backend, "topic").as_deref(),
+        Some("origin/one")
+    );
+
+    backend
+        .set_upstream("topic", Some("refs/remotes/origin/two"))
+        .unwrap();
+    assert_eq!(
+        upstream_of(&backend, "topic").as_deref(),
+        Some("origin/two")
+    );
+
+    backend.set_upstream("topic", None).unwrap();
+    assert_eq!(upstream_of(&backend, "topic"), None);
+    // Unsetting again is a no-op
+    backend.set_upstream("topic", None).unwrap();
+
+    assert!(backend.set_upstream("missing", None).is_err());
+    assert!(backend.set_upstream("topic", Some("origin/none")).is_err());
+}
+
+#[test]
+fn checkout_remote_branch_creates_tracking_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    let default_branch = backend.current_branch().unwrap();
+    push_remote_branch(&backend, "colleague/fix");
+
+    let local = backend
+        .checkout_remote_branch("origin/colleague/fix", None)
+        .unwrap();
+
+    assert_eq!(local, "colleague/fix");
+    assert_eq!(backend.current_branch().unwrap(), "colleague/fix");
+    assert_eq!(
+        upstream_of(&backend, "colleague/fix").as_deref(),
+        Some("origin/colleague/fix")
+    );
+
+    // Checking it out again reuses the tracking branch
+    backend.checkout_branch(&default_branch).unwrap();
+    let again = backend
+        .checkout_remote_branch("refs/remotes/origin/colleague/fix", None)
+        .unwrap();
+    assert_eq!(again, "colleague/fix");
+    assert_eq!(backend.current_branch().unwrap(), "colleague/fix");
+}
+
+#[test]
+fn checkout_remote_branch_with_local_name() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_remote_branch(&backend, "feature");
+
+    let local = backend
+        .checkout_remote_branch("origin/feature", Some("review-feature"))
+        .unwrap();
+
+    assert_eq!(local, "review-feature");
+    assert_eq!(backend.current_branch().unwrap(), "review-feature");
+    assert_eq!(
+        upstream_of(&backend, "review-feature").as_deref(),
+        Some("origin/feature")
+    );
+}
+
+#[test]
+fn checkout_remote_branch_rejects_unrelated_local_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_remote_branch(&backend, "feature");
+    backend.create_branch("feature").unwrap();
+    let before = backend.current_branch().unwrap();
+
+    assert!(backend
+        .checkout_remote_branch("origin/feature", None)
+        .is_err());
+    assert!(backend.checkout_remote_branch("feature", None).is_err());
+    assert_eq!(backend.current_branch().unwrap(), before);
+}
+
+#[test]
+fn merge_fast_forward() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let default_branch = backend.current_branch().unwrap();
+
+    backend.create_branch("feature").unwrap();
+    backend.checkout_branch("feature").unwrap();
+
+    fs::write(tmp.path().join("feature.txt"), "feature work").unwrap();
+    backend.stage(Path::new("feature.txt")).unwrap();
+    backend
+        .commit("feature commit", false, false, false)
+        .unwrap();
+
+    backend.checkout_branch(&default_branch).unwrap();
+
+    let result = backend
+        .merge_branch("feature", MergeOption::Default)
+        .unwrap();
+    assert_eq!(result.kind, app_lib::git::types::MergeKind::FastForward);
+    assert!(result.oid.is_some());
+}
+
+#[test]
+fn merge_up_to_date() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend.create_branch("feature").unwrap();
+
+    let result = backend
+        .merge_branch("feature", MergeOption::Default)
+        .unwrap();
+    assert_eq!(result.kind, app_lib::git::types::MergeKind::UpToDate);
+    assert!(result.oid.is_none());
+}
+
+#[test]
+fn create_duplicate_branch_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend.create_branch("dup").unwrap();
+
+    let result = backend.create_branch("dup");
+    assert!(result.is_err());
+}
+
+#[test]
+fn delete_nonexistent_branch_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let result = backend.delete_branch(
+        "nonexistent",
+        &DeleteBranchOptions::default(),
+        &TransferControl::default(),
+    );
+    assert!(result.is_err());
+}
+
+fn delete_branch_default(backend: &TestBackend, name: &str) -> DeleteBranchResult {
+    backend
+        .delete_branch(
+            name,
+            &DeleteBranchOptions::default(),
+            &TransferControl::default(),
+        )
+        .unwrap()
+}
+
+/// Commit `file` on `branch` and switch back to the previous branch.
+fn commit_on_branch(dir: &Path, backend: &TestBackend, branch: &str, file: &str) {
+    let previous = backend.current_branch().unwrap();
+    backend.checkout_branch(branch).unwrap();
+    commit_content(dir, backend, file, file, &format!("add {file}"));
+    backend.checkout_branch(&previous).unwrap();
+}
+
+#[test]
+fn delete_unmerged_branch_is_refused_and_reports_commits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend.create_branch("topic").unwrap();
+    commit_on_branch(tmp.path(), &backend, "topic", "a.txt");
+    commit_on_branch(tmp.path(), &backend, "topic", "b.txt");
+
+    let result = delete_branch_default(&backend, "topic");
+
+    assert!(!result.deleted);
+    let messages: Vec<&str> = result
+        .unmerged_commits
+        .iter()
+        .map(|c| c.message.as_str())
+        .collect();
+    assert_eq!(messages, vec!["add b.txt", "add a.txt"]);
+    assert!(backend
+        .list_branches()
+        .unwrap()
+        .iter()
+        .any(|b| b.name == "topic"));
+}
+
+#[test]
+fn force_delete_unmerged_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend.create_branch("topic").unwrap();
+    commit_on_branch(tmp.path(), &backend, "topic", "a.txt");
+
+    let options = DeleteBranchOptions {
+        force: true,
+        ..DeleteBranchOptions::default()
+    };
+    let result = backend
+        .delete_branch("topic", &options, &TransferControl::default())
+        .unwrap();
+
+    assert!(result.deleted);
+    assert_eq!(result.unmerged_commits.len(), 1);
+    assert!(!backend
+        .list_branches()
+        .unwrap()
+        .iter()
+        .any(|b| b.name == "topic"));
+}
+
+#[test]
+fn delete_checked_out_branch_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let current = backend.current_branch().unwrap();
+
+    let options = DeleteBranchOptions {
+        force: true,
+        ..DeleteBranchOptions::default()
+    };
+    assert!(backend
+        .delete_branch(&current, &options, &TransferControl::default())
+        .is_err());
+}
+
+#[test]
+fn delete_branch_merged_into_upstream_and_remote() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    let main = backend.current_branch().unwrap();
+    push_default(&backend);
+    backend.create_branch("topic").unwrap();
+    backend.checkout_branch("topic").unwrap();
+    fs::write(tmp.path().join("local/a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    backend.commit("add a.txt", false, false, false).unwrap();
+    let options = PushOptions {
+        set_upstream: true,
+        ..PushOptions::default()
+    };
+    backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    backend.checkout_branch(&main).unwrap();
+    assert!(remote_ref_oid(tmp.path(), "refs/heads/topic").is_some());
+
+    // Not in HEAD but pushed to its upstream, so nothing is lost
+    let options = DeleteBranchOptions {
+        delete_remote: true,
+        ..DeleteBranchOptions::default()
+    };
+    let result = backend
+        .delete_branch("topic", &options, &TransferControl::default())
+        .unwrap();
+
+    assert!(result.deleted);
+    assert!(result.remote_deleted);
+    assert!(result.unmerged_commits.is_empty());
+    assert!(remote_ref_oid(tmp.path(), "refs/heads/topic").is_none());
+    let branches = backend.list_branches().unwrap();
+    assert!(!branches
+        .iter()
+        .any(|b| b.name == "topic" || b.name == "origin/topic"));
+}
+
+#[test]
+fn delete_upstream_branch_keeps_the_local_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    let main = backend.current_branch().unwrap();
+    push_default(&backend);
+    backend.create_branch("topic").unwrap();
+    backend.checkout_branch("topic").unwrap();
+    fs::write(tmp.path().join("local/a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    backend.commit("add a.txt", false, false, false).unwrap();
+    let options = PushOptions {
+        set_upstream: true,
+        ..PushOptions::default()
+    };
+    backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    backend.checkout_branch(&main).unwrap();
+    let options = DeleteBranchOptions {
+        delete_remote: true,
+        ..DeleteBranchOptions::default()
+    };
+
+    // Unpushed commits keep both the upstream and the branch
+    backend.checkout_branch("topic").unwrap();
+    fs::write(tmp.path().join("local/b.txt"), "b").unwrap();
+    backend.stage(Path::new("b.txt")).unwrap();
+    backend.commit("add b.txt", false, false, false).unwrap();
+    backend.checkout_branch(&main).unwrap();
+    let result = backend
+        .delete_upstream_branch("topic", &options, &TransferControl::default())
+        .unwrap();
+    assert!(!result.remote_deleted);
+    assert_eq!(result.unmerged_commits.len(), 1);
+    assert!(remote_ref_oid(tmp.path(), "refs/heads/topic").is_some());
+
+    let options = DeleteBranchOptions {
+        force: true,
+        ..options
+    };
+    let result = backend
+        .delete_upstream_branch("topic", &options, &TransferControl::default())
+        .unwrap();
+
+    assert!(!result.deleted);
+    assert!(result.remote_deleted);
+    assert!(remote_ref_oid(tmp.path(), "refs/heads/topic").is_none());
+    let branches = backend.list_branches().unwrap();
+    assert!(branches.iter().any(|b| b.name == "topic" && !b.is_remote));
+}
+
+#[test]
+fn list_prune_candidates_reports_merged_and_gone_branches() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    let local = tmp.path().join("local");
+    let main = backend.current_branch().unwrap();
+    push_default(&backend);
+
+    backend.create_branch("merged").unwrap();
+    backend.create_branch("unmerged").unwrap();
+    commit_on_branch(&local, &backend, "unmerged", "a.txt");
+    backend.create_branch("gone").unwrap();
+    commit_on_branch(&local, &backend, "gone", "b.txt");
+    let options = PushOptions {
+        refspec: Some("gone".to_string()),
+        set_upstream: true,
+        ..PushOptions::default()
+    };
+    backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    commit_from_other_clone(tmp.path(), "c.txt");
+    run_in_other_clone(tmp.path(), &["push", "-q", "origin", "--delete", "gone"]);
+    fetch_default(&backend);
+
+    let gone = backend
+        .list_branches()
+        .unwrap()
+        .into_iter()
+        .find(|b| b.name == "gone")
+        .unwrap();
+    assert!(gone.upstream_gone);
+    assert_eq!(gone.upstream, None);
+
+    let candidates = backend.list_prune_candidates(Some(&main)).unwrap();
+    let mut summary: Vec<(&str, bool, bool)> = candidates
+        .iter()
+        .map(|c| (c.name.as_str(), c.merged, c.upstream_gone))
+        .collect();
+    summary.sort();
+    assert_eq!(
+        summary,
+        vec![("gone", false, true), ("merged", true, false)]
+    );
+}
+
+#[test]
+fn list_branches_includes_remote_fields() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let branches = backend.list_branches().unwrap();
+    let default = branches.iter().find(|b| b.is_head).unwrap();
+
+    assert!(!default.is_remote);
+    assert!(default.remote_name.is_none());
+}
+
+#[test]
+fn list_remotes_empty_by_default() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let remotes = backend.list_remotes().unwrap();
+    assert!(remotes.is_empty());
+}
+
+#[test]
+fn add_and_list_remote() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    backend
+        .add_remote("origin", "https://example.com/repo.git")
+        .unwrap();
+
+    let remotes = backend.list_remotes().unwrap();
+    assert_eq!(remotes.len(), 1);
+    assert_eq!(remotes[0].name, "origin");
+    assert_eq!(remotes[0].url, "https://example.com/repo.git");
+}
+
+#[test]
+fn remove_remote() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend
+        .add_remote("origin", "https://example.com/repo.git")
+        .unwrap();
+
+    backend.remove_remote("origin").unwrap();
+
+    let remotes = backend.list_remotes().unwrap();
+    assert!(remotes.is_empty());
+}
+
+#[test]
+fn edit_remote_url() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend
+        .add_remote("origin", "https://example.com/old.git")
+        .unwrap();
+
+    backend
+        .edit_remote("origin", "https://example.com/new.git")
+        .unwrap();
+
+    let remotes = backend.list_remotes().unwrap();
+    assert_eq!(remotes[0].url, "https://example.com/new.git");
+}
+
+#[test]
+fn add_duplicate_remote_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend
+        .add_remote("origin", "https://example.com/repo.git")
+        .unwrap();
+
+    let result = backend.add_remote("origin", "https://example.com/other.git");
+    assert!(result.is_err());
+}
+
+#[test]
+fn remove_nonexistent_remote_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let result = backend.remove_remote("nonexistent");
+    assert!(result.is_err());
+}
+
+#[test]
+fn merge_ff_only_fails_when_not_possible() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let default_branch = backend.current_branch().unwrap();
+
+    backend.create_branch("feature").unwrap();
+    backend.checkout_branch("feature").unwrap();
+    fs::write(tmp.path().join("feature.txt"), "feature").unwrap();
+    backend.stage(Path::new("feature.txt")).unwrap();
+    backend
+        .commit("feature commit", false, false, false)
+        .unwrap();
+
+    backend.checkout_branch(&default_branch).unwrap();
+    fs::write(tmp.path().join("main.txt"), "main work").unwrap();
+    backend.stage(Path::new("main.txt")).unwrap();
+    backend.commit("main commit", false, false, false).unwrap();
+
+    let result = backend.merge_branch("feature", MergeOption::FastForwardOnly);
+    assert!(result.is_err());
+}
+
+#[test]
+fn fetch_nonexistent_remote_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let result = backend.fetch(
+        "nonexistent",
+        &FetchOptions::default(),
+        &TransferControl::default(),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn pull_nonexistent_remote_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let result = backend.pull(
+        "nonexistent",
+        PullOption::Merge,
+        &TransferControl::default(),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn push_nonexistent_remote_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let result = backend.push(
+        "nonexistent",
+        &PushOptions::default(),
+        &TransferControl::default(),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn fetch_invalid_url_remote_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend
+        .add_remote("bad", "file:///nonexistent/path/repo.git")
+        .unwrap();
+
+    let result = backend.fetch("bad", &FetchOptions::default(), &TransferControl::default());
+    assert!(result.is_err());
+}
+
+#[test]
+fn push_reports_progress() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+
+    let (control, updates) = counting_control();
+    backend
+        .push("origin", &PushOptions::default(), &control)
+        .unwrap();
+
+    assert!(updates.load(std::sync::atomic::Ordering::SeqCst) > 0);
+}
+
+#[test]
+fn push_sets_upstream_when_missing() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+
+    let result = push_default(&backend);
+
+    assert_eq!(result.updates.len(), 1);
+    assert_eq!(result.updates[0].status, PushRefStatus::Created);
+    let branch = backend.current_branch().unwrap();
+    assert_eq!(result.branch, branch);
+    let upstream = backend
+        .list_branches()
+        .unwrap()
+        .into_iter()
+        .find(|b| b.name == branch)
+        .and_then(|b| b.upstream);
+    assert_eq!(upstream, Some(format!("origin/{branch}")));
+}
+
+#[test]
+fn push_reports_up_to_date() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_default(&backend);
+
+    let result = push_default(&backend);
+    assert_eq!(result.updates[0].status, PushRefStatus::UpToDate);
+}
+
+#[test]
+fn push_rejects_non_fast_forward() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_default(&backend);
+    let pushed = remote_ref_oid(tmp.path(), "HEAD");
+
+    backend.commit("rewritten", true, false, false).unwrap();
+    let result = push_default(&backend);
+
+    assert_eq!(
+        result.updates[0].status,
+        PushRefStatus::RejectedNonFastForward
+    );
+    assert_eq!(remote_ref_oid(tmp.path(), "HEAD"), pushed);
+}
+
+#[test]
+fn push_force_with_lease_overwrites_last_fetched() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_default(&backend);
+
+    let amended = backend.commit("rewritten", true, false, false).unwrap();
+    let options = PushOptions {
+        force_with_lease: true,
+        ..PushOptions::default()
+    };
+    let result = backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+
+    assert_eq!(result.updates[0].status, PushRefStatus::Forced);
+    assert_eq!(remote_ref_oid(tmp.path(), "HEAD"), Some(amended.oid));
+}
+
+#[test]
+fn push_force_with_lease_rejects_stale_tracking_ref() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_default(&backend);
+    commit_from_other_clone(tmp.path(), "other.txt");
+    let remote_head = remote_ref_oid(tmp.path(), "HEAD");
+
+    backend.commit("rewritten", true, false, false).unwrap();
+    let options = PushOptions {
+        force_with_lease: true,
+        ..PushOptions::default()
+    };
+    let result = backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+
+    assert_eq!(result.updates[0].status, PushRefStatus::RejectedStale);
+    assert_eq!(remote_ref_oid(tmp.path(), "HEAD"), remote_head);
+}
+
+#[test]
+fn push_refspec_and_delete_remote_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    let branch = backend.current_branch().unwrap();
+
+    let options = PushOptions {
+        refspec: Some(format!("{branch}:feature")),
+        ..PushOptions::default()
+    };
+    let result = backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    assert_eq!(result.updates[0].remote_ref, "refs/heads/feature");
+    assert_eq!(result.updates[0].status, PushRefStatus::Created);
+    assert!(remote_ref_oid(tmp.path(), "refs/heads/feature").is_some());
+
+    let options = PushOptions {
+        delete_branch: Some("feature".to_string()),
+        ..PushOptions::default()
+    };
+    let result = backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    assert_eq!(result.updates[0].status, PushRefStatus::Deleted);
+    assert!(remote_ref_oid(tmp.path(), "refs/heads/feature").is_none());
+}
+
+#[test]
+fn push_single_and_all_tags() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    backend
+        .create_tag("v1.0", None, &CreateTagOptions::default())
+        .unwrap();
+    backend
+        .create_tag("v2.0", Some("annotated"), &CreateTagOptions::default())
+        .unwrap();
+
+    let options = PushOptions {
+        tag: Some("v1.0".to_string()),
+        ..PushOptions::default()
+    };
+    let result = backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    assert_eq!(result.updates.len(), 1);
+    assert!(result.branch.is_empty());
+    assert!(remote_ref_oid(tmp.path(), "refs/tags/v1.0").is_some());
+    assert!(remote_ref_oid(tmp.path(), "refs/tags/v2.0").is_none());
+
+    let options = PushOptions {
+        all_tags: true,
+        ..PushOptions::default()
+    };
+    let result = backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    let statuses: Vec<_> = result
+        .updates
+        .iter()
+        .map(|u| (u.remote_ref.as_str(), u.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            ("refs/tags/v1.0", PushRefStatus::UpToDate),
+            ("refs/tags/v2.0", PushRefStatus::Created),
+        ]
+    );
+}
+
+fn fetch_default(backend: &TestBackend) -> FetchResult {
+    backend
+        .fetch(
+            "origin",
+            &FetchOptions::default(),
+            &TransferControl::default(),
+        )
+        .unwrap()
+}
+
+/// Run a git command inside the clone created by `commit_from_other_clone`.
+fn run_in_other_clone(dir: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir.join("other"))
+        .output()
+        .expect("git command failed");
+    assert!(output.status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn fetch_reports_fast_forward_with_new_commits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_default(&backend);
+    commit_from_other_clone(tmp.path(), "a.txt");
+    commit_from_other_clone(tmp.path(), "b.txt");
+
+    let result = fetch_default(&backend);
+
+    let branch = backend.current_branch().unwrap();
+    assert_eq!(result.updates.len(), 1);
+    let update = &result.updates[0];
+    assert_eq!(update.ref_name, format!("refs/remotes/origin/{branch}"));
+    assert_eq!(update.status, FetchRefStatus::FastForward);
+    assert_eq!(update.new_oid, remote_ref_oid(tmp.path(), "HEAD"));
+    assert_eq!(update.new_commits, 2);
+    assert_eq!(result.new_commits, 2);
+}
+
+#[test]
+fn pull_fetched_merges_the_fetched_upstream() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_default(&backend);
+    commit_from_other_clone(tmp.path(), "a.txt");
+    fetch_default(&backend);
+
+    let result = backend.pull_fetched("origin", PullOption::Merge).unwrap();
+
+    assert_eq!(result.kind, app_lib::git::types::MergeKind::FastForward);
+    assert_eq!(result.oid, remote_ref_oid(tmp.path(), "HEAD"));
+    assert!(tmp.path().join("local/a.txt").exists());
+    let result = backend.pull_fetched("origin", PullOption::Rebase).unwrap();
+    assert_eq!(result.kind, app_lib::git::types::MergeKind::UpToDate);
+}
+
+#[test]
+fn fetch_reports_forced_update() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_default(&backend);
+    commit_from_other_clone(tmp.path(), "a.txt");
+    fetch_default(&backend);
+
+    run_in_other_clone(
+        tmp.path(),
+        &[
+            "-c",
+            "user.name=Other",
+            "-c",
+            "user.email=other@example.com",
+            "commit",
+            "-q",
+            "--amend",
+            "-m",
+            "rewritten",
+        ],
+    );
+    run_in_other_clone(tmp.path(), &["push", "-q", "--force", "origin", "HEAD"]);
+
+    let result = fetch_default(&backend);
+    assert_eq!(result.updates.len(), 1);
+    assert_eq!(result.updates[0].status, FetchRefStatus::Forced);
+    assert_eq!(result.updates[0].new_commits, 1);
+}
+
+#[test]
+fn fetch_reports_pruned_refs_and_new_tags() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_default(&backend);
+    let branch = backend.current_branch().unwrap();
+    let options = PushOptions {
+        refspec: Some(format!("{branch}:feature")),
+        ..PushOptions::default()
+    };
+    backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    fetch_default(&backend);
+
+    commit_from_other_clone(tmp.path(), "a.txt");
+    run_in_other_clone(tmp.path(), &["tag", "v1.0"]);
+    run_in_other_clone(
+        tmp.path(),
+        &["push", "-q", "origin", "v1.0", ":refs/heads/feature"],
+    );
+
+    let options = FetchOptions {
+        tags: FetchTagMode::All,
+        ..FetchOptions::default()
+    };
+    let result = backend
+        .fetch("origin", &options, &TransferControl::default())
+        .unwrap();
+
+    let statuses: Vec<_> = result
+        .updates
+        .iter()
+        .map(|u| (u.ref_name.as_str(), u.status))
+        .collect();
+    let tracking = format!("refs/remotes/origin/{branch}");
+    assert_eq!(
+        statuses,
+        vec![
+            (tracking.as_str(), FetchRefStatus::FastForward),
+            ("refs/tags/v1.0", FetchRefStatus::Created),
+            ("refs/remotes/origin/feature", FetchRefStatus::Pruned),
+        ]
+    );
+    assert_eq!(result.new_commits, 1);
+}
+
+#[test]
+fn fetch_without_prune_keeps_deleted_remote_branches() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_default(&backend);
+    let branch = backend.current_branch().unwrap();
+    let options = PushOptions {
+        refspec: Some(format!("{branch}:feature")),
+        ..PushOptions::default()
+    };
+    backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    Command::new("git")
+        .args(["branch", "-D", "feature"])
+        .current_dir(tmp.path().join("remote.git"))
+        .output()
+        .unwrap();
+
+    let options = FetchOptions {
+        prune: false,
+        ..FetchOptions::default()
+    };
+    let result = backend
+        .fetch("origin", &options, &TransferControl::default())
+        .unwrap();
+
+    assert!(result.updates.is_empty());
+    assert!(backend
+        .list_branches()
+        .unwrap()
+        .iter()
+        .any(|b| b.name == "origin/feature"));
+}
+
+#[test]
+fn fetch_all_remotes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_default(&backend);
+    let url = format!("file://{}", tmp.path().join("remote.git").display());
+    backend.add_remote("mirror", &url).unwrap();
+
+    let options = FetchOptions {
+        all_remotes: true,
+        ..FetchOptions::default()
+    };
+    let result = backend
+        .fetch("origin", &options, &TransferControl::default())
+        .unwrap();
+
+    let branch = backend.current_branch().unwrap();
+    assert_eq!(result.updates.len(), 1);
+    assert_eq!(
+        result.updates[0].ref_name,
+        format!("refs/remotes/mirror/{branch}")
+    );
+    assert_eq!(result.updates[0].status, FetchRefStatus::Created);
+    assert_eq!(result.new_commits, 0);
+}
+
+#[test]
+fn fetch_reports_progress() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    push_default(&backend);
+    commit_from_other_clone(tmp.path(), "new.txt");
+
+    let (control, updates) = counting_control();
+    backend
+        .fetch("origin", &FetchOptions::default(), &control)
+        .unwrap();
+
+    assert!(updates.load(std::sync::atomic::Ordering::SeqCst) > 0);
+}
+
+#[test]
+fn fetch_cancelled_returns_cancelled() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+
+    let result = backend.fetch("origin", &FetchOptions::default(), &cancelled_control());
+    assert!(matches!(result, Err(GitError::Cancelled)));
+}
+
+#[test]
+fn push_cancelled_leaves_remote_untouched() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+
+    let result = backend.push("origin", &PushOptions::default(), &cancelled_control());
+    assert!(matches!(result, Err(GitError::Cancelled)));
+
+    let output = Command::new("git")
+        .args(["for-each-ref", "refs/heads"])
+        .current_dir(tmp.path().join("remote.git"))
+        .output()
+        .unwrap();
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn get_commit_log_returns_commits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("second.txt"), "second").unwrap();
+    backend.stage(Path::new("second.txt")).unwrap();
+    backend
+        .commit("second commit", false, false, false)
+        .unwrap();
+
+    let filter = LogFilter {
+        author: None,
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let result = backend.get_commit_log(&filter, 100, 0).unwrap();
+
+    assert_eq!(result.commits.len(), 2);
+    assert_eq!(result.commits[0].message, "second commit");
+    assert_eq!(result.commits[1].message, "initial commit");
+}
+
+#[test]
+fn get_commit_log_filters_by_author() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let filter = LogFilter {
+        author: Some("Test User".to_string()),
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let result = backend.get_commit_log(&filter, 100, 0).unwrap();
+
+    assert_eq!(result.commits.len(), 1);
+
+    let filter_no_match = LogFilter {
+        author: Some("Nonexistent".to_string()),
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let result = backend.get_commit_log(&filter_no_match, 100, 0).unwrap();
+
+    assert!(result.commits.is_empty());
+}
+
+fn log_page(backend: &TestBackend, limit: usize, skip: usize) -> Vec<(String, usize)> {
+    let log = backend
+        .get_commit_log(&LogFilter::default(), limit, skip)
+        .unwrap();
+    log.commits
+        .iter()
+        .zip(&log.graph)
+        .map(|(commit, row)| (commit.oid.clone(), row.column))
+        .collect()
+}
+
+#[test]
+fn get_commit_log_pages_match_a_single_walk() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    run_git(tmp.path(), &["checkout", "-b", "feature"]);
+    commit_file(tmp.path(), &backend, "feature1.txt");
+    commit_file(tmp.path(), &backend, "feature2.txt");
+    run_git(tmp.path(), &["checkout", "-"]);
+    commit_file(tmp.path(), &backend, "main.txt");
+    run_git(
+        tmp.path(),
+        &["merge", "--no-ff", "-m", "merge feature", "feature"],
+    );
+    commit_file(tmp.path(), &backend, "after.txt");
+
+    let full = log_page(&TestBackend::open(tmp.path()).unwrap(), 100, 0);
+    assert_eq!(full.len(), 6);
+
+    let paged: Vec<_> = (0..3)
+        .flat_map(|page| log_page(&backend, 2, page * 2))
+        .collect();
+    assert_eq!(paged, full);
+    assert_eq!(log_page(&backend, 2, 6), Vec::new());
+}
+
+#[test]
+fn get_commit_log_follows_head_moves() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    commit_file(tmp.path(), &backend, "second.txt");
+    let fresh = || log_page(&TestBackend::open(tmp.path()).unwrap(), 100, 0);
+    assert_eq!(log_page(&backend, 100, 0), fresh());
+
+    // New commit on top
+    commit_file(tmp.path(), &backend, "third.txt");
+    assert_eq!(log_page(&backend, 100, 0).len(), 3);
+    assert_eq!(log_page(&backend, 100, 0), fresh());
+
+    // Amend
+    backend.commit("third amended", true, false, false).unwrap();
+    let log = backend.get_commit_log(&LogFilter::default(), 1, 0).unwrap();
+    assert_eq!(log.commits[0].message, "third amended");
+    assert_eq!(log_page(&backend, 100, 0), fresh());
+
+    // Reset back
+    run_git(tmp.path(), &["reset", "--hard", "HEAD~2"]);
+    assert_eq!(log_page(&backend, 100, 0).len(), 1);
+    assert_eq!(log_page(&backend, 100, 0), fresh());
+}
+
+#[test]
+fn get_commit_log_filtered_view_follows_head_moves() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    commit_file(tmp.path(), &backend, "a.txt");
+    commit_file(tmp.path(), &backend, "b.txt");
+    let filter = LogFilter {
+        path: Some("a.txt".to_string()),
+        ..LogFilter::default()
+    };
+    let subjects = |backend: &TestBackend| -> Vec<String> {
+        let log = backend.get_commit_log(&filter, 100, 0).unwrap();
+        log.commits.into_iter().map(|c| c.message).collect()
+    };
+    assert_eq!(subjects(&backend), vec!["a.txt"]);
+
+    // New commit on top
+    fs::write(tmp.path().join("a.txt"), "changed").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    backend.commit("change a", false, false, false).unwrap();
+    assert_eq!(subjects(&backend), vec!["change a", "a.txt"]);
+
+    // Amend
+    backend
+        .commit("change a again", true, false, false)
+        .unwrap();
+    assert_eq!(subjects(&backend), vec!["change a again", "a.txt"]);
+    let fresh = TestBackend::open(tmp.path()).unwrap();
+    assert_eq!(subjects(&backend), subjects(&fresh));
+}
+
+#[test]
+fn get_commit_log_shows_refs_created_after_caching() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend
+        .get_commit_log(&LogFilter::default(), 10, 0)
+        .unwrap();
+
+    backend.create_branch("topic").unwrap();
+    let log = backend
+        .get_commit_log(&LogFilter::default(), 10, 0)
+        .unwrap();
+
+    assert!(log.commits[0].refs.iter().any(|r| r.name == "topic"));
+}
+
+#[test]
+fn get_commit_detail_returns_info_and_files() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("detail.txt"), "detail content").unwrap();
+    backend.stage(Path::new("detail.txt")).unwrap();
+    let commit_result = backend
+        .commit("detail commit", false, false, false)
+        .unwrap();
+
+    let detail = backend.get_commit_detail(&commit_result.oid).unwrap();
+
+    assert_eq!(detail.info.message, "detail commit");
+    assert_eq!(detail.files.len(), 1);
+    assert_eq!(detail.files[0].path, "detail.txt");
+    assert_eq!(detail.stats.files_changed, 1);
+}
+
+#[test]
+fn commit_trailers_and_co_authors_are_parsed() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let message = trailers::append(
+        "Pair on parser\n\nRefs: #42\n",
+        &[
+            trailers::co_author_trailer(&CoAuthor {
+                name: "Ann Lee".to_string(),
+                email: "ann@example.com".to_string(),
+            }),
+            CommitTrailer {
+                key: trailers::SIGNED_OFF_BY.to_string(),
+                value: "Test User <test@example.com>".to_string(),
+            },
+        ],
+    )
+    .unwrap();
+    fs::write(tmp.path().join("pair.txt"), "pair").unwrap();
+    backend.stage(Path::new("pair.txt")).unwrap();
+    let result = backend.commit(&message, false, false, false).unwrap();
+
+    let detail = backend.get_commit_detail(&result.oid).unwrap();
+    let keys: Vec<&str> = detail
+        .info
+        .trailers
+        .iter()
+        .map(|t| t.key.as_str())
+        .collect();
+    assert_eq!(keys, vec!["Refs", "Co-authored-by", "Signed-off-by"]);
+    assert_eq!(detail.info.co_authors.len(), 1);
+    assert_eq!(detail.info.co_authors[0].email, "ann@example.com");
+
+    let log = backend.get_commit_log(&LogFilter::default(), 1, 0).unwrap();
+    assert_eq!(log.commits[0].co_authors, detail.info.co_authors);
+}
+
+fn head_idents(dir: &Path) -> String {
+    let output = Command::new("git")
+        .args([
+            "log",
+            "-1",
+            "--format=%an <%ae> %ad | %cn <%ce> %cd",
+            "--date=raw",
+        ])
+        .current_dir(dir)
+        .output()
+        .expect("git log failed");
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn commit_as_overrides_author_and_committer() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("as.txt"), "as").unwrap();
+    backend.stage(Path::new("as.txt")).unwrap();
+    let identity = CommitIdentity {
+        author: Some(SignatureOverride {
+            name: Some("Ann Lee".to_string()),
+            email: Some("ann@acme.example".to_string()),
+            time: Some(1_700_000_000),
+            offset_minutes: Some(570),
+        }),
+        committer: Some(SignatureOverride {
+            email: Some("bot@acme.example".to_string()),
+            time: Some(1_600_000_000),
+            offset_minutes: Some(-60),
+            ..Default::default()
+        }),
+    };
+    backend
+        .commit_as("as someone else", false, false, false, &identity)
+        .unwrap();
+    assert_eq!(
+        head_idents(tmp.path()),
+        "Ann Lee <ann@acme.example> 1700000000 +0930 | Test User <bot@acme.example> 1600000000 -0100"
+    );
+
+    // amend でも指定した作者に置き換わり、未指定の項目は設定値になる
+    let identity = CommitIdentity {
+        author: Some(SignatureOverride {
+            name: Some("Bo".to_string()),
+            ..Default::default()
+        }),
+        committer: None,
+    };
+    backend
+        .commit_as("amended", true, false, false, &identity)
+        .unwrap();
+    let idents = head_idents(tmp.path());
+    assert!(
+        idents.starts_with("Bo <test@example.com> "),
+        "unexpected identities: {idents}"
+    );
+    assert!(
+        idents.contains("| Test User <test@example.com> "),
+        "unexpected identities: {idents}"
+    );
+}
+
+#[test]
+fn apply_identity_profile_fixes_mismatch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let profile = IdentityProfile {
+        label: "work".to_string(),
+        name: "Ann Lee".to_string(),
+        email: "ann@acme.example".to_string(),
+        signing_key: Some("ABCD1234".to_string()),
+        signing_format: Some(SigningFormat::Ssh),
+        remote_patterns: Vec::new(),
+        path_patterns: Vec::new(),
+    };
+
+    let check = identity::check(
+        tmp.path(),
+        Some(&profile),
+        IdentityMismatchAction::Block,
+        None,
+    );
+    assert!(!check.matches);
+    assert_eq!(check.email.as_deref(), Some("test@example.com"));
+    assert!(identity::enforce(&check).is_err());
+
+    identity::apply_profile(&backend, &profile).unwrap();
+
+    let check = identity::check(
+        tmp.path(),
+        Some(&profile),
+        IdentityMismatchAction::Block,
+        None,
+    );
+    assert!(check.matches);
+    assert_eq!(check.name.as_deref(), Some("Ann Lee"));
+    assert_eq!(
+        backend
+            .get_gitconfig_value(GitConfigScope::Local, "gpg.format")
+            .unwrap()
+            .as_deref(),
+        Some("ssh")
+    );
+}
+
+#[test]
+fn get_commit_detail_invalid_oid_returns_error() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let result = backend.get_commit_detail("invalid_oid");
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn get_blame_returns_correct_line_count() {
+    let tmp = tempfile::tempdir().unwrap();
+    init_test_repo(tmp.path());
+
+    let content = "line1\nline2\nline3\nline4\nline5\n";
+    fs::write(tmp.path().join("blame.txt"), content).unwrap();
+
+    let backend = TestBackend::open(tmp.path()).unwrap();
+    backend.stage(Path::new("blame.txt")).unwrap();
+    backend
+        .commit("add blame file", false, false, false)
+        .unwrap();
+
+    let blame_result = backend
+        .get_blame("blame.txt", None, &BlameOptions::default())
+        .unwrap();
+
+    assert_eq!(blame_result.path, "blame.txt");
+    assert_eq!(blame_result.lines.len(), 5);
+
+    for (i, line) in blame_result.lines.iter().enumerate() {
+        assert_eq!(line.line_number, (i + 1) as u32);
+    }
+
+    assert_eq!(blame_result.lines[0].content, "line1");
+    assert_eq!(blame_result.lines[4].content, "line5");
+}
+
+#[test]
+fn get_blame_with_multiple_commits_tracks_authors() {
+    let tmp = tempfile::tempdir().unwrap();
+    init_test_repo(tmp.path());
+
+    fs::write(tmp.path().join("multi.txt"), "original\n").unwrap();
+    let backend = TestBackend::open(tmp.path()).unwrap();
+    backend.stage(Path::new("multi.txt")).unwrap();
+    let first_commit = backend.commit("first", false, false, false).unwrap();
+
+    fs::write(tmp.path().join("multi.txt"), "original\nadded\n").unwrap();
+    backend.stage(Path::new("multi.txt")).unwrap();
+    backend.commit("second", false, false, false).unwrap();
+
+    let blame_result = backend
+        .get_blame("multi.txt", None, &BlameOptions::default())
+        .unwrap();
+
+    assert_eq!(blame_result.lines.len(), 2);
+    assert_eq!(blame_result.lines[0].commit_oid, first_commit.oid);
+    assert_eq!(blame_result.lines[0].content, "original");
+    assert_eq!(blame_result.lines[1].content, "added");
+}
+
+#[test]
+fn get_file_history_returns_only_touching_commits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("tracked.txt"), "v1").unwrap();
+    backend.stage(Path::new("tracked.txt")).unwrap();
+    backend.commit("add tracked", false, false, false).unwrap();
+
+    fs::write(tmp.path().join("other.txt"), "other").unwrap();
+    backend.stage(Path::new("other.txt")).unwrap();
+    backend.commit("add other", false, false, false).unwrap();
+
+    fs::write(tmp.path().join("tracked.txt"), "v2").unwrap();
+    backend.stage(Path::new("tracked.txt")).unwrap();
+    backend
+        .commit("update tracked", false, false, false)
+        .unwrap();
+
+    let history = backend
+        .get_file_history("tracked.txt", 100, 0, false)
+        .unwrap();
+
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].commit.message, "update tracked");
+    assert_eq!(history[1].commit.message, "add tracked");
+}
+
+fn commit_rename_history(dir: &Path, backend: &TestBackend) {
+    fs::write(dir.join("old.txt"), "one\ntwo\nthree\nfour\n").unwrap();
+    backend.stage(Path::new("old.txt")).unwrap();
+    backend.commit("add", false, false, false).unwrap();
+    fs::write(dir.join("old.txt"), "one\ntwo\nthree\nfour\nfive\n").unwrap();
+    backend.stage(Path::new("old.txt")).unwrap();
+    backend.commit("edit", false, false, false).unwrap();
+    fs::create_dir_all(dir.join("dir")).unwrap();
+    fs::rename(dir.join("old.txt"), dir.join("dir/new.txt")).unwrap();
+    backend.stage_all().unwrap();
+    backend.commit("move", false, false, false).unwrap();
+    fs::write(
+        dir.join("dir/new.txt"),
+        "one\ntwo\nthree\nfour\nfive\nsix\n",
+    )
+    .unwrap();
+    backend.stage(Path::new("dir/new.txt")).unwrap();
+    backend.commit("after", false, false, false).unwrap();
+}
+
+#[test]
+fn get_file_history_follows_renames() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    commit_rename_history(tmp.path(), &backend);
+
+    let history = |follow, skip| {
+        backend
+            .get_file_history("dir/new.txt", 100, skip, follow)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.commit.message, e.path, e.previous_path))
+            .collect::<Vec<_>>()
+    };
+    let entry = |message: &str, path: &str, previous: Option<&str>| {
+        (
+            message.to_string(),
+            path.to_string(),
+            previous.map(str::to_string),
+        )
+    };
+
+    assert_eq!(
+        history(false, 0),
+        vec![
+            entry("after", "dir/new.txt", None),
+            entry("move", "dir/new.txt", None),
+        ]
+    );
+    assert_eq!(
+        history(true, 0),
+        vec![
+            entry("after", "dir/new.txt", None),
+            entry("move", "dir/new.txt", Some("old.txt")),
+            entry("edit", "old.txt", None),
+            entry("add", "old.txt", None),
+        ]
+    );
+    assert_eq!(
+        history(true, 2),
+        vec![
+            entry("edit", "old.txt", None),
+            entry("add", "old.txt", None)
+        ]
+    );
+}
+
+#[test]
+fn get_blame_reports_original_path_across_renames() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    commit_rename_history(tmp.path(), &backend);
+
+    let blame = backend
+        .get_blame("dir/new.txt", None, &BlameOptions::default())
+        .unwrap();
+
+    let first = &blame.lines[0];
+    let detail = backend.get_commit_detail(&first.commit_oid).unwrap();
+    assert_eq!(detail.info.message, "add");
+    assert_eq!(first.original_path, "old.txt");
+    assert_eq!(first.original_line_number, 1);
+    let last = &blame.lines[5];
+    assert_eq!(last.original_path, "dir/new.txt");
+    assert_eq!(last.original_line_number, 6);
+}
+
+#[test]
+fn get_blame_reports_commit_summary() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("notes.txt"), "line\n").unwrap();
+    backend.stage(Path::new("notes.txt")).unwrap();
+    let commit = backend
+        .commit("Add notes\n\nWith a body.", false, false, false)
+        .unwrap();
+
+    let blame = backend
+        .get_blame("notes.txt", Some(&commit.oid), &BlameOptions::default())
+        .unwrap();
+
+    assert_eq!(blame.commit_oid.as_deref(), Some(commit.oid.as_str()));
+    assert_eq!(blame.lines[0].summary, "Add notes");
+}
+
+#[test]
+fn get_blame_skips_ignored_revisions() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("code.txt"), "alpha = 1\nbeta = 2\n").unwrap();
+    backend.stage(Path::new("code.txt")).unwrap();
+    let original = backend.commit("add code", false, false, false).unwrap();
+    fs::write(tmp.path().join("code.txt"), "alpha=1\nbeta=2\n").unwrap();
+    backend.stage(Path::new("code.txt")).unwrap();
+    let format = backend.commit("format code", false, false, false).unwrap();
+
+    let plain = backend
+        .get_blame("code.txt", None, &BlameOptions::default())
+        .unwrap();
+    assert!(plain.lines.iter().all(|l| l.commit_oid == format.oid));
+
+    let options = BlameOptions {
+        ignore_revs: vec![format.oid.clone()],
+        ..BlameOptions::default()
+    };
+    let blame = backend.get_blame("code.txt", None, &options).unwrap();
+    assert!(blame.lines.iter().all(|l| l.commit_oid == original.oid));
+    assert_eq!(blame.lines[1].content, "beta=2");
+
+    // The conventional ignore file is picked up without any options.
+    fs::write(
+        tmp.path().join(".git-blame-ignore-revs"),
+        format!("# formatting\n{}\n", format.oid),
+    )
+    .unwrap();
+    let blame = backend
+        .get_blame("code.txt", None, &BlameOptions::default())
+        .unwrap();
+    assert!(blame.lines.iter().all(|l| l.commit_oid == original.oid));
+
+    // 明示的に外すか、git と同じく blame.ignoreRevsFile を空にすれば使わない
+    let options = BlameOptions {
+        no_default_ignore_revs: true,
+        ..BlameOptions::default()
+    };
+    let blame = backend.get_blame("code.txt", None, &options).unwrap();
+    assert!(blame.lines.iter().all(|l| l.commit_oid == format.oid));
+    run_git(tmp.path(), &["config", "blame.ignoreRevsFile", ""]);
+    let blame = backend
+        .get_blame("code.txt", None, &BlameOptions::default())
+        .unwrap();
+    assert!(blame.lines.iter().all(|l| l.commit_oid == format.oid));
+}
+
+#[test]
+fn get_blame_before_walks_back_across_renames() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    commit_rename_history(tmp.path(), &backend);
+    let oids: HashMap<String, String> = backend
+        .get_file_history("dir/new.txt", 100, 0, true)
+        .unwrap()
+        .into_iter()
+        .map(|e| (e.commit.message, e.commit.oid))
+        .collect();
+    let options = BlameOptions::default();
+
+    let before_after = backend
+        .get_blame_before(&oids["after"], "dir/new.txt", &options)
+        .unwrap();
+    assert_eq!(before_after.path, "dir/new.txt");
+    assert_eq!(
+        before_after.commit_oid.as_deref(),
+        Some(oids["move"].as_str())
+    );
+    assert_eq!(before_after.lines.len(), 5);
+    assert_eq!(before_after.lines[4].summary, "edit");
+
+    let before_move = backend
+        .get_blame_before(&oids["move"], "dir/new.txt", &options)
+        .unwrap();
+    assert_eq!(before_move.path, "old.txt");
+    assert_eq!(
+        before_move.commit_oid.as_deref(),
+        Some(oids["edit"].as_str())
+    );
+    assert_eq!(before_move.lines[0].summary, "add");
+}
+
+#[test]
+fn get_blame_before_root_commit_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let head = backend.get_commit_log(&LogFilter::default(), 1, 0).unwrap();
+    let root = &head.commits[0].oid;
+
+    let result = backend.get_blame_before(root, "init.txt", &BlameOptions::default());
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn get_blame_detects_lines_moved_from_other_files() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let moved = "fn parse_configuration_file(path: &Path) -> Result<Config> {\n\
+    let content = std::fs::read_to_string(path)?;\n\
+    toml::from_str(&content).map_err(Into::into)\n\
+}\n";
+    fs::write(tmp.path().join("a.rs"), format!("// a\n{moved}")).unwrap();
+    backend.stage(Path::new("a.rs")).unwrap();
+    let original = backend.commit("add parser", false, false, false).unwrap();
+    fs::write(tmp.path().join("a.rs"), "// a\n").unwrap();
+    fs::write(tmp.path().join("b.rs"), format!("// b\n{moved}")).unwrap();
+    backend.stage_all().unwrap();
+    backend.commit("move parser", false, false, false).unwrap();
+
+    let plain = backend
+        .get_blame("b.rs", None, &BlameOptions::default())
+        .unwrap();
+    assert_ne!(plain.lines[1].commit_oid, original.oid);
+
+    let options = BlameOptions {
+        detect_copies: true,
+        ..BlameOptions::default()
+    };
+    let blame = backend.get_blame("b.rs", None, &options).unwrap();
+    assert_eq!(blame.lines[1].commit_oid, original.oid);
+    assert_eq!(blame.lines[1].original_path, "a.rs");
+    assert_eq!(blame.lines[1].original_line_number, 2);
+    assert_eq!(blame.lines[1].line_number, 2);
+}
+
+#[test]
+fn path_history_is_unchanged_by_commit_graph() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    for (file, content) in [
+        ("src/a.txt", "a1"),
+        ("b.txt", "b1"),
+        ("src/a.txt", "a2"),
+        ("src/c.txt", "c1"),
+    ] {
+        fs::create_dir_all(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join(file), content).unwrap();
+        backend.stage(Path::new(file)).unwrap();
+        backend
+            .commit(&format!("{file} {content}"), false, false, false)
+            .unwrap();
+    }
+    let src_filter = LogFilter {
+        path: Some("src".to_string()),
+        ..LogFilter::default()
+    };
+    let messages = |commits: &[CommitInfo]| {
+        commits
+            .iter()
+            .map(|c| c.message.clone())
+            .collect::<Vec<_>>()
+    };
+    let history_messages = |backend: &TestBackend| {
+        backend
+            .get_file_history("src/a.txt", 100, 0, false)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.commit.message)
+            .collect::<Vec<_>>()
+    };
+    let file_history = history_messages(&backend);
+    let dir_log = messages(&backend.get_commit_log(&src_filter, 100, 0).unwrap().commits);
+    assert_eq!(file_history, vec!["src/a.txt a2", "src/a.txt a1"]);
+    assert_eq!(dir_log.len(), 3);
+
+    backend.write_commit_graph().unwrap();
+    assert!(tmp.path().join(".git/objects/info/commit-graph").exists());
+
+    let reopened = TestBackend::open(tmp.path()).unwrap();
+    assert_eq!(history_messages(&reopened), file_history);
+    assert_eq!(
+        messages(
+            &reopened
+                .get_commit_log(&src_filter, 100, 0)
+                .unwrap()
+                .commits
+        ),
+        dir_log
+    );
+}
+
+#[test]
+fn list_branches_has_ahead_behind_zero_without_upstream() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let branches = backend.list_branches().unwrap();
+    let head = branches.iter().find(|b| b.is_head).unwrap();
+
+    assert_eq!(head.ahead_count, 0);
+    assert_eq!(head.behind_count, 0);
+}
+
+#[test]
+fn get_branch_commits_returns_commits_for_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    backend.create_branch("feature").unwrap();
+    backend.checkout_branch("feature").unwrap();
+
+    fs::write(tmp.path().join("feature.txt"), "feature work").unwrap();
+    backend.stage(Path::new("feature.txt")).unwrap();
+    backend
+        .commit("feature commit", false, false, false)
+        .unwrap();
+
+    let commits = backend.get_branch_commits("feature", 10).unwrap();
+
+    assert_eq!(commits.len(), 2);
+    assert_eq!(commits[0].message, "feature commit");
+    assert_eq!(commits[1].message, "initial commit");
+}
+
+#[test]
+fn get_branch_commits_respects_limit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    for i in 0..5 {
+        let filename = format!("file{i}.txt");
+        fs::write(tmp.path().join(&filename), format!("content {i}")).unwrap();
+        backend.stage(Path::new(&filename)).unwrap();
+        backend
+            .commit(&format!("commit {i}"), false, false, false)
+            .unwrap();
+    }
+
+    let branch_name = backend.current_branch().unwrap();
+    let commits = backend.get_branch_commits(&branch_name, 3).unwrap();
+
+    assert_eq!(commits.len(), 3);
+}
+
+#[test]
+fn get_branch_commits_nonexistent_branch_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let result = backend.get_branch_commits("nonexistent", 10);
+    assert!(result.is_err());
+}
+
+#[test]
+fn diff_includes_hunk_range_fields() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("file.txt"), "line1\nline2\nline3\n").unwrap();
+    backend.stage(Path::new("file.txt")).unwrap();
+    backend.commit("add file", false, false, false).unwrap();
+
+    fs::write(tmp.path().join("file.txt"), "line1\nmodified\nline3\n").unwrap();
+
+    let options = DiffOptions {
+        staged: false,
+        ..Default::default()
+    };
+    let diffs = backend.diff(Some(Path::new("file.txt")), &options).unwrap();
+
+    assert!(!diffs.is_empty());
+    let hunk = &diffs[0].hunks[0];
+    assert!(hunk.old_start > 0);
+    assert!(hunk.new_start > 0);
+    assert!(hunk.old_lines > 0);
+    assert!(hunk.new_lines > 0);
+}
+
+fn create_two_hunk_file(dir: &Path, backend: &TestBackend) {
+    let mut content = String::new();
+    for i in 1..=20 {
+        content.push_str(&format!("line{i}\n"));
+    }
+    fs::write(dir.join("multi.txt"), &content).unwrap();
+    backend.stage(Path::new("multi.txt")).unwrap();
+    backend.commit("add multi", false, false, false).unwrap();
+
+    let mut modified = String::new();
+    for i in 1..=20 {
+        if i == 2 {
+            modified.push_str("modified_top\n");
+        } else if i == 19 {
+            modified.push_str("modified_bottom\n");
+        } else {
+            modified.push_str(&format!("line{i}\n"));
+        }
+    }
+    fs::write(dir.join("multi.txt"), &modified).unwrap();
+}
+
+#[test]
+fn stage_hunk_stages_only_specified_hunk() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    create_two_hunk_file(tmp.path(), &backend);
+
+    let options = DiffOptions {
+        staged: false,
+        ..Default::default()
+    };
+    let diffs = backend
+        .diff(Some(Path::new("multi.txt")), &options)
+        .unwrap();
+    assert!(diffs[0].hunks.len() >= 2, "Expected at least 2 hunks");
+
+    let first_hunk = &diffs[0].hunks[0];
+    let hunk_id = app_lib::git::types::HunkIdentifier {
+        old_start: first_hunk.old_start,
+        old_lines: first_hunk.old_lines,
+        new_start: first_hunk.new_start,
+        new_lines: first_hunk.new_lines,
+    };
+
+    backend
+        .stage_hunk(Path::new("multi.txt"), &hunk_id)
+        .unwrap();
+
+    let staged_diffs = backend
+        .diff(
+            Some(Path::new("multi.txt")),
+            &DiffOptions {
+                staged: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        staged_diffs[0].hunks.len(),
+        1,
+        "Only one hunk should be staged"
+    );
+
+    let unstaged_diffs = backend
+        .diff(
+            Some(Path::new("multi.txt")),
+            &DiffOptions {
+                staged: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        unstaged_diffs[0].hunks.len(),
+        1,
+        "One hunk should remain unstaged"
+    );
+}
+
+#[test]
+fn unstage_hunk_unstages_only_specified_hunk() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    create_two_hunk_file(tmp.path(), &backend);
+
+    backend.stage(Path::new("multi.txt")).unwrap();
+
+    let staged_diffs = backend
+        .diff(
+            Some(Path::new("multi.txt")),
+            &DiffOptions {
+                staged: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert!(staged_diffs[0].hunks.len() >= 2);
+
+    let first_hunk = &staged_diffs[0].hunks[0];
+    let hunk_id = app_lib::git::types::HunkIdentifier {
+        old_start: first_hunk.old_start,
+        old_lines: first_hunk.old_lines,
+        new_start: first_hunk.new_start,
+        new_lines: first_hunk.new_lines,
+    };
+
+    backend
+        .unstage_hunk(Path::new("multi.txt"), &hunk_id)
+        .unwrap();
+
+    let staged_after = backend
+        .diff(
+            Some(Path::new("multi.txt")),
+            &DiffOptions {
+                staged: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        staged_after[0].hunks.len(),
+        1,
+        "One hunk should remain staged"
+    );
+}
+
+#[test]
+fn discard_hunk_discards_only_specified_hunk() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    create_two_hunk_file(tmp.path(), &backend);
+
+    let options = DiffOptions {
+        staged: false,
+        ..Default::default()
+    };
+    let diffs = backend
+        .diff(Some(Path::new("multi.txt")), &options)
+        .unwrap();
+    assert!(diffs[0].hunks.len() >= 2);
+
+    let first_hunk = &diffs[0].hunks[0];
+    let hunk_id = app_lib::git::types::HunkIdentifier {
+        old_start: first_hunk.old_start,
+        old_lines: first_hunk.old_lines,
+        new_start: first_hunk.new_start,
+        new_lines: first_hunk.new_lines,
+    };
+
+    backend
+        .discard_hunk(Path::new("multi.txt"), &hunk_id)
+        .unwrap();
+
+    let diffs_after = backend
+        .diff(Some(Path::new("multi.txt")), &options)
+        .unwrap();
+    assert_eq!(
+        diffs_after[0].hunks.len(),
+        1,
+        "One hunk should remain after discard"
+    );
+}
+
+// --- Line staging tests ---
+
+#[test]
+fn stage_lines_stages_only_selected_lines() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    create_file_with_multiple_changed_lines(tmp.path(), &backend);
+
+    let options = DiffOptions {
+        staged: false,
+        ..Default::default()
+    };
+    let diffs = backend
+        .diff(Some(Path::new("lines.txt")), &options)
+        .unwrap();
+    assert!(!diffs.is_empty());
+
+    let hunk = &diffs[0].hunks[0];
+    let hunk_id = HunkIdentifier {
+        old_start: hunk.old_start,
+        old_lines: hunk.old_lines,
+        new_start: hunk.new_start,
+        new_lines: hunk.new_lines,
+    };
+
+    let first_add = hunk
+        .lines
+        .iter()
+        .enumerate()
+        .find(|(_, l)| l.kind == DiffLineKind::Addition)
+        .map(|(i, _)| i)
+        .unwrap();
+
+    let line_range = LineRange {
+        hunk: hunk_id,
+        line_indices: vec![first_add],
+    };
+    backend
+        .stage_lines(Path::new("lines.txt"), &line_range)
+        .unwrap();
+
+    let staged = backend
+        .diff(
+            Some(Path::new("lines.txt")),
+            &DiffOptions {
+                staged: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert!(!staged.is_empty(), "Should have staged changes");
+
+    let unstaged = backend
+        .diff(Some(Path::new("lines.txt")), &options)
+        .unwrap();
+    assert!(!unstaged.is_empty(), "Should still have unstaged changes");
+}
+
+#[test]
+fn unstage_lines_unstages_only_selected_lines() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    create_file_with_two_hunks(tmp.path(), &backend);
+
+    backend.stage(Path::new("twohunk.txt")).unwrap();
+
+    let staged_diffs = backend
+        .diff(
+            Some(Path::new("twohunk.txt")),
+            &DiffOptions {
+                staged: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert!(!staged_diffs.is_empty());
+    assert!(
+        staged_diffs[0].hunks.len() >= 2,
+        "Should have at least 2 hunks"
+    );
+
+    let hunk = &staged_diffs[0].hunks[0];
+    let hunk_id = HunkIdentifier {
+        old_start: hunk.old_start,
+        old_lines: hunk.old_lines,
+        new_start: hunk.new_start,
+        new_lines: hunk.new_lines,
+    };
+
+    let del_idx = hunk
+        .lines
+        .iter()
+        .enumerate()
+        .find(|(_, l)| l.kind == DiffLineKind::Deletion)
+        .map(|(i, _)| i);
+    let add_idx = hunk
+        .lines
+        .iter()
+        .enumerate()
+        .find(|(_, l)| l.kind == DiffLineKind::Addition)
+        .map(|(i, _)| i);
+
+    let mut selected = Vec::new();
+    if let Some(idx) = del_idx {
+        selected.push(idx);
+    }
+    if let Some(idx) = add_idx {
+        selected.push(idx);
+    }
+
+    let line_range = LineRange {
+        hunk: hunk_id,
+        line_indices: selected,
+    };
+    backend
+        .unstage_lines(Path::new("twohunk.txt"), &line_range)
+        .unwrap();
+
+    let unstaged_after = backend
+        .diff(
+            Some(Path::new("twohunk.txt")),
+            &DiffOptions {
+                staged: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert!(
+        !unstaged_after.is_empty(),
+        "Should have unstaged changes after unstage_lines"
+    );
+}
+
+#[test]
+fn discard_lines_discards_only_selected_lines() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    create_file_with_multiple_changed_lines(tmp.path(), &backend);
+
+    let options = DiffOptions {
+        staged: false,
+        ..Default::default()
+    };
+    let diffs = backend
+        .diff(Some(Path::new("lines.txt")), &options)
+        .unwrap();
+    let hunk = &diffs[0].hunks[0];
+    let hunk_id = HunkIdentifier {
+        old_start: hunk.old_start,
+        old_lines: hunk.old_lines,
+        new_start: hunk.new_start,
+        new_lines: hunk.new_lines,
+    };
+
+    let mut all_indices = Vec::new();
+    for (i, line) in hunk.lines.iter().enumerate() {
+        if line.kind == DiffLineKind::Addition || line.kind == DiffLineKind::Deletion {
+            all_indices.push(i);
+        }
+    }
+
+    let line_range = LineRange {
+        hunk: hunk_id,
+        line_indices: all_indices,
+    };
+    backend
+        .discard_lines(Path::new("lines.txt"), &line_range)
+        .unwrap();
+
+    let diffs_after = backend
+        .diff(Some(Path::new("lines.txt")), &options)
+        .unwrap();
+    assert!(
+        diffs_after.is_empty() || diffs_after[0].hunks.is_empty(),
+        "All changes should be discarded"
+    );
+}
+
+// --- Amend tests ---
+
+#[test]
+fn commit_amend_changes_message() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let msg = backend.get_head_commit_message().unwrap();
+    assert_eq!(msg.trim(), "initial commit");
+
+    backend
+        .commit("amended message", true, false, false)
+        .unwrap();
+
+    let msg_after = backend.get_head_commit_message().unwrap();
+    assert_eq!(msg_after.trim(), "amended message");
+}
+
+#[test]
+fn commit_amend_includes_new_staged_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("new.txt"), "new content").unwrap();
+    backend.stage(Path::new("new.txt")).unwrap();
+
+    backend
+        .commit("amend with new file", true, false, false)
+        .unwrap();
+
+    let detail = backend.get_head_commit_message().unwrap();
+    assert_eq!(detail.trim(), "amend with new file");
+}
+
+#[test]
+fn get_head_commit_message_returns_message() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let msg = backend.get_head_commit_message().unwrap();
+    assert_eq!(msg.trim(), "initial commit");
+}
+
+// === Stash tests ===
+
+#[test]
+fn stash_save_and_list() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    // Create a modification to stash
+    fs::write(tmp.path().join("init.txt"), "modified").unwrap();
+
+    backend
+        .stash_save(Some("test stash"), &StashOptions::default())
+        .unwrap();
+
+    let stashes = backend.stash_list().unwrap();
+    assert_eq!(stashes.len(), 1);
+    assert_eq!(stashes[0].index, 0);
+    assert!(stashes[0].message.contains("test stash"));
+}
+
+#[test]
+fn stash_list_empty() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let stashes = backend.stash_list().unwrap();
+    assert!(stashes.is_empty());
+}
+
+#[test]
+fn stash_apply_restores_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("init.txt"), "modified").unwrap();
+    backend
+        .stash_save(Some("to apply"), &StashOptions::default())
+        .unwrap();
+
+    // Working tree should be clean after stash
+    let status = backend.status().unwrap();
+    assert!(status.files.is_empty());
+
+    backend.stash_apply(0, false).unwrap();
+
+    // Changes should be restored
+    let status = backend.status().unwrap();
+    assert!(!status.files.is_empty());
+
+    // Stash should still exist after apply
+    let stashes = backend.stash_list().unwrap();
+    assert_eq!(stashes.len(), 1);
+}
+
+#[test]
+fn stash_pop_restores_and_removes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("init.txt"), "modified").unwrap();
+    backend
+        .stash_save(Some("to pop"), &StashOptions::default())
+        .unwrap();
+
+    backend.stash_pop(0, false).unwrap();
+
+    // Changes should be restored
+    let status = backend.status().unwrap();
+    assert!(!status.files.is_empty());
+
+    // Stash should be removed after pop
+    let stashes = backend.stash_list().unwrap();
+    assert!(stashes.is_empty());
+}
+
+#[test]
+fn stash_drop_removes_stash() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("init.txt"), "modified").unwrap();
+    backend
+        .stash_save(Some("to drop"), &StashOptions::default())
+        .unwrap();
+
+    backend.stash_drop(0).unwrap();
+
+    let stashes = backend.stash_list().unwrap();
+    assert!(stashes.is_empty());
+}
+
+#[test]
+fn stash_diff_returns_file_diffs() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("init.txt"), "modified content").unwrap();
+    backend
+        .stash_save(Some("diff test"), &StashOptions::default())
+        .unwrap();
+
+    let diffs = backend.stash_diff(0).unwrap();
+    assert!(!diffs.is_empty());
+    assert!(!diffs[0].hunks.is_empty());
+}
+
+#[test]
+fn stash_branch_name_parsed_from_message() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("init.txt"), "modified").unwrap();
+    backend.stash_save(None, &StashOptions::default()).unwrap();
+
+    let stashes = backend.stash_list().unwrap();
+    assert_eq!(stashes.len(), 1);
+    // Default stash message is "WIP on <branch>: ..." so branch_name should match current branch
+    let current = backend.current_branch().unwrap();
+    assert_eq!(stashes[0].branch_name, current);
+}
+
+#[test]
+fn stash_save_only_selected_paths() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("other.txt"), "base").unwrap();
+    backend.stage(Path::new("other.txt")).unwrap();
+    backend.commit("add other", false, false, false).unwrap();
+    fs::write(tmp.path().join("init.txt"), "modified").unwrap();
+    fs::write(tmp.path().join("other.txt"), "experiment").unwrap();
+
+    let options = StashOptions {
+        paths: vec!["other.txt".to_string()],
+        ..StashOptions::default()
+    };
+    backend.stash_save(Some("experiment"), &options).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("other.txt")).unwrap(),
+        "base"
+    );
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("init.txt")).unwrap(),
+        "modified"
+    );
+    let stashes = backend.stash_list().unwrap();
+    assert_eq!(stashes.len(), 1);
+    assert!(stashes[0].message.ends_with(": experiment"));
+
+    fs::write(tmp.path().join("init.txt"), "init").unwrap();
+    backend.stash_pop(0, false).unwrap();
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("other.txt")).unwrap(),
+        "experiment"
+    );
+}
+
+#[test]
+fn stash_save_includes_untracked_and_ignored_files() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join(".gitignore"), "*.log\n").unwrap();
+    backend.stage(Path::new(".gitignore")).unwrap();
+    backend.commit("ignore logs", false, false, false).unwrap();
+    fs::write(tmp.path().join("new.txt"), "untracked").unwrap();
+    fs::write(tmp.path().join("debug.log"), "ignored").unwrap();
+
+    let untracked = StashOptions {
+        include_untracked: true,
+        ..StashOptions::default()
+    };
+    backend.stash_save(None, &untracked).unwrap();
+    assert!(!tmp.path().join("new.txt").exists());
+    assert!(tmp.path().join("debug.log").exists());
+
+    let ignored = StashOptions {
+        include_ignored: true,
+        ..StashOptions::default()
+    };
+    backend.stash_save(None, &ignored).unwrap();
+    assert!(!tmp.path().join("debug.log").exists());
+
+    backend.stash_pop(0, false).unwrap();
+    backend.stash_pop(0, false).unwrap();
+    assert!(tmp.path().join("new.txt").exists());
+    assert!(tmp.path().join("debug.log").exists());
+}
+
+#[test]
+fn stash_save_keep_index_leaves_staged_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("init.txt"), "staged").unwrap();
+    backend.stage(Path::new("init.txt")).unwrap();
+
+    let options = StashOptions {
+        keep_index: true,
+        ..StashOptions::default()
+    };
+    backend.stash_save(None, &options).unwrap();
+
+    let status = backend.status().unwrap();
+    assert!(status
+        .files
+        .iter()
+        .any(|f| f.path == "init.txt" && f.staging == StagingState::Staged));
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("init.txt")).unwrap(),
+        "staged"
+    );
+    assert_eq!(backend.stash_list().unwrap().len(), 1);
+}
+
+#[test]
+fn stash_save_only_selected_hunks() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let original: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+    fs::write(tmp.path().join("lines.txt"), &original).unwrap();
+    backend.stage(Path::new("lines.txt")).unwrap();
+    backend.commit("add lines", false, false, false).unwrap();
+    let both = original
+        .replace("line 2\n", "keep 2\n")
+        .replace("line 18\n", "shelve 18\n");
+    fs::write(tmp.path().join("lines.txt"), &both).unwrap();
+
+    let diffs = backend
+        .diff(Some(Path::new("lines.txt")), &DiffOptions::default())
+        .unwrap();
+    assert_eq!(diffs[0].hunks.len(), 2);
+    let hunk = &diffs[0].hunks[1];
+    let options = StashOptions {
+        hunks: vec![StashHunk {
+            path: "lines.txt".to_string(),
+            hunk: HunkIdentifier {
+                old_start: hunk.old_start,
+                old_lines: hunk.old_lines,
+                new_start: hunk.new_start,
+                new_lines: hunk.new_lines,
+            },
+        }],
+        ..StashOptions::default()
+    };
+    backend.stash_save(Some("shelve"), &options).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("lines.txt")).unwrap(),
+        original.replace("line 2\n", "keep 2\n")
+    );
+    let stashes = backend.stash_list().unwrap();
+    assert_eq!(stashes.len(), 1);
+    assert!(stashes[0].message.ends_with(": shelve"));
+
+    // Like `git stash --patch`, popping needs the file clean again.
+    backend.stage(Path::new("lines.txt")).unwrap();
+    backend.commit("keep", false, false, false).unwrap();
+    backend.stash_pop(0, false).unwrap();
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("lines.txt")).unwrap(),
+        both
+    );
+}
+
+#[test]
+fn stash_save_rejects_hunks_with_paths() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("init.txt"), "modified").unwrap();
+    let options = StashOptions {
+        paths: vec!["init.txt".to_string()],
+        hunks: vec![StashHunk {
+            path: "init.txt".to_string(),
+            hunk: HunkIdentifier {
+                old_start: 1,
+                old_lines: 1,
+                new_start: 1,
+                new_lines: 1,
+            },
+        }],
+        ..StashOptions::default()
+    };
+
+    let result = backend.stash_save(None, &options);
+
+    assert!(matches!(result, Err(GitError::StashFailed(_))));
+    assert!(backend.stash_list().unwrap().is_empty());
+}
+
+#[test]
+fn stash_apply_restores_index() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("init.txt"), "staged").unwrap();
+    backend.stage(Path::new("init.txt")).unwrap();
+    backend.stash_save(None, &StashOptions::default()).unwrap();
+
+    backend.stash_apply(0, true).unwrap();
+
+    let status = backend.status().unwrap();
+    assert!(status
+        .files
+        .iter()
+        .any(|f| f.path == "init.txt" && f.staging == StagingState::Staged));
+}
+
+#[test]
+fn stash_branch_checks_out_stash_base() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let base = backend.get_commit_log(&LogFilter::default(), 1, 0).unwrap();
+    let base_oid = base.commits[0].oid.clone();
+    fs::write(tmp.path().join("init.txt"), "stashed").unwrap();
+    backend.stage(Path::new("init.txt")).unwrap();
+    backend
+        .stash_save(Some("wip"), &StashOptions::default())
+        .unwrap();
+    fs::write(tmp.path().join("later.txt"), "later").unwrap();
+    backend.stage(Path::new("later.txt")).unwrap();
+    backend.commit("later", false, false, false).unwrap();
+
+    backend.stash_branch(0, "from-stash").unwrap();
+
+    assert_eq!(backend.current_branch().unwrap(), "from-stash");
+    let head = backend.get_commit_log(&LogFilter::default(), 1, 0).unwrap();
+    assert_eq!(head.commits[0].oid, base_oid);
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("init.txt")).unwrap(),
+        "stashed"
+    );
+    assert!(!tmp.path().join("later.txt").exists());
+    assert!(backend.stash_list().unwrap().is_empty());
+    let status = backend.status().unwrap();
+    assert!(status
+        .files
+        .iter()
+        .any(|f| f.path == "init.txt" && f.staging == StagingState::Staged));
+}
+
+#[test]
+fn stash_detail_separates_staged_unstaged_and_untracked_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let head = backend.get_commit_log(&LogFilter::default(), 1, 0).unwrap();
+    fs::write(tmp.path().join("init.txt"), "staged\n").unwrap();
+    backend.stage(Path::new("init.txt")).unwrap();
+    fs::write(tmp.path().join("init.txt"), "unstaged\n").unwrap();
+    fs::write(tmp.path().join("new.txt"), "untracked").unwrap();
+    let options = StashOptions {
+        include_untracked: true,
+        ..StashOptions::default()
+    };
+    backend.stash_save(Some("detail"), &options).unwrap();
+
+    let detail = backend.stash_detail(0).unwrap();
+
+    assert!(detail.entry.message.ends_with(": detail"));
+    assert_eq!(detail.base_oid, head.commits[0].oid);
+    assert_eq!(detail.base_summary, "initial commit");
+    let paths = |diffs: &[app_lib::git::types::FileDiff]| {
+        diffs
+            .iter()
+            .map(|d| d.new_path.clone().unwrap_or_default())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(paths(&detail.index_changes), vec!["init.txt"]);
+    assert_eq!(paths(&detail.worktree_changes), vec!["init.txt"]);
+    assert_eq!(paths(&detail.untracked_files), vec!["new.txt"]);
+    let added: Vec<&str> = detail.worktree_changes[0].hunks[0]
+        .lines
+        .iter()
+        .filter(|l| l.kind == DiffLineKind::Addition)
+        .map(|l| l.content.trim_end())
+        .collect();
+    assert_eq!(added, vec!["unstaged"]);
+}
+
+#[test]
+fn stash_detail_without_untracked_files() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("init.txt"), "modified").unwrap();
+    backend.stash_save(None, &StashOptions::default()).unwrap();
+
+    let detail = backend.stash_detail(0).unwrap();
+
+    assert!(detail.index_changes.is_empty());
+    assert_eq!(detail.worktree_changes.len(), 1);
+    assert!(detail.untracked_files.is_empty());
+}
+
+#[test]
+fn stash_rename_keeps_position() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("init.txt"), "first").unwrap();
+    backend
+        .stash_save(Some("first"), &StashOptions::default())
+        .unwrap();
+    fs::write(tmp.path().join("init.txt"), "second").unwrap();
+    backend
+        .stash_save(Some("second"), &StashOptions::default())
+        .unwrap();
+    let branch = backend.current_branch().unwrap();
+
+    backend.stash_rename(1, "renamed").unwrap();
+
+    let stashes = backend.stash_list().unwrap();
+    assert_eq!(stashes.len(), 2);
+    assert_eq!(stashes[0].message, format!("On {branch}: second"));
+    assert_eq!(stashes[1].message, format!("On {branch}: renamed"));
+    assert_eq!(stashes[1].branch_name, branch);
+    backend.stash_pop(1, false).unwrap();
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("init.txt")).unwrap(),
+        "first"
+    );
+}
+
+#[test]
+fn stash_apply_reports_conflicts() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("init.txt"), "stashed").unwrap();
+    backend.stash_save(None, &StashOptions::default()).unwrap();
+    fs::write(tmp.path().join("init.txt"), "committed").unwrap();
+    backend.stage(Path::new("init.txt")).unwrap();
+    backend.commit("change init", false, false, false).unwrap();
+
+    let result = backend.stash_pop(0, false).unwrap();
+
+    assert!(!result.completed);
+    assert_eq!(result.conflicts, vec!["init.txt"]);
+    assert!(fs::read_to_string(tmp.path().join("init.txt"))
+        .unwrap()
+        .contains("<<<<<<<"));
+    // A conflicted pop keeps the stash.
+    assert_eq!(backend.stash_list().unwrap().len(), 1);
+}
+
+#[test]
+fn stash_apply_reports_completion() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("init.txt"), "modified").unwrap();
+    backend.stash_save(None, &StashOptions::default()).unwrap();
+
+    let result = backend.stash_apply(0, false).unwrap();
+
+    assert!(result.completed);
+    assert!(result.conflicts.is_empty());
+}
+
+// === Tag tests ===
+
+#[test]
+fn list_tags_empty() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let tags = backend.list_tags().unwrap();
+    assert!(tags.is_empty());
+}
+
+#[test]
+fn create_lightweight_tag_and_list() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    backend
+        .create_tag("v0.1.0", None, &CreateTagOptions::default())
+        .unwrap();
+
+    let tags = backend.list_tags().unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].name, "v0.1.0");
+    assert!(!tags[0].is_annotated);
+    assert!(tags[0].message.is_none());
+}
+
+#[test]
+fn create_annotated_tag_and_list() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    backend
+        .create_tag("v1.0.0", Some("Release 1.0"), &CreateTagOptions::default())
+        .unwrap();
+
+    let tags = backend.list_tags().unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].name, "v1.0.0");
+    assert!(tags[0].is_annotated);
+    assert_eq!(tags[0].message.as_deref(), Some("Release 1.0"));
+}
+
+#[test]
+fn delete_tag_removes_it() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    backend
+        .create_tag("to-delete", None, &CreateTagOptions::default())
+        .unwrap();
+    assert_eq!(backend.list_tags().unwrap().len(), 1);
+
+    backend.delete_tag("to-delete").unwrap();
+    assert!(backend.list_tags().unwrap().is_empty());
+}
+
+#[test]
+fn checkout_tag_detaches_head() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    backend
+        .create_tag("v0.1.0", None, &CreateTagOptions::default())
+        .unwrap();
+    backend.checkout_tag("v0.1.0").unwrap();
+
+    // HEAD should be detached, so current_branch returns an error or "HEAD"
+    let branch = backend.current_branch();
+    assert!(branch.is_err() || branch.unwrap().contains("HEAD"));
+}
+
+#[test]
+fn create_multiple_tags_and_list() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    backend
+        .create_tag("alpha", None, &CreateTagOptions::default())
+        .unwrap();
+    backend
+        .create_tag("beta", Some("Beta release"), &CreateTagOptions::default())
+        .unwrap();
+
+    let tags = backend.list_tags().unwrap();
+    assert_eq!(tags.len(), 2);
+
+    let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+    assert!(names.contains(&"alpha"));
+    assert!(names.contains(&"beta"));
+}
+
+#[test]
+fn create_tag_on_target_commit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    let first = backend.commit("first", false, false, false).unwrap().oid;
+    fs::write(tmp.path().join("b.txt"), "b").unwrap();
+    backend.stage(Path::new("b.txt")).unwrap();
+    backend.commit("second", false, false, false).unwrap();
+
+    let options = CreateTagOptions {
+        target: Some("HEAD~1".to_string()),
+        ..CreateTagOptions::default()
+    };
+    backend.create_tag("light", None, &options).unwrap();
+    backend
+        .create_tag("annotated", Some("first"), &options)
+        .unwrap();
+
+    let tags = backend.list_tags().unwrap();
+    assert!(tags.iter().all(|t| t.target_oid == first));
+    assert!(tags
+        .iter()
+        .all(|t| t.signature_status == SignatureStatus::None));
+
+    let options = CreateTagOptions {
+        target: Some("no-such-rev".to_string()),
+        ..CreateTagOptions::default()
+    };
+    assert!(backend.create_tag("bad", None, &options).is_err());
+}
+
+#[test]
+fn list_tags_sorted_by_version() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    for name in ["v1.10.0", "v1.2.0", "v1.2.0-rc.1", "v1.9.1", "nightly"] {
+        backend
+            .create_tag(name, None, &CreateTagOptions::default())
+            .unwrap();
+    }
+
+    let names: Vec<String> = backend
+        .list_tags()
+        .unwrap()
+        .into_iter()
+        .map(|t| t.name)
+        .collect();
+    assert_eq!(
+        names,
+        vec!["v1.2.0-rc.1", "v1.2.0", "v1.9.1", "v1.10.0", "nightly"]
+    );
+}
+
+#[test]
+fn create_signed_tag_requires_message() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let options = CreateTagOptions {
+        sign: true,
+        ..CreateTagOptions::default()
+    };
+    assert!(backend.create_tag("v1.0", None, &options).is_err());
+    assert!(backend.list_tags().unwrap().is_empty());
+}
+
+/// Configure SSH signing with a fresh key trusted for `test@example.com`.
+fn configure_ssh_signing(dir: &Path) {
+    let key = dir.join(".ssh-key");
+    Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+        .arg(&key)
+        .output()
+        .expect("ssh-keygen failed");
+    let public_key = fs::read_to_string(key.with_extension("pub")).unwrap();
+    let allowed_signers = dir.join(".allowed-signers");
+    fs::write(
+        &allowed_signers,
+        format!("test@example.com {}", public_key.trim()),
+    )
+    .unwrap();
+
+    for (name, value) in [
+        ("gpg.format", "ssh".to_string()),
+        ("user.signingKey", key.to_string_lossy().to_string()),
+        (
+            "gpg.ssh.allowedSignersFile",
+            allowed_signers.to_string_lossy().to_string(),
+        ),
+    ] {
+        Command::new("git")
+            .args(["config", name, &value])
+            .current_dir(dir)
+            .output()
+            .expect("git config failed");
+    }
+}
+
+#[test]
+fn create_signed_tag_is_verified() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    configure_ssh_signing(tmp.path());
+
+    let options = CreateTagOptions {
+        sign: true,
+        ..CreateTagOptions::default()
+    };
+    backend
+        .create_tag("v1.0", Some("Release 1.0\n"), &options)
+        .unwrap();
+
+    let output = Command::new("git")
+        .args(["verify-tag", "v1.0"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let tags = backend.list_tags().unwrap();
+    assert!(tags[0].is_annotated);
+    assert_eq!(tags[0].message.as_deref(), Some("Release 1.0\n"));
+    assert_eq!(tags[0].signature_status, SignatureStatus::Good);
+}
+
+#[test]
+fn push_and_delete_tag_on_remote() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    backend
+        .create_tag("v1.0", Some("Release"), &CreateTagOptions::default())
+        .unwrap();
+
+    let options = PushOptions {
+        tag: Some("v1.0".to_string()),
+        ..PushOptions::default()
+    };
+    backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    assert!(remote_ref_oid(tmp.path(), "refs/tags/v1.0").is_some());
+
+    let options = PushOptions {
+        delete_tag: Some("v1.0".to_string()),
+        ..PushOptions::default()
+    };
+    let result = backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    assert_eq!(result.updates[0].status, PushRefStatus::Deleted);
+    assert!(remote_ref_oid(tmp.path(), "refs/tags/v1.0").is_none());
+    assert_eq!(backend.list_tags().unwrap().len(), 1);
+}
+
+// === Release notes tests ===
+
+#[test]
+fn release_notes_group_commits_since_tag() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend
+        .create_tag(
+            "v1.0.0",
+            Some("Release 1.0.0"),
+            &CreateTagOptions::default(),
+        )
+        .unwrap();
+    commit_content(
+        tmp.path(),
+        &backend,
+        "search.txt",
+        "search.txt",
+        "feat(api): add search (#4)",
+    );
+    commit_content(
+        tmp.path(),
+        &backend,
+        "empty.txt",
+        "empty.txt",
+        "fix: handle empty input",
+    );
+    commit_content(
+        tmp.path(),
+        &backend,
+        "config.txt",
+        "config.txt",
+        "refactor!: rename config\n\nBREAKING CHANGE: config.toml is now settings.toml",
+    );
+    commit_content(
+        tmp.path(),
+        &backend,
+        "notes.txt",
+        "notes.txt",
+        "Update notes",
+    );
+
+    let options = ReleaseNotesOptions {
+        from: Some("v1.0.0".to_string()),
+        to: None,
+    };
+    let notes = backend.release_notes(&options).unwrap();
+
+    assert_eq!(notes.from.as_deref(), Some("v1.0.0"));
+    assert_eq!(notes.to, "HEAD");
+    let sections: Vec<(&str, usize)> = notes
+        .sections
+        .iter()
+        .map(|s| (s.kind.as_str(), s.entries.len()))
+        .collect();
+    assert_eq!(
+        sections,
+        vec![("feat", 1), ("fix", 1), ("refactor", 1), ("other", 1)]
+    );
+    assert_eq!(notes.sections[0].entries[0].scope.as_deref(), Some("api"));
+    assert_eq!(notes.breaking_changes.len(), 1);
+    assert_eq!(
+        notes.breaking_changes[0].description,
+        "config.toml is now settings.toml"
+    );
+    assert_eq!(notes.contributors.len(), 1);
+    assert_eq!(notes.contributors[0].commits, 4);
+    assert_eq!(notes.references, vec![4]);
+}
+
+#[test]
+fn release_notes_without_start_cover_whole_history() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    commit_content(
+        tmp.path(),
+        &backend,
+        "a.txt",
+        "a.txt",
+        "feat: first feature",
+    );
+
+    let notes = backend
+        .release_notes(&ReleaseNotesOptions::default())
+        .unwrap();
+    let entries: usize = notes.sections.iter().map(|s| s.entries.len()).sum();
+    assert_eq!(entries, 2);
+
+    let options = ReleaseNotesOptions {
+        from: Some("no-such-tag".to_string()),
+        to: None,
+    };
+    assert!(backend.release_notes(&options).is_err());
+}
+
+// === Commit lint tests ===
+
+#[test]
+fn commit_rejected_by_commit_lint_config() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(
+        tmp.path().join(".commitlint.toml"),
+        "types = [\"feat\", \"fix\"]\nrequired_trailers = [\"Signed-off-by\"]\n",
+    )
+    .unwrap();
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    let head = backend.get_head_commit_message().unwrap();
+
+    let err = backend.commit("add a", false, false, false).unwrap_err();
+    assert!(matches!(err, GitError::CommitLintFailed(_)));
+    assert!(err.to_string().contains("Signed-off-by"));
+    assert_eq!(backend.get_head_commit_message().unwrap(), head);
+
+    // amend も同じルールで検査する
+    assert!(backend.commit("add a", true, false, false).is_err());
+
+    backend
+        .commit(
+            "feat: add a\n\nSigned-off-by: Test User <test@example.com>",
+            false,
+            false,
+            false,
+        )
+        .unwrap();
+    assert!(backend
+        .get_head_commit_message()
+        .unwrap()
+        .starts_with("feat: add a"));
+}
+
+#[test]
+fn commit_lint_warnings_do_not_block_commit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(
+        tmp.path().join(".commitlint.toml"),
+        "subject_max_length = 10\nwarnings = [\"subject_max_length\"]\n",
+    )
+    .unwrap();
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+
+    backend
+        .commit("a subject longer than ten", false, false, false)
+        .unwrap();
+}
+
+#[test]
+fn commit_fails_with_invalid_commit_lint_config() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join(".commitlint.toml"), "types = \"feat\"\n").unwrap();
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+
+    let result = backend.commit("feat: add a", false, false, false);
+    assert!(matches!(result, Err(GitError::ConfigReadFailed(_))));
+}
+
+// === Hook tests ===
+
+fn install_hook(dir: &Path, name: &str, script: &str) {
+    let hooks = dir.join(".git").join("hooks");
+    fs::create_dir_all(&hooks).unwrap();
+    let path = hooks.join(name);
+    fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+}
+
+fn hook_error(err: GitError) -> HookError {
+    match err {
+        GitError::HookRejected(e) => e.downcast_ref::<HookError>().unwrap().clone(),
+        other => panic!("expected a hook rejection, got {other}"),
+    }
+}
+
+#[test]
+fn commit_rejected_by_pre_commit_hook_reports_output() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    install_hook(
+        tmp.path(),
+        "pre-commit",
+        "echo scanning\necho 'secret found' >&2\nexit 3",
+    );
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+
+    let err = hook_error(backend.commit("add a", false, false, false).unwrap_err());
+    assert_eq!(err.hook, "pre-commit");
+    assert_eq!(err.exit_code, Some(3));
+    assert_eq!(err.stdout.trim(), "scanning");
+    assert_eq!(err.stderr.trim(), "secret found");
+    assert_eq!(backend.get_head_commit_message().unwrap(), "initial commit");
+
+    // skip_hooks は --no-verify と同じく pre-commit を飛ばす
+    backend.commit("add a", false, false, true).unwrap();
+    assert_eq!(backend.get_head_commit_message().unwrap(), "add a");
+}
+
+#[test]
+fn commit_rejected_by_commit_lint_runs_no_hooks() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join(".commitlint.toml"), "conventional = true\n").unwrap();
+    install_hook(tmp.path(), "pre-commit", "touch pre-commit.txt");
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+
+    let result = backend.commit("add a", false, false, false);
+
+    assert!(matches!(result, Err(GitError::CommitLintFailed(_))));
+    assert!(!tmp.path().join("pre-commit.txt").exists());
+}
+
+#[test]
+fn commit_message_hooks_can_edit_message() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    install_hook(
+        tmp.path(),
+        "prepare-commit-msg",
+        "echo \"$2 $3\" > prepare-args.txt",
+    );
+    install_hook(
+        tmp.path(),
+        "commit-msg",
+        "printf '\\nSigned-off-by: Hook <hook@example.com>\\n' >> \"$1\"",
+    );
+    install_hook(tmp.path(), "post-commit", "touch post-commit.txt");
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+
+    backend.commit("add a", false, false, false).unwrap();
+
+    let message = backend.get_head_commit_message().unwrap();
+    assert!(message.starts_with("add a"));
+    assert!(message.contains("Signed-off-by: Hook <hook@example.com>"));
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("prepare-args.txt"))
+            .unwrap()
+            .trim(),
+        "message"
+    );
+    assert!(tmp.path().join("post-commit.txt").exists());
+
+    backend.commit("amended", true, false, true).unwrap();
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("prepare-args.txt"))
+            .unwrap()
+            .trim(),
+        "commit HEAD"
+    );
+    assert!(!backend
+        .get_head_commit_message()
+        .unwrap()
+        .contains("Signed-off-by"));
+}
+
+#[test]
+fn commit_uses_core_hooks_path() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let hooks = tmp.path().join("custom-hooks");
+    fs::create_dir(&hooks).unwrap();
+    fs::write(hooks.join("pre-commit"), "#!/bin/sh\nexit 1\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(hooks.join("pre-commit"), fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    run_git(tmp.path(), &["config", "core.hooksPath", "custom-hooks"]);
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+
+    let err = hook_error(backend.commit("add a", false, false, false).unwrap_err());
+    assert_eq!(err.hook, "pre-commit");
+}
+
+#[test]
+fn merge_rejected_by_pre_merge_commit_hook_stays_in_progress() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let default_branch = backend.current_branch().unwrap();
+    backend.create_branch("feature").unwrap();
+    backend.checkout_branch("feature").unwrap();
+    commit_content(
+        tmp.path(),
+        &backend,
+        "feature.txt",
+        "feature.txt",
+        "feature work",
+    );
+    backend.checkout_branch(&default_branch).unwrap();
+    commit_content(tmp.path(), &backend, "main.txt", "main.txt", "main work");
+    install_hook(
+        tmp.path(),
+        "pre-merge-commit",
+        "echo 'merge blocked' >&2\nexit 1",
+    );
+    install_hook(tmp.path(), "post-merge", "touch post-merge.txt");
+
+    let err = hook_error(
+        backend
+            .merge_branch("feature", MergeOption::Default)
+            .unwrap_err(),
+    );
+    assert_eq!(err.hook, "pre-merge-commit");
+    assert_eq!(err.stderr.trim(), "merge blocked");
+    assert!(backend.is_merging().unwrap());
+    assert!(!tmp.path().join("post-merge.txt").exists());
+
+    // マージの続行は git commit と同じく pre-commit を実行する
+    backend.continue_merge("").unwrap();
+    assert!(!backend.is_merging().unwrap());
+    assert!(tmp.path().join("feature.txt").exists());
+}
+
+#[test]
+fn cherry_pick_and_revert_run_commit_hooks() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, feature_oid) = setup_cherry_pick_repo(tmp.path());
+    install_hook(tmp.path(), "commit-msg", "exit 1");
+
+    let err = hook_error(
+        backend
+            .cherry_pick(&[&feature_oid], CherryPickMode::Normal)
+            .unwrap_err(),
+    );
+    assert_eq!(err.hook, "commit-msg");
+    assert!(backend.is_cherry_picking().unwrap());
+    backend.abort_cherry_pick().unwrap();
+
+    install_hook(
+        tmp.path(),
+        "commit-msg",
+        "printf '\\nReviewed-by: Hook\\n' >> \"$1\"",
+    );
+    let result = backend
+        .cherry_pick(&[&feature_oid], CherryPickMode::Normal)
+        .unwrap();
+    assert!(result.completed);
+    assert!(backend
+        .get_head_commit_message()
+        .unwrap()
+        .contains("Reviewed-by: Hook"));
+
+    let head = result.oid.unwrap();
+    let result = backend.revert(&head, RevertMode::Auto).unwrap();
+    assert!(result.completed);
+    let message = backend.get_head_commit_message().unwrap();
+    assert!(message.starts_with("Revert"));
+    assert!(message.contains("Reviewed-by: Hook"));
+}
+
+#[test]
+fn push_rejected_by_pre_push_hook() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    let local = tmp.path().join("local");
+    let branch = backend.current_branch().unwrap();
+    install_hook(
+        &local,
+        "pre-push",
+        "echo \"$1\" > pre-push.txt\ncat >> pre-push.txt\nexit 1",
+    );
+
+    let err = hook_error(
+        backend
+            .push(
+                "origin",
+                &PushOptions::default(),
+                &TransferControl::default(),
+            )
+            .unwrap_err(),
+    );
+    assert_eq!(err.hook, "pre-push");
+    let head = backend
+        .get_commit_log(&LogFilter::default(), 1, 0)
+        .unwrap()
+        .commits[0]
+        .oid
+        .clone();
+    let input = fs::read_to_string(local.join("pre-push.txt")).unwrap();
+    assert_eq!(
+        input,
+        format!(
+            "origin\nrefs/heads/{branch} {head} refs/heads/{branch} {}\n",
+            "0".repeat(40)
+        )
+    );
+    assert!(remote_ref_oid(tmp.path(), &format!("refs/heads/{branch}")).is_none());
+
+    let options = PushOptions {
+        no_verify: true,
+        ..PushOptions::default()
+    };
+    backend
+        .push("origin", &options, &TransferControl::default())
+        .unwrap();
+    assert!(remote_ref_oid(tmp.path(), &format!("refs/heads/{branch}")).is_some());
+}
+
+// === Conflict tests ===
+
+/// Set up a repo with two branches that conflict on the same file.
+/// Returns (backend, default_branch_name).
+fn setup_conflict_repo(dir: &Path) -> (TestBackend, String) {
+    let backend = init_repo_with_commit(dir);
+    let default_branch = backend.current_branch().unwrap();
+
+    // Create shared file on default branch
+    fs::write(dir.join("shared.txt"), "line1\nline2\nline3\n").unwrap();
+    backend.stage(Path::new("shared.txt")).unwrap();
+    backend.commit("add shared", false, false, false).unwrap();
+
+    // Create feature branch and modify shared.txt
+    backend.create_branch("conflict-branch").unwrap();
+    backend.checkout_branch("conflict-branch").unwrap();
+    fs::write(dir.join("shared.txt"), "line1\nfeature-change\nline3\n").unwrap();
+    backend.stage(Path::new("shared.txt")).unwrap();
+    backend
+        .commit("feature change", false, false, false)
+        .unwrap();
+
+    // Go back to default branch and make a conflicting change
+    backend.checkout_branch(&default_branch).unwrap();
+    fs::write(dir.join("shared.txt"), "line1\nmain-change\nline3\n").unwrap();
+    backend.stage(Path::new("shared.txt")).unwrap();
+    backend.commit("main change", false, false, false).unwrap();
+
+    (backend, default_branch)
+}
+
+#[test]
+fn merge_branch_conflict_detection() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+
+    let result = backend
+        .merge_branch("conflict-branch", MergeOption::Default)
+        .unwrap();
+
+    assert_eq!(result.kind, app_lib::git::types::MergeKind::Conflict);
+    assert!(result.oid.is_none());
+    assert!(!result.conflicts.is_empty());
+    assert!(result.conflicts.contains(&"shared.txt".to_string()));
+}
+
+#[test]
+fn get_conflict_files_returns_conflict_blocks() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+
+    let conflict_files = backend.get_conflict_files().unwrap();
+    assert!(!conflict_files.is_empty());
+
+    let file = &conflict_files[0];
+    assert_eq!(file.path, "shared.txt");
+    assert!(file.conflict_count > 0);
+    assert!(!file.conflicts.is_empty());
+
+    let block = &file.conflicts[0];
+    assert!(!block.ours.is_empty());
+    assert!(!block.theirs.is_empty());
+}
+
+#[test]
+fn resolve_conflict_ours() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+
+    backend
+        .resolve_conflict("shared.txt", ConflictResolution::Ours)
+        .unwrap();
+
+    let content = fs::read_to_string(tmp.path().join("shared.txt")).unwrap();
+    assert!(content.contains("main-change"));
+    assert!(!content.contains("<<<<<<<"));
+}
+
+#[test]
+fn resolve_conflict_theirs() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+
+    backend
+        .resolve_conflict("shared.txt", ConflictResolution::Theirs)
+        .unwrap();
+
+    let content = fs::read_to_string(tmp.path().join("shared.txt")).unwrap();
+    assert!(content.contains("feature-change"));
+    assert!(!content.contains("<<<<<<<"));
+}
+
+#[test]
+fn resolve_conflict_both() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+
+    backend
+        .resolve_conflict("shared.txt", ConflictResolution::Both)
+        .unwrap();
+
+    let content = fs::read_to_string(tmp.path().join("shared.txt")).unwrap();
+    assert!(content.contains("main-change"));
+    assert!(content.contains("feature-change"));
+    assert!(!content.contains("<<<<<<<"));
+}
+
+#[test]
+fn resolve_conflict_manual() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+
+    backend
+        .resolve_conflict(
+            "shared.txt",
+            ConflictResolution::Manual("custom resolution\n".to_string()),
+        )
+        .unwrap();
+
+    let content = fs::read_to_string(tmp.path().join("shared.txt")).unwrap();
+    assert!(content.contains("custom resolution"));
+    assert!(!content.contains("<<<<<<<"));
+}
+
+#[test]
+fn mark_resolved_adds_to_index() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+
+    backend
+        .resolve_conflict("shared.txt", ConflictResolution::Ours)
+        .unwrap();
+    backend.mark_resolved("shared.txt").unwrap();
+
+    let conflict_files = backend.get_conflict_files().unwrap();
+    assert!(
+        conflict_files.is_empty(),
+        "No conflict files should remain after mark_resolved"
+    );
+}
+
+#[test]
+fn abort_merge_cleans_state() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+
+    assert!(backend.is_merging().unwrap());
+
+    backend.abort_merge().unwrap();
+
+    assert!(!backend.is_merging().unwrap());
+    let conflict_files = backend.get_conflict_files().unwrap();
+    assert!(conflict_files.is_empty());
+}
+
+#[test]
+fn continue_merge_creates_commit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+
+    backend
+        .resolve_conflict("shared.txt", ConflictResolution::Ours)
+        .unwrap();
+    backend.mark_resolved("shared.txt").unwrap();
+
+    let result = backend.continue_merge("merge commit message").unwrap();
+    assert!(!result.oid.is_empty());
+    assert!(!backend.is_merging().unwrap());
+}
+
+#[test]
+fn continue_merge_fails_with_unresolved() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+
+    let result = backend.continue_merge("should fail");
+    assert!(result.is_err());
+}
+
+#[test]
+fn continue_merge_checks_commit_lint_rules() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+    backend
+        .resolve_conflict("shared.txt", ConflictResolution::Ours)
+        .unwrap();
+    backend.mark_resolved("shared.txt").unwrap();
+    fs::write(tmp.path().join(".commitlint.toml"), "conventional = true\n").unwrap();
+
+    let result = backend.continue_merge("resolve conflict");
+    assert!(matches!(result, Err(GitError::CommitLintFailed(_))));
+    assert!(backend.is_merging().unwrap());
+
+    // git が生成するマージメッセージは対象外
+    backend
+        .continue_merge("Merge branch 'conflict-branch'")
+        .unwrap();
+    assert!(!backend.is_merging().unwrap());
+}
+
+#[test]
+fn is_merging_returns_true_during_merge() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+
+    assert!(backend.is_merging().unwrap());
+}
+
+#[test]
+fn is_merging_returns_false_normally() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    assert!(!backend.is_merging().unwrap());
+}
+
+#[test]
+fn status_shows_conflicted_files() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+
+    let status = backend.status().unwrap();
+    let conflicted = status
+        .files
+        .iter()
+        .filter(|f| f.kind == app_lib::git::types::FileStatusKind::Conflicted)
+        .count();
+
+    assert!(conflicted > 0, "Should have conflicted files in status");
+}
+
+#[test]
+fn resolve_conflict_block_resolves_single_block() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, _) = setup_conflict_repo(tmp.path());
+    let _ = backend.merge_branch("conflict-branch", MergeOption::Default);
+
+    let conflict_files = backend.get_conflict_files().unwrap();
+    assert!(!conflict_files.is_empty());
+
+    backend
+        .resolve_conflict_block("shared.txt", 0, ConflictResolution::Theirs)
+        .unwrap();
+
+    let content = fs::read_to_string(tmp.path().join("shared.txt")).unwrap();
+    assert!(content.contains("feature-change"));
+    assert!(!content.contains("<<<<<<<"));
+}
+
+// ============================
+// Cherry-pick tests
+// ============================
+
+/// Helper: create a repo with a main branch containing an initial commit,
+/// then create a feature branch with one extra commit, and switch back to main.
+/// Returns (backend, feature_commit_oid).
+fn setup_cherry_pick_repo(dir: &Path) -> (TestBackend, String) {
+    let backend = init_repo_with_commit(dir);
+    let default_branch = backend.current_branch().unwrap();
+
+    // Create feature branch with a new commit
+    backend.create_branch("feature").unwrap();
+    backend.checkout_branch("feature").unwrap();
+
+    fs::write(dir.join("cherry.txt"), "cherry content\n").unwrap();
+    backend.stage(Path::new("cherry.txt")).unwrap();
+    backend
+        .commit("feature: add cherry.txt", false, false, false)
+        .unwrap();
+
+    // Get the OID of the feature commit
+    let log_filter = LogFilter {
+        author: None,
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let log = backend.get_commit_log(&log_filter, 1, 0).unwrap();
+    let feature_oid = log.commits[0].oid.clone();
+
+    // Switch back to main branch
+    backend.checkout_branch(&default_branch).unwrap();
+
+    (backend, feature_oid)
+}
+
+#[test]
+fn cherry_pick_normal_mode() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, feature_oid) = setup_cherry_pick_repo(tmp.path());
+
+    let result = backend
+        .cherry_pick(&[&feature_oid], CherryPickMode::Normal)
+        .unwrap();
+
+    assert!(
+        result.completed,
+        "Cherry-pick should complete without conflicts"
+    );
+    assert!(result.oid.is_some(), "Should produce a new commit OID");
+    assert!(result.conflicts.is_empty(), "Should have no conflicts");
+
+    // Verify the file was applied
+    let content = fs::read_to_string(tmp.path().join("cherry.txt")).unwrap();
+    assert_eq!(content, "cherry content\n");
+
+    // Verify not in cherry-pick state
+    assert!(!backend.is_cherry_picking().unwrap());
+}
+
+#[test]
+fn cherry_pick_no_commit_mode() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (backend, feature_oid) = setup_cherry_pick_repo(tmp.path());
+
+    let result = backend
+        .cherry_pick(&[&feature_oid], CherryPickMode::NoCommit)
+        .unwrap();
+
+    assert!(
+        result.completed,
+        "Cherry-pick should complete without conflicts"
+    );
+    assert!(
+        result.oid.is_none(),
+        "NoCommit mode should not produce a commit OID"
+    );
+
+    // Verify the file is in the worktree
+    let content = fs::read_to_string(tmp.path().join("cherry.txt")).unwrap();
+    assert_eq!(content, "cherry content\n");
+
+    // Verify changes are staged
+    let status = backend.status().unwrap();
+    assert!(
+        status
+            .files
+            .iter()
+            .any(|f| f.path == "cherry.txt"
+                && f.staging == app_lib::git::types::StagingState::Staged),
+        "cherry.txt should be staged"
+    );
+}
+
+#[test]
+fn cherry_pick_conflict_and_abort() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let default_branch = backend.current_branch().unwrap();
+
+    // Create a file on main
+    fs::write(tmp.path().join("conflict.txt"), "main content\n").unwrap();
+    backend.stage(Path::new("conflict.txt")).unwrap();
+    backend
+        .commit("main: add conflict.txt", false, false, false)
+        .unwrap();
+
+    // Create feature branch and modify the same file
+    backend.create_branch("feature").unwrap();
+    backend.checkout_branch("feature").unwrap();
+    fs::write(tmp.path().join("conflict.txt"), "feature content\n").unwrap();
+    backend.stage(Path::new("conflict.txt")).unwrap();
+    backend
+        .commit("feature: modify conflict.txt", false, false, false)
+        .unwrap();
+
+    let log_filter = LogFilter {
+        author: None,
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let log = backend.get_commit_log(&log_filter, 1, 0).unwrap();
+    let feature_oid = log.commits[0].oid.clone();
+
+    // Switch to main and modify the same file differently
+    backend.checkout_branch(&default_branch).unwrap();
+    fs::write(tmp.path().join("conflict.txt"), "different main content\n").unwrap();
+    backend.stage(Path::new("conflict.txt")).unwrap();
+    backend
+        .commit("main: modify conflict.txt differently", false, false, false)
+        .unwrap();
+
+    // Cherry-pick should detect conflicts
+    let result = backend
+        .cherry_pick(&[&feature_oid], CherryPickMode::Normal)
+        .unwrap();
+
+    assert!(!result.completed, "Should have conflicts");
+    assert!(
+        !result.conflicts.is_empty(),
+        "Should list conflicting files"
+    );
+    assert!(
+        backend.is_cherry_picking().unwrap(),
+        "Should be in cherry-pick state"
+    );
+
+    // Abort the cherry-pick
+    backend.abort_cherry_pick().unwrap();
+    assert!(
+        !backend.is_cherry_picking().unwrap(),
+        "Should no longer be in cherry-pick state"
+    );
+
+    // Working directory should be clean (back to main content)
+    let content = fs::read_to_string(tmp.path().join("conflict.txt")).unwrap();
+    assert_eq!(content, "different main content\n");
+}
+
+#[test]
+fn is_cherry_picking_false_by_default() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    assert!(!backend.is_cherry_picking().unwrap());
+}
+
+// ============================
+// Revert tests
+// ============================
+
+#[test]
+fn revert_auto_mode() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    // Create a file and commit
+    fs::write(tmp.path().join("revert_target.txt"), "to be reverted\n").unwrap();
+    backend.stage(Path::new("revert_target.txt")).unwrap();
+    backend
+        .commit("add revert_target.txt", false, false, false)
+        .unwrap();
+
+    // Get the OID of the commit to revert
+    let log_filter = LogFilter {
+        author: None,
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let log = backend.get_commit_log(&log_filter, 1, 0).unwrap();
+    let target_oid = log.commits[0].oid.clone();
+
+    let result = backend.revert(&target_oid, RevertMode::Auto).unwrap();
+
+    assert!(result.completed, "Revert should complete without conflicts");
+    assert!(result.oid.is_some(), "Should produce a new commit OID");
+    assert!(result.conflicts.is_empty(), "Should have no conflicts");
+
+    // The file should be deleted since the commit that added it was reverted
+    assert!(
+        !tmp.path().join("revert_target.txt").exists(),
+        "File should be removed by revert"
+    );
+
+    assert!(!backend.is_reverting().unwrap());
+}
+
+#[test]
+fn revert_no_commit_mode() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    // Create a file and commit
+    fs::write(tmp.path().join("revert_nc.txt"), "no-commit revert\n").unwrap();
+    backend.stage(Path::new("revert_nc.txt")).unwrap();
+    backend
+        .commit("add revert_nc.txt", false, false, false)
+        .unwrap();
+
+    let log_filter = LogFilter {
+        author: None,
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let log = backend.get_commit_log(&log_filter, 1, 0).unwrap();
+    let target_oid = log.commits[0].oid.clone();
+
+    let result = backend.revert(&target_oid, RevertMode::NoCommit).unwrap();
+
+    assert!(result.completed, "Revert should complete without conflicts");
+    assert!(
+        result.oid.is_none(),
+        "NoCommit mode should not produce a commit OID"
+    );
+
+    // File should be removed in worktree
+    assert!(
+        !tmp.path().join("revert_nc.txt").exists(),
+        "File should be removed in worktree"
+    );
+}
+
+#[test]
+fn revert_conflict_and_abort() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    // Create a file and commit
+    fs::write(tmp.path().join("revert_conflict.txt"), "original\n").unwrap();
+    backend.stage(Path::new("revert_conflict.txt")).unwrap();
+    backend
+        .commit("add revert_conflict.txt", false, false, false)
+        .unwrap();
+
+    let log_filter = LogFilter {
+        author: None,
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let log = backend.get_commit_log(&log_filter, 1, 0).unwrap();
+    let add_oid = log.commits[0].oid.clone();
+
+    // Modify the file in a later commit (this will conflict with reverting the addition)
+    fs::write(tmp.path().join("revert_conflict.txt"), "modified content\n").unwrap();
+    backend.stage(Path::new("revert_conflict.txt")).unwrap();
+    backend
+        .commit("modify revert_conflict.txt", false, false, false)
+        .unwrap();
+
+    // Try to revert the original addition commit — should conflict
+    let result = backend.revert(&add_oid, RevertMode::Auto).unwrap();
+
+    assert!(!result.completed, "Revert should have conflicts");
+    assert!(
+        !result.conflicts.is_empty(),
+        "Should list conflicting files"
+    );
+    assert!(backend.is_reverting().unwrap(), "Should be in revert state");
+
+    // Abort
+    backend.abort_revert().unwrap();
+    assert!(
+        !backend.is_reverting().unwrap(),
+        "Should no longer be in revert state"
+    );
+
+    // File should be restored
+    let content = fs::read_to_string(tmp.path().join("revert_conflict.txt")).unwrap();
+    assert_eq!(content, "modified content\n");
+}
+
+#[test]
+fn is_reverting_false_by_default() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    assert!(!backend.is_reverting().unwrap());
+}
+
+#[test]
+fn continue_cherry_pick_after_conflict_resolution() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let default_branch = backend.current_branch().unwrap();
+
+    // Create a file on main
+    fs::write(tmp.path().join("conflict.txt"), "main content\n").unwrap();
+    backend.stage(Path::new("conflict.txt")).unwrap();
+    backend
+        .commit("main: add conflict.txt", false, false, false)
+        .unwrap();
+
+    // Create feature branch and modify the same file
+    backend.create_branch("feature").unwrap();
+    backend.checkout_branch("feature").unwrap();
+    fs::write(tmp.path().join("conflict.txt"), "feature content\n").unwrap();
+    backend.stage(Path::new("conflict.txt")).unwrap();
+    backend
+        .commit("feature: modify conflict.txt", false, false, false)
+        .unwrap();
+
+    let log_filter = LogFilter {
+        author: None,
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let log = backend.get_commit_log(&log_filter, 1, 0).unwrap();
+    let feature_oid = log.commits[0].oid.clone();
+
+    // Switch to main and modify the same file differently
+    backend.checkout_branch(&default_branch).unwrap();
+    fs::write(tmp.path().join("conflict.txt"), "different main content\n").unwrap();
+    backend.stage(Path::new("conflict.txt")).unwrap();
+    backend
+        .commit("main: modify conflict.txt differently", false, false, false)
+        .unwrap();
+
+    // Cherry-pick should detect conflicts
+    let result = backend
+        .cherry_pick(&[&feature_oid], CherryPickMode::Normal)
+        .unwrap();
+    assert!(!result.completed);
+    assert!(backend.is_cherry_picking().unwrap());
+
+    // Resolve the conflict and mark resolved
+    backend
+        .resolve_conflict("conflict.txt", ConflictResolution::Theirs)
+        .unwrap();
+    backend.mark_resolved("conflict.txt").unwrap();
+
+    // Continue cherry-pick
+    let result = backend.continue_cherry_pick().unwrap();
+    assert!(result.completed);
+    assert!(result.oid.is_some());
+    assert!(!backend.is_cherry_picking().unwrap());
+
+    let content = fs::read_to_string(tmp.path().join("conflict.txt")).unwrap();
+    assert_eq!(content, "feature content\n");
+}
+
+#[test]
+fn continue_revert_after_conflict_resolution() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    // Create a file and commit
+    fs::write(tmp.path().join("revert_cont.txt"), "original\n").unwrap();
+    backend.stage(Path::new("revert_cont.txt")).unwrap();
+    backend
+        .commit("add revert_cont.txt", false, false, false)
+        .unwrap();
+
+    let log_filter = LogFilter {
+        author: None,
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let log = backend.get_commit_log(&log_filter, 1, 0).unwrap();
+    let add_oid = log.commits[0].oid.clone();
+
+    // Modify the file in a later commit to cause conflict on revert
+    fs::write(tmp.path().join("revert_cont.txt"), "modified content\n").unwrap();
+    backend.stage(Path::new("revert_cont.txt")).unwrap();
+    backend
+        .commit("modify revert_cont.txt", false, false, false)
+        .unwrap();
+
+    // Revert the original addition — should conflict
+    let result = backend.revert(&add_oid, RevertMode::Auto).unwrap();
+    assert!(!result.completed);
+    assert!(backend.is_reverting().unwrap());
+
+    // Resolve the conflict manually and mark resolved
+    backend
+        .resolve_conflict(
+            "revert_cont.txt",
+            ConflictResolution::Manual("resolved content\n".to_string()),
+        )
+        .unwrap();
+    backend.mark_resolved("revert_cont.txt").unwrap();
+
+    // Continue revert
+    let result = backend.continue_revert().unwrap();
+    assert!(result.completed);
+    assert!(result.oid.is_some());
+    assert!(!backend.is_reverting().unwrap());
+
+    let content = fs::read_to_string(tmp.path().join("revert_cont.txt")).unwrap();
+    assert_eq!(content, "resolved content\n");
+}
+
+// === Reset tests ===
+
+#[test]
+fn reset_soft_moves_head_preserves_staging() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    // Create a second commit
+    fs::write(tmp.path().join("second.txt"), "second").unwrap();
+    backend.stage(Path::new("second.txt")).unwrap();
+    backend
+        .commit("second commit", false, false, false)
+        .unwrap();
+
+    let log_filter = LogFilter {
+        author: None,
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let log = backend.get_commit_log(&log_filter, 2, 0).unwrap();
+    let first_oid = log.commits[1].oid.clone();
+
+    let result = backend.reset(&first_oid, ResetMode::Soft).unwrap();
+    assert_eq!(result.oid, first_oid);
+
+    // second.txt should still exist in working tree
+    assert!(tmp.path().join("second.txt").exists());
+
+    // second.txt should be staged (soft reset keeps index)
+    let status = backend.status().unwrap();
+    let staged_files: Vec<_> = status
+        .files
+        .iter()
+        .filter(|f| f.staging == app_lib::git::types::StagingState::Staged)
+        .collect();
+    assert!(
+        !staged_files.is_empty(),
+        "Soft reset should keep changes staged"
+    );
+}
+
+#[test]
+fn reset_mixed_moves_head_unstages_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("mixed.txt"), "mixed content").unwrap();
+    backend.stage(Path::new("mixed.txt")).unwrap();
+    backend.commit("mixed commit", false, false, false).unwrap();
+
+    let log_filter = LogFilter {
+        author: None,
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let log = backend.get_commit_log(&log_filter, 2, 0).unwrap();
+    let first_oid = log.commits[1].oid.clone();
+
+    let result = backend.reset(&first_oid, ResetMode::Mixed).unwrap();
+    assert_eq!(result.oid, first_oid);
+
+    // File should still exist in working tree
+    assert!(tmp.path().join("mixed.txt").exists());
+
+    // File should be unstaged
+    let status = backend.status().unwrap();
+    let unstaged: Vec<_> = status
+        .files
+        .iter()
+        .filter(|f| {
+            f.path == "mixed.txt" && f.staging == app_lib::git::types::StagingState::Unstaged
+        })
+        .collect();
+    assert!(!unstaged.is_empty(), "Mixed reset should unstage changes");
+}
+
+#[test]
+fn reset_hard_discards_all_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    fs::write(tmp.path().join("hard.txt"), "hard content").unwrap();
+    backend.stage(Path::new("hard.txt")).unwrap();
+    backend.commit("hard commit", false, false, false).unwrap();
+
+    let log_filter = LogFilter {
+        author: None,
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let log = backend.get_commit_log(&log_filter, 2, 0).unwrap();
+    let first_oid = log.commits[1].oid.clone();
+
+    let result = backend.reset(&first_oid, ResetMode::Hard).unwrap();
+    assert_eq!(result.oid, first_oid);
+
+    // File should be removed from working tree
+    assert!(!tmp.path().join("hard.txt").exists());
+}
+
+#[test]
+fn reset_file_restores_file_in_index() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    // Create and commit a file
+    fs::write(tmp.path().join("resetfile.txt"), "original\n").unwrap();
+    backend.stage(Path::new("resetfile.txt")).unwrap();
+    backend
+        .commit("add resetfile", false, false, false)
+        .unwrap();
+
+    let log_filter = LogFilter {
+        author: None,
+        since: None,
+        until: None,
+        message: None,
+        path: None,
+    };
+    let log = backend.get_commit_log(&log_filter, 1, 0).unwrap();
+    let commit_oid = log.commits[0].oid.clone();
+
+    // Modify and stage again
+    fs::write(tmp.path().join("resetfile.txt"), "modified\n").unwrap();
+    backend.stage(Path::new("resetfile.txt")).unwrap();
+
+    // Reset file to original commit
+    backend.reset_file("resetfile.txt", &commit_oid).unwrap();
+
+    // After reset_file, the index should be reverted but working tree should still have modified content
+    let status = backend.status().unwrap();
+    // The file should show as modified in working tree (unstaged)
+    let has_changes = status.files.iter().any(|f| f.path == "resetfile.txt");
+    assert!(has_changes, "File should show changes after reset_file");
+}
+
+// === Operation journal tests ===
+
+fn branch_tip(backend: &TestBackend, branch: &str) -> String {
+    backend.get_branch_commits(branch, 1).unwrap()[0]
+        .oid
+        .clone()
+}
+
+#[test]
+fn undo_without_operations_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    assert!(backend.list_operations().unwrap().is_empty());
+    assert!(matches!(
+        backend.undo_last_operation(),
+        Err(GitError::UndoFailed(_))
+    ));
+}
+
+#[test]
+fn undo_discard_hunk_restores_file_content() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    create_two_hunk_file(tmp.path(), &backend);
+    let before = fs::read_to_string(tmp.path().join("multi.txt")).unwrap();
+
+    let options = DiffOptions {
+        staged: false,
+        ..Default::default()
+    };
+    let diffs = backend
+        .diff(Some(Path::new("multi.txt")), &options)
+        .unwrap();
+    let hunk = &diffs[0].hunks[0];
+    let hunk_id = HunkIdentifier {
+        old_start: hunk.old_start,
+        old_lines: hunk.old_lines,
+        new_start: hunk.new_start,
+        new_lines: hunk.new_lines,
+    };
+    backend
+        .discard_hunk(Path::new("multi.txt"), &hunk_id)
+        .unwrap();
+    assert_ne!(
+        fs::read_to_string(tmp.path().join("multi.txt")).unwrap(),
+        before
+    );
+
+    let operations = backend.list_operations().unwrap();
+    assert_eq!(operations.len(), 1);
+    assert_eq!(operations[0].kind, OperationKind::Discard);
+
+    let undone = backend.undo_last_operation().unwrap();
+    assert_eq!(undone.kind, OperationKind::Discard);
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("multi.txt")).unwrap(),
+        before
+    );
+    assert!(backend.list_operations().unwrap().is_empty());
+}
+
+#[test]
+fn undo_hard_reset_restores_commit_index_and_worktree() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let branch = backend.current_branch().unwrap();
+    let first = branch_tip(&backend, &branch);
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    backend.commit("add a", false, false, false).unwrap();
+    let second = branch_tip(&backend, &branch);
+    fs::write(tmp.path().join("staged.txt"), "staged").unwrap();
+    backend.stage(Path::new("staged.txt")).unwrap();
+    fs::write(tmp.path().join("init.txt"), "unstaged edit").unwrap();
+
+    backend.reset(&first, ResetMode::Hard).unwrap();
+    assert!(!tmp.path().join("a.txt").exists());
+
+    backend.undo_last_operation().unwrap();
+
+    assert_eq!(branch_tip(&backend, &branch), second);
+    assert_eq!(fs::read_to_string(tmp.path().join("a.txt")).unwrap(), "a");
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("init.txt")).unwrap(),
+        "unstaged edit"
+    );
+    let staged = backend
+        .status()
+        .unwrap()
+        .files
+        .into_iter()
+        .find(|f| f.path == "staged.txt")
+        .unwrap();
+    assert_eq!(staged.staging, app_lib::git::types::StagingState::Staged);
+}
+
+#[test]
+fn undo_amend_restores_previous_commit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let branch = backend.current_branch().unwrap();
+    let original = branch_tip(&backend, &branch);
+
+    backend
+        .commit("amended message", true, false, false)
+        .unwrap();
+    assert_ne!(branch_tip(&backend, &branch), original);
+
+    let undone = backend.undo_last_operation().unwrap();
+    assert_eq!(undone.kind, OperationKind::Amend);
+    assert_eq!(branch_tip(&backend, &branch), original);
+    assert_eq!(backend.get_head_commit_message().unwrap(), "initial commit");
+}
+
+#[test]
+fn undo_branch_delete_restores_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend.create_branch("topic").unwrap();
+    commit_on_branch(tmp.path(), &backend, "topic", "a.txt");
+    let tip = branch_tip(&backend, "topic");
+    let options = DeleteBranchOptions {
+        force: true,
+        ..DeleteBranchOptions::default()
+    };
+    backend
+        .delete_branch("topic", &options, &TransferControl::default())
+        .unwrap();
+
+    backend.undo_last_operation().unwrap();
+
+    assert_eq!(branch_tip(&backend, "topic"), tip);
+}
+
+#[test]
+fn undo_stash_drop_restores_stash() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("init.txt"), "modified").unwrap();
+    backend
+        .stash_save(Some("keep me"), &StashOptions::default())
+        .unwrap();
+    let message = backend.stash_list().unwrap()[0].message.clone();
+    backend.stash_drop(0).unwrap();
+
+    backend.undo_last_operation().unwrap();
+
+    let stashes = backend.stash_list().unwrap();
+    assert_eq!(stashes.len(), 1);
+    assert_eq!(stashes[0].message, message);
+    backend.stash_apply(0, false).unwrap();
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("init.txt")).unwrap(),
+        "modified"
+    );
+}
+
+#[test]
+fn undo_rebase_restores_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    backend.create_branch("topic").unwrap();
+    commit_on_branch(tmp.path(), &backend, "topic", "topic.txt");
+    commit_on_branch(tmp.path(), &backend, &main, "main.txt");
+    backend.checkout_branch("topic").unwrap();
+    let before = branch_tip(&backend, "topic");
+
+    let result = backend
+        .rebase(&main, &RebaseOptions::default(), &RebaseControl::default())
+        .unwrap();
+    assert!(result.completed);
+    assert_ne!(branch_tip(&backend, "topic"), before);
+    assert!(tmp.path().join("main.txt").exists());
+
+    let undone = backend.undo_last_operation().unwrap();
+    assert_eq!(undone.kind, OperationKind::Rebase);
+    assert_eq!(branch_tip(&backend, "topic"), before);
+    assert!(!tmp.path().join("main.txt").exists());
+}
+
+#[test]
+fn list_operations_is_newest_first() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend.create_branch("one").unwrap();
+    backend.create_branch("two").unwrap();
+    delete_branch_default(&backend, "one");
+    delete_branch_default(&backend, "two");
+
+    let operations = backend.list_operations().unwrap();
+    let descriptions: Vec<&str> = operations
+        .iter()
+        .map(|op| op.description.as_str())
+        .collect();
+    assert_eq!(descriptions, vec!["Delete branch two", "Delete branch one"]);
+    assert!(operations[0].id > operations[1].id);
+}
+
+// === Fixup and autosquash tests ===
+
+#[test]
+fn fixup_commits_are_autosquashed_into_their_targets() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    let base = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "a.txt", "a1\n", "Add a");
+    let target = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "b.txt", "b1\n", "Add b");
+
+    fs::write(tmp.path().join("a.txt"), "a2\n").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    rebase::create_fixup_commit(&backend, &target, FixupKind::Fixup, "", false, false).unwrap();
+    rebase::create_fixup_commit(
+        &backend,
+        &target,
+        FixupKind::Amend,
+        "Add a file",
+        false,
+        false,
+    )
+    .unwrap();
+    // fixup! / squash! はステージされた変更がないと作れない
+    assert!(matches!(
+        rebase::create_fixup_commit(&backend, &target, FixupKind::Squash, "", false, false),
+        Err(GitError::CommitFailed(_))
+    ));
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD"),
+        vec!["amend! Add a", "fixup! Add a", "Add b", "Add a"]
+    );
+
+    let todo = rebase::autosquash(backend.get_rebase_todo(&base, 100).unwrap());
+    let plan: Vec<(RebaseAction, &str)> = todo
+        .iter()
+        .map(|e| (e.action, e.message.as_str()))
+        .collect();
+    assert_eq!(
+        plan,
+        vec![
+            (RebaseAction::Pick, "Add a"),
+            (RebaseAction::Fixup, "fixup! Add a"),
+            (RebaseAction::FixupMessage, "amend! Add a"),
+            (RebaseAction::Pick, "Add b"),
+        ]
+    );
+
+    let result = backend
+        .interactive_rebase(&base, &todo, &RebaseControl::default())
+        .unwrap();
+    assert!(result.completed);
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD"),
+        vec!["Add b", "Add a file"]
+    );
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("a.txt")).unwrap(),
+        "a2\n"
+    );
+}
+
+#[test]
+fn autosquash_squash_does_not_consume_reword_messages() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    let base = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "a.txt", "a1\n", "Add a");
+    let target = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "b.txt", "b1\n", "Add b");
+    fs::write(tmp.path().join("a.txt"), "a2\n").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    rebase::create_fixup_commit(
+        &backend,
+        &target,
+        FixupKind::Squash,
+        "More detail",
+        false,
+        false,
+    )
+    .unwrap();
+
+    let mut todo = rebase::autosquash(backend.get_rebase_todo(&base, 100).unwrap());
+    assert_eq!(todo[1].action, RebaseAction::Squash);
+    todo[2].action = RebaseAction::Reword;
+    todo[2].message = "Add the b file".to_string();
+    let result = backend
+        .interactive_rebase(&base, &todo, &RebaseControl::default())
+        .unwrap();
+
+    // squash のメッセージ結合で reword 用のメッセージが消費されないこと
+    assert!(result.completed);
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD"),
+        vec!["Add the b file", "Add a"]
+    );
+    let squashed = &backend.get_branch_commits(&main, 2).unwrap()[1];
+    assert_eq!(squashed.body, "More detail");
+}
+
+#[test]
+fn fixup_commit_target_must_be_on_current_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    backend.create_branch("topic").unwrap();
+    commit_on_branch(tmp.path(), &backend, "topic", "topic.txt");
+    let other = branch_tip(&backend, "topic");
+    assert_eq!(backend.current_branch().unwrap(), main);
+
+    fs::write(tmp.path().join("init.txt"), "changed").unwrap();
+    backend.stage(Path::new("init.txt")).unwrap();
+    let result = rebase::create_fixup_commit(&backend, &other, FixupKind::Fixup, "", false, false);
+    assert!(matches!(result, Err(GitError::CommitFailed(_))));
+}
+
+// === Absorb tests ===
+
+fn staged_paths(backend: &TestBackend) -> Vec<String> {
+    backend
+        .status()
+        .unwrap()
+        .files
+        .into_iter()
+        .filter(|f| f.staging == StagingState::Staged)
+        .map(|f| f.path)
+        .collect()
+}
+
+#[test]
+fn absorb_creates_fixups_for_the_commits_that_touched_each_hunk() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    let base = branch_tip(&backend, &main);
+    let lines = |n: usize, tag: &str| (1..=n).map(|i| format!("{tag}{i}\n")).collect::<String>();
+    commit_content(tmp.path(), &backend, "a.txt", &lines(12, "a"), "Add a");
+    commit_content(tmp.path(), &backend, "b.txt", &lines(12, "b"), "Add b");
+    // 1 つの hunk が "Add a" と "Add mixed" の両方の行を変える
+    fs::write(
+        tmp.path().join("mixed.txt"),
+        format!("{}{}", lines(2, "x"), lines(2, "y")),
+    )
+    .unwrap();
+    backend.stage(Path::new("mixed.txt")).unwrap();
+    backend.commit("Add mixed", false, false, false).unwrap();
+    fs::write(
+        tmp.path().join("mixed.txt"),
+        format!("{}{}", lines(2, "x"), lines(2, "z")),
+    )
+    .unwrap();
+    backend.stage(Path::new("mixed.txt")).unwrap();
+    backend.commit("Change mixed", false, false, false).unwrap();
+
+    fs::write(
+        tmp.path().join("a.txt"),
+        lines(12, "a")
+            .replace("a2\n", "A2\n")
+            .replace("a11\n", "A11\n"),
+    )
+    .unwrap();
+    fs::write(
+        tmp.path().join("b.txt"),
+        lines(12, "b").replace("b5\n", "B5\n"),
+    )
+    .unwrap();
+    fs::write(tmp.path().join("init.txt"), "changed").unwrap();
+    fs::write(tmp.path().join("mixed.txt"), "x1\nX2\nZ1\nz2\n").unwrap();
+    backend.stage_all().unwrap();
+
+    let options = AbsorbOptions {
+        base: Some(base.clone()),
+        dry_run: true,
+        ..AbsorbOptions::default()
+    };
+    let plan = absorb::absorb(&backend, &options).unwrap();
+    let targets: Vec<(&str, usize)> = plan
+        .fixups
+        .iter()
+        .map(|f| (f.target_summary.as_str(), f.hunks.len()))
+        .collect();
+    assert_eq!(targets, vec![("Add a", 2), ("Add b", 1)]);
+    assert!(plan.fixups.iter().all(|f| f.oid.is_none()));
+    let mut skipped: Vec<(&str, AbsorbSkipReason)> = plan
+        .skipped
+        .iter()
+        .map(|s| (s.path.as_str(), s.reason))
+        .collect();
+    skipped.sort_by_key(|(path, _)| *path);
+    assert_eq!(
+        skipped,
+        vec![
+            ("init.txt", AbsorbSkipReason::OutsideRange),
+            ("mixed.txt", AbsorbSkipReason::Ambiguous),
+        ]
+    );
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD"),
+        vec!["Change mixed", "Add mixed", "Add b", "Add a"]
+    );
+
+    let options = AbsorbOptions {
+        dry_run: false,
+        ..options
+    };
+    let result = absorb::absorb(&backend, &options).unwrap();
+    assert!(result.rebase.is_none());
+    for (fixup, path) in result.fixups.iter().zip(["a.txt", "b.txt"]) {
+        let detail = backend
+            .get_commit_detail(fixup.oid.as_deref().unwrap())
+            .unwrap();
+        let paths: Vec<&str> = detail.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec![path]);
+    }
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD")[..2],
+        ["fixup! Add b", "fixup! Add a"]
+    );
+    let mut left = staged_paths(&backend);
+    left.sort();
+    assert_eq!(left, vec!["init.txt", "mixed.txt"]);
+}
+
+#[test]
+fn absorb_targets_the_commit_that_last_changed_each_line() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    let base = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "a.txt", "a1\na2\na3\na4\n", "Add a");
+    commit_content(
+        tmp.path(),
+        &backend,
+        "a.txt",
+        "a1\na2\na3;\na4\n",
+        "Format a",
+    );
+    let format = branch_tip(&backend, &main);
+    // blame の既定では整形コミットを飛ばすが、absorb は行を最後に変えたコミットを使う
+    fs::write(
+        tmp.path().join(".git-blame-ignore-revs"),
+        format!("{format}\n"),
+    )
+    .unwrap();
+    // 隣り合う変更も、文脈行に引きずられずに別々のコミットへ振り分ける
+    fs::write(tmp.path().join("a.txt"), "A1\nnew\na2\nA3;\na4\n").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+
+    let result = absorb::absorb(
+        &backend,
+        &AbsorbOptions {
+            base: Some(base.clone()),
+            ..AbsorbOptions::default()
+        },
+    )
+    .unwrap();
+
+    let targets: Vec<&str> = result
+        .fixups
+        .iter()
+        .map(|f| f.target_summary.as_str())
+        .collect();
+    assert_eq!(targets, vec!["Add a", "Format a"]);
+    assert!(result.skipped.is_empty());
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD")[..2],
+        ["fixup! Format a", "fixup! Add a"]
+    );
+    assert_eq!(
+        show_file(tmp.path(), "HEAD", "a.txt"),
+        "A1\nnew\na2\nA3;\na4\n"
+    );
+    assert!(staged_paths(&backend).is_empty());
+}
+
+#[test]
+fn absorb_autosquashes_when_nothing_is_left_over() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    let base = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "a.txt", "a1\na2\n", "Add a");
+    commit_content(tmp.path(), &backend, "b.txt", "b1\nb2\n", "Add b");
+    fs::write(tmp.path().join("a.txt"), "a1\nA2\n").unwrap();
+    backend.stage_all().unwrap();
+
+    let result = absorb::absorb(
+        &backend,
+        &AbsorbOptions {
+            base: Some(base.clone()),
+            autosquash: true,
+            dry_run: false,
+        },
+    )
+    .unwrap();
+
+    assert!(result.rebase.unwrap().completed);
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD"),
+        vec!["Add b", "Add a"]
+    );
+    assert!(backend.status().unwrap().files.is_empty());
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("a.txt")).unwrap(),
+        "a1\nA2\n"
+    );
+}
+
+#[test]
+fn absorb_without_upstream_or_base_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let result = absorb::absorb(&backend, &AbsorbOptions::default());
+    assert!(matches!(result, Err(GitError::AbsorbFailed(_))));
+}
+
+// === Split commit tests ===
+
+fn show_file(dir: &Path, rev: &str, file: &str) -> String {
+    git_output(dir, &["show", &format!("{rev}:{file}")])
+}
+
+fn tree_of(dir: &Path, rev: &str) -> String {
+    git_output(dir, &["rev-parse", &format!("{rev}^{{tree}}")])
+        .trim()
+        .to_string()
+}
+
+fn commit_hunk(backend: &TestBackend, oid: &str, path: &str, index: usize) -> HunkIdentifier {
+    let hunk = &backend.get_commit_file_diff(oid, path).unwrap()[0].hunks[index];
+    HunkIdentifier {
+        old_start: hunk.old_start,
+        old_lines: hunk.old_lines,
+        new_start: hunk.new_start,
+        new_lines: hunk.new_lines,
+    }
+}
+
+#[test]
+fn split_commit_by_files_hunks_and_lines_replays_descendants() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    let base = branch_tip(&backend, &main);
+    let lines = |n: usize, tag: &str| (1..=n).map(|i| format!("{tag}{i}\n")).collect::<String>();
+    fs::write(tmp.path().join("a.txt"), lines(12, "a")).unwrap();
+    fs::write(tmp.path().join("b.txt"), "b1\nb2\n").unwrap();
+    backend.stage_all().unwrap();
+    backend.commit("Add a and b", false, false, false).unwrap();
+
+    let a = lines(12, "a")
+        .replace("a1\n", "A1\n")
+        .replace("a12\n", "A12\n");
+    fs::write(tmp.path().join("a.txt"), &a).unwrap();
+    fs::write(tmp.path().join("b.txt"), "b1\nb2\nX\nY\n").unwrap();
+    fs::write(tmp.path().join("new.txt"), "new\n").unwrap();
+    backend.stage_all().unwrap();
+    let big = backend
+        .commit("Big change\n\nDoes too much.", false, false, false)
+        .unwrap()
+        .oid;
+    commit_content(tmp.path(), &backend, "after.txt", "after\n", "After");
+    let head_tree = tree_of(tmp.path(), "HEAD");
+
+    let groups = vec![
+        SplitCommitGroup {
+            message: "Change the top of a".to_string(),
+            hunks: vec![SplitHunk {
+                path: "a.txt".to_string(),
+                hunk: commit_hunk(&backend, &big, "a.txt", 0),
+            }],
+            lines: vec![SplitLines {
+                path: "b.txt".to_string(),
+                range: LineRange {
+                    hunk: commit_hunk(&backend, &big, "b.txt", 0),
+                    line_indices: vec![2],
+                },
+            }],
+            ..SplitCommitGroup::default()
+        },
+        SplitCommitGroup {
+            message: "Add new".to_string(),
+            files: vec!["new.txt".to_string()],
+            ..SplitCommitGroup::default()
+        },
+    ];
+    let result = split::split_commit(&backend, &big, &groups).unwrap();
+
+    assert!(result.rebase.completed);
+    assert!(result.rebase.conflicts.is_empty());
+    assert_eq!(result.commits.len(), 3);
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD"),
+        vec![
+            "After",
+            "Big change",
+            "Add new",
+            "Change the top of a",
+            "Add a and b"
+        ]
+    );
+    assert_eq!(tree_of(tmp.path(), "HEAD"), head_tree);
+    assert!(backend.status().unwrap().files.is_empty());
+
+    let first = &result.commits[0];
+    assert_eq!(
+        show_file(tmp.path(), first, "a.txt"),
+        lines(12, "a").replace("a1\n", "A1\n")
+    );
+    assert_eq!(show_file(tmp.path(), first, "b.txt"), "b1\nb2\nX\n");
+    let detail = backend.get_commit_detail(&result.commits[1]).unwrap();
+    let paths: Vec<&str> = detail.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["new.txt"]);
+    let rest = backend.get_commit_detail(&result.commits[2]).unwrap().info;
+    assert_eq!(rest.body, "Does too much.");
+    assert_eq!(rest.author_email, "test@example.com");
+}
+
+#[test]
+fn split_commit_rejects_invalid_groups() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("a.txt"), "a1\n").unwrap();
+    fs::write(tmp.path().join("init.txt"), "changed\n").unwrap();
+    backend.stage_all().unwrap();
+    let oid = backend.commit("Add a", false, false, false).unwrap().oid;
+    let hunk = commit_hunk(&backend, &oid, "a.txt", 0);
+
+    let added_by_hunk = SplitCommitGroup {
+        message: "Part".to_string(),
+        hunks: vec![SplitHunk {
+            path: "a.txt".to_string(),
+            hunk,
+        }],
+        ..SplitCommitGroup::default()
+    };
+    let nothing_new = SplitCommitGroup {
+        message: "Again".to_string(),
+        files: vec!["a.txt".to_string()],
+        ..SplitCommitGroup::default()
+    };
+    let no_message = SplitCommitGroup {
+        files: vec!["a.txt".to_string()],
+        ..SplitCommitGroup::default()
+    };
+    let cases = [
+        vec![added_by_hunk],
+        vec![nothing_new.clone(), nothing_new],
+        vec![no_message],
+        Vec::new(),
+    ];
+    for groups in cases {
+        let result = split::split_commit(&backend, &oid, &groups);
+        assert!(matches!(result, Err(GitError::SplitFailed(_))));
+    }
+    let main = backend.current_branch().unwrap();
+    assert_eq!(branch_tip(&backend, &main), oid);
+    assert!(backend.status().unwrap().files.is_empty());
+}
+
+// === Single commit edit tests ===
+
+#[test]
+fn edit_commit_rewords_a_commit_in_the_middle() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    let base = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "a.txt", "a\n", "Add a");
+    commit_content(tmp.path(), &backend, "b.txt", "b\n", "Add b");
+    let target = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "c.txt", "c\n", "Add c");
+    let tree = tree_of(tmp.path(), "HEAD");
+
+    let result = backend
+        .edit_commit(&CommitEdit::Reword {
+            oid: target,
+            message: "Add file b\n\nWith a body.".to_string(),
+        })
+        .unwrap();
+
+    assert!(result.completed);
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD"),
+        vec!["Add c", "Add file b", "Add a"]
+    );
+    let commits = backend.get_branch_commits(&main, 2).unwrap();
+    assert_eq!(commits[1].body, "With a body.");
+    assert_eq!(tree_of(tmp.path(), "HEAD"), tree);
+}
+
+#[test]
+fn edit_commit_moves_and_drops_commits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    let base = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "a.txt", "a\n", "Add a");
+    let a = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "b.txt", "b\n", "Add b");
+    commit_content(tmp.path(), &backend, "c.txt", "c\n", "Add c");
+    let c = branch_tip(&backend, &main);
+
+    let result = backend
+        .edit_commit(&CommitEdit::Move {
+            oid: c.clone(),
+            direction: MoveDirection::Down,
+        })
+        .unwrap();
+    assert!(result.completed);
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD"),
+        vec!["Add b", "Add c", "Add a"]
+    );
+
+    backend
+        .edit_commit(&CommitEdit::Move {
+            oid: a,
+            direction: MoveDirection::Up,
+        })
+        .unwrap();
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD"),
+        vec!["Add b", "Add a", "Add c"]
+    );
+
+    let newest = branch_tip(&backend, &main);
+    let result = backend.edit_commit(&CommitEdit::Move {
+        oid: newest.clone(),
+        direction: MoveDirection::Up,
+    });
+    assert!(matches!(result, Err(GitError::RebaseFailed(_))));
+
+    let result = backend
+        .edit_commit(&CommitEdit::Drop { oid: newest })
+        .unwrap();
+    assert!(result.completed);
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD"),
+        vec!["Add a", "Add c"]
+    );
+    assert!(!tmp.path().join("b.txt").exists());
+    assert!(backend.status().unwrap().files.is_empty());
+}
+
+#[test]
+fn edit_commit_stops_on_conflict() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    commit_content(tmp.path(), &backend, "f.txt", "one\n", "One");
+    commit_content(tmp.path(), &backend, "f.txt", "two\n", "Two");
+    let head = branch_tip(&backend, &main);
+
+    let result = backend
+        .edit_commit(&CommitEdit::Move {
+            oid: head.clone(),
+            direction: MoveDirection::Down,
+        })
+        .unwrap();
+
+    assert!(!result.completed);
+    assert_eq!(result.conflicts, vec!["f.txt"]);
+    assert!(backend.is_rebasing().unwrap());
+    backend.abort_rebase().unwrap();
+    assert_eq!(branch_tip(&backend, &main), head);
+}
+
+#[test]
+fn edit_commit_refuses_the_root_commit_and_reports_unknown_commits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    let root = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "a.txt", "a\n", "Add a");
+    let a = branch_tip(&backend, &main);
+
+    let err = backend
+        .edit_commit(&CommitEdit::Reword {
+            oid: root,
+            message: "Initial".to_string(),
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("root commit"), "{err}");
+    let err = backend
+        .edit_commit(&CommitEdit::Move {
+            oid: a.clone(),
+            direction: MoveDirection::Down,
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("root commit"), "{err}");
+
+    let err = backend
+        .edit_commit(&CommitEdit::Drop {
+            oid: "0123456789abcdef0123456789abcdef01234567".to_string(),
+        })
+        .unwrap_err();
+    assert!(!err.to_string().contains("root commit"), "{err}");
+    assert_eq!(branch_tip(&backend, &main), a);
+}
+
+// === Rebase engine tests ===
+
+fn no_progress() -> RebaseControl {
+    RebaseControl::default()
+}
+
+/// `main` gets `x.txt`, `topic` gets `Add a`, a merge of `side` and `Add c`.
+fn branch_with_merge(dir: &Path, backend: &TestBackend) -> String {
+    let main = backend.current_branch().unwrap();
+    backend.create_branch("topic").unwrap();
+    backend.checkout_branch("topic").unwrap();
+    commit_content(dir, backend, "a.txt", "a\n", "Add a");
+    run_git(dir, &["checkout", "-q", "-b", "side"]);
+    fs::write(dir.join("s.txt"), "s\n").unwrap();
+    run_git(dir, &["add", "s.txt"]);
+    run_git(dir, &["commit", "-q", "-m", "Add s"]);
+    run_git(dir, &["checkout", "-q", "topic"]);
+    commit_content(dir, backend, "b.txt", "b\n", "Add b");
+    run_git(
+        dir,
+        &["merge", "--no-ff", "-m", "Merge branch 'side'", "side"],
+    );
+    // マージは git で作ったので、続くコミットも git で作りバックエンドの index を経由しない
+    fs::write(dir.join("c.txt"), "c\n").unwrap();
+    run_git(dir, &["add", "c.txt"]);
+    run_git(dir, &["commit", "-q", "-m", "Add c"]);
+    commit_on_branch(dir, backend, &main, "x.txt");
+    main
+}
+
+fn rev_parse(dir: &Path, rev: &str) -> String {
+    git_output(dir, &["rev-parse", rev]).trim().to_string()
+}
+
+fn merges_since(dir: &Path, base: &str) -> Vec<String> {
+    let range = format!("{base}..HEAD");
+    git_output(dir, &["log", "--merges", "--format=%s", &range])
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn rebase_runs_exec_after_each_commit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    backend.create_branch("topic").unwrap();
+    backend.checkout_branch("topic").unwrap();
+    commit_content(tmp.path(), &backend, "a.txt", "a\n", "Add a");
+    commit_content(tmp.path(), &backend, "b.txt", "b\n", "Add b");
+    commit_on_branch(tmp.path(), &backend, &main, "x.txt");
+
+    let options = RebaseOptions {
+        exec: Some("git log -1 --format=%s >> .git/exec.log".to_string()),
+        ..RebaseOptions::default()
+    };
+    let result = backend.rebase(&main, &options, &no_progress()).unwrap();
+
+    assert!(result.completed);
+    assert_eq!(
+        fs::read_to_string(tmp.path().join(".git/exec.log")).unwrap(),
+        "Add a\nAdd b\n"
+    );
+    assert_eq!(
+        subjects_since(tmp.path(), &main, "HEAD"),
+        vec!["Add b", "Add a"]
+    );
+    assert!(tmp.path().join("x.txt").exists());
+}
+
+#[test]
+fn rebase_reports_progress_of_each_step() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    let base = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "a.txt", "a\n", "Add a");
+    commit_content(tmp.path(), &backend, "b.txt", "b\n", "Add b");
+    let mut todo = backend.get_rebase_todo(&base, 100).unwrap();
+    todo[1].action = RebaseAction::Fixup;
+
+    let steps = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = steps.clone();
+    let control = RebaseControl::default().with_progress(move |progress| {
+        seen.lock().unwrap().push((
+            progress.step,
+            progress.total,
+            progress.entry.message.clone(),
+        ));
+    });
+    let result = backend.interactive_rebase(&base, &todo, &control).unwrap();
+
+    assert!(result.completed);
+    assert_eq!(subjects_since(tmp.path(), &base, "HEAD"), vec!["Add a"]);
+    // git に任せる CLI バックエンドは途中経過を報告しない
+    let steps = steps.lock().unwrap();
+    if !steps.is_empty() {
+        assert_eq!(
+            *steps,
+            vec![(1, 2, "Add a".to_string()), (2, 2, "Add b".to_string())]
+        );
+    }
+}
+
+#[test]
+fn interactive_rebase_stops_at_break_and_failed_exec() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    let base = branch_tip(&backend, &main);
+    commit_content(tmp.path(), &backend, "a.txt", "a\n", "Add a");
+    commit_content(tmp.path(), &backend, "b.txt", "b\n", "Add b");
+    let mut todo = backend.get_rebase_todo(&base, 100).unwrap();
+    todo.insert(1, rebase::todo_step(RebaseAction::Break, ""));
+    todo.push(rebase::todo_step(
+        RebaseAction::Exec,
+        "echo missing ok >&2; test -f .git/ok",
+    ));
+    todo[2].action = RebaseAction::Reword;
+    todo[2].message = "Add the b file".to_string();
+
+    let result = backend
+        .interactive_rebase(&base, &todo, &no_progress())
+        .unwrap();
+    assert!(!result.completed);
+    assert!(result.conflicts.is_empty());
+    let state = backend.get_rebase_state().unwrap().unwrap();
+    assert_eq!(state.stop_reason, Some(RebaseStopReason::Break));
+    assert_eq!(state.head_name.as_deref(), Some(main.as_str()));
+    assert_eq!((state.current_step, state.total_steps), (2, 4));
+
+    let result = backend.continue_rebase(&no_progress()).unwrap();
+    assert!(!result.completed);
+    let state = backend.get_rebase_state().unwrap().unwrap();
+    assert_eq!(state.stop_reason, Some(RebaseStopReason::ExecFailed));
+    assert_eq!((state.current_step, state.total_steps), (4, 4));
+    // git に任せる CLI バックエンドは exec の出力を残さない
+    if let Some(failure) = state.exec_failure {
+        assert_eq!(failure.command, "echo missing ok >&2; test -f .git/ok");
+        assert_eq!(failure.exit_code, Some(1));
+        assert!(!failure.cancelled);
+        assert_eq!(failure.stderr, "missing ok\n");
+    }
+
+    let result = backend.continue_rebase(&no_progress()).unwrap();
+    assert!(result.completed);
+    assert!(!backend.is_rebasing().unwrap());
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "HEAD"),
+        vec!["Add the b file", "Add a"]
+    );
+    assert_eq!(backend.current_branch().unwrap(), main);
+}
+
+#[test]
+fn rebase_continues_after_conflicts_are_resolved() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    backend.create_branch("topic").unwrap();
+    backend.checkout_branch("topic").unwrap();
+    commit_content(tmp.path(), &backend, "f.txt", "topic\n", "Topic change");
+    commit_content(tmp.path(), &backend, "g.txt", "g\n", "Add g");
+    backend.checkout_branch(&main).unwrap();
+    commit_content(tmp.path(), &backend, "f.txt", "main\n", "Main change");
+    backend.checkout_branch("topic").unwrap();
+
+    let result = backend
+        .rebase(&main, &RebaseOptions::default(), &no_progress())
+        .unwrap();
+    assert!(!result.completed);
+    assert_eq!(result.conflicts, vec!["f.txt"]);
+    let state = backend.get_rebase_state().unwrap().unwrap();
+    assert_eq!(state.stop_reason, Some(RebaseStopReason::Conflict));
+    assert!(state.has_conflicts);
+
+    fs::write(tmp.path().join("f.txt"), "both\n").unwrap();
+    backend.stage(Path::new("f.txt")).unwrap();
+    let result = backend.continue_rebase(&no_progress()).unwrap();
+
+    assert!(result.completed);
+    assert_eq!(
+        subjects_since(tmp.path(), &main, "HEAD"),
+        vec!["Add g", "Topic change"]
+    );
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("f.txt")).unwrap(),
+        "both\n"
+    );
+    assert!(backend.status().unwrap().files.is_empty());
+}
+
+#[test]
+fn rebase_merges_keeps_merge_commits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = branch_with_merge(tmp.path(), &backend);
+
+    let options = RebaseOptions {
+        rebase_merges: true,
+        ..RebaseOptions::default()
+    };
+    let result = backend.rebase(&main, &options, &no_progress()).unwrap();
+
+    assert!(result.completed);
+    assert_eq!(merges_since(tmp.path(), &main), vec!["Merge branch 'side'"]);
+    for file in ["a.txt", "b.txt", "c.txt", "s.txt", "x.txt"] {
+        assert!(tmp.path().join(file).exists(), "{file} is missing");
+    }
+    assert_eq!(backend.current_branch().unwrap(), "topic");
+}
+
+#[test]
+fn edit_commit_rewords_a_commit_below_a_merge() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = branch_with_merge(tmp.path(), &backend);
+    let base = rev_parse(tmp.path(), &format!("{main}~1"));
+    let target = rev_parse(tmp.path(), ":/Add a");
+    let tree = tree_of(tmp.path(), "HEAD");
+
+    let result = backend
+        .edit_commit(&CommitEdit::Reword {
+            oid: target,
+            message: "Add the a file".to_string(),
+        })
+        .unwrap();
+
+    assert!(result.completed);
+    assert!(!backend.is_rebasing().unwrap());
+    assert_eq!(merges_since(tmp.path(), &base), vec!["Merge branch 'side'"]);
+    let mut subjects = subjects_since(tmp.path(), &base, "HEAD");
+    subjects.sort();
+    assert_eq!(
+        subjects,
+        vec![
+            "Add b",
+            "Add c",
+            "Add s",
+            "Add the a file",
+            "Merge branch 'side'"
+        ]
+    );
+    assert_eq!(tree_of(tmp.path(), "HEAD"), tree);
+    assert_eq!(backend.current_branch().unwrap(), "topic");
+}
+
+#[test]
+fn interactive_rebase_runs_a_todo_with_merges() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = branch_with_merge(tmp.path(), &backend);
+
+    let todo = rebase::todo_with_merges(&backend, &main).unwrap();
+    assert_eq!(todo[0].action, RebaseAction::Label);
+    assert_eq!(todo[0].argument.as_deref(), Some("onto"));
+    let merge = todo
+        .iter()
+        .find(|entry| entry.action == RebaseAction::Merge)
+        .unwrap();
+    assert_eq!(merge.argument.as_deref(), Some("side"));
+    let picks = todo
+        .iter()
+        .filter(|entry| entry.action == RebaseAction::Pick)
+        .count();
+    assert_eq!(picks, 4);
+
+    let result = backend
+        .interactive_rebase(&main, &todo, &no_progress())
+        .unwrap();
+
+    assert!(result.completed);
+    assert_eq!(merges_since(tmp.path(), &main), vec!["Merge branch 'side'"]);
+    // 同じ時刻のコミットは log の並びが定まらないので並べ替えて比べる
+    let mut subjects = subjects_since(tmp.path(), &main, "HEAD");
+    subjects.sort();
+    assert_eq!(
+        subjects,
+        vec!["Add a", "Add b", "Add c", "Add s", "Merge branch 'side'"]
+    );
+    // ラベル用の参照は残らない
+    let output = Command::new("git")
+        .args(["for-each-ref", "refs/rewritten/"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn interactive_rebase_leaves_no_half_done_rebase_when_a_step_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    backend.create_branch("topic").unwrap();
+    backend.checkout_branch("topic").unwrap();
+    commit_content(tmp.path(), &backend, "a.txt", "a\n", "Add a");
+    commit_content(tmp.path(), &backend, "b.txt", "b\n", "Add b");
+    commit_on_branch(tmp.path(), &backend, &main, "x.txt");
+    let tip = branch_tip(&backend, "topic");
+    let mut todo = backend.get_rebase_todo(&main, 100).unwrap();
+    // 最初の pick が済んだあとで失敗する手順
+    todo.insert(1, rebase::todo_step(RebaseAction::Label, "bad..label"));
+
+    let result = backend.interactive_rebase(&main, &todo, &no_progress());
+
+    // 失敗を返すなら元に戻っており、止まったなら続行か中止を選べる状態が残る
+    match result {
+        Err(_) => assert!(!backend.is_rebasing().unwrap()),
+        Ok(result) => {
+            assert!(!result.completed);
+            assert!(backend.get_rebase_state().unwrap().is_some());
+            backend.abort_rebase().unwrap();
+        }
+    }
+    assert!(!backend.is_rebasing().unwrap());
+    assert_eq!(backend.current_branch().unwrap(), "topic");
+    assert_eq!(branch_tip(&backend, "topic"), tip);
+    assert!(!tmp.path().join(".git").join("rebase-merge").exists());
+}
+
+#[test]
+fn continue_rebase_stays_stopped_when_a_step_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = backend.current_branch().unwrap();
+    backend.create_branch("topic").unwrap();
+    backend.checkout_branch("topic").unwrap();
+    commit_content(tmp.path(), &backend, "a.txt", "a\n", "Add a");
+    commit_content(tmp.path(), &backend, "b.txt", "b\n", "Add b");
+    commit_on_branch(tmp.path(), &backend, &main, "x.txt");
+    let tip = branch_tip(&backend, "topic");
+    let mut todo = backend.get_rebase_todo(&main, 100).unwrap();
+    todo.insert(1, rebase::todo_step(RebaseAction::Break, ""));
+    todo.insert(2, rebase::todo_step(RebaseAction::Label, "bad..label"));
+    let result = backend
+        .interactive_rebase(&main, &todo, &no_progress())
+        .unwrap();
+    assert!(!result.completed);
+
+    let result = backend.continue_rebase(&no_progress());
+
+    // 続行中の失敗では止まったままになり、中止すれば元に戻せる
+    assert!(!result.map(|result| result.completed).unwrap_or(false));
+    assert!(backend.is_rebasing().unwrap());
+    assert!(backend.get_rebase_state().unwrap().is_some());
+    backend.abort_rebase().unwrap();
+    assert!(!backend.is_rebasing().unwrap());
+    assert_eq!(backend.current_branch().unwrap(), "topic");
+    assert_eq!(branch_tip(&backend, "topic"), tip);
+}
+
+// === Stacked branch tests ===
+
+/// `feature-1` to `feature-3` stacked on `main` with one commit each, and a
+/// commit on `main` they are not based on yet. `feature-3` is checked out.
+fn stacked_branches(dir: &Path, backend: &TestBackend) -> String {
+    let main = backend.current_branch().unwrap();
+    for (n, name) in ["a", "b", "c"].into_iter().enumerate() {
+        let branch = format!("feature-{}", n + 1);
+        backend.create_branch(&branch).unwrap();
+        backend.checkout_branch(&branch).unwrap();
+        commit_content(
+            dir,
+            backend,
+            &format!("{name}.txt"),
+            &format!("{name}\n"),
+            &format!("Add {name}"),
+        );
+    }
+    commit_on_branch(dir, backend, &main, "x.txt");
+    main
+}
+
+#[test]
+fn rebase_moves_the_branches_stacked_below_head() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = stacked_branches(tmp.path(), &backend);
+    let feature_1 = branch_tip(&backend, "feature-1");
+
+    let result = backend
+        .rebase(&main, &RebaseOptions::default(), &no_progress())
+        .unwrap();
+
+    assert!(result.completed);
+    assert_eq!(
+        subjects_since(tmp.path(), &main, "feature-1"),
+        vec!["Add a"]
+    );
+    assert_eq!(
+        subjects_since(tmp.path(), "feature-1", "feature-2"),
+        vec!["Add b"]
+    );
+    assert_eq!(
+        subjects_since(tmp.path(), "feature-2", "feature-3"),
+        vec!["Add c"]
+    );
+    assert_eq!(backend.current_branch().unwrap(), "feature-3");
+
+    // 取り消すと積まれたブランチも元に戻る
+    backend.undo_last_operation().unwrap();
+    assert_eq!(branch_tip(&backend, "feature-1"), feature_1);
+    assert_eq!(
+        subjects_since(tmp.path(), "feature-1", "feature-3"),
+        vec!["Add c", "Add b"]
+    );
+}
+
+#[test]
+fn interactive_rebase_moves_the_branches_stacked_below_head() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let base = branch_tip(&backend, &backend.current_branch().unwrap());
+    stacked_branches(tmp.path(), &backend);
+    let mut todo = backend.get_rebase_todo(&base, 100).unwrap();
+    todo[0].action = RebaseAction::Reword;
+    todo[0].message = "Add the a file".to_string();
+
+    let result = backend
+        .interactive_rebase(&base, &todo, &no_progress())
+        .unwrap();
+
+    assert!(result.completed);
+    assert_eq!(
+        subjects_since(tmp.path(), &base, "feature-1"),
+        vec!["Add the a file"]
+    );
+    assert_eq!(
+        subjects_since(tmp.path(), "feature-1", "feature-2"),
+        vec!["Add b"]
+    );
+    assert_eq!(
+        subjects_since(tmp.path(), "feature-2", "feature-3"),
+        vec!["Add c"]
+    );
+}
+
+#[test]
+fn edit_commit_moves_the_branches_stacked_below_head() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = stacked_branches(tmp.path(), &backend);
+
+    let result = backend
+        .edit_commit(&CommitEdit::Reword {
+            oid: branch_tip(&backend, "feature-2"),
+            message: "Add the b file".to_string(),
+        })
+        .unwrap();
+
+    assert!(result.completed);
+    assert_eq!(
+        subjects_since(tmp.path(), &main, "feature-2"),
+        vec!["Add the b file", "Add a"]
+    );
+    assert_eq!(
+        subjects_since(tmp.path(), "feature-2", "feature-3"),
+        vec!["Add c"]
+    );
+    assert_eq!(
+        subjects_since(tmp.path(), "feature-1", "feature-2"),
+        vec!["Add the b file"]
+    );
+}
+
+#[test]
+fn list_stack_follows_the_branches_from_the_base() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let main = stacked_branches(tmp.path(), &backend);
+    commit_on_branch(tmp.path(), &backend, "feature-3", "d.txt");
+    // feature-1 から枝分かれしたブランチと main から生えた無関係なブランチ
+    run_git(tmp.path(), &["branch", "fork", "feature-1"]);
+    commit_on_branch(tmp.path(), &backend, "fork", "f.txt");
+    run_git(tmp.path(), &["branch", "other", &main]);
+    commit_on_branch(tmp.path(), &backend, "other", "o.txt");
+    backend.checkout_branch("feature-2").unwrap();
+
+    let stack = stack::list_stack(&backend, None).unwrap();
+
+    assert_eq!(stack.base, main);
+    let branches: Vec<(&str, u32, bool)> = stack
+        .branches
+        .iter()
+        .map(|b| (b.name.as_str(), b.commit_count, b.is_head))
+        .collect();
+    assert_eq!(
+        branches,
+        vec![
+            ("feature-1", 1, false),
+            ("feature-2", 1, true),
+            ("feature-3", 2, false),
+        ]
+    );
+    assert_eq!(stack.branches[2].oid, branch_tip(&backend, "feature-3"));
+
+    // base から何も積んでいないブランチでもそれ自身は含まれる
+    run_git(tmp.path(), &["checkout", "-q", "-b", "empty", &main]);
+    let stack = stack::list_stack(&backend, None).unwrap();
+    let branches: Vec<(&str, u32)> = stack
+        .branches
+        .iter()
+        .map(|b| (b.name.as_str(), b.commit_count))
+        .collect();
+    assert_eq!(branches, vec![("empty", 0)]);
+
+    backend.checkout_branch(&main).unwrap();
+    assert!(stack::list_stack(&backend, None).is_err());
+}
+
+#[test]
+fn push_stack_pushes_every_branch_with_lease() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    let local = tmp.path().join("local");
+    let main = stacked_branches(&local, &backend);
+    let control = TransferControl::default();
+
+    let result = stack::push_stack(&backend, Some(&main), "origin", &control).unwrap();
+
+    assert!(result.failed.is_none());
+    assert_eq!(result.pushed.len(), 3);
+    for (result, name) in result
+        .pushed
+        .iter()
+        .zip(["feature-1", "feature-2", "feature-3"])
+    {
+        assert_eq!(result.updates[0].status, PushRefStatus::Created);
+        assert_eq!(upstream_of(&backend, name), Some(format!("origin/{name}")));
+    }
+
+    backend
+        .rebase(&main, &RebaseOptions::default(), &no_progress())
+        .unwrap();
+    let result = stack::push_stack(&backend, Some(&main), "origin", &control).unwrap();
+
+    assert!(result.failed.is_none());
+    for (result, name) in result
+        .pushed
+        .iter()
+        .zip(["feature-1", "feature-2", "feature-3"])
+    {
+        assert_eq!(result.updates[0].status, PushRefStatus::Forced);
+        assert_eq!(
+            remote_ref_oid(tmp.path(), &format!("refs/heads/{name}")),
+            Some(branch_tip(&backend, name))
+        );
+    }
+}
+
+#[test]
+fn push_stack_stops_at_a_rejected_branch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_bare_remote(tmp.path());
+    let local = tmp.path().join("local");
+    let main = stacked_branches(&local, &backend);
+    let control = TransferControl::default();
+    stack::push_stack(&backend, Some(&main), "origin", &control).unwrap();
+    let feature_2 = remote_ref_oid(tmp.path(), "refs/heads/feature-2").unwrap();
+    // 他の誰かが feature-1 を動かしたので、手元の追跡ブランチは古い
+    run_git(
+        &tmp.path().join("remote.git"),
+        &["update-ref", "refs/heads/feature-1", &feature_2],
+    );
+
+    backend
+        .rebase(&main, &RebaseOptions::default(), &no_progress())
+        .unwrap();
+    let result = stack::push_stack(&backend, Some(&main), "origin", &control).unwrap();
+
+    assert!(result.pushed.is_empty());
+    let failed = result.failed.unwrap();
+    assert_eq!(failed.branch, "feature-1");
+    assert_eq!(
+        failed.result.unwrap().updates[0].status,
+        PushRefStatus::RejectedStale
+    );
+    assert!(failed.message.contains("stale info"));
+    // 上のブランチは送られない
+    assert_eq!(
+        remote_ref_oid(tmp.path(), "refs/heads/feature-2"),
+        Some(feature_2)
+    );
+}
+
+// === Reflog tests ===
+
+#[test]
+fn get_reflog_returns_entries() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let entries = backend.get_reflog("HEAD", 10).unwrap();
+    assert!(!entries.is_empty(), "Reflog should have at least one entry");
+    assert_eq!(entries[0].index, 0);
+    assert!(!entries[0].new_oid.is_empty());
+    assert!(!entries[0].new_short_oid.is_empty());
+}
+
+#[test]
+fn get_reflog_respects_limit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    // Create additional commits to have multiple reflog entries
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    backend.commit("commit a", false, false, false).unwrap();
+
+    fs::write(tmp.path().join("b.txt"), "b").unwrap();
+    backend.stage(Path::new("b.txt")).unwrap();
+    backend.commit("commit b", false, false, false).unwrap();
+
+    // Limit to 2 entries
+    let entries = backend.get_reflog("HEAD", 2).unwrap();
+    assert_eq!(entries.len(), 2);
+}
+
+#[test]
+fn get_reflog_has_action_and_message() {
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+
+    let entries = backend.get_reflog("HEAD", 10).unwrap();
+    // The first commit's reflog entry should have "commit" as action
+    let has_commit_action = entries.iter().any(|e| e.action.contains("commit"));
+    assert!(
+        has_commit_action,
+        "Reflog should contain commit action entries"
+    );
+}
@@ -34,7 +34,9 @@ fn init_repo_with_commit(dir: &Path) -> Box<dyn GitBackend> {
     fs::write(dir.join("init.txt"), "init").unwrap();
     let backend = Git2Backend::open(dir).unwrap();
     backend.stage(Path::new("init.txt")).unwrap();
-    backend.commit("initial commit", false, false).unwrap();
+    backend
+        .commit("initial commit", false, false, false)
+        .unwrap();
     Box::new(backend)
 }
 
@@ -49,6 +51,7 @@ fn build_test_app(state: AppState) -> tauri::App<tauri::test::MockRuntime> {
             commands::git::stage_all,
             commands::git::unstage_all,
             commands::git::commit,
+            commands::git::lint_commit_message,
             commands::git::get_current_branch,
             commands::git::stage_hunk,
             commands::git::unstage_hunk,
@@ -59,16 +62,26 @@ fn build_test_app(state: AppState) -> tauri::App<tauri::test::MockRuntime> {
             commands::git::get_head_commit_message,
             commands::config::get_config,
             commands::config::save_config,
+            commands::config::get_co_authors,
+            commands::config::add_co_author,
+            commands::config::remove_co_author,
             commands::branch::list_branches,
             commands::branch::create_branch,
             commands::branch::checkout_branch,
             commands::branch::delete_branch,
+            commands::branch::list_prune_candidates,
+            commands::branch::prune_branches,
             commands::branch::rename_branch,
+            commands::branch::set_branch_upstream,
+            commands::branch::checkout_remote_branch,
             commands::branch::merge_branch,
             commands::branch::get_branch_commits,
+            commands::branch::list_stack,
+            commands::branch::push_stack,
             commands::remote::fetch_remote,
             commands::remote::pull_remote,
             commands::remote::push_remote,
+            commands::remote::cancel_remote_operation,
             commands::remote::list_remotes,
             commands::remote::add_remote,
             commands::remote::remove_remote,
@@ -77,17 +90,24 @@ fn build_test_app(state: AppState) -> tauri::App<tauri::test::MockRuntime> {
             commands::history::get_commit_detail,
             commands::history::get_commit_file_diff,
             commands::history::get_blame,
+            commands::history::get_blame_before,
             commands::history::get_file_history,
+            commands::history::write_commit_graph,
             commands::stash::stash_save,
             commands::stash::list_stashes,
             commands::stash::apply_stash,
             commands::stash::pop_stash,
             commands::stash::drop_stash,
+            commands::stash::stash_branch,
             commands::stash::get_stash_diff,
+            commands::stash::get_stash_detail,
+            commands::stash::rename_stash,
             commands::tag::list_tags,
             commands::tag::create_tag,
             commands::tag::delete_tag,
             commands::tag::checkout_tag,
+            commands::tag::get_release_notes,
+            commands::tag::render_release_notes,
             commands::conflict::get_conflict_files,
             commands::conflict::resolve_conflict,
             commands::conflict::resolve_conflict_block,
@@ -100,14 +120,26 @@ fn build_test_app(state: AppState) -> tauri::App<tauri::test::MockRuntime> {
             commands::rebase::is_rebasing,
             commands::rebase::abort_rebase,
             commands::rebase::continue_rebase,
+            commands::rebase::cancel_rebase,
             commands::rebase::get_rebase_state,
             commands::rebase::get_rebase_todo,
+            commands::rebase::create_fixup_commit,
+            commands::rebase::absorb,
+            commands::rebase::split_commit,
+            commands::rebase::reword_commit,
+            commands::rebase::drop_commit,
+            commands::rebase::move_commit,
             commands::rebase::get_merge_base_content,
             commands::gitconfig::get_gitconfig_entries,
             commands::gitconfig::get_gitconfig_value,
             commands::gitconfig::set_gitconfig_value,
             commands::gitconfig::unset_gitconfig_value,
             commands::gitconfig::get_gitconfig_path,
+            commands::identity::get_identity_check,
+            commands::identity::apply_identity_profile,
+            commands::reset::reset,
+            commands::reset::list_operations,
+            commands::reset::undo_last_operation,
         ])
         .build(tauri::generate_context!())
         .unwrap()
@@ -142,6 +174,8 @@ fn test_no_repo_returns_error() {
         tabs: Mutex::new(HashMap::new()),
         active_tab: Mutex::new(None),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -181,6 +215,8 @@ fn test_get_status_empty_repo() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -218,6 +254,8 @@ fn test_get_status_with_files() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -256,6 +294,8 @@ fn test_stage_and_unstage_file() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -319,6 +359,8 @@ fn test_stage_all_and_unstage_all() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -374,6 +416,8 @@ fn test_commit() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -398,6 +442,273 @@ fn test_commit() {
     assert!(!result.oid.is_empty());
 }
 
+#[test]
+fn test_commit_reports_hook_output_and_skips_hooks() {
+    // Given: a repository whose pre-commit hook rejects every commit
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let hook = tmp.path().join(".git").join("hooks").join("pre-commit");
+    fs::create_dir_all(hook.parent().unwrap()).unwrap();
+    fs::write(&hook, "#!/bin/sh\necho 'lint failed' >&2\nexit 1\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    fs::write(tmp.path().join("new.txt"), "new content").unwrap();
+    backend.stage(Path::new("new.txt")).unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: commit is called without skipping hooks
+    let request = make_request(
+        "commit",
+        serde_json::json!({ "tabId": "test", "message": "test commit", "amend": false, "sign": false }),
+    );
+    let response = tauri::test::get_ipc_response(&webview, request);
+
+    // Then: the error carries the hook's name and output
+    let err_str = response
+        .expect_err("commit should be rejected by the hook")
+        .to_string();
+    assert!(
+        err_str.contains("pre-commit") && err_str.contains("lint failed"),
+        "Expected hook output, got: {err_str}"
+    );
+
+    // When: commit is called with skipHooks
+    let request = make_request(
+        "commit",
+        serde_json::json!({ "tabId": "test", "message": "test commit", "amend": false, "sign": false, "skipHooks": true }),
+    );
+
+    // Then: the commit is created
+    tauri::test::get_ipc_response(&webview, request).expect("commit should skip the hook");
+}
+
+#[test]
+fn test_commit_appends_trailers() {
+    // Given: a repository with a staged file
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("new.txt"), "new content").unwrap();
+    backend.stage(Path::new("new.txt")).unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: commit is called with structured trailers
+    let request = make_request(
+        "commit",
+        serde_json::json!({
+            "tabId": "test",
+            "message": "test commit",
+            "amend": false,
+            "sign": false,
+            "trailers": [
+                { "key": "Signed-off-by", "value": "Test User <test@example.com>" },
+                { "key": "Refs", "value": "#7" }
+            ]
+        }),
+    );
+    let result = tauri::test::get_ipc_response(&webview, request)
+        .expect("commit should succeed")
+        .deserialize::<app_lib::git::types::CommitResult>()
+        .unwrap();
+
+    // Then: the commit detail lists the trailers in order
+    let request = make_request(
+        "get_commit_detail",
+        serde_json::json!({ "tabId": "test", "oid": result.oid }),
+    );
+    let detail = tauri::test::get_ipc_response(&webview, request)
+        .expect("get_commit_detail should succeed")
+        .deserialize::<app_lib::git::types::CommitDetail>()
+        .unwrap();
+    let keys: Vec<&str> = detail
+        .info
+        .trailers
+        .iter()
+        .map(|t| t.key.as_str())
+        .collect();
+    assert_eq!(keys, vec!["Signed-off-by", "Refs"]);
+
+    // When: a trailer key is not a valid token
+    let request = make_request(
+        "commit",
+        serde_json::json!({
+            "tabId": "test",
+            "message": "test commit",
+            "amend": true,
+            "sign": false,
+            "trailers": [{ "key": "Bad key", "value": "x" }]
+        }),
+    );
+
+    // Then: the commit is refused
+    assert!(tauri::test::get_ipc_response(&webview, request).is_err());
+}
+
+#[test]
+fn test_commit_with_identity_override() {
+    // Given: a repository with a staged file
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("new.txt"), "new content").unwrap();
+    backend.stage(Path::new("new.txt")).unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: commit is called with an author override
+    let request = make_request(
+        "commit",
+        serde_json::json!({
+            "tabId": "test",
+            "message": "test commit",
+            "amend": false,
+            "sign": false,
+            "identity": {
+                "author": { "name": "Ann Lee", "email": "ann@acme.example", "time": 1700000000 }
+            }
+        }),
+    );
+    let result = tauri::test::get_ipc_response(&webview, request)
+        .expect("commit should succeed")
+        .deserialize::<app_lib::git::types::CommitResult>()
+        .unwrap();
+
+    // Then: the commit is authored by the override
+    let request = make_request(
+        "get_commit_detail",
+        serde_json::json!({ "tabId": "test", "oid": result.oid }),
+    );
+    let detail = tauri::test::get_ipc_response(&webview, request)
+        .expect("get_commit_detail should succeed")
+        .deserialize::<app_lib::git::types::CommitDetail>()
+        .unwrap();
+    assert_eq!(detail.info.author_name, "Ann Lee");
+    assert_eq!(detail.info.author_email, "ann@acme.example");
+    assert_eq!(detail.info.author_date, 1700000000);
+
+    // When: the identity check is requested
+    let request = make_request("get_identity_check", serde_json::json!({ "tabId": "test" }));
+    let check = tauri::test::get_ipc_response(&webview, request)
+        .expect("get_identity_check should succeed")
+        .deserialize::<app_lib::git::types::IdentityCheck>()
+        .unwrap();
+
+    // Then: the repository's own identity is reported
+    assert_eq!(check.email.as_deref(), Some("test@example.com"));
+}
+
+#[test]
+fn test_lint_commit_message() {
+    // Given: a repository with a commit lint config
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(
+        tmp.path().join(".commitlint.toml"),
+        "types = [\"feat\"]\nsubject_max_length = 20\nwarnings = [\"subject_max_length\"]\n",
+    )
+    .unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: lint_commit_message is called with a non-conventional long subject
+    let request = make_request(
+        "lint_commit_message",
+        serde_json::json!({ "tabId": "test", "message": "update the readme file" }),
+    );
+    let body = tauri::test::get_ipc_response(&webview, request)
+        .expect("lint_commit_message should succeed");
+
+    // Then: the length warning and the header error are reported
+    let violations = body
+        .deserialize::<Vec<app_lib::git::types::CommitLintViolation>>()
+        .expect("should deserialize violations");
+    let rules: Vec<_> = violations.iter().map(|v| (v.rule, v.severity)).collect();
+    assert_eq!(
+        rules,
+        vec![
+            (
+                app_lib::git::types::CommitLintRule::SubjectMaxLength,
+                app_lib::git::types::LintSeverity::Warning
+            ),
+            (
+                app_lib::git::types::CommitLintRule::HeaderFormat,
+                app_lib::git::types::LintSeverity::Error
+            ),
+        ]
+    );
+}
+
 #[test]
 fn test_get_current_branch() {
     // Given: a repository with at least one commit
@@ -417,6 +728,8 @@ fn test_get_current_branch() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -453,6 +766,8 @@ fn test_get_diff() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -491,6 +806,8 @@ fn test_get_head_commit_message() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -531,6 +848,8 @@ fn test_list_branches() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -567,6 +886,8 @@ fn test_create_and_checkout_branch() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -595,13 +916,21 @@ fn test_create_and_checkout_branch() {
     assert_eq!(branch, "feature");
 }
 
-// === Phase 4: リモートコマンド (remote.rs) ===
-
 #[test]
-fn test_list_remotes_empty() {
-    // Given: a repository with no remotes
+fn test_create_branch_from_start_point_and_set_upstream() {
+    // Given: a repository with a tag and a second commit
     let tmp = tempfile::tempdir().unwrap();
     let backend = init_repo_with_commit(tmp.path());
+    backend
+        .create_tag(
+            "v1.0",
+            None,
+            &app_lib::git::types::CreateTagOptions::default(),
+        )
+        .unwrap();
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    backend.commit("second", false, false, false).unwrap();
     let mut tabs = HashMap::new();
     tabs.insert(
         "test".to_string(),
@@ -616,27 +945,48 @@ fn test_list_remotes_empty() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
 
-    // When: list_remotes is called
-    let request = make_request("list_remotes", serde_json::json!({ "tabId": "test" }));
-    let body =
-        tauri::test::get_ipc_response(&webview, request).expect("list_remotes should succeed");
+    // When: create_branch is called with a start point
+    let request = make_request(
+        "create_branch",
+        serde_json::json!({ "tabId": "test", "name": "hotfix", "startPoint": "v1.0" }),
+    );
+    tauri::test::get_ipc_response(&webview, request).expect("create_branch should succeed");
 
-    // Then: empty list
-    let remotes = body
-        .deserialize::<Vec<app_lib::git::types::RemoteInfo>>()
-        .expect("should deserialize remotes");
-    assert!(remotes.is_empty());
+    // Then: the branch points at the tagged commit
+    let request = make_request(
+        "get_branch_commits",
+        serde_json::json!({ "tabId": "test", "branchName": "hotfix", "limit": 10 }),
+    );
+    let body = tauri::test::get_ipc_response(&webview, request)
+        .expect("get_branch_commits should succeed");
+    let commits = body
+        .deserialize::<Vec<serde_json::Value>>()
+        .expect("should deserialize commits");
+    assert_eq!(commits.len(), 1);
+
+    // When: the upstream is unset on a branch without one
+    let request = make_request(
+        "set_branch_upstream",
+        serde_json::json!({ "tabId": "test", "branchName": "hotfix", "upstream": null }),
+    );
+
+    // Then: it succeeds as a no-op
+    tauri::test::get_ipc_response(&webview, request).expect("set_branch_upstream should succeed");
 }
 
 #[test]
-fn test_add_and_list_remote() {
-    // Given: a repository with no remotes
+fn test_list_prune_candidates_and_prune_branches() {
+    // Given: a repository with a merged branch
     let tmp = tempfile::tempdir().unwrap();
     let backend = init_repo_with_commit(tmp.path());
+    let base = backend.current_branch().unwrap();
+    backend.create_branch("merged").unwrap();
     let mut tabs = HashMap::new();
     tabs.insert(
         "test".to_string(),
@@ -651,36 +1001,58 @@ fn test_add_and_list_remote() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
 
-    // When: add_remote is called
+    // When: list_prune_candidates is called
     let request = make_request(
-        "add_remote",
-        serde_json::json!({ "tabId": "test", "name": "origin", "url": "https://example.com/repo.git" }),
+        "list_prune_candidates",
+        serde_json::json!({ "tabId": "test", "base": base }),
     );
-    tauri::test::get_ipc_response(&webview, request).expect("add_remote should succeed");
+    let body = tauri::test::get_ipc_response(&webview, request)
+        .expect("list_prune_candidates should succeed");
 
-    // Then: list_remotes returns the added remote
-    let request = make_request("list_remotes", serde_json::json!({ "tabId": "test" }));
+    // Then: the merged branch is a candidate
+    let candidates = body
+        .deserialize::<Vec<serde_json::Value>>()
+        .expect("should deserialize candidates");
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0]["name"], "merged");
+    assert_eq!(candidates[0]["merged"], true);
+
+    // When: prune_branches deletes it
+    let request = make_request(
+        "prune_branches",
+        serde_json::json!({ "tabId": "test", "names": ["merged"] }),
+    );
     let body =
-        tauri::test::get_ipc_response(&webview, request).expect("list_remotes should succeed");
-    let remotes = body
-        .deserialize::<Vec<app_lib::git::types::RemoteInfo>>()
-        .expect("should deserialize remotes");
-    assert_eq!(remotes.len(), 1);
-    assert_eq!(remotes[0].name, "origin");
-    assert_eq!(remotes[0].url, "https://example.com/repo.git");
-}
+        tauri::test::get_ipc_response(&webview, request).expect("prune_branches should succeed");
 
-// === Phase 5: タグコマンド (tag.rs) ===
+    // Then: the branch is reported as deleted
+    let results = body
+        .deserialize::<Vec<serde_json::Value>>()
+        .expect("should deserialize results");
+    assert_eq!(results[0]["deleted"], true);
+}
 
 #[test]
-fn test_list_tags_empty() {
-    // Given: a repository with no tags
+fn test_list_stack_and_update_refs_in_rebase_todo() {
+    // Given: feature-2 stacked on feature-1, which is stacked on the base
     let tmp = tempfile::tempdir().unwrap();
     let backend = init_repo_with_commit(tmp.path());
+    let base = backend.current_branch().unwrap();
+    for (branch, file) in [("feature-1", "a.txt"), ("feature-2", "b.txt")] {
+        backend.create_branch(branch).unwrap();
+        backend.checkout_branch(branch).unwrap();
+        fs::write(tmp.path().join(file), file).unwrap();
+        backend.stage(Path::new(file)).unwrap();
+        backend
+            .commit(&format!("Add {file}"), false, false, false)
+            .unwrap();
+    }
     let mut tabs = HashMap::new();
     tabs.insert(
         "test".to_string(),
@@ -695,24 +1067,50 @@ fn test_list_tags_empty() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
 
-    // When: list_tags is called
-    let request = make_request("list_tags", serde_json::json!({ "tabId": "test" }));
-    let body = tauri::test::get_ipc_response(&webview, request).expect("list_tags should succeed");
+    // When: list_stack is called
+    let request = make_request(
+        "list_stack",
+        serde_json::json!({ "tabId": "test", "base": base }),
+    );
+    let body = tauri::test::get_ipc_response(&webview, request).expect("list_stack should succeed");
+
+    // Then: both branches are listed bottom first
+    let stack = body
+        .deserialize::<serde_json::Value>()
+        .expect("should deserialize stack");
+    assert_eq!(stack["base"], base);
+    assert_eq!(stack["branches"][0]["name"], "feature-1");
+    assert_eq!(stack["branches"][0]["commit_count"], 1);
+    assert_eq!(stack["branches"][1]["name"], "feature-2");
+    assert_eq!(stack["branches"][1]["is_head"], true);
+
+    // When: the rebase todo of the stack is requested
+    let request = make_request(
+        "get_rebase_todo",
+        serde_json::json!({ "tabId": "test", "onto": base, "limit": 100 }),
+    );
+    let todo = tauri::test::get_ipc_response(&webview, request)
+        .expect("get_rebase_todo should succeed")
+        .deserialize::<Vec<serde_json::Value>>()
+        .unwrap();
 
-    // Then: empty list
-    let tags = body
-        .deserialize::<Vec<app_lib::git::types::TagInfo>>()
-        .expect("should deserialize tags");
-    assert!(tags.is_empty());
+    // Then: feature-1 is updated right after its commit
+    let plan: Vec<&str> = todo.iter().map(|e| e["action"].as_str().unwrap()).collect();
+    assert_eq!(plan, vec!["pick", "update_ref", "pick"]);
+    assert_eq!(todo[1]["argument"], "refs/heads/feature-1");
 }
 
+// === Phase 4: リモートコマンド (remote.rs) ===
+
 #[test]
-fn test_create_and_list_tag() {
-    // Given: a repository with at least one commit
+fn test_list_remotes_empty() {
+    // Given: a repository with no remotes
     let tmp = tempfile::tempdir().unwrap();
     let backend = init_repo_with_commit(tmp.path());
     let mut tabs = HashMap::new();
@@ -729,32 +1127,27 @@ fn test_create_and_list_tag() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
 
-    // When: create_tag is called
-    let request = make_request(
-        "create_tag",
-        serde_json::json!({ "tabId": "test", "name": "v0.1.0", "message": null }),
-    );
-    tauri::test::get_ipc_response(&webview, request).expect("create_tag should succeed");
+    // When: list_remotes is called
+    let request = make_request("list_remotes", serde_json::json!({ "tabId": "test" }));
+    let body =
+        tauri::test::get_ipc_response(&webview, request).expect("list_remotes should succeed");
 
-    // Then: list_tags returns the created tag
-    let request = make_request("list_tags", serde_json::json!({ "tabId": "test" }));
-    let body = tauri::test::get_ipc_response(&webview, request).expect("list_tags should succeed");
-    let tags = body
-        .deserialize::<Vec<app_lib::git::types::TagInfo>>()
-        .expect("should deserialize tags");
-    assert_eq!(tags.len(), 1);
-    assert_eq!(tags[0].name, "v0.1.0");
+    // Then: empty list
+    let remotes = body
+        .deserialize::<Vec<app_lib::git::types::RemoteInfo>>()
+        .expect("should deserialize remotes");
+    assert!(remotes.is_empty());
 }
 
-// === Phase 6: Stash コマンド (stash.rs) ===
-
 #[test]
-fn test_list_stashes_empty() {
-    // Given: a repository with no stashes
+fn test_add_and_list_remote() {
+    // Given: a repository with no remotes
     let tmp = tempfile::tempdir().unwrap();
     let backend = init_repo_with_commit(tmp.path());
     let mut tabs = HashMap::new();
@@ -771,26 +1164,497 @@ fn test_list_stashes_empty() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
 
-    // When: list_stashes is called
-    let request = make_request("list_stashes", serde_json::json!({ "tabId": "test" }));
-    let body =
-        tauri::test::get_ipc_response(&webview, request).expect("list_stashes should succeed");
+    // When: add_remote is called
+    let request = make_request(
+        "add_remote",
+        serde_json::json!({ "tabId": "test", "name": "origin", "url": "https://example.com/repo.git" }),
+    );
+    tauri::test::get_ipc_response(&webview, request).expect("add_remote should succeed");
 
-    // Then: empty list
-    let stashes = body
+    // Then: list_remotes returns the added remote
+    let request = make_request("list_remotes", serde_json::json!({ "tabId": "test" }));
+    let body =
+        tauri::test::get_ipc_response(&webview, request).expect("list_remotes should succeed");
+    let remotes = body
+        .deserialize::<Vec<app_lib::git::types::RemoteInfo>>()
+        .expect("should deserialize remotes");
+    assert_eq!(remotes.len(), 1);
+    assert_eq!(remotes[0].name, "origin");
+    assert_eq!(remotes[0].url, "https://example.com/repo.git");
+}
+
+#[test]
+fn test_cancel_remote_operation_without_transfer() {
+    // Given: no network operation running
+    let state = AppState {
+        tabs: Mutex::new(HashMap::new()),
+        active_tab: Mutex::new(None),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: cancel_remote_operation is called
+    let request = make_request(
+        "cancel_remote_operation",
+        serde_json::json!({ "tabId": "test" }),
+    );
+    let body = tauri::test::get_ipc_response(&webview, request)
+        .expect("cancel_remote_operation should succeed");
+
+    // Then: nothing was cancelled
+    let cancelled = body.deserialize::<bool>().expect("should deserialize bool");
+    assert!(!cancelled);
+}
+
+// === Phase 5: タグコマンド (tag.rs) ===
+
+#[test]
+fn test_list_tags_empty() {
+    // Given: a repository with no tags
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: list_tags is called
+    let request = make_request("list_tags", serde_json::json!({ "tabId": "test" }));
+    let body = tauri::test::get_ipc_response(&webview, request).expect("list_tags should succeed");
+
+    // Then: empty list
+    let tags = body
+        .deserialize::<Vec<app_lib::git::types::TagInfo>>()
+        .expect("should deserialize tags");
+    assert!(tags.is_empty());
+}
+
+#[test]
+fn test_create_and_list_tag() {
+    // Given: a repository with at least one commit
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: create_tag is called
+    let request = make_request(
+        "create_tag",
+        serde_json::json!({ "tabId": "test", "name": "v0.1.0", "message": null }),
+    );
+    tauri::test::get_ipc_response(&webview, request).expect("create_tag should succeed");
+
+    // Then: list_tags returns the created tag
+    let request = make_request("list_tags", serde_json::json!({ "tabId": "test" }));
+    let body = tauri::test::get_ipc_response(&webview, request).expect("list_tags should succeed");
+    let tags = body
+        .deserialize::<Vec<app_lib::git::types::TagInfo>>()
+        .expect("should deserialize tags");
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].name, "v0.1.0");
+}
+
+#[test]
+fn test_create_tag_on_target_commit() {
+    // Given: a repository with two commits
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let first = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    let first = String::from_utf8_lossy(&first.stdout).trim().to_string();
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    backend.commit("second", false, false, false).unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: create_tag is called with the first commit as target
+    let request = make_request(
+        "create_tag",
+        serde_json::json!({
+            "tabId": "test",
+            "name": "v0.1.0",
+            "message": "First",
+            "options": { "target": first },
+        }),
+    );
+    tauri::test::get_ipc_response(&webview, request).expect("create_tag should succeed");
+
+    // Then: the tag points at the first commit and is not signed
+    let request = make_request("list_tags", serde_json::json!({ "tabId": "test" }));
+    let body = tauri::test::get_ipc_response(&webview, request).expect("list_tags should succeed");
+    let tags = body
+        .deserialize::<Vec<app_lib::git::types::TagInfo>>()
+        .expect("should deserialize tags");
+    assert_eq!(tags[0].target_oid, first);
+    assert_eq!(
+        tags[0].signature_status,
+        app_lib::git::types::SignatureStatus::None
+    );
+}
+
+#[test]
+fn test_render_release_notes_as_markdown() {
+    // Given: a tagged repository with a conventional commit after the tag
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    backend
+        .create_tag(
+            "v1.0",
+            None,
+            &app_lib::git::types::CreateTagOptions::default(),
+        )
+        .unwrap();
+    fs::write(tmp.path().join("a.txt"), "a").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    backend
+        .commit("fix(ui): align buttons (#7)", false, false, false)
+        .unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: render_release_notes is called for v1.0..HEAD
+    let request = make_request(
+        "render_release_notes",
+        serde_json::json!({
+            "tabId": "test",
+            "options": { "from": "v1.0" },
+            "format": "markdown",
+        }),
+    );
+    let body = tauri::test::get_ipc_response(&webview, request)
+        .expect("render_release_notes should succeed");
+    let markdown = body
+        .deserialize::<String>()
+        .expect("should deserialize markdown");
+
+    // Then: the fix is listed under Bug Fixes with its scope
+    assert!(markdown.starts_with("## HEAD (v1.0...HEAD)"));
+    assert!(markdown.contains("### Bug Fixes\n\n- **ui:** align buttons (#7)"));
+    assert!(!markdown.contains("initial commit"));
+}
+
+// === Phase 6: Stash コマンド (stash.rs) ===
+
+#[test]
+fn test_list_stashes_empty() {
+    // Given: a repository with no stashes
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: list_stashes is called
+    let request = make_request("list_stashes", serde_json::json!({ "tabId": "test" }));
+    let body =
+        tauri::test::get_ipc_response(&webview, request).expect("list_stashes should succeed");
+
+    // Then: empty list
+    let stashes = body
         .deserialize::<Vec<app_lib::git::types::StashEntry>>()
         .expect("should deserialize stashes");
     assert!(stashes.is_empty());
 }
 
-// === Phase 7: History コマンド (history.rs) ===
+#[test]
+fn test_stash_save_with_paths() {
+    // Given: two modified files
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("other.txt"), "base").unwrap();
+    backend.stage(Path::new("other.txt")).unwrap();
+    backend.commit("add other", false, false, false).unwrap();
+    fs::write(tmp.path().join("init.txt"), "modified").unwrap();
+    fs::write(tmp.path().join("other.txt"), "experiment").unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: stash_save is called with only paths set
+    let request = make_request(
+        "stash_save",
+        serde_json::json!({
+            "tabId": "test",
+            "message": "experiment",
+            "options": { "paths": ["other.txt"] }
+        }),
+    );
+    tauri::test::get_ipc_response(&webview, request).expect("stash_save should succeed");
+
+    // Then: only the selected file is stashed
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("other.txt")).unwrap(),
+        "base"
+    );
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("init.txt")).unwrap(),
+        "modified"
+    );
+}
+
+#[test]
+fn test_get_stash_detail() {
+    // Given: a stash with an untracked file
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("new.txt"), "untracked").unwrap();
+    let options = app_lib::git::types::StashOptions {
+        include_untracked: true,
+        ..Default::default()
+    };
+    backend.stash_save(Some("detail"), &options).unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: get_stash_detail is called
+    let request = make_request(
+        "get_stash_detail",
+        serde_json::json!({ "tabId": "test", "index": 0 }),
+    );
+    let body =
+        tauri::test::get_ipc_response(&webview, request).expect("get_stash_detail should succeed");
+
+    // Then: the untracked file is reported separately
+    let detail = body
+        .deserialize::<app_lib::git::types::StashDetail>()
+        .expect("should deserialize StashDetail");
+    assert_eq!(detail.untracked_files.len(), 1);
+    assert!(detail.worktree_changes.is_empty());
+}
+
+// === Phase 7: History コマンド (history.rs) ===
+
+#[test]
+fn test_get_commit_log() {
+    // Given: a repository with commits
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: get_commit_log is called
+    let request = make_request(
+        "get_commit_log",
+        serde_json::json!({ "tabId": "test", "filter": {
+                "author": null,
+                "since": null,
+                "until": null,
+                "message": null,
+                "path": null
+            },
+            "limit": 100,
+            "skip": 0
+        }),
+    );
+    let body =
+        tauri::test::get_ipc_response(&webview, request).expect("get_commit_log should succeed");
+
+    // Then: at least one commit in the log
+    let log = body
+        .deserialize::<app_lib::git::types::CommitLogResult>()
+        .expect("should deserialize CommitLogResult");
+    assert!(!log.commits.is_empty());
+    assert_eq!(log.commits[0].message, "initial commit");
+}
+
+#[test]
+fn test_get_blame_with_ignore_revs() {
+    // Given: a file whose only change after creation is a reformat
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("code.txt"), "a = 1\n").unwrap();
+    backend.stage(Path::new("code.txt")).unwrap();
+    let original = backend.commit("add code", false, false, false).unwrap();
+    fs::write(tmp.path().join("code.txt"), "a=1\n").unwrap();
+    backend.stage(Path::new("code.txt")).unwrap();
+    let format = backend.commit("format code", false, false, false).unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: get_blame is called with only ignore_revs set
+    let request = make_request(
+        "get_blame",
+        serde_json::json!({
+            "tabId": "test",
+            "path": "code.txt",
+            "commitOid": null,
+            "options": { "ignore_revs": [format.oid] }
+        }),
+    );
+    let body = tauri::test::get_ipc_response(&webview, request).expect("get_blame should succeed");
+
+    // Then: the line is attributed to the commit before the reformat
+    let blame = body
+        .deserialize::<app_lib::git::types::BlameResult>()
+        .expect("should deserialize BlameResult");
+    assert_eq!(blame.lines[0].commit_oid, original.oid);
+    assert_eq!(blame.lines[0].summary, "add code");
+}
 
 #[test]
-fn test_get_commit_log() {
+fn test_write_commit_graph() {
     // Given: a repository with commits
     let tmp = tempfile::tempdir().unwrap();
     let backend = init_repo_with_commit(tmp.path());
@@ -808,33 +1672,18 @@ fn test_get_commit_log() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
 
-    // When: get_commit_log is called
-    let request = make_request(
-        "get_commit_log",
-        serde_json::json!({ "tabId": "test", "filter": {
-                "author": null,
-                "since": null,
-                "until": null,
-                "message": null,
-                "path": null
-            },
-            "limit": 100,
-            "skip": 0
-        }),
-    );
-    let body =
-        tauri::test::get_ipc_response(&webview, request).expect("get_commit_log should succeed");
+    // When: write_commit_graph is called
+    let request = make_request("write_commit_graph", serde_json::json!({ "tabId": "test" }));
+    tauri::test::get_ipc_response(&webview, request).expect("write_commit_graph should succeed");
 
-    // Then: at least one commit in the log
-    let log = body
-        .deserialize::<app_lib::git::types::CommitLogResult>()
-        .expect("should deserialize CommitLogResult");
-    assert!(!log.commits.is_empty());
-    assert_eq!(log.commits[0].message, "initial commit");
+    // Then: the commit-graph file exists
+    assert!(tmp.path().join(".git/objects/info/commit-graph").exists());
 }
 
 // === Phase 8: Conflict コマンド (conflict.rs) ===
@@ -858,6 +1707,8 @@ fn test_is_merging_false() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -892,6 +1743,8 @@ fn test_is_rebasing_false() {
         tabs: Mutex::new(tabs),
         active_tab: Mutex::new(Some("test".to_string())),
         auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
     };
     let app = build_test_app(state);
     let webview = build_test_webview(&app);
@@ -905,3 +1758,429 @@ fn test_is_rebasing_false() {
     let is_rebasing = body.deserialize::<bool>().expect("should deserialize bool");
     assert!(!is_rebasing);
 }
+
+#[test]
+fn test_cancel_rebase_without_rebase() {
+    // Given: no rebase running
+    let state = AppState {
+        tabs: Mutex::new(HashMap::new()),
+        active_tab: Mutex::new(None),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: cancel_rebase is called
+    let request = make_request("cancel_rebase", serde_json::json!({ "tabId": "test" }));
+    let body =
+        tauri::test::get_ipc_response(&webview, request).expect("cancel_rebase should succeed");
+
+    // Then: nothing was cancelled
+    let cancelled = body.deserialize::<bool>().expect("should deserialize bool");
+    assert!(!cancelled);
+}
+
+#[test]
+fn test_create_fixup_commit_and_autosquash_todo() {
+    // Given: a repository with two commits on top of the initial one and a staged fix
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let branch = backend.current_branch().unwrap();
+    let base = backend.get_branch_commits(&branch, 1).unwrap()[0]
+        .oid
+        .clone();
+    fs::write(tmp.path().join("a.txt"), "a1").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    let target = backend.commit("Add a", false, false, false).unwrap().oid;
+    fs::write(tmp.path().join("b.txt"), "b1").unwrap();
+    backend.stage(Path::new("b.txt")).unwrap();
+    backend.commit("Add b", false, false, false).unwrap();
+    fs::write(tmp.path().join("a.txt"), "a2").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: a fixup commit is created for the first commit
+    let request = make_request(
+        "create_fixup_commit",
+        serde_json::json!({ "tabId": "test", "target": target, "kind": "fixup" }),
+    );
+    tauri::test::get_ipc_response(&webview, request).expect("create_fixup_commit should succeed");
+
+    // Then: the autosquashed todo puts it after its target as a fixup
+    let request = make_request(
+        "get_rebase_todo",
+        serde_json::json!({ "tabId": "test", "onto": base, "limit": 100, "autosquash": true }),
+    );
+    let todo = tauri::test::get_ipc_response(&webview, request)
+        .expect("get_rebase_todo should succeed")
+        .deserialize::<Vec<app_lib::git::types::RebaseTodoEntry>>()
+        .unwrap();
+    let plan: Vec<(app_lib::git::types::RebaseAction, &str)> = todo
+        .iter()
+        .map(|e| (e.action, e.message.as_str()))
+        .collect();
+    assert_eq!(
+        plan,
+        vec![
+            (app_lib::git::types::RebaseAction::Pick, "Add a"),
+            (app_lib::git::types::RebaseAction::Fixup, "fixup! Add a"),
+            (app_lib::git::types::RebaseAction::Pick, "Add b"),
+        ]
+    );
+}
+
+#[test]
+fn test_absorb_dry_run_reports_targets() {
+    // Given: a commit on top of the initial one and a staged change to it
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let branch = backend.current_branch().unwrap();
+    let base = backend.get_branch_commits(&branch, 1).unwrap()[0]
+        .oid
+        .clone();
+    fs::write(tmp.path().join("a.txt"), "a1\na2\n").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    let target = backend.commit("Add a", false, false, false).unwrap().oid;
+    fs::write(tmp.path().join("a.txt"), "a1\nA2\n").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: absorb is called as a dry run
+    let request = make_request(
+        "absorb",
+        serde_json::json!({ "tabId": "test", "options": { "base": base, "dry_run": true } }),
+    );
+    let result = tauri::test::get_ipc_response(&webview, request)
+        .expect("absorb should succeed")
+        .deserialize::<app_lib::git::types::AbsorbResult>()
+        .unwrap();
+
+    // Then: the hunk is planned for the commit that added the line, and nothing is committed
+    assert_eq!(result.fixups.len(), 1);
+    assert_eq!(result.fixups[0].target_oid, target);
+    assert!(result.fixups[0].oid.is_none());
+    assert!(result.skipped.is_empty());
+}
+
+#[test]
+fn test_split_commit_by_file() {
+    // Given: a commit that adds two files
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+    fs::write(tmp.path().join("b.txt"), "b\n").unwrap();
+    backend.stage_all().unwrap();
+    let oid = backend
+        .commit("Add a and b", false, false, false)
+        .unwrap()
+        .oid;
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: split_commit moves a.txt into its own commit
+    let request = make_request(
+        "split_commit",
+        serde_json::json!({
+            "tabId": "test",
+            "oid": oid,
+            "groups": [{ "message": "Add a", "files": ["a.txt"] }],
+        }),
+    );
+    let result = tauri::test::get_ipc_response(&webview, request)
+        .expect("split_commit should succeed")
+        .deserialize::<app_lib::git::types::SplitCommitResult>()
+        .unwrap();
+
+    // Then: the rest keeps the original message and HEAD ends at the last new commit
+    assert!(result.rebase.completed);
+    assert_eq!(result.commits.len(), 2);
+    let log = Command::new("git")
+        .args(["log", "--format=%s", "-3"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&log.stdout),
+        "Add a and b\nAdd a\ninitial commit\n"
+    );
+}
+
+#[test]
+fn test_reword_and_move_commit() {
+    // Given: two commits on top of the initial one
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    let a = backend.commit("Add a", false, false, false).unwrap().oid;
+    fs::write(tmp.path().join("b.txt"), "b\n").unwrap();
+    backend.stage(Path::new("b.txt")).unwrap();
+    backend.commit("Add b", false, false, false).unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: the first commit is moved up, then the newest commit reworded
+    let request = make_request(
+        "move_commit",
+        serde_json::json!({ "tabId": "test", "oid": a, "direction": "up" }),
+    );
+    let moved = tauri::test::get_ipc_response(&webview, request)
+        .expect("move_commit should succeed")
+        .deserialize::<app_lib::git::types::RebaseResult>()
+        .unwrap();
+    let head = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    let head = String::from_utf8_lossy(&head.stdout).trim().to_string();
+    let request = make_request(
+        "reword_commit",
+        serde_json::json!({ "tabId": "test", "oid": head, "message": "Add file a" }),
+    );
+    let reworded = tauri::test::get_ipc_response(&webview, request)
+        .expect("reword_commit should succeed")
+        .deserialize::<app_lib::git::types::RebaseResult>()
+        .unwrap();
+
+    // Then: both complete and the log shows the new order and message
+    assert!(moved.completed);
+    assert!(reworded.completed);
+    let log = Command::new("git")
+        .args(["log", "--format=%s", "-3"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&log.stdout),
+        "Add file a\nAdd b\ninitial commit\n"
+    );
+}
+
+#[test]
+fn test_interactive_rebase_stops_at_failed_exec() {
+    // Given: a commit on top of the initial one
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let branch = backend.current_branch().unwrap();
+    let base = backend.get_branch_commits(&branch, 1).unwrap()[0]
+        .oid
+        .clone();
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+    backend.stage(Path::new("a.txt")).unwrap();
+    backend.commit("Add a", false, false, false).unwrap();
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: the todo runs a failing command after each commit
+    let request = make_request(
+        "get_rebase_todo",
+        serde_json::json!({
+            "tabId": "test",
+            "onto": base,
+            "limit": 100,
+            "options": { "exec": "false" },
+        }),
+    );
+    let todo = tauri::test::get_ipc_response(&webview, request)
+        .expect("get_rebase_todo should succeed")
+        .deserialize::<Vec<app_lib::git::types::RebaseTodoEntry>>()
+        .unwrap();
+    let request = make_request(
+        "interactive_rebase",
+        serde_json::json!({ "tabId": "test", "onto": base, "todo": todo }),
+    );
+    let result = tauri::test::get_ipc_response(&webview, request)
+        .expect("interactive_rebase should succeed")
+        .deserialize::<app_lib::git::types::RebaseResult>()
+        .unwrap();
+
+    // Then: the rebase stops at the exec step and reports why
+    assert_eq!(todo.len(), 2);
+    assert_eq!(todo[1].action, app_lib::git::types::RebaseAction::Exec);
+    assert_eq!(todo[1].argument.as_deref(), Some("false"));
+    assert!(!result.completed);
+    let request = make_request("get_rebase_state", serde_json::json!({ "tabId": "test" }));
+    let rebase_state = tauri::test::get_ipc_response(&webview, request)
+        .expect("get_rebase_state should succeed")
+        .deserialize::<Option<app_lib::git::types::RebaseState>>()
+        .unwrap()
+        .expect("a rebase should be in progress");
+    assert_eq!(
+        rebase_state.stop_reason,
+        Some(app_lib::git::types::RebaseStopReason::ExecFailed)
+    );
+    assert_eq!(rebase_state.head_name.as_deref(), Some(branch.as_str()));
+}
+
+// === Phase 10: Reset コマンド (reset.rs) ===
+
+#[test]
+fn test_reset_is_listed_and_undone() {
+    // Given: a repository with two commits
+    let tmp = tempfile::tempdir().unwrap();
+    let backend = init_repo_with_commit(tmp.path());
+    let first = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    let first = String::from_utf8_lossy(&first.stdout).trim().to_string();
+    fs::write(tmp.path().join("second.txt"), "second").unwrap();
+    backend.stage(Path::new("second.txt")).unwrap();
+    let second = backend
+        .commit("second commit", false, false, false)
+        .unwrap()
+        .oid;
+    let mut tabs = HashMap::new();
+    tabs.insert(
+        "test".to_string(),
+        RepoContext {
+            backend: backend,
+            watcher: None,
+            path: tmp.path().to_string_lossy().to_string(),
+            name: "test-repo".to_string(),
+        },
+    );
+    let state = AppState {
+        tabs: Mutex::new(tabs),
+        active_tab: Mutex::new(Some("test".to_string())),
+        auto_fetch_handle: Mutex::new(None),
+        transfers: Mutex::new(HashMap::new()),
+        rebases: Mutex::new(HashMap::new()),
+    };
+    let app = build_test_app(state);
+    let webview = build_test_webview(&app);
+
+    // When: HEAD is hard reset to the first commit
+    let request = make_request(
+        "reset",
+        serde_json::json!({ "tabId": "test", "oid": first, "mode": "hard" }),
+    );
+    tauri::test::get_ipc_response(&webview, request).expect("reset should succeed");
+
+    // Then: the reset is recorded
+    let request = make_request("list_operations", serde_json::json!({ "tabId": "test" }));
+    let body =
+        tauri::test::get_ipc_response(&webview, request).expect("list_operations should succeed");
+    let operations = body
+        .deserialize::<Vec<serde_json::Value>>()
+        .expect("should deserialize operations");
+    assert_eq!(operations.len(), 1);
+    assert_eq!(operations[0]["kind"], "reset");
+
+    // When: the last operation is undone
+    let request = make_request(
+        "undo_last_operation",
+        serde_json::json!({ "tabId": "test" }),
+    );
+    tauri::test::get_ipc_response(&webview, request).expect("undo_last_operation should succeed");
+
+    // Then: HEAD is back on the second commit and its file is restored
+    let head = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&head.stdout).trim(), second);
+    assert!(tmp.path().join("second.txt").exists());
+
+    // When: there is nothing left to undo
+    let request = make_request(
+        "undo_last_operation",
+        serde_json::json!({ "tabId": "test" }),
+    );
+    let result = tauri::test::get_ipc_response(&webview, request);
+
+    // Then: an error is returned
+    assert!(result.is_err());
+}
@@ -1,12 +1,14 @@
-import { useCallback, useEffect } from "react";
+import { useCallback, useEffect, useState } from "react";
 import { PullDialog } from "./components/organisms/PullDialog";
 import { RemoteModal } from "./components/organisms/RemoteModal";
 import { SearchModal } from "./components/organisms/SearchModal";
 import { SettingsModal } from "./components/organisms/SettingsModal";
 import { TagsModal } from "./components/organisms/TagsModal";
 import { ToastContainer } from "./components/organisms/ToastContainer";
+import type { RemoteTransfer } from "./components/organisms/transferProgress";
 import { AppShell } from "./components/templates/AppShell";
 import { useFileWatcher } from "./hooks/useFileWatcher";
+import { useRemoteProgress } from "./hooks/useRemoteProgress";
 import { useSystemNotification } from "./hooks/useSystemNotification";
 import { useTheme } from "./hooks/useTheme";
 import { BlamePage } from "./pages/blame";
@@ -27,10 +29,10 @@ import { RevertPage } from "./pages/revert";
 import { StashPage } from "./pages/stash";
 import { SubmodulesPage } from "./pages/submodules";
 import { WorktreesPage } from "./pages/worktrees";
-import type { PullOption } from "./services/git";
+import type { PullOption, RemoteOperation } from "./services/git";
 import { useConfigStore } from "./stores/configStore";
 import { useGitStore } from "./stores/gitStore";
-import { useTabStore } from "./stores/tabStore";
+import { DEFAULT_TAB_ID, getActiveTabId, useTabStore } from "./stores/tabStore";
 import { useUIStore } from "./stores/uiStore";
 
 export function App() {
@@ -43,6 +45,7 @@ export function App() {
   const fetchRemote = useGitStore((s) => s.fetchRemote);
   const pullRemote = useGitStore((s) => s.pullRemote);
   const pushRemote = useGitStore((s) => s.pushRemote);
+  const cancelRemoteOperation = useGitStore((s) => s.cancelRemoteOperation);
   const merging = useGitStore((s) => s.merging);
   const rebasing = useGitStore((s) => s.rebasing);
   const fetchMergeState = useGitStore((s) => s.fetchMergeState);
@@ -143,46 +146,140 @@ export function App() {
 
   const defaultRemote = remotes.length > 0 ? remotes[0].name : null;
 
+  // 実行中のネットワーク操作をタブごとに保持する
+  const [transfers, setTransfers] = useState<Record<string, RemoteTransfer>>(
+    {},
+  );
+
+  useRemoteProgress((event) => {
+    setTransfers((current) =>
+      current[event.tab_id]
+        ? {
+            ...current,
+            [event.tab_id]: {
+              operation: event.operation,
+              progress: event.progress,
+            },
+          }
+        : current,
+    );
+  });
+
+  const runTransfer = useCallback(
+    async <T,>(operation: RemoteOperation, run: () => Promise<T>) => {
+      const tabId = getActiveTabId();
+      setTransfers((current) => ({
+        ...current,
+        [tabId]: { operation, progress: null },
+      }));
+      try {
+        return await run();
+      } finally {
+        setTransfers(({ [tabId]: _, ...rest }) => rest);
+      }
+    },
+    [],
+  );
+
+  const handleCancelTransfer = useCallback(() => {
+    cancelRemoteOperation().catch((e: unknown) => {
+      addToast(`Cancel failed: ${String(e)}`, "error");
+    });
+  }, [cancelRemoteOperation, addToast]);
+
+  const reportFailure = useCallback(
+    (action: string, e: unknown) => {
+      if (String(e).includes("operation cancelled")) {
+        addToast(`${action} cancelled`, "info");
+      } else {
+        addToast(`${action} failed: ${String(e)}`, "error");
+      }
+    },
+    [addToast],
+  );
+
   const handleFetch = useCallback(async () => {
     if (!defaultRemote) return;
     try {
-      await fetchRemote(defaultRemote);
-      addToast(`Fetched from '${defaultRemote}'`, "success");
+      const result = await runTransfer("fetch", () =>
+        fetchRemote(defaultRemote),
+      );
+      addToast(
+        result.new_commits > 0
+          ? `Fetched ${result.new_commits} new commit(s) from '${defaultRemote}'`
+          : `Fetched from '${defaultRemote}'`,
+        "success",
+      );
       await fetchBranch();
     } catch (e: unknown) {
-      addToast(`Fetch failed: ${String(e)}`, "error");
+      reportFailure("Fetch", e);
     }
-  }, [defaultRemote, fetchRemote, addToast, fetchBranch]);
+  }, [
+    defaultRemote,
+    fetchRemote,
+    runTransfer,
+    reportFailure,
+    addToast,
+    fetchBranch,
+  ]);
 
   const handlePull = useCallback(
     async (option: PullOption) => {
       if (!defaultRemote) return;
       try {
-        const result = await pullRemote(defaultRemote, option);
-        addToast(`Pull ${result.kind}: ${defaultRemote}`, "success");
         closeModal();
+        const result = await runTransfer("pull", () =>
+          pullRemote(defaultRemote, option),
+        );
+        addToast(`Pull ${result.kind}: ${defaultRemote}`, "success");
         await fetchStatus();
         await fetchBranch();
       } catch (e: unknown) {
-        addToast(`Pull failed: ${String(e)}`, "error");
+        reportFailure("Pull", e);
       }
     },
-    [defaultRemote, pullRemote, addToast, closeModal, fetchStatus, fetchBranch],
+    [
+      defaultRemote,
+      pullRemote,
+      runTransfer,
+      reportFailure,
+      addToast,
+      closeModal,
+      fetchStatus,
+      fetchBranch,
+    ],
   );
 
   const handlePush = useCallback(async () => {
     if (!defaultRemote) return;
     try {
-      const result = await pushRemote(defaultRemote);
-      addToast(
-        `Pushed '${result.branch}' to '${result.remote_name}'`,
-        "success",
+      const result = await runTransfer("push", () => pushRemote(defaultRemote));
+      const rejected = result.updates.find((u) =>
+        u.status.startsWith("rejected"),
       );
+      if (rejected) {
+        addToast(
+          `Push rejected: ${rejected.remote_ref} (${rejected.message ?? rejected.status})`,
+          "error",
+        );
+      } else {
+        addToast(
+          `Pushed '${result.branch}' to '${result.remote_name}'`,
+          "success",
+        );
+      }
       await fetchBranch();
     } catch (e: unknown) {
-      addToast(`Push failed: ${String(e)}`, "error");
+      reportFailure("Push", e);
     }
-  }, [defaultRemote, pushRemote, addToast, fetchBranch]);
+  }, [
+    defaultRemote,
+    pushRemote,
+    runTransfer,
+    reportFailure,
+    addToast,
+    fetchBranch,
+  ]);
 
   const changesCount = status?.files.length ?? 0;
 
@@ -200,6 +297,8 @@ export function App() {
         onPush={handlePush}
         onTags={() => openModal("tags")}
         onRemote={() => openModal("remotes")}
+        onCancelTransfer={handleCancelTransfer}
+        transfer={transfers[activeTabId ?? DEFAULT_TAB_ID] ?? null}
       >
         {activePage === "changes" && <ChangesPage />}
         {activePage === "branches" && <BranchesPage />}
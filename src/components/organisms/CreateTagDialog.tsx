@@ -1,20 +1,34 @@
 import { useId, useState } from "react";
+import type { CreateTagOptions } from "../../services/tag";
 import { Modal } from "./Modal";
 
 interface CreateTagDialogProps {
-  onConfirm: (name: string, message: string | null) => void;
+  onConfirm: (
+    name: string,
+    message: string | null,
+    options: CreateTagOptions,
+  ) => void;
   onClose: () => void;
 }
 
 export function CreateTagDialog({ onConfirm, onClose }: CreateTagDialogProps) {
   const [name, setName] = useState("");
   const [message, setMessage] = useState("");
+  const [target, setTarget] = useState("");
+  const [sign, setSign] = useState(false);
   const nameId = useId();
   const messageId = useId();
+  const targetId = useId();
+
+  // 署名付きタグは注釈付きタグでなければならない
+  const canSign = message.trim() !== "";
 
   const handleSubmit = () => {
     if (name.trim()) {
-      onConfirm(name.trim(), message.trim() || null);
+      onConfirm(name.trim(), message.trim() || null, {
+        target: target.trim() || null,
+        sign: sign && canSign,
+      });
     }
   };
 
@@ -73,6 +87,31 @@ export function CreateTagDialog({ onConfirm, onClose }: CreateTagDialogProps) {
         onChange={(e) => setMessage(e.target.value)}
         onKeyDown={handleKeyDown}
       />
+      <label
+        className="modal-label"
+        htmlFor={targetId}
+        style={{ marginTop: 12 }}
+      >
+        Commit (optional, defaults to HEAD)
+      </label>
+      <input
+        id={targetId}
+        className="modal-input"
+        type="text"
+        placeholder="HEAD"
+        value={target}
+        onChange={(e) => setTarget(e.target.value)}
+        onKeyDown={handleKeyDown}
+      />
+      <label className="amend-toggle" style={{ marginTop: 12 }}>
+        <input
+          type="checkbox"
+          checked={sign && canSign}
+          disabled={!canSign}
+          onChange={() => setSign(!sign)}
+        />
+        <span>Sign (requires a message)</span>
+      </label>
     </Modal>
   );
 }
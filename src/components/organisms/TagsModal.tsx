@@ -1,4 +1,6 @@
 import { useCallback, useEffect, useState } from "react";
+import type { SignatureStatus } from "../../services/history";
+import type { CreateTagOptions } from "../../services/tag";
 import { useGitStore } from "../../stores/gitStore";
 import { useUIStore } from "../../stores/uiStore";
 import { CreateTagDialog } from "./CreateTagDialog";
@@ -13,12 +15,34 @@ function formatDate(timestamp: number | null): string {
   return new Date(timestamp * 1000).toLocaleDateString();
 }
 
+function signatureLabel(status: SignatureStatus): string {
+  switch (status) {
+    case "good":
+      return " \u00B7 signed";
+    case "bad":
+      return " \u00B7 bad signature";
+    case "untrusted":
+      return " \u00B7 signed (untrusted)";
+    case "expired":
+      return " \u00B7 signed (expired)";
+    case "error":
+      return " \u00B7 signature unverifiable";
+    default:
+      return "";
+  }
+}
+
 export function TagsModal({ onClose }: TagsModalProps) {
   const tags = useGitStore((s) => s.tags);
   const fetchTags = useGitStore((s) => s.fetchTags);
   const createTag = useGitStore((s) => s.createTag);
   const deleteTag = useGitStore((s) => s.deleteTag);
   const checkoutTag = useGitStore((s) => s.checkoutTag);
+  const pushTag = useGitStore((s) => s.pushTag);
+  const deleteRemoteTag = useGitStore((s) => s.deleteRemoteTag);
+  const renderReleaseNotes = useGitStore((s) => s.renderReleaseNotes);
+  const remotes = useGitStore((s) => s.remotes);
+  const fetchRemotes = useGitStore((s) => s.fetchRemotes);
   const fetchBranch = useGitStore((s) => s.fetchBranch);
   const addToast = useUIStore((s) => s.addToast);
 
@@ -30,10 +54,19 @@ export function TagsModal({ onClose }: TagsModalProps) {
     });
   }, [fetchTags, addToast]);
 
+  useEffect(() => {
+    fetchRemotes().catch((e: unknown) => {
+      addToast(String(e), "error");
+    });
+  }, [fetchRemotes, addToast]);
+
+  const remoteName =
+    remotes.find((r) => r.name === "origin")?.name ?? remotes[0]?.name ?? null;
+
   const handleCreate = useCallback(
-    async (name: string, message: string | null) => {
+    async (name: string, message: string | null, options: CreateTagOptions) => {
       try {
-        await createTag(name, message);
+        await createTag(name, message, options);
         addToast(`Tag '${name}' created`, "success");
         setShowCreate(false);
         await fetchTags();
@@ -57,6 +90,58 @@ export function TagsModal({ onClose }: TagsModalProps) {
     [deleteTag, addToast, fetchTags],
   );
 
+  const handlePush = useCallback(
+    async (name: string) => {
+      if (!remoteName) return;
+      try {
+        const result = await pushTag(remoteName, name);
+        const rejected = result.updates.find((u) =>
+          u.status.startsWith("rejected"),
+        );
+        if (rejected) {
+          addToast(
+            `Push of tag '${name}' rejected: ${rejected.message ?? rejected.status}`,
+            "error",
+          );
+        } else {
+          addToast(`Tag '${name}' pushed to ${remoteName}`, "success");
+        }
+      } catch (e: unknown) {
+        addToast(`Failed to push tag: ${String(e)}`, "error");
+      }
+    },
+    [remoteName, pushTag, addToast],
+  );
+
+  const handleDeleteRemote = useCallback(
+    async (name: string) => {
+      if (!remoteName) return;
+      try {
+        await deleteRemoteTag(remoteName, name);
+        addToast(`Tag '${name}' deleted from ${remoteName}`, "success");
+      } catch (e: unknown) {
+        addToast(`Failed to delete remote tag: ${String(e)}`, "error");
+      }
+    },
+    [remoteName, deleteRemoteTag, addToast],
+  );
+
+  // タグはバージョン順なので、一つ前のタグからの差分をリリースノートにする
+  const handleCopyNotes = useCallback(
+    async (index: number) => {
+      const to = tags[index].name;
+      const from = index > 0 ? tags[index - 1].name : null;
+      try {
+        const notes = await renderReleaseNotes({ from, to }, "markdown");
+        await navigator.clipboard.writeText(notes);
+        addToast(`Release notes for '${to}' copied to clipboard`, "success");
+      } catch (e: unknown) {
+        addToast(`Failed to generate release notes: ${String(e)}`, "error");
+      }
+    },
+    [tags, renderReleaseNotes, addToast],
+  );
+
   const handleCheckout = useCallback(
     async (name: string) => {
       try {
@@ -93,7 +178,7 @@ export function TagsModal({ onClose }: TagsModalProps) {
           </div>
         ) : (
           <div className="tag-list">
-            {tags.map((tag) => (
+            {tags.map((tag, index) => (
               <div key={tag.name} className="tag-item">
                 <div className="tag-info">
                   <span className="tag-name">{tag.name}</span>
@@ -103,6 +188,7 @@ export function TagsModal({ onClose }: TagsModalProps) {
                       ? ` \u00B7 ${formatDate(tag.tagger_date)}`
                       : ""}
                     {tag.is_annotated ? " \u00B7 annotated" : ""}
+                    {signatureLabel(tag.signature_status)}
                   </span>
                 </div>
                 <div className="tag-actions">
@@ -113,6 +199,31 @@ export function TagsModal({ onClose }: TagsModalProps) {
                   >
                     Checkout
                   </button>
+                  <button
+                    type="button"
+                    className="btn btn-secondary btn-sm"
+                    onClick={() => handleCopyNotes(index)}
+                  >
+                    Copy Notes
+                  </button>
+                  {remoteName && (
+                    <>
+                      <button
+                        type="button"
+                        className="btn btn-secondary btn-sm"
+                        onClick={() => handlePush(tag.name)}
+                      >
+                        Push
+                      </button>
+                      <button
+                        type="button"
+                        className="btn btn-secondary btn-sm"
+                        onClick={() => handleDeleteRemote(tag.name)}
+                      >
+                        Delete Remote
+                      </button>
+                    </>
+                  )}
                   <button
                     type="button"
                     className="btn btn-danger btn-sm"
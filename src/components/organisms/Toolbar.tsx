@@ -1,3 +1,6 @@
+import type { RemoteTransfer } from "./transferProgress";
+import { formatTransferProgress } from "./transferProgress";
+
 interface ToolbarProps {
   branch: string | null;
   defaultRemoteName: string | null;
@@ -6,6 +9,8 @@ interface ToolbarProps {
   onPush: () => void;
   onTags: () => void;
   onRemote: () => void;
+  onCancelTransfer: () => void;
+  transfer: RemoteTransfer | null;
   disabled: boolean;
 }
 
@@ -17,8 +22,12 @@ export function Toolbar({
   onPush,
   onTags,
   onRemote,
+  onCancelTransfer,
+  transfer,
   disabled,
 }: ToolbarProps) {
+  const busy = disabled || transfer !== null;
+
   return (
     <div className="toolbar">
       <div className="toolbar-group">
@@ -42,7 +51,7 @@ export function Toolbar({
           type="button"
           className="toolbar-btn"
           onClick={onFetch}
-          disabled={disabled}
+          disabled={busy}
         >
           <svg viewBox="0 0 16 16" fill="currentColor" aria-hidden="true">
             <path d="M8 1a.5.5 0 0 1 .5.5v11.793l3.146-3.147a.5.5 0 0 1 .708.708l-4 4a.5.5 0 0 1-.708 0l-4-4a.5.5 0 0 1 .708-.708L7.5 13.293V1.5A.5.5 0 0 1 8 1z" />
@@ -53,7 +62,7 @@ export function Toolbar({
           type="button"
           className="toolbar-btn"
           onClick={onPull}
-          disabled={disabled}
+          disabled={busy}
         >
           <svg viewBox="0 0 16 16" fill="currentColor" aria-hidden="true">
             <path d="M8 1a.5.5 0 0 1 .5.5v11.793l3.146-3.147a.5.5 0 0 1 .708.708l-4 4a.5.5 0 0 1-.708 0l-4-4a.5.5 0 0 1 .708-.708L7.5 13.293V1.5A.5.5 0 0 1 8 1z" />
@@ -64,7 +73,7 @@ export function Toolbar({
           type="button"
           className="toolbar-btn"
           onClick={onPush}
-          disabled={disabled}
+          disabled={busy}
         >
           <svg viewBox="0 0 16 16" fill="currentColor" aria-hidden="true">
             <path d="M8 15a.5.5 0 0 0 .5-.5V2.707l3.146 3.147a.5.5 0 0 0 .708-.708l-4-4a.5.5 0 0 0-.708 0l-4 4a.5.5 0 1 0 .708.708L7.5 2.707V14.5a.5.5 0 0 0 .5.5z" />
@@ -72,6 +81,20 @@ export function Toolbar({
           <span>Push</span>
         </button>
       </div>
+      {transfer && (
+        <div className="toolbar-group toolbar-transfer">
+          <span className="toolbar-transfer-progress">
+            {formatTransferProgress(transfer.operation, transfer.progress)}
+          </span>
+          <button
+            type="button"
+            className="toolbar-btn"
+            onClick={onCancelTransfer}
+          >
+            <span>Cancel</span>
+          </button>
+        </div>
+      )}
       <div className="toolbar-group">
         <button
           type="button"
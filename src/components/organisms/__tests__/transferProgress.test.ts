@@ -0,0 +1,41 @@
+import { describe, expect, it } from "vitest";
+import type { TransferProgress } from "../../../services/git";
+import { formatTransferProgress } from "../transferProgress";
+
+const progress: TransferProgress = {
+  total_objects: 0,
+  transferred_objects: 0,
+  indexed_objects: 0,
+  total_deltas: 0,
+  indexed_deltas: 0,
+  transferred_bytes: 0,
+  message: null,
+};
+
+describe("formatTransferProgress", () => {
+  it("shows only the operation before any progress arrives", () => {
+    expect(formatTransferProgress("fetch", null)).toBe("Fetching…");
+    expect(formatTransferProgress("push", progress)).toBe("Pushing…");
+  });
+
+  it("shows transferred objects and bytes", () => {
+    expect(
+      formatTransferProgress("pull", {
+        ...progress,
+        total_objects: 10,
+        transferred_objects: 4,
+        transferred_bytes: 2048,
+      }),
+    ).toBe("Pulling 4/10 objects, 2.0 KiB");
+  });
+
+  it("prefers the remote's message", () => {
+    expect(
+      formatTransferProgress("fetch", {
+        ...progress,
+        total_objects: 10,
+        message: "Counting objects: 100% (10/10)\n",
+      }),
+    ).toBe("Fetching: Counting objects: 100% (10/10)");
+  });
+});
@@ -0,0 +1,35 @@
+import type { RemoteOperation, TransferProgress } from "../../services/git";
+
+export interface RemoteTransfer {
+  operation: RemoteOperation;
+  progress: TransferProgress | null;
+}
+
+const LABELS: Record<RemoteOperation, string> = {
+  fetch: "Fetching",
+  pull: "Pulling",
+  push: "Pushing",
+  clone: "Cloning",
+};
+
+function formatBytes(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`;
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KiB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MiB`;
+}
+
+export function formatTransferProgress(
+  operation: RemoteOperation,
+  progress: TransferProgress | null,
+): string {
+  const label = LABELS[operation];
+  if (!progress) return `${label}…`;
+  if (progress.message) return `${label}: ${progress.message.trim()}`;
+  if (progress.total_objects === 0) return `${label}…`;
+
+  const { transferred_objects: done, total_objects: total } = progress;
+  const objects = `${done}/${total} objects`;
+  return progress.transferred_bytes > 0
+    ? `${label} ${objects}, ${formatBytes(progress.transferred_bytes)}`
+    : `${label} ${objects}`;
+}
@@ -5,6 +5,7 @@ import { Sidebar } from "../organisms/Sidebar";
 import { Statusbar } from "../organisms/Statusbar";
 import { Titlebar } from "../organisms/Titlebar";
 import { Toolbar } from "../organisms/Toolbar";
+import type { RemoteTransfer } from "../organisms/transferProgress";
 
 interface AppShellProps {
   branch: string | null;
@@ -18,6 +19,8 @@ interface AppShellProps {
   onPush: () => void;
   onTags: () => void;
   onRemote: () => void;
+  onCancelTransfer: () => void;
+  transfer: RemoteTransfer | null;
   children: ReactNode;
 }
 
@@ -33,6 +36,8 @@ export function AppShell({
   onPush,
   onTags,
   onRemote,
+  onCancelTransfer,
+  transfer,
   children,
 }: AppShellProps) {
   const defaultRemoteName = remotes[0]?.name ?? null;
@@ -50,6 +55,8 @@ export function AppShell({
         onPush={onPush}
         onTags={onTags}
         onRemote={onRemote}
+        onCancelTransfer={onCancelTransfer}
+        transfer={transfer}
         disabled={!hasRemotes}
       />
       <div className="main-layout">
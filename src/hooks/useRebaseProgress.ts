@@ -0,0 +1,39 @@
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useRef } from "react";
+import type { RebaseProgress, RebaseProgressEvent } from "../services/rebase";
+import { getActiveTabId } from "../stores/tabStore";
+
+export function useRebaseProgress(
+  onProgress: (progress: RebaseProgress) => void,
+) {
+  const onProgressRef = useRef(onProgress);
+  onProgressRef.current = onProgress;
+
+  useEffect(() => {
+    let cancelled = false;
+
+    const setup = async () => {
+      const unlisten = await listen<RebaseProgressEvent>(
+        "rebase:progress",
+        (event) => {
+          if (cancelled) return;
+          if (event.payload.tab_id !== getActiveTabId()) return;
+          onProgressRef.current(event.payload.progress);
+        },
+      );
+
+      if (cancelled) {
+        unlisten();
+      }
+
+      return unlisten;
+    };
+
+    const unlistenPromise = setup();
+
+    return () => {
+      cancelled = true;
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+}
@@ -0,0 +1,37 @@
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useRef } from "react";
+import type { RemoteProgressEvent } from "../services/git";
+
+export function useRemoteProgress(
+  onProgress: (event: RemoteProgressEvent) => void,
+) {
+  const onProgressRef = useRef(onProgress);
+  onProgressRef.current = onProgress;
+
+  useEffect(() => {
+    let cancelled = false;
+
+    const setup = async () => {
+      const unlisten = await listen<RemoteProgressEvent>(
+        "remote:progress",
+        (event) => {
+          if (cancelled) return;
+          onProgressRef.current(event.payload);
+        },
+      );
+
+      if (cancelled) {
+        unlisten();
+      }
+
+      return unlisten;
+    };
+
+    const unlistenPromise = setup();
+
+    return () => {
+      cancelled = true;
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+}
@@ -1,5 +1,5 @@
 import { useCallback, useEffect, useState } from "react";
-import type { MergeOption } from "../../services/git";
+import type { DeleteBranchOptions, MergeOption } from "../../services/git";
 import { getBranchCommits } from "../../services/git";
 import type { CommitInfo } from "../../services/history";
 import { useGitStore } from "../../stores/gitStore";
@@ -20,6 +20,7 @@ export function BranchesPage() {
   const fetchBranch = useGitStore((s) => s.fetchBranch);
   const createBranch = useGitStore((s) => s.createBranch);
   const checkoutBranch = useGitStore((s) => s.checkoutBranch);
+  const checkoutRemoteBranch = useGitStore((s) => s.checkoutRemoteBranch);
   const deleteBranch = useGitStore((s) => s.deleteBranch);
   const renameBranch = useGitStore((s) => s.renameBranch);
   const mergeBranch = useGitStore((s) => s.mergeBranch);
@@ -84,26 +85,45 @@ export function BranchesPage() {
   const handleCheckoutBranch = useCallback(async () => {
     if (!selected) return;
     try {
-      await checkoutBranch(selected.name);
-      addToast(`Switched to '${selected.name}'`, "success");
+      if (selected.is_remote) {
+        const local = await checkoutRemoteBranch(selected.name);
+        addToast(
+          `Switched to '${local}' tracking '${selected.name}'`,
+          "success",
+        );
+        setSelectedBranch(local);
+      } else {
+        await checkoutBranch(selected.name);
+        addToast(`Switched to '${selected.name}'`, "success");
+      }
       await refreshAll();
     } catch (e: unknown) {
       addToast(`Failed to switch branch: ${String(e)}`, "error");
     }
-  }, [selected, checkoutBranch, addToast, refreshAll]);
+  }, [selected, checkoutBranch, checkoutRemoteBranch, addToast, refreshAll]);
 
-  const handleDeleteBranch = useCallback(async () => {
-    if (!selected) return;
-    try {
-      await deleteBranch(selected.name);
-      addToast(`Branch '${selected.name}' deleted`, "success");
-      setSelectedBranch(null);
-      closeModal();
-      await refreshAll();
-    } catch (e: unknown) {
-      addToast(`Failed to delete branch: ${String(e)}`, "error");
-    }
-  }, [selected, deleteBranch, addToast, closeModal, refreshAll]);
+  const handleDeleteBranch = useCallback(
+    async (options: DeleteBranchOptions) => {
+      if (!selected) return;
+      try {
+        const result = await deleteBranch(selected.name, options);
+        if (!result.deleted) {
+          addToast(
+            `'${selected.name}' has ${result.unmerged_commits.length} unmerged commit(s). Check "Delete even if not merged" to delete it.`,
+            "error",
+          );
+          return;
+        }
+        addToast(`Branch '${selected.name}' deleted`, "success");
+        setSelectedBranch(null);
+        closeModal();
+        await refreshAll();
+      } catch (e: unknown) {
+        addToast(`Failed to delete branch: ${String(e)}`, "error");
+      }
+    },
+    [selected, deleteBranch, addToast, closeModal, refreshAll],
+  );
 
   const handleRenameBranch = useCallback(
     async (newName: string) => {
@@ -205,6 +225,7 @@ export function BranchesPage() {
       {activeModal === "delete" && selected && (
         <DeleteBranchDialog
           branchName={selected.name}
+          upstream={selected.upstream}
           onConfirm={handleDeleteBranch}
           onClose={closeModal}
         />
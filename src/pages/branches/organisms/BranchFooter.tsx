@@ -61,7 +61,7 @@ export function BranchFooter({
             Delete
           </button>
         )}
-        {!branch.is_head && !branch.is_remote && (
+        {!branch.is_head && (
           <button type="button" className="btn btn-primary" onClick={onSwitch}>
             <svg viewBox="0 0 16 16" fill="currentColor" aria-hidden="true">
               <path d="M13.854 3.646a.5.5 0 0 1 0 .708l-7 7a.5.5 0 0 1-.708 0l-3.5-3.5a.5.5 0 1 1 .708-.708L6.5 10.293l6.646-6.647a.5.5 0 0 1 .708 0z" />
@@ -1,16 +1,23 @@
+import { useState } from "react";
 import { Modal } from "../../../components/organisms/Modal";
+import type { DeleteBranchOptions } from "../../../services/git";
 
 interface DeleteBranchDialogProps {
   branchName: string;
-  onConfirm: () => void;
+  upstream: string | null;
+  onConfirm: (options: DeleteBranchOptions) => void;
   onClose: () => void;
 }
 
 export function DeleteBranchDialog({
   branchName,
+  upstream,
   onConfirm,
   onClose,
 }: DeleteBranchDialogProps) {
+  const [force, setForce] = useState(false);
+  const [deleteRemote, setDeleteRemote] = useState(false);
+
   return (
     <Modal
       title="Delete Branch"
@@ -21,7 +28,11 @@ export function DeleteBranchDialog({
           <button type="button" className="btn btn-secondary" onClick={onClose}>
             Cancel
           </button>
-          <button type="button" className="btn btn-danger" onClick={onConfirm}>
+          <button
+            type="button"
+            className="btn btn-danger"
+            onClick={() => onConfirm({ force, delete_remote: deleteRemote })}
+          >
             Delete
           </button>
         </>
@@ -31,6 +42,24 @@ export function DeleteBranchDialog({
         Are you sure you want to delete branch <strong>{branchName}</strong>?
         This action cannot be undone.
       </p>
+      <label className="amend-toggle">
+        <input
+          type="checkbox"
+          checked={force}
+          onChange={() => setForce(!force)}
+        />
+        <span>Delete even if not merged</span>
+      </label>
+      {upstream && (
+        <label className="amend-toggle">
+          <input
+            type="checkbox"
+            checked={deleteRemote}
+            onChange={() => setDeleteRemote(!deleteRemote)}
+          />
+          <span>Also delete {upstream}</span>
+        </label>
+      )}
     </Modal>
   );
 }
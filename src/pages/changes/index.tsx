@@ -1,5 +1,8 @@
-import { useCallback, useEffect } from "react";
+import { useCallback, useEffect, useState } from "react";
 import type { HunkIdentifier, StagingState } from "../../services/git";
+import type { CommitTrailer } from "../../services/history";
+import type { CommitIdentity, IdentityCheck } from "../../services/identity";
+import { useConfigStore } from "../../stores/configStore";
 import { useGitStore } from "../../stores/gitStore";
 import { useUIStore } from "../../stores/uiStore";
 import { CommitPanel } from "./organisms/CommitPanel";
@@ -17,6 +20,7 @@ export function ChangesPage() {
   const stageAllAction = useGitStore((s) => s.stageAll);
   const unstageAllAction = useGitStore((s) => s.unstageAll);
   const commitAction = useGitStore((s) => s.commit);
+  const lintCommitMessage = useGitStore((s) => s.lintCommitMessage);
   const stageHunkAction = useGitStore((s) => s.stageHunk);
   const unstageHunkAction = useGitStore((s) => s.unstageHunk);
   const discardHunkAction = useGitStore((s) => s.discardHunk);
@@ -24,6 +28,12 @@ export function ChangesPage() {
   const unstageLinesAction = useGitStore((s) => s.unstageLines);
   const discardLinesAction = useGitStore((s) => s.discardLines);
   const getHeadCommitMessage = useGitStore((s) => s.getHeadCommitMessage);
+  const getIdentityCheck = useGitStore((s) => s.getIdentityCheck);
+  const applyIdentityProfile = useGitStore((s) => s.applyIdentityProfile);
+  const absorbAction = useGitStore((s) => s.absorb);
+  const [identityCheck, setIdentityCheck] = useState<IdentityCheck | null>(
+    null,
+  );
 
   const selectedFile = useUIStore((s) => s.selectedFile);
   const selectedFileStaged = useUIStore((s) => s.selectedFileStaged);
@@ -31,6 +41,7 @@ export function ChangesPage() {
   const addToast = useUIStore((s) => s.addToast);
   const diffViewMode = useUIStore((s) => s.diffViewMode);
   const setDiffViewMode = useUIStore((s) => s.setDiffViewMode);
+  const loadCoAuthors = useConfigStore((s) => s.loadCoAuthors);
 
   useEffect(() => {
     fetchStatus().catch((e: unknown) => {
@@ -38,6 +49,12 @@ export function ChangesPage() {
     });
   }, [fetchStatus, addToast]);
 
+  useEffect(() => {
+    getIdentityCheck()
+      .then(setIdentityCheck)
+      .catch(() => {});
+  }, [getIdentityCheck]);
+
   useEffect(() => {
     if (selectedFile) {
       fetchDiff(selectedFile, selectedFileStaged).catch((e: unknown) => {
@@ -76,21 +93,86 @@ export function ChangesPage() {
   }, [unstageAllAction, fetchStatus]);
 
   const handleCommit = useCallback(
-    async (message: string, amend: boolean, sign: boolean) => {
+    async (
+      message: string,
+      amend: boolean,
+      sign: boolean,
+      skipHooks: boolean,
+      trailers: CommitTrailer[],
+      identity: CommitIdentity,
+    ) => {
       try {
-        await commitAction(message, amend, sign);
+        const violations = await lintCommitMessage(message, trailers);
+        const errors = violations.filter((v) => v.severity === "error");
+        if (errors.length > 0) {
+          addToast(
+            `Commit message rejected: ${errors.map((v) => v.message).join("; ")}`,
+            "error",
+          );
+          return;
+        }
+        for (const warning of violations) {
+          addToast(warning.message, "warning");
+        }
+        await commitAction(
+          message,
+          amend,
+          sign,
+          skipHooks,
+          trailers,
+          identity,
+        );
         addToast(
           amend ? "Commit amended successfully" : "Commit created successfully",
           "success",
         );
         await fetchStatus();
+        if (trailers.length > 0) {
+          // 使った共同作者はバックエンドが記録しているので読み直す
+          loadCoAuthors().catch(() => {});
+        }
       } catch (e: unknown) {
         addToast(`Commit failed: ${String(e)}`, "error");
       }
     },
-    [commitAction, fetchStatus, addToast],
+    [commitAction, lintCommitMessage, fetchStatus, loadCoAuthors, addToast],
   );
 
+  const handleApplyProfile = useCallback(
+    async (label: string) => {
+      try {
+        setIdentityCheck(await applyIdentityProfile(label));
+        addToast(`Identity set from profile "${label}"`, "success");
+      } catch (e: unknown) {
+        addToast(String(e), "error");
+      }
+    },
+    [applyIdentityProfile, addToast],
+  );
+
+  const handleAbsorb = useCallback(async () => {
+    try {
+      const result = await absorbAction({ autosquash: false });
+      if (result.fixups.length === 0) {
+        addToast("Absorb: no staged hunk belongs to a branch commit", "info");
+      } else {
+        addToast(
+          `Absorb: created ${result.fixups.length} fixup commit(s)`,
+          "success",
+        );
+      }
+      if (result.skipped.length > 0) {
+        addToast(
+          `Absorb: ${result.skipped.length} change(s) left staged`,
+          "warning",
+        );
+      }
+      await fetchStatus();
+    } catch (e: unknown) {
+      addToast(`Absorb failed: ${String(e)}`, "error");
+    }
+  }, [absorbAction, fetchStatus, addToast]);
+
   const handleStageHunk = useCallback(
     async (hunk: HunkIdentifier) => {
       if (!selectedFile) return;
@@ -220,6 +302,9 @@ export function ChangesPage() {
         hasStagedFiles={hasStagedFiles}
         hasChanges={hasChanges}
         onLoadHeadMessage={getHeadCommitMessage}
+        identityCheck={identityCheck}
+        onApplyProfile={handleApplyProfile}
+        onAbsorb={handleAbsorb}
       />
     </div>
   );
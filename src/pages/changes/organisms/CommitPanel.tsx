@@ -1,13 +1,30 @@
-import { useCallback, useState } from "react";
+import { useCallback, useEffect, useState } from "react";
 import { Button } from "../../../components/atoms/Button";
+import type { CoAuthor, CommitTrailer } from "../../../services/history";
+import type {
+  CommitIdentity,
+  IdentityCheck,
+} from "../../../services/identity";
 import { useAiStore } from "../../../stores/aiStore";
+import { useConfigStore } from "../../../stores/configStore";
 import { useUIStore } from "../../../stores/uiStore";
+import { coAuthorTrailer, parseCoAuthor } from "../../../utils/coAuthor";
 
 interface CommitPanelProps {
-  onCommit: (message: string, amend: boolean, sign: boolean) => void;
+  onCommit: (
+    message: string,
+    amend: boolean,
+    sign: boolean,
+    skipHooks: boolean,
+    trailers: CommitTrailer[],
+    identity: CommitIdentity,
+  ) => void;
   hasStagedFiles: boolean;
   hasChanges: boolean;
   onLoadHeadMessage?: () => Promise<string>;
+  identityCheck?: IdentityCheck | null;
+  onApplyProfile?: (label: string) => void;
+  onAbsorb?: () => void;
 }
 
 export function CommitPanel({
@@ -15,11 +32,21 @@ export function CommitPanel({
   hasStagedFiles,
   hasChanges,
   onLoadHeadMessage,
+  identityCheck,
+  onApplyProfile,
+  onAbsorb,
 }: CommitPanelProps) {
   const [subject, setSubject] = useState("");
   const [body, setBody] = useState("");
   const [amend, setAmend] = useState(false);
   const [sign, setSign] = useState(false);
+  const [skipHooks, setSkipHooks] = useState(false);
+  const [coAuthors, setCoAuthors] = useState<CoAuthor[]>([]);
+  const [coAuthorInput, setCoAuthorInput] = useState("");
+  const [authorInput, setAuthorInput] = useState("");
+
+  const knownCoAuthors = useConfigStore((s) => s.coAuthors);
+  const loadCoAuthors = useConfigStore((s) => s.loadCoAuthors);
 
   const generating = useAiStore((s) => s.generating);
   const reviewing = useAiStore((s) => s.reviewing);
@@ -28,14 +55,51 @@ export function CommitPanel({
   const reviewDiff = useAiStore((s) => s.reviewDiff);
   const addToast = useUIStore((s) => s.addToast);
 
+  useEffect(() => {
+    loadCoAuthors().catch(() => {});
+  }, [loadCoAuthors]);
+
   const handleCommit = () => {
     const message = body.trim() ? `${subject}\n\n${body}` : subject;
-    onCommit(message, amend, sign);
+    let identity: CommitIdentity = {};
+    if (authorInput.trim()) {
+      const author = parseCoAuthor(authorInput);
+      if (!author) {
+        addToast("Author must look like Name <email>", "error");
+        return;
+      }
+      identity = { author };
+    }
+    // ペアの共同作者は次のコミットでも使うので残しておく
+    onCommit(
+      message,
+      amend,
+      sign,
+      skipHooks,
+      coAuthors.map(coAuthorTrailer),
+      identity,
+    );
     setSubject("");
     setBody("");
     setAmend(false);
   };
 
+  const handleAddCoAuthor = () => {
+    const coAuthor = parseCoAuthor(coAuthorInput);
+    if (!coAuthor) {
+      addToast("Co-author must look like Name <email>", "error");
+      return;
+    }
+    if (!coAuthors.some((c) => c.email === coAuthor.email)) {
+      setCoAuthors([...coAuthors, coAuthor]);
+    }
+    setCoAuthorInput("");
+  };
+
+  const handleRemoveCoAuthor = (email: string) => {
+    setCoAuthors(coAuthors.filter((c) => c.email !== email));
+  };
+
   const handleAmendToggle = useCallback(async () => {
     const next = !amend;
     setAmend(next);
@@ -91,11 +155,44 @@ export function CommitPanel({
           />
           <span>Sign</span>
         </label>
+        <label
+          className="amend-toggle"
+          title="Skip pre-commit and commit-msg hooks (--no-verify)"
+        >
+          <input
+            type="checkbox"
+            checked={skipHooks}
+            onChange={() => setSkipHooks(!skipHooks)}
+          />
+          <span>Skip Hooks</span>
+        </label>
         <label className="amend-toggle">
           <input type="checkbox" checked={amend} onChange={handleAmendToggle} />
           <span>Amend</span>
         </label>
       </div>
+      {identityCheck?.profile && !identityCheck.matches && (
+        <div
+          className={`identity-warning identity-warning-${identityCheck.action}`}
+        >
+          <span>
+            Committing as {identityCheck.email ?? "(no user.email)"}, but
+            profile &quot;{identityCheck.profile.label}&quot; expects{" "}
+            {identityCheck.profile.email}
+          </span>
+          {onApplyProfile && (
+            <button
+              type="button"
+              onClick={() =>
+                identityCheck.profile &&
+                onApplyProfile(identityCheck.profile.label)
+              }
+            >
+              Use profile
+            </button>
+          )}
+        </div>
+      )}
       <div className="commit-form">
         <div className="commit-input-group">
           <input
@@ -132,6 +229,45 @@ export function CommitPanel({
           value={body}
           onChange={(e) => setBody(e.target.value)}
         />
+        <div className="commit-co-authors">
+          {coAuthors.map((c) => (
+            <span key={c.email} className="co-author-chip" title={c.email}>
+              {c.name || c.email}
+              <button
+                type="button"
+                aria-label={`Remove ${c.name || c.email}`}
+                onClick={() => handleRemoveCoAuthor(c.email)}
+              >
+                &times;
+              </button>
+            </span>
+          ))}
+          <input
+            type="text"
+            className="co-author-input"
+            placeholder="Add co-author (Name <email>)"
+            list="known-co-authors"
+            value={coAuthorInput}
+            onChange={(e) => setCoAuthorInput(e.target.value)}
+            onKeyDown={(e) => {
+              if (e.key === "Enter" && coAuthorInput.trim()) {
+                handleAddCoAuthor();
+              }
+            }}
+          />
+          <datalist id="known-co-authors">
+            {knownCoAuthors.map((c) => (
+              <option key={c.email} value={`${c.name} <${c.email}>`} />
+            ))}
+          </datalist>
+        </div>
+        <input
+          type="text"
+          className="commit-author-input"
+          placeholder="Author override (Name <email>, optional)"
+          value={authorInput}
+          onChange={(e) => setAuthorInput(e.target.value)}
+        />
         <div className="commit-actions">
           <button
             type="button"
@@ -148,6 +284,17 @@ export function CommitPanel({
             )}
             AI Review
           </button>
+          {onAbsorb && (
+            <button
+              type="button"
+              className="absorb-btn"
+              title="Create fixup! commits for the branch commits that last touched the staged hunks"
+              onClick={onAbsorb}
+              disabled={!hasStagedFiles || amend}
+            >
+              Absorb
+            </button>
+          )}
           <Button
             variant="primary"
             onClick={handleCommit}
@@ -1,4 +1,10 @@
 import { useCallback, useEffect } from "react";
+import type {
+  FixupKind,
+  MoveDirection,
+  RebaseResult,
+} from "../../services/rebase";
+import { useGitStore } from "../../stores/gitStore";
 import { useHistoryStore } from "../../stores/historyStore";
 import { useUIStore } from "../../stores/uiStore";
 import { CommitDetailPanel } from "./organisms/CommitDetailPanel";
@@ -25,6 +31,11 @@ export function HistoryPage() {
   const addToast = useUIStore((s) => s.addToast);
   const openBlame = useUIStore((s) => s.openBlame);
   const openFileHistory = useUIStore((s) => s.openFileHistory);
+  const createFixupCommit = useGitStore((s) => s.createFixupCommit);
+  const rewordCommit = useGitStore((s) => s.rewordCommit);
+  const moveCommit = useGitStore((s) => s.moveCommit);
+  const dropCommit = useGitStore((s) => s.dropCommit);
+  const fetchStatus = useGitStore((s) => s.fetchStatus);
 
   useEffect(() => {
     fetchCommitLog(INITIAL_LIMIT, 0).catch((e: unknown) => {
@@ -91,6 +102,61 @@ export function HistoryPage() {
     [openFileHistory],
   );
 
+  const handleCreateFixup = useCallback(
+    async (oid: string, kind: FixupKind) => {
+      try {
+        await createFixupCommit(oid, kind);
+        addToast(`Created ${kind}! commit`, "success");
+        await fetchStatus();
+        await fetchCommitLog(INITIAL_LIMIT, 0);
+      } catch (e: unknown) {
+        addToast(String(e), "error");
+      }
+    },
+    [createFixupCommit, fetchStatus, fetchCommitLog, addToast],
+  );
+
+  const runCommitEdit = useCallback(
+    async (edit: () => Promise<RebaseResult>, done: string) => {
+      try {
+        const result = await edit();
+        if (result.completed) {
+          addToast(done, "success");
+        } else {
+          addToast(
+            `Rebase stopped with ${result.conflicts.length} conflict(s)`,
+            "warning",
+          );
+        }
+        await fetchStatus();
+        await fetchCommitLog(INITIAL_LIMIT, 0);
+      } catch (e: unknown) {
+        addToast(String(e), "error");
+      }
+    },
+    [fetchStatus, fetchCommitLog, addToast],
+  );
+
+  const handleRewordCommit = useCallback(
+    (oid: string, message: string) =>
+      runCommitEdit(() => rewordCommit(oid, message), "Commit reworded"),
+    [runCommitEdit, rewordCommit],
+  );
+
+  const handleMoveCommit = useCallback(
+    (oid: string, direction: MoveDirection) =>
+      runCommitEdit(
+        () => moveCommit(oid, direction),
+        `Commit moved ${direction}`,
+      ),
+    [runCommitEdit, moveCommit],
+  );
+
+  const handleDropCommit = useCallback(
+    (oid: string) => runCommitEdit(() => dropCommit(oid), "Commit dropped"),
+    [runCommitEdit, dropCommit],
+  );
+
   return (
     <div className="page-layout">
       <div className="page-header">
@@ -121,6 +187,10 @@ export function HistoryPage() {
           onToggleFile={handleToggleFile}
           onOpenBlame={handleOpenBlame}
           onOpenFileHistory={handleOpenFileHistory}
+          onCreateFixup={handleCreateFixup}
+          onRewordCommit={handleRewordCommit}
+          onMoveCommit={handleMoveCommit}
+          onDropCommit={handleDropCommit}
         />
       </div>
     </div>
@@ -1,5 +1,7 @@
+import { useEffect, useState } from "react";
 import type { FileDiff } from "../../../services/git";
 import type { CommitDetail } from "../../../services/history";
+import type { FixupKind, MoveDirection } from "../../../services/rebase";
 import { formatAbsoluteDate, formatRelativeDate } from "../../../utils/date";
 import { CommitFileDiff } from "../molecules/CommitFileDiff";
 import { CommitFileItem } from "../molecules/CommitFileItem";
@@ -10,15 +12,31 @@ interface CommitDetailPanelProps {
   onToggleFile: (path: string) => void;
   onOpenBlame: (path: string, commitOid: string) => void;
   onOpenFileHistory: (path: string) => void;
+  onCreateFixup?: (oid: string, kind: FixupKind) => void;
+  onRewordCommit?: (oid: string, message: string) => void;
+  onMoveCommit?: (oid: string, direction: MoveDirection) => void;
+  onDropCommit?: (oid: string) => void;
 }
 
+const FIXUP_KINDS: FixupKind[] = ["fixup", "squash", "amend"];
+
 export function CommitDetailPanel({
   detail,
   expandedFileDiffs,
   onToggleFile,
   onOpenBlame,
   onOpenFileHistory,
+  onCreateFixup,
+  onRewordCommit,
+  onMoveCommit,
+  onDropCommit,
 }: CommitDetailPanelProps) {
+  const [rewording, setRewording] = useState<string | null>(null);
+
+  useEffect(() => {
+    setRewording(null);
+  }, [detail?.info.oid]);
+
   if (!detail) {
     return (
       <div className="commit-detail-panel">
@@ -67,11 +85,106 @@ export function CommitDetailPanel({
               </span>
             </div>
           )}
+          {info.co_authors.length > 0 && (
+            <div className="detail-co-author-row">
+              <span className="detail-label">Co-authors</span>
+              <span className="detail-co-authors">
+                {info.co_authors.map((c) => (
+                  <span
+                    key={c.email}
+                    className="detail-co-author"
+                    title={c.email}
+                  >
+                    {c.name || c.email}
+                  </span>
+                ))}
+              </span>
+            </div>
+          )}
+          {onCreateFixup && (
+            <div className="detail-fixup-row">
+              <span className="detail-label">Staged</span>
+              <span className="detail-fixup-actions">
+                {FIXUP_KINDS.map((kind) => (
+                  <button
+                    key={kind}
+                    type="button"
+                    title={`Commit staged changes as ${kind}! ${info.message}`}
+                    onClick={() => onCreateFixup(info.oid, kind)}
+                  >
+                    {kind}!
+                  </button>
+                ))}
+              </span>
+            </div>
+          )}
         </div>
 
+        {onRewordCommit && onMoveCommit && onDropCommit && (
+          <div className="detail-fixup-row">
+            <span className="detail-label">History</span>
+            <span className="detail-fixup-actions">
+              <button
+                type="button"
+                onClick={() =>
+                  setRewording(
+                    info.body
+                      ? `${info.message}\n\n${info.body}`
+                      : info.message,
+                  )
+                }
+              >
+                Reword
+              </button>
+              <button
+                type="button"
+                title="Swap with the next commit"
+                onClick={() => onMoveCommit(info.oid, "up")}
+              >
+                Move up
+              </button>
+              <button
+                type="button"
+                title="Swap with the previous commit"
+                onClick={() => onMoveCommit(info.oid, "down")}
+              >
+                Move down
+              </button>
+              <button type="button" onClick={() => onDropCommit(info.oid)}>
+                Drop
+              </button>
+            </span>
+          </div>
+        )}
+
         <div className="detail-message-section">
-          <div className="detail-message-title">{info.message}</div>
-          {info.body && <div className="detail-message-body">{info.body}</div>}
+          {rewording !== null && onRewordCommit ? (
+            <div className="detail-reword">
+              <textarea
+                value={rewording}
+                onChange={(e) => setRewording(e.target.value)}
+              />
+              <div className="detail-fixup-actions">
+                <button
+                  type="button"
+                  disabled={!rewording.trim()}
+                  onClick={() => onRewordCommit(info.oid, rewording)}
+                >
+                  Save
+                </button>
+                <button type="button" onClick={() => setRewording(null)}>
+                  Cancel
+                </button>
+              </div>
+            </div>
+          ) : (
+            <>
+              <div className="detail-message-title">{info.message}</div>
+              {info.body && (
+                <div className="detail-message-body">{info.body}</div>
+              )}
+            </>
+          )}
         </div>
 
         <div className="detail-stats">
@@ -1,5 +1,10 @@
 import { useCallback, useEffect, useState } from "react";
-import type { RebaseTodoEntry } from "../../services/rebase";
+import { useRebaseProgress } from "../../hooks/useRebaseProgress";
+import type {
+  RebaseOptions,
+  RebaseProgress,
+  RebaseTodoEntry,
+} from "../../services/rebase";
 import { useGitStore } from "../../stores/gitStore";
 import { useUIStore } from "../../stores/uiStore";
 import "../../styles/rebase.css";
@@ -20,6 +25,12 @@ export function RebasePage() {
   const [ontoBranch, setOntoBranch] = useState<string>("");
   const [entries, setEntries] = useState<RebaseTodoEntry[]>([]);
   const [loading, setLoading] = useState(false);
+  const [autosquash, setAutosquash] = useState(true);
+  const [rebaseMerges, setRebaseMerges] = useState(false);
+  const [exec, setExec] = useState("");
+  const [progress, setProgress] = useState<RebaseProgress | null>(null);
+
+  useRebaseProgress(setProgress);
 
   useEffect(() => {
     fetchBranches().catch((e: unknown) => {
@@ -31,8 +42,8 @@ export function RebasePage() {
     (b) => b.name !== currentBranch && !b.is_remote,
   );
 
-  const handleSelectBranch = useCallback(
-    async (branch: string) => {
+  const loadTodo = useCallback(
+    async (branch: string, squash: boolean, options: RebaseOptions) => {
       setOntoBranch(branch);
       if (!branch) {
         setEntries([]);
@@ -40,7 +51,7 @@ export function RebasePage() {
       }
       setLoading(true);
       try {
-        const todo = await getRebaseTodo(branch);
+        const todo = await getRebaseTodo(branch, squash, options);
         setEntries(todo);
       } catch (e: unknown) {
         addToast(String(e), "error");
@@ -52,20 +63,62 @@ export function RebasePage() {
     [getRebaseTodo, addToast],
   );
 
+  const rebaseOptions = useCallback(
+    (merges: boolean): RebaseOptions => ({
+      rebase_merges: merges,
+      exec: exec.trim() || null,
+    }),
+    [exec],
+  );
+
+  const handleSelectBranch = useCallback(
+    (branch: string) =>
+      loadTodo(branch, autosquash, rebaseOptions(rebaseMerges)),
+    [loadTodo, autosquash, rebaseOptions, rebaseMerges],
+  );
+
+  const handleToggleAutosquash = useCallback(() => {
+    const next = !autosquash;
+    setAutosquash(next);
+    if (ontoBranch) {
+      loadTodo(ontoBranch, next, rebaseOptions(rebaseMerges));
+    }
+  }, [autosquash, ontoBranch, loadTodo, rebaseOptions, rebaseMerges]);
+
+  const handleToggleRebaseMerges = useCallback(() => {
+    const next = !rebaseMerges;
+    setRebaseMerges(next);
+    if (ontoBranch) {
+      loadTodo(ontoBranch, autosquash, rebaseOptions(next));
+    }
+  }, [rebaseMerges, ontoBranch, autosquash, loadTodo, rebaseOptions]);
+
+  // exec は入力を終えた時点で todo に差し込み直す
+  const handleApplyExec = useCallback(() => {
+    if (ontoBranch) {
+      loadTodo(ontoBranch, autosquash, rebaseOptions(rebaseMerges));
+    }
+  }, [ontoBranch, autosquash, loadTodo, rebaseOptions, rebaseMerges]);
+
   const handleStartRebase = useCallback(async () => {
     if (!ontoBranch || entries.length === 0) return;
     try {
       const result = await interactiveRebase(ontoBranch, entries);
       if (result.completed) {
         addToast("Rebase completed successfully", "success");
-      } else {
+      } else if (result.conflicts.length > 0) {
         addToast("Rebase has conflicts. Opening conflict resolver.", "warning");
         await fetchRebaseState();
         openModal("conflict");
+      } else {
+        addToast("Rebase stopped. Continue it when you are ready.", "info");
+        await fetchRebaseState();
       }
       await fetchStatus();
     } catch (e: unknown) {
       addToast(String(e), "error");
+    } finally {
+      setProgress(null);
     }
   }, [
     ontoBranch,
@@ -97,6 +150,39 @@ export function RebasePage() {
             </option>
           ))}
         </select>
+        <label
+          className="rebase-autosquash-toggle"
+          title="Move fixup!, squash! and amend! commits after their targets"
+        >
+          <input
+            type="checkbox"
+            checked={autosquash}
+            onChange={handleToggleAutosquash}
+          />
+          Autosquash
+        </label>
+        <label
+          className="rebase-autosquash-toggle"
+          title="Keep the branch's merge commits (--rebase-merges)"
+        >
+          <input
+            type="checkbox"
+            checked={rebaseMerges}
+            onChange={handleToggleRebaseMerges}
+          />
+          Rebase merges
+        </label>
+        <input
+          className="rebase-exec-input"
+          placeholder="Exec after each commit"
+          title="A command to run after each commit, e.g. npm test"
+          value={exec}
+          onChange={(e) => setExec(e.target.value)}
+          onBlur={handleApplyExec}
+          onKeyDown={(e) => {
+            if (e.key === "Enter") handleApplyExec();
+          }}
+        />
         <button
           type="button"
           className="btn btn-primary btn-sm"
@@ -107,6 +193,13 @@ export function RebasePage() {
         </button>
       </div>
 
+      {progress && (
+        <div className="rebase-progress">
+          Step {progress.step}/{progress.total}: {progress.entry.action}{" "}
+          {progress.entry.short_oid || progress.entry.argument}
+        </div>
+      )}
+
       {loading ? (
         <div className="rebase-empty">
           <p>Loading commits...</p>
@@ -7,9 +7,15 @@ const ACTIONS: RebaseAction[] = [
   "edit",
   "squash",
   "fixup",
+  "fixup_message",
   "drop",
 ];
 
+const ACTION_LABELS: Partial<Record<RebaseAction, string>> = {
+  fixup_message: "fixup -C",
+  update_ref: "update-ref",
+};
+
 function nextAction(current: RebaseAction): RebaseAction {
   const idx = ACTIONS.indexOf(current);
   return ACTIONS[(idx + 1) % ACTIONS.length];
@@ -44,7 +50,11 @@ export function RebaseCommitItem({
     }
   }, [editing]);
 
+  // exec や label などコミットを持たない手順は動かせるが書き換えない
+  const isCommit = ACTIONS.includes(entry.action);
+
   const handleCycleAction = () => {
+    if (!isCommit) return;
     const next = nextAction(entry.action);
     onChangeAction(next);
     if (next === "reword") {
@@ -93,11 +103,15 @@ export function RebaseCommitItem({
         type="button"
         className={`rebase-action action-${entry.action}`}
         onClick={handleCycleAction}
-        title="Click to cycle action"
+        disabled={!isCommit}
+        title={isCommit ? "Click to cycle action" : undefined}
       >
-        {entry.action}
+        {ACTION_LABELS[entry.action] ?? entry.action}
       </button>
       <span className="rebase-hash">{entry.short_oid}</span>
+      {!isCommit || entry.action === "merge" ? (
+        <span className="rebase-argument">{entry.argument}</span>
+      ) : null}
       {editing ? (
         <input
           ref={inputRef}
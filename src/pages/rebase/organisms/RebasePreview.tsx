@@ -8,10 +8,14 @@ interface RebasePreviewProps {
 export function RebasePreview({ entries, ontoBranch }: RebasePreviewProps) {
   const pickCount = entries.filter((e) => e.action === "pick").length;
   const squashCount = entries.filter((e) => e.action === "squash").length;
-  const fixupCount = entries.filter((e) => e.action === "fixup").length;
+  const fixupCount = entries.filter(
+    (e) => e.action === "fixup" || e.action === "fixup_message",
+  ).length;
   const dropCount = entries.filter((e) => e.action === "drop").length;
   const rewordCount = entries.filter((e) => e.action === "reword").length;
   const editCount = entries.filter((e) => e.action === "edit").length;
+  const mergeCount = entries.filter((e) => e.action === "merge").length;
+  const execCount = entries.filter((e) => e.action === "exec").length;
 
   return (
     <div className="rebase-footer">
@@ -25,6 +29,8 @@ export function RebasePreview({ entries, ontoBranch }: RebasePreviewProps) {
         {dropCount > 0 && `, ${dropCount} drop`}
         {rewordCount > 0 && `, ${rewordCount} reword`}
         {editCount > 0 && `, ${editCount} edit`}
+        {mergeCount > 0 && `, ${mergeCount} merge`}
+        {execCount > 0 && `, ${execCount} exec`}
       </span>
       <div className="rebase-footer-spacer" />
     </div>
@@ -47,7 +47,7 @@ export function RebaseTodoList({ entries, onChange }: RebaseTodoListProps) {
     <div className="rebase-commit-list">
       {entries.map((entry, i) => (
         <RebaseCommitItem
-          key={entry.oid}
+          key={entry.oid || `${entry.action}-${entry.argument}-${i}`}
           entry={entry}
           index={i}
           total={entries.length}
@@ -51,8 +51,15 @@ export function StashPage() {
   const handleApply = useCallback(async () => {
     if (selectedIndex === null) return;
     try {
-      await applyStash(selectedIndex);
-      addToast("Stash applied", "success");
+      const result = await applyStash(selectedIndex);
+      if (result.completed) {
+        addToast("Stash applied", "success");
+      } else {
+        addToast(
+          `Stash applied with conflicts: ${result.conflicts.join(", ")}`,
+          "warning",
+        );
+      }
       await refreshAll();
     } catch (e: unknown) {
       addToast(`Apply failed: ${String(e)}`, "error");
@@ -62,9 +69,16 @@ export function StashPage() {
   const handlePop = useCallback(async () => {
     if (selectedIndex === null) return;
     try {
-      await popStash(selectedIndex);
-      addToast("Stash popped", "success");
-      setSelectedIndex(null);
+      const result = await popStash(selectedIndex);
+      if (result.completed) {
+        addToast("Stash popped", "success");
+        setSelectedIndex(null);
+      } else {
+        addToast(
+          `Stash kept because of conflicts: ${result.conflicts.join(", ")}`,
+          "warning",
+        );
+      }
       await refreshAll();
     } catch (e: unknown) {
       addToast(`Pop failed: ${String(e)}`, "error");
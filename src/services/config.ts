@@ -1,4 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
+import type { CoAuthor } from "./history";
+import type { IdentityConfig } from "./identity";
 
 export interface AppearanceConfig {
   theme: string;
@@ -50,6 +52,8 @@ export interface AppConfig {
   editor: EditorConfig;
   keybindings: KeybindingsConfig;
   tools: ToolsConfig;
+  co_authors: CoAuthor[];
+  identity: IdentityConfig;
 }
 
 export function getConfig(): Promise<AppConfig> {
@@ -59,3 +63,15 @@ export function getConfig(): Promise<AppConfig> {
 export function saveConfig(config: AppConfig): Promise<void> {
   return invoke<void>("save_config", { config });
 }
+
+export function getCoAuthors(): Promise<CoAuthor[]> {
+  return invoke<CoAuthor[]>("get_co_authors");
+}
+
+export function addCoAuthor(coAuthor: CoAuthor): Promise<CoAuthor[]> {
+  return invoke<CoAuthor[]>("add_co_author", { coAuthor });
+}
+
+export function removeCoAuthor(email: string): Promise<CoAuthor[]> {
+  return invoke<CoAuthor[]>("remove_co_author", { email });
+}
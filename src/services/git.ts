@@ -1,5 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { CommitInfo } from "./history";
+import type { CommitInfo, CommitTrailer } from "./history";
+import type { CommitIdentity } from "./identity";
 
 export type FileStatusKind =
   | "untracked"
@@ -105,8 +106,52 @@ export function commitChanges(
   message: string,
   amend: boolean,
   sign: boolean,
+  skipHooks = false,
+  trailers: CommitTrailer[] = [],
+  identity: CommitIdentity = {},
 ): Promise<CommitResult> {
-  return invoke<CommitResult>("commit", { tabId, message, amend, sign });
+  return invoke<CommitResult>("commit", {
+    tabId,
+    message,
+    amend,
+    sign,
+    skipHooks,
+    trailers,
+    identity,
+  });
+}
+
+export type CommitLintRule =
+  | "subject_empty"
+  | "subject_max_length"
+  | "header_format"
+  | "type_enum"
+  | "scope_enum"
+  | "scope_required"
+  | "body_leading_blank"
+  | "body_max_line_length"
+  | "trailer_required"
+  | "ticket_reference";
+
+export type LintSeverity = "error" | "warning";
+
+export interface CommitLintViolation {
+  rule: CommitLintRule;
+  severity: LintSeverity;
+  message: string;
+  line: number | null;
+}
+
+export function lintCommitMessage(
+  tabId: string,
+  message: string,
+  trailers: CommitTrailer[] = [],
+): Promise<CommitLintViolation[]> {
+  return invoke<CommitLintViolation[]>("lint_commit_message", {
+    tabId,
+    message,
+    trailers,
+  });
 }
 
 export function getCurrentBranch(tabId: string): Promise<string> {
@@ -119,10 +164,29 @@ export interface BranchInfo {
   is_remote: boolean;
   remote_name: string | null;
   upstream: string | null;
+  upstream_gone: boolean;
   ahead_count: number;
   behind_count: number;
 }
 
+export interface DeleteBranchOptions {
+  force?: boolean;
+  delete_remote?: boolean;
+}
+
+export interface DeleteBranchResult {
+  branch: string;
+  deleted: boolean;
+  remote_deleted: boolean;
+  unmerged_commits: CommitInfo[];
+}
+
+export interface PruneCandidate {
+  name: string;
+  merged: boolean;
+  upstream_gone: boolean;
+}
+
 export type MergeKind =
   | "fast_forward"
   | "normal"
@@ -138,20 +202,62 @@ export interface MergeResult {
 
 export type MergeOption = "default" | "fast_forward_only" | "no_fast_forward";
 
+export interface StackBranch {
+  name: string;
+  oid: string;
+  commit_count: number;
+  is_head: boolean;
+  upstream: string | null;
+  ahead_count: number;
+  behind_count: number;
+}
+
+export interface BranchStack {
+  base: string;
+  branches: StackBranch[];
+}
+
 export function listBranches(tabId: string): Promise<BranchInfo[]> {
   return invoke<BranchInfo[]>("list_branches", { tabId });
 }
 
-export function createBranch(tabId: string, name: string): Promise<void> {
-  return invoke<void>("create_branch", { tabId, name });
+export function createBranch(
+  tabId: string,
+  name: string,
+  startPoint?: string,
+): Promise<void> {
+  return invoke<void>("create_branch", { tabId, name, startPoint });
 }
 
 export function checkoutBranch(tabId: string, name: string): Promise<void> {
   return invoke<void>("checkout_branch", { tabId, name });
 }
 
-export function deleteBranch(tabId: string, name: string): Promise<void> {
-  return invoke<void>("delete_branch", { tabId, name });
+export function deleteBranch(
+  tabId: string,
+  name: string,
+  options?: DeleteBranchOptions,
+): Promise<DeleteBranchResult> {
+  return invoke<DeleteBranchResult>("delete_branch", { tabId, name, options });
+}
+
+export function listPruneCandidates(
+  tabId: string,
+  base?: string,
+): Promise<PruneCandidate[]> {
+  return invoke<PruneCandidate[]>("list_prune_candidates", { tabId, base });
+}
+
+export function pruneBranches(
+  tabId: string,
+  names: string[],
+  options?: DeleteBranchOptions,
+): Promise<DeleteBranchResult[]> {
+  return invoke<DeleteBranchResult[]>("prune_branches", {
+    tabId,
+    names,
+    options,
+  });
 }
 
 export function renameBranch(
@@ -162,6 +268,26 @@ export function renameBranch(
   return invoke<void>("rename_branch", { tabId, oldName, newName });
 }
 
+export function setBranchUpstream(
+  tabId: string,
+  branchName: string,
+  upstream: string | null,
+): Promise<void> {
+  return invoke<void>("set_branch_upstream", { tabId, branchName, upstream });
+}
+
+export function checkoutRemoteBranch(
+  tabId: string,
+  remoteBranch: string,
+  localName?: string,
+): Promise<string> {
+  return invoke<string>("checkout_remote_branch", {
+    tabId,
+    remoteBranch,
+    localName,
+  });
+}
+
 export function mergeBranch(
   tabId: string,
   branchName: string,
@@ -170,20 +296,54 @@ export function mergeBranch(
   return invoke<MergeResult>("merge_branch", { tabId, branchName, option });
 }
 
+export function listStack(tabId: string, base?: string): Promise<BranchStack> {
+  return invoke<BranchStack>("list_stack", { tabId, base });
+}
+
 export interface RemoteInfo {
   name: string;
   url: string;
 }
 
+export type FetchRefStatus = "created" | "fast_forward" | "forced" | "pruned";
+
+export interface FetchRefUpdate {
+  ref_name: string;
+  old_oid: string | null;
+  new_oid: string | null;
+  status: FetchRefStatus;
+  new_commits: number;
+}
+
 export interface FetchResult {
   remote_name: string;
+  updates: FetchRefUpdate[];
+  new_commits: number;
 }
 
 export type PullOption = "merge" | "rebase";
 
+export type PushRefStatus =
+  | "created"
+  | "updated"
+  | "forced"
+  | "deleted"
+  | "up_to_date"
+  | "rejected_non_fast_forward"
+  | "rejected_stale"
+  | "rejected";
+
+export interface PushRefUpdate {
+  local_ref: string | null;
+  remote_ref: string;
+  status: PushRefStatus;
+  message: string | null;
+}
+
 export interface PushResult {
   remote_name: string;
   branch: string;
+  updates: PushRefUpdate[];
 }
 
 export function fetchRemote(
@@ -208,6 +368,47 @@ export function pushRemote(
   return invoke<PushResult>("push_remote", { tabId, remoteName });
 }
 
+export interface StackPushFailure {
+  branch: string;
+  result: PushResult | null;
+  message: string;
+}
+
+export interface StackPushResult {
+  pushed: PushResult[];
+  failed: StackPushFailure | null;
+}
+
+export function pushStack(
+  tabId: string,
+  remoteName: string,
+  base?: string,
+): Promise<StackPushResult> {
+  return invoke<StackPushResult>("push_stack", { tabId, remoteName, base });
+}
+
+export type RemoteOperation = "fetch" | "pull" | "push" | "clone";
+
+export interface TransferProgress {
+  total_objects: number;
+  transferred_objects: number;
+  indexed_objects: number;
+  total_deltas: number;
+  indexed_deltas: number;
+  transferred_bytes: number;
+  message: string | null;
+}
+
+export interface RemoteProgressEvent {
+  tab_id: string;
+  operation: RemoteOperation;
+  progress: TransferProgress;
+}
+
+export function cancelRemoteOperation(tabId: string): Promise<boolean> {
+  return invoke<boolean>("cancel_remote_operation", { tabId });
+}
+
 export function listRemotes(tabId: string): Promise<RemoteInfo[]> {
   return invoke<RemoteInfo[]>("list_remotes", { tabId });
 }
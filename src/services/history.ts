@@ -42,6 +42,18 @@ export interface CommitInfo {
   parent_oids: string[];
   refs: CommitRef[];
   signature_status: SignatureStatus;
+  trailers: CommitTrailer[];
+  co_authors: CoAuthor[];
+}
+
+export interface CommitTrailer {
+  key: string;
+  value: string;
+}
+
+export interface CoAuthor {
+  name: string;
+  email: string;
 }
 
 export type CommitFileStatus = "added" | "modified" | "deleted" | "renamed";
@@ -72,14 +84,31 @@ export interface BlameLine {
   commit_short_oid: string;
   author_name: string;
   author_date: number;
+  summary: string;
   is_block_start: boolean;
+  original_path: string;
+  original_line_number: number;
+}
+
+export interface BlameOptions {
+  detect_moves: boolean;
+  detect_copies: boolean;
+  ignore_revs: string[];
+  ignore_revs_file: string | null;
+  no_default_ignore_revs?: boolean;
 }
 
 export interface BlameResult {
   path: string;
+  commit_oid: string | null;
   lines: BlameLine[];
 }
 
+export interface FileHistoryEntry extends CommitInfo {
+  path: string;
+  previous_path: string | null;
+}
+
 export interface LogFilter {
   author: string | null;
   since: number | null;
@@ -126,8 +155,28 @@ export function getBlame(
   tabId: string,
   path: string,
   commitOid: string | null,
+  options: BlameOptions | null = null,
 ): Promise<BlameResult> {
-  return invoke<BlameResult>("get_blame", { tabId, path, commitOid });
+  return invoke<BlameResult>("get_blame", {
+    tabId,
+    path,
+    commitOid,
+    options,
+  });
+}
+
+export function getBlameBefore(
+  tabId: string,
+  commitOid: string,
+  path: string,
+  options: BlameOptions | null = null,
+): Promise<BlameResult> {
+  return invoke<BlameResult>("get_blame_before", {
+    tabId,
+    commitOid,
+    path,
+    options,
+  });
 }
 
 export function getFileHistory(
@@ -135,11 +184,17 @@ export function getFileHistory(
   path: string,
   limit: number,
   skip: number,
-): Promise<CommitInfo[]> {
-  return invoke<CommitInfo[]>("get_file_history", {
+  follow = false,
+): Promise<FileHistoryEntry[]> {
+  return invoke<FileHistoryEntry[]>("get_file_history", {
     tabId,
     path,
     limit,
     skip,
+    follow,
   });
 }
+
+export function writeCommitGraph(tabId: string): Promise<void> {
+  return invoke<void>("write_commit_graph", { tabId });
+}
@@ -0,0 +1,51 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface SignatureOverride {
+  name?: string | null;
+  email?: string | null;
+  time?: number | null;
+  offset_minutes?: number | null;
+}
+
+export interface CommitIdentity {
+  author?: SignatureOverride | null;
+  committer?: SignatureOverride | null;
+}
+
+export type SigningFormat = "openpgp" | "ssh" | "x509";
+
+export interface IdentityProfile {
+  label: string;
+  name: string;
+  email: string;
+  signing_key: string | null;
+  signing_format: SigningFormat | null;
+  remote_patterns: string[];
+  path_patterns: string[];
+}
+
+export type IdentityMismatchAction = "warn" | "block";
+
+export interface IdentityConfig {
+  profiles: IdentityProfile[];
+  on_mismatch: IdentityMismatchAction;
+}
+
+export interface IdentityCheck {
+  profile: IdentityProfile | null;
+  name: string | null;
+  email: string | null;
+  matches: boolean;
+  action: IdentityMismatchAction;
+}
+
+export function getIdentityCheck(tabId: string): Promise<IdentityCheck> {
+  return invoke<IdentityCheck>("get_identity_check", { tabId });
+}
+
+export function applyIdentityProfile(
+  tabId: string,
+  label: string,
+): Promise<IdentityCheck> {
+  return invoke<IdentityCheck>("apply_identity_profile", { tabId, label });
+}
@@ -1,4 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
+import type { CommitResult, HunkIdentifier, LineRange } from "./git";
 
 export type RebaseAction =
   | "pick"
@@ -6,7 +7,16 @@ export type RebaseAction =
   | "edit"
   | "squash"
   | "fixup"
-  | "drop";
+  | "fixup_message"
+  | "drop"
+  | "exec"
+  | "break"
+  | "label"
+  | "reset"
+  | "merge"
+  | "update_ref";
+
+export type FixupKind = "fixup" | "squash" | "amend";
 
 export interface RebaseTodoEntry {
   action: RebaseAction;
@@ -14,14 +24,44 @@ export interface RebaseTodoEntry {
   short_oid: string;
   message: string;
   author_name: string;
+  argument?: string | null;
+}
+
+export interface RebaseOptions {
+  rebase_merges?: boolean;
+  exec?: string | null;
 }
 
+export type RebaseStopReason = "conflict" | "edit" | "break" | "exec_failed";
+
 export interface RebaseState {
   onto_branch: string;
   onto_oid: string;
   current_step: number;
   total_steps: number;
   has_conflicts: boolean;
+  head_name: string | null;
+  stop_reason: RebaseStopReason | null;
+  exec_failure: RebaseExecFailure | null;
+}
+
+export interface RebaseExecFailure {
+  command: string;
+  exit_code: number | null;
+  cancelled: boolean;
+  stdout: string;
+  stderr: string;
+}
+
+export interface RebaseProgress {
+  step: number;
+  total: number;
+  entry: RebaseTodoEntry;
+}
+
+export interface RebaseProgressEvent {
+  tab_id: string;
+  progress: RebaseProgress;
 }
 
 export interface RebaseResult {
@@ -29,16 +69,84 @@ export interface RebaseResult {
   conflicts: string[];
 }
 
-export function rebase(tabId: string, onto: string): Promise<RebaseResult> {
-  return invoke<RebaseResult>("rebase", { tabId, onto });
+export interface AbsorbOptions {
+  base?: string | null;
+  autosquash?: boolean;
+  dry_run?: boolean;
+}
+
+export type AbsorbSkipReason =
+  | "not_modified"
+  | "no_hunks"
+  | "outside_range"
+  | "ambiguous";
+
+export interface AbsorbHunk {
+  path: string;
+  hunk: HunkIdentifier;
+}
+
+export interface AbsorbFixup {
+  target_oid: string;
+  target_summary: string;
+  oid: string | null;
+  hunks: AbsorbHunk[];
+}
+
+export interface AbsorbSkipped {
+  path: string;
+  hunk: HunkIdentifier | null;
+  reason: AbsorbSkipReason;
+}
+
+export interface AbsorbResult {
+  fixups: AbsorbFixup[];
+  skipped: AbsorbSkipped[];
+  rebase: RebaseResult | null;
+}
+
+export interface SplitHunk {
+  path: string;
+  hunk: HunkIdentifier;
+}
+
+export interface SplitLines {
+  path: string;
+  range: LineRange;
+}
+
+export interface SplitCommitGroup {
+  message: string;
+  files?: string[];
+  hunks?: SplitHunk[];
+  lines?: SplitLines[];
+}
+
+export interface SplitCommitResult {
+  commits: string[];
+  rebase: RebaseResult;
+}
+
+export function rebase(
+  tabId: string,
+  onto: string,
+  options?: RebaseOptions,
+): Promise<RebaseResult> {
+  return invoke<RebaseResult>("rebase", { tabId, onto, options });
 }
 
 export function interactiveRebase(
   tabId: string,
   onto: string,
   todo: RebaseTodoEntry[],
+  autosquash = false,
 ): Promise<RebaseResult> {
-  return invoke<RebaseResult>("interactive_rebase", { tabId, onto, todo });
+  return invoke<RebaseResult>("interactive_rebase", {
+    tabId,
+    onto,
+    todo,
+    autosquash,
+  });
 }
 
 export function isRebasing(tabId: string): Promise<boolean> {
@@ -53,6 +161,10 @@ export function continueRebase(tabId: string): Promise<RebaseResult> {
   return invoke<RebaseResult>("continue_rebase", { tabId });
 }
 
+export function cancelRebase(tabId: string): Promise<boolean> {
+  return invoke<boolean>("cancel_rebase", { tabId });
+}
+
 export function getRebaseState(tabId: string): Promise<RebaseState | null> {
   return invoke<RebaseState | null>("get_rebase_state", { tabId });
 }
@@ -61,6 +173,65 @@ export function getRebaseTodo(
   tabId: string,
   onto: string,
   limit: number,
+  autosquash = false,
+  options?: RebaseOptions,
 ): Promise<RebaseTodoEntry[]> {
-  return invoke<RebaseTodoEntry[]>("get_rebase_todo", { tabId, onto, limit });
+  return invoke<RebaseTodoEntry[]>("get_rebase_todo", {
+    tabId,
+    onto,
+    limit,
+    autosquash,
+    options,
+  });
+}
+
+export function createFixupCommit(
+  tabId: string,
+  target: string,
+  kind: FixupKind,
+  message = "",
+): Promise<CommitResult> {
+  return invoke<CommitResult>("create_fixup_commit", {
+    tabId,
+    target,
+    kind,
+    message,
+  });
+}
+
+export function absorb(
+  tabId: string,
+  options: AbsorbOptions = {},
+): Promise<AbsorbResult> {
+  return invoke<AbsorbResult>("absorb", { tabId, options });
+}
+
+export function splitCommit(
+  tabId: string,
+  oid: string,
+  groups: SplitCommitGroup[],
+): Promise<SplitCommitResult> {
+  return invoke<SplitCommitResult>("split_commit", { tabId, oid, groups });
+}
+
+export type MoveDirection = "up" | "down";
+
+export function rewordCommit(
+  tabId: string,
+  oid: string,
+  message: string,
+): Promise<RebaseResult> {
+  return invoke<RebaseResult>("reword_commit", { tabId, oid, message });
+}
+
+export function dropCommit(tabId: string, oid: string): Promise<RebaseResult> {
+  return invoke<RebaseResult>("drop_commit", { tabId, oid });
+}
+
+export function moveCommit(
+  tabId: string,
+  oid: string,
+  direction: MoveDirection,
+): Promise<RebaseResult> {
+  return invoke<RebaseResult>("move_commit", { tabId, oid, direction });
 }
@@ -21,3 +21,46 @@ export function resetFile(
 ): Promise<void> {
   return invoke<void>("reset_file", { tabId, path, oid });
 }
+
+export type OperationKind =
+  | "reset"
+  | "discard"
+  | "delete_branch"
+  | "stash_drop"
+  | "rebase"
+  | "amend";
+
+export interface RefState {
+  name: string;
+  oid: string | null;
+}
+
+export interface FileState {
+  path: string;
+  blob: string | null;
+}
+
+export interface StashState {
+  oid: string;
+  message: string;
+}
+
+export interface OperationEntry {
+  id: number;
+  kind: OperationKind;
+  description: string;
+  timestamp: number;
+  refs: RefState[];
+  index_tree: string | null;
+  files: FileState[];
+  stash: StashState | null;
+  restore_worktree: boolean;
+}
+
+export function listOperations(tabId: string): Promise<OperationEntry[]> {
+  return invoke<OperationEntry[]>("list_operations", { tabId });
+}
+
+export function undoLastOperation(tabId: string): Promise<OperationEntry> {
+  return invoke<OperationEntry>("undo_last_operation", { tabId });
+}
@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { FileDiff } from "./git";
+import type { FileDiff, HunkIdentifier } from "./git";
 
 export interface StashEntry {
   index: number;
@@ -8,23 +8,76 @@ export interface StashEntry {
   author_date: number;
 }
 
+export interface StashDetail {
+  entry: StashEntry;
+  oid: string;
+  base_oid: string;
+  base_summary: string;
+  index_changes: FileDiff[];
+  worktree_changes: FileDiff[];
+  untracked_files: FileDiff[];
+}
+
+export interface StashApplyResult {
+  completed: boolean;
+  conflicts: string[];
+}
+
+export interface StashHunk {
+  path: string;
+  hunk: HunkIdentifier;
+}
+
+export interface StashOptions {
+  include_untracked: boolean;
+  include_ignored: boolean;
+  keep_index: boolean;
+  paths: string[];
+  hunks: StashHunk[];
+}
+
 export function stashSave(
   tabId: string,
   message: string | null,
+  options: Partial<StashOptions> | null = null,
 ): Promise<void> {
-  return invoke<void>("stash_save", { tabId, message });
+  return invoke<void>("stash_save", { tabId, message, options });
 }
 
 export function listStashes(tabId: string): Promise<StashEntry[]> {
   return invoke<StashEntry[]>("list_stashes", { tabId });
 }
 
-export function applyStash(tabId: string, index: number): Promise<void> {
-  return invoke<void>("apply_stash", { tabId, index });
+export function applyStash(
+  tabId: string,
+  index: number,
+  restoreIndex = false,
+): Promise<StashApplyResult> {
+  return invoke<StashApplyResult>("apply_stash", {
+    tabId,
+    index,
+    restoreIndex,
+  });
+}
+
+export function popStash(
+  tabId: string,
+  index: number,
+  restoreIndex = false,
+): Promise<StashApplyResult> {
+  return invoke<StashApplyResult>("pop_stash", {
+    tabId,
+    index,
+    restoreIndex,
+  });
 }
 
-export function popStash(tabId: string, index: number): Promise<void> {
-  return invoke<void>("pop_stash", { tabId, index });
+export function stashBranch(
+  tabId: string,
+  index: number,
+  branchName: string,
+): Promise<void> {
+  return invoke<void>("stash_branch", { tabId, index, branchName });
 }
 
 export function dropStash(tabId: string, index: number): Promise<void> {
@@ -37,3 +90,18 @@ export function getStashDiff(
 ): Promise<FileDiff[]> {
   return invoke<FileDiff[]>("get_stash_diff", { tabId, index });
 }
+
+export function getStashDetail(
+  tabId: string,
+  index: number,
+): Promise<StashDetail> {
+  return invoke<StashDetail>("get_stash_detail", { tabId, index });
+}
+
+export function renameStash(
+  tabId: string,
+  index: number,
+  message: string,
+): Promise<void> {
+  return invoke<void>("rename_stash", { tabId, index, message });
+}
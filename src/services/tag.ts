@@ -1,4 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
+import type { PushResult } from "./git";
+import type { SignatureStatus } from "./history";
 
 export interface TagInfo {
   name: string;
@@ -8,8 +10,58 @@ export interface TagInfo {
   tagger_name: string | null;
   tagger_date: number | null;
   message: string | null;
+  signature_status: SignatureStatus;
 }
 
+export interface CreateTagOptions {
+  target: string | null;
+  sign: boolean;
+}
+
+export interface ReleaseNotesOptions {
+  from: string | null;
+  to: string | null;
+}
+
+export interface ReleaseNoteEntry {
+  oid: string;
+  short_oid: string;
+  scope: string | null;
+  description: string;
+  breaking: boolean;
+  references: number[];
+  author_name: string;
+}
+
+export interface ReleaseSection {
+  kind: string;
+  title: string;
+  entries: ReleaseNoteEntry[];
+}
+
+export interface BreakingChange {
+  short_oid: string;
+  scope: string | null;
+  description: string;
+}
+
+export interface Contributor {
+  name: string;
+  email: string;
+  commits: number;
+}
+
+export interface ReleaseNotes {
+  from: string | null;
+  to: string;
+  sections: ReleaseSection[];
+  breaking_changes: BreakingChange[];
+  contributors: Contributor[];
+  references: number[];
+}
+
+export type ReleaseNotesFormat = "markdown" | "json";
+
 export function listTags(tabId: string): Promise<TagInfo[]> {
   return invoke<TagInfo[]>("list_tags", { tabId });
 }
@@ -18,8 +70,9 @@ export function createTag(
   tabId: string,
   name: string,
   message: string | null,
+  options: CreateTagOptions | null = null,
 ): Promise<void> {
-  return invoke<void>("create_tag", { tabId, name, message });
+  return invoke<void>("create_tag", { tabId, name, message, options });
 }
 
 export function deleteTag(tabId: string, name: string): Promise<void> {
@@ -29,3 +82,42 @@ export function deleteTag(tabId: string, name: string): Promise<void> {
 export function checkoutTag(tabId: string, name: string): Promise<void> {
   return invoke<void>("checkout_tag", { tabId, name });
 }
+
+export function pushTag(
+  tabId: string,
+  remoteName: string,
+  name: string,
+): Promise<PushResult> {
+  return invoke<PushResult>("push_remote", {
+    tabId,
+    remoteName,
+    options: { tag: name },
+  });
+}
+
+export function deleteRemoteTag(
+  tabId: string,
+  remoteName: string,
+  name: string,
+): Promise<PushResult> {
+  return invoke<PushResult>("push_remote", {
+    tabId,
+    remoteName,
+    options: { delete_tag: name },
+  });
+}
+
+export function getReleaseNotes(
+  tabId: string,
+  options: ReleaseNotesOptions,
+): Promise<ReleaseNotes> {
+  return invoke<ReleaseNotes>("get_release_notes", { tabId, options });
+}
+
+export function renderReleaseNotes(
+  tabId: string,
+  options: ReleaseNotesOptions,
+  format: ReleaseNotesFormat,
+): Promise<string> {
+  return invoke<string>("render_release_notes", { tabId, options, format });
+}
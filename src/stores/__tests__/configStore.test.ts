@@ -49,6 +49,8 @@ const mockConfig = {
     auto_fetch_interval: 300,
     open_in_editor_on_double_click: false,
   },
+  co_authors: [],
+  identity: { profiles: [], on_mismatch: "warn" },
 };
 
 describe("configStore", () => {
@@ -56,6 +58,7 @@ describe("configStore", () => {
     vi.clearAllMocks();
     useConfigStore.setState({
       config: null,
+      coAuthors: [],
       loading: false,
       error: null,
     });
@@ -156,4 +159,52 @@ describe("configStore", () => {
       expect(useConfigStore.getState().error).toContain("appearance error");
     });
   });
+  describe("co-authors", () => {
+    const ann = { name: "Ann Lee", email: "ann@example.com" };
+
+    it("loads the stored co-authors", async () => {
+      mockedInvoke.mockResolvedValueOnce([ann]);
+
+      await useConfigStore.getState().loadCoAuthors();
+
+      expect(useConfigStore.getState().coAuthors).toEqual([ann]);
+      expect(mockedInvoke).toHaveBeenCalledWith("get_co_authors");
+    });
+
+    it("adds a co-author and keeps the loaded config in sync", async () => {
+      useConfigStore.setState({ config: mockConfig });
+      mockedInvoke.mockResolvedValueOnce([ann]);
+
+      await useConfigStore.getState().addCoAuthor(ann);
+
+      const state = useConfigStore.getState();
+      expect(state.coAuthors).toEqual([ann]);
+      expect(state.config?.co_authors).toEqual([ann]);
+      expect(mockedInvoke).toHaveBeenCalledWith("add_co_author", {
+        coAuthor: ann,
+      });
+    });
+
+    it("removes a co-author by email", async () => {
+      useConfigStore.setState({ coAuthors: [ann] });
+      mockedInvoke.mockResolvedValueOnce([]);
+
+      await useConfigStore.getState().removeCoAuthor("ann@example.com");
+
+      expect(useConfigStore.getState().coAuthors).toEqual([]);
+      expect(mockedInvoke).toHaveBeenCalledWith("remove_co_author", {
+        email: "ann@example.com",
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("co-author error"));
+
+      await expect(
+        useConfigStore.getState().addCoAuthor(ann),
+      ).rejects.toThrow();
+
+      expect(useConfigStore.getState().error).toContain("co-author error");
+    });
+  });
 });
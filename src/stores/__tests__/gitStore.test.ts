@@ -112,6 +112,9 @@ describe("gitStore", () => {
         message: "test message",
         amend: false,
         sign: false,
+        skipHooks: false,
+        trailers: [],
+        identity: {},
       });
     });
 
@@ -128,6 +131,67 @@ describe("gitStore", () => {
         message: "signed commit",
         amend: false,
         sign: true,
+        skipHooks: false,
+        trailers: [],
+        identity: {},
+      });
+    });
+
+    it("passes skipHooks parameter", async () => {
+      mockedInvoke.mockResolvedValueOnce({ oid: "fed789" });
+
+      await useGitStore.getState().commit("wip", false, false, true);
+
+      expect(mockedInvoke).toHaveBeenCalledWith("commit", {
+        tabId: "default",
+        message: "wip",
+        amend: false,
+        sign: false,
+        skipHooks: true,
+        trailers: [],
+        identity: {},
+      });
+    });
+
+    it("passes trailers parameter", async () => {
+      mockedInvoke.mockResolvedValueOnce({ oid: "abc999" });
+      const trailers = [
+        { key: "Co-authored-by", value: "Ann Lee <ann@example.com>" },
+      ];
+
+      await useGitStore
+        .getState()
+        .commit("pair", false, false, false, trailers);
+
+      expect(mockedInvoke).toHaveBeenCalledWith("commit", {
+        tabId: "default",
+        message: "pair",
+        amend: false,
+        sign: false,
+        skipHooks: false,
+        trailers,
+        identity: {},
+      });
+    });
+
+    it("passes identity parameter", async () => {
+      mockedInvoke.mockResolvedValueOnce({ oid: "abc777" });
+      const identity = {
+        author: { name: "Ann Lee", email: "ann@acme.example" },
+      };
+
+      await useGitStore
+        .getState()
+        .commit("as ann", false, false, false, [], identity);
+
+      expect(mockedInvoke).toHaveBeenCalledWith("commit", {
+        tabId: "default",
+        message: "as ann",
+        amend: false,
+        sign: false,
+        skipHooks: false,
+        trailers: [],
+        identity,
       });
     });
 
@@ -142,6 +206,85 @@ describe("gitStore", () => {
     });
   });
 
+  describe("lintCommitMessage", () => {
+    it("returns violations", async () => {
+      const violations = [
+        {
+          rule: "header_format",
+          severity: "error",
+          message: "subject must look like `type(scope): description`",
+          line: 1,
+        },
+      ];
+      mockedInvoke.mockResolvedValueOnce(violations);
+
+      const result = await useGitStore
+        .getState()
+        .lintCommitMessage("update readme");
+
+      expect(result).toEqual(violations);
+      expect(mockedInvoke).toHaveBeenCalledWith("lint_commit_message", {
+        tabId: "default",
+        message: "update readme",
+        trailers: [],
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("config read failed"));
+
+      await expect(
+        useGitStore.getState().lintCommitMessage("update readme"),
+      ).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("config read failed");
+    });
+  });
+
+  describe("identity", () => {
+    const check = {
+      profile: null,
+      name: "Test User",
+      email: "test@example.com",
+      matches: true,
+      action: "warn",
+    };
+
+    it("getIdentityCheck returns the check", async () => {
+      mockedInvoke.mockResolvedValueOnce(check);
+
+      const result = await useGitStore.getState().getIdentityCheck();
+
+      expect(result).toEqual(check);
+      expect(mockedInvoke).toHaveBeenCalledWith("get_identity_check", {
+        tabId: "default",
+      });
+    });
+
+    it("applyIdentityProfile passes the label", async () => {
+      mockedInvoke.mockResolvedValueOnce(check);
+
+      await useGitStore.getState().applyIdentityProfile("work");
+
+      expect(mockedInvoke).toHaveBeenCalledWith("apply_identity_profile", {
+        tabId: "default",
+        label: "work",
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(
+        new Error("identity profile not found: work"),
+      );
+
+      await expect(
+        useGitStore.getState().applyIdentityProfile("work"),
+      ).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("not found");
+    });
+  });
+
   describe("fetchBranches", () => {
     it("sets branches on success", async () => {
       const mockBranches = [
@@ -212,13 +355,23 @@ describe("gitStore", () => {
 
   describe("deleteBranch", () => {
     it("calls invoke on success", async () => {
-      mockedInvoke.mockResolvedValueOnce(undefined);
+      const result = {
+        branch: "old-branch",
+        deleted: true,
+        remote_deleted: false,
+        unmerged_commits: [],
+      };
+      mockedInvoke.mockResolvedValueOnce(result);
 
-      await useGitStore.getState().deleteBranch("old-branch");
+      const returned = await useGitStore
+        .getState()
+        .deleteBranch("old-branch", { force: true });
 
+      expect(returned).toEqual(result);
       expect(mockedInvoke).toHaveBeenCalledWith("delete_branch", {
         tabId: "default",
         name: "old-branch",
+        options: { force: true },
       });
     });
 
@@ -361,6 +514,88 @@ describe("gitStore", () => {
     });
   });
 
+  describe("cancelRemoteOperation", () => {
+    it("cancels the transfer of the active tab", async () => {
+      mockedInvoke.mockResolvedValueOnce(true);
+
+      const result = await useGitStore.getState().cancelRemoteOperation();
+
+      expect(result).toBe(true);
+      expect(mockedInvoke).toHaveBeenCalledWith("cancel_remote_operation", {
+        tabId: "default",
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("cancel error"));
+
+      await expect(
+        useGitStore.getState().cancelRemoteOperation(),
+      ).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("cancel error");
+    });
+  });
+
+  describe("listStack", () => {
+    it("returns the stack of the current branch", async () => {
+      const mockStack = {
+        base: "main",
+        branches: [{ name: "feature-1", commit_count: 1, is_head: true }],
+      };
+      mockedInvoke.mockResolvedValueOnce(mockStack);
+
+      const result = await useGitStore.getState().listStack("main");
+
+      expect(result).toEqual(mockStack);
+      expect(mockedInvoke).toHaveBeenCalledWith("list_stack", {
+        tabId: "default",
+        base: "main",
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("stack error"));
+
+      await expect(useGitStore.getState().listStack()).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("stack error");
+    });
+  });
+
+  describe("pushStack", () => {
+    it("returns the pushed branches and the one that failed", async () => {
+      const mockResult = {
+        pushed: [{ remote_name: "origin", branch: "feature-1", updates: [] }],
+        failed: {
+          branch: "feature-2",
+          result: null,
+          message: "connection refused",
+        },
+      };
+      mockedInvoke.mockResolvedValueOnce(mockResult);
+
+      const result = await useGitStore.getState().pushStack("origin");
+
+      expect(result).toEqual(mockResult);
+      expect(mockedInvoke).toHaveBeenCalledWith("push_stack", {
+        tabId: "default",
+        remoteName: "origin",
+        base: undefined,
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("push stack error"));
+
+      await expect(
+        useGitStore.getState().pushStack("origin"),
+      ).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("push stack error");
+    });
+  });
+
   describe("fetchRemotes", () => {
     it("sets remotes on success", async () => {
       const mockRemotes = [
@@ -715,6 +950,7 @@ describe("gitStore", () => {
       expect(mockedInvoke).toHaveBeenCalledWith("stash_save", {
         tabId: "default",
         message: "test message",
+        options: null,
       });
     });
 
@@ -736,6 +972,7 @@ describe("gitStore", () => {
       expect(mockedInvoke).toHaveBeenCalledWith("apply_stash", {
         tabId: "default",
         index: 0,
+        restoreIndex: false,
       });
     });
 
@@ -757,6 +994,7 @@ describe("gitStore", () => {
       expect(mockedInvoke).toHaveBeenCalledWith("pop_stash", {
         tabId: "default",
         index: 0,
+        restoreIndex: false,
       });
     });
 
@@ -790,6 +1028,54 @@ describe("gitStore", () => {
     });
   });
 
+  describe("renameStash", () => {
+    it("calls invoke on success", async () => {
+      mockedInvoke.mockResolvedValueOnce(undefined);
+
+      await useGitStore.getState().renameStash(1, "renamed");
+
+      expect(mockedInvoke).toHaveBeenCalledWith("rename_stash", {
+        tabId: "default",
+        index: 1,
+        message: "renamed",
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("rename error"));
+
+      await expect(
+        useGitStore.getState().renameStash(1, "renamed"),
+      ).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("rename error");
+    });
+  });
+
+  describe("stashBranch", () => {
+    it("calls invoke on success", async () => {
+      mockedInvoke.mockResolvedValueOnce(undefined);
+
+      await useGitStore.getState().stashBranch(0, "from-stash");
+
+      expect(mockedInvoke).toHaveBeenCalledWith("stash_branch", {
+        tabId: "default",
+        index: 0,
+        branchName: "from-stash",
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("branch error"));
+
+      await expect(
+        useGitStore.getState().stashBranch(0, "from-stash"),
+      ).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("branch error");
+    });
+  });
+
   describe("fetchTags", () => {
     it("sets tags on success", async () => {
       const mockTags = [
@@ -801,6 +1087,7 @@ describe("gitStore", () => {
           tagger_name: null,
           tagger_date: null,
           message: null,
+          signature_status: "none",
         },
       ];
       mockedInvoke.mockResolvedValueOnce(mockTags);
@@ -829,6 +1116,23 @@ describe("gitStore", () => {
         tabId: "default",
         name: "v1.0.0",
         message: "Release 1.0",
+        options: null,
+      });
+    });
+
+    it("passes target and signing options", async () => {
+      mockedInvoke.mockResolvedValueOnce(undefined);
+
+      await useGitStore.getState().createTag("v1.0.0", "Release 1.0", {
+        target: "abc1234",
+        sign: true,
+      });
+
+      expect(mockedInvoke).toHaveBeenCalledWith("create_tag", {
+        tabId: "default",
+        name: "v1.0.0",
+        message: "Release 1.0",
+        options: { target: "abc1234", sign: true },
       });
     });
 
@@ -864,6 +1168,83 @@ describe("gitStore", () => {
     });
   });
 
+  describe("pushTag", () => {
+    it("pushes the tag to the remote", async () => {
+      mockedInvoke.mockResolvedValueOnce({
+        remote_name: "origin",
+        branch: "",
+        updates: [],
+      });
+
+      await useGitStore.getState().pushTag("origin", "v1.0.0");
+
+      expect(mockedInvoke).toHaveBeenCalledWith("push_remote", {
+        tabId: "default",
+        remoteName: "origin",
+        options: { tag: "v1.0.0" },
+      });
+    });
+  });
+
+  describe("deleteRemoteTag", () => {
+    it("deletes the tag on the remote", async () => {
+      mockedInvoke.mockResolvedValueOnce({
+        remote_name: "origin",
+        branch: "",
+        updates: [],
+      });
+
+      await useGitStore.getState().deleteRemoteTag("origin", "v1.0.0");
+
+      expect(mockedInvoke).toHaveBeenCalledWith("push_remote", {
+        tabId: "default",
+        remoteName: "origin",
+        options: { delete_tag: "v1.0.0" },
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("remote tag error"));
+
+      await expect(
+        useGitStore.getState().deleteRemoteTag("origin", "v1.0.0"),
+      ).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("remote tag error");
+    });
+  });
+
+  describe("renderReleaseNotes", () => {
+    it("returns the rendered notes", async () => {
+      mockedInvoke.mockResolvedValueOnce("## v1.1.0");
+
+      const notes = await useGitStore.getState().renderReleaseNotes(
+        { from: "v1.0.0", to: "v1.1.0" },
+        "markdown",
+      );
+
+      expect(mockedInvoke).toHaveBeenCalledWith("render_release_notes", {
+        tabId: "default",
+        options: { from: "v1.0.0", to: "v1.1.0" },
+        format: "markdown",
+      });
+      expect(notes).toBe("## v1.1.0");
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("notes error"));
+
+      await expect(
+        useGitStore.getState().renderReleaseNotes(
+          { from: null, to: null },
+          "json",
+        ),
+      ).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("notes error");
+    });
+  });
+
   describe("checkoutTag", () => {
     it("calls invoke on success", async () => {
       mockedInvoke.mockResolvedValueOnce(undefined);
@@ -1101,6 +1482,20 @@ describe("gitStore", () => {
       });
     });
 
+    it("passes rebase options", async () => {
+      mockedInvoke.mockResolvedValueOnce({ completed: true, conflicts: [] });
+
+      await useGitStore
+        .getState()
+        .rebase("main", { rebase_merges: true, exec: "make test" });
+
+      expect(mockedInvoke).toHaveBeenCalledWith("rebase", {
+        tabId: "default",
+        onto: "main",
+        options: { rebase_merges: true, exec: "make test" },
+      });
+    });
+
     it("sets error on failure", async () => {
       mockedInvoke.mockRejectedValueOnce(new Error("rebase error"));
 
@@ -1133,6 +1528,20 @@ describe("gitStore", () => {
         tabId: "default",
         onto: "main",
         todo,
+        autosquash: false,
+      });
+    });
+
+    it("passes autosquash parameter", async () => {
+      mockedInvoke.mockResolvedValueOnce({ completed: true, conflicts: [] });
+
+      await useGitStore.getState().interactiveRebase("main", [], true);
+
+      expect(mockedInvoke).toHaveBeenCalledWith("interactive_rebase", {
+        tabId: "default",
+        onto: "main",
+        todo: [],
+        autosquash: true,
       });
     });
 
@@ -1171,6 +1580,27 @@ describe("gitStore", () => {
     });
   });
 
+  describe("cancelRebase", () => {
+    it("cancels the rebase of the active tab", async () => {
+      mockedInvoke.mockResolvedValueOnce(true);
+
+      const result = await useGitStore.getState().cancelRebase();
+
+      expect(result).toBe(true);
+      expect(mockedInvoke).toHaveBeenCalledWith("cancel_rebase", {
+        tabId: "default",
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("cancel rebase error"));
+
+      await expect(useGitStore.getState().cancelRebase()).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("cancel rebase error");
+    });
+  });
+
   describe("continueRebase", () => {
     it("resets state when completed", async () => {
       useGitStore.setState({ rebasing: true });
@@ -1228,6 +1658,47 @@ describe("gitStore", () => {
         tabId: "default",
         onto: "main",
         limit: 100,
+        autosquash: false,
+      });
+    });
+
+    it("passes autosquash parameter", async () => {
+      mockedInvoke.mockResolvedValueOnce([]);
+
+      await useGitStore.getState().getRebaseTodo("main", true);
+
+      expect(mockedInvoke).toHaveBeenCalledWith("get_rebase_todo", {
+        tabId: "default",
+        onto: "main",
+        limit: 100,
+        autosquash: true,
+      });
+    });
+
+    it("passes rebase options", async () => {
+      const mockTodo = [
+        {
+          action: "label",
+          oid: "",
+          short_oid: "",
+          message: "",
+          author_name: "",
+          argument: "onto",
+        },
+      ];
+      mockedInvoke.mockResolvedValueOnce(mockTodo);
+
+      const result = await useGitStore
+        .getState()
+        .getRebaseTodo("main", false, { rebase_merges: true });
+
+      expect(result).toEqual(mockTodo);
+      expect(mockedInvoke).toHaveBeenCalledWith("get_rebase_todo", {
+        tabId: "default",
+        onto: "main",
+        limit: 100,
+        autosquash: false,
+        options: { rebase_merges: true },
       });
     });
 
@@ -1242,6 +1713,143 @@ describe("gitStore", () => {
     });
   });
 
+  describe("createFixupCommit", () => {
+    it("returns oid on success", async () => {
+      mockedInvoke.mockResolvedValueOnce({ oid: "fix123" });
+
+      const oid = await useGitStore
+        .getState()
+        .createFixupCommit("abc123", "amend", "Better message");
+
+      expect(oid).toBe("fix123");
+      expect(mockedInvoke).toHaveBeenCalledWith("create_fixup_commit", {
+        tabId: "default",
+        target: "abc123",
+        kind: "amend",
+        message: "Better message",
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(
+        new Error("nothing staged for the fixup"),
+      );
+
+      await expect(
+        useGitStore.getState().createFixupCommit("abc123", "fixup"),
+      ).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("nothing staged");
+    });
+  });
+
+  describe("absorb", () => {
+    it("passes options and returns the result", async () => {
+      const mockResult = { fixups: [], skipped: [], rebase: null };
+      mockedInvoke.mockResolvedValueOnce(mockResult);
+
+      const result = await useGitStore
+        .getState()
+        .absorb({ autosquash: true });
+
+      expect(result).toEqual(mockResult);
+      expect(mockedInvoke).toHaveBeenCalledWith("absorb", {
+        tabId: "default",
+        options: { autosquash: true },
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("no upstream"));
+
+      await expect(useGitStore.getState().absorb()).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("no upstream");
+    });
+  });
+
+  describe("splitCommit", () => {
+    it("passes oid and groups and returns the result", async () => {
+      const mockResult = {
+        commits: ["aaa", "bbb"],
+        rebase: { completed: true, conflicts: [] },
+      };
+      mockedInvoke.mockResolvedValueOnce(mockResult);
+      const groups = [{ message: "Add a", files: ["a.txt"] }];
+
+      const result = await useGitStore.getState().splitCommit("abc", groups);
+
+      expect(result).toEqual(mockResult);
+      expect(mockedInvoke).toHaveBeenCalledWith("split_commit", {
+        tabId: "default",
+        oid: "abc",
+        groups,
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("merge commit"));
+
+      await expect(
+        useGitStore.getState().splitCommit("abc", []),
+      ).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("merge commit");
+    });
+  });
+
+  describe("single commit edits", () => {
+    const completed = { completed: true, conflicts: [] };
+
+    it("rewordCommit passes the new message", async () => {
+      mockedInvoke.mockResolvedValueOnce(completed);
+
+      const result = await useGitStore
+        .getState()
+        .rewordCommit("abc", "New message");
+
+      expect(result).toEqual(completed);
+      expect(mockedInvoke).toHaveBeenCalledWith("reword_commit", {
+        tabId: "default",
+        oid: "abc",
+        message: "New message",
+      });
+    });
+
+    it("dropCommit passes the oid", async () => {
+      mockedInvoke.mockResolvedValueOnce(completed);
+
+      await useGitStore.getState().dropCommit("abc");
+
+      expect(mockedInvoke).toHaveBeenCalledWith("drop_commit", {
+        tabId: "default",
+        oid: "abc",
+      });
+    });
+
+    it("moveCommit returns conflicts from a stopped rebase", async () => {
+      const stopped = { completed: false, conflicts: ["a.txt"] };
+      mockedInvoke.mockResolvedValueOnce(stopped);
+
+      const result = await useGitStore.getState().moveCommit("abc", "down");
+
+      expect(result).toEqual(stopped);
+      expect(mockedInvoke).toHaveBeenCalledWith("move_commit", {
+        tabId: "default",
+        oid: "abc",
+        direction: "down",
+      });
+    });
+
+    it("sets error on failure", async () => {
+      mockedInvoke.mockRejectedValueOnce(new Error("root commit"));
+
+      await expect(useGitStore.getState().dropCommit("abc")).rejects.toThrow();
+
+      expect(useGitStore.getState().error).toContain("root commit");
+    });
+  });
+
   describe("cherryPick", () => {
     it("returns result and resets cherryPicking on completion", async () => {
       const mockResult = { completed: true, conflicts: [], oid: "abc123" };
@@ -1,9 +1,17 @@
 import { create } from "zustand";
 import type { AppConfig, AppearanceConfig } from "../services/config";
-import { getConfig, saveConfig as saveConfigService } from "../services/config";
+import {
+  addCoAuthor as addCoAuthorService,
+  getCoAuthors,
+  getConfig,
+  removeCoAuthor as removeCoAuthorService,
+  saveConfig as saveConfigService,
+} from "../services/config";
+import type { CoAuthor } from "../services/history";
 
 interface ConfigState {
   config: AppConfig | null;
+  coAuthors: CoAuthor[];
   loading: boolean;
   error: string | null;
 }
@@ -12,11 +20,23 @@ interface ConfigActions {
   loadConfig: () => Promise<void>;
   saveConfig: (config: AppConfig) => Promise<void>;
   updateAppearance: (appearance: AppearanceConfig) => Promise<void>;
+  loadCoAuthors: () => Promise<void>;
+  addCoAuthor: (coAuthor: CoAuthor) => Promise<void>;
+  removeCoAuthor: (email: string) => Promise<void>;
+}
+
+// 設定画面からの保存で一覧が巻き戻らないよう、読み込み済みの config にも反映する
+function withCoAuthors(coAuthors: CoAuthor[]) {
+  return (state: ConfigState) => ({
+    coAuthors,
+    config: state.config && { ...state.config, co_authors: coAuthors },
+  });
 }
 
 export const useConfigStore = create<ConfigState & ConfigActions>(
   (set, get) => ({
     config: null,
+    coAuthors: [],
     loading: false,
     error: null,
 
@@ -53,5 +73,32 @@ export const useConfigStore = create<ConfigState & ConfigActions>(
         throw e;
       }
     },
+
+    loadCoAuthors: async () => {
+      try {
+        set(withCoAuthors(await getCoAuthors()));
+      } catch (e) {
+        set({ error: String(e) });
+        throw e;
+      }
+    },
+
+    addCoAuthor: async (coAuthor: CoAuthor) => {
+      try {
+        set(withCoAuthors(await addCoAuthorService(coAuthor)));
+      } catch (e) {
+        set({ error: String(e) });
+        throw e;
+      }
+    },
+
+    removeCoAuthor: async (email: string) => {
+      try {
+        set(withCoAuthors(await removeCoAuthorService(email)));
+      } catch (e) {
+        set({ error: String(e) });
+        throw e;
+      }
+    },
   }),
 );
@@ -18,6 +18,10 @@ import {
 } from "../services/conflict";
 import type {
   BranchInfo,
+  BranchStack,
+  CommitLintViolation,
+  DeleteBranchOptions,
+  DeleteBranchResult,
   FetchResult,
   FileDiff,
   HunkIdentifier,
@@ -28,10 +32,13 @@ import type {
   PushResult,
   RemoteInfo,
   RepoStatus,
+  StackPushResult,
 } from "../services/git";
 import {
   addRemote as addRemoteService,
+  cancelRemoteOperation as cancelRemoteOperationService,
   checkoutBranch as checkoutBranchService,
+  checkoutRemoteBranch as checkoutRemoteBranchService,
   commitChanges,
   createBranch as createBranchService,
   deleteBranch as deleteBranchService,
@@ -43,11 +50,14 @@ import {
   getDiff,
   getHeadCommitMessage,
   getStatus,
+  lintCommitMessage as lintCommitMessageService,
   listBranches,
   listRemotes,
+  listStack as listStackService,
   mergeBranch as mergeBranchService,
   pullRemote as pullRemoteService,
   pushRemote as pushRemoteService,
+  pushStack as pushStackService,
   removeRemote as removeRemoteService,
   renameBranch as renameBranchService,
   stageAll as stageAllService,
@@ -59,19 +69,39 @@ import {
   unstageHunk as unstageHunkService,
   unstageLines as unstageLinesService,
 } from "../services/git";
+import type { CommitTrailer } from "../services/history";
+import type { CommitIdentity, IdentityCheck } from "../services/identity";
+import {
+  applyIdentityProfile as applyIdentityProfileService,
+  getIdentityCheck as getIdentityCheckService,
+} from "../services/identity";
 import type {
+  AbsorbOptions,
+  AbsorbResult,
+  FixupKind,
+  MoveDirection,
+  RebaseOptions,
   RebaseResult,
   RebaseState,
   RebaseTodoEntry,
+  SplitCommitGroup,
+  SplitCommitResult,
 } from "../services/rebase";
 import {
   abortRebase as abortRebaseService,
+  absorb as absorbService,
+  cancelRebase as cancelRebaseService,
   continueRebase as continueRebaseService,
+  createFixupCommit as createFixupCommitService,
+  dropCommit as dropCommitService,
   getRebaseState,
   getRebaseTodo as getRebaseTodoService,
   interactiveRebase as interactiveRebaseService,
+  moveCommit as moveCommitService,
   isRebasing as isRebasingService,
   rebase as rebaseService,
+  rewordCommit as rewordCommitService,
+  splitCommit as splitCommitService,
 } from "../services/rebase";
 import type { ResetMode, ResetResult } from "../services/reset";
 import {
@@ -85,12 +115,18 @@ import {
   isReverting as isRevertingService,
   revertCommit as revertCommitService,
 } from "../services/revert";
-import type { StashEntry } from "../services/stash";
+import type {
+  StashApplyResult,
+  StashEntry,
+  StashOptions,
+} from "../services/stash";
 import {
   applyStash as applyStashService,
   dropStash as dropStashService,
   listStashes,
   popStash as popStashService,
+  renameStash as renameStashService,
+  stashBranch as stashBranchService,
   stashSave as stashSaveService,
 } from "../services/stash";
 import type { SubmoduleInfo } from "../services/submodule";
@@ -101,12 +137,20 @@ import {
   updateAllSubmodules as updateAllSubmodulesService,
   updateSubmodule as updateSubmoduleService,
 } from "../services/submodule";
-import type { TagInfo } from "../services/tag";
+import type {
+  CreateTagOptions,
+  ReleaseNotesFormat,
+  ReleaseNotesOptions,
+  TagInfo,
+} from "../services/tag";
 import {
   checkoutTag as checkoutTagService,
   createTag as createTagService,
+  deleteRemoteTag as deleteRemoteTagService,
   deleteTag as deleteTagService,
   listTags,
+  pushTag as pushTagService,
+  renderReleaseNotes as renderReleaseNotesService,
 } from "../services/tag";
 import type { WorktreeInfo } from "../services/worktree";
 import {
@@ -147,10 +191,27 @@ interface GitActions {
   unstageFile: (path: string) => Promise<void>;
   stageAll: () => Promise<void>;
   unstageAll: () => Promise<void>;
-  commit: (message: string, amend: boolean, sign: boolean) => Promise<string>;
+  commit: (
+    message: string,
+    amend: boolean,
+    sign: boolean,
+    skipHooks?: boolean,
+    trailers?: CommitTrailer[],
+    identity?: CommitIdentity,
+  ) => Promise<string>;
+  lintCommitMessage: (
+    message: string,
+    trailers?: CommitTrailer[],
+  ) => Promise<CommitLintViolation[]>;
+  getIdentityCheck: () => Promise<IdentityCheck>;
+  applyIdentityProfile: (label: string) => Promise<IdentityCheck>;
   createBranch: (name: string) => Promise<void>;
   checkoutBranch: (name: string) => Promise<void>;
-  deleteBranch: (name: string) => Promise<void>;
+  checkoutRemoteBranch: (remoteBranch: string) => Promise<string>;
+  deleteBranch: (
+    name: string,
+    options?: DeleteBranchOptions,
+  ) => Promise<DeleteBranchResult>;
   renameBranch: (oldName: string, newName: string) => Promise<void>;
   mergeBranch: (
     branchName: string,
@@ -159,6 +220,9 @@ interface GitActions {
   fetchRemote: (remoteName: string) => Promise<FetchResult>;
   pullRemote: (remoteName: string, option: PullOption) => Promise<MergeResult>;
   pushRemote: (remoteName: string) => Promise<PushResult>;
+  cancelRemoteOperation: () => Promise<boolean>;
+  listStack: (base?: string) => Promise<BranchStack>;
+  pushStack: (remoteName: string, base?: string) => Promise<StackPushResult>;
   fetchRemotes: () => Promise<void>;
   addRemote: (name: string, url: string) => Promise<void>;
   removeRemote: (name: string) => Promise<void>;
@@ -171,13 +235,34 @@ interface GitActions {
   discardLines: (path: string, lineRange: LineRange) => Promise<void>;
   getHeadCommitMessage: () => Promise<string>;
   fetchStashes: () => Promise<void>;
-  stashSave: (message: string | null) => Promise<void>;
-  applyStash: (index: number) => Promise<void>;
-  popStash: (index: number) => Promise<void>;
+  stashSave: (
+    message: string | null,
+    options?: Partial<StashOptions> | null,
+  ) => Promise<void>;
+  applyStash: (
+    index: number,
+    restoreIndex?: boolean,
+  ) => Promise<StashApplyResult>;
+  popStash: (
+    index: number,
+    restoreIndex?: boolean,
+  ) => Promise<StashApplyResult>;
   dropStash: (index: number) => Promise<void>;
+  stashBranch: (index: number, branchName: string) => Promise<void>;
+  renameStash: (index: number, message: string) => Promise<void>;
   fetchTags: () => Promise<void>;
-  createTag: (name: string, message: string | null) => Promise<void>;
+  createTag: (
+    name: string,
+    message: string | null,
+    options?: CreateTagOptions | null,
+  ) => Promise<void>;
   deleteTag: (name: string) => Promise<void>;
+  pushTag: (remoteName: string, name: string) => Promise<PushResult>;
+  deleteRemoteTag: (remoteName: string, name: string) => Promise<PushResult>;
+  renderReleaseNotes: (
+    options: ReleaseNotesOptions,
+    format: ReleaseNotesFormat,
+  ) => Promise<string>;
   checkoutTag: (name: string) => Promise<void>;
   fetchMergeState: () => Promise<void>;
   fetchConflictFiles: () => Promise<void>;
@@ -194,14 +279,33 @@ interface GitActions {
   abortMerge: () => Promise<void>;
   continueMerge: (message: string) => Promise<string>;
   fetchRebaseState: () => Promise<void>;
-  rebase: (onto: string) => Promise<RebaseResult>;
+  rebase: (onto: string, options?: RebaseOptions) => Promise<RebaseResult>;
   interactiveRebase: (
     onto: string,
     todo: RebaseTodoEntry[],
+    autosquash?: boolean,
   ) => Promise<RebaseResult>;
   abortRebase: () => Promise<void>;
   continueRebase: () => Promise<RebaseResult>;
-  getRebaseTodo: (onto: string) => Promise<RebaseTodoEntry[]>;
+  cancelRebase: () => Promise<boolean>;
+  getRebaseTodo: (
+    onto: string,
+    autosquash?: boolean,
+    options?: RebaseOptions,
+  ) => Promise<RebaseTodoEntry[]>;
+  createFixupCommit: (
+    target: string,
+    kind: FixupKind,
+    message?: string,
+  ) => Promise<string>;
+  absorb: (options?: AbsorbOptions) => Promise<AbsorbResult>;
+  splitCommit: (
+    oid: string,
+    groups: SplitCommitGroup[],
+  ) => Promise<SplitCommitResult>;
+  rewordCommit: (oid: string, message: string) => Promise<RebaseResult>;
+  dropCommit: (oid: string) => Promise<RebaseResult>;
+  moveCommit: (oid: string, direction: MoveDirection) => Promise<RebaseResult>;
   cherryPick: (
     oids: string[],
     mode: CherryPickMode,
@@ -322,13 +426,23 @@ export const useGitStore = create<GitState & GitActions>((set) => ({
     }
   },
 
-  commit: async (message: string, amend: boolean, sign: boolean) => {
+  commit: async (
+    message: string,
+    amend: boolean,
+    sign: boolean,
+    skipHooks = false,
+    trailers: CommitTrailer[] = [],
+    identity: CommitIdentity = {},
+  ) => {
     try {
       const result = await commitChanges(
         getActiveTabId(),
         message,
         amend,
         sign,
+        skipHooks,
+        trailers,
+        identity,
       );
       return result.oid;
     } catch (e) {
@@ -337,6 +451,40 @@ export const useGitStore = create<GitState & GitActions>((set) => ({
     }
   },
 
+  lintCommitMessage: async (
+    message: string,
+    trailers: CommitTrailer[] = [],
+  ) => {
+    try {
+      return await lintCommitMessageService(
+        getActiveTabId(),
+        message,
+        trailers,
+      );
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  getIdentityCheck: async () => {
+    try {
+      return await getIdentityCheckService(getActiveTabId());
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  applyIdentityProfile: async (label: string) => {
+    try {
+      return await applyIdentityProfileService(getActiveTabId(), label);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
   createBranch: async (name: string) => {
     try {
       await createBranchService(getActiveTabId(), name);
@@ -355,9 +503,18 @@ export const useGitStore = create<GitState & GitActions>((set) => ({
     }
   },
 
-  deleteBranch: async (name: string) => {
+  checkoutRemoteBranch: async (remoteBranch: string) => {
+    try {
+      return await checkoutRemoteBranchService(getActiveTabId(), remoteBranch);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  deleteBranch: async (name: string, options?: DeleteBranchOptions) => {
     try {
-      await deleteBranchService(getActiveTabId(), name);
+      return await deleteBranchService(getActiveTabId(), name, options);
     } catch (e) {
       set({ error: String(e) });
       throw e;
@@ -409,6 +566,33 @@ export const useGitStore = create<GitState & GitActions>((set) => ({
     }
   },
 
+  cancelRemoteOperation: async () => {
+    try {
+      return await cancelRemoteOperationService(getActiveTabId());
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  listStack: async (base?: string) => {
+    try {
+      return await listStackService(getActiveTabId(), base);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  pushStack: async (remoteName: string, base?: string) => {
+    try {
+      return await pushStackService(getActiveTabId(), remoteName, base);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
   fetchRemotes: async () => {
     try {
       const remotes = await listRemotes(getActiveTabId());
@@ -519,27 +703,30 @@ export const useGitStore = create<GitState & GitActions>((set) => ({
     }
   },
 
-  stashSave: async (message: string | null) => {
+  stashSave: async (
+    message: string | null,
+    options: Partial<StashOptions> | null = null,
+  ) => {
     try {
-      await stashSaveService(getActiveTabId(), message);
+      await stashSaveService(getActiveTabId(), message, options);
     } catch (e) {
       set({ error: String(e) });
       throw e;
     }
   },
 
-  applyStash: async (index: number) => {
+  applyStash: async (index: number, restoreIndex = false) => {
     try {
-      await applyStashService(getActiveTabId(), index);
+      return await applyStashService(getActiveTabId(), index, restoreIndex);
     } catch (e) {
       set({ error: String(e) });
       throw e;
     }
   },
 
-  popStash: async (index: number) => {
+  popStash: async (index: number, restoreIndex = false) => {
     try {
-      await popStashService(getActiveTabId(), index);
+      return await popStashService(getActiveTabId(), index, restoreIndex);
     } catch (e) {
       set({ error: String(e) });
       throw e;
@@ -555,6 +742,24 @@ export const useGitStore = create<GitState & GitActions>((set) => ({
     }
   },
 
+  renameStash: async (index: number, message: string) => {
+    try {
+      await renameStashService(getActiveTabId(), index, message);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  stashBranch: async (index: number, branchName: string) => {
+    try {
+      await stashBranchService(getActiveTabId(), index, branchName);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
   fetchTags: async () => {
     try {
       const tags = await listTags(getActiveTabId());
@@ -565,9 +770,13 @@ export const useGitStore = create<GitState & GitActions>((set) => ({
     }
   },
 
-  createTag: async (name: string, message: string | null) => {
+  createTag: async (
+    name: string,
+    message: string | null,
+    options: CreateTagOptions | null = null,
+  ) => {
     try {
-      await createTagService(getActiveTabId(), name, message);
+      await createTagService(getActiveTabId(), name, message, options);
     } catch (e) {
       set({ error: String(e) });
       throw e;
@@ -583,6 +792,36 @@ export const useGitStore = create<GitState & GitActions>((set) => ({
     }
   },
 
+  pushTag: async (remoteName: string, name: string) => {
+    try {
+      return await pushTagService(getActiveTabId(), remoteName, name);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  deleteRemoteTag: async (remoteName: string, name: string) => {
+    try {
+      return await deleteRemoteTagService(getActiveTabId(), remoteName, name);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  renderReleaseNotes: async (
+    options: ReleaseNotesOptions,
+    format: ReleaseNotesFormat,
+  ) => {
+    try {
+      return await renderReleaseNotesService(getActiveTabId(), options, format);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
   checkoutTag: async (name: string) => {
     try {
       await checkoutTagService(getActiveTabId(), name);
@@ -680,18 +919,27 @@ export const useGitStore = create<GitState & GitActions>((set) => ({
     }
   },
 
-  rebase: async (onto: string) => {
+  rebase: async (onto: string, options?: RebaseOptions) => {
     try {
-      return await rebaseService(getActiveTabId(), onto);
+      return await rebaseService(getActiveTabId(), onto, options);
     } catch (e) {
       set({ error: String(e) });
       throw e;
     }
   },
 
-  interactiveRebase: async (onto: string, todo: RebaseTodoEntry[]) => {
+  interactiveRebase: async (
+    onto: string,
+    todo: RebaseTodoEntry[],
+    autosquash = false,
+  ) => {
     try {
-      return await interactiveRebaseService(getActiveTabId(), onto, todo);
+      return await interactiveRebaseService(
+        getActiveTabId(),
+        onto,
+        todo,
+        autosquash,
+      );
     } catch (e) {
       set({ error: String(e) });
       throw e;
@@ -721,13 +969,88 @@ export const useGitStore = create<GitState & GitActions>((set) => ({
     }
   },
 
-  getRebaseTodo: async (onto: string) => {
+  cancelRebase: async () => {
+    try {
+      return await cancelRebaseService(getActiveTabId());
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  getRebaseTodo: async (
+    onto: string,
+    autosquash = false,
+    options?: RebaseOptions,
+  ) => {
     try {
       return await getRebaseTodoService(
         getActiveTabId(),
         onto,
         REBASE_TODO_DEFAULT_LIMIT,
+        autosquash,
+        options,
+      );
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  createFixupCommit: async (target: string, kind: FixupKind, message = "") => {
+    try {
+      const result = await createFixupCommitService(
+        getActiveTabId(),
+        target,
+        kind,
+        message,
       );
+      return result.oid;
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  absorb: async (options: AbsorbOptions = {}) => {
+    try {
+      return await absorbService(getActiveTabId(), options);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  splitCommit: async (oid: string, groups: SplitCommitGroup[]) => {
+    try {
+      return await splitCommitService(getActiveTabId(), oid, groups);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  rewordCommit: async (oid: string, message: string) => {
+    try {
+      return await rewordCommitService(getActiveTabId(), oid, message);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  dropCommit: async (oid: string) => {
+    try {
+      return await dropCommitService(getActiveTabId(), oid);
+    } catch (e) {
+      set({ error: String(e) });
+      throw e;
+    }
+  },
+
+  moveCommit: async (oid: string, direction: MoveDirection) => {
+    try {
+      return await moveCommitService(getActiveTabId(), oid, direction);
     } catch (e) {
       set({ error: String(e) });
       throw e;
@@ -5,10 +5,12 @@ import type {
   CommitDetail,
   CommitGraphRow,
   CommitInfo,
+  FileHistoryEntry,
   LogFilter,
 } from "../services/history";
 import {
   getBlame,
+  getBlameBefore,
   getCommitDetail,
   getCommitFileDiff,
   getCommitLog,
@@ -27,7 +29,7 @@ interface HistoryState {
   error: string | null;
   blameResult: BlameResult | null;
   blameLoading: boolean;
-  fileHistoryCommits: CommitInfo[];
+  fileHistoryCommits: FileHistoryEntry[];
   fileHistorySelectedOid: string | null;
   fileHistoryDetail: CommitDetail | null;
   fileHistoryLoading: boolean;
@@ -43,6 +45,7 @@ interface HistoryActions {
   fetchFileDiff: (oid: string, path: string) => Promise<void>;
   collapseFileDiff: (path: string) => void;
   fetchBlame: (path: string, commitOid: string | null) => Promise<void>;
+  fetchBlameBefore: (commitOid: string, path: string) => Promise<void>;
   fetchFileHistory: (
     path: string,
     limit: number,
@@ -155,10 +158,27 @@ export const useHistoryStore = create<HistoryState & HistoryActions>((set) => ({
     }
   },
 
+  fetchBlameBefore: async (commitOid: string, path: string) => {
+    set({ blameLoading: true });
+    try {
+      const result = await getBlameBefore(getActiveTabId(), commitOid, path);
+      set({ blameResult: result, blameLoading: false });
+    } catch (e) {
+      set({ error: String(e), blameLoading: false });
+      throw e;
+    }
+  },
+
   fetchFileHistory: async (path: string, limit: number, skip: number) => {
     set({ fileHistoryLoading: true });
     try {
-      const commits = await getFileHistory(getActiveTabId(), path, limit, skip);
+      const commits = await getFileHistory(
+        getActiveTabId(),
+        path,
+        limit,
+        skip,
+        true,
+      );
       set({ fileHistoryCommits: commits, fileHistoryLoading: false });
     } catch (e) {
       set({ error: String(e), fileHistoryLoading: false });
@@ -0,0 +1,27 @@
+import { describe, expect, it } from "vitest";
+import { coAuthorTrailer, parseCoAuthor } from "../coAuthor";
+
+describe("parseCoAuthor", () => {
+  it("reads name and email", () => {
+    expect(parseCoAuthor(" Ann Lee <ann@example.com> ")).toEqual({
+      name: "Ann Lee",
+      email: "ann@example.com",
+    });
+  });
+
+  it("returns null without an email in angle brackets", () => {
+    expect(parseCoAuthor("Ann Lee")).toBeNull();
+    expect(parseCoAuthor("Ann <not an email>")).toBeNull();
+  });
+});
+
+describe("coAuthorTrailer", () => {
+  it("formats a Co-authored-by trailer", () => {
+    expect(
+      coAuthorTrailer({ name: "Ann Lee", email: "ann@example.com" }),
+    ).toEqual({
+      key: "Co-authored-by",
+      value: "Ann Lee <ann@example.com>",
+    });
+  });
+});
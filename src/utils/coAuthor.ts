@@ -0,0 +1,16 @@
+import type { CoAuthor, CommitTrailer } from "../services/history";
+
+const IDENTITY = /^(.*?)\s*<([^<>\s]+@[^<>\s]+)>$/;
+
+export function parseCoAuthor(text: string): CoAuthor | null {
+  const match = IDENTITY.exec(text.trim());
+  if (!match) return null;
+  return { name: match[1], email: match[2] };
+}
+
+export function coAuthorTrailer(coAuthor: CoAuthor): CommitTrailer {
+  return {
+    key: "Co-authored-by",
+    value: `${coAuthor.name} <${coAuthor.email}>`,
+  };
+}